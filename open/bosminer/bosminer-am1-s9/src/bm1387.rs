@@ -49,6 +49,9 @@ pub const CHIP_OSC_CLK_BASE_BAUD_DIV: usize = 8;
 /// How many cores are on the chip
 pub const NUM_CORES_ON_CHIP: usize = 114;
 
+/// Difference between hardware addresses of two neighbouring chips
+pub const CHIP_ADDRESS_STEP: usize = 4;
+
 /// `MidstateCount` represents the number of midstates S9 FPGA sends to chips.
 /// This information needs to be accessible to everyone that processes `work_id`.
 ///
@@ -128,7 +131,7 @@ impl ChipAddress {
     fn to_hw_addr(&self) -> u8 {
         match self {
             ChipAddress::All => 0,
-            ChipAddress::One(x) => ((*x) * 4)
+            ChipAddress::One(x) => ((*x) * CHIP_ADDRESS_STEP)
                 .try_into()
                 .expect("chip address doesn't fit into a byte"),
         }
@@ -642,7 +645,7 @@ pub struct PllFrequency {
 
 impl PllFrequency {
     /// Minimum and maximum supported frequency
-    const MIN_FREQ_HZ: usize = 100_000_000;
+    pub const MIN_FREQ_HZ: usize = 100_000_000;
     const MAX_FREQ_HZ: usize = 1_200_000_000;
     const BIN_SIZE_HZ: usize = 1_000_000;
 
@@ -0,0 +1,560 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Detection of hash chain brown-outs. A marginal PSU may cause a chain to lose some of its chips
+//! while mining which otherwise shows up only as lower hash rate. The chain is periodically checked
+//! for number of responding chips and when it drops significantly below the number of chips found
+//! by enumeration, the chain is restarted to re-enumerate them. Repeated brown-outs latch the chain
+//! into reduced frequency.
+
+use ii_logging::macros::*;
+
+use async_trait::async_trait;
+
+use crate::error;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How often the number of responding chips is checked
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Parameters of brown-out detection and recovery
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// Minimal number of chips missing (compared to enumeration) which is considered brown-out
+    pub min_missing_chips: usize,
+    /// Number of restarts attempted to recover from one brown-out
+    pub max_recovery_attempts: usize,
+    /// Time window in which brown-outs are counted
+    pub window: Duration,
+    /// Number of brown-outs within `window` which latches the chain into reduced frequency
+    pub latch_count: usize,
+    /// Reduced frequency in percent of the configured one
+    pub reduced_frequency_percent: usize,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            // one voltage domain
+            min_missing_chips: crate::CHIPS_IN_VOLTAGE_DOMAIN,
+            max_recovery_attempts: 2,
+            window: Duration::from_secs(3600),
+            latch_count: 3,
+            reduced_frequency_percent: 85,
+        }
+    }
+}
+
+/// State of the chain as seen by the brown-out detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Chain mines with (nearly) all enumerated chips
+    Healthy,
+    /// Chips are missing and recovery attempt `attempt` (starting from 1) is in progress
+    Recovering { attempt: usize },
+    /// All recovery attempts failed, the chain is left as it is
+    Degraded,
+}
+
+/// What has to be done with the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    None,
+    /// Restart the chain to re-enumerate its chips, optionally at reduced frequency
+    Recover {
+        reduced_frequency: bool,
+    },
+}
+
+/// Brown-out report raised when the chain loses its chips
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    /// Number of chips found by enumeration
+    pub before: usize,
+    /// Number of chips responding now
+    pub after: usize,
+    /// Number of brown-outs within the window (including this one)
+    pub brownouts: usize,
+    /// Chain has been latched into reduced frequency
+    pub latched: bool,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} out of {} chips responding ({} brown-out(s) in window{})",
+            self.after,
+            self.before,
+            self.brownouts,
+            if self.latched {
+                ", latched to reduced frequency"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+/// Brown-out detection and recovery policy driven by observed chip counts. It does not touch the
+/// hardware by itself so that it can be tested without it.
+#[derive(Debug)]
+pub struct Detector {
+    policy: Policy,
+    /// Number of chips found by the initial enumeration
+    baseline: usize,
+    state: State,
+    /// Times of brown-outs within the window
+    brownouts: VecDeque<Instant>,
+    latched: bool,
+}
+
+impl Detector {
+    pub fn new(policy: Policy, baseline: usize) -> Self {
+        Self {
+            policy,
+            baseline,
+            state: State::Healthy,
+            brownouts: VecDeque::new(),
+            latched: false,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn baseline(&self) -> usize {
+        self.baseline
+    }
+
+    /// Chain has to run at reduced frequency
+    pub fn is_latched(&self) -> bool {
+        self.latched
+    }
+
+    fn is_brownout(&self, chip_count: usize) -> bool {
+        chip_count + self.policy.min_missing_chips <= self.baseline
+    }
+
+    /// Account number of chips responding on a running chain. An alert is returned when
+    /// a brown-out is detected.
+    pub fn observe(&mut self, now: Instant, chip_count: usize) -> (Action, Option<Alert>) {
+        match self.state {
+            State::Healthy if self.is_brownout(chip_count) => {
+                while let Some(&oldest) = self.brownouts.front() {
+                    if now.duration_since(oldest) < self.policy.window {
+                        break;
+                    }
+                    self.brownouts.pop_front();
+                }
+                self.brownouts.push_back(now);
+                if self.brownouts.len() >= self.policy.latch_count {
+                    self.latched = true;
+                }
+                self.state = State::Recovering { attempt: 1 };
+
+                let alert = Alert {
+                    before: self.baseline,
+                    after: chip_count,
+                    brownouts: self.brownouts.len(),
+                    latched: self.latched,
+                };
+                let action = Action::Recover {
+                    reduced_frequency: self.latched,
+                };
+                (action, Some(alert))
+            }
+            // Chips have come back without any intervention
+            State::Degraded if !self.is_brownout(chip_count) => {
+                self.state = State::Healthy;
+                (Action::None, None)
+            }
+            // Observations are not expected during recovery and there is nothing more to do with
+            // degraded chain
+            _ => (Action::None, None),
+        }
+    }
+
+    /// Account result of a recovery attempt: number of enumerated chips or `None` when the chain
+    /// failed to start. Further attempts are made at reduced frequency.
+    pub fn recovered(&mut self, chip_count: Option<usize>) -> Action {
+        let attempt = match self.state {
+            State::Recovering { attempt } => attempt,
+            _ => panic!("BUG: no recovery in progress"),
+        };
+        match chip_count {
+            Some(chip_count) if !self.is_brownout(chip_count) => {
+                self.state = State::Healthy;
+                Action::None
+            }
+            _ if attempt < self.policy.max_recovery_attempts => {
+                self.state = State::Recovering {
+                    attempt: attempt + 1,
+                };
+                Action::Recover {
+                    reduced_frequency: true,
+                }
+            }
+            _ => {
+                self.state = State::Degraded;
+                Action::None
+            }
+        }
+    }
+}
+
+/// Hardware interactions required by `Supervisor`
+#[async_trait]
+pub trait Chain: Send + Sync {
+    /// Number of chips found by enumeration or `None` when the chain is not running
+    async fn enumerated_chips(&self) -> Option<usize>;
+
+    /// Number of chips responding to a broadcast command or `None` when the chain is not running
+    async fn responding_chips(&self) -> error::Result<Option<usize>>;
+
+    /// Restart the chain and return the number of enumerated chips. The frequency is set to
+    /// `frequency_percent` of the configured one when specified.
+    async fn restart(&self, frequency_percent: Option<usize>) -> error::Result<usize>;
+}
+
+/// Periodically checks one chain and runs recovery when brown-out is detected
+pub struct Supervisor<T> {
+    chain: T,
+    policy: Policy,
+    /// Detector is created on the first check of a running chain
+    detector: Option<Detector>,
+    /// Alerts raised so far
+    alerts: Vec<Alert>,
+}
+
+impl<T: Chain> Supervisor<T> {
+    pub fn new(chain: T, policy: Policy) -> Self {
+        Self {
+            chain,
+            policy,
+            detector: None,
+            alerts: Vec::new(),
+        }
+    }
+
+    pub fn detector(&self) -> Option<&Detector> {
+        self.detector.as_ref()
+    }
+
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Read number of responding chips. A failed command is re-verified once before the chain is
+    /// considered to have no responding chips.
+    async fn responding_chips(&self) -> Option<usize> {
+        match self.chain.responding_chips().await {
+            Ok(chip_count) => chip_count,
+            Err(e) => {
+                warn!("Brown-out check: cannot count chips, re-verifying: {}", e);
+                match self.chain.responding_chips().await {
+                    Ok(chip_count) => chip_count,
+                    Err(e) => {
+                        error!("Brown-out check: cannot count chips: {}", e);
+                        Some(0)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check the chain once and run bounded recovery when chips are missing
+    pub async fn check(&mut self, now: Instant) {
+        let baseline = match self.detector.as_ref() {
+            Some(detector) => detector.baseline(),
+            None => match self.chain.enumerated_chips().await {
+                Some(chip_count) => chip_count,
+                None => return,
+            },
+        };
+        let chip_count = match self.responding_chips().await {
+            Some(chip_count) => chip_count,
+            None => return,
+        };
+        let policy = self.policy.clone();
+        let detector = self
+            .detector
+            .get_or_insert_with(|| Detector::new(policy, baseline));
+
+        let (mut action, alert) = detector.observe(now, chip_count);
+        if let Some(alert) = alert {
+            error!("ALERT: chain brown-out detected: {}", alert);
+            self.alerts.push(alert);
+        }
+        let recovering = action != Action::None;
+        while let Action::Recover { reduced_frequency } = action {
+            let frequency_percent = if reduced_frequency {
+                Some(self.policy.reduced_frequency_percent)
+            } else {
+                None
+            };
+            let chip_count = match self.chain.restart(frequency_percent).await {
+                Ok(chip_count) => {
+                    info!("Brown-out recovery: {} chips enumerated", chip_count);
+                    Some(chip_count)
+                }
+                Err(e) => {
+                    error!("Brown-out recovery: chain restart failed: {}", e);
+                    None
+                }
+            };
+            action = self
+                .detector
+                .as_mut()
+                .expect("BUG: missing detector")
+                .recovered(chip_count);
+        }
+        if recovering && self.detector.as_ref().map(Detector::state) == Some(State::Degraded) {
+            error!("ALERT: chain brown-out recovery failed, leaving the chain degraded");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::error::ErrorKind;
+
+    use futures::lock::Mutex;
+    use ii_async_compat::{futures, tokio};
+
+    fn policy() -> Policy {
+        Policy {
+            min_missing_chips: 3,
+            max_recovery_attempts: 2,
+            window: Duration::from_secs(100),
+            latch_count: 2,
+            reduced_frequency_percent: 80,
+        }
+    }
+
+    #[test]
+    fn test_detector_small_drop_ignored() {
+        let now = Instant::now();
+        let mut detector = Detector::new(policy(), 63);
+
+        assert_eq!(detector.observe(now, 63), (Action::None, None));
+        assert_eq!(detector.observe(now, 61), (Action::None, None));
+        assert_eq!(detector.state(), State::Healthy);
+    }
+
+    #[test]
+    fn test_detector_recovery() {
+        let now = Instant::now();
+        let mut detector = Detector::new(policy(), 63);
+
+        let (action, alert) = detector.observe(now, 32);
+        assert_eq!(
+            action,
+            Action::Recover {
+                reduced_frequency: false
+            }
+        );
+        assert_eq!(
+            alert,
+            Some(Alert {
+                before: 63,
+                after: 32,
+                brownouts: 1,
+                latched: false
+            })
+        );
+        assert_eq!(detector.state(), State::Recovering { attempt: 1 });
+
+        // second attempt is made at reduced frequency
+        assert_eq!(
+            detector.recovered(Some(32)),
+            Action::Recover {
+                reduced_frequency: true
+            }
+        );
+        assert_eq!(detector.recovered(Some(63)), Action::None);
+        assert_eq!(detector.state(), State::Healthy);
+        assert!(!detector.is_latched());
+    }
+
+    #[test]
+    fn test_detector_recovery_exhausted() {
+        let now = Instant::now();
+        let mut detector = Detector::new(policy(), 63);
+
+        detector.observe(now, 10);
+        detector.recovered(None);
+        assert_eq!(detector.recovered(Some(10)), Action::None);
+        assert_eq!(detector.state(), State::Degraded);
+
+        // degraded chain is left alone until the chips come back
+        assert_eq!(detector.observe(now, 10), (Action::None, None));
+        assert_eq!(detector.observe(now, 63), (Action::None, None));
+        assert_eq!(detector.state(), State::Healthy);
+    }
+
+    #[test]
+    fn test_detector_latch() {
+        let now = Instant::now();
+        let mut detector = Detector::new(policy(), 63);
+
+        detector.observe(now, 30);
+        detector.recovered(Some(63));
+
+        // brown-out outside of the window does not latch
+        let now = now + Duration::from_secs(150);
+        let (_, alert) = detector.observe(now, 30);
+        assert_eq!(alert.map(|alert| alert.brownouts), Some(1));
+        detector.recovered(Some(63));
+        assert!(!detector.is_latched());
+
+        let now = now + Duration::from_secs(50);
+        let (action, alert) = detector.observe(now, 30);
+        assert_eq!(
+            action,
+            Action::Recover {
+                reduced_frequency: true
+            }
+        );
+        assert_eq!(alert.map(|alert| alert.latched), Some(true));
+        detector.recovered(Some(63));
+        assert!(detector.is_latched());
+    }
+
+    /// Chain mock replaying injected chip counts and recording restarts
+    struct MockChain {
+        enumerated: Option<usize>,
+        responding: Mutex<VecDeque<error::Result<Option<usize>>>>,
+        restarts: Mutex<VecDeque<error::Result<usize>>>,
+        restarted_with: Mutex<Vec<Option<usize>>>,
+    }
+
+    impl MockChain {
+        fn new(
+            enumerated: Option<usize>,
+            responding: Vec<error::Result<Option<usize>>>,
+            restarts: Vec<error::Result<usize>>,
+        ) -> Self {
+            Self {
+                enumerated,
+                responding: Mutex::new(responding.into()),
+                restarts: Mutex::new(restarts.into()),
+                restarted_with: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Chain for MockChain {
+        async fn enumerated_chips(&self) -> Option<usize> {
+            self.enumerated
+        }
+
+        async fn responding_chips(&self) -> error::Result<Option<usize>> {
+            self.responding
+                .lock()
+                .await
+                .pop_front()
+                .expect("BUG: unexpected chip count request")
+        }
+
+        async fn restart(&self, frequency_percent: Option<usize>) -> error::Result<usize> {
+            self.restarted_with.lock().await.push(frequency_percent);
+            self.restarts
+                .lock()
+                .await
+                .pop_front()
+                .expect("BUG: unexpected restart")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_recovery() {
+        let chain = MockChain::new(
+            Some(63),
+            vec![Ok(Some(63)), Ok(Some(31))],
+            vec![
+                Err(ErrorKind::ChipEnumeration("no chips".into()).into()),
+                Ok(62),
+            ],
+        );
+        let mut supervisor = Supervisor::new(chain, policy());
+        let now = Instant::now();
+
+        supervisor.check(now).await;
+        assert!(supervisor.alerts().is_empty());
+
+        supervisor.check(now).await;
+        assert_eq!(supervisor.alerts().len(), 1);
+        assert_eq!(supervisor.alerts()[0].after, 31);
+        assert_eq!(
+            *supervisor.chain.restarted_with.lock().await,
+            vec![None, Some(80)]
+        );
+        assert_eq!(
+            supervisor.detector().map(|detector| detector.state()),
+            Some(State::Healthy)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_command_error() {
+        // the first failure is re-verified
+        let chain = MockChain::new(
+            Some(63),
+            vec![
+                Err(ErrorKind::Hashchip("timeout".into()).into()),
+                Ok(Some(63)),
+                Err(ErrorKind::Hashchip("timeout".into()).into()),
+                Err(ErrorKind::Hashchip("timeout".into()).into()),
+            ],
+            vec![Ok(0), Ok(0)],
+        );
+        let mut supervisor = Supervisor::new(chain, policy());
+        let now = Instant::now();
+
+        supervisor.check(now).await;
+        assert!(supervisor.alerts().is_empty());
+
+        supervisor.check(now).await;
+        assert_eq!(supervisor.alerts()[0].after, 0);
+        assert_eq!(
+            supervisor.detector().map(|detector| detector.state()),
+            Some(State::Degraded)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_stopped_chain() {
+        let chain = MockChain::new(None, vec![], vec![]);
+        let mut supervisor = Supervisor::new(chain, policy());
+
+        supervisor.check(Instant::now()).await;
+        assert!(supervisor.detector().is_none());
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Front panel "IP Report" button of the S9 control board.
+//!
+//! Short press broadcasts IP and MAC address of the miner over UDP in the same format as the
+//! stock firmware so the miner can be found by the vendor discovery tool. Holding the button for
+//! `Config::long_press` restores the factory configuration and restarts the miner. When the
+//! factory configuration cannot be restored, the next start is forced to safe mode instead (see
+//! `safe_mode`).
+
+use ii_logging::macros::*;
+
+use crate::gpio;
+use crate::hooks;
+use crate::safe_mode;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use embedded_hal::digital::v2::InputPin;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Location of the configuration shipped with the firmware (read-only root file system)
+pub const DEFAULT_FACTORY_CONFIG_PATH: &'static str = "/rom/etc/bosminer.toml";
+/// Network interface whose MAC address is reported
+pub const DEFAULT_INTERFACE: &'static str = "eth0";
+/// UDP port the vendor discovery tool listens on
+pub const REPORT_PORT: u16 = 14235;
+
+/// Interval of sampling the button pin
+pub const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Level of the pin has to be stable at least for this time to be accepted
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(60);
+/// Holding the button at least for this time restores the factory configuration
+pub const DEFAULT_LONG_PRESS: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Listen to the button at all
+    pub enabled: bool,
+    /// Sysfs path of already exported button pin (`None` uses the IP Report button of S9)
+    pub gpio_path: Option<PathBuf>,
+    pub factory_config_path: PathBuf,
+    pub interface: String,
+    pub debounce: Duration,
+    pub long_press: Duration,
+}
+
+/// Classified press of the button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    /// Button has been released before `long_press`
+    Short,
+    /// Button has been held for `long_press` (reported without waiting for release)
+    Long,
+}
+
+impl fmt::Display for Press {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Press::Short => write!(f, "short"),
+            Press::Long => write!(f, "long"),
+        }
+    }
+}
+
+/// Debouncing and classification of presses from samples of the button level
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    debounce: Duration,
+    long_press: Duration,
+    /// Debounced level of the button
+    pressed: bool,
+    /// Time when the raw level started to differ from the debounced one
+    change_start: Option<Instant>,
+    /// Time when the current press has started
+    press_start: Option<Instant>,
+    /// The current press has been already reported as long
+    long_reported: bool,
+}
+
+impl Classifier {
+    pub fn new(debounce: Duration, long_press: Duration) -> Self {
+        Self {
+            debounce,
+            long_press,
+            pressed: false,
+            change_start: None,
+            press_start: None,
+            long_reported: false,
+        }
+    }
+
+    /// Process a sample of the button level taken at `now` and return the press which has just
+    /// been recognized
+    pub fn update(&mut self, pressed: bool, now: Instant) -> Option<Press> {
+        if pressed == self.pressed {
+            // glitch shorter than debounce time
+            self.change_start = None;
+        } else {
+            let change_start = *self.change_start.get_or_insert(now);
+            if now.duration_since(change_start) >= self.debounce {
+                self.pressed = pressed;
+                self.change_start = None;
+                if pressed {
+                    self.press_start = Some(change_start);
+                    self.long_reported = false;
+                } else if self.press_start.take().is_some() && !self.long_reported {
+                    return Some(Press::Short);
+                }
+            }
+        }
+
+        match self.press_start {
+            Some(press_start)
+                if !self.long_reported && now.duration_since(press_start) >= self.long_press =>
+            {
+                self.long_reported = true;
+                Some(Press::Long)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse MAC address in the format used by sysfs (`/sys/class/net/<iface>/address`)
+pub fn parse_mac(value: &str) -> Result<[u8; 6], String> {
+    let value = value.trim();
+    let mut mac = [0u8; 6];
+    let mut octets = value.split(':');
+    for octet in mac.iter_mut() {
+        *octet = octets
+            .next()
+            .and_then(|octet| u8::from_str_radix(octet, 16).ok())
+            .ok_or_else(|| format!("invalid MAC address '{}'", value))?;
+    }
+    if octets.next().is_some() {
+        Err(format!("invalid MAC address '{}'", value))?;
+    }
+    Ok(mac)
+}
+
+/// Assemble payload of the IP report in the format of the stock firmware (`IP,MAC` with upper
+/// case MAC address)
+pub fn report_packet(ip: Ipv4Addr, mac: &[u8; 6]) -> Vec<u8> {
+    let mac = mac
+        .iter()
+        .map(|octet| format!("{:02X}", octet))
+        .collect::<Vec<_>>()
+        .join(":");
+    format!("{},{}", ip, mac).into_bytes()
+}
+
+/// Broadcast IP and MAC address of the miner and return the sent payload
+pub fn send_ip_report(interface: &str) -> io::Result<String> {
+    let address = fs::read_to_string(format!("/sys/class/net/{}/address", interface))?;
+    let mac = parse_mac(&address).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    // The local address of the socket connected to the broadcast address is the address of
+    // the interface used for the broadcast
+    socket.connect((Ipv4Addr::BROADCAST, REPORT_PORT))?;
+    let ip = match socket.local_addr()? {
+        SocketAddr::V4(address) => *address.ip(),
+        SocketAddr::V6(_) => unreachable!("BUG: IPv6 address of IPv4 socket"),
+    };
+    let packet = report_packet(ip, &mac);
+    socket.send(&packet)?;
+
+    Ok(String::from_utf8_lossy(&packet).into_owned())
+}
+
+/// Replace `config_path` with the factory configuration
+fn restore_config(factory_config_path: &Path, config_path: &str) -> io::Result<()> {
+    let content = fs::read(factory_config_path)?;
+    let tmp_path = format!("{}.tmp", config_path);
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, config_path)
+}
+
+/// Listener of the button which executes the actions of recognized presses
+pub struct Listener<T> {
+    pin: T,
+    config: Config,
+    /// Location of the configuration file replaced by the factory one
+    config_path: Option<String>,
+    hooks: Arc<dyn hooks::Hooks>,
+}
+
+impl Listener<gpio::PinIn> {
+    /// Open the button pin selected in `config`
+    pub fn open(
+        gpio_mgr: &gpio::ControlPinManager,
+        config: Config,
+        config_path: Option<String>,
+        hooks: Arc<dyn hooks::Hooks>,
+    ) -> Result<Self, sysfs_gpio::Error> {
+        let pin = match config.gpio_path.as_ref() {
+            Some(path) => gpio::PinIn::from_path(path)?,
+            None => gpio_mgr.get_pin_in(gpio::PinInName::IPSelect)?,
+        };
+        Ok(Self::new(pin, config, config_path, hooks))
+    }
+}
+
+impl<T> Listener<T>
+where
+    T: InputPin + Send,
+    T::Error: fmt::Debug,
+{
+    pub fn new(
+        pin: T,
+        config: Config,
+        config_path: Option<String>,
+        hooks: Arc<dyn hooks::Hooks>,
+    ) -> Self {
+        Self {
+            pin,
+            config,
+            config_path,
+            hooks,
+        }
+    }
+
+    async fn handle_press(&self, press: Press) {
+        info!("Front panel button: {} press", press);
+        self.hooks.button_pressed(press).await;
+        match press {
+            Press::Short => match send_ip_report(&self.config.interface) {
+                Ok(report) => info!("IP report '{}' has been broadcast", report),
+                Err(e) => error!("Cannot broadcast IP report: {}", e),
+            },
+            Press::Long => {
+                self.restore_factory_config();
+                info!("Restarting miner with factory configuration");
+                // use the same path as any other termination to halt hash chains properly
+                unsafe {
+                    libc::kill(libc::getpid(), libc::SIGTERM);
+                }
+            }
+        }
+    }
+
+    fn restore_factory_config(&self) {
+        let result = match self.config_path.as_ref() {
+            Some(config_path) => restore_config(&self.config.factory_config_path, config_path)
+                .map(|_| config_path)
+                .map_err(|e| e.to_string()),
+            None => Err("miner has not been started from configuration file".to_string()),
+        };
+        match result {
+            Ok(config_path) => warn!(
+                "Configuration '{}' has been restored from '{}'",
+                config_path,
+                self.config.factory_config_path.display()
+            ),
+            Err(e) => {
+                error!("Cannot restore factory configuration: {}", e);
+                warn!("Forcing safe mode for the next start instead");
+                safe_mode::CrashLoop::load(safe_mode::DEFAULT_STATE_PATH, Default::default())
+                    .force_safe_mode();
+            }
+        }
+    }
+
+    /// Sample the button until the pin cannot be read anymore
+    pub async fn run(mut self) {
+        let mut classifier = Classifier::new(self.config.debounce, self.config.long_press);
+        loop {
+            // the button is active low
+            let pressed = match self.pin.is_low() {
+                Ok(value) => value,
+                Err(e) => {
+                    error!("Cannot read front panel button: {:?}", e);
+                    return;
+                }
+            };
+            if let Some(press) = classifier.update(pressed, Instant::now()) {
+                self.handle_press(press).await;
+            }
+            delay_for(POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Mutex as StdMutex;
+
+    const DEBOUNCE: Duration = Duration::from_millis(50);
+    const LONG_PRESS: Duration = Duration::from_secs(10);
+
+    /// Feed the classifier with `(level, duration)` steps sampled each 10 ms and collect presses
+    fn classify(steps: &[(bool, Duration)]) -> Vec<Press> {
+        const SAMPLE: Duration = Duration::from_millis(10);
+        let mut classifier = Classifier::new(DEBOUNCE, LONG_PRESS);
+        let mut now = Instant::now();
+        let mut presses = Vec::new();
+        for (pressed, duration) in steps {
+            let end = now + *duration;
+            while now < end {
+                presses.extend(classifier.update(*pressed, now));
+                now += SAMPLE;
+            }
+        }
+        presses
+    }
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn test_short_press() {
+        assert_eq!(
+            classify(&[(false, ms(100)), (true, ms(300)), (false, ms(100))]),
+            vec![Press::Short]
+        );
+        // the press is recognized only after release
+        assert_eq!(classify(&[(false, ms(100)), (true, ms(300))]), vec![]);
+        // two presses
+        assert_eq!(
+            classify(&[
+                (true, ms(200)),
+                (false, ms(200)),
+                (true, ms(200)),
+                (false, ms(200))
+            ]),
+            vec![Press::Short, Press::Short]
+        );
+    }
+
+    #[test]
+    fn test_debounce() {
+        // glitches shorter than debounce time are ignored
+        assert_eq!(
+            classify(&[(false, ms(100)), (true, ms(30)), (false, ms(100))]),
+            vec![]
+        );
+        // bouncing contact during the press does not split it
+        assert_eq!(
+            classify(&[
+                (true, ms(200)),
+                (false, ms(20)),
+                (true, ms(200)),
+                (false, ms(100))
+            ]),
+            vec![Press::Short]
+        );
+    }
+
+    #[test]
+    fn test_long_press() {
+        // long press is reported while the button is still held and release is not reported
+        assert_eq!(classify(&[(true, LONG_PRESS + ms(100))]), vec![Press::Long]);
+        assert_eq!(
+            classify(&[(true, LONG_PRESS * 2), (false, ms(100))]),
+            vec![Press::Long]
+        );
+        // the press is classified by its duration from the first edge
+        assert_eq!(
+            classify(&[(true, LONG_PRESS - ms(100)), (false, ms(100))]),
+            vec![Press::Short]
+        );
+        assert_eq!(
+            classify(&[
+                (true, LONG_PRESS + ms(100)),
+                (false, ms(100)),
+                (true, ms(100)),
+                (false, ms(100))
+            ]),
+            vec![Press::Long, Press::Short]
+        );
+    }
+
+    #[test]
+    fn test_report_packet() {
+        let mac = parse_mac("a0:b0:45:0c:1d:ff\n").expect("BUG: invalid MAC");
+        assert_eq!(mac, [0xa0, 0xb0, 0x45, 0x0c, 0x1d, 0xff]);
+        assert_eq!(
+            report_packet(Ipv4Addr::new(192, 168, 1, 37), &mac),
+            b"192.168.1.37,A0:B0:45:0C:1D:FF".to_vec()
+        );
+
+        for invalid in &[
+            "",
+            "a0:b0:45:0c:1d",
+            "a0:b0:45:0c:1d:ff:00",
+            "a0:b0:45:0c:1d:xx",
+        ] {
+            assert!(parse_mac(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    /// Pin with levels given by a script of samples
+    struct MockPin {
+        levels: StdMutex<std::vec::IntoIter<bool>>,
+    }
+
+    impl InputPin for MockPin {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            self.is_low().map(|value| !value)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.levels
+                .lock()
+                .expect("BUG: cannot lock levels")
+                .next()
+                .ok_or(())
+        }
+    }
+
+    /// Listener reads the pin through the mockable trait and stops when it cannot be read
+    #[tokio::test]
+    async fn test_listener_pin_error() {
+        let pin = MockPin {
+            levels: StdMutex::new(vec![false; 3].into_iter()),
+        };
+        let config = Config {
+            enabled: true,
+            gpio_path: None,
+            factory_config_path: DEFAULT_FACTORY_CONFIG_PATH.into(),
+            interface: DEFAULT_INTERFACE.to_string(),
+            debounce: DEBOUNCE,
+            long_press: LONG_PRESS,
+        };
+        Listener::new(pin, config, None, Arc::new(hooks::NoHooks))
+            .run()
+            .await;
+    }
+}
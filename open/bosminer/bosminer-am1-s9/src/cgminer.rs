@@ -20,13 +20,20 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
-use ii_cgminer_api::command::{DEVDETAILS, FANS, TEMPCTRL, TEMPS};
-use ii_cgminer_api::{command, commands, response};
+use ii_cgminer_api::command::{FANS, SET_ASIC_BOOST, SET_QUOTA, TEMPCTRL, TEMPS};
+use ii_cgminer_api::{command, commands, json, response};
+
+use ii_logging::macros::*;
+
+use bosminer::client;
 
 use serde::Serialize;
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::bm1387::MidstateCount;
+use crate::config;
 use crate::monitor;
 use crate::sensor;
 
@@ -34,6 +41,10 @@ use crate::sensor;
 #[repr(u32)]
 pub enum StatusCode {
     NotReady = 1,
+    InvalidAsicBoost = 2,
+    AsicBoostFailed = 3,
+    InvalidQuota = 4,
+    QuotaFailed = 5,
 }
 
 impl From<StatusCode> for u32 {
@@ -44,30 +55,38 @@ impl From<StatusCode> for u32 {
 
 pub enum ErrorCode {
     NotReady,
+    InvalidAsicBoost,
+    AsicBoostFailed(String),
+    InvalidQuota,
+    QuotaFailed(String),
 }
 
 impl From<ErrorCode> for response::Error {
     fn from(code: ErrorCode) -> Self {
         let (code, msg) = match code {
             ErrorCode::NotReady => (StatusCode::NotReady, "Not ready".to_string()),
+            ErrorCode::InvalidAsicBoost => (
+                StatusCode::InvalidAsicBoost,
+                "Missing or invalid AsicBoost parameter (expected 'on' or 'off')".to_string(),
+            ),
+            ErrorCode::AsicBoostFailed(msg) => (
+                StatusCode::AsicBoostFailed,
+                format!("Cannot change AsicBoost: {}", msg),
+            ),
+            ErrorCode::InvalidQuota => (
+                StatusCode::InvalidQuota,
+                "Missing or invalid quota parameter (expected 'pool,quota[,horizon]')".to_string(),
+            ),
+            ErrorCode::QuotaFailed(msg) => (
+                StatusCode::QuotaFailed,
+                format!("Cannot change quota: {}", msg),
+            ),
         };
 
         Self::from_custom_error(code, msg)
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
-pub struct DevDetailInfo {
-    #[serde(rename = "Voltage")]
-    pub voltage: f64,
-    #[serde(rename = "Frequency")]
-    pub frequency: u32,
-    #[serde(rename = "Chips")]
-    pub chips: u32,
-    #[serde(rename = "Cores")]
-    pub cores: u32,
-}
-
 #[derive(Serialize, PartialEq, Clone, Debug)]
 pub struct TempInfo {
     #[serde(rename = "Board")]
@@ -77,24 +96,75 @@ pub struct TempInfo {
 }
 
 pub struct Handler {
-    model: String,
     managers: Vec<Arc<crate::Manager>>,
     monitor: Arc<monitor::Monitor>,
+    client_manager: client::Manager,
+    /// Configuration file where runtime changes are persisted
+    config_path: Option<String>,
+}
+
+/// Parameters of quota change
+#[derive(Clone, Debug, PartialEq)]
+struct QuotaParameter {
+    pool_idx: usize,
+    quota: usize,
+    horizon: Duration,
 }
 
 impl Handler {
     pub fn new(
-        model: String,
         managers: Vec<Arc<crate::Manager>>,
         monitor: Arc<monitor::Monitor>,
+        client_manager: client::Manager,
+        config_path: Option<String>,
     ) -> Self {
         Self {
-            model,
             managers,
             monitor,
+            client_manager,
+            config_path,
         }
     }
 
+    /// Parse parameter in format `pool,quota[,horizon]` with optional horizon in seconds
+    fn parse_quota(parameter: Option<&json::Value>) -> command::Result<QuotaParameter> {
+        let parameter = parameter
+            .and_then(|value| value.as_str())
+            .ok_or(ErrorCode::InvalidQuota)?;
+        let values = parameter
+            .split(',')
+            .map(|value| value.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ErrorCode::InvalidQuota)?;
+        let horizon = match values.len() {
+            2 => client::Manager::DEFAULT_QUOTA_HORIZON,
+            3 => Duration::from_secs(values[2]),
+            _ => Err(ErrorCode::InvalidQuota)?,
+        };
+
+        Ok(QuotaParameter {
+            pool_idx: values[0] as usize,
+            quota: values[1] as usize,
+            horizon,
+        })
+    }
+
+    fn check_quota(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_quota(*parameter).map(|_| ())
+    }
+
+    fn parse_asic_boost(parameter: Option<&json::Value>) -> command::Result<bool> {
+        match parameter.and_then(|value| value.as_str()) {
+            Some("on") => Ok(true),
+            Some("off") => Ok(false),
+            _ => Err(ErrorCode::InvalidAsicBoost.into()),
+        }
+    }
+
+    fn check_asic_boost(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_asic_boost(*parameter).map(|_| ())
+    }
+
     fn get_monitor_status(&self) -> command::Result<monitor::Status> {
         match self.monitor.status_receiver.borrow().clone() {
             Some(status) => Ok(status),
@@ -102,38 +172,6 @@ impl Handler {
         }
     }
 
-    async fn handle_dev_details(&self) -> command::Result<response::DevDetails<DevDetailInfo>> {
-        let mut list = vec![];
-        for manager in self.managers.iter() {
-            let inner = manager.inner.lock().await;
-            let mut chip_count = 0;
-            let mut voltage = 0.0;
-            let mut frequency = 0;
-            if let Some(hash_chain) = inner.hash_chain.as_ref() {
-                chip_count = hash_chain.chip_count;
-                voltage = hash_chain.get_voltage().await.as_volts() as f64;
-                frequency = hash_chain.get_frequency().await.avg() as u32;
-            }
-            list.push(response::DevDetail {
-                idx: list.len() as i32,
-                name: manager.to_string(),
-                id: manager.hashboard_idx as i32,
-                driver: "".to_string(),
-                kernel: "".to_string(),
-                model: self.model.clone(),
-                device_path: "".to_string(),
-                info: DevDetailInfo {
-                    voltage,
-                    frequency,
-                    chips: chip_count as u32,
-                    cores: (chip_count * crate::bm1387::NUM_CORES_ON_CHIP) as u32,
-                },
-            });
-        }
-
-        Ok(response::DevDetails { list })
-    }
-
     async fn handle_temp_ctrl(&self) -> command::Result<response::ext::TempCtrl> {
         let config = self.get_monitor_status()?.config;
 
@@ -167,15 +205,13 @@ impl Handler {
         for manager in self.managers.iter() {
             let inner = manager.inner.lock().await;
             if let Some(hash_chain) = inner.hash_chain.as_ref() {
-                if let Some(sensor::Temperature { local, remote }) =
-                    hash_chain.current_temperature()
-                {
+                if let Some(readings) = hash_chain.current_temperature() {
                     list.push(response::ext::Temp {
                         idx: list.len() as i32,
                         id: manager.hashboard_idx as i32,
                         info: TempInfo {
-                            board: Option::from(local).unwrap_or(0.0) as f64,
-                            chip: Option::from(remote).unwrap_or(0.0) as f64,
+                            board: readings.max(sensor::Location::Board).unwrap_or(0.0) as f64,
+                            chip: readings.max(sensor::Location::Chip).unwrap_or(0.0) as f64,
                         },
                     });
                 }
@@ -184,6 +220,81 @@ impl Handler {
         Ok(response::ext::Temps { list: list })
     }
 
+    async fn handle_set_asic_boost(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::AsicBoost> {
+        let enabled =
+            Self::parse_asic_boost(parameter).expect("BUG: invalid SET_ASIC_BOOST parameter");
+        let midstate_count = config::midstate_count(enabled);
+
+        // Hashchains are re-initialized only when the number of midstates really changes
+        if midstate_count != self.client_manager.midstate_count() {
+            crate::reconfigure_midstate_count(
+                &self.managers,
+                &self.client_manager,
+                MidstateCount::new(midstate_count),
+            )
+            .await
+            .map_err(|e| ErrorCode::AsicBoostFailed(e.to_string()))?;
+        }
+
+        if let Some(config_path) = self.config_path.as_ref() {
+            if let Err(e) = config::save_asic_boost(config_path, enabled) {
+                warn!("Cannot save AsicBoost setting to '{}': {}", config_path, e);
+            }
+        }
+
+        Ok(response::ext::AsicBoost {
+            enabled,
+            midstate_count: midstate_count as u32,
+        })
+    }
+
+    async fn handle_set_quota(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::Quota> {
+        let parameter = Self::parse_quota(parameter).expect("BUG: invalid SET_QUOTA parameter");
+
+        // Pools are indexed across all groups in the same way as in the POOLS command
+        let mut pools = vec![];
+        for group in self.client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                pools.push((group.clone(), client));
+            }
+        }
+        let (group, client) = pools.get(parameter.pool_idx).cloned().ok_or_else(|| {
+            response::ErrorCode::InvalidPoolId(parameter.pool_idx as i32, pools.len() as i32 - 1)
+        })?;
+
+        self.client_manager
+            .set_quota(&group, parameter.quota, parameter.horizon)
+            .await
+            .map_err(|e| ErrorCode::QuotaFailed(e.to_string()))?;
+
+        if let Some(config_path) = self.config_path.as_ref() {
+            if let Err(e) =
+                config::save_group_quota(config_path, &group.descriptor().name, parameter.quota)
+            {
+                warn!(
+                    "Cannot save quota of group '{}' to '{}': {}",
+                    group.descriptor().name,
+                    config_path,
+                    e
+                );
+            }
+        }
+
+        Ok(response::ext::Quota {
+            idx: parameter.pool_idx as i32,
+            url: client.descriptor().await.get_url(true, true, false),
+            group: group.descriptor().name.clone(),
+            quota: parameter.quota as u32,
+            horizon: parameter.horizon.as_secs() as u32,
+        })
+    }
+
     async fn handle_fans(&self) -> command::Result<response::ext::Fans> {
         let status = self.get_monitor_status()?;
         let speed = status.fan_speed.map(|speed| speed.to_pwm()).unwrap_or(0);
@@ -205,17 +316,24 @@ impl Handler {
 }
 
 pub fn create_custom_commands(
-    backend: Arc<crate::Backend>,
     managers: Vec<Arc<crate::Manager>>,
     monitor: Arc<monitor::Monitor>,
+    client_manager: client::Manager,
+    config_path: Option<String>,
 ) -> Option<command::Map> {
-    let handler = Arc::new(Handler::new(backend.to_string(), managers, monitor));
+    let handler = Arc::new(Handler::new(managers, monitor, client_manager, config_path));
+
+    let check_set_asic_boost: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_asic_boost(command, parameter));
+    let check_set_quota: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_quota(command, parameter));
 
     let custom_commands = commands![
-        (DEVDETAILS: ParameterLess -> handler.handle_dev_details),
         (TEMPCTRL: ParameterLess -> handler.handle_temp_ctrl),
         (TEMPS: ParameterLess -> handler.handle_temps),
-        (FANS: ParameterLess -> handler.handle_fans)
+        (FANS: ParameterLess -> handler.handle_fans),
+        (SET_ASIC_BOOST: Parameter(check_set_asic_boost) -> handler.handle_set_asic_boost, Write),
+        (SET_QUOTA: Parameter(check_set_quota) -> handler.handle_set_quota, Write)
     ];
 
     Some(custom_commands)
@@ -127,14 +127,11 @@ impl InnerContext {
     /// How long to wait for command RX queue flush
     const COMMAND_FLUSH_TIMEOUT: Duration = Duration::from_micros(5);
 
-    /// Read register(s)
-    ///
-    /// Throw an error if unexpected number of replies have been received.
-    /// (expected number is one reply per chip)
-    async fn read_register<T: bm1387::Register>(
+    /// Send request for reading register(s) and collect raw replies without checking their count
+    async fn collect_responses<T: bm1387::Register>(
         &mut self,
         chip_address: ChipAddress,
-    ) -> error::Result<Vec<T>> {
+    ) -> error::Result<Vec<u32>> {
         let cmd = bm1387::GetStatusCmd::new(chip_address, T::REG_NUM);
         // send command, do not wait for it to be sent out
         self.command_io
@@ -161,6 +158,18 @@ impl InnerContext {
                 None => break,
             }
         }
+        Ok(responses)
+    }
+
+    /// Read register(s)
+    ///
+    /// Throw an error if unexpected number of replies have been received.
+    /// (expected number is one reply per chip)
+    async fn read_register<T: bm1387::Register>(
+        &mut self,
+        chip_address: ChipAddress,
+    ) -> error::Result<Vec<T>> {
+        let responses = self.collect_responses::<T>(chip_address).await?;
 
         // figure out how many responses are we expecting, and thrown an error
         // if less were received
@@ -276,6 +285,14 @@ impl Context {
         inner.send_raw_command(cmd, wait).await
     }
 
+    /// Broadcast read of register `T` and return the number of chips which replied. Unlike
+    /// `read_register` the count is not required to match the number of enumerated chips.
+    pub async fn count_responses<T: bm1387::Register>(&self) -> error::Result<usize> {
+        let mut inner = self.inner.lock().await;
+        let responses = inner.collect_responses::<T>(ChipAddress::All).await?;
+        Ok(responses.len())
+    }
+
     pub async fn set_chip_count(&self, chip_count: usize) {
         let mut inner = self.inner.lock().await;
         inner.set_chip_count(chip_count);
@@ -31,27 +31,40 @@ use ii_logging::macros::*;
 
 pub mod api;
 mod metadata;
+pub mod remote;
 pub mod support;
 
 use crate::bm1387::MidstateCount;
+use crate::button;
+use crate::environment::{self, Environment};
+use crate::error;
 use crate::fan;
 use crate::hooks;
 use crate::monitor;
 use crate::power;
+use crate::realtime;
 use crate::FrequencySettings;
 
 use support::OptionDefault;
 
 use bosminer::client;
 use bosminer::hal::{self, BackendConfig as _};
+use bosminer::progress;
+use bosminer::shutdown;
+use bosminer::stats;
 
-use bosminer_config::{ClientDescriptor, ClientUserInfo};
+use bosminer_config::{
+    ApiConfig, ClientDescriptor, ClientProtocol, ClientUserInfo, DigestConfig, FanOutConfig,
+    GroupDescriptor, LoadBalanceStrategy, SolutionExportConfig, SolutionRoutingPolicy,
+    WorkHistoryConfig,
+};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt;
 use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -74,6 +87,9 @@ pub const DEFAULT_CONFIG_PATH: &'static str = "/etc/bosminer.toml";
 /// Default Hardware ID path
 pub const DEFAULT_HW_ID_PATH: &'static str = "/tmp/miner_hwid";
 
+/// Default directory of the archive of block candidates
+pub const DEFAULT_BLOCK_ARCHIVE_PATH: &'static str = "/etc/bosminer-blocks";
+
 /// Default value for hash chain enabled flag
 pub const DEFAULT_HASH_CHAIN_ENABLED: bool = true;
 
@@ -86,12 +102,19 @@ pub const ASIC_BOOST_MIDSTATE_COUNT: usize = 4;
 /// Default number of midstates
 pub const DEFAULT_ASIC_BOOST: bool = true;
 
+/// Numbers of midstates supported by the FPGA when AsicBoost is enabled
+pub const ASIC_BOOST_MIDSTATE_COUNTS: [usize; 3] = [1, 2, 4];
+
 /// Default PLL frequency for clocking the chips in MHz
 pub const DEFAULT_FREQUENCY_MHZ: f64 = 650.0;
 
 /// Default voltage
 pub const DEFAULT_VOLTAGE_V: f64 = 8.8;
 
+/// Conservative chip settings used in safe mode after repeated crashes
+pub const SAFE_MODE_FREQUENCY_MHZ: f64 = 550.0;
+pub const SAFE_MODE_VOLTAGE_V: f64 = 8.8;
+
 /// Default temperature control mode
 pub const DEFAULT_TEMP_CONTROL_MODE: TempControlMode = TempControlMode::Auto;
 
@@ -142,11 +165,57 @@ pub const DEFAULT_HASHRATE_INTERVAL: Duration = Duration::from_secs(60);
 /// Maximum time it takes to compute one job under normal circumstances
 pub const JOB_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Default settings of the dedicated runtime for hash chain IO tasks
+pub const DEFAULT_REALTIME_IO_ENABLED: bool = true;
+pub const DEFAULT_REALTIME_IO_SCHED_FIFO: bool = false;
+pub const DEFAULT_REALTIME_IO_PRIORITY: i32 = 10;
+pub const DEFAULT_REALTIME_IO_LATENCY_BUDGET_MS: u64 = 50;
+
+/// Front panel button is handled by default
+pub const DEFAULT_BUTTON_ENABLED: bool = true;
+
+/// Range of SCHED_FIFO priority
+pub const REALTIME_IO_PRIORITY_MIN: i32 = 1;
+pub const REALTIME_IO_PRIORITY_MAX: i32 = 99;
+
+/// Number of midstates for enabled or disabled AsicBoost
+pub fn midstate_count(asic_boost: bool) -> usize {
+    if asic_boost {
+        ASIC_BOOST_MIDSTATE_COUNT
+    } else {
+        1
+    }
+}
+
+/// Parse per-chain value from command line in format `IDX:VALUE` (e.g. `6:650`) and check that
+/// the hash chain index is valid
+pub fn parse_hash_chain_value(value: &str) -> Result<(usize, f64), String> {
+    let mut parts = value.splitn(2, ':');
+    let (idx, value) = match (parts.next(), parts.next()) {
+        (Some(idx), Some(value)) => (idx, value),
+        _ => Err("expected format is 'IDX:VALUE'".to_string())?,
+    };
+    let idx = idx
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("hash chain index '{}' is not number", idx))?;
+    if !(HASH_CHAIN_INDEX_MIN..=HASH_CHAIN_INDEX_MAX).contains(&idx) {
+        Err(format!(
+            "hash chain index '{}' is out of range '{}..{}'",
+            idx, HASH_CHAIN_INDEX_MIN, HASH_CHAIN_INDEX_MAX
+        ))?;
+    }
+    let value = value.trim().parse::<f64>().map_err(|e| e.to_string())?;
+    Ok((idx, value))
+}
+
 pub struct ResolvedChainConfig {
     pub midstate_count: MidstateCount,
     pub frequency: FrequencySettings,
     pub voltage: power::Voltage,
     pub enabled: bool,
+    pub io: realtime::Config,
+    pub assignment_max_age: Option<Duration>,
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
@@ -182,6 +251,9 @@ pub struct Format {
 pub struct HashChainGlobal {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub asic_boost: Option<bool>,
+    /// Explicit number of midstates which takes precedence over the default one for AsicBoost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub midstate_count: Option<usize>,
     #[serde(flatten)]
     pub overridable: Option<HashChain>,
 }
@@ -195,6 +267,9 @@ pub struct HashChain {
     pub frequency: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub voltage: Option<f64>,
+    /// Maximal age (in milliseconds) of work sent to the hash chain, older work is skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignment_max_age: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -219,13 +294,58 @@ pub struct FanControl {
     min_fans: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RealtimeIo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sched_fifo: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<i32>,
+    /// Maximal expected time (in milliseconds) between solution IRQ and drained FIFO
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_budget: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Button {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    /// Sysfs path of the (already exported) GPIO pin of the button
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpio_path: Option<String>,
+    /// Configuration restored by long press
+    #[serde(skip_serializing_if = "Option::is_none")]
+    factory_config: Option<String>,
+    /// Network interface reported by short press
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interface: Option<String>,
+    /// Time (in seconds) the button has to be held to restore the factory configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    long_press: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Backend {
     #[serde(skip)]
     pub info: hal::BackendInfo,
     #[serde(skip)]
     pub client_manager: Option<client::Manager>,
+    #[serde(skip)]
+    pub progress: Option<Arc<progress::Registry>>,
+    /// Bitcoin network (mainnet, testnet or regtest) used by all pools without explicit network
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// Time in seconds given to solutions of the last work to reach pools when the miner is
+    /// being shut down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shutdown_grace_period: Option<u64>,
+    /// Handling of solutions whose pool has been removed while they were being solved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solution_routing: Option<SolutionRoutingPolicy>,
     // TODO: merge pools and clients
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hash_chain_global: Option<HashChainGlobal>,
@@ -241,10 +361,43 @@ pub struct Backend {
     #[serde(rename = "group")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub groups: Option<Vec<bosminer_config::GroupConfig>>,
+    /// Daily digest of shares submitted to pools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<DigestConfig>,
+    /// Log of work engine lifecycle events used for post-mortem analysis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_history: Option<WorkHistoryConfig>,
+    /// Directory where solutions meeting the network target are archived
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_archive: Option<String>,
+    /// Ring buffer in shared memory where all solutions are exported for a co-located process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solution_export: Option<SolutionExportConfig>,
+    /// File where lifetime statistics are persisted across restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats_persistence_path: Option<String>,
+    /// Period in seconds of saving the lifetime statistics
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats_persistence_interval: Option<u64>,
+    /// Staggered distribution of new jobs to hash chains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan_out: Option<FanOutConfig>,
+    /// Listen address and allowed clients of the CGMiner API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api: Option<ApiConfig>,
+    /// Dedicated (optionally real-time) runtime for hash chain IO tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realtime_io: Option<RealtimeIo>,
+    /// Front panel button for IP report and restore of the factory configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    button: Option<Button>,
     #[serde(skip)]
     pub hooks: Option<Arc<dyn hooks::Hooks>>,
     #[serde(skip)]
     pub fans_on_while_warming_up: Option<bool>,
+    /// Location of the configuration file where runtime changes are persisted
+    #[serde(skip)]
+    pub config_path: Option<String>,
 }
 
 pub trait ConfigBody
@@ -354,6 +507,42 @@ impl Backend {
         }
     }
 
+    /// Expand references to environment variables in connection settings of all pools. It is
+    /// done only for the running miner so the file keeps the references when it is saved back.
+    pub fn expand_env(&mut self) -> Result<(), String> {
+        for group in self.groups.iter_mut().flatten() {
+            for pool in group.pools.iter_mut().flatten() {
+                pool.expand_env()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn asic_boost(&self) -> bool {
+        self.hash_chain_global
+            .as_ref()
+            .and_then(|v| v.asic_boost)
+            .unwrap_or(DEFAULT_ASIC_BOOST)
+    }
+
+    /// Check that explicitly set number of midstates is supported by the backend
+    pub fn check_midstate_count(&self) -> Result<(), String> {
+        if let Some(midstate_count) = self
+            .hash_chain_global
+            .as_ref()
+            .and_then(|v| v.midstate_count)
+        {
+            let supported_midstate_counts = self.supported_midstate_counts();
+            if !supported_midstate_counts.contains(&midstate_count) {
+                Err(format!(
+                    "midstate count {} is not supported (supported: {:?})",
+                    midstate_count, supported_midstate_counts
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn resolve_chain_config(&self, hash_chain_idx: usize) -> ResolvedChainConfig {
         // Take global hash chain configuration or default value
         let overridable = self
@@ -369,6 +558,7 @@ impl Backend {
             DEFAULT_VOLTAGE_V,
         );
         let mut enabled = DEFAULT_HASH_CHAIN_ENABLED;
+        let mut assignment_max_age = overridable.as_ref().and_then(|v| v.assignment_max_age);
 
         // If there's a per-chain override then apply it
         if let Some(hash_chain) = self
@@ -385,6 +575,7 @@ impl Backend {
                 .voltage
                 .map(|v| OptionDefault::Some(v))
                 .unwrap_or(voltage);
+            assignment_max_age = hash_chain.assignment_max_age.or(assignment_max_age);
         }
 
         // Computed s9-specific values
@@ -395,6 +586,51 @@ impl Backend {
             voltage: power::Voltage::from_volts(*voltage as f32)
                 .expect("TODO: bad voltage requested"),
             enabled,
+            io: self.resolve_realtime_io(),
+            assignment_max_age: assignment_max_age.map(Duration::from_millis),
+        }
+    }
+
+    pub fn resolve_realtime_io(&self) -> realtime::Config {
+        let realtime_io = self.realtime_io.as_ref();
+        realtime::Config {
+            enabled: realtime_io
+                .and_then(|v| v.enabled)
+                .unwrap_or(DEFAULT_REALTIME_IO_ENABLED),
+            sched_fifo: realtime_io
+                .and_then(|v| v.sched_fifo)
+                .unwrap_or(DEFAULT_REALTIME_IO_SCHED_FIFO),
+            priority: realtime_io
+                .and_then(|v| v.priority)
+                .unwrap_or(DEFAULT_REALTIME_IO_PRIORITY),
+            latency_budget: Duration::from_millis(
+                realtime_io
+                    .and_then(|v| v.latency_budget)
+                    .unwrap_or(DEFAULT_REALTIME_IO_LATENCY_BUDGET_MS),
+            ),
+        }
+    }
+
+    pub fn resolve_button(&self) -> button::Config {
+        let button = self.button.as_ref();
+        button::Config {
+            enabled: button
+                .and_then(|v| v.enabled)
+                .unwrap_or(DEFAULT_BUTTON_ENABLED),
+            gpio_path: button.and_then(|v| v.gpio_path.as_ref()).map(Into::into),
+            factory_config_path: button
+                .and_then(|v| v.factory_config.as_deref())
+                .unwrap_or(button::DEFAULT_FACTORY_CONFIG_PATH)
+                .into(),
+            interface: button
+                .and_then(|v| v.interface.as_deref())
+                .unwrap_or(button::DEFAULT_INTERFACE)
+                .to_string(),
+            debounce: button::DEFAULT_DEBOUNCE,
+            long_press: button
+                .and_then(|v| v.long_press)
+                .map(Duration::from_secs)
+                .unwrap_or(button::DEFAULT_LONG_PRESS),
         }
     }
 
@@ -498,17 +734,266 @@ impl Backend {
         }
     }
 
-    pub fn fill_info<T>(&mut self) -> Result<(), std::io::Error>
+    /// Enable or disable AsicBoost. The explicit number of midstates is removed because it would
+    /// take precedence over the default one for AsicBoost.
+    pub fn set_asic_boost(&mut self, asic_boost: bool) {
+        let hash_chain_global = self
+            .hash_chain_global
+            .get_or_insert_with(|| Default::default());
+        hash_chain_global.asic_boost.replace(asic_boost);
+        hash_chain_global.midstate_count.take();
+    }
+
+    /// Derive conservative variant of the configuration which is used after repeated crashes.
+    /// All chips run at safe frequency and voltage without ASIC boost, temperature and fan
+    /// control use defaults and only the first enabled pool is kept. Disabled hash chains stay
+    /// disabled.
+    pub fn into_safe_mode(mut self) -> Self {
+        self.hash_chain_global = Some(HashChainGlobal {
+            asic_boost: Some(false),
+            midstate_count: None,
+            overridable: Some(HashChain {
+                enabled: None,
+                frequency: Some(SAFE_MODE_FREQUENCY_MHZ),
+                voltage: Some(SAFE_MODE_VOLTAGE_V),
+                assignment_max_age: None,
+            }),
+        });
+        self.hash_chains = self.hash_chains.map(|hash_chains| {
+            hash_chains
+                .into_iter()
+                .map(|(idx, hash_chain)| {
+                    (
+                        idx,
+                        HashChain {
+                            enabled: hash_chain.enabled,
+                            frequency: None,
+                            voltage: None,
+                            assignment_max_age: hash_chain.assignment_max_age,
+                        },
+                    )
+                })
+                .collect()
+        });
+        self.temp_control = None;
+        self.fan_control = None;
+
+        let is_enabled =
+            |pool: &bosminer_config::PoolConfig| pool.enabled.unwrap_or(DEFAULT_POOL_ENABLED);
+        let mut groups = self.groups.take().unwrap_or_default();
+        // prefer the first enabled pool but keep any pool when all of them are disabled
+        let group_idx = groups
+            .iter()
+            .position(|group| group.pools.iter().flatten().any(is_enabled))
+            .or_else(|| {
+                groups
+                    .iter()
+                    .position(|group| group.pools.iter().flatten().next().is_some())
+            });
+        self.groups = group_idx.map(|group_idx| {
+            let mut group = groups.swap_remove(group_idx);
+            let mut pools = group.pools.take().unwrap_or_default();
+            let pool_idx = pools.iter().position(is_enabled).unwrap_or(0);
+            group.pools = Some(vec![pools.swap_remove(pool_idx)]);
+            vec![group]
+        });
+
+        self
+    }
+
+    /// Change quota of the group with `group_name`
+    pub fn set_group_quota(&mut self, group_name: &str, quota: usize) -> Result<(), String> {
+        let group = self
+            .groups
+            .iter_mut()
+            .flatten()
+            .find(|group| group.descriptor.name == group_name)
+            .ok_or_else(|| format!("missing group '{}'", group_name))?;
+        if group.descriptor.get_fixed_share_ratio().is_some() {
+            Err(format!("group '{}' has fixed share ratio", group_name))?;
+        }
+        let failover = group.descriptor.failover.take();
+        group.descriptor = GroupDescriptor::new(
+            group.descriptor.name.clone(),
+            group.descriptor.private,
+            LoadBalanceStrategy::Quota(quota),
+        );
+        group.descriptor.failover = failover;
+        Ok(())
+    }
+
+    /// Per-chain settings of hash chain with index `hash_chain_idx` which are created when they
+    /// do not exist yet
+    pub fn hash_chain_mut(&mut self, hash_chain_idx: usize) -> &mut HashChain {
+        self.hash_chains
+            .get_or_insert_with(|| Default::default())
+            .entry(hash_chain_idx.to_string())
+            .or_default()
+    }
+
+    /// Indices of hash chains which are not disabled in configuration
+    pub fn enabled_hash_chains(&self) -> Vec<usize> {
+        (HASH_CHAIN_INDEX_MIN..=HASH_CHAIN_INDEX_MAX)
+            .filter(|idx| self.resolve_chain_config(*idx).enabled)
+            .collect()
+    }
+
+    pub fn fill_info<T>(&mut self) -> error::Result<()>
     where
         T: ConfigBody,
     {
         self.info.hw_rev = HW_MODEL.to_string();
         self.info.dev_id = fs::read_to_string(DEFAULT_HW_ID_PATH)?.trim().to_string();
         self.info.fw_ver = format!("{} {}", T::variant(), bosminer::version::STRING.to_string());
-        Ok(())
+
+        let environment = Environment::probe(&environment::HostSystem, &self.enabled_hash_chains());
+        self.info.environment = environment.to_map();
+        // Refuse to continue on a system without all required UIO devices
+        environment.check()
+    }
+}
+
+/// Collect paths of keys which are in the `raw` configuration but missing in the `known` one
+fn collect_unknown_keys(
+    raw: &toml::Value,
+    known: Option<&toml::Value>,
+    path: &str,
+    keys: &mut Vec<String>,
+) {
+    match raw {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match known.and_then(|known| known.get(key)) {
+                    Some(known_value) => {
+                        collect_unknown_keys(value, Some(known_value), &key_path, keys)
+                    }
+                    None => keys.push(key_path),
+                }
+            }
+        }
+        toml::Value::Array(array) => {
+            for (idx, value) in array.iter().enumerate() {
+                collect_unknown_keys(
+                    value,
+                    known.and_then(|known| known.get(idx)),
+                    &format!("{}[{}]", path, idx),
+                    keys,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Find keys in the configuration file at `config_path` which are silently ignored by the parser.
+/// Most sections deny unknown fields so this covers only the lenient ones (e.g. `format`).
+pub fn unknown_keys(config_path: &str) -> Result<Vec<String>, String> {
+    let raw: toml::Value = fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())?
+        .parse()
+        .map_err(|e: toml::de::Error| e.to_string())?;
+    let config: FormatWrapper<Backend> = raw.clone().try_into().map_err(|e| e.to_string())?;
+    let known = toml::Value::try_from(&config).map_err(|e| e.to_string())?;
+
+    let mut keys = vec![];
+    collect_unknown_keys(&raw, Some(&known), "", &mut keys);
+    Ok(keys)
+}
+
+/// Top-level sections of the configuration which can be applied without restart of the miner
+const LIVE_SECTIONS: &[&str] = &["group"];
+
+/// Collect paths of keys whose values differ between the `running` and `new` configuration
+fn collect_changed_keys(
+    running: Option<&toml::Value>,
+    new: Option<&toml::Value>,
+    path: &str,
+    keys: &mut Vec<String>,
+) {
+    match (running, new) {
+        (Some(toml::Value::Table(running)), Some(toml::Value::Table(new))) => {
+            let table_keys: BTreeSet<_> = running.keys().chain(new.keys()).collect();
+            for key in table_keys {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_changed_keys(running.get(key), new.get(key), &key_path, keys);
+            }
+        }
+        (running, new) => {
+            if running != new {
+                keys.push(path.to_string());
+            }
+        }
+    }
+}
+
+/// Way a changed setting is applied to the running miner
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Apply {
+    /// Applied by reloading the configuration file without touching hash chains
+    Live,
+    /// Applied only after restart of the miner
+    Restart,
+}
+
+/// Setting which differs between two configurations
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Change {
+    /// Path of the changed key (e.g. `hash_chain.6.frequency`)
+    pub key: String,
+    pub apply: Apply,
+}
+
+impl Change {
+    fn new(key: String) -> Self {
+        let section = key.split('.').next().unwrap_or_default();
+        let apply = if LIVE_SECTIONS.contains(&section) {
+            Apply::Live
+        } else {
+            Apply::Restart
+        };
+        Self { key, apply }
     }
 }
 
+/// Compute changed settings between the `running` and `new` configuration body and classify
+/// whether they can be applied live
+pub fn diff(running: &toml::Value, new: &toml::Value) -> Vec<Change> {
+    let mut keys = vec![];
+    collect_changed_keys(Some(running), Some(new), "", &mut keys);
+    keys.into_iter().map(Change::new).collect()
+}
+
+/// Persist quota of the group changed at runtime into the configuration file at `config_path`
+pub fn save_group_quota(config_path: &str, group_name: &str, quota: usize) -> Result<(), String> {
+    let mut config: FormatWrapper<Backend> = match FormatWrapper::parse(config_path) {
+        Ok(config) | Err(FormatWrapperError::IncompatibleVersion(_, Some(config))) => config,
+        Err(e) => Err(e.to_string())?,
+    };
+    config.body.set_group_quota(group_name, quota)?;
+    api::save(&config, Path::new(config_path)).map_err(|e| e.to_string())
+}
+
+/// Persist AsicBoost changed in the running miner to the configuration file. The file is stamped
+/// with the generator and time of the change.
+pub fn save_asic_boost(config_path: &str, asic_boost: bool) -> Result<(), String> {
+    let mut config: FormatWrapper<Backend> = match FormatWrapper::parse(config_path) {
+        Ok(config) | Err(FormatWrapperError::IncompatibleVersion(_, Some(config))) => config,
+        Err(e) => Err(e.to_string())?,
+    };
+    config.body.set_asic_boost(asic_boost);
+    api::save_runtime_change(&mut config, Path::new(config_path)).map_err(|e| e.to_string())
+}
+
 impl ConfigBody for Backend {
     fn model() -> String {
         return FORMAT_MODEL.into();
@@ -542,6 +1027,63 @@ impl ConfigBody for Backend {
             }
         }
 
+        self.check_midstate_count()?;
+
+        if let Some(network) = &self.network {
+            let _ = network.parse::<ii_bitcoin::Network>()?;
+        }
+
+        if let Some(digest) = &self.digest {
+            digest.sanity_check()?;
+        }
+
+        if let Some(work_history) = &self.work_history {
+            work_history.sanity_check()?;
+        }
+
+        if self.block_archive.as_deref() == Some("") {
+            Err("block archive path cannot be empty".to_string())?;
+        }
+
+        if let Some(solution_export) = &self.solution_export {
+            solution_export.sanity_check()?;
+        }
+
+        if self.stats_persistence_path.as_deref() == Some("") {
+            Err("stats persistence path cannot be empty".to_string())?;
+        }
+        if self.stats_persistence_interval == Some(0) {
+            Err("stats persistence interval must be greater than 0".to_string())?;
+        }
+
+        if let Some(fan_out) = &self.fan_out {
+            fan_out.sanity_check()?;
+        }
+
+        if let Some(api) = &self.api {
+            api.sanity_check()?;
+        }
+
+        if let Some(realtime_io) = &self.realtime_io {
+            if let Some(priority) = realtime_io.priority {
+                if !(REALTIME_IO_PRIORITY_MIN..=REALTIME_IO_PRIORITY_MAX).contains(&priority) {
+                    Err(format!(
+                        "realtime IO priority '{}' is out of range '{}..{}'",
+                        priority, REALTIME_IO_PRIORITY_MIN, REALTIME_IO_PRIORITY_MAX
+                    ))?;
+                }
+            }
+            if realtime_io.latency_budget == Some(0) {
+                Err("realtime IO latency budget must be greater than zero".to_string())?;
+            }
+        }
+
+        if let Some(button) = &self.button {
+            if button.long_press == Some(0) {
+                Err("button long press must be greater than zero".to_string())?;
+            }
+        }
+
         // Analyze group configuration, make sure the groups are unique, and build descriptor
         // topology out of the configuration data
         // Don't worry if is this section missing, maybe there are some pools on command line
@@ -551,9 +1093,55 @@ impl ConfigBody for Backend {
                 if let Some(name) = group_names.replace(&group.descriptor.name) {
                     Err(format!("group with name '{}' already defined", name))?;
                 }
+                if let Some(failover) = &group.descriptor.failover {
+                    failover
+                        .sanity_check()
+                        .map_err(|e| format!("{} in group '{}'", e, group.descriptor.name))?;
+                }
                 if let Some(pools) = &group.pools {
                     for pool in pools {
-                        let _ = ClientDescriptor::create(
+                        if let Some(network) = &pool.network {
+                            let _ = network.parse::<ii_bitcoin::Network>().map_err(|e| {
+                                format!("{} in pool '{}@{}'", e, pool.url, pool.user)
+                            })?;
+                        }
+                        if let Some(label) = &pool.label {
+                            // label is used as a value of API filters delimited with ','
+                            if label.trim().is_empty() || label.contains(',') {
+                                Err(format!(
+                                    "invalid label '{}' in pool '{}@{}'",
+                                    label, pool.url, pool.user
+                                ))?;
+                            }
+                        }
+                        if let Some(reject_breaker) = &pool.reject_breaker {
+                            reject_breaker.sanity_check().map_err(|e| {
+                                format!("{} in pool '{}@{}'", e, pool.url, pool.user)
+                            })?;
+                        }
+                        if let Some(coinbase_check) = &pool.coinbase_check {
+                            coinbase_check.sanity_check().map_err(|e| {
+                                format!("{} in pool '{}@{}'", e, pool.url, pool.user)
+                            })?;
+                        }
+                        if pool.min_difficulty == Some(0) || pool.fixed_difficulty == Some(0) {
+                            Err(format!(
+                                "share difficulty cannot be zero in pool '{}@{}'",
+                                pool.url, pool.user
+                            ))?;
+                        }
+                        if let (Some(min_difficulty), Some(fixed_difficulty)) =
+                            (pool.min_difficulty, pool.fixed_difficulty)
+                        {
+                            if fixed_difficulty < min_difficulty {
+                                Err(format!(
+                                    "fixed difficulty {} is lower than minimal difficulty {} in \
+                                     pool '{}@{}'",
+                                    fixed_difficulty, min_difficulty, pool.url, pool.user
+                                ))?;
+                            }
+                        }
+                        let descriptor = ClientDescriptor::create(
                             pool.url.as_str(),
                             &ClientUserInfo::new(pool.user.as_str(), pool.password.as_deref()),
                             pool.enabled.unwrap_or(DEFAULT_POOL_ENABLED),
@@ -561,6 +1149,24 @@ impl ConfigBody for Backend {
                         .map_err(|e| {
                             format!("{} in pool '{}@{}'", e.to_string(), pool.url, pool.user)
                         })?;
+                        // Coinbase is not available to clients of other protocols
+                        if pool.coinbase_check.is_some() {
+                            match descriptor.protocol {
+                                ClientProtocol::StratumV1 | ClientProtocol::StratumV1Tls => {}
+                                _ => Err(format!(
+                                    "coinbase check is not supported by pool '{}@{}'",
+                                    pool.url, pool.user
+                                ))?,
+                            }
+                        }
+                        if (pool.ca_cert.is_some() || pool.insecure.is_some())
+                            && !descriptor.protocol.is_tls()
+                        {
+                            Err(format!(
+                                "TLS settings are not supported by pool '{}@{}'",
+                                pool.url, pool.user
+                            ))?;
+                        }
                     }
                 }
             }
@@ -581,23 +1187,754 @@ impl ConfigBody for Backend {
 impl hal::BackendConfig for Backend {
     #[inline]
     fn midstate_count(&self) -> usize {
-        if self
-            .hash_chain_global
+        self.hash_chain_global
             .as_ref()
-            .and_then(|v| v.asic_boost)
-            .unwrap_or(DEFAULT_ASIC_BOOST)
-        {
-            ASIC_BOOST_MIDSTATE_COUNT
+            .and_then(|v| v.midstate_count)
+            .unwrap_or_else(|| midstate_count(self.asic_boost()))
+    }
+
+    fn supported_midstate_counts(&self) -> Vec<usize> {
+        if self.asic_boost() {
+            ASIC_BOOST_MIDSTATE_COUNTS.to_vec()
         } else {
-            1
+            vec![midstate_count(false)]
         }
     }
 
+    fn network(&self) -> ii_bitcoin::Network {
+        self.network
+            .as_ref()
+            .map(|network| {
+                network
+                    .parse()
+                    .expect("BUG: network has not been validated")
+            })
+            .unwrap_or_default()
+    }
+
     fn set_client_manager(&mut self, client_manager: client::Manager) {
         self.client_manager.replace(client_manager);
     }
 
+    fn set_progress(&mut self, progress: Arc<progress::Registry>) {
+        self.progress.replace(progress);
+    }
+
     fn info(&self) -> Option<hal::BackendInfo> {
         Some(self.info.clone())
     }
+
+    fn digest(&self) -> Option<DigestConfig> {
+        self.digest
+            .as_ref()
+            .filter(|digest| digest.is_enabled())
+            .cloned()
+    }
+
+    fn work_history(&self) -> WorkHistoryConfig {
+        self.work_history.clone().unwrap_or_default()
+    }
+
+    fn block_archive(&self) -> Option<String> {
+        Some(
+            self.block_archive
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BLOCK_ARCHIVE_PATH.to_string()),
+        )
+    }
+
+    fn solution_export(&self) -> Option<SolutionExportConfig> {
+        self.solution_export.clone()
+    }
+
+    fn stats_persistence_path(&self) -> Option<String> {
+        self.stats_persistence_path.clone()
+    }
+
+    fn stats_persistence_interval(&self) -> Duration {
+        self.stats_persistence_interval
+            .map(Duration::from_secs)
+            .unwrap_or(stats::persist::DEFAULT_INTERVAL)
+    }
+
+    fn fan_out(&self) -> Option<FanOutConfig> {
+        self.fan_out.clone()
+    }
+
+    fn shutdown_grace_period(&self) -> Duration {
+        self.shutdown_grace_period
+            .map(Duration::from_secs)
+            .unwrap_or(shutdown::DEFAULT_GRACE_PERIOD)
+    }
+
+    fn solution_routing(&self) -> SolutionRoutingPolicy {
+        self.solution_routing.unwrap_or_default()
+    }
+
+    fn assignment_max_age(&self) -> Option<Duration> {
+        self.hash_chain_global
+            .as_ref()
+            .and_then(|v| v.overridable.as_ref())
+            .and_then(|v| v.assignment_max_age)
+            .map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_CONFIG: &str = r#"
+        [hash_chain_global]
+        asic_boost = true
+        frequency = 700.0
+        voltage = 9.0
+
+        [hash_chain.6]
+        frequency = 750.0
+
+        [hash_chain.7]
+        enabled = false
+        voltage = 9.2
+
+        [temp_control]
+        mode = "manual"
+
+        [fan_control]
+        speed = 30
+
+        [[group]]
+        name = "Disabled"
+
+        [[group.pool]]
+        enabled = false
+        url = "stratum+tcp://disabled.example.com"
+        user = "disabled"
+
+        [[group]]
+        name = "Main"
+
+        [group.failover]
+        timeout = 60
+
+        [[group.pool]]
+        enabled = false
+        url = "stratum+tcp://backup.example.com"
+        user = "backup"
+
+        [[group.pool]]
+        url = "stratum+tcp://main.example.com"
+        user = "main"
+
+        [[group.pool]]
+        url = "stratum+tcp://other.example.com"
+        user = "other"
+    "#;
+
+    #[test]
+    fn test_safe_mode_config() {
+        let backend_config: Backend = toml::from_str(TEST_CONFIG).expect("BUG: invalid config");
+        let safe_config = backend_config.into_safe_mode();
+
+        assert_eq!(
+            hal::BackendConfig::midstate_count(&safe_config),
+            midstate_count(false)
+        );
+        for idx in HASH_CHAIN_INDEX_MIN..=HASH_CHAIN_INDEX_MAX {
+            let chain_config = safe_config.resolve_chain_config(idx);
+            assert_eq!(
+                chain_config.frequency.avg(),
+                (SAFE_MODE_FREQUENCY_MHZ * 1_000_000.0) as usize
+            );
+            assert!(
+                chain_config.voltage
+                    == power::Voltage::from_volts(SAFE_MODE_VOLTAGE_V as f32).unwrap()
+            );
+        }
+        // disabled hash chain is not enabled by safe mode
+        assert_eq!(safe_config.enabled_hash_chains(), vec![6, 8]);
+        assert!(safe_config.temp_control.is_none());
+        assert!(safe_config.fan_control.is_none());
+
+        let groups = safe_config.groups.expect("BUG: missing groups");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].descriptor.name, "Main");
+        let pools = groups[0].pools.as_ref().expect("BUG: missing pools");
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].user, "main");
+    }
+
+    #[test]
+    fn test_safe_mode_config_without_enabled_pool() {
+        let mut backend_config: Backend = toml::from_str(TEST_CONFIG).expect("BUG: invalid config");
+        for group in backend_config.groups.iter_mut().flatten() {
+            for pool in group.pools.iter_mut().flatten() {
+                pool.enabled = Some(false);
+            }
+        }
+        let groups = backend_config
+            .into_safe_mode()
+            .groups
+            .expect("BUG: missing groups");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].descriptor.name, "Disabled");
+
+        // configuration without pools stays without pools
+        assert!(Backend::default().into_safe_mode().groups.is_none());
+    }
+
+    #[test]
+    fn test_midstate_count() {
+        let backend_config = Backend::default();
+        assert_eq!(
+            hal::BackendConfig::midstate_count(&backend_config),
+            ASIC_BOOST_MIDSTATE_COUNT
+        );
+        assert_eq!(
+            hal::BackendConfig::supported_midstate_counts(&backend_config),
+            ASIC_BOOST_MIDSTATE_COUNTS.to_vec()
+        );
+
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [hash_chain_global]
+            midstate_count = 2
+            "#,
+        )
+        .expect("BUG: invalid config");
+        assert_eq!(hal::BackendConfig::midstate_count(&backend_config), 2);
+        assert!(backend_config.check_midstate_count().is_ok());
+
+        // Multiple midstates are not supported without AsicBoost
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [hash_chain_global]
+            asic_boost = false
+            midstate_count = 4
+            "#,
+        )
+        .expect("BUG: invalid config");
+        assert_eq!(
+            hal::BackendConfig::supported_midstate_counts(&backend_config),
+            vec![1]
+        );
+        assert!(backend_config.check_midstate_count().is_err());
+
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [hash_chain_global]
+            midstate_count = 3
+            "#,
+        )
+        .expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_err());
+    }
+
+    #[test]
+    fn test_expand_env() {
+        std::env::set_var("BOSMINER_TEST_WORKER", "worker1");
+        let mut backend_config: Backend = toml::from_str(
+            r#"
+            [[group]]
+            name = "Main"
+
+            [[group.pool]]
+            url = "stratum+tcp://pool.example.com"
+            user = "user.${BOSMINER_TEST_WORKER}"
+            password = "pa$$word"
+            "#,
+        )
+        .expect("BUG: invalid config");
+        backend_config
+            .expand_env()
+            .expect("BUG: cannot expand config");
+        let pool = &backend_config.groups.as_ref().expect("BUG: missing groups")[0]
+            .pools
+            .as_ref()
+            .expect("BUG: missing pools")[0];
+        assert_eq!(pool.user, "user.worker1");
+        assert_eq!(pool.password.as_deref(), Some("pa$word"));
+
+        let mut backend_config: Backend = toml::from_str(
+            r#"
+            [[group]]
+            name = "Main"
+
+            [[group.pool]]
+            url = "stratum+tcp://pool.example.com"
+            user = "user.${BOSMINER_TEST_MISSING}"
+            "#,
+        )
+        .expect("BUG: invalid config");
+        assert_eq!(
+            backend_config.expand_env(),
+            Err(
+                "undefined environment variable 'BOSMINER_TEST_MISSING' in 'user' of pool \
+                 'stratum+tcp://pool.example.com'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_pool_difficulty() {
+        let parse_pool = |options: &str| {
+            let backend_config: Backend = toml::from_str(&format!(
+                r#"
+                [[group]]
+                name = "Main"
+
+                [[group.pool]]
+                url = "stratum+tcp://pool.example.com"
+                user = "user"
+                {}
+                "#,
+                options
+            ))
+            .expect("BUG: invalid config");
+            let sanity = backend_config.sanity_check();
+            let pool = backend_config.groups.expect("BUG: missing groups")[0]
+                .pools
+                .clone()
+                .expect("BUG: missing pools")
+                .remove(0);
+            (pool, sanity)
+        };
+
+        let (pool, sanity) = parse_pool("");
+        assert!(sanity.is_ok());
+        assert_eq!(pool.min_difficulty, None);
+        assert_eq!(pool.fixed_difficulty, None);
+
+        let (pool, sanity) = parse_pool("min_difficulty = 1024\nfixed_difficulty = 4096");
+        assert!(sanity.is_ok());
+        assert_eq!(pool.min_difficulty, Some(1024));
+        assert_eq!(pool.fixed_difficulty, Some(4096));
+
+        assert!(parse_pool("min_difficulty = 0").1.is_err());
+        assert!(parse_pool("fixed_difficulty = 0").1.is_err());
+        assert!(parse_pool("min_difficulty = 4096\nfixed_difficulty = 1024")
+            .1
+            .is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let raw: toml::Value = r#"
+            [format]
+            version = "1.0"
+            model = "Antminer S9"
+            generatr = "manual"
+
+            [hash_chain_global]
+            asic_boost = true
+        "#
+        .parse()
+        .expect("BUG: invalid TOML");
+        let config: FormatWrapper<Backend> = raw.clone().try_into().expect("BUG: invalid config");
+        let known = toml::Value::try_from(&config).expect("BUG: cannot serialize");
+
+        let mut keys = vec![];
+        collect_unknown_keys(&raw, Some(&known), "", &mut keys);
+        assert_eq!(keys, vec!["format.generatr".to_string()]);
+
+        // unknown keys in the body are not ignored but rejected
+        let raw = raw.to_string().replace("asic_boost", "asic_bost");
+        assert!(toml::from_str::<FormatWrapper<Backend>>(&raw).is_err());
+    }
+
+    #[test]
+    fn test_set_group_quota() {
+        let mut backend_config: Backend = toml::from_str(TEST_CONFIG).expect("BUG: invalid config");
+        backend_config
+            .set_group_quota("Main", 3)
+            .expect("BUG: cannot set quota");
+        assert!(backend_config.set_group_quota("Missing", 3).is_err());
+
+        let groups = backend_config.groups.as_ref().expect("BUG: missing groups");
+        assert_eq!(
+            groups[0].descriptor.get_quota(),
+            Some(GroupDescriptor::DEFAULT_QUOTA)
+        );
+        assert_eq!(groups[1].descriptor.get_quota(), Some(3));
+        // failover settings are kept untouched
+        let failover = groups[1].descriptor.get_failover();
+        assert_eq!(failover.timeout(), Duration::from_secs(60));
+        assert_eq!(
+            failover.hold_down(),
+            Duration::from_secs(bosminer_config::GroupFailover::DEFAULT_HOLD_DOWN)
+        );
+        // quota is persisted in the configuration file
+        let content = toml::to_string_pretty(&backend_config).expect("BUG: cannot serialize");
+        assert!(content.contains("quota = 3"));
+    }
+
+    #[test]
+    fn test_set_asic_boost() {
+        let mut backend_config: Backend = toml::from_str(TEST_CONFIG).expect("BUG: invalid config");
+        backend_config
+            .hash_chain_global
+            .get_or_insert_with(|| Default::default())
+            .midstate_count
+            .replace(2);
+        assert_eq!(backend_config.midstate_count(), 2);
+
+        backend_config.set_asic_boost(false);
+        assert_eq!(backend_config.midstate_count(), 1);
+        backend_config.set_asic_boost(true);
+        assert_eq!(backend_config.midstate_count(), ASIC_BOOST_MIDSTATE_COUNT);
+        // the setting is persisted in the configuration file
+        let content = toml::to_string_pretty(&backend_config).expect("BUG: cannot serialize");
+        assert!(content.contains("asic_boost = true"));
+        assert!(!content.contains("midstate_count"));
+    }
+
+    #[test]
+    fn test_hash_chain_override() {
+        assert_eq!(parse_hash_chain_value("6:650"), Ok((6, 650.0)));
+        assert_eq!(parse_hash_chain_value("8: 8.9"), Ok((8, 8.9)));
+        for invalid in &["650", "x:650", "5:650", "9:650", "7:fast"] {
+            assert!(parse_hash_chain_value(invalid).is_err(), "{}", invalid);
+        }
+
+        let mut backend_config: Backend = toml::from_str(TEST_CONFIG).expect("BUG: invalid config");
+        let chain_6 = backend_config.resolve_chain_config(6);
+        let chain_8 = backend_config.resolve_chain_config(8);
+        backend_config.hash_chain_mut(7).frequency.replace(637.5);
+        backend_config.hash_chain_mut(8).voltage.replace(8.9);
+        assert!(backend_config.sanity_check().is_ok());
+
+        // Hash chain without override is untouched
+        let chain_config = backend_config.resolve_chain_config(6);
+        assert_eq!(chain_config.frequency.avg(), chain_6.frequency.avg());
+        assert_eq!(
+            chain_config.voltage.as_pic_value(),
+            chain_6.voltage.as_pic_value()
+        );
+        // Override wins over the value from the configuration file
+        let chain_config = backend_config.resolve_chain_config(7);
+        assert_eq!(chain_config.frequency.avg(), 637_500_000);
+        // Other settings of overridden hash chain are kept
+        let chain_config = backend_config.resolve_chain_config(8);
+        assert_eq!(chain_config.frequency.avg(), chain_8.frequency.avg());
+        assert_eq!(
+            chain_config.voltage.as_pic_value(),
+            power::Voltage::from_volts(8.9)
+                .expect("BUG: invalid voltage")
+                .as_pic_value()
+        );
+        assert_ne!(
+            chain_config.voltage.as_pic_value(),
+            chain_8.voltage.as_pic_value()
+        );
+    }
+
+    #[test]
+    fn test_realtime_io_config() {
+        let io_config = Backend::default().resolve_realtime_io();
+        assert!(io_config.enabled);
+        assert!(!io_config.sched_fifo);
+        assert_eq!(
+            io_config.latency_budget,
+            Duration::from_millis(DEFAULT_REALTIME_IO_LATENCY_BUDGET_MS)
+        );
+
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [realtime_io]
+            sched_fifo = true
+            priority = 50
+            latency_budget = 20
+        "#,
+        )
+        .expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_ok());
+        let io_config = backend_config.resolve_chain_config(S9_HASHBOARD_INDEX).io;
+        assert!(io_config.enabled);
+        assert!(io_config.sched_fifo);
+        assert_eq!(io_config.priority, 50);
+        assert_eq!(io_config.latency_budget, Duration::from_millis(20));
+
+        for invalid in &["priority = 0", "priority = 100", "latency_budget = 0"] {
+            let backend_config: Backend = toml::from_str(&format!("[realtime_io]\n{}", invalid))
+                .expect("BUG: invalid config");
+            assert!(backend_config.sanity_check().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_button_config() {
+        let button_config = Backend::default().resolve_button();
+        assert!(button_config.enabled);
+        assert_eq!(button_config.gpio_path, None);
+        assert_eq!(
+            button_config.factory_config_path,
+            Path::new(button::DEFAULT_FACTORY_CONFIG_PATH)
+        );
+        assert_eq!(button_config.long_press, button::DEFAULT_LONG_PRESS);
+
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [button]
+            gpio_path = "/sys/class/gpio/ip-report"
+            factory_config = "/etc/bosminer.toml.factory"
+            interface = "eth1"
+            long_press = 5
+        "#,
+        )
+        .expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_ok());
+        let button_config = backend_config.resolve_button();
+        assert_eq!(
+            button_config.gpio_path,
+            Some("/sys/class/gpio/ip-report".into())
+        );
+        assert_eq!(
+            button_config.factory_config_path,
+            Path::new("/etc/bosminer.toml.factory")
+        );
+        assert_eq!(button_config.interface, "eth1");
+        assert_eq!(button_config.long_press, Duration::from_secs(5));
+
+        let backend_config: Backend =
+            toml::from_str("[button]\nlong_press = 0").expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_err());
+    }
+
+    #[test]
+    fn test_shutdown_grace_period() {
+        assert_eq!(
+            Backend::default().shutdown_grace_period(),
+            shutdown::DEFAULT_GRACE_PERIOD
+        );
+
+        let backend_config: Backend =
+            toml::from_str("shutdown_grace_period = 10").expect("BUG: invalid config");
+        assert_eq!(
+            backend_config.shutdown_grace_period(),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn test_solution_routing_config() {
+        assert_eq!(
+            Backend::default().solution_routing(),
+            SolutionRoutingPolicy::Drop
+        );
+
+        let backend_config: Backend = toml::from_str("solution_routing = \"reroute_same_group\"")
+            .expect("BUG: invalid config");
+        assert_eq!(
+            backend_config.solution_routing(),
+            SolutionRoutingPolicy::RerouteSameGroup
+        );
+        assert!(toml::from_str::<Backend>("solution_routing = \"reroute\"").is_err());
+    }
+
+    #[test]
+    fn test_assignment_max_age_config() {
+        let backend_config = Backend::default();
+        assert_eq!(backend_config.assignment_max_age(), None);
+        assert_eq!(
+            backend_config.resolve_chain_config(6).assignment_max_age,
+            None
+        );
+
+        let backend_config: Backend = toml::from_str(
+            "[hash_chain_global]\nassignment_max_age = 500\n\
+             [hash_chain.7]\nassignment_max_age = 200",
+        )
+        .expect("BUG: invalid config");
+        assert_eq!(
+            backend_config.assignment_max_age(),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            backend_config.resolve_chain_config(6).assignment_max_age,
+            Some(Duration::from_millis(500))
+        );
+        // per-chain override wins over the global value
+        assert_eq!(
+            backend_config.resolve_chain_config(7).assignment_max_age,
+            Some(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn test_fan_out_config() {
+        assert_eq!(Backend::default().fan_out(), None);
+
+        let backend_config: Backend =
+            toml::from_str("[fan_out]\nspacing = 200").expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_ok());
+        let fan_out = backend_config.fan_out().expect("BUG: missing fan-out");
+        assert_eq!(fan_out.batch_size(), FanOutConfig::DEFAULT_BATCH_SIZE);
+        assert_eq!(fan_out.spacing(), 200);
+
+        for invalid in &["batch_size = 0", "spacing = 1000000"] {
+            let backend_config: Backend =
+                toml::from_str(&format!("[fan_out]\n{}", invalid)).expect("BUG: invalid config");
+            assert!(backend_config.sanity_check().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_failover_health_config() {
+        let group = "[[group]]\nname = \"Main\"\n[group.failover.health]\n";
+        let backend_config: Backend =
+            toml::from_str(&format!("{}job_timeout = 120\nlatency_weight = 0", group))
+                .expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_ok());
+        let groups = backend_config.groups.as_ref().expect("BUG: missing groups");
+        let health = groups[0].descriptor.get_failover().health();
+        assert_eq!(health.job_timeout(), Duration::from_secs(120));
+        assert_eq!(health.latency_weight(), 0.0);
+        assert_eq!(
+            health.min_score(),
+            bosminer_config::GroupFailoverHealth::DEFAULT_MIN_SCORE
+        );
+
+        for invalid in &[
+            "job_weight = -1.0",
+            "job_weight = 0\nlatency_weight = 0\nreject_weight = 0\nauthorization_weight = 0",
+            "reject_limit = 0.0",
+            "min_score = 1.5",
+            "job_timeout = 0",
+        ] {
+            let backend_config: Backend =
+                toml::from_str(&format!("{}{}", group, invalid)).expect("BUG: invalid config");
+            assert!(backend_config.sanity_check().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_api_config() {
+        use ii_cgminer_api::access::{AllowList, Privilege};
+
+        let api = Backend::default().api.unwrap_or_default();
+        assert_eq!(api.listen_addr(), "0.0.0.0:4028".parse().unwrap());
+        assert_eq!(api.allow_list(), AllowList::local_write());
+
+        let backend_config: Backend = toml::from_str(
+            r#"
+            [api]
+            host = "127.0.0.1"
+            port = 4029
+            allow = ["127.0.0.1", "::1", "W:10.0.0.0/24"]
+            "#,
+        )
+        .expect("BUG: invalid config");
+        assert!(backend_config.sanity_check().is_ok());
+        let api = backend_config.api.expect("BUG: missing API config");
+        assert_eq!(api.listen_addr(), "127.0.0.1:4029".parse().unwrap());
+        let allow_list = api.allow_list();
+        assert_eq!(
+            allow_list.privilege("127.0.0.1".parse().unwrap()),
+            Some(Privilege::Read)
+        );
+        assert_eq!(
+            allow_list.privilege("::1".parse().unwrap()),
+            Some(Privilege::Read)
+        );
+        assert_eq!(
+            allow_list.privilege("10.0.0.42".parse().unwrap()),
+            Some(Privilege::Write)
+        );
+        assert_eq!(allow_list.privilege("10.0.1.1".parse().unwrap()), None);
+
+        for invalid in &["port = 0", "allow = []"] {
+            let backend_config: Backend =
+                toml::from_str(&format!("[api]\n{}", invalid)).expect("BUG: invalid config");
+            assert!(backend_config.sanity_check().is_err(), "{}", invalid);
+        }
+        for invalid in &[
+            "host = \"localhost\"",
+            "allow = [\"localhost\"]",
+            "allow = [\"10.0.0.0/33\"]",
+            "allow = [\"X:10.0.0.1\"]",
+        ] {
+            assert!(
+                toml::from_str::<Backend>(&format!("[api]\n{}", invalid)).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let parse = |config: &str| -> toml::Value {
+            let backend_config: Backend = toml::from_str(config).expect("BUG: invalid config");
+            toml::Value::try_from(backend_config).expect("BUG: cannot serialize config")
+        };
+        let running = parse(
+            r#"
+            network = "mainnet"
+
+            [hash_chain.6]
+            frequency = 600.0
+
+            [[group]]
+            name = "A"
+
+            [[group.pool]]
+            url = "stratum+tcp://a.example.com"
+            user = "a"
+            "#,
+        );
+        assert!(diff(&running, &running).is_empty());
+
+        // Only pools changed
+        let new = parse(
+            r#"
+            network = "mainnet"
+
+            [hash_chain.6]
+            frequency = 600.0
+
+            [[group]]
+            name = "A"
+
+            [[group.pool]]
+            url = "stratum+tcp://b.example.com"
+            user = "b"
+            "#,
+        );
+        assert_eq!(
+            diff(&running, &new),
+            vec![Change {
+                key: "group".to_string(),
+                apply: Apply::Live
+            }]
+        );
+
+        // Pools together with hash chain and global settings
+        let new = parse(
+            r#"
+            network = "mainnet"
+            shutdown_grace_period = 10
+
+            [hash_chain.6]
+            frequency = 650.0
+            "#,
+        );
+        assert_eq!(
+            diff(&running, &new),
+            vec![
+                Change {
+                    key: "group".to_string(),
+                    apply: Apply::Live
+                },
+                Change {
+                    key: "hash_chain.6.frequency".to_string(),
+                    apply: Apply::Restart
+                },
+                Change {
+                    key: "shutdown_grace_period".to_string(),
+                    apply: Apply::Restart
+                },
+            ]
+        );
+    }
 }
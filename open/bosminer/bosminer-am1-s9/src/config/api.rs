@@ -59,6 +59,7 @@ pub enum StatusCode {
     MissingFile = 2,
     InvalidFormat = 3,
     IncompatibleFormatVersion = 4,
+    AccessDenied = 5,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -80,6 +81,11 @@ impl Status {
     }
 }
 
+#[derive(Serialize, Clone, Debug)]
+struct ErrorResponse {
+    pub status: Status,
+}
+
 #[derive(Serialize, Clone, Debug)]
 struct MetadataResponse {
     pub status: Status,
@@ -103,6 +109,22 @@ struct SaveRequest {
 struct SaveSuccess {
     pub path: String,
     pub format: Format,
+    /// Settings changed by the save together with the way they are applied
+    pub changes: Vec<Change>,
+    /// At least one of the changes is applied only after restart of the miner. Otherwise the
+    /// miner only has to reload its configuration file.
+    pub restart_required: bool,
+}
+
+impl SaveSuccess {
+    fn new(path: String, format: Format, changes: Vec<Change>) -> Self {
+        Self {
+            path,
+            format,
+            restart_required: changes.iter().any(|change| change.apply == Apply::Restart),
+            changes,
+        }
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -176,105 +198,358 @@ impl<'a> Drop for FileGuard<'a> {
     }
 }
 
-pub struct Handler<'a> {
-    config_path: &'a str,
-    // TODO: consider phantomdata to include `ConfigBody` type in this type
+/// Write the `config` atomically to the `config_path` through temporary file
+pub fn save<B: ConfigBody>(config: &FormatWrapper<B>, config_path: &Path) -> io::Result<()> {
+    let config_tmp_path = config_path.with_extension(CONFIG_TMP_EXTENSION);
+    let mut file = FileGuard::create(&config_tmp_path)?;
+
+    let content = toml::to_string_pretty(config)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    file.write_all(content.as_bytes())?;
+
+    file.persist(config_path)
 }
 
-impl<'a> Handler<'a> {
-    pub const CONFIG_TMP_EXTENSION: &'static str = "toml.part";
+/// Save configuration changed by the running miner. The format is stamped with the generator and
+/// time of the change the same way as configuration saved through the API.
+pub fn save_runtime_change<B: ConfigBody>(
+    config: &mut FormatWrapper<B>,
+    config_path: &Path,
+) -> io::Result<()> {
+    config.format.generator = generator_string::<B>().into();
+    config.format.timestamp = UnixTime::now().into();
+    save(config, config_path)
+}
 
-    pub fn new(config_path: &'a str) -> Self {
-        Self { config_path }
+/// Compute changes of the `new` configuration body against the one stored at `config_path`
+fn config_changes<B: ConfigBody>(config_path: &str, new: &B) -> Vec<Change> {
+    let old = match FormatWrapper::<B>::parse(config_path) {
+        Ok(config) | Err(FormatWrapperError::IncompatibleVersion(_, Some(config))) => {
+            toml::Value::try_from(&config.body).ok()
+        }
+        Err(_) => None,
+    };
+    let new = toml::Value::try_from(new).expect("BUG: cannot serialize configuration");
+    diff(
+        &old.unwrap_or_else(|| toml::Value::Table(Default::default())),
+        &new,
+    )
+}
+
+/// Extension of the temporary file used for atomic write of the configuration
+pub const CONFIG_TMP_EXTENSION: &'static str = "toml.part";
+
+/// Configuration request which is executed by a transport
+#[derive(Serialize, Deserialize, Eq, PartialEq, Copy, Clone, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    Metadata,
+    Data,
+    Save,
+}
+
+/// Executes configuration requests either directly on the configuration file or in a remote
+/// miner. The returned response is always complete and errors are reported in its status.
+pub trait Transport {
+    fn execute<B: ConfigBody>(
+        &mut self,
+        command: Command,
+        data: Option<serde_json::Value>,
+    ) -> serde_json::Value;
+}
+
+fn to_response<T: Serialize>(response: T) -> serde_json::Value {
+    serde_json::to_value(response).expect("BUG: cannot serialize response")
+}
+
+/// Response without any data which only reports the error `code` together with its `message`
+pub fn error_response<B: ConfigBody>(code: StatusCode, message: String) -> serde_json::Value {
+    to_response(ErrorResponse {
+        status: Status::new::<_, B>(code, message),
+    })
+}
+
+pub fn metadata_response<B: ConfigBody>() -> serde_json::Value {
+    to_response(MetadataResponse {
+        status: Status::new::<_, B>(StatusCode::Success, None),
+        data: FormatWrapper::<B>::metadata(),
+    })
+}
+
+pub fn data_response<B: ConfigBody>(config_path: &str) -> serde_json::Value {
+    match FormatWrapper::<B>::parse(config_path) {
+        // TODO: Improve error handling
+        Ok(config) | Err(FormatWrapperError::IncompatibleVersion(_, Some(config))) => {
+            to_response(DataResponse {
+                status: Status::new::<_, B>(StatusCode::Success, None),
+                data: Some(config),
+            })
+        }
+        Err(e) => error_response::<B>(StatusCode::InvalidFormat, format!("{}", e)),
     }
+}
 
-    fn send_response<T>(self, response: T)
-    where
-        T: Serialize,
-    {
-        serde_json::to_writer(io::stdout(), &response).expect("BUG: cannot serialize response");
+/// Stamp configuration `data` received in the save request with the current format and check
+/// that it can be used by the miner. Returns the configuration together with its changes against
+/// the configuration stored at `config_path` or the error response.
+pub fn prepare_save<B: ConfigBody>(
+    config_path: &str,
+    data: Option<serde_json::Value>,
+) -> Result<(FormatWrapper<B>, Vec<Change>), serde_json::Value> {
+    let invalid_format = |message| Err(error_response::<B>(StatusCode::InvalidFormat, message));
+
+    let mut data = match data {
+        Some(data @ serde_json::Value::Object(_)) => data,
+        _ => return invalid_format("configuration data have to be an object".to_string()),
+    };
+    let config_format = Format {
+        generator: generator_string::<B>().into(),
+        timestamp: UnixTime::now().into(),
+        version: B::version(),
+        model: B::model(),
+    };
+    let json_format = serde_json::to_value(config_format).expect("BUG: cannot serialize Format");
+    data.as_object_mut()
+        .expect("BUG: configuration data are not an object")
+        .insert("format".to_string(), json_format);
+
+    let mut config: FormatWrapper<B> = match serde_json::from_value(data) {
+        Ok(config) => config,
+        Err(e) => return invalid_format(format!("invalid configuration: {}", e)),
+    };
+    if let Err(e) = config.sanity_check() {
+        return invalid_format(format!("invalid configuration: {}", e));
     }
 
-    pub fn handle_metadata<B: ConfigBody>(self) {
-        let metadata = FormatWrapper::<B>::metadata();
+    let changes = config_changes(config_path, &config.body);
+    Ok((config, changes))
+}
 
-        let response = MetadataResponse {
-            status: Status::new::<_, B>(StatusCode::Success, None),
-            data: metadata,
-        };
+/// Successful response to the save request after the configuration has been written to
+/// `config_path`
+pub fn save_response<B: ConfigBody>(
+    config_path: &Path,
+    format: Format,
+    changes: Vec<Change>,
+) -> serde_json::Value {
+    let path = match config_path.canonicalize() {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(e) => {
+            return error_response::<B>(
+                StatusCode::SystemError,
+                format!("cannot resolve configuration path: {}", e),
+            )
+        }
+    };
+    to_response(SaveResponse {
+        status: Status::new::<_, B>(StatusCode::Success, None),
+        data: Some(SaveSuccess::new(path, format, changes)),
+    })
+}
 
-        self.send_response(response);
+/// Transport which executes requests directly on the local configuration file
+pub struct Local<'a> {
+    config_path: &'a str,
+}
+
+impl<'a> Local<'a> {
+    pub fn new(config_path: &'a str) -> Self {
+        Self { config_path }
     }
+}
 
-    pub fn handle_data<B: ConfigBody>(self) {
-        let response = match FormatWrapper::<B>::parse(self.config_path) {
-            // TODO: Improve error handling
-            Ok(config)
-            | Err(crate::config::FormatWrapperError::IncompatibleVersion(_, Some(config))) => {
-                DataResponse {
-                    status: Status::new::<_, B>(StatusCode::Success, None),
-                    data: Some(config),
+impl<'a> Transport for Local<'a> {
+    fn execute<B: ConfigBody>(
+        &mut self,
+        command: Command,
+        data: Option<serde_json::Value>,
+    ) -> serde_json::Value {
+        match command {
+            Command::Metadata => metadata_response::<B>(),
+            Command::Data => data_response::<B>(self.config_path),
+            Command::Save => {
+                let (config, changes) = match prepare_save::<B>(self.config_path, data) {
+                    Ok(result) => result,
+                    Err(response) => return response,
+                };
+                let config_path = Path::new(self.config_path);
+                match save(&config, config_path) {
+                    Ok(_) => save_response::<B>(config_path, config.format, changes),
+                    Err(e) => error_response::<B>(
+                        StatusCode::SystemError,
+                        format!("cannot save configuration: {}", e),
+                    ),
                 }
             }
-            Err(e) => DataResponse {
-                status: Status::new::<_, B>(StatusCode::InvalidFormat, format!("{}", e)),
-                data: None,
-            },
-        };
+        }
+    }
+}
+
+/// Handles requests of the configuration backend API passed through stdin/stdout
+pub struct Handler<T> {
+    transport: T,
+}
 
+impl<T: Transport> Handler<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    fn send_response(self, response: serde_json::Value) {
+        serde_json::to_writer(io::stdout(), &response).expect("BUG: cannot serialize response");
+    }
+
+    pub fn handle_metadata<B: ConfigBody>(mut self) {
+        let response = self.transport.execute::<B>(Command::Metadata, None);
         self.send_response(response);
     }
 
-    pub fn handle_save<B: ConfigBody>(self) {
-        let mut request: SaveRequest =
-            serde_json::from_reader(io::stdin()).expect("TODO: deserialize SaveRequest");
+    pub fn handle_data<B: ConfigBody>(mut self) {
+        let response = self.transport.execute::<B>(Command::Data, None);
+        self.send_response(response);
+    }
 
-        let config_format = Format {
-            generator: generator_string::<B>().into(),
-            timestamp: UnixTime::now().into(),
-            version: B::version(),
-            model: B::model(),
+    pub fn handle_save<B: ConfigBody>(mut self) {
+        let response = match serde_json::from_reader::<_, SaveRequest>(io::stdin()) {
+            Ok(request) => self
+                .transport
+                .execute::<B>(Command::Save, Some(request.data)),
+            Err(e) => error_response::<B>(
+                StatusCode::InvalidFormat,
+                format!("invalid save request: {}", e),
+            ),
         };
+        self.send_response(response);
+    }
+}
 
-        let json_format =
-            serde_json::to_value(config_format).expect("BUG: cannot serialize Format");
-        request
-            .data
-            .as_object_mut()
-            .expect("TODO: invalid data type")
-            .insert("format".to_string(), json_format);
-
-        let mut config: FormatWrapper<B> =
-            serde_json::from_value(request.data).expect("TODO: deserialize Backend");
-        config.sanity_check().expect("TODO: invalid configuration");
-
-        let config_path = Path::new(self.config_path);
-        let config_tmp_path = config_path.with_extension(Self::CONFIG_TMP_EXTENSION);
-
-        let mut file = FileGuard::create(&config_tmp_path).expect("TODO: File::create");
-
-        file.write_all(
-            toml::to_string_pretty(&config)
-                .expect("TODO: toml::to_string_pretty")
-                .as_bytes(),
-        )
-        .expect("TODO: file.write_all");
-
-        file.persist(config_path).expect("TODO: file.persist");
-
-        let response = SaveResponse {
-            status: Status::new::<_, B>(StatusCode::Success, None),
-            data: Some(SaveSuccess {
-                path: config_path
-                    .canonicalize()
-                    .expect("TODO: path.canonicalize")
-                    .into_os_string()
-                    .into_string()
-                    .expect("TODO: into_os_string"),
-                format: config.format,
-            }),
-        };
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn wrap(body: &str) -> FormatWrapper<Backend> {
+        let mut config: FormatWrapper<Backend> = toml::from_str(&format!(
+            "[format]\nversion = '{}'\nmodel = '{}'\n{}",
+            FORMAT_VERSION, FORMAT_MODEL, body
+        ))
+        .expect("BUG: invalid config");
+        config.sanity_check().expect("BUG: invalid config");
+        config
+    }
 
-        self.send_response(response);
+    #[test]
+    fn test_save_changes() {
+        let config_path =
+            std::env::temp_dir().join(format!("bosminer-config-api-{}.toml", std::process::id()));
+        let old = wrap(
+            r#"
+            [hash_chain_global]
+            frequency = 600.0
+
+            [[group]]
+            name = "A"
+            "#,
+        );
+        save(&old, &config_path).expect("BUG: cannot save config");
+        let config_path_str = config_path.to_str().expect("BUG: invalid path");
+
+        // Only pool groups changed so the miner just reloads the configuration
+        let new = wrap(
+            r#"
+            [hash_chain_global]
+            frequency = 600.0
+
+            [[group]]
+            name = "B"
+            "#,
+        );
+        let success = SaveSuccess::new(
+            String::new(),
+            new.format.clone(),
+            config_changes(config_path_str, &new.body),
+        );
+        assert!(!success.restart_required);
+        let response = serde_json::to_value(&success).expect("BUG: cannot serialize response");
+        assert_eq!(
+            response["changes"],
+            serde_json::json!([{ "key": "group", "apply": "live" }])
+        );
+
+        // Hash chain change requires restart
+        let new = wrap(
+            r#"
+            [hash_chain_global]
+            frequency = 650.0
+
+            [[group]]
+            name = "B"
+            "#,
+        );
+        let success = SaveSuccess::new(
+            String::new(),
+            new.format.clone(),
+            config_changes(config_path_str, &new.body),
+        );
+        assert!(success.restart_required);
+        assert_eq!(
+            success.changes,
+            vec![
+                Change {
+                    key: "group".to_string(),
+                    apply: Apply::Live
+                },
+                Change {
+                    key: "hash_chain_global.frequency".to_string(),
+                    apply: Apply::Restart
+                },
+            ]
+        );
+
+        // Configuration which cannot be read is replaced entirely
+        fs::remove_file(&config_path).expect("BUG: cannot remove config");
+        assert!(
+            SaveSuccess::new(
+                String::new(),
+                new.format.clone(),
+                config_changes(config_path_str, &new.body),
+            )
+            .restart_required
+        );
+    }
+
+    #[test]
+    fn test_local_transport() {
+        let config_path =
+            std::env::temp_dir().join(format!("bosminer-config-local-{}.toml", std::process::id()));
+        let config_path_str = config_path.to_str().expect("BUG: invalid path");
+        let mut transport = Local::new(config_path_str);
+
+        let response = transport.execute::<Backend>(Command::Metadata, None);
+        assert_eq!(response["status"]["code"], StatusCode::Success as u32);
+
+        // Missing configuration file cannot be read
+        let response = transport.execute::<Backend>(Command::Data, None);
+        assert_eq!(response["status"]["code"], StatusCode::InvalidFormat as u32);
+        assert!(response.get("data").is_none());
+
+        let response = transport.execute::<Backend>(
+            Command::Save,
+            Some(serde_json::json!({ "group": [{ "name": "A" }] })),
+        );
+        assert_eq!(response["status"]["code"], StatusCode::Success as u32);
+        assert!(response["data"]["path"].is_string());
+
+        let response = transport.execute::<Backend>(Command::Data, None);
+        assert_eq!(response["status"]["code"], StatusCode::Success as u32);
+        assert_eq!(response["data"]["group"][0]["name"], "A");
+
+        // Invalid configuration is refused and the file is kept
+        let response =
+            transport.execute::<Backend>(Command::Save, Some(serde_json::json!({ "group": "A" })));
+        assert_eq!(response["status"]["code"], StatusCode::InvalidFormat as u32);
+        let response = transport.execute::<Backend>(Command::Data, None);
+        assert_eq!(response["data"]["group"][0]["name"], "A");
+
+        fs::remove_file(&config_path).expect("BUG: cannot remove config");
     }
 }
@@ -24,7 +24,7 @@
 
 use super::*;
 
-use bosminer_config::CLIENT_URL_JAVA_SCRIPT_REGEX;
+use bosminer_config::{ClientSharePolicy, CLIENT_URL_JAVA_SCRIPT_REGEX};
 
 const DESCRIPTION_CAUTION_OVERCLOCKING: &'static str =
     "Caution: Overclocking may damage your device. Proceed at your own risk!";
@@ -33,6 +33,10 @@ const DESCRIPTION_CAUTION_CHANGING_DEFAULT: &'static str =
      shutdown of the system or even irreversible hardware damage. Proceed at your own risk!";
 const DESCRIPTION_NUMBER_OF_FANS: &'static str =
     "Number of fans required for system to run. For immersion cooling, use the value '0'.";
+const DESCRIPTION_SHARE_POLICY: &'static str =
+    "Shares of jobs invalidated by the pool are submitted only within the grace period. Shares \
+     of jobs superseded by a job on a new block are submitted only when it is allowed. Shares \
+     with timestamp out of range are never submitted.";
 
 use serde_json::{self, json};
 
@@ -107,6 +111,7 @@ pub fn for_backend() -> serde_json::Value {
                             {
                                 "type": "number",
                                 "label": "Quota",
+                                "min": 0,
                                 "default": 1,
                                 "span": 3
                             }
@@ -171,6 +176,112 @@ pub fn for_backend() -> serde_json::Value {
                                                 "default": null,
                                                 "span": 5
                                             }
+                                        ],
+                                        [
+                                            "name",
+                                            {
+                                                "type": "string",
+                                                "label": "Name",
+                                                "default": null,
+                                                "span": 12
+                                            }
+                                        ],
+                                        [
+                                            "ca_cert",
+                                            {
+                                                "type": "string",
+                                                "label": "TLS CA Certificate File",
+                                                "default": null,
+                                                "span": 9
+                                            }
+                                        ],
+                                        [
+                                            "insecure",
+                                            {
+                                                "type": "bool",
+                                                "label": "Skip TLS Verification",
+                                                "default": false,
+                                                "span": 3
+                                            }
+                                        ],
+                                        [
+                                            "share_policy",
+                                            {
+                                                "type": "object",
+                                                "label": "Share Policy",
+                                                "description": DESCRIPTION_SHARE_POLICY,
+                                                "optional": true,
+                                                "fields": [
+                                                    [
+                                                        "grace_period",
+                                                        {
+                                                            "type": "number",
+                                                            "label": "Grace Period",
+                                                            "unit": "ms",
+                                                            "min": 0,
+                                                            "default": ClientSharePolicy::DEFAULT_GRACE_PERIOD,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "submit_clean_jobs",
+                                                        {
+                                                            "type": "bool",
+                                                            "label": "Submit Shares of Clean Jobs",
+                                                            "default": ClientSharePolicy::DEFAULT_SUBMIT_CLEAN_JOBS,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "submit_stale",
+                                                        {
+                                                            "type": "bool",
+                                                            "label": "Submit Stale Shares",
+                                                            "default": ClientSharePolicy::DEFAULT_SUBMIT_STALE,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "max_ntime_delta",
+                                                        {
+                                                            "type": "number",
+                                                            "label": "Maximal nTime Delta",
+                                                            "unit": "s",
+                                                            "min": 0,
+                                                            "default": ClientSharePolicy::DEFAULT_MAX_NTIME_DELTA,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "submit_old_difficulty",
+                                                        {
+                                                            "type": "bool",
+                                                            "label": "Submit Shares of Old Difficulty",
+                                                            "default": ClientSharePolicy::DEFAULT_SUBMIT_OLD_DIFFICULTY,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "duplicate_window",
+                                                        {
+                                                            "type": "number",
+                                                            "label": "Duplicate Share Window",
+                                                            "min": 0,
+                                                            "default": ClientSharePolicy::DEFAULT_DUPLICATE_WINDOW,
+                                                            "span": 3
+                                                        }
+                                                    ],
+                                                    [
+                                                        "accept_foreign",
+                                                        {
+                                                            "type": "bool",
+                                                            "label": "Accept Shares of Removed Pools",
+                                                            "default": ClientSharePolicy::DEFAULT_ACCEPT_FOREIGN,
+                                                            "span": 3
+                                                        }
+                                                    ]
+                                                ]
+                                            }
                                         ]
                                     ]
                                 }
@@ -0,0 +1,648 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Execution of configuration backend API requests in the running miner. The miner listens on
+//! a local Unix socket which can be forwarded over SSH so that a provisioning host is able to
+//! configure many miners centrally.
+//!
+//! Each connection carries exactly one request followed by one response. Both are JSON
+//! documents prefixed with their length encoded as 32-bit big-endian integer. The request has to
+//! contain the token stored in a file on the miner otherwise it is refused.
+
+use ii_logging::macros::*;
+
+use super::api::{self, Command, StatusCode, Transport};
+use super::{Backend, ConfigBody, FormatWrapper, FormatWrapperError};
+use crate::reload;
+
+use serde::{Deserialize, Serialize};
+
+use ii_async_compat::tokio;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default path of the socket on which the running miner accepts configuration requests
+pub const DEFAULT_SOCKET_PATH: &str = "/var/run/bosminer/config.sock";
+
+/// Default path of the file with the token shared by the miner and its remote callers
+pub const DEFAULT_TOKEN_PATH: &str = "/etc/bosminer-config.token";
+
+/// Frames larger than this are refused before any buffer is allocated for them
+pub const MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Timeout for connecting to the miner and for each read/write on the connection. Saving
+/// includes reload of pools so it has to be generous.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Extension of the configuration file staged by the first phase of the save
+const STAGED_EXTENSION: &str = "staged.toml";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct Request {
+    token: String,
+    command: Command,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
+fn encode_frame<T: Serialize>(message: &T) -> io::Result<Vec<u8>> {
+    let payload = serde_json::to_vec(message)?;
+    if payload.len() > MAX_FRAME_SIZE {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds the limit", payload.len()),
+        ))?;
+    }
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+fn frame_length(header: [u8; 4]) -> io::Result<usize> {
+    let length = u32::from_be_bytes(header) as usize;
+    if length > MAX_FRAME_SIZE {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds the limit", length),
+        ))
+    } else {
+        Ok(length)
+    }
+}
+
+/// Read payload of one frame from blocking `reader`
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header)?;
+    let mut payload = vec![0u8; frame_length(header)?];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// Read payload of one frame from asynchronous `reader`
+async fn read_frame_async<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header).await?;
+    let mut payload = vec![0u8; frame_length(header)?];
+    reader.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+/// Read the shared token. Surrounding whitespace is ignored and an empty token is refused.
+pub fn read_token<P: AsRef<Path>>(path: P) -> Result<String, String> {
+    let path = path.as_ref();
+    let token = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read token '{}': {}", path.display(), e))?;
+    let token = token.trim();
+    if token.is_empty() {
+        Err(format!("token '{}' is empty", path.display()))?;
+    }
+    Ok(token.to_string())
+}
+
+/// Compare tokens in constant time so the response time does not reveal matching prefix
+fn token_matches(expected: &str, token: &str) -> bool {
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |result, (a, b)| result | (a ^ b))
+            == 0
+}
+
+/// Address of the remote miner which is either path to its Unix socket or `HOST:PORT` of the
+/// socket forwarded over TCP (e.g. with `ssh -L`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl Address {
+    pub fn parse(address: &str) -> Self {
+        if address.contains(':') && !address.contains('/') {
+            Address::Tcp(address.to_string())
+        } else {
+            Address::Unix(address.into())
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Unix(path) => write!(f, "{}", path.display()),
+            Address::Tcp(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
+/// Transport which forwards requests to the running miner
+pub struct Client {
+    address: Address,
+    token_path: PathBuf,
+}
+
+impl Client {
+    pub fn new<P: Into<PathBuf>>(address: Address, token_path: P) -> Self {
+        Self {
+            address,
+            token_path: token_path.into(),
+        }
+    }
+
+    fn connect(&self) -> io::Result<Box<dyn Stream>> {
+        match &self.address {
+            Address::Unix(path) => {
+                let stream = StdUnixStream::connect(path)?;
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+            Address::Tcp(address) => {
+                let addr = address
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address found"))?;
+                let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+
+    fn request(
+        &self,
+        command: Command,
+        data: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let request = Request {
+            token: read_token(&self.token_path)?,
+            command,
+            data,
+        };
+        let frame = encode_frame(&request).map_err(|e| format!("invalid request: {}", e))?;
+
+        let mut stream = self
+            .connect()
+            .map_err(|e| format!("cannot connect to {}: {}", self.address, e))?;
+        let io_error = |e| format!("request to {} failed: {}", self.address, e);
+        stream.write_all(&frame).map_err(io_error)?;
+        stream.flush().map_err(io_error)?;
+        let payload = read_frame(&mut stream).map_err(io_error)?;
+
+        serde_json::from_slice(&payload)
+            .map_err(|e| format!("invalid response from {}: {}", self.address, e))
+    }
+}
+
+impl Transport for Client {
+    fn execute<B: ConfigBody>(
+        &mut self,
+        command: Command,
+        data: Option<serde_json::Value>,
+    ) -> serde_json::Value {
+        // Responses of the miner are passed through unchanged so errors of the transport itself
+        // are the only ones reported here
+        self.request(command, data)
+            .unwrap_or_else(|e| api::error_response::<B>(StatusCode::SystemError, e))
+    }
+}
+
+/// Load the staged configuration the same way as the miner does on start
+fn check_staged(staged_path: &Path) -> Result<(), String> {
+    let staged_path = staged_path
+        .to_str()
+        .ok_or("invalid path of staged configuration")?;
+    let mut config = match FormatWrapper::<Backend>::parse(staged_path) {
+        Ok(config) | Err(FormatWrapperError::IncompatibleVersion(_, Some(config))) => config,
+        Err(e) => Err(e.to_string())?,
+    };
+    config.body.expand_env()
+}
+
+/// Executes requests of remote callers in the running miner. Requests are handled one by one so
+/// concurrent saves cannot interleave.
+pub struct Server {
+    listener: UnixListener,
+    config_path: String,
+    token: String,
+    /// Applies pools from the saved configuration without restart
+    reloader: Option<reload::Reloader>,
+}
+
+impl Server {
+    /// Listen on `socket_path`. The server cannot be created without the token because access
+    /// to the configuration is never allowed without authentication.
+    pub fn bind<P, Q>(
+        socket_path: P,
+        token_path: Q,
+        config_path: String,
+        reloader: Option<reload::Reloader>,
+    ) -> Result<Self, String>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let token = read_token(token_path)?;
+        let socket_path = socket_path.as_ref();
+        let io_error = |e| format!("cannot listen on '{}': {}", socket_path.display(), e);
+
+        if let Some(socket_dir) = socket_path.parent() {
+            fs::create_dir_all(socket_dir).map_err(io_error)?;
+        }
+        // Remove stale socket left by the previous instance of the miner
+        match fs::remove_file(socket_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(io_error(e))?,
+            _ => {}
+        }
+        let listener = UnixListener::bind(socket_path).map_err(io_error)?;
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o600)).map_err(io_error)?;
+
+        Ok(Self {
+            listener,
+            config_path,
+            token,
+            reloader,
+        })
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            let stream = match self.listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Cannot accept remote configuration request: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.handle_connection(stream).await {
+                warn!("Remote configuration request failed: {}", e);
+            }
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: UnixStream) -> io::Result<()> {
+        // Stalled caller must not block the following requests
+        let payload = tokio::time::timeout(TIMEOUT, read_frame_async(&mut stream))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "request timed out"))??;
+        let response = match serde_json::from_slice::<Request>(&payload) {
+            Ok(request) => self.handle_request(request).await,
+            Err(e) => api::error_response::<Backend>(
+                StatusCode::InvalidFormat,
+                format!("invalid request: {}", e),
+            ),
+        };
+        stream.write_all(&encode_frame(&response)?).await
+    }
+
+    async fn handle_request(&self, request: Request) -> serde_json::Value {
+        if !token_matches(&self.token, &request.token) {
+            warn!("Remote configuration request with invalid token has been refused");
+            return api::error_response::<Backend>(
+                StatusCode::AccessDenied,
+                "invalid token".to_string(),
+            );
+        }
+        match request.command {
+            Command::Metadata => api::metadata_response::<Backend>(),
+            Command::Data => api::data_response::<Backend>(&self.config_path),
+            Command::Save => self.handle_save(request.data).await,
+        }
+    }
+
+    /// Save the configuration in two phases. The configuration is staged next to the current
+    /// one and loaded back first. Only then it replaces the current file and pools are reloaded.
+    /// The previous file is restored when the reload fails.
+    async fn handle_save(&self, data: Option<serde_json::Value>) -> serde_json::Value {
+        let system_error =
+            |message| api::error_response::<Backend>(StatusCode::SystemError, message);
+
+        let (config, changes) = match api::prepare_save::<Backend>(&self.config_path, data) {
+            Ok(result) => result,
+            Err(response) => return response,
+        };
+        let config_path = Path::new(&self.config_path);
+        let staged_path = config_path.with_extension(STAGED_EXTENSION);
+
+        // Phase 1: stage the configuration and check that the miner is able to load it
+        if let Err(e) = api::save(&config, &staged_path) {
+            return system_error(format!("cannot stage configuration: {}", e));
+        }
+        if let Err(e) = check_staged(&staged_path) {
+            let _ = fs::remove_file(&staged_path);
+            return api::error_response::<Backend>(
+                StatusCode::InvalidFormat,
+                format!("configuration cannot be loaded: {}", e),
+            );
+        }
+
+        // Phase 2: replace the current configuration and apply it
+        let previous = fs::read(config_path).ok();
+        if let Err(e) = fs::rename(&staged_path, config_path) {
+            let _ = fs::remove_file(&staged_path);
+            return system_error(format!("cannot replace configuration: {}", e));
+        }
+        if let Some(reloader) = &self.reloader {
+            if let Err(e) = reloader.reload().await {
+                let message = match self.restore(previous) {
+                    Ok(_) => {
+                        // Put back pools of the previous configuration
+                        let _ = reloader.reload().await;
+                        format!(
+                            "cannot apply configuration: {} (previous configuration restored)",
+                            e
+                        )
+                    }
+                    Err(restore_e) => format!(
+                        "cannot apply configuration: {} (restore of previous one failed: {})",
+                        e, restore_e
+                    ),
+                };
+                error!("Remote configuration save failed: {}", message);
+                return system_error(message);
+            }
+        }
+
+        info!("Configuration has been saved by remote request");
+        api::save_response::<Backend>(config_path, config.format, changes)
+    }
+
+    /// Return the configuration file to the state before the save
+    fn restore(&self, previous: Option<Vec<u8>>) -> io::Result<()> {
+        let config_path = Path::new(&self.config_path);
+        match previous {
+            Some(content) => {
+                let staged_path = config_path.with_extension(STAGED_EXTENSION);
+                fs::write(&staged_path, content)?;
+                fs::rename(&staged_path, config_path)
+            }
+            None => fs::remove_file(config_path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::net::TcpListener;
+    use std::os::unix::net::UnixListener as StdUnixListener;
+    use std::thread;
+
+    const TOKEN: &str = "secret";
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bosminer-remote-{}-{}", std::process::id(), name))
+    }
+
+    fn write_token(name: &str, token: &str) -> PathBuf {
+        let token_path = temp_path(name);
+        fs::write(&token_path, format!("{}\n", token)).expect("BUG: cannot write token");
+        token_path
+    }
+
+    /// Receive one request and reply with the canned `response`. Request with invalid token is
+    /// refused in the same way as by the miner.
+    fn serve_canned<S: Read + Write>(mut stream: S, response: serde_json::Value) -> Request {
+        let payload = read_frame(&mut stream).expect("BUG: cannot read request");
+        let request: Request = serde_json::from_slice(&payload).expect("BUG: invalid request");
+        let response = if token_matches(TOKEN, &request.token) {
+            response
+        } else {
+            api::error_response::<Backend>(StatusCode::AccessDenied, "invalid token".to_string())
+        };
+        stream
+            .write_all(&encode_frame(&response).expect("BUG: cannot encode response"))
+            .expect("BUG: cannot write response");
+        request
+    }
+
+    #[test]
+    fn test_frame() {
+        let message = serde_json::json!({ "command": "data" });
+        let frame = encode_frame(&message).expect("BUG: cannot encode frame");
+        assert_eq!(frame[..4], (frame.len() as u32 - 4).to_be_bytes());
+        let payload = read_frame(&mut &frame[..]).expect("BUG: cannot read frame");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&payload).expect("BUG: invalid payload"),
+            message
+        );
+
+        assert!(read_frame(&mut &frame[..frame.len() - 1]).is_err());
+        // Oversized frame is refused before its payload is read
+        let header = (MAX_FRAME_SIZE as u32 + 1).to_be_bytes();
+        assert_eq!(
+            read_frame(&mut &header[..])
+                .expect_err("BUG: oversized frame accepted")
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_token() {
+        assert!(token_matches(TOKEN, "secret"));
+        assert!(!token_matches(TOKEN, "secreT"));
+        assert!(!token_matches(TOKEN, "secret2"));
+        assert!(!token_matches(TOKEN, ""));
+
+        let token_path = write_token("empty.token", " \n");
+        assert!(read_token(&token_path).is_err());
+        fs::remove_file(&token_path).expect("BUG: cannot remove token");
+    }
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            Address::parse("/var/run/bosminer/config.sock"),
+            Address::Unix("/var/run/bosminer/config.sock".into())
+        );
+        assert_eq!(
+            Address::parse("config.sock"),
+            Address::Unix("config.sock".into())
+        );
+        assert_eq!(
+            Address::parse("localhost:4029"),
+            Address::Tcp("localhost:4029".to_string())
+        );
+    }
+
+    #[test]
+    fn test_client_unix() {
+        let socket_path = temp_path("client.sock");
+        let _ = fs::remove_file(&socket_path);
+        let listener = StdUnixListener::bind(&socket_path).expect("BUG: cannot bind socket");
+        let canned = serde_json::json!({ "status": { "code": 0 }, "data": { "group": [] } });
+        let server = {
+            let canned = canned.clone();
+            thread::spawn(move || {
+                let (stream, _) = listener.accept().expect("BUG: cannot accept connection");
+                serve_canned(stream, canned)
+            })
+        };
+
+        let token_path = write_token("client-unix.token", TOKEN);
+        let mut client = Client::new(Address::Unix(socket_path.clone()), &token_path);
+        assert_eq!(client.execute::<Backend>(Command::Data, None), canned);
+
+        let request = server.join().expect("BUG: server failed");
+        assert_eq!(request.token, TOKEN);
+        assert_eq!(request.command, Command::Data);
+        assert!(request.data.is_none());
+
+        fs::remove_file(&socket_path).expect("BUG: cannot remove socket");
+        fs::remove_file(&token_path).expect("BUG: cannot remove token");
+    }
+
+    #[test]
+    fn test_client_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind socket");
+        let address = Address::parse(
+            &listener
+                .local_addr()
+                .expect("BUG: missing local address")
+                .to_string(),
+        );
+        let canned = serde_json::json!({ "status": { "code": 0 }, "data": { "path": "a" } });
+        let server = {
+            let canned = canned.clone();
+            thread::spawn(move || {
+                (0..2)
+                    .map(|_| {
+                        let (stream, _) = listener.accept().expect("BUG: cannot accept connection");
+                        serve_canned(stream, canned.clone())
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // Configuration is forwarded as it is
+        let data = serde_json::json!({ "group": [{ "name": "A" }] });
+        let token_path = write_token("client-tcp.token", TOKEN);
+        let mut client = Client::new(address.clone(), &token_path);
+        assert_eq!(
+            client.execute::<Backend>(Command::Save, Some(data.clone())),
+            canned
+        );
+
+        // Error of the miner is passed to the caller
+        let invalid_token_path = write_token("client-tcp-invalid.token", "invalid");
+        let mut client = Client::new(address, &invalid_token_path);
+        let response = client.execute::<Backend>(Command::Save, Some(data.clone()));
+        assert_eq!(response["status"]["code"], StatusCode::AccessDenied as u32);
+
+        let requests = server.join().expect("BUG: server failed");
+        assert_eq!(requests[0].command, Command::Save);
+        assert_eq!(requests[0].data, Some(data));
+
+        fs::remove_file(&token_path).expect("BUG: cannot remove token");
+        fs::remove_file(&invalid_token_path).expect("BUG: cannot remove token");
+    }
+
+    #[test]
+    fn test_client_errors() {
+        let socket_path = temp_path("missing.sock");
+        let token_path = write_token("client-errors.token", TOKEN);
+
+        // Nobody is listening on the socket
+        let mut client = Client::new(Address::Unix(socket_path.clone()), &token_path);
+        let response = client.execute::<Backend>(Command::Metadata, None);
+        assert_eq!(response["status"]["code"], StatusCode::SystemError as u32);
+
+        // Missing token is reported without connecting to the miner
+        let mut client = Client::new(Address::Unix(socket_path), temp_path("missing.token"));
+        let response = client.execute::<Backend>(Command::Metadata, None);
+        assert_eq!(response["status"]["code"], StatusCode::SystemError as u32);
+        assert!(response["status"]["message"]
+            .as_str()
+            .expect("BUG: missing message")
+            .contains("cannot read token"));
+
+        fs::remove_file(&token_path).expect("BUG: cannot remove token");
+    }
+
+    #[tokio::test]
+    async fn test_server_save() {
+        let socket_path = temp_path("server.sock");
+        let config_path = temp_path("server.toml");
+        let token_path = write_token("server.token", TOKEN);
+        let server = Server::bind(
+            &socket_path,
+            &token_path,
+            config_path.to_string_lossy().into_owned(),
+            None,
+        )
+        .expect("BUG: cannot bind server");
+        let request = |token: &str, command, data| Request {
+            token: token.to_string(),
+            command,
+            data,
+        };
+
+        let response = server
+            .handle_request(request("invalid", Command::Data, None))
+            .await;
+        assert_eq!(response["status"]["code"], StatusCode::AccessDenied as u32);
+
+        let data = serde_json::json!({ "group": [{ "name": "A" }] });
+        let response = server
+            .handle_request(request(TOKEN, Command::Save, Some(data)))
+            .await;
+        assert_eq!(response["status"]["code"], StatusCode::Success as u32);
+        assert!(!config_path.with_extension(STAGED_EXTENSION).exists());
+
+        // Invalid configuration does not replace the saved one
+        let data = serde_json::json!({ "group": "B" });
+        let response = server
+            .handle_request(request(TOKEN, Command::Save, Some(data)))
+            .await;
+        assert_eq!(response["status"]["code"], StatusCode::InvalidFormat as u32);
+
+        let response = server
+            .handle_request(request(TOKEN, Command::Data, None))
+            .await;
+        assert_eq!(response["status"]["code"], StatusCode::Success as u32);
+        assert_eq!(response["data"]["group"][0]["name"], "A");
+
+        fs::remove_file(&socket_path).expect("BUG: cannot remove socket");
+        fs::remove_file(&config_path).expect("BUG: cannot remove config");
+        fs::remove_file(&token_path).expect("BUG: cannot remove token");
+    }
+}
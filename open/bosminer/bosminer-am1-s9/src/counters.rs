@@ -74,11 +74,54 @@ impl Chip {
     }
 }
 
+/// Bookkeeping of latencies measured on the solution RX and work TX paths
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Latency {
+    pub count: usize,
+    pub total: Duration,
+    pub max: Duration,
+    /// Number of measurements exceeding the budget
+    pub over_budget: usize,
+}
+
+impl Latency {
+    /// Account one measurement without any budget
+    pub fn add(&mut self, latency: Duration) {
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// Account one measurement and return `true` when it exceeds the `budget`
+    pub fn record(&mut self, latency: Duration, budget: Duration) -> bool {
+        self.add(latency);
+        let over_budget = latency > budget;
+        if over_budget {
+            self.over_budget += 1;
+        }
+        over_budget
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.total / self.count as u32)
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct HashChain {
     pub chip: Vec<Chip>,
     pub valid: usize,
     pub errors: usize,
+    /// Number of detected overflows of the solution RX FIFO (some nonces have been lost)
+    pub fifo_overflows: usize,
+    /// Time from the IRQ to draining the solution RX FIFO
+    pub rx_latency: Latency,
+    /// Time of submitting work to the work TX FIFO
+    pub tx_latency: Latency,
     pub started: Instant,
     pub stopped: Option<Instant>,
     pub asic_difficulty: usize,
@@ -89,6 +132,9 @@ impl HashChain {
         Self {
             valid: 0,
             errors: 0,
+            fifo_overflows: 0,
+            rx_latency: Default::default(),
+            tx_latency: Default::default(),
             started: Instant::now(),
             stopped: None,
             chip: vec![Chip::new(); chip_count],
@@ -99,6 +145,9 @@ impl HashChain {
     pub fn reset(&mut self) {
         self.valid = 0;
         self.errors = 0;
+        self.fifo_overflows = 0;
+        self.rx_latency = Default::default();
+        self.tx_latency = Default::default();
         for chip in self.chip.iter_mut() {
             chip.reset();
         }
@@ -150,3 +199,34 @@ impl HashChain {
         self.chip.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_latency_bookkeeping() {
+        let budget = Duration::from_millis(50);
+        let mut latency = Latency::default();
+        assert_eq!(latency.mean(), None);
+
+        assert!(!latency.record(Duration::from_millis(10), budget));
+        assert!(latency.record(Duration::from_millis(80), budget));
+        // latency equal to the budget is still acceptable
+        assert!(!latency.record(Duration::from_millis(50), budget));
+
+        assert_eq!(latency.count, 3);
+        assert_eq!(latency.over_budget, 1);
+        assert_eq!(latency.max, Duration::from_millis(80));
+        assert_eq!(latency.mean(), Some(Duration::from_millis(140) / 3));
+
+        let mut counters = HashChain::new(1, 64);
+        counters.fifo_overflows += 1;
+        counters.rx_latency = latency;
+        counters.tx_latency.add(Duration::from_micros(20));
+        counters.reset();
+        assert_eq!(counters.fifo_overflows, 0);
+        assert_eq!(counters.rx_latency, Latency::default());
+        assert_eq!(counters.tx_latency, Latency::default());
+    }
+}
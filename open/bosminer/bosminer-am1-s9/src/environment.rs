@@ -0,0 +1,383 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Fingerprint of the firmware/OS environment the miner is running in. It is gathered before
+//! any hardware is touched so that an unsupported kernel or device-tree is reported early and
+//! with a meaningful message instead of failing somewhere in the middle of hash chain
+//! initialization.
+
+use crate::config;
+use crate::error::{self, ErrorKind};
+use crate::io;
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kernel release string
+pub const KERNEL_VERSION_PATH: &'static str = "/proc/sys/kernel/osrelease";
+/// Model string of the loaded device-tree
+pub const DEVICE_TREE_MODEL_PATH: &'static str = "/proc/device-tree/model";
+/// Directory with all registered UIO devices (`uioN/name` holds the device-tree name)
+pub const UIO_CLASS_PATH: &'static str = "/sys/class/uio";
+
+/// UIO device of the fan controller
+pub const FAN_CONTROL_UIO_NAME: &'static str = "fan-control";
+
+/// Read-only view of the system. It allows testing the detection logic without real sysfs
+/// and procfs.
+pub trait SystemView {
+    /// Return content of a file or `None` when the file is not readable
+    fn read_to_string(&self, path: &Path) -> Option<String>;
+    /// Return all entries of a directory (empty when the directory does not exist)
+    fn read_dir(&self, path: &Path) -> Vec<PathBuf>;
+    /// Return version of FPGA bitstream serving given hashboard if it can be obtained
+    fn bitstream_version(&self, hashboard_idx: usize) -> Option<String>;
+}
+
+/// The real system the miner is running on
+pub struct HostSystem;
+
+impl SystemView for HostSystem {
+    fn read_to_string(&self, path: &Path) -> Option<String> {
+        fs::read_to_string(path).ok()
+    }
+
+    fn read_dir(&self, path: &Path) -> Vec<PathBuf> {
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn bitstream_version(&self, hashboard_idx: usize) -> Option<String> {
+        io::Common::read_bitstream_version(hashboard_idx).ok()
+    }
+}
+
+/// Environment fingerprint
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Environment {
+    pub kernel_version: Option<String>,
+    pub device_tree_model: Option<String>,
+    /// Names of all UIO devices registered in the system (sorted)
+    pub uio_devices: Vec<String>,
+    /// Names of UIO devices required by this build which are not present
+    pub missing_uio_devices: Vec<String>,
+    pub bitstream_version: Option<String>,
+}
+
+impl Environment {
+    /// Names of UIO devices required for running given hash chains
+    pub fn expected_uio_devices<I>(hash_chains: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut expected = vec![FAN_CONTROL_UIO_NAME.to_string()];
+        for hashboard_idx in hash_chains {
+            expected.extend(
+                io::uio::Type::ALL
+                    .iter()
+                    .map(|uio_type| uio_type.to_uio_name(hashboard_idx)),
+            );
+        }
+        expected
+    }
+
+    /// Strip trailing whitespace and NUL terminator (device-tree strings have one)
+    fn sanitize(value: String) -> String {
+        value
+            .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+            .to_string()
+    }
+
+    /// Gather the fingerprint. UIO devices are only looked up by their names, none of them is
+    /// opened. The bitstream version is read from the first hash chain that has its `common`
+    /// block present.
+    pub fn probe<T>(system: &T, hash_chains: &[usize]) -> Self
+    where
+        T: SystemView,
+    {
+        let read = |path: &str| {
+            system
+                .read_to_string(Path::new(path))
+                .map(Self::sanitize)
+                .filter(|value| !value.is_empty())
+        };
+
+        let mut uio_devices: Vec<_> = system
+            .read_dir(Path::new(UIO_CLASS_PATH))
+            .iter()
+            .filter_map(|uio_path| system.read_to_string(&uio_path.join("name")))
+            .map(Self::sanitize)
+            .collect();
+        uio_devices.sort();
+
+        let missing_uio_devices = Self::expected_uio_devices(hash_chains.iter().cloned())
+            .into_iter()
+            .filter(|name| !uio_devices.contains(name))
+            .collect();
+
+        let bitstream_version = hash_chains
+            .iter()
+            .find(|hashboard_idx| {
+                uio_devices.contains(&io::uio::Type::Common.to_uio_name(**hashboard_idx))
+            })
+            .and_then(|hashboard_idx| system.bitstream_version(*hashboard_idx));
+
+        Self {
+            kernel_version: read(KERNEL_VERSION_PATH),
+            device_tree_model: read(DEVICE_TREE_MODEL_PATH),
+            uio_devices,
+            missing_uio_devices,
+            bitstream_version,
+        }
+    }
+
+    /// Fail when the system lacks anything this build cannot run without
+    pub fn check(&self) -> error::Result<()> {
+        if !self.missing_uio_devices.is_empty() {
+            Err(ErrorKind::MissingUioDevices(
+                self.missing_uio_devices.join(", "),
+            ))?
+        }
+        Ok(())
+    }
+
+    /// Flatten the fingerprint into key/value pairs suitable for `hal::BackendInfo`
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let unknown = || "unknown".to_string();
+        let mut map = BTreeMap::new();
+        map.insert(
+            "kernel".to_string(),
+            self.kernel_version.clone().unwrap_or_else(unknown),
+        );
+        map.insert(
+            "device_tree".to_string(),
+            self.device_tree_model.clone().unwrap_or_else(unknown),
+        );
+        map.insert(
+            "bitstream".to_string(),
+            self.bitstream_version.clone().unwrap_or_else(unknown),
+        );
+        map.insert("uio".to_string(), self.uio_devices.join(","));
+        map
+    }
+}
+
+/// Hardware detected without starting mining (used by `--check-hardware`)
+#[derive(Clone, Debug, Serialize)]
+pub struct HardwareReport {
+    pub model: &'static str,
+    /// Indices of hash boards detected by their plug pins
+    pub hashboards: Vec<usize>,
+    #[serde(flatten)]
+    pub environment: Environment,
+}
+
+impl HardwareReport {
+    pub fn new(hashboards: Vec<usize>, environment: Environment) -> Self {
+        Self {
+            model: config::HW_MODEL,
+            hashboards,
+            environment,
+        }
+    }
+
+    /// Mining can be started with the detected hardware
+    pub fn is_usable(&self) -> bool {
+        !self.hashboards.is_empty() && self.environment.check().is_ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    /// Mocked sysfs/procfs
+    #[derive(Default)]
+    struct MockSystem {
+        files: HashMap<PathBuf, String>,
+        bitstream_version: Option<String>,
+    }
+
+    impl MockSystem {
+        fn with_file(mut self, path: &str, content: &str) -> Self {
+            self.files.insert(PathBuf::from(path), content.to_string());
+            self
+        }
+
+        fn with_uio_devices(mut self, names: &[&str]) -> Self {
+            for (i, name) in names.iter().enumerate() {
+                let path = Path::new(UIO_CLASS_PATH)
+                    .join(format!("uio{}", i))
+                    .join("name");
+                self.files.insert(path, format!("{}\n", name));
+            }
+            self
+        }
+    }
+
+    impl SystemView for MockSystem {
+        fn read_to_string(&self, path: &Path) -> Option<String> {
+            self.files.get(path).cloned()
+        }
+
+        fn read_dir(&self, path: &Path) -> Vec<PathBuf> {
+            let mut entries: Vec<_> = self
+                .files
+                .keys()
+                .filter_map(|file| file.parent())
+                .filter(|dir| dir.parent() == Some(path))
+                .map(|dir| dir.to_path_buf())
+                .collect();
+            entries.sort();
+            entries.dedup();
+            entries
+        }
+
+        fn bitstream_version(&self, _hashboard_idx: usize) -> Option<String> {
+            self.bitstream_version.clone()
+        }
+    }
+
+    fn all_uio_devices(hash_chains: &[usize]) -> Vec<String> {
+        Environment::expected_uio_devices(hash_chains.iter().cloned())
+    }
+
+    #[test]
+    fn test_complete_environment() {
+        let uio_devices = all_uio_devices(&[6, 7, 8]);
+        let system = MockSystem {
+            bitstream_version: Some("1.0.0 for Antminer S9".to_string()),
+            ..Default::default()
+        }
+        .with_file(KERNEL_VERSION_PATH, "4.19.0\n")
+        .with_file(DEVICE_TREE_MODEL_PATH, "Antminer S9\0")
+        .with_uio_devices(&uio_devices.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+
+        let environment = Environment::probe(&system, &[6, 7, 8]);
+        assert_eq!(environment.kernel_version, Some("4.19.0".to_string()));
+        assert_eq!(
+            environment.device_tree_model,
+            Some("Antminer S9".to_string())
+        );
+        assert_eq!(
+            environment.bitstream_version,
+            Some("1.0.0 for Antminer S9".to_string())
+        );
+        assert_eq!(environment.uio_devices.len(), uio_devices.len());
+        assert!(environment.missing_uio_devices.is_empty());
+        environment
+            .check()
+            .expect("BUG: complete environment rejected");
+    }
+
+    #[test]
+    fn test_missing_uio_devices() {
+        let system = MockSystem {
+            // read through `common` block of chain 8 which is the only one present
+            bitstream_version: Some("1.0.0 for Antminer S9".to_string()),
+            ..Default::default()
+        }
+        .with_file(KERNEL_VERSION_PATH, "4.9.0\n")
+        .with_uio_devices(&[
+            "fan-control",
+            "chain8-common",
+            "chain8-work-rx",
+            "chain8-work-tx",
+        ]);
+
+        let environment = Environment::probe(&system, &[7, 8]);
+        assert_eq!(environment.device_tree_model, None);
+        assert_eq!(
+            environment.missing_uio_devices,
+            vec![
+                "chain7-common",
+                "chain7-work-rx",
+                "chain7-work-tx",
+                "chain7-cmd-rx",
+                "chain8-cmd-rx"
+            ]
+        );
+        assert_eq!(
+            environment.bitstream_version,
+            Some("1.0.0 for Antminer S9".to_string())
+        );
+
+        let error = environment
+            .check()
+            .expect_err("BUG: missing devices accepted");
+        match error.kind() {
+            ErrorKind::MissingUioDevices(names) => {
+                assert_eq!(
+                    names,
+                    "chain7-common, chain7-work-rx, chain7-work-tx, chain7-cmd-rx, chain8-cmd-rx"
+                );
+            }
+            kind => panic!("unexpected error kind: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_no_uio_at_all() {
+        let system = MockSystem::default();
+
+        let environment = Environment::probe(&system, &[8]);
+        assert_eq!(environment.kernel_version, None);
+        assert!(environment.uio_devices.is_empty());
+        // without `common` block there is no way to read the bitstream version
+        assert_eq!(environment.bitstream_version, None);
+        assert_eq!(environment.missing_uio_devices, all_uio_devices(&[8]));
+        assert!(environment.check().is_err());
+        assert_eq!(environment.to_map()["kernel"], "unknown");
+    }
+
+    #[test]
+    fn test_hardware_report() {
+        let uio_devices = all_uio_devices(&[6, 8]);
+        let system = MockSystem::default()
+            .with_uio_devices(&uio_devices.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+
+        let report = HardwareReport::new(vec![6, 8], Environment::probe(&system, &[6, 8]));
+        assert!(report.is_usable());
+        let json = serde_json::to_value(&report).expect("BUG: cannot serialize report");
+        assert_eq!(json["model"], config::HW_MODEL);
+        assert_eq!(json["hashboards"], serde_json::json!([6, 8]));
+        assert_eq!(json["missing_uio_devices"], serde_json::json!([]));
+
+        // no hash board is plugged in
+        let report = HardwareReport::new(vec![], Environment::probe(&system, &[]));
+        assert!(!report.is_usable());
+
+        // a hash board is plugged in but the device-tree does not support it
+        let report = HardwareReport::new(vec![6, 7, 8], Environment::probe(&system, &[6, 7, 8]));
+        assert!(!report.is_usable());
+    }
+}
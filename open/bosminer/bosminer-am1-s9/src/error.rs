@@ -25,6 +25,8 @@
 use failure::{Backtrace, Context, Fail};
 use std::fmt::{self, Debug, Display};
 
+pub use bosminer::error::{Classify, Severity};
+
 use std::io;
 use sysfs_gpio;
 use uio_async;
@@ -51,6 +53,13 @@ pub enum ErrorKind {
     #[fail(display = "UIO: {}", _0)]
     Uio(String),
 
+    /// UIO devices expected by this build are not present in the running system
+    #[fail(
+        display = "Missing UIO devices (unsupported kernel or device-tree?): {}",
+        _0
+    )]
+    MissingUioDevices(String),
+
     /// Unexpected version of something.
     #[fail(display = "Unexpected {} version: {}, expected: {}", _0, _1, _2)]
     UnexpectedVersion(String, String, String),
@@ -120,6 +129,35 @@ pub enum Fifo {
     TimedOut,
 }
 
+impl Classify for ErrorKind {
+    fn severity(&self) -> Severity {
+        match self {
+            // Communication with hashboards and peripherals may recover after reset or
+            // re-initialization of the hashchain
+            ErrorKind::General(_)
+            | ErrorKind::Io(_)
+            | ErrorKind::Uio(_)
+            | ErrorKind::Hashboard(_, _)
+            | ErrorKind::Hashchip(_)
+            | ErrorKind::ChipEnumeration(_)
+            | ErrorKind::I2cHashchip(_)
+            | ErrorKind::Fifo(_, _)
+            | ErrorKind::Gpio(_)
+            | ErrorKind::I2c(_)
+            | ErrorKind::Power(_)
+            | ErrorKind::Sensors(_) => Severity::Transient,
+            // Missing or incompatible hardware, invalid settings and halting of the miner
+            ErrorKind::UioDevice(_, _)
+            | ErrorKind::MissingUioDevices(_)
+            | ErrorKind::UnexpectedVersion(_, _, _)
+            | ErrorKind::BaudRate(_)
+            | ErrorKind::PLL(_)
+            | ErrorKind::HashChainManager(_)
+            | ErrorKind::Halt(_) => Severity::Fatal,
+        }
+    }
+}
+
 /// Implement Fail trait instead of use Derive to get more control over custom type.
 /// The main advantage is customization of Context type which allows conversion of
 /// any error types to this custom error with general error kind by calling context
@@ -150,6 +188,26 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.inner.get_context().clone()
     }
+
+    /// Returns description of the error with all its causes
+    pub fn chain(&self) -> String {
+        let mut chain = self.to_string();
+        for cause in (self as &dyn Fail).iter_causes() {
+            let cause = cause.to_string();
+            if !chain.ends_with(&cause) {
+                chain.push_str(": ");
+                chain.push_str(&cause);
+            }
+        }
+        chain
+    }
+}
+
+impl Classify for Error {
+    #[inline]
+    fn severity(&self) -> Severity {
+        self.inner.get_context().severity()
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -218,3 +276,43 @@ impl From<sysfs_gpio::Error> for Error {
 
 /// A specialized `Result` type bound to [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let transient: Vec<Error> = vec![
+            io::Error::new(io::ErrorKind::TimedOut, "timed out").into(),
+            ErrorKind::Hashchip("no response".to_string()).into(),
+            ErrorKind::ChipEnumeration("chips missing".to_string()).into(),
+            ErrorKind::Fifo(Fifo::TimedOut, "work TX".to_string()).into(),
+            ErrorKind::I2c("write failed".to_string()).into(),
+        ];
+        for error in transient {
+            assert_eq!(error.severity(), Severity::Transient, "{}", error);
+        }
+
+        let fatal: Vec<Error> = vec![
+            ErrorKind::MissingUioDevices("chain8-common".to_string()).into(),
+            ErrorKind::UnexpectedVersion("FPGA".to_string(), "1".to_string(), "2".to_string())
+                .into(),
+            ErrorKind::PLL("frequency out of range".to_string()).into(),
+            ErrorKind::HashChainManager(HashChainManager::ParamsNotSet).into(),
+            ErrorKind::Halt("timeout".to_string()).into(),
+        ];
+        for error in fatal {
+            assert_eq!(error.severity(), Severity::Fatal, "{}", error);
+            assert!(!error.is_transient());
+        }
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let error: Error = io::Error::new(io::ErrorKind::NotFound, "no such device")
+            .context("cannot open hashboard".to_string())
+            .into();
+        assert_eq!(error.chain(), "cannot open hashboard: no such device");
+    }
+}
@@ -23,6 +23,8 @@
 use embedded_hal;
 use sysfs_gpio;
 
+use std::path::Path;
+
 /// Helper struct for altering output pins which implements OutputPin trait
 #[derive(Clone)]
 pub struct PinOut(sysfs_gpio::Pin);
@@ -43,6 +45,15 @@ impl embedded_hal::digital::v2::OutputPin for PinOut {
 #[derive(Clone)]
 pub struct PinIn(sysfs_gpio::Pin);
 
+impl PinIn {
+    /// Returns input pin which has been already exported at `path` in sysfs
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, sysfs_gpio::Error> {
+        let pin = sysfs_gpio::Pin::from_path(path)?;
+        pin.set_direction(sysfs_gpio::Direction::In)?;
+        Ok(PinIn(pin))
+    }
+}
+
 impl embedded_hal::digital::v2::InputPin for PinIn {
     type Error = sysfs_gpio::Error;
 
@@ -40,7 +40,7 @@ use futures::channel::mpsc;
 use futures::future::{select, Either};
 use futures::lock::Mutex;
 use ii_async_compat::prelude::*;
-use tokio::signal::unix::{signal, SignalKind};
+use tokio::runtime;
 use tokio::task;
 
 /// Token sent by halted task to confirm that halting is done
@@ -101,22 +101,35 @@ impl NotifyReceiver {
     where
         F: Future<Output = ()> + 'static + Send,
     {
-        tokio::spawn(async move {
-            match select(f.boxed(), self.wait_for_halt().boxed()).await {
-                // in case we received halt notification, reply and exit
-                Either::Right((halt_result, _)) => {
-                    match halt_result {
-                        // confirm we are done (there's no cleanup)
-                        Some(done_sender) => done_sender.confirm(),
-                        // halt sender was dropped
-                        None => (),
-                    }
-                }
-                Either::Left(_) => {
-                    // task exited normally, do nothing
+        tokio::spawn(self.run_until_halt(f));
+    }
+
+    /// Same as `spawn` but the task is run by runtime with given `handle`
+    pub fn spawn_on<F>(self, handle: &runtime::Handle, f: F)
+    where
+        F: Future<Output = ()> + 'static + Send,
+    {
+        handle.spawn(self.run_until_halt(f));
+    }
+
+    async fn run_until_halt<F>(self, f: F)
+    where
+        F: Future<Output = ()> + 'static + Send,
+    {
+        match select(f.boxed(), self.wait_for_halt().boxed()).await {
+            // in case we received halt notification, reply and exit
+            Either::Right((halt_result, _)) => {
+                match halt_result {
+                    // confirm we are done (there's no cleanup)
+                    Some(done_sender) => done_sender.confirm(),
+                    // halt sender was dropped
+                    None => (),
                 }
             }
-        });
+            Either::Left(_) => {
+                // task exited normally, do nothing
+            }
+        }
     }
 }
 
@@ -240,29 +253,6 @@ impl Sender {
         Ok(())
     }
 
-    /// This is a hack around `halt_sender` having to be run from tokio context, because it spawns
-    /// additional threads.
-    pub fn hook_termination_signals(self: Arc<Self>) {
-        // Hook `SIGINT`, `SIGHUP` and `SIGTERM`
-        for signal_type in vec![
-            SignalKind::interrupt(),
-            SignalKind::hangup(),
-            SignalKind::terminate(),
-        ] {
-            let halt_sender = self.clone();
-            tokio::spawn(async move {
-                if let Some(_) = signal(signal_type)
-                    .expect("BUG: failed hooking signal")
-                    .next()
-                    .await
-                {
-                    // Exit after receiving signal
-                    halt_sender.send_halt().await;
-                }
-            });
-        }
-    }
-
     pub async fn send_halt(self: Arc<Self>) {
         let (finish_tx, mut finish_rx) = mpsc::unbounded();
         let handle: task::JoinHandle<error::Result<()>> = tokio::spawn(async move {
@@ -20,6 +20,7 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use crate::button;
 use crate::halt;
 use crate::monitor;
 use crate::Manager;
@@ -60,6 +61,9 @@ pub trait Hooks: Send + Sync + Debug {
 
     /// Called after all groups with clients has been loaded
     async fn clients_loaded(&self, _client_manager: client::Manager) {}
+
+    /// Called when the front panel button has been pressed (before its action is executed)
+    async fn button_pressed(&self, _press: button::Press) {}
 }
 
 /// NoHooks uses default implementation of all hooks
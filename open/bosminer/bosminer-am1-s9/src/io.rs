@@ -29,7 +29,7 @@
 //!     and implements few higher-level functions to read/write work
 
 mod ext_work_id;
-mod uio;
+pub mod uio;
 
 use crate::error::{self, ErrorKind};
 use crate::MidstateCount;
@@ -38,10 +38,12 @@ use ext_work_id::ExtWorkId;
 use bosminer::work;
 use std::convert::TryInto;
 use std::fmt;
+use std::mem;
+use std::slice;
 
 use chrono::prelude::DateTime;
 use chrono::Utc;
-use std::time::{Duration, UNIX_EPOCH};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use ii_async_compat::prelude::*;
 use tokio::time::delay_for;
@@ -128,6 +130,57 @@ pub struct Solution {
     pub solution_idx: usize,
     /// Hardware specific solution identifier
     pub hardware_id: u32,
+    /// Time when the solution has been read from the FIFO
+    pub timestamp: Instant,
+    /// Time since the wake up by IRQ when the solution has been read from backlogged FIFO
+    pub age: Option<Duration>,
+}
+
+/// Decoded content of the `WORK_RX_STAT_REG` register
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RxStatus {
+    pub irq_pending: bool,
+    pub full: bool,
+    pub empty: bool,
+}
+
+impl RxStatus {
+    const IRQ_PEND: u32 = 1 << 4;
+    const RX_FULL: u32 = 1 << 1;
+    const RX_EMPTY: u32 = 1 << 0;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            irq_pending: bits & Self::IRQ_PEND != 0,
+            full: bits & Self::RX_FULL != 0,
+            empty: bits & Self::RX_EMPTY != 0,
+        }
+    }
+}
+
+/// The IP core does not report lost solutions so the overflow is detected from the FIFO being
+/// full when it is about to be read. Each contiguous period of full FIFO is one overflow event.
+#[derive(Debug, Default)]
+pub struct OverflowDetector {
+    full: bool,
+    events: usize,
+}
+
+impl OverflowDetector {
+    /// Account sampled status and return `true` when a new overflow event has started
+    pub fn update(&mut self, status: RxStatus) -> bool {
+        let new_event = status.full && !self.full;
+        self.full = status.full;
+        if new_event {
+            self.events += 1;
+        }
+        new_event
+    }
+
+    /// Return number of overflow events since the last call
+    pub fn take_events(&mut self) -> usize {
+        std::mem::replace(&mut self.events, 0)
+    }
 }
 
 struct WorkRxFifo {
@@ -141,6 +194,31 @@ impl WorkRxFifo {
         self.regs.work_rx_stat_reg.read().rx_empty().bit()
     }
 
+    #[inline]
+    pub fn status(&self) -> RxStatus {
+        RxStatus::from_bits(self.regs.work_rx_stat_reg.read().bits())
+    }
+
+    /// Wait until there is something to read and return time of the IRQ when it had to be
+    /// awaited
+    pub async fn async_wait_for_data(&self) -> error::Result<Option<Instant>> {
+        if !self.is_empty() {
+            return Ok(None);
+        }
+        // same as `async_irq_wait_cond` but the time is taken as soon as the IRQ is delivered
+        let mut irq_time = None;
+        while self.is_empty() {
+            self.uio.irq_enable()?;
+            if !self.is_empty() {
+                irq_time = Some(Instant::now());
+                break;
+            }
+            self.uio.irq_wait_async().await?;
+            irq_time = Some(Instant::now());
+        }
+        Ok(irq_time)
+    }
+
     /// Try to read from work rx fifo.
     /// Performs blocking read with timeout. Uses IRQ.
     #[allow(dead_code)]
@@ -196,11 +274,6 @@ impl WorkTxFifo {
     /// fit one more work.
     const FIFO_THRESHOLD: u32 = Self::FIFO_SIZE - Self::BIGGEST_WORK;
 
-    #[inline]
-    pub fn is_full(&self) -> bool {
-        self.regs.work_tx_stat_reg.read().tx_full().bit()
-    }
-
     #[inline]
     pub fn has_space_for_one_job(&self) -> bool {
         self.regs.work_tx_stat_reg.read().irq_pend().bit()
@@ -213,15 +286,20 @@ impl WorkTxFifo {
         self.regs.work_tx_last_id.read().bits()
     }
 
-    /// Try to write work item to work TX FIFO.
-    /// Performs blocking write without timeout. Uses IRQ.
-    /// The idea is that you don't call this function until you are sure you
-    /// can fit in all the entries you want.
-    #[inline]
-    pub fn write(&mut self, item: u32) -> error::Result<()> {
-        let cond = || !self.is_full();
+    /// Write all words of one work item to work TX FIFO.
+    /// Performs blocking wait for room without timeout. Uses IRQ.
+    /// The room is checked only once for the whole item (the IRQ threshold guarantees room for
+    /// the biggest work) and the words are then written with back-to-back 32-bit stores which
+    /// are posted by the bus without waiting for each other. Wider or burst writes cannot be
+    /// used: the IP core is an AXI4-Lite slave where each write access to `WORK_TX_FIFO` pushes
+    /// exactly one 32-bit word and the next address belongs to `WORK_TX_CTRL_REG`.
+    pub fn write_item(&mut self, words: &[u32]) -> error::Result<()> {
+        debug_assert!(words.len() <= Self::BIGGEST_WORK as usize);
+        let cond = || self.has_space_for_one_job();
         self.uio.irq_wait_cond(cond, None)?;
-        self.regs.work_tx_fifo.write(|w| unsafe { w.bits(item) });
+        for word in words {
+            self.regs.work_tx_fifo.write(|w| unsafe { w.bits(*word) });
+        }
         Ok(())
     }
 
@@ -232,6 +310,13 @@ impl WorkTxFifo {
         Ok(())
     }
 
+    /// Drop all work which has not been sent to the hash chain yet
+    pub fn reset(&mut self) {
+        self.regs
+            .work_tx_ctrl_reg
+            .modify(|_, w| w.rst_tx_fifo().set_bit());
+    }
+
     pub fn init(&mut self) -> error::Result<()> {
         // Set threshold for work TX so that there's space for
         // at least one job.
@@ -239,9 +324,7 @@ impl WorkTxFifo {
             .work_tx_irq_thr
             .write(|w| unsafe { w.bits(Self::FIFO_THRESHOLD) });
         // reset output FIFO
-        self.regs
-            .work_tx_ctrl_reg
-            .modify(|_, w| w.rst_tx_fifo().set_bit());
+        self.reset();
         // enable IRQ_WORK_TX interrupt
         self.regs
             .work_tx_ctrl_reg
@@ -258,6 +341,88 @@ impl WorkTxFifo {
     }
 }
 
+/// Number of midstates of the biggest work supported by the IP core
+const MAX_MIDSTATE_COUNT: usize = 4;
+
+/// Header of the work item as specified by the IP core documentation (bytes 0-15 of the work
+/// specification). All words are little endian.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct WorkTxHeader {
+    ext_work_id: u32,
+    nbits: u32,
+    ntime: u32,
+    merkle_root_tail: u32,
+}
+
+/// Midstate words in reverse order `State[7]..State[0]`. The IP core transmits each of them as
+/// big endian.
+type WorkTxMidstate = [u32; 8];
+
+/// Complete work item with room for the biggest midstate count
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct WorkTxLayout {
+    header: WorkTxHeader,
+    midstates: [WorkTxMidstate; MAX_MIDSTATE_COUNT],
+}
+
+impl WorkTxLayout {
+    const HEADER_WORDS: usize = mem::size_of::<WorkTxHeader>() / mem::size_of::<u32>();
+    const MIDSTATE_WORDS: usize = mem::size_of::<WorkTxMidstate>() / mem::size_of::<u32>();
+    const WORDS: usize = mem::size_of::<Self>() / mem::size_of::<u32>();
+}
+
+// The layout has to match the IP core documentation: 16 bytes of header directly followed by
+// 32 bytes of each midstate without any padding so that it can be viewed as a slice of words
+const _: [(); 16] = [(); mem::size_of::<WorkTxHeader>()];
+const _: [(); 32] = [(); mem::size_of::<WorkTxMidstate>()];
+const _: [(); 16 + 32 * MAX_MIDSTATE_COUNT] = [(); mem::size_of::<WorkTxLayout>()];
+const _: [(); 4] = [(); mem::align_of::<WorkTxLayout>()];
+// The biggest work item has to fit into the room guaranteed by the TX FIFO IRQ threshold
+const _: [(); 1] = [(); (WorkTxLayout::WORDS <= WorkTxFifo::BIGGEST_WORK as usize) as usize];
+
+/// Work item assembled for the current midstate count and ready to be written to the FIFO
+struct WorkTxItem {
+    layout: WorkTxLayout,
+    len: usize,
+}
+
+impl WorkTxItem {
+    fn new(midstate_count: MidstateCount, work: &work::Assignment, work_id: usize) -> Self {
+        let mut layout = WorkTxLayout::default();
+        layout.header = WorkTxHeader {
+            ext_work_id: ExtWorkId::new(work_id, 0).to_hw(midstate_count).to_le(),
+            nbits: work.bits().to_le(),
+            ntime: work.ntime.to_le(),
+            merkle_root_tail: work.merkle_root_tail().to_le(),
+        };
+        for (midstate, mid) in layout.midstates.iter_mut().zip(work.midstates.iter()) {
+            for (word, midstate_word) in midstate.iter_mut().zip(mid.state.words::<u32>().rev()) {
+                *word = midstate_word.to_be();
+            }
+        }
+
+        Self {
+            layout,
+            len: WorkTxLayout::HEADER_WORDS
+                + WorkTxLayout::MIDSTATE_WORDS * midstate_count.to_count(),
+        }
+    }
+
+    /// Return words of the work item in the order they are written to the FIFO
+    fn words(&self) -> &[u32] {
+        // The layout consists of `u32` words only and it has no padding (see the assertions above)
+        let words = unsafe {
+            slice::from_raw_parts(
+                &self.layout as *const WorkTxLayout as *const u32,
+                WorkTxLayout::WORDS,
+            )
+        };
+        &words[..self.len]
+    }
+}
+
 /// This object drives both FIFOs, because we handle command responses
 /// in a task synchronously.
 ///
@@ -386,12 +551,22 @@ impl WorkRxResponse {
 pub struct WorkRx {
     fifo: WorkRxFifo,
     midstate_count: MidstateCount,
+    overflow_detector: OverflowDetector,
+    /// Time of wake up by IRQ when the FIFO has not been drained since
+    drain_start: Option<Instant>,
 }
 
 impl WorkRx {
     pub async fn recv_solution(mut self) -> Result<(Self, Solution), failure::Error> {
+        if let Some(irq_time) = self.fifo.async_wait_for_data().await? {
+            self.drain_start.get_or_insert(irq_time);
+        }
+        // the status has to be sampled before the solution is read to catch the full FIFO
+        self.overflow_detector.update(self.fifo.status());
+
         let word1 = self.fifo.async_read().await?;
         let word2 = self.fifo.async_read().await?;
+        let timestamp = Instant::now();
         let resp = WorkRxResponse::from_hw(self.midstate_count, word1, word2);
 
         let solution = Solution {
@@ -399,11 +574,30 @@ impl WorkRx {
             midstate_idx: resp.midstate_idx,
             solution_idx: resp.solution_idx,
             hardware_id: resp.work_id as u32,
+            timestamp,
+            // All solutions drained after the wake up have been waiting in the FIFO at least
+            // since then
+            age: self
+                .drain_start
+                .map(|start| timestamp.saturating_duration_since(start)),
         };
 
         Ok((self, solution))
     }
 
+    /// Return number of FIFO overflow events detected since the last call
+    pub fn take_overflow_events(&mut self) -> usize {
+        self.overflow_detector.take_events()
+    }
+
+    /// Return time from the IRQ wake up to draining the FIFO when it has just become empty
+    pub fn take_drain_latency(&mut self) -> Option<Duration> {
+        if !self.fifo.is_empty() {
+            return None;
+        }
+        self.drain_start.take().map(|start| start.elapsed())
+    }
+
     fn init(&mut self) -> error::Result<()> {
         self.fifo.init()
     }
@@ -412,6 +606,8 @@ impl WorkRx {
         Ok(Self {
             fifo: WorkRxFifo::new(hashboard_idx)?,
             midstate_count,
+            overflow_detector: Default::default(),
+            drain_start: None,
         })
     }
 }
@@ -419,6 +615,8 @@ impl WorkRx {
 pub struct WorkTx {
     fifo: WorkTxFifo,
     midstate_count: MidstateCount,
+    /// Time of submitting the last work which has not been taken yet
+    send_time: Option<Duration>,
 }
 
 impl WorkTx {
@@ -442,20 +640,26 @@ impl WorkTx {
         work_id: usize,
     ) -> Result<(), failure::Error> {
         self.assert_midstate_count(work.midstates.len());
-        let ext_work_id = ExtWorkId::new(work_id, 0);
+        // NOTE: The nonce start of the work cannot be programmed because chips split the nonce
+        // space by their address (see `bm1387::CoreAddress`) and the IP core has no such field.
+        // Hash chains do not enable nonce offset of their generators so it is always zero.
+        debug_assert_eq!(work.nonce_start(), 0, "BUG: nonce offset is not supported");
+
+        let start = Instant::now();
+        let item = WorkTxItem::new(self.midstate_count, work, work_id);
+        self.fifo.write_item(item.words())?;
+        self.send_time = Some(start.elapsed());
+        Ok(())
+    }
 
-        self.fifo
-            .write(ext_work_id.to_hw(self.midstate_count).to_le())?;
-        self.fifo.write(work.bits().to_le())?;
-        self.fifo.write(work.ntime.to_le())?;
-        self.fifo.write(work.merkle_root_tail().to_le())?;
+    /// Return time of assembling and writing the last work item to the FIFO
+    pub fn take_send_time(&mut self) -> Option<Duration> {
+        self.send_time.take()
+    }
 
-        for mid in work.midstates.iter() {
-            for midstate_word in mid.state.words::<u32>().rev() {
-                self.fifo.write(midstate_word.to_be())?;
-            }
-        }
-        Ok(())
+    /// Drop the work queued in the FIFO which has not been sent to the hash chain yet
+    pub fn flush(&mut self) {
+        self.fifo.reset();
     }
 
     /// Return upper bound for `work_id`
@@ -472,6 +676,7 @@ impl WorkTx {
         Ok(Self {
             fifo: WorkTxFifo::new(hashboard_idx)?,
             midstate_count,
+            send_time: None,
         })
     }
 }
@@ -661,6 +866,20 @@ impl Common {
         Ok(())
     }
 
+    /// Describe bitstream loaded in FPGA for given hashboard without touching the IP core
+    /// configuration (only `VERSION` and `BUILD_ID` registers are read)
+    pub fn read_bitstream_version(hashboard_idx: usize) -> error::Result<String> {
+        let mut common = Self::new(hashboard_idx, MidstateCount::new(1))?;
+        let build_id = common.get_build_id();
+        if !build_id.seems_legit() {
+            Err(ErrorKind::Hashboard(
+                hashboard_idx,
+                "no s9_io bistream found".to_string(),
+            ))?
+        }
+        Ok(format!("{} built on {}", common.get_version(), build_id))
+    }
+
     fn new(hashboard_idx: usize, midstate_count: MidstateCount) -> error::Result<Self> {
         let uio = uio::Device::open(hashboard_idx, uio::Type::Common)?;
         Ok(Self {
@@ -713,9 +932,45 @@ impl Core {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::null_work;
+
+    use std::sync::Arc;
+
     /// Index of chain for testing (must exist and be defined in DTS)
     const TEST_CHAIN_INDEX: usize = 8;
 
+    /// Work item with 1 midstate as written by the previous implementation word by word
+    #[rustfmt::skip]
+    const WORK_TX_ITEM_1: [u8; 48] = [
+        0x23, 0x01, 0x00, 0x00, 0x1e, 0x5b, 0x11, 0x17, 0x2b, 0x1a, 0x8f, 0x5e, 0xff, 0xff, 0xff, 0xff,
+        0x1f, 0x1e, 0x1d, 0x1c, 0x1b, 0x1a, 0x19, 0x18, 0x17, 0x16, 0x15, 0x14, 0x13, 0x12, 0x11, 0x10,
+        0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+    ];
+
+    /// Work item with 2 midstates as written by the previous implementation word by word
+    #[rustfmt::skip]
+    const WORK_TX_ITEM_2: [u8; 80] = [
+        0x46, 0x02, 0x00, 0x00, 0x1e, 0x5b, 0x11, 0x17, 0x2b, 0x1a, 0x8f, 0x5e, 0xff, 0xff, 0xff, 0xff,
+        0x1f, 0x1e, 0x1d, 0x1c, 0x1b, 0x1a, 0x19, 0x18, 0x17, 0x16, 0x15, 0x14, 0x13, 0x12, 0x11, 0x10,
+        0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+        0x3f, 0x3e, 0x3d, 0x3c, 0x3b, 0x3a, 0x39, 0x38, 0x37, 0x36, 0x35, 0x34, 0x33, 0x32, 0x31, 0x30,
+        0x2f, 0x2e, 0x2d, 0x2c, 0x2b, 0x2a, 0x29, 0x28, 0x27, 0x26, 0x25, 0x24, 0x23, 0x22, 0x21, 0x20,
+    ];
+
+    /// Work item with 4 midstates as written by the previous implementation word by word
+    #[rustfmt::skip]
+    const WORK_TX_ITEM_4: [u8; 144] = [
+        0x8c, 0x04, 0x00, 0x00, 0x1e, 0x5b, 0x11, 0x17, 0x2b, 0x1a, 0x8f, 0x5e, 0xff, 0xff, 0xff, 0xff,
+        0x1f, 0x1e, 0x1d, 0x1c, 0x1b, 0x1a, 0x19, 0x18, 0x17, 0x16, 0x15, 0x14, 0x13, 0x12, 0x11, 0x10,
+        0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+        0x3f, 0x3e, 0x3d, 0x3c, 0x3b, 0x3a, 0x39, 0x38, 0x37, 0x36, 0x35, 0x34, 0x33, 0x32, 0x31, 0x30,
+        0x2f, 0x2e, 0x2d, 0x2c, 0x2b, 0x2a, 0x29, 0x28, 0x27, 0x26, 0x25, 0x24, 0x23, 0x22, 0x21, 0x20,
+        0x5f, 0x5e, 0x5d, 0x5c, 0x5b, 0x5a, 0x59, 0x58, 0x57, 0x56, 0x55, 0x54, 0x53, 0x52, 0x51, 0x50,
+        0x4f, 0x4e, 0x4d, 0x4c, 0x4b, 0x4a, 0x49, 0x48, 0x47, 0x46, 0x45, 0x44, 0x43, 0x42, 0x41, 0x40,
+        0x7f, 0x7e, 0x7d, 0x7c, 0x7b, 0x7a, 0x79, 0x78, 0x77, 0x76, 0x75, 0x74, 0x73, 0x72, 0x71, 0x70,
+        0x6f, 0x6e, 0x6d, 0x6c, 0x6b, 0x6a, 0x69, 0x68, 0x67, 0x66, 0x65, 0x64, 0x63, 0x62, 0x61, 0x60,
+    ];
+
     /// Test that we are able to construct HChainFifo instance
     #[test]
     fn test_fifo_initialization() {
@@ -787,6 +1042,47 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_rx_status_decoding() {
+        // reset value of the register
+        assert_eq!(
+            RxStatus::from_bits(0x0000_0001),
+            RxStatus {
+                irq_pending: false,
+                full: false,
+                empty: true,
+            }
+        );
+        assert_eq!(
+            RxStatus::from_bits(0x0000_0012),
+            RxStatus {
+                irq_pending: true,
+                full: true,
+                empty: false,
+            }
+        );
+        // reserved bits are ignored
+        assert_eq!(RxStatus::from_bits(0xffff_ffec), RxStatus::default());
+    }
+
+    #[test]
+    fn test_overflow_detector() {
+        let full = RxStatus::from_bits(0x12);
+        let partial = RxStatus::from_bits(0x10);
+        let mut detector = OverflowDetector::default();
+
+        assert!(!detector.update(partial));
+        assert!(detector.update(full));
+        // FIFO stays full during the same overflow event
+        assert!(!detector.update(full));
+        assert!(!detector.update(partial));
+        assert!(detector.update(full));
+        assert_eq!(detector.take_events(), 2);
+        assert_eq!(detector.take_events(), 0);
+        assert!(!detector.update(full));
+        assert_eq!(detector.take_events(), 0);
+    }
+
     #[test]
     fn test_version_display() {
         let version = Version {
@@ -813,4 +1109,49 @@ mod test {
         let build_id = BuildId(0x5D8255F0);
         assert_eq!(build_id.to_string(), "2019-09-18 16:06:08 UTC");
     }
+
+    /// Prepare work with distinct bytes in all midstates
+    fn prepare_fixture_work(midstate_count: usize) -> work::Assignment {
+        let time = 0x5e8f_1a2b;
+        let job = Arc::new(null_work::NullJob::new(time, 0x1711_5b1e, 0x2000_0000));
+
+        let midstates = (0..midstate_count)
+            .map(|i| {
+                let mut state = [0u8; 32];
+                let len = state.len();
+                for (j, byte) in state.iter_mut().enumerate() {
+                    *byte = (i * len + j) as u8;
+                }
+                work::Midstate {
+                    version: 0,
+                    state: state.into(),
+                }
+            })
+            .collect();
+        work::Assignment::new(job, midstates, time)
+    }
+
+    /// Verify that the assembled work item is the same as the one written by the previous
+    /// implementation (the bytes are in the order in which they are written to the bus)
+    #[test]
+    fn test_work_tx_item() {
+        let fixtures: [(usize, &[u8]); 3] = [
+            (1, &WORK_TX_ITEM_1),
+            (2, &WORK_TX_ITEM_2),
+            (4, &WORK_TX_ITEM_4),
+        ];
+        for (count, fixture) in fixtures.iter() {
+            let item = WorkTxItem::new(
+                MidstateCount::new(*count),
+                &prepare_fixture_work(*count),
+                0x123,
+            );
+            let bytes: Vec<u8> = item
+                .words()
+                .iter()
+                .flat_map(|word| word.to_ne_bytes().to_vec())
+                .collect();
+            assert_eq!(bytes.as_slice(), *fixture, "midstate count {}", count);
+        }
+    }
 }
@@ -47,6 +47,14 @@ impl Type {
             &Type::Command => "cmd-rx",
         }
     }
+
+    /// All IO blocks that have to be present for each hash chain
+    pub const ALL: [Type; 4] = [Type::Common, Type::WorkRx, Type::WorkTx, Type::Command];
+
+    /// Name of UIO device (as found in device-tree) of this type for given hashboard
+    pub fn to_uio_name(&self, hashboard_idx: usize) -> String {
+        format!("chain{}-{}", hashboard_idx, self.as_str())
+    }
 }
 
 impl Device {
@@ -57,7 +65,7 @@ impl Device {
     /// * `uio_type` - type of uio device, determines what IO block to map
     pub fn open(hashboard_idx: usize, uio_type: Type) -> error::Result<Self> {
         assert!(hashboard_idx > 0);
-        let uio_name = format!("chain{}-{}", hashboard_idx, uio_type.as_str());
+        let uio_name = uio_type.to_uio_name(hashboard_idx);
         let uio = uio_async::UioDevice::open_by_name(&uio_name).with_context(|_| {
             ErrorKind::UioDevice(uio_name.clone(), "cannot open uio device".to_string())
         })?;
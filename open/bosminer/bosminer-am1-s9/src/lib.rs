@@ -23,10 +23,13 @@
 
 mod async_i2c;
 pub mod bm1387;
+pub mod brownout;
+pub mod button;
 mod cgminer;
 pub mod command;
 pub mod config;
 pub mod counters;
+pub mod environment;
 pub mod error;
 pub mod fan;
 pub mod gpio;
@@ -37,8 +40,12 @@ pub mod io;
 pub mod monitor;
 pub mod null_work;
 pub mod power;
+pub mod realtime;
 pub mod registry;
+pub mod reload;
+pub mod safe_mode;
 pub mod sensor;
+pub mod status;
 pub mod utils;
 
 #[cfg(test)]
@@ -47,21 +54,27 @@ pub mod test;
 use ii_logging::macros::*;
 
 use bosminer::async_trait;
+use bosminer::client;
 use bosminer::hal::{self, BackendConfig as _};
-use bosminer::node;
+use bosminer::log_id;
+use bosminer::node::{self, WorkSolver as _};
+use bosminer::progress;
 use bosminer::stats;
 use bosminer::work;
 
 use bosminer_macros::WorkSolverNode;
 
 use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-use error::ErrorKind;
+use error::{Classify, ErrorKind};
 use failure::ResultExt;
 
 use futures::channel::mpsc;
+use futures::future::{self, Either};
 use futures::lock::{Mutex, MutexGuard};
 use futures::stream::StreamExt;
 use ii_async_compat::futures;
@@ -78,7 +91,7 @@ use ii_bitcoin::MeetsTarget;
 
 use ii_async_compat::tokio;
 use tokio::sync::watch;
-use tokio::time::delay_for;
+use tokio::time::{self, delay_for};
 
 /// Timing constants
 const INACTIVATE_FROM_CHAIN_DELAY: Duration = Duration::from_millis(100);
@@ -94,6 +107,8 @@ const ENUM_RETRY_COUNT: usize = 10;
 pub const MAX_CHIPS_ON_CHAIN: usize = 64;
 /// Number of chips to consider OK for initialization
 pub const EXPECTED_CHIPS_ON_CHAIN: usize = 63;
+/// Chips on S9 hash boards are powered in series of voltage domains with 3 chips each
+pub const CHIPS_IN_VOLTAGE_DOMAIN: usize = 3;
 
 /// Oscillator speed for all chips on S9 hash boards
 pub const CHIP_OSC_CLK_HZ: usize = 25_000_000;
@@ -112,6 +127,9 @@ const HALT_TIMEOUT: Duration = Duration::from_secs(30);
 /// Core address space size (it should be 114, but the addresses are non-consecutive)
 const CORE_ADR_SPACE_SIZE: usize = 128;
 
+/// Minimal interval between warnings about exceeded latency of the solution RX path
+const RX_LATENCY_WARNING_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Power type alias
 /// TODO: Implement it as a proper type (not just alias)
 pub type Power = usize;
@@ -194,13 +212,15 @@ pub struct HashChain {
     pub common_io: io::Common,
     work_rx_io: Mutex<Option<io::WorkRx>>,
     work_tx_io: Mutex<Option<io::WorkTx>>,
+    /// Dedicated runtime of the RX/TX tasks (when enabled)
+    io_runtime: Mutex<Option<realtime::Runtime>>,
     monitor_tx: mpsc::UnboundedSender<monitor::Message>,
     /// Do not send open-core work if this is true (some tests that test chip initialization may
     /// want to do this).
     disable_init_work: bool,
     /// channels through which temperature status is sent
-    temperature_sender: Mutex<Option<watch::Sender<Option<sensor::Temperature>>>>,
-    temperature_receiver: watch::Receiver<Option<sensor::Temperature>>,
+    temperature_sender: Mutex<Option<watch::Sender<Option<sensor::Readings>>>>,
+    temperature_receiver: watch::Receiver<Option<sensor::Readings>>,
     /// nonce counter
     pub counter: Arc<Mutex<counters::HashChain>>,
     /// halter to stop this hashchain
@@ -210,6 +230,10 @@ pub struct HashChain {
     halt_receiver: halt::Receiver,
     /// Current hashchain settings
     frequency: Mutex<FrequencySettings>,
+    /// Time of the last response (of any kind) received from the hashchain
+    last_activity: stats::Timestamp,
+    /// Requests immediate brown-out check when a command fails (set by `Manager`)
+    brownout_tx: Option<mpsc::UnboundedSender<()>>,
 }
 
 impl HashChain {
@@ -259,6 +283,7 @@ impl HashChain {
             command_context: command::Context::new(command_io),
             work_rx_io: Mutex::new(Some(work_rx_io)),
             work_tx_io: Mutex::new(Some(work_tx_io)),
+            io_runtime: Mutex::new(None),
             monitor_tx,
             disable_init_work: false,
             temperature_sender: Mutex::new(Some(temperature_sender)),
@@ -270,10 +295,12 @@ impl HashChain {
             halt_sender,
             halt_receiver,
             frequency: Mutex::new(FrequencySettings::from_frequency(0)),
+            last_activity: Default::default(),
+            brownout_tx: None,
         })
     }
 
-    pub fn current_temperature(&self) -> Option<sensor::Temperature> {
+    pub fn current_temperature(&self) -> Option<sensor::Readings> {
         self.temperature_receiver.borrow().clone()
     }
 
@@ -359,9 +386,11 @@ impl HashChain {
         &mut self,
         accept_less_chips: bool,
         initial_frequency: &FrequencySettings,
+        progress: &progress::Reporter,
     ) -> error::Result<()> {
         // Reset hashboard, toggle voltage
         info!("Resetting hash board");
+        progress.report(progress::Phase::Probing);
         self.enter_reset()?;
         self.voltage_ctrl.disable_voltage().await?;
         delay_for(INIT_DELAY).await;
@@ -372,7 +401,7 @@ impl HashChain {
 
         // Enumerate chips
         info!("Starting chip enumeration");
-        self.enumerate_chips().await?;
+        self.enumerate_chips(progress).await?;
 
         // Figure out if we found enough chips
         info!("Discovered {} chips", self.chip_count);
@@ -389,7 +418,8 @@ impl HashChain {
         }
 
         // set PLL
-        self.set_pll(initial_frequency).await?;
+        self.set_pll_with_progress(initial_frequency, Some(progress))
+            .await?;
 
         // configure the hashing chain to operate at desired baud rate. Note that gate block is
         // enabled to allow continuous start of chips in the chain
@@ -407,11 +437,14 @@ impl HashChain {
     /// * if enumeration fails (for enumeration-related reason), try to retry
     ///   it up to pre-defined number of times
     /// * if less than 63 chips is found, retry the enumeration
+    ///
+    /// Each phase of the initialization is reported to `progress`.
     async fn init(
         &mut self,
         initial_frequency: &FrequencySettings,
         initial_voltage: power::Voltage,
         accept_less_chips: bool,
+        progress: &progress::Reporter,
     ) -> error::Result<Arc<Mutex<registry::WorkRegistry>>> {
         info!("Hashboard IP core initialized");
         self.voltage_ctrl
@@ -426,7 +459,7 @@ impl HashChain {
         self.ip_core_init().await?;
 
         // Enumerate chips
-        self.reset_and_enumerate_and_init(accept_less_chips, initial_frequency)
+        self.reset_and_enumerate_and_init(accept_less_chips, initial_frequency, progress)
             .await?;
 
         // Build shared work registry
@@ -456,7 +489,7 @@ impl HashChain {
     }
 
     /// Detects the number of chips on the hashing chain and assigns an address to each chip
-    async fn enumerate_chips(&mut self) -> error::Result<()> {
+    async fn enumerate_chips(&mut self, progress: &progress::Reporter) -> error::Result<()> {
         // Enumerate all chips (broadcast read address register request)
         let responses = self
             .command_context
@@ -507,6 +540,10 @@ impl HashChain {
             self.command_context
                 .send_raw_command(cmd.pack().to_vec(), false)
                 .await;
+            progress.report(progress::Phase::Enumerating {
+                chip: i + 1,
+                chips: self.chip_count,
+            });
         }
 
         Ok(())
@@ -540,21 +577,37 @@ impl HashChain {
     ///
     /// Takes care of adjusting `work_time`
     pub async fn set_pll(&self, frequency: &FrequencySettings) -> error::Result<()> {
+        self.set_pll_with_progress(frequency, None).await
+    }
+
+    /// Load PLL register of all chips and report each loaded register to `progress`
+    async fn set_pll_with_progress(
+        &self,
+        frequency: &FrequencySettings,
+        progress: Option<&progress::Reporter>,
+    ) -> error::Result<()> {
         // TODO: find a better way - how to communicate with frequency setter how many chips we have?
         assert!(frequency.chip.len() >= self.chip_count);
+        let report = |step, steps| {
+            if let Some(progress) = progress {
+                progress.report(progress::Phase::SettingFrequency { step, steps });
+            }
+        };
 
         // Check if the frequencies are identical
         if frequency.min() == frequency.max() {
             // Update them in one go
             self.set_chip_pll(ChipAddress::All, frequency.chip[0])
                 .await?;
+            report(1, 1);
         } else {
             // Update chips one-by-one
             for i in 0..self.chip_count {
-                let new_freq = self.frequency.lock().await.chip[i];
-                if new_freq != frequency.chip[i] {
+                let new_freq = frequency.chip[i];
+                if new_freq != self.frequency.lock().await.chip[i] {
                     self.set_chip_pll(ChipAddress::One(i), new_freq).await?;
                 }
+                report(i + 1, self.chip_count);
             }
         }
 
@@ -624,6 +677,13 @@ impl HashChain {
         Ok(())
     }
 
+    /// Count chips which respond to broadcast read of the address register
+    pub async fn count_responding_chips(&self) -> error::Result<usize> {
+        self.command_context
+            .count_responses::<bm1387::GetAddressReg>()
+            .await
+    }
+
     pub fn get_chip_count(&self) -> usize {
         self.chip_count
     }
@@ -649,26 +709,78 @@ impl HashChain {
         }
     }
 
+    /// Wait for room in TX fifo and take the next work from the generator
+    async fn next_work(
+        tx_fifo: &io::WorkTx,
+        work_generator: &mut work::Generator,
+    ) -> Option<work::Assignment> {
+        tx_fifo.wait_for_room().await.expect("wait for tx room");
+        work_generator.generate().await
+    }
+
     /// This task picks up work from frontend (via generator), saves it to
     /// registry (to pair with `Assignment` later) and sends it out to hw.
     /// It makes sure that TX fifo is empty before requesting work from
-    /// generator.
+    /// generator. Work which is too old by the time it would be sent out is skipped.
+    /// Work queued in TX fifo is flushed as soon as the engine channel is invalidated so that
+    /// the hash chain does not mine stale work until the fifo is drained.
+    /// Time of submitting each work to the fifo is accounted in `counter`.
     /// It exits when generator returns `None`.
     async fn work_tx_task(
         work_registry: Arc<Mutex<registry::WorkRegistry>>,
         mut tx_fifo: io::WorkTx,
         mut work_generator: work::Generator,
+        counter: Arc<Mutex<counters::HashChain>>,
     ) {
+        let mut invalidations = work_generator.invalidation_receiver();
+        let mut invalidation = invalidations.current();
         loop {
-            tx_fifo.wait_for_room().await.expect("wait for tx room");
-            let work = work_generator.generate().await;
+            let next = {
+                let next_work = Self::next_work(&tx_fifo, &mut work_generator);
+                let invalidated = invalidations.wait_for_change(invalidation);
+                futures::pin_mut!(next_work, invalidated);
+                match future::select(next_work, invalidated).await {
+                    Either::Left((work, _)) => Ok(work),
+                    Either::Right((current, _)) => Err(current),
+                }
+            };
+            let work = match next {
+                Err(current) => {
+                    trace!("Flushing work invalidated in TX fifo");
+                    invalidation = current;
+                    tx_fifo.flush();
+                    continue;
+                }
+                Ok(work) => work,
+            };
             match work {
                 None => return,
                 Some(work) => {
+                    // work generated before the invalidation is dropped
+                    if !work_generator.is_current(&work) {
+                        work_generator.account_skipped(&work);
+                        continue;
+                    }
+                    if work.invalidation() != invalidation {
+                        // the invalidation has not been noticed yet so the fifo still contains
+                        // stale work
+                        invalidation = work.invalidation();
+                        tx_fifo.flush();
+                    }
+                    if !work_generator.is_fresh(&work).await {
+                        work_generator.account_skipped(&work);
+                        continue;
+                    }
+                    // the work is shared with the registry and all its solutions
+                    let work = Arc::new(work);
                     // assign `work_id` to `work`
                     let work_id = work_registry.lock().await.store_work(work.clone(), false);
                     // send work is synchronous
                     tx_fifo.send_work(&work, work_id).expect("send work");
+                    if let Some(send_time) = tx_fifo.take_send_time() {
+                        counter.lock().await.tx_latency.add(send_time);
+                    }
+                    work_generator.account_dispatched(&work).await;
                 }
             }
         }
@@ -679,6 +791,7 @@ impl HashChain {
     /// sends them back to frontend (via `solution_sender`).
     /// If solution is duplicated, it gets dropped (and errors stats incremented).
     /// It prints warnings when solution doesn't hit ASIC target.
+    /// It also accounts FIFO overflows and latency of draining the FIFO after IRQ.
     /// TODO: this task is not very platform dependent, maybe move it somewhere else?
     /// TODO: figure out when and how to stop this task
     async fn solution_rx_task(
@@ -687,12 +800,44 @@ impl HashChain {
         mut rx_fifo: io::WorkRx,
         solution_sender: work::SolutionSender,
         counter: Arc<Mutex<counters::HashChain>>,
+        latency_budget: Duration,
     ) {
+        let mut last_latency_warning: Option<Instant> = None;
         // solution receiving/filtering part
         loop {
             let (rx_fifo_out, hw_solution) =
                 rx_fifo.recv_solution().await.expect("recv solution failed");
             rx_fifo = rx_fifo_out;
+            self.last_activity.touch(None).await;
+
+            let overflows = rx_fifo.take_overflow_events();
+            if overflows > 0 {
+                counter.lock().await.fifo_overflows += overflows;
+                warn!(
+                    "Hash chain {}: solution FIFO overflowed, some nonces have been lost",
+                    self.hashboard_idx
+                );
+            }
+            if let Some(latency) = rx_fifo.take_drain_latency() {
+                let over_budget = counter
+                    .lock()
+                    .await
+                    .rx_latency
+                    .record(latency, latency_budget);
+                if over_budget
+                    && last_latency_warning
+                        .map_or(true, |last| last.elapsed() >= RX_LATENCY_WARNING_INTERVAL)
+                {
+                    warn!(
+                        "Hash chain {}: draining solution FIFO took {} ms (budget {} ms)",
+                        self.hashboard_idx,
+                        latency.as_millis(),
+                        latency_budget.as_millis()
+                    );
+                    last_latency_warning = Some(Instant::now());
+                }
+            }
+
             let work_id = hw_solution.hardware_id;
             let solution = Solution::from_hw_solution(&hw_solution, self.asic_target);
             let mut work_registry = work_registry.lock().await;
@@ -710,12 +855,20 @@ impl HashChain {
                     // work item detected a new unique solution, we will push it for further processing
                     if let Some(unique_solution) = status.unique_solution {
                         if !status.duplicate {
-                            let hash = unique_solution.hash();
-                            if !hash.meets(unique_solution.backend_target()) {
-                                info!("Solution from hashchain not hitting ASIC target; {}", hash);
-                                counter.lock().await.add_error(core_addr);
-                            } else {
-                                counter.lock().await.add_valid(core_addr);
+                            // Malformed solution cannot be hashed, the solution sender accounts
+                            // and drops it
+                            if !unique_solution.is_malformed() {
+                                let hash = unique_solution.hash();
+                                if !hash.meets(unique_solution.backend_target()) {
+                                    info!(
+                                        "{} Solution from hashchain not hitting ASIC target; {}",
+                                        log_id::Prefix::solution(&unique_solution),
+                                        hash
+                                    );
+                                    counter.lock().await.add_error(core_addr);
+                                } else {
+                                    counter.lock().await.add_valid(core_addr);
+                                }
                             }
                             solution_sender.send(unique_solution);
                         }
@@ -739,31 +892,14 @@ impl HashChain {
 
     async fn try_to_initialize_sensor(
         command_context: command::Context,
-    ) -> error::Result<Box<dyn sensor::Sensor>> {
+    ) -> error::Result<sensor::Manager> {
         // construct I2C bus via command interface
         let i2c_bus = bm1387::i2c::Bus::new_and_init(command_context, TEMP_CHIP)
             .await
             .with_context(|_| ErrorKind::Sensors("bus construction failed".into()))?;
 
-        // try to probe sensor
-        let sensor = sensor::probe_i2c_sensors(i2c_bus)
-            .await
-            .with_context(|_| ErrorKind::Sensors("error when probing sensors".into()))?;
-
-        // did we find anything?
-        let mut sensor = match sensor {
-            Some(sensor) => sensor,
-            None => Err(ErrorKind::Sensors("no sensors found".into()))?,
-        };
-
-        // try to initialize sensor
-        sensor
-            .init()
-            .await
-            .with_context(|_| ErrorKind::Sensors("failed to initialize sensors".into()))?;
-
-        // done
-        Ok(sensor)
+        // probe and initialize all sensors
+        sensor::Manager::probe(i2c_bus).await
     }
 
     /// Monitor watchdog task.
@@ -790,9 +926,9 @@ impl HashChain {
         // a hashing chip only if the hashchain allows it (hashchain is in operation etc.)
         delay_for(Duration::from_secs(5)).await;
 
-        // Try to probe sensor
-        // This may fail - in which case we put `None` into `sensor`
-        let mut sensor = match Self::try_to_initialize_sensor(self.command_context.clone())
+        // Try to probe sensors
+        // This may fail - in which case we put `None` into `sensors`
+        let mut sensors = match Self::try_to_initialize_sensor(self.command_context.clone())
             .await
             .with_context(|_| ErrorKind::Hashboard(self.hashboard_idx, "sensor error".into()))
             .map_err(|e| e.into())
@@ -801,33 +937,32 @@ impl HashChain {
                 error!("Sensor probing failed: {}", e);
                 None
             }
-            error::Result::Ok(sensor) => Some(sensor),
+            error::Result::Ok(sensors) => Some(sensors),
         };
 
         // "Watchdog" loop that pings monitor every some seconds
         loop {
-            // If we have temperature sensor, try to read it
-            let temp = if let Some(sensor) = sensor.as_mut() {
-                match sensor
-                    .read_temperature()
+            // If we have temperature sensors, try to read them
+            let temp = if let Some(sensors) = sensors.as_mut() {
+                let now = Instant::now();
+                if let Err(e) = sensors
+                    .update(now)
                     .await
                     .with_context(|_| {
                         ErrorKind::Hashboard(self.hashboard_idx, "temperature read fail".into())
                     })
-                    .map_err(|e| e.into())
+                    .map_err(error::Error::from)
                 {
-                    error::Result::Ok(temp) => {
-                        info!("Measured temperature: {:?}", temp);
-                        temp
-                    }
-                    error::Result::Err(e) => {
-                        error!("Sensor temperature read failed: {}", e);
-                        sensor::INVALID_TEMPERATURE_READING
+                    error!("Sensor temperature read failed: {}", e);
+                    // chips behind the sensor may be missing
+                    if let Some(brownout_tx) = self.brownout_tx.as_ref() {
+                        brownout_tx.unbounded_send(()).ok();
                     }
                 }
+                sensors.readings(now)
             } else {
                 // Otherwise just make empty temperature reading
-                sensor::INVALID_TEMPERATURE_READING
+                sensor::Readings::default()
             };
 
             // Broadcast
@@ -872,35 +1007,74 @@ impl HashChain {
         }
     }
 
+    /// Spawn time critical IO task on the dedicated runtime (if any)
+    async fn spawn_io_task<F>(&self, name: &str, io_handle: Option<&tokio::runtime::Handle>, f: F)
+    where
+        F: Future<Output = ()> + 'static + Send,
+    {
+        let halt_client = self.halt_receiver.register_client(name.into()).await;
+        match io_handle {
+            Some(io_handle) => halt_client.spawn_on(io_handle, f),
+            None => halt_client.spawn(f),
+        }
+    }
+
     async fn start(
         self: Arc<Self>,
         work_generator: work::Generator,
         solution_sender: work::SolutionSender,
         work_registry: Arc<Mutex<registry::WorkRegistry>>,
+        io_config: realtime::Config,
     ) {
-        // spawn tx task
-        let tx_fifo = self.take_work_tx_io().await;
-        self.halt_receiver
-            .register_client("work-tx".into())
-            .await
-            .spawn(Self::work_tx_task(
-                work_registry.clone(),
-                tx_fifo,
-                work_generator,
-            ));
+        let io_runtime = if io_config.enabled {
+            match realtime::Runtime::start(format!("io-chain-{}", self.hashboard_idx), &io_config) {
+                Ok(io_runtime) => Some(io_runtime),
+                Err(e) => {
+                    warn!(
+                        "Hash chain {}: cannot start IO runtime, IO tasks share the main one: {}",
+                        self.hashboard_idx, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let io_handle = io_runtime
+            .as_ref()
+            .map(|io_runtime| io_runtime.handle().clone());
+        *self.io_runtime.lock().await = io_runtime;
 
-        // spawn rx task
+        // spawn rx task before the tx one so that solutions of the very first work are
+        // collected
         let rx_fifo = self.take_work_rx_io().await;
-        self.halt_receiver
-            .register_client("work-rx".into())
-            .await
-            .spawn(Self::solution_rx_task(
+        self.spawn_io_task(
+            "work-rx",
+            io_handle.as_ref(),
+            Self::solution_rx_task(
                 self.clone(),
                 work_registry.clone(),
                 rx_fifo,
                 solution_sender,
                 self.counter.clone(),
-            ));
+                io_config.latency_budget,
+            ),
+        )
+        .await;
+
+        // spawn tx task
+        let tx_fifo = self.take_work_tx_io().await;
+        self.spawn_io_task(
+            "work-tx",
+            io_handle.as_ref(),
+            Self::work_tx_task(
+                work_registry.clone(),
+                tx_fifo,
+                work_generator,
+                self.counter.clone(),
+            ),
+        )
+        .await;
 
         // spawn hashrate monitor
         // Disabled until we found a use for this
@@ -970,6 +1144,17 @@ impl FrequencySettings {
         self.chip.resize(chip_count, 0);
     }
 
+    /// Build frequency settings with each chip running at `percent` of its frequency
+    pub fn scaled(&self, percent: usize) -> Self {
+        Self {
+            chip: self
+                .chip
+                .iter()
+                .map(|frequency| frequency / 100 * percent)
+                .collect(),
+        }
+    }
+
     pub fn total(&self) -> u64 {
         self.chip.iter().fold(0, |total_f, &f| total_f + f as u64)
     }
@@ -1034,6 +1219,11 @@ impl StoppedChain {
         StoppedChain { manager }
     }
 
+    /// Change number of midstates used by the hashchain from its next start
+    pub async fn set_midstate_count(&self, midstate_count: MidstateCount) {
+        self.manager.inner.lock().await.midstate_count = midstate_count;
+    }
+
     pub async fn start(
         self,
         initial_frequency: &FrequencySettings,
@@ -1042,6 +1232,7 @@ impl StoppedChain {
     ) -> Result<RunningChain, (Self, error::Error)> {
         // if miner initialization fails, retry
         let mut tries_left = ENUM_RETRY_COUNT;
+        self.manager.progress.begin();
 
         loop {
             info!(
@@ -1073,11 +1264,24 @@ impl StoppedChain {
                 }
                 // start failed
                 Err(e) => {
-                    error!("Chain {} start failed: {}", self.manager.hashboard_idx, e);
+                    error!(
+                        "Chain {} start failed: {}",
+                        self.manager.hashboard_idx,
+                        e.chain()
+                    );
+                    self.manager.report_error(stats::HealthIssue::NoStart);
 
                     // retry if possible
-                    if tries_left == 0 {
+                    if !e.is_transient() {
+                        error!(
+                            "Chain {} start cannot be retried",
+                            self.manager.hashboard_idx
+                        );
+                        self.manager.progress.report(progress::Phase::Failed);
+                        return Err((self, e));
+                    } else if tries_left == 0 {
                         error!("No tries left");
+                        self.manager.progress.report(progress::Phase::Failed);
                         return Err((self, e.into()));
                     } else {
                         tries_left -= 1;
@@ -1198,7 +1402,7 @@ impl RunningChain {
             .await
     }
 
-    pub async fn current_temperature(&self) -> Option<sensor::Temperature> {
+    pub async fn current_temperature(&self) -> Option<sensor::Readings> {
         self.manager
             .inner
             .lock()
@@ -1287,6 +1491,8 @@ pub struct ManagerInner {
     pub hash_chain: Option<Arc<HashChain>>,
     /// Each (attempted) hashchain start increments this counter by 1
     pub start_count: usize,
+    /// Number of midstates the hashchain is started with
+    pub midstate_count: MidstateCount,
 }
 
 /// Hashchain manager that can start and stop instances of hashchain
@@ -1302,7 +1508,6 @@ pub struct Manager {
     plug_pin: PlugPin,
     reset_pin: ResetPin,
     voltage_ctrl_backend: Arc<power::I2cBackend>,
-    midstate_count: MidstateCount,
     /// channel to report to the monitor
     monitor_tx: mpsc::UnboundedSender<monitor::Message>,
     /// TODO: wrap this type in a structure (in Monitor)
@@ -1310,6 +1515,13 @@ pub struct Manager {
     owned_by: StdMutex<Option<&'static str>>,
     pub inner: Mutex<ManagerInner>,
     pub chain_config: config::ResolvedChainConfig,
+    /// Context for reporting of the initialization progress
+    progress: progress::Reporter,
+    /// Channel for requesting immediate brown-out check
+    brownout_tx: mpsc::UnboundedSender<()>,
+    brownout_rx: Mutex<Option<mpsc::UnboundedReceiver<()>>>,
+    /// Frequency of chips to be restored when the paused hashchain is resumed
+    paused_frequency: Mutex<Option<FrequencySettings>>,
 }
 
 impl Manager {
@@ -1365,15 +1577,21 @@ impl Manager {
             self.plug_pin.clone(),
             self.voltage_ctrl_backend.clone(),
             self.hashboard_idx,
-            self.midstate_count,
+            inner.midstate_count,
             asic_difficulty,
             self.monitor_tx.clone(),
         )
         .expect("BUG: hashchain instantiation failed");
+        hash_chain.brownout_tx = Some(self.brownout_tx.clone());
 
         // initialize it
         let work_registry = match hash_chain
-            .init(initial_frequency, initial_voltage, accept_less_chips)
+            .init(
+                initial_frequency,
+                initial_voltage,
+                accept_less_chips,
+                &self.progress,
+            )
             .await
         {
             Err(e) => {
@@ -1389,19 +1607,28 @@ impl Manager {
             Ok(a) => a,
         };
 
+        // chains with higher nominal hash rate receive new jobs first when they are staggered
+        let freq_sum = hash_chain.frequency.lock().await.total();
+        self.work_generator
+            .set_fan_out_weight(freq_sum * (bm1387::NUM_CORES_ON_CHIP as u64));
+        let mut work_generator = self.work_generator.clone();
+        work_generator.set_assignment_max_age(self.chain_config.assignment_max_age);
+
         // spawn worker tasks for hash chain and start mining
         let hash_chain = Arc::new(hash_chain);
         hash_chain
             .clone()
             .start(
-                self.work_generator.clone(),
+                work_generator,
                 self.solution_sender.clone(),
                 work_registry,
+                self.chain_config.io.clone(),
             )
             .await;
 
         // remember we started
         inner.hash_chain.replace(hash_chain);
+        self.progress.report(progress::Phase::Ready);
 
         Ok(())
     }
@@ -1430,6 +1657,82 @@ impl Manager {
     async fn termination_handler(self: Arc<Self>) {
         self.stop_chain(true).await;
     }
+
+    /// Periodically check the chain for missing chips and try to recover it. The check is done
+    /// earlier when the chain reports a command failure.
+    async fn brownout_task(self: Arc<Self>) {
+        let mut brownout_rx = self
+            .brownout_rx
+            .lock()
+            .await
+            .take()
+            .expect("BUG: brown-out receiver missing");
+        let mut supervisor = brownout::Supervisor::new(self, Default::default());
+        loop {
+            // both the timeout and the request trigger the check
+            let _ = time::timeout(brownout::CHECK_INTERVAL, brownout_rx.next()).await;
+            supervisor.check(Instant::now()).await;
+            // drop requests which came during the check
+            while let Ok(Some(_)) = brownout_rx.try_next() {}
+        }
+    }
+}
+
+#[async_trait]
+impl brownout::Chain for Arc<Manager> {
+    async fn enumerated_chips(&self) -> Option<usize> {
+        let inner = self.inner.lock().await;
+        inner
+            .hash_chain
+            .as_ref()
+            .map(|hash_chain| hash_chain.chip_count)
+    }
+
+    async fn responding_chips(&self) -> error::Result<Option<usize>> {
+        let inner = self.inner.lock().await;
+        match inner.hash_chain.as_ref() {
+            Some(hash_chain) => Ok(Some(hash_chain.count_responding_chips().await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn restart(&self, frequency_percent: Option<usize>) -> error::Result<usize> {
+        let running_chain = match self.clone().acquire("brown-out recovery").await {
+            Ok(ChainStatus::Running(running_chain)) => running_chain,
+            Ok(ChainStatus::Stopped(_)) => Err(ErrorKind::Hashboard(
+                self.hashboard_idx,
+                "chain stopped before recovery".into(),
+            ))?,
+            Err(owner) => Err(ErrorKind::Hashboard(
+                self.hashboard_idx,
+                format!("cannot recover hashchain owned by '{}'", owner),
+            ))?,
+        };
+        let frequency = match frequency_percent {
+            Some(percent) => self.chain_config.frequency.scaled(percent),
+            None => {
+                let mut frequency = running_chain.get_frequency().await;
+                // frequency of chips which have not been enumerated is not known
+                let avg = frequency.avg();
+                frequency.chip.resize(EXPECTED_CHIPS_ON_CHAIN, avg);
+                frequency
+            }
+        };
+        let voltage = running_chain.get_voltage().await;
+        let asic_difficulty = running_chain.asic_difficulty;
+
+        info!(
+            "Chain {}: restarting to recover from brown-out at {}",
+            self.hashboard_idx, frequency
+        );
+        let _running_chain = running_chain
+            .stop()
+            .await
+            .start(&frequency, voltage, asic_difficulty)
+            .await
+            .map_err(|(_, e)| e)?;
+        Ok(self.enumerated_chips().await.unwrap_or_default())
+    }
 }
 
 #[async_trait]
@@ -1448,6 +1751,133 @@ impl node::WorkSolver for Manager {
             None => None,
         }
     }
+
+    async fn get_last_activity(&self) -> Option<SystemTime> {
+        let inner = self.inner.lock().await;
+        match inner.hash_chain.as_ref() {
+            Some(hash_chain) => hash_chain
+                .last_activity
+                .take_snapshot()
+                .await
+                .map(|time| *time),
+            None => None,
+        }
+    }
+
+    async fn get_status(&self) -> node::Status {
+        let inner = self.inner.lock().await;
+        match inner.hash_chain.as_ref() {
+            Some(hash_chain) if hash_chain.chip_count < EXPECTED_CHIPS_ON_CHAIN => {
+                node::Status::Degraded
+            }
+            Some(_) => node::Status::Ok,
+            // hash chain is stopped or it has failed to start
+            None => node::Status::Failed,
+        }
+    }
+
+    async fn topology(&self) -> Option<node::Topology> {
+        let inner = self.inner.lock().await;
+        inner.hash_chain.as_ref().map(|hash_chain| {
+            node::Topology::new(
+                hash_chain.chip_count,
+                node::Addressing::Linear {
+                    step: bm1387::CHIP_ADDRESS_STEP,
+                },
+            )
+            .with_domains(EXPECTED_CHIPS_ON_CHAIN / CHIPS_IN_VOLTAGE_DOMAIN)
+        })
+    }
+
+    async fn backend_stats(&self) -> Option<serde_json::Value> {
+        let inner = self.inner.lock().await;
+        let hash_chain = inner.hash_chain.as_ref()?;
+        let frequency = hash_chain.get_frequency().await;
+        let readings = hash_chain.current_temperature().unwrap_or_default();
+        let sensors: Vec<_> = readings
+            .channels
+            .iter()
+            .map(|reading| {
+                serde_json::json!({
+                    "Label": reading.label,
+                    "Location": reading.location.to_string(),
+                    "Temp": reading.value,
+                    "Fault": reading.fault.map(|fault| fault.to_string()),
+                    "Stale": reading.stale,
+                })
+            })
+            .collect();
+        let (fifo_overflows, rx_latency, tx_latency) = {
+            let counter = hash_chain.counter.lock().await;
+            (
+                counter.fifo_overflows,
+                counter.rx_latency,
+                counter.tx_latency,
+            )
+        };
+
+        Some(serde_json::json!({
+            "Chain": self.hashboard_idx,
+            "Chips": hash_chain.chip_count,
+            "Cores": hash_chain.chip_count * bm1387::NUM_CORES_ON_CHIP,
+            "Frequency": frequency.avg(),
+            "Frequency Min": frequency.min(),
+            "Frequency Max": frequency.max(),
+            "Voltage": hash_chain.get_voltage().await.as_volts(),
+            "Temp Board": readings.max(sensor::Location::Board),
+            "Temp Chip": readings.max(sensor::Location::Chip),
+            "Temp Sensors": sensors,
+            "Work TX Time": tx_latency.mean().map(|mean| mean.as_secs_f64()),
+            "Work TX Time Max": tx_latency.max.as_secs_f64(),
+            "Solution RX Latency": rx_latency.mean().map(|mean| mean.as_secs_f64()),
+            "Solution RX Latency Max": rx_latency.max.as_secs_f64(),
+            "Solution RX Over Budget": rx_latency.over_budget,
+            "FIFO Overflows": fifo_overflows,
+        }))
+    }
+
+    /// Paused hashchain is kept running at the minimal frequency so that it can be resumed
+    /// without re-initialization. The original frequency of each chip is restored on resume.
+    async fn set_power_saving(&self, enabled: bool) -> bosminer::Result<()> {
+        let mut paused_frequency = self.paused_frequency.lock().await;
+        let inner = self.inner.lock().await;
+        let hash_chain = match inner.hash_chain.as_ref() {
+            Some(hash_chain) => hash_chain,
+            None => {
+                // hashchain is started again with the configured frequency
+                paused_frequency.take();
+                return Ok(());
+            }
+        };
+
+        if enabled {
+            if paused_frequency.is_none() {
+                let frequency = hash_chain.get_frequency().await;
+                info!(
+                    "Chain {}: lowering frequency from {} while paused",
+                    self.hashboard_idx, frequency
+                );
+                hash_chain
+                    .set_pll(&FrequencySettings::from_frequency(
+                        bm1387::PllFrequency::MIN_FREQ_HZ,
+                    ))
+                    .await
+                    .map_err(bosminer::error::backend::from_error)?;
+                paused_frequency.replace(frequency);
+            }
+        } else if let Some(frequency) = paused_frequency.as_ref() {
+            info!(
+                "Chain {}: restoring frequency {} after pause",
+                self.hashboard_idx, frequency
+            );
+            hash_chain
+                .set_pll(frequency)
+                .await
+                .map_err(bosminer::error::backend::from_error)?;
+            paused_frequency.take();
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Debug for Manager {
@@ -1462,6 +1892,91 @@ impl fmt::Display for Manager {
     }
 }
 
+/// Change number of midstates (AsicBoost) of a running miner.
+///
+/// All running hashchains are stopped first so that no work with unexpected number of midstates
+/// reaches the hardware. Then the frontend is switched to generate work with the new number of
+/// midstates and the hashchains are started again with their previous settings. The function
+/// returns when all hashchains have been started and it fails when any of them cannot be
+/// started. Solutions for work generated before the change carry their own midstates so they are
+/// processed correctly.
+pub async fn reconfigure_midstate_count(
+    managers: &[Arc<Manager>],
+    client_manager: &client::Manager,
+    midstate_count: MidstateCount,
+) -> error::Result<()> {
+    const OWNER_NAME: &str = "midstate reconfiguration";
+
+    // Acquire all hashchains first so that the change is not applied only partially
+    let mut chains = Vec::with_capacity(managers.len());
+    for manager in managers {
+        let chain = manager.clone().acquire(OWNER_NAME).await.map_err(|owner| {
+            ErrorKind::Hashboard(
+                manager.hashboard_idx,
+                format!("cannot reconfigure hashchain owned by '{}'", owner),
+            )
+        })?;
+        chains.push(chain);
+    }
+
+    // Quiesce running hashchains and remember their settings for re-initialization
+    let mut stopped_chains = Vec::with_capacity(chains.len());
+    for chain in chains {
+        let (stopped_chain, settings) = match chain {
+            ChainStatus::Running(running_chain) => {
+                let settings = (
+                    running_chain.get_frequency().await,
+                    running_chain.get_voltage().await,
+                    running_chain.asic_difficulty,
+                );
+                (running_chain.stop().await, Some(settings))
+            }
+            ChainStatus::Stopped(stopped_chain) => (stopped_chain, None),
+        };
+        stopped_chain.set_midstate_count(midstate_count).await;
+        stopped_chains.push((stopped_chain, settings));
+    }
+
+    client_manager
+        .set_midstate_count(midstate_count.to_count())
+        .await;
+    info!(
+        "Midstate count changed to {}, re-initializing hashchains",
+        midstate_count.to_count()
+    );
+
+    // Hashchains are started concurrently because each start may be retried several times
+    let restarts = stopped_chains
+        .into_iter()
+        .filter_map(|(stopped_chain, settings)| {
+            settings.map(|(frequency, voltage, asic_difficulty)| async move {
+                let hashboard_idx = stopped_chain.manager.hashboard_idx;
+                stopped_chain
+                    .start(&frequency, voltage, asic_difficulty)
+                    .await
+                    .map(|_| ())
+                    .map_err(|(_, e)| (hashboard_idx, e))
+            })
+        });
+    let mut failed_chains = vec![];
+    for result in future::join_all(restarts).await {
+        if let Err((hashboard_idx, e)) = result {
+            error!(
+                "Chain {} re-initialization after midstate change failed: {}",
+                hashboard_idx, e
+            );
+            failed_chains.push(hashboard_idx.to_string());
+        }
+    }
+    if !failed_chains.is_empty() {
+        Err(ErrorKind::General(format!(
+            "re-initialization of chains {} failed",
+            failed_chains.join(", ")
+        )))?;
+    }
+    Ok(())
+}
+
 /// Represents solution from the hardware combined with difficulty
 #[derive(Clone, Debug)]
 pub struct Solution {
@@ -1473,6 +1988,10 @@ pub struct Solution {
     solution_idx: usize,
     /// Target to which was this solution solved
     target: ii_bitcoin::Target,
+    /// Time when the solution has been read from the solution FIFO
+    timestamp: Instant,
+    /// Time spent in the solution FIFO
+    age_hint: Option<Duration>,
 }
 
 impl Solution {
@@ -1482,6 +2001,8 @@ impl Solution {
             midstate_idx: hw.midstate_idx,
             solution_idx: hw.solution_idx,
             target,
+            timestamp: hw.timestamp,
+            age_hint: hw.age,
         }
     }
 }
@@ -1506,18 +2027,33 @@ impl hal::BackendSolution for Solution {
     fn target(&self) -> &ii_bitcoin::Target {
         &self.target
     }
+
+    #[inline]
+    fn age_hint(&self) -> Option<Duration> {
+        self.age_hint
+    }
+
+    // NOTE: `hw_timestamp` is not provided because s9-io IP core has no timestamp registers for
+    // work nor solutions (there is only `WORK_TX_LAST_ID`). The latency of solutions is measured
+    // in software from the dispatch of the work to the TX FIFO.
 }
 
-#[derive(Debug, WorkSolverNode)]
+#[derive(WorkSolverNode)]
 pub struct Backend {
     #[member_work_solver_stats]
     work_solver_stats: stats::BasicWorkSolver,
+    /// Halts the whole miner when it is being shut down
+    app_halt_sender: StdMutex<Option<Arc<halt::Sender>>>,
+    /// The program exits when the miner is halted unless it is only being stopped for restart
+    exit_on_halt: Arc<AtomicBool>,
 }
 
 impl Backend {
     pub fn new() -> Self {
         Self {
             work_solver_stats: Default::default(),
+            app_halt_sender: StdMutex::new(None),
+            exit_on_halt: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -1534,6 +2070,14 @@ impl Backend {
         Ok(detected)
     }
 
+    /// Detect present hash boards and probe the environment without touching the hash chains
+    pub fn check_hardware() -> error::Result<environment::HardwareReport> {
+        let gpio_mgr = gpio::ControlPinManager::new();
+        let hashboards = Self::detect_hashboards(&gpio_mgr)?;
+        let environment = environment::Environment::probe(&environment::HostSystem, &hashboards);
+        Ok(environment::HardwareReport::new(hashboards, environment))
+    }
+
     /// Miner termination handler called when app is shutdown.
     /// Just propagate the shutdown to all hashchain managers
     async fn termination_handler(halt_sender: Arc<halt::Sender>) {
@@ -1583,6 +2127,7 @@ impl Backend {
         hooks.monitor_started(monitor.clone()).await;
 
         let voltage_ctrl_backend = Arc::new(power::I2cBackend::new(0));
+        let progress_registry = backend_config.progress.clone().unwrap_or_default();
         let mut managers = Vec::new();
         info!(
             "Initializing miner, enabled_chains={:?}, midstate_count={}",
@@ -1597,6 +2142,7 @@ impl Backend {
             let chain_config = backend_config.resolve_chain_config(hashboard_idx);
 
             let status_receiver = monitor.status_receiver.clone();
+            let (brownout_tx, brownout_rx) = mpsc::unbounded();
 
             // build hashchain_node for statistics and static parameters
             let manager = work_hub
@@ -1612,7 +2158,6 @@ impl Backend {
                             .expect("failed to make pin"),
                         voltage_ctrl_backend: voltage_ctrl_backend.clone(),
                         hashboard_idx,
-                        midstate_count: chain_config.midstate_count,
                         work_solver_stats: Default::default(),
                         solution_sender,
                         work_generator,
@@ -1622,8 +2167,13 @@ impl Backend {
                         inner: Mutex::new(ManagerInner {
                             hash_chain: None,
                             start_count: 0,
+                            midstate_count: chain_config.midstate_count,
                         }),
                         chain_config,
+                        progress: progress_registry.reporter(hashboard_idx),
+                        brownout_tx,
+                        brownout_rx: Mutex::new(Some(brownout_rx)),
+                        paused_frequency: Mutex::new(None),
                     }
                 })
                 .await;
@@ -1644,6 +2194,10 @@ impl Backend {
                 .register_client("hashchain".into())
                 .await
                 .spawn_halt_handler(Manager::termination_handler(manager.clone()));
+            halt_receiver
+                .register_client("brown-out monitor".into())
+                .await
+                .spawn(Manager::brownout_task(manager.clone()));
 
             // Suppress haschain start if chain is either not enabled or haschain hook doesn't
             // want us to start it (default `NoHooks` has all chains enabled).
@@ -1692,30 +2246,85 @@ impl hal::Backend for Backend {
             .take()
             .expect("BUG: missing client manager");
         let group_configs = backend_config.groups.take();
+        // Keep client manager for runtime reconfiguration through the API
+        let api_client_manager = client_manager.clone();
+        let config_path = backend_config.config_path.take();
+        let api_config = backend_config.api.clone().unwrap_or_default();
+        let button_config = backend_config.resolve_button();
         let backend_info = backend_config.info();
+        // Pool configuration can be reloaded only from the file it was loaded from. Safe mode
+        // derives its own pools so the reload is disabled there.
+        let reloader = match config_path.as_ref() {
+            Some(config_path) if !backend_config.info.safe_mode => Some(reload::Reloader::new(
+                config_path.clone(),
+                toml::Value::try_from(&backend_config).expect("BUG: cannot serialize config"),
+                client_manager.clone(),
+                backend_info.clone(),
+            )),
+            _ => None,
+        };
+        // Remote configuration is available only when the token shared with callers exists
+        let config_server = match config_path.as_ref() {
+            Some(config_path) => match config::remote::Server::bind(
+                config::remote::DEFAULT_SOCKET_PATH,
+                config::remote::DEFAULT_TOKEN_PATH,
+                config_path.clone(),
+                reloader.clone(),
+            ) {
+                Ok(config_server) => Some(config_server),
+                Err(e) => {
+                    info!("Remote configuration is disabled: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
 
         let backend = work_hub.to_node().clone();
         let gpio_mgr = gpio::ControlPinManager::new();
+        let button_listener = if button_config.enabled {
+            match button::Listener::open(
+                &gpio_mgr,
+                button_config,
+                config_path.clone(),
+                hooks.clone().unwrap_or_else(|| Arc::new(hooks::NoHooks)),
+            ) {
+                Ok(button_listener) => Some(button_listener),
+                Err(e) => {
+                    warn!("Front panel button is disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         let (app_halt_sender, app_halt_receiver) = halt::make_pair(HALT_TIMEOUT);
         let (managers, monitor) = Self::start_miner(
             &gpio_mgr,
-            Self::detect_hashboards(&gpio_mgr).expect("failed detecting hashboards"),
+            Self::detect_hashboards(&gpio_mgr).map_err(bosminer::error::backend::from_error)?,
             work_hub,
             backend_config,
-            app_halt_receiver,
+            app_halt_receiver.clone(),
             app_halt_sender.clone(),
         )
         .await;
 
         // On miner exit, halt the whole program
+        let exit_on_halt = backend.exit_on_halt.clone();
         app_halt_sender
-            .add_exit_hook(async {
-                println!("Exiting.");
-                std::process::exit(0);
+            .add_exit_hook(async move {
+                if exit_on_halt.load(Ordering::Relaxed) {
+                    println!("Exiting.");
+                    std::process::exit(0);
+                }
             })
             .await;
-        // Hook `Ctrl-C`, `SIGTERM` and other termination methods
-        app_halt_sender.hook_termination_signals();
+        // Termination signals are handled by the core which calls `shutdown` in the end
+        backend
+            .app_halt_sender
+            .lock()
+            .expect("BUG: lock failed")
+            .replace(app_halt_sender);
 
         // Load initial pool configuration
         client_manager
@@ -1725,13 +2334,39 @@ impl hal::Backend for Backend {
                 config::DEFAULT_POOL_ENABLED,
             )
             .await?;
+        // Auxiliary tasks are stopped together with the miner so that a restarted backend can
+        // start them again
+        if let Some(reloader) = reloader {
+            app_halt_receiver
+                .register_client("config reloader".into())
+                .await
+                .spawn(reloader.run());
+        }
+        if let Some(config_server) = config_server {
+            app_halt_receiver
+                .register_client("config server".into())
+                .await
+                .spawn(config_server.run());
+        }
+        if let Some(button_listener) = button_listener {
+            app_halt_receiver
+                .register_client("button listener".into())
+                .await
+                .spawn(button_listener.run());
+        }
         if let Some(hooks) = hooks {
             // Pass the client manager to hook for further processing
             hooks.clients_loaded(client_manager).await;
         }
 
         Ok(hal::FrontendConfig {
-            cgminer_custom_commands: cgminer::create_custom_commands(backend, managers, monitor),
+            cgminer_custom_commands: cgminer::create_custom_commands(
+                managers,
+                monitor,
+                api_client_manager,
+                config_path,
+            ),
+            api: api_config,
         })
     }
 
@@ -1741,6 +2376,24 @@ impl hal::Backend for Backend {
     ) -> bosminer::Result<hal::FrontendConfig> {
         panic!("BUG: called `init_work_solver`");
     }
+
+    async fn shutdown(backend: Arc<Self>) {
+        let app_halt_sender = backend
+            .app_halt_sender
+            .lock()
+            .expect("BUG: lock failed")
+            .take();
+        // Halt stops all hash chains (which turns off their voltage) and exits the miner
+        if let Some(app_halt_sender) = app_halt_sender {
+            app_halt_sender.send_halt().await;
+        }
+    }
+
+    async fn stop(backend: Arc<Self>) {
+        // Hash chains are halted the same way as on shutdown but the program keeps running
+        backend.exit_on_halt.store(false, Ordering::Relaxed);
+        Self::shutdown(backend).await;
+    }
 }
 
 #[async_trait]
@@ -1750,6 +2403,12 @@ impl node::WorkSolver for Backend {
     }
 }
 
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bitmain Antminer S9")
+    }
+}
+
 impl fmt::Display for Backend {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Bitmain Antminer S9")
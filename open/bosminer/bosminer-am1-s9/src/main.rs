@@ -22,15 +22,28 @@
 
 use ii_logging::macros::*;
 
+use bosminer::ExitCode;
 use bosminer_am1_s9::config;
+use bosminer_am1_s9::safe_mode;
 
 use bosminer_config::clap;
 use bosminer_config::{ClientDescriptor, ClientUserInfo, GroupConfig, PoolConfig};
 
 use ii_async_compat::tokio;
 
+/// Allocations are counted only when running `bench` sub-command
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: bosminer::bench::CountingAllocator = bosminer::bench::CountingAllocator;
+
 #[tokio::main]
 async fn main() {
+    // the logger is flushed when `run` returns
+    run().await.exit();
+}
+
+/// Run the miner or one of the sub-commands and return the exit status
+async fn run() -> ExitCode {
     let app = clap::App::new(bosminer::SIGNATURE)
         .version(bosminer::version::STRING.as_str())
         .arg(
@@ -45,20 +58,36 @@ async fn main() {
                 .short("p")
                 .long("pool")
                 .value_name("HOSTNAME:PORT")
-                .help("Address the stratum V2 server")
+                .help("Address the stratum V2 server (repeat it to add failover pools)")
                 .required(false)
                 .requires("user")
-                .takes_value(true),
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
         )
         .arg(
             clap::Arg::with_name("user")
                 .short("u")
                 .long("user")
                 .value_name("USERNAME.WORKERNAME[:PASSWORD]")
-                .help("Specify user and worker name")
+                .help("Specify user and worker name (one for each pool in the same order)")
                 .required(false)
                 .requires("pool")
-                .takes_value(true),
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            clap::Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("Validate configuration, print the effective one and exit")
+                .required(false),
+        )
+        .arg(
+            clap::Arg::with_name("check-hardware")
+                .long("check-hardware")
+                .help("Detect hash boards, print them as JSON and exit without mining")
+                .required(false),
         )
         .arg(
             clap::Arg::with_name("disable-asic-boost")
@@ -66,6 +95,15 @@ async fn main() {
                 .help("Disable ASIC boost (use just one midstate)")
                 .required(false),
         )
+        .arg(
+            clap::Arg::with_name("midstate-count")
+                .long("midstate-count")
+                .value_name("N")
+                .help("Set number of midstates solved at once (1, 2 or 4 with ASIC boost)")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with("disable-asic-boost"),
+        )
         .arg(
             clap::Arg::with_name("frequency")
                 .long("frequency")
@@ -80,6 +118,26 @@ async fn main() {
                 .required(false)
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("chain-frequency")
+                .long("chain-frequency")
+                .value_name("CHAIN:MHZ")
+                .help("Set chip frequency (in MHz) of one hash chain (e.g. 6:650)")
+                .required(false)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            clap::Arg::with_name("chain-voltage")
+                .long("chain-voltage")
+                .value_name("CHAIN:VOLTS")
+                .help("Set chip voltage (in volts) of one hash chain (e.g. 7:8.9)")
+                .required(false)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
         .subcommand(
             clap::SubCommand::with_name("config")
                 .about("Configuration backend API")
@@ -105,12 +163,32 @@ async fn main() {
                         .required(false)
                         .takes_value(false),
                 )
+                .arg(
+                    clap::Arg::with_name("remote")
+                        .long("remote")
+                        .value_name("SOCKET|HOST:PORT")
+                        .help("Execute the request in the running miner (Unix socket or TCP port)")
+                        .required(false)
+                        .takes_value(true),
+                )
+                .arg(
+                    clap::Arg::with_name("token-file")
+                        .long("token-file")
+                        .value_name("PATH")
+                        .help("Set path to the file with token authorizing remote requests")
+                        .required(false)
+                        .requires("remote")
+                        .takes_value(true),
+                )
                 .group(
                     clap::ArgGroup::with_name("command")
                         .args(&["metadata", "data", "save"])
                         .required(true),
                 ),
-        );
+        )
+        .subcommand(bosminer_am1_s9::status::sub_command());
+    #[cfg(feature = "bench")]
+    let app = app.subcommand(bosminer::bench::sub_command());
 
     let matches = app.get_matches();
     let _log_guard =
@@ -122,17 +200,135 @@ async fn main() {
 
     // Handle special 'config' sub-command available for configuration backend API
     if let Some(matches) = matches.subcommand_matches("config") {
-        let config_handler = config::api::Handler::new(config_path);
-        if matches.is_present("metadata") {
-            config_handler.handle_metadata::<config::Backend>();
-        } else if matches.is_present("data") {
-            config_handler.handle_data::<config::Backend>();
-        } else if matches.is_present("save") {
-            config_handler.handle_save::<config::Backend>();
+        match matches.value_of("remote") {
+            Some(address) => handle_config(
+                matches,
+                config::api::Handler::new(config::remote::Client::new(
+                    config::remote::Address::parse(address),
+                    matches
+                        .value_of("token-file")
+                        .unwrap_or(config::remote::DEFAULT_TOKEN_PATH),
+                )),
+            ),
+            None => handle_config(
+                matches,
+                config::api::Handler::new(config::api::Local::new(config_path)),
+            ),
+        }
+        return ExitCode::Success;
+    }
+
+    // Handle 'bench' sub-command which measures frontend performance without hardware
+    #[cfg(feature = "bench")]
+    if let Some(matches) = matches.subcommand_matches("bench") {
+        if let Err(e) = bosminer::bench::handle(matches) {
+            error!("Cannot run benchmark: {}", e);
+            return ExitCode::Failure;
+        }
+        return ExitCode::Success;
+    }
+
+    // Handle 'status' sub-command which queries already running miner
+    if let Some(matches) = matches.subcommand_matches("status") {
+        if let Err(e) = bosminer_am1_s9::status::handle(matches) {
+            error!("Cannot get miner status: {}", e);
+            return ExitCode::Failure;
+        }
+        return ExitCode::Success;
+    }
+
+    // Only probe the hardware, the configuration is not needed
+    if matches.is_present("check-hardware") {
+        return check_hardware();
+    }
+
+    let backend_config = load_config(&matches, config_path);
+    if matches.is_present("dry-run") {
+        return match backend_config {
+            Ok(backend_config) => print_config(backend_config, config_path),
+            Err(code) => code,
+        };
+    }
+    let mut backend_config = match backend_config {
+        Ok(backend_config) => backend_config,
+        Err(code) => return code,
+    };
+
+    // Fall back to conservative configuration when the miner keeps crashing
+    let mut crash_loop =
+        safe_mode::CrashLoop::load(safe_mode::DEFAULT_STATE_PATH, Default::default());
+    let mode = crash_loop.start(std::time::SystemTime::now());
+    if mode == safe_mode::Mode::Safe {
+        warn!("********************************************************");
+        warn!("Miner has crashed repeatedly, starting in SAFE MODE:");
+        warn!("    stock frequency and voltage, single pool, default fan control");
+        warn!("********************************************************");
+        backend_config = backend_config.into_safe_mode();
+    }
+
+    let info_result = backend_config.fill_info::<config::Backend>();
+    info!("Environment: {:?}", backend_config.info.environment);
+    if let Err(e) = info_result {
+        error!("Cannot get backend information: {}", e.to_string());
+        return ExitCode::BackendInit;
+    }
+    backend_config.info.safe_mode = mode == safe_mode::Mode::Safe;
+
+    // Make the environment fingerprint part of every crash report
+    let environment = backend_config.info.environment.clone();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        eprintln!("Environment: {:?}", environment);
+        default_hook(panic_info);
+    }));
+    ii_async_compat::setup_panic_handling();
+    tokio::spawn(crash_loop.run());
+    // logging has been already set up for handling of the sub-commands
+    bosminer::main::<bosminer_am1_s9::Backend>(
+        backend_config,
+        bosminer::SIGNATURE.to_string(),
+        None,
+    )
+    .await
+}
+
+/// Dispatch request of the configuration backend API to the handler with selected transport
+fn handle_config<T>(matches: &clap::ArgMatches, config_handler: config::api::Handler<T>)
+where
+    T: config::api::Transport,
+{
+    if matches.is_present("metadata") {
+        config_handler.handle_metadata::<config::Backend>();
+    } else if matches.is_present("data") {
+        config_handler.handle_data::<config::Backend>();
+    } else if matches.is_present("save") {
+        config_handler.handle_save::<config::Backend>();
+    }
+}
+
+/// Print hash boards and environment of the miner as JSON to stdout
+fn check_hardware() -> ExitCode {
+    let report = match bosminer_am1_s9::Backend::check_hardware() {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Cannot detect hardware: {}", e);
+            return ExitCode::NoHardware;
         }
-        return;
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&report).expect("BUG: cannot serialize hardware report")
+    );
+    if report.is_usable() {
+        ExitCode::Success
+    } else {
+        ExitCode::NoHardware
     }
+}
 
+/// Parse configuration file and apply overrides from command line. Errors are logged and the exit
+/// code is returned when the configuration cannot be used.
+fn load_config(matches: &clap::ArgMatches, config_path: &str) -> Result<config::Backend, ExitCode> {
     let mut backend_config: config::Backend = match config::FormatWrapper::parse(config_path) {
         Err(config::FormatWrapperError::IncompatibleVersion(version, Some(v))) => {
             warn!(
@@ -144,33 +340,71 @@ async fn main() {
         Err(e) => {
             error!("Cannot load configuration file \"{}\"", config_path);
             error!("Reason: {}", e);
-            return;
+            return Err(ExitCode::Config);
         }
         Ok(v) => v.body,
     };
+    if let Err(e) = backend_config.expand_env() {
+        error!("Cannot load configuration file \"{}\"", config_path);
+        error!("Reason: {}", e);
+        return Err(ExitCode::Config);
+    }
+    backend_config.config_path = Some(config_path.to_string());
 
     // Add pools from command line
-    if let Some(url) = matches.value_of("pool") {
-        let user_info = matches
-            .value_of("user")
-            .expect("BUG: missing 'user' argument");
-        let user_info = ClientUserInfo::parse(user_info);
+    if let Some(urls) = matches.values_of("pool") {
+        let urls: Vec<_> = urls.collect();
+        let user_infos: Vec<_> = matches
+            .values_of("user")
+            .expect("BUG: missing 'user' argument")
+            .collect();
+        if urls.len() != user_infos.len() {
+            error!(
+                "Cannot set pools from command line: {} pool(s) but {} user(s) specified",
+                urls.len(),
+                user_infos.len()
+            );
+            info!("Each '--pool' has to be followed by its own '--user'");
+            return Err(ExitCode::Config);
+        }
 
-        match ClientDescriptor::create(url, &user_info, true) {
-            Err(e) => {
-                error!("Cannot set pool from command line: {}", e.to_string());
-                return;
-            }
-            Ok(_) => {}
-        };
-        let group_config = GroupConfig {
-            descriptor: Default::default(),
-            pools: Some(vec![PoolConfig {
+        // The first pool is primary and the rest are used as failover in the given order
+        let mut pools = Vec::with_capacity(urls.len());
+        for (url, user_info) in urls.into_iter().zip(user_infos) {
+            let user_info = ClientUserInfo::parse(user_info);
+            match ClientDescriptor::create(url, &user_info, true) {
+                Err(e) => {
+                    error!(
+                        "Cannot set pool '{}' from command line: {}",
+                        url,
+                        e.to_string()
+                    );
+                    return Err(ExitCode::Config);
+                }
+                Ok(_) => {}
+            };
+            pools.push(PoolConfig {
                 enabled: Default::default(),
                 url: url.to_string(),
                 user: user_info.user.to_string(),
                 password: user_info.password.map(|v| v.to_string()),
-            }]),
+                network: None,
+                label: None,
+                name: None,
+                reject_breaker: None,
+                coinbase_check: None,
+                share_policy: None,
+                cert_expiry_warning: None,
+                ca_cert: None,
+                insecure: None,
+                min_difficulty: None,
+                fixed_difficulty: None,
+                dns_ttl: None,
+            });
+        }
+        let group_config = GroupConfig {
+            descriptor: Default::default(),
+            pools: Some(pools),
         };
 
         if backend_config.has_groups() {
@@ -178,6 +412,8 @@ async fn main() {
         }
 
         backend_config.groups = Some(vec![group_config]);
+        // Runtime changes of pools from command line cannot be persisted
+        backend_config.config_path = None;
     }
 
     // Check if there's enough pools
@@ -190,7 +426,7 @@ async fn main() {
             config_path
         );
         info!("    in [[group.pool]] section");
-        return;
+        return Err(ExitCode::NoPools);
     }
 
     // Set just 1 midstate if user requested disabling asicboost
@@ -201,6 +437,28 @@ async fn main() {
             .asic_boost
             .replace(false);
     }
+    if let Some(value) = matches.value_of("midstate-count") {
+        let midstate_count = match value.parse::<usize>() {
+            Ok(value) => value,
+            Err(e) => {
+                error!(
+                    "Cannot use midstate count '{}' from command line: {}",
+                    value,
+                    e.to_string()
+                );
+                return Err(ExitCode::Config);
+            }
+        };
+        backend_config
+            .hash_chain_global
+            .get_or_insert_with(|| Default::default())
+            .midstate_count
+            .replace(midstate_count);
+        if let Err(e) = backend_config.check_midstate_count() {
+            error!("Cannot use midstate count from command line: {}", e);
+            return Err(ExitCode::Config);
+        }
+    }
     if let Some(value) = matches.value_of("frequency") {
         let frequency = match value.parse::<f64>() {
             Ok(value) => value,
@@ -210,7 +468,7 @@ async fn main() {
                     value,
                     e.to_string()
                 );
-                return;
+                return Err(ExitCode::Config);
             }
         };
         backend_config
@@ -230,7 +488,7 @@ async fn main() {
                     value,
                     e.to_string()
                 );
-                return;
+                return Err(ExitCode::Config);
             }
         };
         backend_config
@@ -241,13 +499,70 @@ async fn main() {
             .voltage
             .replace(voltage);
     }
+    // Per-chain overrides replace only settings of the given hash chain from configuration file
+    for value in matches.values_of("chain-frequency").into_iter().flatten() {
+        let (idx, frequency) = match config::parse_hash_chain_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                error!(
+                    "Cannot use chain frequency '{}' from command line: {}",
+                    value, e
+                );
+                return Err(ExitCode::Config);
+            }
+        };
+        backend_config
+            .hash_chain_mut(idx)
+            .frequency
+            .replace(frequency);
+    }
+    for value in matches.values_of("chain-voltage").into_iter().flatten() {
+        let (idx, voltage) = match config::parse_hash_chain_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                error!(
+                    "Cannot use chain voltage '{}' from command line: {}",
+                    value, e
+                );
+                return Err(ExitCode::Config);
+            }
+        };
+        backend_config.hash_chain_mut(idx).voltage.replace(voltage);
+    }
 
+    Ok(backend_config)
+}
+
+/// Print the effective configuration as TOML for `--dry-run`. Keys from the configuration file
+/// which have been ignored are reported as TOML comments before the configuration.
+fn print_config(mut backend_config: config::Backend, config_path: &str) -> ExitCode {
+    // Validate also the overrides from command line
+    if let Err(e) = config::ConfigBody::sanity_check(&backend_config) {
+        error!("Invalid configuration: {}", e);
+        return ExitCode::Config;
+    }
     if let Err(e) = backend_config.fill_info::<config::Backend>() {
         error!("Cannot get backend information: {}", e.to_string());
-        return;
+        return ExitCode::BackendInit;
     }
+    info!("Environment: {:?}", backend_config.info.environment);
 
-    ii_async_compat::setup_panic_handling();
-    bosminer::main::<bosminer_am1_s9::Backend>(backend_config, bosminer::SIGNATURE.to_string())
-        .await;
+    let content = match toml::to_string_pretty(&backend_config) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("Cannot serialize configuration: {}", e.to_string());
+            return ExitCode::Failure;
+        }
+    };
+    match config::unknown_keys(config_path) {
+        Ok(keys) => {
+            for key in keys {
+                warn!("Unknown key '{}' in '{}' is ignored", key, config_path);
+                println!("# WARNING: unknown key '{}' is ignored", key);
+            }
+        }
+        Err(e) => warn!("Cannot check unknown keys in '{}': {}", config_path, e),
+    }
+    print!("{}", content);
+    ExitCode::Success
 }
@@ -27,7 +27,7 @@ use ii_logging::macros::*;
 
 use crate::fan;
 use crate::halt;
-use crate::sensor::{self, Measurement};
+use crate::sensor;
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -62,7 +62,7 @@ const WARM_UP_PERIOD: Duration = Duration::from_secs(90);
 #[derive(Debug, Clone)]
 pub enum Message {
     On,
-    Running(sensor::Temperature),
+    Running(sensor::Readings),
     Off,
 }
 
@@ -78,24 +78,23 @@ pub enum ChainTemperature {
 }
 
 impl ChainTemperature {
-    /// Convert temperature to monitor interpretation.
+    /// Convert filtered sensor readings to monitor interpretation.
     /// Specific to S9, because it fakes chip temperature.
     ///
-    /// TODO: Maybe figure out a strage for disabling remote sensors that are failing. Sometimes
-    /// remote sensors fail while mining and instead of signalizing error they return non-sensical
-    /// numbers.
-    /// TODO: Is returning "Unknown" when sensor fails OK?
-    fn from_s9_sensor(temp: sensor::Temperature) -> Self {
-        match temp.remote {
-            // remote is chip temperature
-            Measurement::Ok(t) => Self::Ok(t),
-            _ => {
-                // fake chip temperature from local (PCB) temperature
-                match temp.local {
-                    Measurement::Ok(t) => Self::Ok(t + 15.0),
-                    _ => Self::Unknown,
-                }
-            }
+    /// Only plausible values are used. When there are sensors but none of them has provided
+    /// a plausible value for some time the temperature is considered `Failed`, because the
+    /// hashboard may be overheating without anyone noticing.
+    fn from_s9_sensor(readings: &sensor::Readings) -> Self {
+        if readings.all_stale() {
+            return Self::Failed;
+        }
+        match readings.max(sensor::Location::Chip) {
+            Some(t) => Self::Ok(t),
+            // fake chip temperature from PCB temperature
+            None => match readings.max(sensor::Location::Board) {
+                Some(t) => Self::Ok(t + 15.0),
+                None => Self::Unknown,
+            },
         }
     }
 }
@@ -109,7 +108,7 @@ enum ChainState {
     Running {
         started: Instant,
         last_heartbeat: Instant,
-        temperature: sensor::Temperature,
+        temperature: sensor::Readings,
     },
     Off,
     Broken(&'static str),
@@ -177,7 +176,7 @@ impl ChainState {
             ChainState::Off => ChainTemperature::Unknown,
             ChainState::Broken(_) => ChainTemperature::Failed,
             ChainState::Running { temperature, .. } => {
-                ChainTemperature::from_s9_sensor(temperature.clone())
+                ChainTemperature::from_s9_sensor(temperature)
             }
         }
     }
@@ -679,35 +678,71 @@ mod test {
         }}; // TODO: Additional patterns for trailing args, like assert and assert_eq
     }
 
+    fn reading(location: sensor::Location, value: Option<f32>, stale: bool) -> sensor::Reading {
+        sensor::Reading {
+            label: location.to_string(),
+            location,
+            value,
+            fault: None,
+            stale,
+        }
+    }
+
+    fn readings(board: Option<f32>, chip: Option<f32>, stale: bool) -> sensor::Readings {
+        sensor::Readings {
+            channels: vec![
+                reading(sensor::Location::Board, board, stale),
+                reading(sensor::Location::Chip, chip, stale),
+            ],
+        }
+    }
+
     /// Test that faking S9 chip temperature from board temperature works
     #[test]
     fn test_monitor_s9_chip_temp() {
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::Ok(10.0),
-            remote: sensor::Measurement::Ok(22.0),
-        };
-        match ChainTemperature::from_s9_sensor(temp) {
+        let temp = readings(Some(10.0), Some(22.0), false);
+        match ChainTemperature::from_s9_sensor(&temp) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 22.0),
             _ => panic!("missing temperature"),
         };
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::Ok(10.0),
-            remote: sensor::Measurement::OpenCircuit,
-        };
-        match ChainTemperature::from_s9_sensor(temp) {
+        let temp = readings(Some(10.0), None, false);
+        match ChainTemperature::from_s9_sensor(&temp) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 25.0),
             _ => panic!("missing temperature"),
         };
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::InvalidReading,
-            remote: sensor::Measurement::OpenCircuit,
-        };
+        let temp = readings(None, None, false);
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(&temp),
+            ChainTemperature::Unknown
+        );
         assert_eq!(
-            ChainTemperature::from_s9_sensor(temp),
+            ChainTemperature::from_s9_sensor(&Default::default()),
             ChainTemperature::Unknown
         );
     }
 
+    /// Test that only plausible values are used and that all-stale sensors are an emergency
+    #[test]
+    fn test_monitor_stale_sensors() {
+        // stale chip temperature is not used even though its last value is known
+        let mut temp = readings(Some(10.0), Some(80.0), false);
+        temp.channels[1].stale = true;
+        match ChainTemperature::from_s9_sensor(&temp) {
+            ChainTemperature::Ok(t) => assert_relative_eq!(t, 25.0),
+            _ => panic!("missing temperature"),
+        };
+        let temp = readings(Some(10.0), Some(22.0), true);
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(&temp),
+            ChainTemperature::Failed
+        );
+        let temp = readings(None, None, true);
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(&temp),
+            ChainTemperature::Failed
+        );
+    }
+
     fn send(mut state: ChainState, when: Instant, message: Message) -> ChainState {
         state.transition(when, message);
         state
@@ -716,10 +751,7 @@ mod test {
     /// Test that miner transitions states as expected
     #[test]
     fn test_monitor_state_transition() {
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::Ok(10.0),
-            remote: sensor::Measurement::Ok(22.0),
-        };
+        let temp = readings(Some(10.0), Some(22.0), false);
         let now = Instant::now();
         let later = now + Duration::from_secs(1);
         let running_state = ChainState::Running {
@@ -773,10 +805,7 @@ mod test {
     /// Test "warm up" period
     #[test]
     fn test_monitor_warm_up() {
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::Ok(10.0),
-            remote: sensor::Measurement::Ok(22.0),
-        };
+        let temp = readings(Some(10.0), Some(22.0), false);
         let now = Instant::now();
         let later = now + Duration::from_secs(20);
         let warmed_time = now + Duration::from_secs(200);
@@ -802,10 +831,7 @@ mod test {
     /// Test timeouts
     #[test]
     fn test_monitor_timeouts() {
-        let temp = sensor::Temperature {
-            local: sensor::Measurement::Ok(10.0),
-            remote: sensor::Measurement::Ok(22.0),
-        };
+        let temp = readings(Some(10.0), Some(22.0), false);
         let now = Instant::now();
         let long = now + Duration::from_secs(10_000);
         let short = now + Duration::from_secs(2);
@@ -189,27 +189,26 @@ impl I2cBackend {
     }
 
     /// Attempt to write a byte to power controller on I2C.
-    /// If write fails then retry (at most `I2C_NUM_RETRIES`).
+    /// If write fails with transient error then retry (at most `I2C_NUM_RETRIES`).
     async fn write_retry(&self, hashboard_idx: usize, data: u8) -> error::Result<()> {
-        let mut tries_left: usize = Self::I2C_NUM_RETRIES;
-        loop {
-            let ret = self
-                .inner
-                .write(Self::get_i2c_address(hashboard_idx), vec![data])
-                .await;
-            if ret.is_ok() {
-                return ret;
-            }
-            tries_left -= 1;
-            if tries_left == 0 {
-                return ret;
-            }
-            warn!(
-                "I2C transaction on hashboard {} failed, retrying...",
-                hashboard_idx
-            );
-            delay_for(Self::I2C_RETRY_DELAY).await;
-        }
+        bosminer::error::retry(
+            Self::I2C_NUM_RETRIES,
+            Self::I2C_RETRY_DELAY,
+            |tries_left| async move {
+                let ret = self
+                    .inner
+                    .write(Self::get_i2c_address(hashboard_idx), vec![data])
+                    .await;
+                if ret.is_err() && tries_left > 0 {
+                    warn!(
+                        "I2C transaction on hashboard {} failed, retrying...",
+                        hashboard_idx
+                    );
+                }
+                ret
+            },
+        )
+        .await
     }
 
     /// Perform a write command to power controller on I2C
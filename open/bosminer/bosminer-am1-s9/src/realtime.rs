@@ -0,0 +1,170 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Dedicated runtime for the time critical IO tasks of hash chains.
+//!
+//! Solutions are read from the FPGA FIFO which overflows (and nonces are silently lost) when
+//! the RX task is not scheduled in time e.g. under heavy API or logging load. The RX/TX tasks of
+//! each hash chain are therefore run on their own thread with elevated priority. Real-time
+//! `SCHED_FIFO` policy is optional because a busy real-time thread can starve the rest of the
+//! system. Missing capabilities (`CAP_SYS_NICE`) are not fatal, the thread just keeps the
+//! default priority.
+
+use ii_logging::macros::*;
+
+use futures::channel::oneshot;
+use ii_async_compat::{futures, tokio};
+
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Nice value of the IO thread when `SCHED_FIFO` is not used
+pub const ELEVATED_NICE: i32 = -10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Run RX/TX tasks on a dedicated thread with elevated priority
+    pub enabled: bool,
+    /// Use real-time `SCHED_FIFO` policy with `priority` instead of lowering nice value
+    pub sched_fifo: bool,
+    pub priority: i32,
+    /// Warn when draining the solution FIFO after IRQ takes longer
+    pub latency_budget: Duration,
+}
+
+impl Config {
+    /// Elevate priority of the calling thread
+    fn elevate_priority(&self) -> io::Result<()> {
+        // NOTE: on Linux both calls with pid 0 change only the calling thread
+        let result = if self.sched_fifo {
+            let param = libc::sched_param {
+                sched_priority: self.priority,
+            };
+            unsafe { libc::sched_setscheduler(0, libc::SCHED_FIFO, &param) }
+        } else {
+            unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, ELEVATED_NICE) }
+        };
+        if result != 0 {
+            Err(io::Error::last_os_error())?;
+        }
+        Ok(())
+    }
+}
+
+/// Single threaded runtime running on its own thread until it is dropped
+pub struct Runtime {
+    handle: tokio::runtime::Handle,
+    stop_sender: Option<oneshot::Sender<()>>,
+}
+
+impl Runtime {
+    pub fn start(name: String, config: &Config) -> io::Result<Self> {
+        let (handle_sender, handle_receiver) = mpsc::channel();
+        let (stop_sender, stop_receiver) = oneshot::channel();
+
+        let config = config.clone();
+        thread::Builder::new().name(name.clone()).spawn(move || {
+            match config.elevate_priority() {
+                Ok(_) => info!(
+                    "Runtime '{}' running with {}",
+                    name,
+                    if config.sched_fifo {
+                        format!("SCHED_FIFO priority {}", config.priority)
+                    } else {
+                        format!("nice {}", ELEVATED_NICE)
+                    }
+                ),
+                Err(e) => warn!(
+                    "Runtime '{}' keeps default priority (cannot elevate it: {})",
+                    name, e
+                ),
+            }
+            let mut runtime = match tokio::runtime::Builder::new()
+                .basic_scheduler()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ = handle_sender.send(Err(e));
+                    return;
+                }
+            };
+            let _ = handle_sender.send(Ok(runtime.handle().clone()));
+            // the spawned tasks are dropped together with the runtime
+            let _ = runtime.block_on(stop_receiver);
+        })?;
+
+        let handle = handle_receiver.recv().map_err(|_| {
+            io::Error::new(io::ErrorKind::Other, "runtime thread exited unexpectedly")
+        })??;
+        Ok(Self {
+            handle,
+            stop_sender: Some(stop_sender),
+        })
+    }
+
+    #[inline]
+    pub fn handle(&self) -> &tokio::runtime::Handle {
+        &self.handle
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        if let Some(stop_sender) = self.stop_sender.take() {
+            let _ = stop_sender.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The runtime has to work even when the priority cannot be elevated (e.g. the tests are not
+    /// run as root)
+    #[test]
+    fn test_runtime_without_capabilities() {
+        for sched_fifo in [false, true].iter() {
+            let config = Config {
+                enabled: true,
+                sched_fifo: *sched_fifo,
+                priority: 10,
+                latency_budget: Duration::from_millis(50),
+            };
+            let runtime = Runtime::start("test-io".to_string(), &config).expect("runtime start");
+            let (sender, receiver) = mpsc::channel();
+            runtime.handle().spawn(async move {
+                sender
+                    .send(thread::current().name().map(|name| name.to_string()))
+                    .expect("send failed");
+            });
+            let name = receiver
+                .recv_timeout(Duration::from_secs(5))
+                .expect("task has not been run");
+            assert_eq!(name.as_deref(), Some("test-io"));
+        }
+    }
+}
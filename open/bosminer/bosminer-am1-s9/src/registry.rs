@@ -24,11 +24,13 @@ use crate::Solution;
 
 use bosminer::work;
 use std::iter::Iterator;
+use std::sync::Arc;
 
 /// Mining registry item contains work and solutions
 #[derive(Clone)]
 pub struct WorkRegistryItem {
-    work: work::Assignment,
+    /// Work is shared with all solutions found for it
+    work: Arc<work::Assignment>,
     /// Each slot in the vector is associated with particular solution index as reported by
     /// the chips.
     solutions: std::vec::Vec<Solution>,
@@ -65,7 +67,13 @@ impl WorkRegistryItem {
         }
 
         // report the unique solution via status
-        status.unique_solution = Some(work::Solution::new(self.work.clone(), new_solution, None));
+        // the solution is timestamped when it has been read from the hardware
+        let timestamp = new_solution.timestamp;
+        status.unique_solution = Some(work::Solution::new(
+            self.work.clone(),
+            new_solution,
+            Some(timestamp),
+        ));
         status
     }
 }
@@ -127,8 +135,13 @@ impl WorkRegistry {
 
     /// Store new work to work registry and generate `work_id` for it
     /// As a side effect, retire stale work.
+    /// The `work` can be passed already shared to avoid copying it when the work is still needed.
     /// Returns: new `work_id`
-    pub fn store_work(&mut self, work: work::Assignment, initial_work: bool) -> usize {
+    pub fn store_work<T: Into<Arc<work::Assignment>>>(
+        &mut self,
+        work: T,
+        initial_work: bool,
+    ) -> usize {
         let work_id = self.alloc_next_work_id();
 
         // retire stale work
@@ -137,7 +150,7 @@ impl WorkRegistry {
 
         // put new work into registry
         self.pending_work_list[work_id] = Some(WorkRegistryItem {
-            work,
+            work: work.into(),
             solutions: std::vec::Vec::new(),
             initial_work,
         });
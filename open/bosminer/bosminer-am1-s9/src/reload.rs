@@ -0,0 +1,108 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Reload of pool configuration from the configuration file when the process receives `SIGHUP`
+
+use ii_logging::macros::*;
+
+use crate::config;
+
+use bosminer::client;
+use bosminer::hal;
+
+use futures::stream::StreamExt;
+use ii_async_compat::{futures, tokio};
+use tokio::signal::unix::{signal, SignalKind};
+
+#[derive(Clone)]
+pub struct Reloader {
+    config_path: String,
+    /// Configuration the miner was started with
+    running_config: toml::Value,
+    client_manager: client::Manager,
+    backend_info: Option<hal::BackendInfo>,
+}
+
+impl Reloader {
+    pub fn new(
+        config_path: String,
+        running_config: toml::Value,
+        client_manager: client::Manager,
+        backend_info: Option<hal::BackendInfo>,
+    ) -> Self {
+        Self {
+            config_path,
+            running_config,
+            client_manager,
+            backend_info,
+        }
+    }
+
+    /// Apply pools from the configuration file to the running clients
+    pub async fn reload(&self) -> Result<(), String> {
+        let mut backend_config: config::Backend =
+            match config::FormatWrapper::parse(&self.config_path) {
+                Err(config::FormatWrapperError::IncompatibleVersion(version, Some(v))) => {
+                    warn!(
+                        "Incompatible format version '{}', but continuing anyway",
+                        version
+                    );
+                    v.body
+                }
+                Err(e) => Err(e.to_string())?,
+                Ok(v) => v.body,
+            };
+        backend_config.expand_env()?;
+
+        let new_config = toml::Value::try_from(&backend_config).map_err(|e| e.to_string())?;
+        // Pools are always reconciled with running clients because they can be changed at runtime
+        // through the API as well
+        for change in config::diff(&self.running_config, &new_config) {
+            if change.apply == config::Apply::Restart {
+                warn!(
+                    "Configuration '{}' has been changed: requires restart",
+                    change.key
+                );
+            }
+        }
+        self.client_manager
+            .reload_config(
+                backend_config.groups,
+                self.backend_info.as_ref(),
+                config::DEFAULT_POOL_ENABLED,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Reload the configuration file on each `SIGHUP`
+    pub async fn run(self) {
+        let mut hangup = signal(SignalKind::hangup()).expect("BUG: failed hooking signal");
+        while hangup.next().await.is_some() {
+            info!("Reloading configuration file '{}'", self.config_path);
+            match self.reload().await {
+                Ok(_) => info!("Configuration has been reloaded"),
+                Err(e) => error!("Cannot reload configuration: {}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Detection of crash loops which switches the miner to safe mode.
+//!
+//! Every start of the miner is recorded to a persistent file and the record is removed when the
+//! miner keeps running for some time. Starts which have not been confirmed as healthy are
+//! therefore crashes (or quick restarts) and when there are too many of them within a short
+//! window, the next start enters safe mode with conservative configuration (see
+//! `config::Backend::into_safe_mode`). Safe mode is left after the miner has been running in it
+//! for a longer recovery period.
+//!
+//! Known limitations: S9 has no RTC so the time after boot may be wrong until it is synchronized
+//! and any restart before the start is confirmed (e.g. configuration change) counts as a crash.
+
+use ii_logging::macros::*;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Location of the persistent crash loop state
+pub const DEFAULT_STATE_PATH: &'static str = "/etc/bosminer-crash-loop.json";
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Number of unconfirmed starts within `window` which turns on safe mode
+    pub max_crashes: usize,
+    pub window: Duration,
+    /// Running time after which the start is confirmed as healthy in normal mode
+    pub healthy_period: Duration,
+    /// Running time after which the miner returns from safe mode to normal mode
+    pub recovery_period: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_crashes: 4,
+            window: Duration::from_secs(20 * 60),
+            healthy_period: Duration::from_secs(5 * 60),
+            recovery_period: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Safe,
+}
+
+/// Persistent part of crash loop detection
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct State {
+    /// Unix times of starts which have not been confirmed as healthy
+    starts: Vec<u64>,
+    /// Safe mode is kept until the miner is confirmed as healthy in it
+    safe_mode: bool,
+}
+
+impl State {
+    fn to_unix_time(time: SystemTime) -> u64 {
+        time.duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    }
+
+    /// Account a new start at `now` and determine mode of this run
+    pub fn start(&mut self, settings: &Settings, now: SystemTime) -> Mode {
+        let now = Self::to_unix_time(now);
+        let window_start = now.saturating_sub(settings.window.as_secs());
+        // NOTE: starts from the future are ignored because the time is not valid until it is
+        // synchronized after boot
+        self.starts
+            .retain(|time| *time >= window_start && *time <= now);
+        if self.starts.len() >= settings.max_crashes {
+            self.safe_mode = true;
+        }
+        self.starts.push(now);
+
+        self.mode()
+    }
+
+    pub fn mode(&self) -> Mode {
+        if self.safe_mode {
+            Mode::Safe
+        } else {
+            Mode::Normal
+        }
+    }
+
+    /// Running time required for confirmation of the current run
+    pub fn health_period(&self, settings: &Settings) -> Duration {
+        match self.mode() {
+            Mode::Normal => settings.healthy_period,
+            Mode::Safe => settings.recovery_period,
+        }
+    }
+
+    /// Confirm that the current run is healthy and return true when safe mode has been left
+    pub fn confirm_healthy(&mut self) -> bool {
+        self.starts.clear();
+        mem::replace(&mut self.safe_mode, false)
+    }
+}
+
+/// Crash loop detector with state stored in a file
+#[derive(Debug)]
+pub struct CrashLoop {
+    path: PathBuf,
+    settings: Settings,
+    state: State,
+}
+
+impl CrashLoop {
+    /// Load state from `path`. Missing or malformed file means there is no crash history.
+    pub fn load<P: Into<PathBuf>>(path: P, settings: Settings) -> Self {
+        let path = path.into();
+        let state = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(
+                    "Ignoring malformed crash loop state '{}': {}",
+                    path.display(),
+                    e
+                );
+                Default::default()
+            }),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(e) => {
+                warn!("Cannot read crash loop state '{}': {}", path.display(), e);
+                Default::default()
+            }
+        };
+
+        Self {
+            path,
+            settings,
+            state,
+        }
+    }
+
+    fn save(&self) {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+
+        let result = serde_json::to_string(&self.state)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+            .and_then(|content| fs::write(&tmp_path, content))
+            .and_then(|_| fs::rename(&tmp_path, &self.path));
+        if let Err(e) = result {
+            error!(
+                "Cannot store crash loop state '{}': {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    /// Record start of the miner and return the mode it should run in
+    pub fn start(&mut self, now: SystemTime) -> Mode {
+        let mode = self.state.start(&self.settings, now);
+        self.save();
+        mode
+    }
+
+    /// Make the next start enter safe mode regardless of the crash history
+    pub fn force_safe_mode(mut self) {
+        self.state.safe_mode = true;
+        self.save();
+    }
+
+    /// Wait until the miner runs long enough to be considered healthy and confirm it. When the
+    /// miner recovers from safe mode, termination is requested so that the miner is restarted
+    /// with normal configuration.
+    pub async fn run(mut self) {
+        delay_for(self.state.health_period(&self.settings)).await;
+
+        let left_safe_mode = self.state.confirm_healthy();
+        self.save();
+        if left_safe_mode {
+            info!("Miner has been running in safe mode without crash, restarting to normal mode");
+            // use the same path as any other termination to halt hash chains properly
+            unsafe {
+                libc::kill(libc::getpid(), libc::SIGTERM);
+            }
+        } else {
+            info!("Miner start has been confirmed as healthy");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            max_crashes: 3,
+            window: Duration::from_secs(10 * 60),
+            healthy_period: Duration::from_secs(60),
+            recovery_period: Duration::from_secs(30 * 60),
+        }
+    }
+
+    /// Mock clock with time given in minutes
+    fn clock(minutes: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_600_000_000 + minutes * 60)
+    }
+
+    #[test]
+    fn test_crash_loop_threshold() {
+        let settings = settings();
+        let mut state = State::default();
+
+        // the first start and crashes below the threshold
+        for minute in 0..3 {
+            assert_eq!(state.start(&settings, clock(minute)), Mode::Normal);
+        }
+        // three unconfirmed starts within the window
+        assert_eq!(state.start(&settings, clock(3)), Mode::Safe);
+        assert_eq!(state.health_period(&settings), settings.recovery_period);
+    }
+
+    #[test]
+    fn test_crashes_out_of_window() {
+        let settings = settings();
+        let mut state = State::default();
+
+        // crashes are too far from each other
+        for minute in (0..60).step_by(5) {
+            assert_eq!(state.start(&settings, clock(minute)), Mode::Normal);
+        }
+        // start from the future (time has not been synchronized) is not counted
+        let mut state = State::default();
+        state.start(&settings, clock(100));
+        state.start(&settings, clock(101));
+        assert_eq!(state.start(&settings, clock(0)), Mode::Normal);
+        assert_eq!(state.starts.len(), 1);
+
+        // healthy runs reset the history
+        let mut state = State::default();
+        for minute in 0..10 {
+            assert_eq!(state.start(&settings, clock(minute)), Mode::Normal);
+            assert_eq!(state.health_period(&settings), settings.healthy_period);
+            assert!(!state.confirm_healthy());
+        }
+    }
+
+    #[test]
+    fn test_recovery() {
+        let settings = settings();
+        let mut state = State::default();
+        for minute in 0..4 {
+            state.start(&settings, clock(minute));
+        }
+        assert_eq!(state.mode(), Mode::Safe);
+
+        // safe mode is kept even when the following crashes are out of the window
+        assert_eq!(state.start(&settings, clock(60)), Mode::Safe);
+        // sustained health in safe mode leads back to normal mode
+        assert!(state.confirm_healthy());
+        assert_eq!(state.mode(), Mode::Normal);
+        assert_eq!(state.start(&settings, clock(120)), Mode::Normal);
+        assert!(!state.confirm_healthy());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path =
+            std::env::temp_dir().join(format!("bosminer-crash-loop-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        for minute in 0..3 {
+            let mut crash_loop = CrashLoop::load(&path, settings());
+            assert_eq!(crash_loop.start(clock(minute)), Mode::Normal);
+        }
+        let mut crash_loop = CrashLoop::load(&path, settings());
+        assert_eq!(crash_loop.start(clock(3)), Mode::Safe);
+        assert_eq!(CrashLoop::load(&path, settings()).state.mode(), Mode::Safe);
+
+        // malformed state is ignored
+        fs::write(&path, "{").unwrap();
+        assert_eq!(CrashLoop::load(&path, settings()).state, State::default());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_force_safe_mode() {
+        let path = std::env::temp_dir().join(format!(
+            "bosminer-crash-loop-forced-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        CrashLoop::load(&path, settings()).force_safe_mode();
+        let mut crash_loop = CrashLoop::load(&path, settings());
+        assert_eq!(crash_loop.start(clock(0)), Mode::Safe);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -21,7 +21,8 @@
 // contact us at opensource@braiins.com.
 
 //! This module contains interface for reading from sensor (`Sensor`) and what
-//! constitutes a sensor reading (`Temperature`, `Measurement`).
+//! constitutes a sensor reading (`Temperature`, `Measurement`). All sensors of one hashboard
+//! are handled by `Manager` which labels their channels and filters out implausible readouts.
 //!
 //! HOW TO EXTEND THIS IN THE FUTURE
 //!
@@ -29,16 +30,22 @@
 //!   sensor drivers. Struct `Measurement` is OK for now, it represents more or less the outcomes
 //!   of temperature readout.
 //!
-//! * Each miner has a topology of sensors. It also has to know how to interpret the readout of
-//!   each sensor (ie. make a `IntoS9Temperature` trait and then implement
-//!   `IntoS9Temperature<TMP451SensorReadout>` and the like).
+//! * Each miner has a topology of sensors. `Manager` knows only sensor placements of S9
+//!   hashboard revisions, other miners will have to provide their own ones. It also has to know
+//!   how to interpret the readout of each sensor (ie. make a `IntoS9Temperature` trait and then
+//!   implement `IntoS9Temperature<TMP451SensorReadout>` and the like).
 //!
 //! * Maybe provide a generic temperature readout structure that has just the `local` and `remote`
 //!   portions (and make a conversion function when needed).
 
+mod filter;
+mod manager;
 mod tmp42x;
 mod tmp451;
 
+pub use filter::Fault;
+pub use manager::{Location, Manager, Reading, Readings};
+
 use crate::error;
 use crate::i2c;
 
@@ -46,6 +53,7 @@ use async_trait::async_trait;
 use ii_logging::macros::*;
 use lazy_static::lazy_static;
 use std::boxed::Box;
+use std::fmt;
 
 /// Generic sensor
 #[async_trait]
@@ -106,6 +114,68 @@ pub const INVALID_TEMPERATURE_READING: Temperature = Temperature {
     remote: Measurement::InvalidReading,
 };
 
+/// Model of the detected sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    TMP451,
+    /// TMP421/TMP422/TMP423 with given number of remote channels
+    TMP42x(usize),
+    ADT7461,
+    NCT218,
+}
+
+impl Model {
+    /// Identify the model from SMBus manufacturer and device ID
+    fn identify(manufacturer_id: u8, device_id: u8) -> Option<Self> {
+        match manufacturer_id {
+            0x55 => match device_id {
+                0x21 | 0x22 | 0x23 => Some(Model::TMP42x(device_id as usize - 0x20)),
+                _ => Some(Model::TMP451),
+            },
+            0x41 => Some(Model::ADT7461),
+            0x1a => Some(Model::NCT218),
+            _ => None,
+        }
+    }
+
+    /// Sensor measures temperature of a remote diode (besides its local temperature)
+    pub fn has_remote(&self) -> bool {
+        match self {
+            Model::NCT218 => false,
+            _ => true,
+        }
+    }
+
+    fn into_driver(self, i2c_device: Box<dyn i2c::AsyncDevice>) -> Box<dyn Sensor> {
+        match self {
+            Model::TMP451 => tmp451::TMP451::new(i2c_device),
+            Model::TMP42x(num_remote_sensors) => {
+                tmp42x::TMP42x::new(i2c_device, num_remote_sensors)
+            }
+            Model::ADT7461 => tmp451::ADT7461::new(i2c_device),
+            Model::NCT218 => tmp451::NCT218::new(i2c_device),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Model::TMP451 => write!(f, "TMP451"),
+            Model::TMP42x(num_remote_sensors) => write!(f, "TMP42{}", num_remote_sensors),
+            Model::ADT7461 => write!(f, "ADT7461"),
+            Model::NCT218 => write!(f, "NCT218"),
+        }
+    }
+}
+
+/// Sensor found on the I2C bus
+pub struct Detected {
+    pub address: i2c::Address,
+    pub model: Model,
+    pub driver: Box<dyn Sensor>,
+}
+
 /// Probe one I2C address for known sensor
 ///
 /// The reason for not using unified API for driver probing is that the sensor detection logic
@@ -113,7 +183,7 @@ pub const INVALID_TEMPERATURE_READING: Temperature = Temperature {
 /// changes to the "probe API" with each new sensor.
 pub async fn probe_i2c_device(
     mut i2c_device: Box<dyn i2c::AsyncDevice>,
-) -> error::Result<Option<Box<dyn Sensor>>> {
+) -> error::Result<Option<Detected>> {
     // Interesting SMBus registers
     const REG_MANUFACTURER_ID: u8 = 0xfe;
     const REG_DEVICE_ID: u8 = 0xff;
@@ -130,38 +200,46 @@ pub async fn probe_i2c_device(
     );
 
     // Decide which sensor to use
-    let sensor = match manufacturer_id {
-        0x55 => match device_id {
-            0x21 | 0x22 | 0x23 => Some(tmp42x::TMP42x::new(i2c_device, device_id as usize - 0x20)),
-            _ => Some(tmp451::TMP451::new(i2c_device)),
-        },
-        0x41 => Some(tmp451::ADT7461::new(i2c_device)),
-        0x1a => Some(tmp451::NCT218::new(i2c_device)),
-        _ => None,
-    };
-
-    Ok(sensor)
+    Ok(
+        Model::identify(manufacturer_id, device_id).map(|model| Detected {
+            address: i2c_device.get_address(),
+            model,
+            driver: model.into_driver(i2c_device),
+        }),
+    )
 }
 
-/// Probe for known addresses for supported sensors
+/// Probe all known addresses for supported sensors. A failure of one address does not prevent
+/// probing of the others, the error is returned only when no sensor has been found.
 pub async fn probe_i2c_sensors<T: 'static + i2c::AsyncBus + Clone>(
     i2c_bus: T,
-) -> error::Result<Option<Box<dyn Sensor>>> {
+) -> error::Result<Vec<Detected>> {
+    let mut detected = vec![];
+    let mut last_error = None;
+
     // Go through all known addresses
     for address in SENSOR_I2C_ADDRESS.iter() {
         // Construct device at given i2c address
         let i2c_device = Box::new(i2c::Device::new(i2c_bus.clone(), *address));
 
         // Try to probe this device
-        match probe_i2c_device(i2c_device).await? {
-            // OK, there's one
-            sensor @ Some(_) => return Ok(sensor),
-            _ => (),
+        match probe_i2c_device(i2c_device).await {
+            Ok(Some(sensor)) => {
+                info!("Found sensor {} at {}", sensor.model, sensor.address);
+                detected.push(sensor);
+            }
+            Ok(None) => (),
+            Err(e) => {
+                warn!("Probing of sensor at {} failed: {}", address, e);
+                last_error = Some(e);
+            }
         }
     }
 
-    // No sensors were found
-    Ok(None)
+    match last_error {
+        Some(e) if detected.is_empty() => Err(e),
+        _ => Ok(detected),
+    }
 }
 
 #[cfg(test)]
@@ -170,7 +248,7 @@ mod test {
     use i2c::test_utils;
     use ii_async_compat::tokio;
 
-    async fn test_probe_address(addr: u8, man_id: u8, dev_id: u8) -> bool {
+    async fn test_probe_address(addr: u8, man_id: u8, dev_id: u8) -> Option<Model> {
         let bus = test_utils::FakeI2cBus::new(
             i2c::Address::new(addr),
             &[
@@ -182,16 +260,32 @@ mod test {
         );
         let bus = i2c::SharedBus::new(bus);
         let result = probe_i2c_sensors(bus).await.unwrap();
-        result.is_some()
+        assert!(result.len() <= 1);
+        result.into_iter().next().map(|sensor| {
+            assert_eq!(sensor.address, i2c::Address::new(addr));
+            sensor.model
+        })
     }
 
     #[tokio::test]
     async fn inner_test_probe_i2c_sensors() {
-        assert_eq!(test_probe_address(0x98, 0x55, 0x13).await, true);
-        assert_eq!(test_probe_address(0x98, 0x55, 0x21).await, true);
-        assert_eq!(test_probe_address(0x9a, 0x41, 0x12).await, true);
-        assert_eq!(test_probe_address(0x9c, 0x1a, 0x37).await, true);
-        assert_eq!(test_probe_address(0x9c, 0x37, 0x21).await, false);
-        assert_eq!(test_probe_address(0x84, 0x55, 0x21).await, false);
+        assert_eq!(
+            test_probe_address(0x98, 0x55, 0x13).await,
+            Some(Model::TMP451)
+        );
+        assert_eq!(
+            test_probe_address(0x98, 0x55, 0x21).await,
+            Some(Model::TMP42x(1))
+        );
+        assert_eq!(
+            test_probe_address(0x9a, 0x41, 0x12).await,
+            Some(Model::ADT7461)
+        );
+        assert_eq!(
+            test_probe_address(0x9c, 0x1a, 0x37).await,
+            Some(Model::NCT218)
+        );
+        assert_eq!(test_probe_address(0x9c, 0x37, 0x21).await, None);
+        assert_eq!(test_probe_address(0x84, 0x55, 0x21).await, None);
     }
 }
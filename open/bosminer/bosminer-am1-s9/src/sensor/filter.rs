@@ -0,0 +1,301 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Plausibility filter of one temperature channel. Readouts which are physically impossible or
+//! which change faster than the hashboard can heat up or cool down are rejected and the last
+//! plausible value is held instead. The held value becomes stale when no plausible readout
+//! arrives for some time.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Readouts below this temperature are impossible for a running hashboard
+const MIN_TEMPERATURE: f32 = -40.0;
+/// Readouts above this temperature are impossible (the chips would be destroyed long before)
+const MAX_TEMPERATURE: f32 = 150.0;
+/// Maximal change between two consecutive plausible readouts regardless of the time between them
+const MAX_STEP: f32 = 10.0;
+/// Maximal rate of change in degrees per second added to `MAX_STEP`
+const MAX_RATE: f32 = 1.0;
+/// Number of consistent readouts after which a jump is accepted as a new temperature level
+const CONFIRM_COUNT: usize = 3;
+/// Temperature of other channels has to change at least by this value while this channel keeps
+/// exactly the same readout to consider it stuck
+const STUCK_REFERENCE_DELTA: f32 = 5.0;
+/// Held value is stale when there has been no plausible readout for this time
+pub const STALE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Reason why the last readout has been rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fault {
+    /// Sensor has not provided any valid readout
+    Dropout,
+    /// Readout is physically impossible
+    OutOfRange(f32),
+    /// Readout differs too much from the last plausible value
+    Spike(f32),
+    /// Readout does not follow temperature changes seen by other channels
+    Stuck(f32),
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::Dropout => write!(f, "dropout"),
+            Fault::OutOfRange(value) => write!(f, "out of range ({})", value),
+            Fault::Spike(value) => write!(f, "spike ({})", value),
+            Fault::Stuck(value) => write!(f, "stuck ({})", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Filter {
+    /// Time of filter creation (staleness is measured from it until the first plausible value)
+    created: Instant,
+    /// Last plausible value and the time of its readout
+    last_good: Option<(f32, Instant)>,
+    /// Level of readouts rejected as a spike and the number of consecutive readouts near it
+    candidate: Option<(f32, usize)>,
+    /// Last readout in the valid range and the reference temperature when it has changed
+    last_raw: Option<(f32, Option<f32>)>,
+    /// Reason of rejecting the last readout
+    fault: Option<Fault>,
+}
+
+impl Filter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            created: now,
+            last_good: None,
+            candidate: None,
+            last_raw: None,
+            fault: None,
+        }
+    }
+
+    /// Process new `readout` (`None` when the sensor has not provided any valid value).
+    /// The `reference` is temperature seen by other channels of the same hashboard and it is
+    /// used for detection of stuck sensors.
+    pub fn update(&mut self, now: Instant, readout: Option<f32>, reference: Option<f32>) {
+        self.fault = self.check(now, readout, reference).err();
+    }
+
+    fn check(
+        &mut self,
+        now: Instant,
+        readout: Option<f32>,
+        reference: Option<f32>,
+    ) -> Result<(), Fault> {
+        let value = readout.ok_or(Fault::Dropout)?;
+        if !(MIN_TEMPERATURE..=MAX_TEMPERATURE).contains(&value) {
+            return Err(Fault::OutOfRange(value));
+        }
+
+        match self.last_raw {
+            Some((last_value, Some(last_reference))) if last_value == value => {
+                if let Some(reference) = reference {
+                    if (reference - last_reference).abs() >= STUCK_REFERENCE_DELTA {
+                        return Err(Fault::Stuck(value));
+                    }
+                }
+            }
+            _ => self.last_raw = Some((value, reference)),
+        }
+
+        if let Some((last_value, last_time)) = self.last_good {
+            let elapsed = now.duration_since(last_time).as_secs_f32();
+            if (value - last_value).abs() > MAX_STEP + MAX_RATE * elapsed {
+                // The jump is accepted only when it is confirmed by consecutive readouts
+                let count = match self.candidate {
+                    Some((level, count)) if (value - level).abs() <= MAX_STEP => count + 1,
+                    _ => 1,
+                };
+                if count < CONFIRM_COUNT {
+                    self.candidate = Some((value, count));
+                    return Err(Fault::Spike(value));
+                }
+            }
+        }
+
+        self.candidate = None;
+        self.last_good = Some((value, now));
+        Ok(())
+    }
+
+    /// Last plausible value (held when the following readouts have been rejected)
+    #[inline]
+    pub fn value(&self) -> Option<f32> {
+        self.last_good.map(|(value, _)| value)
+    }
+
+    /// Reason of rejecting the last readout (`None` when it has been accepted)
+    #[inline]
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    /// There has been no plausible readout for `STALE_TIMEOUT`
+    pub fn is_stale(&self, now: Instant) -> bool {
+        let since = self.last_good.map_or(self.created, |(_, time)| time);
+        now.duration_since(since) >= STALE_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_secs(5);
+
+    /// Feed the filter with `trace` of readouts taken each `PERIOD` without any reference and
+    /// return the filter state after each of them
+    fn run(trace: &[Option<f32>]) -> Vec<(Option<f32>, Option<Fault>, bool)> {
+        let start = Instant::now();
+        let mut filter = Filter::new(start);
+        trace
+            .iter()
+            .enumerate()
+            .map(|(i, readout)| {
+                let now = start + PERIOD * i as u32;
+                filter.update(now, *readout, None);
+                (filter.value(), filter.fault(), filter.is_stale(now))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_plausible() {
+        let result = run(&[Some(40.0), Some(45.5), Some(52.0), Some(60.0), Some(58.0)]);
+        for (state, expected) in result.iter().zip(&[40.0, 45.5, 52.0, 60.0, 58.0]) {
+            assert_eq!(*state, (Some(*expected), None, false));
+        }
+    }
+
+    #[test]
+    fn test_filter_out_of_range() {
+        let result = run(&[Some(60.0), Some(-64.0), Some(191.0), Some(61.0)]);
+        assert_eq!(
+            result[1],
+            (Some(60.0), Some(Fault::OutOfRange(-64.0)), false)
+        );
+        assert_eq!(
+            result[2],
+            (Some(60.0), Some(Fault::OutOfRange(191.0)), false)
+        );
+        assert_eq!(result[3], (Some(61.0), None, false));
+        // impossible value is never accepted even as the first one
+        assert_eq!(run(&[Some(f32::NAN)])[0].0, None);
+    }
+
+    #[test]
+    fn test_filter_spiking() {
+        // single spikes are rejected and the last good value is held
+        let result = run(&[
+            Some(60.0),
+            Some(127.0),
+            Some(61.0),
+            Some(0.0),
+            Some(62.0),
+            Some(127.0),
+            Some(63.0),
+        ]);
+        let values: Vec<_> = result.iter().map(|(value, _, _)| value.unwrap()).collect();
+        assert_eq!(values, vec![60.0, 60.0, 61.0, 61.0, 62.0, 62.0, 63.0]);
+        assert_eq!(result[1].1, Some(Fault::Spike(127.0)));
+        assert_eq!(result[3].1, Some(Fault::Spike(0.0)));
+        assert!(result.iter().all(|(_, _, stale)| !stale));
+
+        // the new level is accepted when it is confirmed by consecutive readouts
+        let result = run(&[Some(40.0), Some(75.0), Some(76.0), Some(75.5), Some(76.0)]);
+        assert_eq!(result[1], (Some(40.0), Some(Fault::Spike(75.0)), false));
+        assert_eq!(result[2], (Some(40.0), Some(Fault::Spike(76.0)), false));
+        assert_eq!(result[3], (Some(75.5), None, false));
+        assert_eq!(result[4], (Some(76.0), None, false));
+    }
+
+    #[test]
+    fn test_filter_dropout() {
+        let mut trace = vec![Some(60.0)];
+        trace.extend(vec![None; 6]);
+        trace.push(Some(80.0));
+        let result = run(&trace);
+
+        // last good value is held during the dropout...
+        assert_eq!(result[1], (Some(60.0), Some(Fault::Dropout), false));
+        assert_eq!(result[5], (Some(60.0), Some(Fault::Dropout), false));
+        // ... until it becomes stale
+        assert_eq!(result[6], (Some(60.0), Some(Fault::Dropout), true));
+        // the readout after the dropout is allowed to differ more
+        assert_eq!(result[7], (Some(80.0), None, false));
+
+        // sensor without any readout is stale since its detection
+        let result = run(&[None; 7]);
+        assert_eq!(result[5], (None, Some(Fault::Dropout), false));
+        assert_eq!(result[6], (None, Some(Fault::Dropout), true));
+    }
+
+    #[test]
+    fn test_filter_stuck() {
+        let start = Instant::now();
+        let mut filter = Filter::new(start);
+        let trace = [
+            (55.0, 40.0),
+            (55.0, 41.0),
+            (55.0, 43.0),
+            (55.0, 44.0),
+            (55.0, 47.0),
+            (55.0, 50.0),
+            (55.0, 52.0),
+            (55.0, 54.0),
+            (55.0, 56.0),
+            (55.0, 58.0),
+            (55.0, 60.0),
+            (55.0, 62.0),
+            (55.0, 64.0),
+        ];
+        let mut faults = vec![];
+        let mut stale = vec![];
+        for (i, (readout, reference)) in trace.iter().enumerate() {
+            let now = start + PERIOD * i as u32;
+            filter.update(now, Some(*readout), Some(*reference));
+            faults.push(filter.fault());
+            stale.push(filter.is_stale(now));
+        }
+        // readout is fine while the other channels do not move much
+        assert!(faults[..4].iter().all(Option::is_none));
+        // then it is rejected as stuck and eventually becomes stale
+        assert!(faults[4..]
+            .iter()
+            .all(|fault| *fault == Some(Fault::Stuck(55.0))));
+        assert!(!stale[8]);
+        assert!(stale[9]);
+        assert_eq!(filter.value(), Some(55.0));
+
+        // the sensor recovers when the readout changes again
+        let now = start + PERIOD * trace.len() as u32;
+        filter.update(now, Some(63.5), Some(64.0));
+        assert_eq!(filter.fault(), None);
+        assert!(!filter.is_stale(now));
+        assert_eq!(filter.value(), Some(63.5));
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Management of all temperature sensors of one hashboard. Sensors which respond at
+//! initialization are classified by sensor placements of known hashboard revisions and each of
+//! their channels (local and remote temperature) is passed through a plausibility filter.
+
+use ii_logging::macros::*;
+
+use super::filter::{self, Filter};
+use super::{Detected, Model, Sensor};
+
+use crate::error::{self, ErrorKind};
+use crate::i2c;
+
+use failure::ResultExt;
+
+use std::fmt;
+use std::iter;
+use std::time::Instant;
+
+/// What the temperature channel measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// Temperature of the hashboard PCB
+    Board,
+    /// Temperature of a hashing chip
+    Chip,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Board => write!(f, "board"),
+            Location::Chip => write!(f, "chip"),
+        }
+    }
+}
+
+/// Placement of one sensor on the hashboard: labels and locations of its local and remote
+/// channels
+struct Placement {
+    address: u8,
+    local: (&'static str, Location),
+    remote: (&'static str, Location),
+}
+
+/// Sensor placements of known hashboard revisions. Each S9 revision has a single sensor measuring
+/// the PCB temperature with a remote diode in the hashing chip but they differ in its address.
+const KNOWN_REVISIONS: &[&[Placement]] = &[
+    &[Placement {
+        address: 0x98,
+        local: ("Board", Location::Board),
+        remote: ("Chip", Location::Chip),
+    }],
+    &[Placement {
+        address: 0x9a,
+        local: ("Board", Location::Board),
+        remote: ("Chip", Location::Chip),
+    }],
+    &[Placement {
+        address: 0x9c,
+        local: ("Board", Location::Board),
+        remote: ("Chip", Location::Chip),
+    }],
+];
+
+/// Find placement of each detected sensor in the known revision which has exactly the detected
+/// sensors
+fn find_placements(addresses: &[i2c::Address]) -> Option<Vec<&'static Placement>> {
+    KNOWN_REVISIONS
+        .iter()
+        .filter(|placements| placements.len() == addresses.len())
+        .find_map(|placements| {
+            addresses
+                .iter()
+                .map(|address| {
+                    placements
+                        .iter()
+                        .find(|placement| i2c::Address::new(placement.address) == *address)
+                })
+                .collect()
+        })
+}
+
+/// Filtered readout of one temperature channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub label: String,
+    pub location: Location,
+    /// Last plausible value (it is held when the following readouts have been rejected)
+    pub value: Option<f32>,
+    /// Reason of rejecting the last readout
+    pub fault: Option<filter::Fault>,
+    /// There has been no plausible readout for some time and the value cannot be trusted
+    pub stale: bool,
+}
+
+impl Reading {
+    /// Value which may be used for temperature control
+    pub fn plausible_value(&self) -> Option<f32> {
+        if self.stale {
+            None
+        } else {
+            self.value
+        }
+    }
+}
+
+/// Filtered readouts of all channels of one hashboard
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Readings {
+    pub channels: Vec<Reading>,
+}
+
+impl Readings {
+    /// No sensor has been detected
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Highest plausible temperature measured at `location`
+    pub fn max(&self, location: Location) -> Option<f32> {
+        self.channels
+            .iter()
+            .filter(|reading| reading.location == location)
+            .filter_map(Reading::plausible_value)
+            .fold(None, |max: Option<f32>, value| {
+                Some(max.map_or(value, |max| max.max(value)))
+            })
+    }
+
+    /// There are sensors but none of them provides any plausible value
+    pub fn all_stale(&self) -> bool {
+        !self.is_empty() && self.channels.iter().all(|reading| reading.stale)
+    }
+}
+
+struct Channel {
+    label: String,
+    location: Location,
+    filter: Filter,
+}
+
+impl Channel {
+    fn new((label, location): (String, Location), now: Instant) -> Self {
+        Self {
+            label,
+            location,
+            filter: Filter::new(now),
+        }
+    }
+}
+
+struct ManagedSensor {
+    address: i2c::Address,
+    model: Model,
+    driver: Box<dyn Sensor>,
+    local: Channel,
+    /// Missing when the sensor does not measure any remote temperature
+    remote: Option<Channel>,
+}
+
+impl ManagedSensor {
+    fn channels(&self) -> impl Iterator<Item = &Channel> {
+        iter::once(&self.local).chain(self.remote.as_ref())
+    }
+
+    fn channels_mut(&mut self) -> impl Iterator<Item = &mut Channel> {
+        iter::once(&mut self.local).chain(self.remote.as_mut())
+    }
+}
+
+/// All initialized sensors of one hashboard
+pub struct Manager {
+    sensors: Vec<ManagedSensor>,
+}
+
+impl Manager {
+    /// Classify `detected` sensors which have been already initialized
+    pub fn new(detected: Vec<Detected>, now: Instant) -> Self {
+        let addresses: Vec<_> = detected.iter().map(|sensor| sensor.address).collect();
+        let placements = find_placements(&addresses);
+        if placements.is_none() {
+            let addresses: Vec<_> = addresses.iter().map(i2c::Address::to_string).collect();
+            warn!(
+                "Unknown placement of sensors at {}, using generic labels",
+                addresses.join(", ")
+            );
+        }
+
+        let sensors = detected
+            .into_iter()
+            .enumerate()
+            .map(|(i, sensor)| {
+                let (local, remote) = match &placements {
+                    Some(placements) => (
+                        (placements[i].local.0.to_string(), placements[i].local.1),
+                        (placements[i].remote.0.to_string(), placements[i].remote.1),
+                    ),
+                    // Remote diode is always in a hashing chip on known hashboards
+                    None => (
+                        (format!("Sensor {} Local", sensor.address), Location::Board),
+                        (format!("Sensor {} Remote", sensor.address), Location::Chip),
+                    ),
+                };
+                ManagedSensor {
+                    address: sensor.address,
+                    model: sensor.model,
+                    driver: sensor.driver,
+                    local: Channel::new(local, now),
+                    remote: if sensor.model.has_remote() {
+                        Some(Channel::new(remote, now))
+                    } else {
+                        None
+                    },
+                }
+            })
+            .collect();
+        Self { sensors }
+    }
+
+    /// Detect and initialize all sensors on the `i2c_bus`. Sensors which fail to initialize are
+    /// skipped.
+    pub async fn probe<T: 'static + i2c::AsyncBus + Clone>(i2c_bus: T) -> error::Result<Self> {
+        let detected = super::probe_i2c_sensors(i2c_bus)
+            .await
+            .with_context(|_| ErrorKind::Sensors("error when probing sensors".into()))?;
+
+        let mut initialized = vec![];
+        for mut sensor in detected {
+            match sensor.driver.init().await {
+                Ok(()) => initialized.push(sensor),
+                Err(e) => error!(
+                    "Failed to initialize sensor {} at {}: {}",
+                    sensor.model, sensor.address, e
+                ),
+            }
+        }
+        if initialized.is_empty() {
+            Err(ErrorKind::Sensors("no sensors found".into()))?
+        }
+        Ok(Self::new(initialized, Instant::now()))
+    }
+
+    fn channels(&self) -> impl Iterator<Item = &Channel> {
+        self.sensors.iter().flat_map(ManagedSensor::channels)
+    }
+
+    fn channels_mut(&mut self) -> impl Iterator<Item = &mut Channel> {
+        self.sensors
+            .iter_mut()
+            .flat_map(ManagedSensor::channels_mut)
+    }
+
+    /// Read all sensors and pass their readouts through the plausibility filters. All sensors
+    /// are read even when some of them fail, the last error is returned then.
+    pub async fn update(&mut self, now: Instant) -> error::Result<()> {
+        let mut result = Ok(());
+        let mut readouts = vec![];
+        for sensor in self.sensors.iter_mut() {
+            let temperature = match sensor.driver.read_temperature().await {
+                Ok(temperature) => {
+                    info!(
+                        "Measured temperature by {} at {}: {:?}",
+                        sensor.model, sensor.address, temperature
+                    );
+                    temperature
+                }
+                Err(e) => {
+                    error!(
+                        "Sensor {} at {} temperature read failed: {}",
+                        sensor.model, sensor.address, e
+                    );
+                    result = Err(e);
+                    super::INVALID_TEMPERATURE_READING
+                }
+            };
+            readouts.push(Option::<f32>::from(temperature.local));
+            if sensor.remote.is_some() {
+                readouts.push(temperature.remote.into());
+            }
+        }
+
+        // Stuck channels are detected against the plausible values of the other channels
+        let values: Vec<_> = self
+            .channels()
+            .map(|channel| {
+                if channel.filter.is_stale(now) {
+                    None
+                } else {
+                    channel.filter.value()
+                }
+            })
+            .collect();
+        for (i, (channel, readout)) in self.channels_mut().zip(readouts).enumerate() {
+            let others: Vec<f32> = values
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .filter_map(|(_, value)| *value)
+                .collect();
+            let reference = match others.len() {
+                0 => None,
+                len => Some(others.iter().sum::<f32>() / len as f32),
+            };
+            channel.filter.update(now, readout, reference);
+            if let Some(fault) = channel.filter.fault() {
+                warn!("Temperature of '{}' rejected: {}", channel.label, fault);
+            }
+        }
+        result
+    }
+
+    /// Filtered readouts of all channels
+    pub fn readings(&self, now: Instant) -> Readings {
+        Readings {
+            channels: self
+                .channels()
+                .map(|channel| Reading {
+                    label: channel.label.clone(),
+                    location: channel.location,
+                    value: channel.filter.value(),
+                    fault: channel.filter.fault(),
+                    stale: channel.filter.is_stale(now),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::sensor::{Measurement, Temperature};
+
+    use async_trait::async_trait;
+    use ii_async_compat::tokio;
+
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    /// Sensor replaying a prepared trace of readouts (`None` is a failed read)
+    struct FakeSensor {
+        trace: VecDeque<Option<(f32, f32)>>,
+    }
+
+    #[async_trait]
+    impl Sensor for FakeSensor {
+        async fn init(&mut self) -> error::Result<()> {
+            Ok(())
+        }
+
+        async fn read_temperature(&mut self) -> error::Result<Temperature> {
+            match self.trace.pop_front().flatten() {
+                Some((local, remote)) => Ok(Temperature {
+                    local: Measurement::Ok(local),
+                    remote: Measurement::Ok(remote),
+                }),
+                None => Err(ErrorKind::Sensors("read failed".into()))?,
+            }
+        }
+    }
+
+    fn detected(address: u8, model: Model, trace: &[Option<(f32, f32)>]) -> Detected {
+        Detected {
+            address: i2c::Address::new(address),
+            model,
+            driver: Box::new(FakeSensor {
+                trace: trace.iter().cloned().collect(),
+            }),
+        }
+    }
+
+    fn labels(readings: &Readings) -> Vec<(&str, Location)> {
+        readings
+            .channels
+            .iter()
+            .map(|reading| (reading.label.as_str(), reading.location))
+            .collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let now = Instant::now();
+
+        let manager = Manager::new(vec![detected(0x9a, Model::TMP451, &[])], now);
+        assert_eq!(
+            labels(&manager.readings(now)),
+            vec![("Board", Location::Board), ("Chip", Location::Chip)]
+        );
+
+        // sensors without known placement get generic labels
+        let manager = Manager::new(
+            vec![
+                detected(0x98, Model::TMP451, &[]),
+                detected(0x9c, Model::NCT218, &[]),
+            ],
+            now,
+        );
+        assert_eq!(
+            labels(&manager.readings(now)),
+            vec![
+                ("Sensor 0x98 Local", Location::Board),
+                ("Sensor 0x98 Remote", Location::Chip),
+                ("Sensor 0x9c Local", Location::Board),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manager_readings() {
+        let start = Instant::now();
+        let period = Duration::from_secs(5);
+        let mut trace = vec![Some((50.0, 60.0)), Some((51.0, 127.0))];
+        trace.extend(vec![None; 6]);
+        let mut manager = Manager::new(vec![detected(0x98, Model::TMP451, &trace)], start);
+
+        manager.update(start).await.expect("BUG: read failed");
+        let readings = manager.readings(start);
+        assert_eq!(readings.max(Location::Board), Some(50.0));
+        assert_eq!(readings.max(Location::Chip), Some(60.0));
+
+        // chip temperature spike is rejected and the last good value is held
+        manager
+            .update(start + period)
+            .await
+            .expect("BUG: read failed");
+        let readings = manager.readings(start + period);
+        assert_eq!(readings.max(Location::Board), Some(51.0));
+        assert_eq!(readings.max(Location::Chip), Some(60.0));
+        assert_eq!(
+            readings.channels[1].fault,
+            Some(filter::Fault::Spike(127.0))
+        );
+        assert!(!readings.all_stale());
+
+        // sensor stops responding and the values become stale
+        for i in 2..8 {
+            let now = start + period * i;
+            assert!(manager.update(now).await.is_err());
+            let readings = manager.readings(now);
+            assert_eq!(readings.channels[0].fault, Some(filter::Fault::Dropout));
+            assert_eq!(readings.all_stale(), i >= 7);
+        }
+        let readings = manager.readings(start + period * 7);
+        assert_eq!(readings.max(Location::Board), None);
+        assert_eq!(readings.max(Location::Chip), None);
+        assert_eq!(readings.channels[0].value, Some(51.0));
+
+        // there is nothing stale without sensors
+        assert!(!Readings::default().all_stale());
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! `status` sub-command which queries a running instance of bosminer over the CGMiner API and
+//! renders a compact summary for humans
+
+use bosminer_config::clap;
+
+use serde::Deserialize;
+use serde_json as json;
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Default address of the CGMiner API of the local bosminer
+pub const DEFAULT_HOST: &str = "127.0.0.1";
+pub const DEFAULT_PORT: u16 = 4028;
+
+/// Timeout for connecting to the API and for each read/write on the connection
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Blocking CGMiner API client issuing one command per connection
+#[derive(Debug, Clone)]
+pub struct Client {
+    addr: SocketAddr,
+}
+
+impl Client {
+    pub fn new(host: &str, port: u16) -> Result<Self, String> {
+        let addr = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("cannot resolve '{}': {}", host, e))?
+            .next()
+            .ok_or_else(|| format!("no address found for '{}'", host))?;
+        Ok(Self { addr })
+    }
+
+    /// Send `command` and return the parsed response. Responses with error status are returned
+    /// as an error with the message from the server.
+    pub fn query(&self, command: &str) -> Result<json::Value, String> {
+        let mut stream = TcpStream::connect_timeout(&self.addr, TIMEOUT)
+            .map_err(|e| format!("cannot connect to {}: {}", self.addr, e))?;
+        let io_error = |e| format!("'{}' request to {} failed: {}", command, self.addr, e);
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(io_error)?;
+        stream.set_write_timeout(Some(TIMEOUT)).map_err(io_error)?;
+        stream
+            .write_all(json::json!({ "command": command }).to_string().as_bytes())
+            .map_err(io_error)?;
+        // The server closes the connection after the response has been sent
+        stream.shutdown(Shutdown::Write).map_err(io_error)?;
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).map_err(io_error)?;
+
+        parse_response(command, &buf)
+    }
+}
+
+/// Parse null terminated JSON response and check its status
+fn parse_response(command: &str, buf: &[u8]) -> Result<json::Value, String> {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let response: json::Value = json::from_slice(&buf[..end])
+        .map_err(|e| format!("invalid response to '{}': {}", command, e))?;
+
+    match response["STATUS"][0]["STATUS"].as_str() {
+        Some("S") | Some("I") => Ok(response),
+        Some(_) => Err(format!(
+            "'{}' failed: {}",
+            command,
+            response["STATUS"][0]["Msg"]
+                .as_str()
+                .unwrap_or("unknown error")
+        )),
+        None => Err(format!("missing status in response to '{}'", command)),
+    }
+}
+
+/// Deserialize list stored under `name` in the response
+fn parse_list<T>(response: &json::Value, name: &str) -> Result<Vec<T>, String>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match response.get(name) {
+        Some(list) => json::from_value(list.clone())
+            .map_err(|e| format!("cannot parse {} in response: {}", name, e)),
+        None => Err(format!("missing {} in response", name)),
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Summary {
+    #[serde(rename = "Elapsed")]
+    pub elapsed: u64,
+    #[serde(rename = "MHS av")]
+    pub mhs_av: f64,
+    #[serde(rename = "MHS 5s")]
+    pub mhs_5s: f64,
+    #[serde(rename = "Accepted")]
+    pub accepted: u64,
+    #[serde(rename = "Rejected")]
+    pub rejected: u64,
+    #[serde(rename = "Hardware Errors")]
+    pub hardware_errors: i64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Dev {
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "MHS av")]
+    pub mhs_av: f64,
+    #[serde(rename = "MHS 5s")]
+    pub mhs_5s: f64,
+    #[serde(rename = "Accepted")]
+    pub accepted: i64,
+    #[serde(rename = "Rejected")]
+    pub rejected: i64,
+    #[serde(rename = "Hardware Errors")]
+    pub hardware_errors: i64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Pool {
+    #[serde(rename = "POOL")]
+    pub idx: i32,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "User")]
+    pub user: String,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Accepted")]
+    pub accepted: u64,
+    #[serde(rename = "Rejected")]
+    pub rejected: u64,
+    #[serde(rename = "Stale")]
+    pub stale: u64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Temp {
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Board")]
+    pub board: f64,
+    #[serde(rename = "Chip")]
+    pub chip: f64,
+}
+
+/// Status of the running miner collected from `summary`, `devs`, `pools` and `temps` commands
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub summary: Summary,
+    pub devs: Vec<Dev>,
+    pub pools: Vec<Pool>,
+    /// Per-chain temperatures are empty when the `temps` command is not available
+    pub temps: Vec<Temp>,
+}
+
+impl Status {
+    pub fn query(client: &Client) -> Result<Self, String> {
+        let summary = parse_list::<Summary>(&client.query("summary")?, "SUMMARY")?
+            .pop()
+            .ok_or_else(|| "empty SUMMARY in response".to_string())?;
+        let devs = parse_list(&client.query("devs")?, "DEVS")?;
+        let pools = parse_list(&client.query("pools")?, "POOLS")?;
+        let temps = client
+            .query("temps")
+            .and_then(|response| parse_list(&response, "TEMPS"))
+            .unwrap_or_default();
+
+        Ok(Self {
+            summary,
+            devs,
+            pools,
+            temps,
+        })
+    }
+
+    fn temp(&self, id: i32) -> Option<&Temp> {
+        self.temps.iter().find(|temp| temp.id == id)
+    }
+}
+
+/// Convert MH/s reported by the API to TH/s
+fn ths(mhs: f64) -> f64 {
+    mhs / 1_000_000.0
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = &self.summary;
+        writeln!(
+            f,
+            "uptime: {} s, hashrate: {:.2} TH/s (5s {:.2} TH/s), accepted: {}, rejected: {}, \
+             hw errors: {}",
+            summary.elapsed,
+            ths(summary.mhs_av),
+            ths(summary.mhs_5s),
+            summary.accepted,
+            summary.rejected,
+            summary.hardware_errors
+        )?;
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>5}  {:<8}  {:>9}  {:>9}  {:>8}  {:>8}  {:>8}  {:>6}  {:>6}",
+            "CHAIN", "STATUS", "TH/s av", "TH/s 5s", "ACC", "REJ", "HW", "BOARD", "CHIP"
+        )?;
+        for dev in self.devs.iter() {
+            let (board, chip) = match self.temp(dev.id) {
+                Some(temp) => (format!("{:.0}", temp.board), format!("{:.0}", temp.chip)),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            writeln!(
+                f,
+                "{:>5}  {:<8}  {:>9.2}  {:>9.2}  {:>8}  {:>8}  {:>8}  {:>6}  {:>6}",
+                dev.id,
+                dev.status,
+                ths(dev.mhs_av),
+                ths(dev.mhs_5s),
+                dev.accepted,
+                dev.rejected,
+                dev.hardware_errors,
+                board,
+                chip
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>4}  {:<8}  {:>8}  {:>8}  {:>8}  URL (USER)",
+            "POOL", "STATUS", "ACC", "REJ", "STALE"
+        )?;
+        for pool in self.pools.iter() {
+            writeln!(
+                f,
+                "{:>4}  {:<8}  {:>8}  {:>8}  {:>8}  {} ({})",
+                pool.idx,
+                pool.status,
+                pool.accepted,
+                pool.rejected,
+                pool.stale,
+                pool.url,
+                pool.user
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn sub_command<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::SubCommand::with_name("status")
+        .about("Query the running miner over the CGMiner API and print its status")
+        .arg(
+            clap::Arg::with_name("host")
+                .long("host")
+                .value_name("HOST")
+                .help("Host of the CGMiner API (default 127.0.0.1)")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("port")
+                .long("port")
+                .value_name("PORT")
+                .help("Port of the CGMiner API (default 4028)")
+                .required(false)
+                .takes_value(true),
+        )
+}
+
+/// Print status of the running miner. An error is returned when the miner is not reachable.
+pub fn handle(matches: &clap::ArgMatches) -> Result<(), String> {
+    let host = matches.value_of("host").unwrap_or(DEFAULT_HOST);
+    let port = match matches.value_of("port") {
+        Some(port) => port
+            .parse()
+            .map_err(|_| format!("invalid port '{}'", port))?,
+        None => DEFAULT_PORT,
+    };
+    let status = Status::query(&Client::new(host, port)?)?;
+    print!("{}", status);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve canned responses to `count` connections, the command is looked up in `responses`
+    fn serve(responses: Vec<(&'static str, json::Value)>, count: usize) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind");
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut buf = Vec::new();
+                stream.read_to_end(&mut buf).unwrap();
+                let request: json::Value = json::from_slice(&buf).unwrap();
+                let response = responses
+                    .iter()
+                    .find(|(command, _)| request["command"] == *command)
+                    .map(|(_, response)| response.clone())
+                    .unwrap_or_else(
+                        || json::json!({"STATUS": [{"STATUS": "E", "Msg": "Invalid command"}]}),
+                    );
+                stream.write_all(response.to_string().as_bytes()).unwrap();
+                stream.write_all(&[0]).unwrap();
+            }
+        });
+        port
+    }
+
+    fn success(name: &str, list: json::Value) -> json::Value {
+        let mut response = json::json!({"STATUS": [{"STATUS": "S", "Msg": "ok"}], "id": 1});
+        response[name] = list;
+        response
+    }
+
+    #[test]
+    fn test_status_query() {
+        let responses = vec![
+            (
+                "summary",
+                success(
+                    "SUMMARY",
+                    json::json!([{
+                        "Elapsed": 120, "MHS av": 13_500_000.0, "MHS 5s": 13_000_000.0,
+                        "Accepted": 10, "Rejected": 1, "Hardware Errors": 3
+                    }]),
+                ),
+            ),
+            (
+                "devs",
+                success(
+                    "DEVS",
+                    json::json!([{
+                        "ID": 6, "Status": "Alive", "MHS av": 4_500_000.0, "MHS 5s": 4_400_000.0,
+                        "Accepted": 4, "Rejected": 0, "Hardware Errors": 1
+                    }]),
+                ),
+            ),
+            (
+                "pools",
+                success(
+                    "POOLS",
+                    json::json!([{
+                        "POOL": 0, "URL": "stratum+tcp://pool:3333", "User": "worker",
+                        "Status": "Alive", "Accepted": 10, "Rejected": 1, "Stale": 0
+                    }]),
+                ),
+            ),
+            (
+                "temps",
+                success(
+                    "TEMPS",
+                    json::json!([{"TEMP": 0, "ID": 6, "Board": 55.0, "Chip": 70.5}]),
+                ),
+            ),
+        ];
+        let client = Client::new("127.0.0.1", serve(responses, 4)).unwrap();
+        let status = Status::query(&client).expect("BUG: cannot query status");
+
+        assert_eq!(status.summary.accepted, 10);
+        assert_eq!(status.devs.len(), 1);
+        assert_eq!(status.pools[0].url, "stratum+tcp://pool:3333");
+        assert_eq!(status.temp(6).map(|temp| temp.chip), Some(70.5));
+
+        let table = status.to_string();
+        assert!(table.contains("hashrate: 13.50 TH/s"));
+        assert!(table.contains("stratum+tcp://pool:3333 (worker)"));
+    }
+
+    #[test]
+    fn test_status_without_temps() {
+        let responses = vec![
+            (
+                "summary",
+                success(
+                    "SUMMARY",
+                    json::json!([{
+                        "Elapsed": 1, "MHS av": 0.0, "MHS 5s": 0.0,
+                        "Accepted": 0, "Rejected": 0, "Hardware Errors": 0
+                    }]),
+                ),
+            ),
+            ("devs", success("DEVS", json::json!([]))),
+            ("pools", success("POOLS", json::json!([]))),
+        ];
+        let client = Client::new("127.0.0.1", serve(responses, 4)).unwrap();
+        let status = Status::query(&client).expect("BUG: cannot query status");
+
+        assert!(status.temps.is_empty());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let error = br#"{"STATUS":[{"STATUS":"E","Msg":"Invalid command"}]}"#;
+        assert_eq!(
+            parse_response("foo", error),
+            Err("'foo' failed: Invalid command".to_string())
+        );
+        assert!(parse_response("foo", b"{\"STATUS\"").is_err());
+        assert!(parse_response("foo", b"{\"STATUS\":[{\"STATUS\":\"S\"}]}\0").is_ok());
+    }
+
+    #[test]
+    fn test_unreachable() {
+        // Bind and immediately drop the listener to get a port nobody listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = Client::new("127.0.0.1", port).unwrap();
+        assert!(Status::query(&client).is_err());
+    }
+}
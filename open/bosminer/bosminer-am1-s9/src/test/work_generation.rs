@@ -25,12 +25,15 @@ use ii_logging::macros::*;
 use super::*;
 use crate::bm1387::MidstateCount;
 use crate::fan;
+use crate::realtime;
 use crate::{FrequencySettings, HashChain, Solution};
 
+use bosminer::progress;
 use bosminer::work;
 
 use std::time::Duration;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use futures::channel::mpsc;
@@ -56,10 +59,12 @@ fn prepare_test_work(midstate_count: usize) -> work::Assignment {
     work::Assignment::new(job, vec![one_midstate; midstate_count], time)
 }
 
-/// Task that receives solutions from hardware and sends them to channel
+/// Task that receives solutions from hardware and sends them to channel. Detected overflows of
+/// the solution FIFO are accumulated in `overflows`.
 async fn receiver_task(
     hash_chain: Arc<HashChain>,
     solution_sender: mpsc::UnboundedSender<Solution>,
+    overflows: Arc<AtomicUsize>,
 ) {
     let mut rx_io = hash_chain.take_work_rx_io().await;
     let target = ii_bitcoin::Target::from_pool_difficulty(ASIC_DIFFICULTY);
@@ -67,6 +72,7 @@ async fn receiver_task(
     loop {
         let (rx_io_out, solution) = rx_io.recv_solution().await.expect("recv solution");
         rx_io = rx_io_out;
+        overflows.fetch_add(rx_io.take_overflow_events(), Ordering::Relaxed);
         solution_sender
             .unbounded_send(Solution::from_hw_solution(&solution, target))
             .expect("solution send failed");
@@ -158,6 +164,7 @@ async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> Ha
             ),
             *crate::power::OPEN_CORE_VOLTAGE,
             true,
+            &Arc::new(progress::Registry::new()).reporter(hashboard_idx),
         )
         .await
         .expect("h_chain init failed");
@@ -187,7 +194,11 @@ async fn test_work_generation() {
     let hash_chain = Arc::new(start_hchain(monitor_sender).await);
 
     // start HW receiver
-    tokio::spawn(receiver_task(hash_chain.clone(), solution_sender));
+    tokio::spawn(receiver_task(
+        hash_chain.clone(),
+        solution_sender,
+        Arc::new(AtomicUsize::new(0)),
+    ));
 
     // start HW sender
     tokio::spawn(sender_task(hash_chain.clone(), work_receiver));
@@ -213,3 +224,69 @@ async fn test_work_generation() {
     // stop everything
     hash_chain.halt_sender.clone().send_halt().await;
 }
+
+/// Verifies that solution FIFO doesn't overflow when the main runtime is overloaded
+///
+/// The RX/TX tasks run on dedicated IO runtime while the main runtime is kept busy by tasks
+/// blocking its thread. Solutions from all chips are then expected to be received without any
+/// overflow event.
+#[tokio::test]
+async fn test_solution_rx_under_load() {
+    // Create channels
+    let (solution_sender, mut solution_receiver) = mpsc::unbounded();
+    let (work_sender, work_receiver) = mpsc::unbounded();
+    let (monitor_sender, _monitor_receiver) = mpsc::unbounded();
+
+    // Guard lives until the end of the block
+    let _work_sender_guard = work_sender.clone();
+    let _solution_sender_guard = solution_sender.clone();
+
+    // Start HW
+    let hash_chain = Arc::new(start_hchain(monitor_sender).await);
+
+    // start HW receiver and sender on the dedicated runtime
+    let io_config = realtime::Config {
+        enabled: true,
+        sched_fifo: true,
+        priority: config::DEFAULT_REALTIME_IO_PRIORITY,
+        latency_budget: Duration::from_millis(config::DEFAULT_REALTIME_IO_LATENCY_BUDGET_MS),
+    };
+    let io_runtime =
+        realtime::Runtime::start("test-io".to_string(), &io_config).expect("IO runtime failed");
+    let overflows = Arc::new(AtomicUsize::new(0));
+    io_runtime.handle().spawn(receiver_task(
+        hash_chain.clone(),
+        solution_sender,
+        overflows.clone(),
+    ));
+    io_runtime
+        .handle()
+        .spawn(sender_task(hash_chain.clone(), work_receiver));
+
+    // keep the main runtime busy with tasks which block its thread
+    for _ in 0..4 {
+        tokio::spawn(async {
+            loop {
+                std::thread::sleep(Duration::from_millis(20));
+                delay_for(Duration::from_millis(1)).await;
+            }
+        });
+    }
+
+    send_and_receive_test_workloads(&work_sender, &mut solution_receiver, 3, 0).await;
+
+    let more_work_count = 2usize;
+    let chip_count = hash_chain.get_chip_count();
+    send_and_receive_test_workloads(
+        &work_sender,
+        &mut solution_receiver,
+        more_work_count,
+        more_work_count * chip_count,
+    )
+    .await;
+
+    assert_eq!(overflows.load(Ordering::Relaxed), 0);
+
+    // stop everything
+    hash_chain.halt_sender.clone().send_halt().await;
+}
@@ -0,0 +1,73 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use ii_cgminer_api::access;
+
+use serde::{Deserialize, Serialize};
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Settings of the CGMiner compatible API server
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Api {
+    /// Address the server listens on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Source networks which are allowed to use the API (write access from loopback and
+    /// read-only access from all other addresses when not specified). The syntax is the same as
+    /// of CGMiner `--api-allow` option, e.g. `10.0.0.0/24` grants read-only access and
+    /// `W:10.0.0.1` grants write access.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<access::Rule>>,
+}
+
+impl Api {
+    pub const DEFAULT_HOST: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    pub const DEFAULT_PORT: u16 = 4028;
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(
+            self.host.unwrap_or(Self::DEFAULT_HOST),
+            self.port.unwrap_or(Self::DEFAULT_PORT),
+        )
+    }
+
+    pub fn allow_list(&self) -> access::AllowList {
+        access::AllowList::new(self.allow.clone())
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.port == Some(0) {
+            Err("API port cannot be zero".to_string())?;
+        }
+        if let Some(allow) = &self.allow {
+            if allow.is_empty() {
+                Err("API allow-list is empty, nobody could access the API".to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -22,43 +22,75 @@
 
 use crate::error;
 
-use ii_stratum::v2;
+use ii_stratum::{v1, v2};
 
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::fmt;
+use std::net;
+use std::time;
 
 use failure::ResultExt;
 
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Default window before expiration of the pool certificate when a warning is raised (30 days)
+pub const DEFAULT_CERT_EXPIRY_WARNING: time::Duration =
+    time::Duration::from_secs(30 * SECONDS_PER_DAY);
+
+/// Default time after which the address of an established connection is resolved again on
+/// reconnect instead of being preferred (5 minutes)
+pub const DEFAULT_DNS_TTL: time::Duration = time::Duration::from_secs(5 * 60);
+
 pub const URL_JAVA_SCRIPT_REGEX: &'static str =
-    "(?:drain|(?:stratum2?\\+tcp(?:\\+insecure)?)):\\/\\/[\\w\\.-]+(?::\\d+)?(?:\\/[\\dA-HJ-NP-Za-km-z]+)?";
+    "(?:drain|(?:stratum2?\\+(?:ssl|tcp(?:\\+insecure)?))):\\/\\/(?:[\\w\\.-]+|\\[[\\da-fA-F:\\.]+\\])(?::\\d+)?(?:\\/[\\dA-HJ-NP-Za-km-z]+)?";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Protocol {
     Drain,
     StratumV1,
+    /// Stratum V1 over TLS connection
+    StratumV1Tls,
     StratumV2(v2::noise::auth::EncodedEd25519PublicKey),
     StratumV2Insecure,
+    /// Stratum V2 over TLS connection (instead of the noise protocol)
+    StratumV2Tls,
 }
 
 impl Protocol {
     pub const SCHEME_DRAIN: &'static str = "drain";
     pub const SCHEME_STRATUM_V1: &'static str = "stratum+tcp";
+    pub const SCHEME_STRATUM_V1_TLS: &'static str = "stratum+ssl";
     pub const SCHEME_STRATUM_V2: &'static str = "stratum2+tcp";
     pub const SCHEME_STRATUM_V2_INSECURE: &'static str = "stratum2+tcp+insecure";
+    pub const SCHEME_STRATUM_V2_TLS: &'static str = "stratum2+ssl";
 
     pub const DEFAULT_PORT_DRAIN: u16 = 0;
     pub const DEFAULT_PORT_STRATUM_V1: u16 = 3333;
+    pub const DEFAULT_PORT_STRATUM_V1_TLS: u16 = 3333;
     pub const DEFAULT_PORT_STRATUM_V2: u16 = 3336;
     pub const DEFAULT_PORT_STRATUM_V2_INSECURE: u16 = 3336;
+    pub const DEFAULT_PORT_STRATUM_V2_TLS: u16 = 3336;
 
     pub fn default_port(&self) -> u16 {
         match self {
             Self::Drain => Self::DEFAULT_PORT_DRAIN,
             Self::StratumV1 => Self::DEFAULT_PORT_STRATUM_V1,
+            Self::StratumV1Tls => Self::DEFAULT_PORT_STRATUM_V1_TLS,
             Self::StratumV2(_) => Self::DEFAULT_PORT_STRATUM_V2,
             Self::StratumV2Insecure => Self::DEFAULT_PORT_STRATUM_V2_INSECURE,
+            Self::StratumV2Tls => Self::DEFAULT_PORT_STRATUM_V2_TLS,
+        }
+    }
+
+    /// Check whether the connection is wrapped in a TLS session
+    pub fn is_tls(&self) -> bool {
+        match self {
+            Self::StratumV1Tls | Self::StratumV2Tls => true,
+            _ => false,
         }
     }
 
@@ -78,6 +110,7 @@ impl Protocol {
         Ok(match scheme {
             Self::SCHEME_DRAIN => Self::Drain,
             Self::SCHEME_STRATUM_V1 => Self::StratumV1,
+            Self::SCHEME_STRATUM_V1_TLS => Self::StratumV1Tls,
             Self::SCHEME_STRATUM_V2 => {
                 let upstream_authority_public_key = match path.get(1..) {
                     Some(s) => Self::get_upstream_auth_public_key_from_string(s)?,
@@ -89,6 +122,7 @@ impl Protocol {
                 Self::StratumV2(upstream_authority_public_key)
             }
             Self::SCHEME_STRATUM_V2_INSECURE => Self::StratumV2Insecure,
+            Self::SCHEME_STRATUM_V2_TLS => Self::StratumV2Tls,
             _ => Err(error::ErrorKind::Client(format!(
                 "unknown protocol '{}'",
                 scheme
@@ -100,8 +134,10 @@ impl Protocol {
         match self {
             Self::Drain => Self::SCHEME_DRAIN,
             Self::StratumV1 => Self::SCHEME_STRATUM_V1,
+            Self::StratumV1Tls => Self::SCHEME_STRATUM_V1_TLS,
             Self::StratumV2(_) => Self::SCHEME_STRATUM_V2,
             Self::StratumV2Insecure => Self::SCHEME_STRATUM_V2_INSECURE,
+            Self::StratumV2Tls => Self::SCHEME_STRATUM_V2_TLS,
         }
     }
 }
@@ -111,10 +147,12 @@ impl fmt::Display for Protocol {
         match self {
             Protocol::Drain => write!(f, "Drain"),
             Protocol::StratumV1 => write!(f, "Stratum V1"),
+            Protocol::StratumV1Tls => write!(f, "Stratum V1 TLS"),
             Protocol::StratumV2(public_key) => {
                 write!(f, "Stratum V2 (authority key: {})", public_key)
             }
             Protocol::StratumV2Insecure => write!(f, "Stratum V2 Insecure"),
+            Protocol::StratumV2Tls => write!(f, "Stratum V2 TLS"),
         }
     }
 }
@@ -143,17 +181,271 @@ impl<'a> UserInfo<'a> {
     }
 }
 
+/// Settings of circuit breaker which temporarily pauses a pool rejecting too many shares
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RejectBreaker {
+    /// The breaker has to be enabled explicitly for each pool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Ratio of rejected shares (0.0 to 1.0) in the rolling window which trips the breaker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    /// Minimal number of shares in the rolling window needed for tripping the breaker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_shares: Option<u64>,
+    /// Length of the rolling window in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<u64>,
+    /// Time in seconds after which the soft-failed pool is tried again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cool_off: Option<u64>,
+    /// Number of shares submitted to the retried pool before it is fully restored (no more work
+    /// is generated for the pool until responses to these shares are received)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probe_shares: Option<u64>,
+}
+
+impl RejectBreaker {
+    pub const DEFAULT_ENABLED: bool = false;
+    pub const DEFAULT_THRESHOLD: f64 = 0.5;
+    pub const DEFAULT_MIN_SHARES: u64 = 20;
+    pub const DEFAULT_WINDOW: u64 = 10 * 60;
+    pub const DEFAULT_COOL_OFF: u64 = 15 * 60;
+    pub const DEFAULT_PROBE_SHARES: u64 = 5;
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(Self::DEFAULT_ENABLED)
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold.unwrap_or(Self::DEFAULT_THRESHOLD)
+    }
+
+    pub fn min_shares(&self) -> u64 {
+        self.min_shares.unwrap_or(Self::DEFAULT_MIN_SHARES)
+    }
+
+    pub fn window(&self) -> time::Duration {
+        time::Duration::from_secs(self.window.unwrap_or(Self::DEFAULT_WINDOW))
+    }
+
+    pub fn cool_off(&self) -> time::Duration {
+        time::Duration::from_secs(self.cool_off.unwrap_or(Self::DEFAULT_COOL_OFF))
+    }
+
+    pub fn probe_shares(&self) -> u64 {
+        self.probe_shares.unwrap_or(Self::DEFAULT_PROBE_SHARES)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        let threshold = self.threshold();
+        if !(0.0..=1.0).contains(&threshold) {
+            Err(format!(
+                "reject breaker threshold {} is out of range <0.0, 1.0>",
+                threshold
+            ))?;
+        }
+        if self.window().as_secs() == 0 {
+            Err("reject breaker window cannot be zero".to_string())?;
+        }
+        if self.probe_shares() == 0 {
+            Err("reject breaker probe shares cannot be zero".to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Rules deciding which solutions of superseded or outdated work are still submitted to the pool
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SharePolicy {
+    /// Time in milliseconds after invalidation of a job during which its solutions are still
+    /// submitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grace_period: Option<u64>,
+    /// Submit solutions of jobs superseded by a job building on another block (within the grace
+    /// period)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_clean_jobs: Option<bool>,
+    /// Submit solutions of invalidated jobs even after the grace period (some pools accept
+    /// slightly stale shares)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_stale: Option<bool>,
+    /// Maximal difference in seconds between timestamp of the solution and timestamp of its job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ntime_delta: Option<u32>,
+    /// Submit solutions which meet only the difficulty in effect when the work has been generated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_old_difficulty: Option<bool>,
+    /// Number of recently submitted solutions remembered for detection of duplicates (zero
+    /// disables the detection)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_window: Option<usize>,
+    /// Submit solutions of jobs from removed pools which have been rerouted to this pool (only
+    /// useful with proxies tolerating such shares)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_foreign: Option<bool>,
+}
+
+impl SharePolicy {
+    pub const DEFAULT_GRACE_PERIOD: u64 = 0;
+    pub const DEFAULT_SUBMIT_CLEAN_JOBS: bool = false;
+    pub const DEFAULT_SUBMIT_STALE: bool = false;
+    pub const DEFAULT_MAX_NTIME_DELTA: u32 = 2 * 60 * 60;
+    pub const DEFAULT_SUBMIT_OLD_DIFFICULTY: bool = true;
+    pub const DEFAULT_DUPLICATE_WINDOW: usize = 4096;
+    pub const DEFAULT_ACCEPT_FOREIGN: bool = false;
+
+    pub fn grace_period(&self) -> time::Duration {
+        time::Duration::from_millis(self.grace_period.unwrap_or(Self::DEFAULT_GRACE_PERIOD))
+    }
+
+    pub fn submit_clean_jobs(&self) -> bool {
+        self.submit_clean_jobs
+            .unwrap_or(Self::DEFAULT_SUBMIT_CLEAN_JOBS)
+    }
+
+    pub fn submit_stale(&self) -> bool {
+        self.submit_stale.unwrap_or(Self::DEFAULT_SUBMIT_STALE)
+    }
+
+    pub fn max_ntime_delta(&self) -> u32 {
+        self.max_ntime_delta
+            .unwrap_or(Self::DEFAULT_MAX_NTIME_DELTA)
+    }
+
+    pub fn submit_old_difficulty(&self) -> bool {
+        self.submit_old_difficulty
+            .unwrap_or(Self::DEFAULT_SUBMIT_OLD_DIFFICULTY)
+    }
+
+    pub fn duplicate_window(&self) -> usize {
+        self.duplicate_window
+            .unwrap_or(Self::DEFAULT_DUPLICATE_WINDOW)
+    }
+
+    pub fn accept_foreign(&self) -> bool {
+        self.accept_foreign.unwrap_or(Self::DEFAULT_ACCEPT_FOREIGN)
+    }
+}
+
+/// Verification of coinbase transactions of jobs from pools which commit to include a marker in
+/// them (only supported for Stratum V1 pools)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CoinbaseCheck {
+    /// ASCII tag which has to be present in the coinbase transaction
+    pub marker: String,
+    /// Hex encoded output script which has to be present in the coinbase transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_script: Option<String>,
+    /// Number of consecutive rejected jobs which raises an alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert_threshold: Option<usize>,
+}
+
+impl CoinbaseCheck {
+    pub const DEFAULT_ALERT_THRESHOLD: usize = 3;
+
+    pub fn marker(&self) -> Vec<u8> {
+        self.marker.as_bytes().to_vec()
+    }
+
+    pub fn payout_script(&self) -> Result<Option<Vec<u8>>, String> {
+        self.payout_script
+            .as_ref()
+            .map(|script| {
+                v1::HexBytes::try_from(script.as_str())
+                    .map(|script| script.as_ref().clone())
+                    .map_err(|_| format!("invalid coinbase payout script '{}'", script))
+            })
+            .transpose()
+    }
+
+    pub fn alert_threshold(&self) -> usize {
+        self.alert_threshold
+            .unwrap_or(Self::DEFAULT_ALERT_THRESHOLD)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.marker.is_empty() {
+            Err("coinbase marker cannot be empty".to_string())?;
+        }
+        if self
+            .payout_script()?
+            .map_or(false, |script| script.is_empty())
+        {
+            Err("coinbase payout script cannot be empty".to_string())?;
+        }
+        if self.alert_threshold() == 0 {
+            Err("coinbase alert threshold cannot be zero".to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Effective target of a job with `pool_target` when the client overrides share `difficulty`.
+/// The pool target is only ever made harder. It is the target the client negotiates with the pool
+/// and reports as effective, while shares are still submitted whenever they meet `pool_target`
+/// because the pool accepts them.
+pub fn clamp_target(
+    pool_target: ii_bitcoin::Target,
+    difficulty: Option<usize>,
+) -> ii_bitcoin::Target {
+    match difficulty {
+        Some(difficulty) => pool_target.min(ii_bitcoin::Target::from_pool_difficulty(difficulty)),
+        None => pool_target,
+    }
+}
+
+/// Host as it is written in URL or `HOST:PORT` address with IPv6 address enclosed in brackets
+pub fn url_host(host: &str) -> Cow<str> {
+    if host.contains(':') {
+        format!("[{}]", host).into()
+    } else {
+        host.into()
+    }
+}
+
 /// Contains basic information about client used for obtaining jobs for solving.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Descriptor {
     pub protocol: Protocol,
     pub enabled: bool,
     pub user: String,
     pub password: Option<String>,
+    /// Hostname or IP address of the pool (IPv6 address is stored without brackets)
     pub host: String,
     pub port: Option<u16>,
     // Currently used only for `#xnsub`: `stratum+tcp://equihash.eu.nicehash.com:3357#xnsub`
     pub fragment: Option<String>,
+    /// Bitcoin network of the pool used for network target and difficulty computations
+    pub network: ii_bitcoin::Network,
+    /// Optional tag attached to statistics of the client. It is not part of the client identity
+    /// so it can be changed without losing accounted shares.
+    pub label: Option<String>,
+    /// Configured display name of the client. It is not part of the client identity either.
+    pub name: Option<String>,
+    pub reject_breaker: RejectBreaker,
+    pub coinbase_check: Option<CoinbaseCheck>,
+    pub share_policy: SharePolicy,
+    /// Warn when the certificate of a secure connection expires within this window
+    pub cert_expiry_warning: time::Duration,
+    /// Path to PEM file with CA certificates verifying the server of TLS connection (built-in
+    /// root certificates are used when it is missing)
+    pub ca_cert: Option<String>,
+    /// Skip verification of the server certificate of TLS connection
+    pub insecure: bool,
+    /// Lowest share difficulty suggested to the pool
+    pub min_difficulty: Option<usize>,
+    /// Share difficulty requested from the pool which takes precedence over `min_difficulty`
+    pub fixed_difficulty: Option<usize>,
+    /// Address of an established connection is preferred on reconnect only within this time
+    /// since the connection has been opened
+    pub dns_ttl: time::Duration,
 }
 
 impl Descriptor {
@@ -173,7 +465,7 @@ impl Descriptor {
         if user {
             result += format!("{}@", self.user).as_str();
         }
-        result += self.host.as_str();
+        result += &url_host(&self.host);
         match self.port {
             Some(value) if port => result += format!(":{}", value).as_str(),
             _ => {}
@@ -187,15 +479,152 @@ impl Descriptor {
         self.get_url(true, true, true)
     }
 
+    /// Full URL followed by the label (if any) for log messages
+    pub fn get_labelled_url(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} [{}]", self.get_full_url(), label),
+            None => self.get_full_url(),
+        }
+    }
+
+    /// Short form of the URL used as the display name when no name is configured. The scheme,
+    /// the `stratum.` prefix of the host, the default port and the worker part of the user are
+    /// left out e.g. `stratum+tcp://braiins.worker1@stratum.slushpool.com:3333` is shortened to
+    /// `braiins@slushpool.com`.
+    pub fn default_name(&self) -> String {
+        let account = self.user.split('.').next().unwrap_or_default();
+        let host = match self.host.find('.') {
+            Some(pos) if &self.host[..pos] == "stratum" && pos + 1 < self.host.len() => {
+                &self.host[pos + 1..]
+            }
+            _ => self.host.as_str(),
+        };
+        let host = url_host(host);
+        let mut name = if account.is_empty() {
+            host.to_string()
+        } else {
+            format!("{}@{}", account, host)
+        };
+        match self.port {
+            Some(port) if port != self.protocol.default_port() => {
+                name += format!(":{}", port).as_str()
+            }
+            _ => {}
+        }
+        name
+    }
+
+    /// Name shown in logs and API which is either configured or derived from the URL
+    pub fn display_name(&self) -> String {
+        match self.name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => self.default_name(),
+        }
+    }
+
+    /// Check whether both descriptors refer to the same pool connection. Neither `enabled`,
+    /// `label` nor `name` is part of the identity because they can be changed on a running
+    /// client.
+    pub fn is_same_client(&self, other: &Self) -> bool {
+        Self {
+            enabled: other.enabled,
+            label: other.label.clone(),
+            name: other.name.clone(),
+            ..self.clone()
+        } == *other
+    }
+
+    /// Set Bitcoin network of the pool (default is mainnet)
+    pub fn with_network(mut self, network: ii_bitcoin::Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_reject_breaker(mut self, reject_breaker: RejectBreaker) -> Self {
+        self.reject_breaker = reject_breaker;
+        self
+    }
+
+    pub fn with_coinbase_check(mut self, coinbase_check: Option<CoinbaseCheck>) -> Self {
+        self.coinbase_check = coinbase_check;
+        self
+    }
+
+    pub fn with_share_policy(mut self, share_policy: SharePolicy) -> Self {
+        self.share_policy = share_policy;
+        self
+    }
+
+    /// Set expiry warning window of the pool certificate in days
+    pub fn with_cert_expiry_warning(mut self, days: Option<u32>) -> Self {
+        self.cert_expiry_warning = days
+            .map(|days| time::Duration::from_secs(u64::from(days) * SECONDS_PER_DAY))
+            .unwrap_or(DEFAULT_CERT_EXPIRY_WARNING);
+        self
+    }
+
+    /// Set verification of the server certificate of TLS connection
+    pub fn with_tls(mut self, ca_cert: Option<String>, insecure: Option<bool>) -> Self {
+        self.ca_cert = ca_cert;
+        self.insecure = insecure.unwrap_or(false);
+        self
+    }
+
+    /// Set difficulty overrides of the shares assigned by the pool
+    pub fn with_difficulty(
+        mut self,
+        min_difficulty: Option<usize>,
+        fixed_difficulty: Option<usize>,
+    ) -> Self {
+        self.min_difficulty = min_difficulty;
+        self.fixed_difficulty = fixed_difficulty;
+        self
+    }
+
+    /// Set DNS TTL of the pool address in seconds
+    pub fn with_dns_ttl(mut self, secs: Option<u64>) -> Self {
+        self.dns_ttl = secs
+            .map(time::Duration::from_secs)
+            .unwrap_or(DEFAULT_DNS_TTL);
+        self
+    }
+
+    /// Difficulty suggested to the pool on connect (if any)
+    pub fn suggested_difficulty(&self) -> Option<usize> {
+        self.fixed_difficulty.or(self.min_difficulty)
+    }
+
     /// Create client `Descriptor` from information provided by user.
     pub fn create(url: &str, user_info: &UserInfo, enabled: bool) -> error::Result<Self> {
         let url = Url::parse(url).context(error::ErrorKind::Client("invalid URL".to_string()))?;
 
         let protocol = Protocol::parse(url.scheme(), url.path())?;
-        let host = url
+        // IPv6 address is stored without brackets so that it can be resolved
+        let host = match url
             .host()
             .ok_or(error::ErrorKind::Client("missing hostname".to_string()))?
-            .to_string();
+        {
+            url::Host::Ipv6(addr) => addr.to_string(),
+            host => host.to_string(),
+        };
+        // TLS session requires host name for SNI and for verification of the certificate (even
+        // an `insecure` pool cannot be connected without SNI)
+        if protocol.is_tls() && host.parse::<net::IpAddr>().is_ok() {
+            Err(error::ErrorKind::Client(format!(
+                "TLS connection requires host name instead of IP address '{}'",
+                host
+            )))?;
+        }
         let port = url.port();
 
         // Parse fragment part
@@ -209,6 +638,147 @@ impl Descriptor {
             host,
             port,
             fragment,
+            network: Default::default(),
+            label: None,
+            name: None,
+            reject_breaker: Default::default(),
+            coinbase_check: None,
+            share_policy: Default::default(),
+            cert_expiry_warning: DEFAULT_CERT_EXPIRY_WARNING,
+            ca_cert: None,
+            insecure: false,
+            min_difficulty: None,
+            fixed_difficulty: None,
+            dns_ttl: DEFAULT_DNS_TTL,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn descriptor(url: &str, user: &str) -> Descriptor {
+        Descriptor::create(url, &UserInfo::new(user, None), true).expect("BUG: invalid URL")
+    }
+
+    #[test]
+    fn test_default_name() {
+        // scheme, `stratum.` prefix, default port and worker are left out
+        assert_eq!(
+            descriptor(
+                "stratum+tcp://stratum.slushpool.com:3333",
+                "braiins.worker1"
+            )
+            .default_name(),
+            "braiins@slushpool.com"
+        );
+        // non-default port is kept because it distinguishes pools on the same host
+        assert_eq!(
+            descriptor("stratum+tcp://stratum.slushpool.com:443", "braiins").default_name(),
+            "braiins@slushpool.com:443"
+        );
+        // only the leading `stratum.` label is removed
+        assert_eq!(
+            descriptor("stratum+tcp://eu.stratum.slushpool.com", "braiins.s9").default_name(),
+            "braiins@eu.stratum.slushpool.com"
+        );
+        assert_eq!(
+            descriptor("stratum+tcp://stratum:3333", "braiins").default_name(),
+            "braiins@stratum"
+        );
+        assert_eq!(
+            descriptor("drain://localhost", "").default_name(),
+            "localhost"
+        );
+    }
+
+    #[test]
+    fn test_display_name() {
+        let pool = descriptor("stratum+tcp://stratum.slushpool.com", "braiins.worker1");
+        assert_eq!(pool.display_name(), "braiins@slushpool.com");
+        assert_eq!(
+            pool.clone()
+                .with_name(Some("Slush primary".to_string()))
+                .display_name(),
+            "Slush primary"
+        );
+        // blank name falls back to the default one
+        assert_eq!(
+            pool.clone()
+                .with_name(Some("  ".to_string()))
+                .display_name(),
+            "braiins@slushpool.com"
+        );
+        // the name can be changed on the running client
+        assert!(pool.is_same_client(&pool.clone().with_name(Some("Slush".to_string()))));
+    }
+
+    #[test]
+    fn test_ipv6_host() {
+        let pool = descriptor("stratum+tcp://[2001:db8::1]:3334", "braiins");
+        // the address is stored without brackets so that it can be resolved
+        assert_eq!(pool.host, "2001:db8::1");
+        assert_eq!(pool.port(), 3334);
+        assert_eq!(
+            pool.get_full_url(),
+            "stratum+tcp://braiins@[2001:db8::1]:3334"
+        );
+        assert_eq!(pool.default_name(), "braiins@[2001:db8::1]:3334");
+        // the URL is parsed back to the same pool
+        assert_eq!(
+            descriptor(&pool.get_url(true, true, false), "braiins"),
+            pool
+        );
+
+        let pool = descriptor("stratum2+tcp+insecure://[::1]", "braiins");
+        assert_eq!(pool.host, "::1");
+        assert_eq!(pool.port(), Protocol::DEFAULT_PORT_STRATUM_V2_INSECURE);
+        assert_eq!(url_host("slushpool.com"), "slushpool.com");
+        assert_eq!(url_host("::1"), "[::1]");
+    }
+
+    #[test]
+    fn test_dns_ttl() {
+        let pool = descriptor("stratum+tcp://stratum.slushpool.com", "braiins");
+        assert_eq!(pool.dns_ttl, DEFAULT_DNS_TTL);
+        let pool = pool.with_dns_ttl(Some(30));
+        assert_eq!(pool.dns_ttl, time::Duration::from_secs(30));
+        assert_eq!(pool.with_dns_ttl(None).dns_ttl, DEFAULT_DNS_TTL);
+    }
+
+    #[test]
+    fn test_tls_protocols() {
+        let pool = descriptor("stratum+ssl://stratum.slushpool.com:443", "braiins");
+        assert_eq!(pool.protocol, Protocol::StratumV1Tls);
+        assert!(pool.protocol.is_tls());
+        assert_eq!(
+            pool.get_full_url(),
+            "stratum+ssl://braiins@stratum.slushpool.com:443"
+        );
+
+        let pool = descriptor("stratum2+ssl://v2.stratum.slushpool.com", "braiins");
+        assert_eq!(pool.protocol, Protocol::StratumV2Tls);
+        assert_eq!(pool.port(), Protocol::DEFAULT_PORT_STRATUM_V2_TLS);
+
+        assert!(
+            !descriptor("stratum+tcp://stratum.slushpool.com", "braiins")
+                .protocol
+                .is_tls()
+        );
+        assert!(
+            !descriptor("stratum2+tcp+insecure://stratum.slushpool.com", "braiins")
+                .protocol
+                .is_tls()
+        );
+
+        // IP address cannot be used for SNI
+        for url in &["stratum+ssl://10.0.0.1:3333", "stratum2+ssl://[::1]"] {
+            assert!(Descriptor::create(url, &UserInfo::new("braiins", None), true).is_err());
+        }
+        assert_eq!(
+            descriptor("stratum+tcp://10.0.0.1", "braiins").host,
+            "10.0.0.1"
+        );
+    }
+}
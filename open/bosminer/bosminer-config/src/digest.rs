@@ -0,0 +1,114 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use serde::{Deserialize, Serialize};
+
+use std::time;
+
+/// Settings of the daily digest of submitted shares
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Digest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Path to JSONL file where daily records are appended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Offset of the local time zone from UTC in minutes used for determining midnight. Only
+    /// fixed offsets are supported (UTC by default), named time zones with daylight saving time
+    /// are not. The offset is not adjusted automatically when the daylight saving time starts
+    /// or ends so the day boundary shifts by an hour unless the setting is changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utc_offset: Option<i32>,
+    /// Number of days kept in the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<u32>,
+    /// Warn when reject or stale rate of a pool in the last hours exceeds its rate over the last
+    /// week by this factor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regression_factor: Option<f64>,
+}
+
+impl Digest {
+    pub const DEFAULT_ENABLED: bool = true;
+    pub const DEFAULT_PATH: &'static str = "/etc/bosminer-digest.jsonl";
+    pub const DEFAULT_UTC_OFFSET: i32 = 0;
+    pub const DEFAULT_RETENTION: u32 = 90;
+    pub const DEFAULT_REGRESSION_FACTOR: f64 = 2.0;
+
+    /// Maximal absolute value of the time zone offset in minutes
+    const MAX_UTC_OFFSET: i32 = 24 * 60 - 1;
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(Self::DEFAULT_ENABLED)
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or(Self::DEFAULT_PATH)
+    }
+
+    /// Return offset of the local time zone in seconds
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset.unwrap_or(Self::DEFAULT_UTC_OFFSET) * 60
+    }
+
+    pub fn retention(&self) -> u32 {
+        self.retention.unwrap_or(Self::DEFAULT_RETENTION)
+    }
+
+    pub fn regression_factor(&self) -> f64 {
+        self.regression_factor
+            .unwrap_or(Self::DEFAULT_REGRESSION_FACTOR)
+    }
+
+    /// Time covered by the retention
+    pub fn retention_time(&self) -> time::Duration {
+        time::Duration::from_secs(self.retention() as u64 * 24 * 60 * 60)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        let utc_offset = self.utc_offset.unwrap_or(Self::DEFAULT_UTC_OFFSET);
+        if utc_offset.abs() > Self::MAX_UTC_OFFSET {
+            Err(format!(
+                "digest UTC offset {} minutes is out of range <-{}, {}>",
+                utc_offset,
+                Self::MAX_UTC_OFFSET,
+                Self::MAX_UTC_OFFSET
+            ))?;
+        }
+        if self.retention() == 0 {
+            Err("digest retention cannot be zero".to_string())?;
+        }
+        let regression_factor = self.regression_factor();
+        if regression_factor.is_nan() || regression_factor < 1.0 {
+            Err(format!(
+                "digest regression factor {} cannot be lower than 1",
+                regression_factor
+            ))?;
+        }
+        if self.path().is_empty() {
+            Err("digest path cannot be empty".to_string())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Expansion of `${VAR}` references to environment variables in configuration values
+
+use std::env;
+
+/// Character introducing a variable reference (doubled it is an escaped literal)
+const REFERENCE_START: char = '$';
+
+/// Expand `${VAR}` references in `value` with values returned by `lookup`. A literal `$$` is
+/// replaced with a single `$` and a `$` not followed by `{` is kept as it is.
+pub fn expand_with<F>(value: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != REFERENCE_START {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some(&REFERENCE_START) => {
+                chars.next();
+                result.push(REFERENCE_START);
+            }
+            Some('{') => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => Err(format!("unterminated variable reference '${{{}'", name))?,
+                    }
+                }
+                if name.is_empty() {
+                    Err("empty variable reference '${}'".to_string())?;
+                }
+                let variable = lookup(&name)
+                    .ok_or_else(|| format!("undefined environment variable '{}'", name))?;
+                result.push_str(&variable);
+            }
+            _ => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+/// Expand `${VAR}` references in `value` from the process environment
+pub fn expand(value: &str) -> Result<String, String> {
+    expand_with(value, |name| env::var(name).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "WORKER" => Some("worker1".to_string()),
+            "HOST" => Some("pool.example.com".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand_with("user", lookup), Ok("user".to_string()));
+        assert_eq!(
+            expand_with("user.${WORKER}", lookup),
+            Ok("user.worker1".to_string())
+        );
+        assert_eq!(
+            expand_with("stratum+tcp://${HOST}:3333/${WORKER}", lookup),
+            Ok("stratum+tcp://pool.example.com:3333/worker1".to_string())
+        );
+        // escaped and lone dollar signs
+        assert_eq!(expand_with("pa$$word", lookup), Ok("pa$word".to_string()));
+        assert_eq!(
+            expand_with("$$${WORKER}", lookup),
+            Ok("$worker1".to_string())
+        );
+        assert_eq!(expand_with("pa$word$", lookup), Ok("pa$word$".to_string()));
+
+        assert_eq!(
+            expand_with("${MISSING}", lookup),
+            Err("undefined environment variable 'MISSING'".to_string())
+        );
+        assert!(expand_with("${WORKER", lookup).is_err());
+        assert!(expand_with("${}", lookup).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of staggered distribution of new jobs to work solvers sharing one engine sender.
+/// Work solvers are woken in batches ordered by their weight (nominal hash rate) and each
+/// subsequent batch is delayed by `spacing` to avoid contention on the shared work engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FanOut {
+    /// Number of work solvers woken at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+    /// Delay between two subsequent batches in microseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<u64>,
+}
+
+impl FanOut {
+    pub const DEFAULT_BATCH_SIZE: usize = 4;
+    pub const DEFAULT_SPACING_US: u64 = 100;
+    /// Upper bound of the spacing so that the last batch does not start mining on a stale job
+    pub const MAX_SPACING_US: u64 = 100_000;
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.unwrap_or(Self::DEFAULT_BATCH_SIZE)
+    }
+
+    pub fn spacing(&self) -> u64 {
+        self.spacing.unwrap_or(Self::DEFAULT_SPACING_US)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.batch_size() == 0 {
+            Err("fan-out batch size cannot be zero".to_string())?;
+        }
+        if self.spacing() > Self::MAX_SPACING_US {
+            Err(format!(
+                "fan-out spacing '{}' exceeds maximum '{}' microseconds",
+                self.spacing(),
+                Self::MAX_SPACING_US
+            ))?;
+        }
+        Ok(())
+    }
+}
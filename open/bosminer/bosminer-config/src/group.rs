@@ -22,9 +22,13 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+use std::time;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub enum LoadBalanceStrategy {
+    /// Quota is the relative amount of accepted shares which is submitted to the group. Group
+    /// with zero quota is used only when no other group can be mined for.
     #[serde(rename = "quota")]
     Quota(usize),
     /// Fixed share ratio is value between 0.0 to 1.0 where 1.0 represents that all work is
@@ -49,6 +53,180 @@ impl LoadBalanceStrategy {
     }
 }
 
+/// Settings of failover between pools of the group. The first available pool supplies all work
+/// of the group and the following pools are used only while the preceding ones are down.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Failover {
+    /// Time in seconds after which a disconnected or failing pool is replaced by the next one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Time in seconds the recovered pool has to stay connected before it takes over the work
+    /// from the pool with lower priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_down: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<Health>,
+}
+
+impl Failover {
+    pub const DEFAULT_TIMEOUT: u64 = 30;
+    pub const DEFAULT_HOLD_DOWN: u64 = 5 * 60;
+
+    pub fn timeout(&self) -> time::Duration {
+        time::Duration::from_secs(self.timeout.unwrap_or(Self::DEFAULT_TIMEOUT))
+    }
+
+    pub fn hold_down(&self) -> time::Duration {
+        time::Duration::from_secs(self.hold_down.unwrap_or(Self::DEFAULT_HOLD_DOWN))
+    }
+
+    /// Health scoring with default values for the missing settings
+    pub fn health(&self) -> Health {
+        self.health.clone().unwrap_or_default()
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        self.health().sanity_check()
+    }
+}
+
+/// Settings of the composite health score of pools used by failover. Each component of the score
+/// is in range <0.0, 1.0> and it drops linearly to zero when the measured value reaches its
+/// limit. The score is the weighted geometric mean of the components so any component at zero
+/// makes the whole pool unhealthy. Components which have not been measured yet are neutral.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Health {
+    /// Weight of the time since the last job received from the pool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_weight: Option<f64>,
+    /// Weight of the 95th percentile of the time the pool takes to respond to a submitted share
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_weight: Option<f64>,
+    /// Weight of the ratio of rejected shares
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject_weight: Option<f64>,
+    /// Weight of the authorization of the user by the pool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_weight: Option<f64>,
+    /// Time in seconds without a new job after which the job component drops to zero
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_timeout: Option<u64>,
+    /// Submit latency in milliseconds at which the latency component drops to zero
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_limit: Option<u64>,
+    /// Ratio of rejected shares (0.0 to 1.0) at which the reject component drops to zero
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject_limit: Option<f64>,
+    /// Pool with lower score is replaced by the next healthy pool of the group
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f64>,
+    /// Margin over the minimal score which the pool has to reach to take over the work so that
+    /// the pools do not flap around the minimal score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<f64>,
+}
+
+impl Health {
+    pub const DEFAULT_JOB_WEIGHT: f64 = 2.0;
+    pub const DEFAULT_LATENCY_WEIGHT: f64 = 1.0;
+    pub const DEFAULT_REJECT_WEIGHT: f64 = 1.0;
+    pub const DEFAULT_AUTHORIZATION_WEIGHT: f64 = 1.0;
+    pub const DEFAULT_JOB_TIMEOUT: u64 = 10 * 60;
+    pub const DEFAULT_LATENCY_LIMIT: u64 = 5000;
+    pub const DEFAULT_REJECT_LIMIT: f64 = 0.2;
+    pub const DEFAULT_MIN_SCORE: f64 = 0.2;
+    pub const DEFAULT_HYSTERESIS: f64 = 0.1;
+
+    pub fn job_weight(&self) -> f64 {
+        self.job_weight.unwrap_or(Self::DEFAULT_JOB_WEIGHT)
+    }
+
+    pub fn latency_weight(&self) -> f64 {
+        self.latency_weight.unwrap_or(Self::DEFAULT_LATENCY_WEIGHT)
+    }
+
+    pub fn reject_weight(&self) -> f64 {
+        self.reject_weight.unwrap_or(Self::DEFAULT_REJECT_WEIGHT)
+    }
+
+    pub fn authorization_weight(&self) -> f64 {
+        self.authorization_weight
+            .unwrap_or(Self::DEFAULT_AUTHORIZATION_WEIGHT)
+    }
+
+    pub fn job_timeout(&self) -> time::Duration {
+        time::Duration::from_secs(self.job_timeout.unwrap_or(Self::DEFAULT_JOB_TIMEOUT))
+    }
+
+    pub fn latency_limit(&self) -> time::Duration {
+        time::Duration::from_millis(self.latency_limit.unwrap_or(Self::DEFAULT_LATENCY_LIMIT))
+    }
+
+    pub fn reject_limit(&self) -> f64 {
+        self.reject_limit.unwrap_or(Self::DEFAULT_REJECT_LIMIT)
+    }
+
+    pub fn min_score(&self) -> f64 {
+        self.min_score.unwrap_or(Self::DEFAULT_MIN_SCORE)
+    }
+
+    pub fn hysteresis(&self) -> f64 {
+        self.hysteresis.unwrap_or(Self::DEFAULT_HYSTERESIS)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        let weights = [
+            ("job", self.job_weight()),
+            ("latency", self.latency_weight()),
+            ("reject", self.reject_weight()),
+            ("authorization", self.authorization_weight()),
+        ];
+        for (name, weight) in weights.iter() {
+            if !weight.is_finite() || *weight < 0.0 {
+                Err(format!(
+                    "health {} weight {} cannot be negative",
+                    name, weight
+                ))?;
+            }
+        }
+        if weights.iter().all(|(_, weight)| *weight == 0.0) {
+            Err("health weights cannot be all zero".to_string())?;
+        }
+        if self.job_timeout().as_secs() == 0 {
+            Err("health job timeout cannot be zero".to_string())?;
+        }
+        if self.latency_limit().as_millis() == 0 {
+            Err("health latency limit cannot be zero".to_string())?;
+        }
+        let reject_limit = self.reject_limit();
+        if !(reject_limit > 0.0 && reject_limit <= 1.0) {
+            Err(format!(
+                "health reject limit {} is out of range (0.0, 1.0>",
+                reject_limit
+            ))?;
+        }
+        let min_score = self.min_score();
+        if !(0.0..=1.0).contains(&min_score) {
+            Err(format!(
+                "health minimal score {} is out of range <0.0, 1.0>",
+                min_score
+            ))?;
+        }
+        let hysteresis = self.hysteresis();
+        if !(0.0..=1.0).contains(&hysteresis) {
+            Err(format!(
+                "health hysteresis {} is out of range <0.0, 1.0>",
+                hysteresis
+            ))?;
+        }
+        Ok(())
+    }
+}
+
 /// Contains basic information about group
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
@@ -59,6 +237,8 @@ pub struct Descriptor {
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
     strategy: Option<LoadBalanceStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failover: Option<Failover>,
 }
 
 impl Descriptor {
@@ -74,6 +254,7 @@ impl Descriptor {
             name,
             private,
             strategy: strategy.into(),
+            failover: None,
         }
     }
 
@@ -95,6 +276,11 @@ impl Descriptor {
             .as_ref()
             .and_then(|strategy| strategy.get_fixed_share_ratio())
     }
+
+    /// Failover settings with default values for the missing ones
+    pub fn get_failover(&self) -> Failover {
+        self.failover.clone().unwrap_or_default()
+    }
 }
 
 impl Default for Descriptor {
@@ -103,6 +289,7 @@ impl Default for Descriptor {
             name: Self::DEFAULT_NAME.to_string(),
             private: false,
             strategy: None,
+            failover: None,
         }
     }
 }
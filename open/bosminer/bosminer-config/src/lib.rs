@@ -20,19 +20,45 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+mod api;
 mod client;
+mod digest;
+pub mod env;
 mod error;
+mod fan_out;
 mod group;
+mod routing;
+mod solution_export;
+mod work_history;
 
 // Reexport inner structures
+pub use api::Api as ApiConfig;
+
+pub use client::clamp_target as clamp_client_target;
+pub use client::url_host as client_url_host;
+pub use client::CoinbaseCheck as ClientCoinbaseCheck;
 pub use client::Descriptor as ClientDescriptor;
 pub use client::Protocol as ClientProtocol;
+pub use client::RejectBreaker as ClientRejectBreaker;
+pub use client::SharePolicy as ClientSharePolicy;
 pub use client::UserInfo as ClientUserInfo;
 pub use client::URL_JAVA_SCRIPT_REGEX as CLIENT_URL_JAVA_SCRIPT_REGEX;
 
+pub use digest::Digest as DigestConfig;
+
+pub use fan_out::FanOut as FanOutConfig;
+
 pub use group::Descriptor as GroupDescriptor;
+pub use group::Failover as GroupFailover;
+pub use group::Health as GroupFailoverHealth;
 pub use group::LoadBalanceStrategy;
 
+pub use routing::RoutingPolicy as SolutionRoutingPolicy;
+
+pub use solution_export::SolutionExport as SolutionExportConfig;
+
+pub use work_history::WorkHistory as WorkHistoryConfig;
+
 // reexport common crates
 pub use clap;
 pub use config;
@@ -48,6 +74,59 @@ pub struct PoolConfig {
     pub user: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
+    /// Override of the global Bitcoin network for this pool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// Operator defined tag used to slice share accounting of pools (e.g. by cost center)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Name of the pool shown in logs and API (shortened URL and user by default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject_breaker: Option<ClientRejectBreaker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coinbase_check: Option<ClientCoinbaseCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_policy: Option<ClientSharePolicy>,
+    /// Number of days before expiration of the pool certificate when a warning is raised
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_expiry_warning: Option<u32>,
+    /// Path to PEM file with CA certificates trusted by TLS connection to the pool instead of
+    /// the built-in root certificates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// Accept any certificate of TLS connection to the pool (e.g. self-signed one of a local
+    /// proxy)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+    /// Lowest share difficulty suggested to the pool (shares meeting the pool target are still
+    /// submitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_difficulty: Option<usize>,
+    /// Share difficulty requested from the pool regardless of the difficulty it assigns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_difficulty: Option<usize>,
+    /// Seconds after which the address of an established connection is not preferred anymore
+    /// and the pool is reconnected to the first address resolved at that time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_ttl: Option<u64>,
+}
+
+impl PoolConfig {
+    /// Expand references to environment variables in the connection settings of the pool
+    pub fn expand_env(&mut self) -> Result<(), String> {
+        let url = self.url.clone();
+        let expand = |key: &str, value: &str| {
+            env::expand(value).map_err(|e| format!("{} in '{}' of pool '{}'", e, key, url))
+        };
+        self.url = expand("url", &self.url)?;
+        self.user = expand("user", &self.user)?;
+        if let Some(password) = self.password.as_ref() {
+            self.password = Some(expand("password", password)?);
+        }
+        Ok(())
+    }
 }
 
 // NOTE: `#[serde(deny_unknown_fields)]` cannot be used due to flatten descriptor but the error is
@@ -0,0 +1,44 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use serde::{Deserialize, Serialize};
+
+/// Handling of solutions whose origin client has been removed while they were being solved
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoutingPolicy {
+    /// Discard the solutions
+    #[serde(rename = "drop")]
+    Drop,
+    /// Submit the solutions through the client which has replaced the removed one in its group
+    #[serde(rename = "reroute_same_group")]
+    RerouteSameGroup,
+    /// Submit the solutions through any connected client whose pool accepts solutions of foreign
+    /// jobs (the client supplying jobs of its group is preferred)
+    #[serde(rename = "reroute_active")]
+    RerouteActive,
+}
+
+impl Default for RoutingPolicy {
+    fn default() -> Self {
+        Self::Drop
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of the export of raw solutions to a ring buffer in shared memory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SolutionExport {
+    /// Path to the file mapped by the miner and by the reader (e.g. in `/dev/shm`)
+    pub path: String,
+    /// Size of the whole ring buffer in bytes including its header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+}
+
+impl SolutionExport {
+    pub const DEFAULT_SIZE: usize = 1024 * 1024;
+    /// The ring buffer has to fill at least one page
+    pub const MIN_SIZE: usize = 4096;
+
+    pub fn size(&self) -> usize {
+        self.size.unwrap_or(Self::DEFAULT_SIZE)
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if cfg!(not(unix)) {
+            Err("solution export is not supported on this platform".to_string())?;
+        }
+        if self.path.is_empty() {
+            Err("solution export path cannot be empty".to_string())?;
+        }
+        if self.size() < Self::MIN_SIZE {
+            Err(format!(
+                "solution export size {} is lower than {} bytes",
+                self.size(),
+                Self::MIN_SIZE
+            ))?;
+        }
+        Ok(())
+    }
+}
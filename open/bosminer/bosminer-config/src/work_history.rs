@@ -0,0 +1,58 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of the log of work engine lifecycle events
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WorkHistory {
+    /// Number of the most recent events kept in memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    /// Path to JSONL file where all events are mirrored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<String>,
+}
+
+impl WorkHistory {
+    pub const DEFAULT_LENGTH: usize = 1024;
+
+    pub fn length(&self) -> usize {
+        self.length.unwrap_or(Self::DEFAULT_LENGTH)
+    }
+
+    pub fn mirror(&self) -> Option<&str> {
+        self.mirror.as_deref()
+    }
+
+    /// Check that the settings are meaningful
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.length() == 0 {
+            Err("work history length cannot be zero".to_string())?;
+        }
+        if self.mirror().map_or(false, |mirror| mirror.is_empty()) {
+            Err("work history mirror path cannot be empty".to_string())?;
+        }
+        Ok(())
+    }
+}
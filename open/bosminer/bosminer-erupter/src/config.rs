@@ -27,6 +27,9 @@ use bosminer_config::ClientDescriptor;
 
 use std::time::Duration;
 
+/// Model of the device reported by `--check-hardware`
+pub const HW_MODEL: &'static str = "Block Erupter";
+
 /// Override the default drain channel size as miner tends to burst messages into the logger
 pub const ASYNC_LOGGER_DRAIN_CHANNEL_SIZE: usize = 128;
 
@@ -39,7 +42,7 @@ pub const DEFAULT_HASHRATE_INTERVAL: Duration = Duration::from_secs(60);
 /// Maximum time it takes to compute one job under normal circumstances
 pub const JOB_TIMEOUT: Duration = Duration::from_secs(30);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Backend {
     client_manager: Option<client::Manager>,
     client_descriptor: Option<ClientDescriptor>,
@@ -55,14 +58,16 @@ impl Backend {
 
     pub async fn init_client(self) {
         if let Some(client_descriptor) = self.client_descriptor {
-            let group = self
-                .client_manager
-                .expect("BUG: missing client manager")
-                .create_or_get_default_group()
-                .await;
+            let client_manager = self.client_manager.expect("BUG: missing client manager");
+            let group = client_manager.create_or_get_default_group().await;
 
             group
-                .push_client(client::Handle::new(client_descriptor, None, None))
+                .push_client(client::Handle::new(
+                    client_descriptor,
+                    client_manager.services(),
+                    None,
+                    None,
+                ))
                 .await;
         }
     }
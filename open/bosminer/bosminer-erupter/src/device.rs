@@ -25,11 +25,13 @@
 
 use crate::error::{self, ErrorKind};
 use crate::icarus;
+use crate::usb;
 use crate::Solution;
 
 use bosminer::work;
 
 use failure::{Fail, ResultExt};
+use rusb::UsbContext as _;
 
 use std::cell::RefCell;
 use std::convert::TryInto;
@@ -48,8 +50,8 @@ const CP210X_VALUE_UART_ENABLE: u16 = 0x0001;
 const CP210X_VALUE_DATA: u16 = 0x0303;
 const CP210X_DATA_BAUD: u32 = 115200;
 
-const ID_VENDOR: u16 = 0x10c4;
-const ID_PRODUCT: u16 = 0xea60;
+pub(crate) const ID_VENDOR: u16 = 0x10c4;
+pub(crate) const ID_PRODUCT: u16 = 0xea60;
 
 const DEVICE_IFACE: u8 = 0;
 const DEVICE_CONFIGURATION: u8 = 1;
@@ -71,18 +73,33 @@ const MAX_READ_TIME: Duration =
     Duration::from_millis((icarus::FULL_NONCE_TIME_MS - READ_REDUCE_MS) as u64);
 
 pub struct BlockErupter<'a> {
-    context: &'a libusb::Context,
-    device: libusb::DeviceHandle<'a>,
+    /// The device handle holds its own reference to the context but the device is still tied to
+    /// the lifetime of the context it has been found in
+    _context: &'a rusb::Context,
+    device: rusb::DeviceHandle<rusb::Context>,
 }
 
 impl<'a> BlockErupter<'a> {
-    pub fn new(context: &'a libusb::Context, device: libusb::DeviceHandle<'a>) -> Self {
-        Self { context, device }
+    pub fn new(context: &'a rusb::Context, device: rusb::DeviceHandle<rusb::Context>) -> Self {
+        Self {
+            _context: context,
+            device,
+        }
+    }
+
+    /// Enumerate all Block Erupters connected to USB without opening them
+    pub fn detect(bus: &impl usb::Bus) -> error::Result<Vec<usb::Location>> {
+        Ok(bus
+            .list_devices()?
+            .into_iter()
+            .filter(|device| device.is(ID_VENDOR, ID_PRODUCT))
+            .map(|device| device.location)
+            .collect())
     }
 
     /// Try to find Block Erupter connected to USB
     /// Only first device is returned when multiple Block Erupters are connected.
-    pub fn find(context: &'a libusb::Context) -> Option<Self> {
+    pub fn find(context: &'a rusb::Context) -> Option<Self> {
         context
             .open_device_with_vid_pid(ID_VENDOR, ID_PRODUCT)
             .map(|device| Self::new(context, device))
@@ -92,12 +109,14 @@ impl<'a> BlockErupter<'a> {
     /// The USB device using a standard `CP210x` chip, which results in loading standard driver into
     /// the kernel for handling USB to UART bridge. This initialization tries to detach this driver
     /// from the kernel and provide its own implementation implemented by the `libusb` library.
+    /// The kernel driver can be detached only on Linux, on other platforms the device has to be
+    /// bound to a generic USB driver (e.g. WinUSB on Windows).
     pub fn init(&mut self) -> error::Result<()> {
         self.device
             .reset()
             .with_context(|_| ErrorKind::Usb("cannot reset device"))?;
 
-        if self.context.supports_detach_kernel_driver() {
+        if rusb::supports_detach_kernel_driver() {
             if self
                 .device
                 .kernel_driver_active(DEVICE_IFACE)
@@ -179,7 +198,7 @@ impl<'a> BlockErupter<'a> {
                     .try_into()
                     .expect("slice with incorrect length"))
             }
-            Err(libusb::Error::Timeout) => Ok(None),
+            Err(rusb::Error::Timeout) => Ok(None),
             Err(e) => Err(e.context(ErrorKind::Usb("cannot read nonce")).into()),
         }
     }
@@ -318,6 +337,7 @@ impl<'a> Iterator for BlockErupterSolver<'a> {
                 // send new work and wait for result in the next iteration when no error occurs
                 Some(work) => {
                     self.send_work(&work);
+                    block_on(self.work_generator.account_dispatched(&work));
                     self.curr_work = Some(work);
                     self.solution_idx = 0;
                 }
@@ -341,8 +361,8 @@ pub mod test {
 
     lazy_static! {
         pub static ref USB_CONTEXT_MUTEX: sync::Mutex<()> = sync::Mutex::new(());
-        pub static ref USB_CONTEXT: libusb::Context =
-            libusb::Context::new().expect("cannot create new USB context");
+        pub static ref USB_CONTEXT: rusb::Context =
+            rusb::Context::new().expect("cannot create new USB context");
     }
 
     struct BlockErupterGuard<'a> {
@@ -26,6 +26,7 @@ pub mod config;
 pub mod device;
 pub mod error;
 pub mod icarus;
+pub mod usb;
 
 use bosminer::async_trait;
 use bosminer::error::backend::ResultExt;
@@ -37,6 +38,8 @@ use bosminer_macros::WorkSolverNode;
 
 use error::ErrorKind;
 
+use serde::Serialize;
+
 use ii_async_compat::tokio;
 use tokio::task;
 
@@ -44,6 +47,35 @@ use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Block Erupters detected without starting mining (used by `--check-hardware`)
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareReport {
+    pub model: &'static str,
+    pub devices: Vec<usb::Location>,
+}
+
+impl HardwareReport {
+    /// Probe the USB for all connected Block Erupters
+    pub fn probe() -> bosminer::Result<Self> {
+        let usb_context =
+            rusb::Context::new().context(ErrorKind::Usb("cannot create USB context"))?;
+        Self::probe_bus(&usb_context)
+    }
+
+    /// Probe the passed `bus` for all connected Block Erupters
+    pub fn probe_bus(bus: &impl usb::Bus) -> bosminer::Result<Self> {
+        Ok(Self {
+            model: config::HW_MODEL,
+            devices: device::BlockErupter::detect(bus)?,
+        })
+    }
+
+    /// Mining can be started with the detected hardware
+    pub fn is_usable(&self) -> bool {
+        !self.devices.is_empty()
+    }
+}
+
 /// Represents raw solution from the Block Erupter
 #[derive(Debug)]
 pub struct Solution {
@@ -105,7 +137,7 @@ impl Backend {
     fn run(&self) -> bosminer::error::Result<()> {
         info!("Block Erupter: finding device in USB...");
         let usb_context =
-            libusb::Context::new().context(ErrorKind::Usb("cannot create USB context"))?;
+            rusb::Context::new().context(ErrorKind::Usb("cannot create USB context"))?;
         let mut device = device::BlockErupter::find(&usb_context)
             .ok_or_else(|| ErrorKind::Usb("cannot find Block Erupter device"))?;
 
@@ -182,6 +214,10 @@ impl hal::Backend for Backend {
         config: config::Backend,
         work_solver: Arc<Self>,
     ) -> bosminer::Result<hal::FrontendConfig> {
+        // Fail early when the device is missing instead of in the background solver task
+        if !HardwareReport::probe()?.is_usable() {
+            Err(ErrorKind::Usb("cannot find Block Erupter device"))?;
+        }
         // TODO: remove it after `node::WorkSolver` trait will be extended with `enable` method
         work_solver.enable();
 
@@ -190,6 +226,7 @@ impl hal::Backend for Backend {
 
         Ok(hal::FrontendConfig {
             cgminer_custom_commands: None,
+            api: Default::default(),
         })
     }
 }
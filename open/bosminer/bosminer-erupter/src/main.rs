@@ -22,6 +22,7 @@
 
 use ii_logging::macros::*;
 
+use bosminer::ExitCode;
 use bosminer_erupter::config;
 
 use bosminer_config::clap;
@@ -29,17 +30,29 @@ use bosminer_config::{ClientDescriptor, ClientUserInfo};
 
 use ii_async_compat::tokio;
 
+/// Needed for allocation statistics reported by `bench`
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: bosminer::bench::CountingAllocator = bosminer::bench::CountingAllocator;
+
 #[tokio::main]
 async fn main() {
+    // the logger is flushed when `run` returns
+    run().await.exit();
+}
+
+/// Run the miner or one of the sub-commands and return the exit status
+async fn run() -> ExitCode {
     let app = clap::App::new(bosminer::SIGNATURE)
         .version(bosminer::version::STRING.as_str())
+        .setting(clap::AppSettings::SubcommandsNegateReqs)
         .arg(
             clap::Arg::with_name("pool")
                 .short("p")
                 .long("pool")
                 .value_name("HOSTNAME:PORT")
                 .help("Address the stratum V2 server")
-                .required(true)
+                .required_unless("check-hardware")
                 .takes_value(true),
         )
         .arg(
@@ -48,14 +61,35 @@ async fn main() {
                 .long("user")
                 .value_name("USERNAME.WORKERNAME[:PASSWORD]")
                 .help("Specify user and worker name")
-                .required(true)
+                .required_unless("check-hardware")
                 .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("check-hardware")
+                .long("check-hardware")
+                .help("Detect connected devices, print them as JSON and exit without mining")
+                .required(false),
         );
+    #[cfg(feature = "bench")]
+    let app = app.subcommand(bosminer::bench::sub_command());
 
     let matches = app.get_matches();
     let _log_guard =
         ii_logging::setup_for_app(bosminer_erupter::config::ASYNC_LOGGER_DRAIN_CHANNEL_SIZE);
 
+    #[cfg(feature = "bench")]
+    if let Some(matches) = matches.subcommand_matches("bench") {
+        if let Err(e) = bosminer::bench::handle(matches) {
+            error!("Cannot run benchmark: {}", e);
+            return ExitCode::Failure;
+        }
+        return ExitCode::Success;
+    }
+
+    if matches.is_present("check-hardware") {
+        return check_hardware();
+    }
+
     let url = matches
         .value_of("pool")
         .expect("BUG: missing 'pool' attribute");
@@ -68,12 +102,37 @@ async fn main() {
         config::Backend::new(match ClientDescriptor::create(url, &user_info, true) {
             Err(e) => {
                 error!("Cannot set pool from command line: {}", e.to_string());
-                return;
+                return ExitCode::Config;
             }
             Ok(v) => v,
         });
 
     ii_async_compat::setup_panic_handling();
-    bosminer::main::<bosminer_erupter::Backend>(backend_config, bosminer::SIGNATURE.to_string())
-        .await;
+    // logging has been already set up for handling of the sub-commands
+    bosminer::main::<bosminer_erupter::Backend>(
+        backend_config,
+        bosminer::SIGNATURE.to_string(),
+        None,
+    )
+    .await
+}
+
+/// Print all detected Block Erupters as JSON to stdout
+fn check_hardware() -> ExitCode {
+    let report = match bosminer_erupter::HardwareReport::probe() {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Cannot detect hardware: {}", e);
+            return ExitCode::NoHardware;
+        }
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&report).expect("BUG: cannot serialize hardware report")
+    );
+    if report.is_usable() {
+        ExitCode::Success
+    } else {
+        ExitCode::NoHardware
+    }
 }
@@ -0,0 +1,159 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Platform independent access to the list of devices connected to USB. The `rusb` library works
+//! the same way on Linux, macOS and Windows so the discovery does not need any OS specific paths
+//! (e.g. sysfs). The bus is abstracted to allow testing of the discovery with a mocked list of
+//! devices.
+
+use crate::error::{self, ErrorKind};
+
+use failure::ResultExt;
+use rusb::UsbContext as _;
+
+use serde::Serialize;
+
+/// Location of a device on the USB
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub bus: u8,
+    pub address: u8,
+}
+
+/// Identification of a device connected to USB
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub location: Location,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl DeviceInfo {
+    #[inline]
+    pub fn is(&self, vendor_id: u16, product_id: u16) -> bool {
+        self.vendor_id == vendor_id && self.product_id == product_id
+    }
+}
+
+/// Source of the devices connected to USB
+pub trait Bus {
+    /// List all connected devices without opening them
+    fn list_devices(&self) -> error::Result<Vec<DeviceInfo>>;
+}
+
+impl Bus for rusb::Context {
+    fn list_devices(&self) -> error::Result<Vec<DeviceInfo>> {
+        let devices = self
+            .devices()
+            .with_context(|_| ErrorKind::Usb("cannot list devices"))?;
+        Ok(devices
+            .iter()
+            .filter_map(|device| {
+                // skip devices whose descriptor cannot be read (e.g. due to missing permissions)
+                let descriptor = device.device_descriptor().ok()?;
+                Some(DeviceInfo {
+                    location: Location {
+                        bus: device.bus_number(),
+                        address: device.address(),
+                    },
+                    vendor_id: descriptor.vendor_id(),
+                    product_id: descriptor.product_id(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::device::{self, BlockErupter};
+    use crate::HardwareReport;
+
+    const OTHER_VENDOR: u16 = 0x1d6b;
+    const OTHER_PRODUCT: u16 = 0x0002;
+
+    /// Bus with a fixed list of devices (`None` simulates failure of the enumeration)
+    struct MockBus(Option<Vec<DeviceInfo>>);
+
+    impl Bus for MockBus {
+        fn list_devices(&self) -> error::Result<Vec<DeviceInfo>> {
+            self.0
+                .clone()
+                .ok_or_else(|| ErrorKind::Usb("cannot list devices").into())
+        }
+    }
+
+    fn usb_device(bus: u8, address: u8, vendor_id: u16, product_id: u16) -> DeviceInfo {
+        DeviceInfo {
+            location: Location { bus, address },
+            vendor_id,
+            product_id,
+        }
+    }
+
+    fn erupter(bus: u8, address: u8) -> DeviceInfo {
+        usb_device(bus, address, device::ID_VENDOR, device::ID_PRODUCT)
+    }
+
+    #[test]
+    fn test_detect() {
+        let bus = MockBus(Some(vec![
+            usb_device(1, 1, OTHER_VENDOR, OTHER_PRODUCT),
+            erupter(1, 4),
+            // the same vendor is used by other CP210x bridges
+            usb_device(1, 5, device::ID_VENDOR, OTHER_PRODUCT),
+            erupter(2, 3),
+        ]));
+        let locations = BlockErupter::detect(&bus).expect("BUG: detection failed");
+        assert_eq!(
+            locations,
+            vec![
+                Location { bus: 1, address: 4 },
+                Location { bus: 2, address: 3 }
+            ]
+        );
+
+        let report = HardwareReport::probe_bus(&bus).expect("BUG: probe failed");
+        assert!(report.is_usable());
+        assert_eq!(report.devices, locations);
+    }
+
+    #[test]
+    fn test_detect_none() {
+        let bus = MockBus(Some(vec![]));
+        assert!(BlockErupter::detect(&bus)
+            .expect("BUG: detection failed")
+            .is_empty());
+
+        let bus = MockBus(Some(vec![usb_device(1, 1, OTHER_VENDOR, OTHER_PRODUCT)]));
+        let report = HardwareReport::probe_bus(&bus).expect("BUG: probe failed");
+        assert!(!report.is_usable());
+    }
+
+    #[test]
+    fn test_detect_error() {
+        let bus = MockBus(None);
+        let error = BlockErupter::detect(&bus).expect_err("BUG: missing error");
+        assert_eq!(error.kind(), ErrorKind::Usb("cannot list devices"));
+    }
+}
@@ -141,7 +141,10 @@ fn impl_derive_work_solver_node(
         member_valid_network_diff,
         member_valid_job_diff,
         member_valid_backend_diff,
-        member_error_backend_diff
+        member_error_backend_diff,
+        member_rejected_job_diff,
+        member_found_blocks,
+        member_dropped_stale
     )
 )]
 pub fn derive_mining_stats(input: TokenStream) -> TokenStream {
@@ -161,6 +164,9 @@ fn impl_derive_mining_stats(ast: &DeriveInput, derive_name: &str) -> proc_macro2
     let valid_job_diff = find_member(&fields, "member_valid_job_diff");
     let valid_backend_diff = find_member(&fields, "member_valid_backend_diff");
     let error_backend_diff = find_member(&fields, "member_error_backend_diff");
+    let rejected_job_diff = find_member(&fields, "member_rejected_job_diff");
+    let found_blocks = find_member(&fields, "member_found_blocks");
+    let dropped_stale = find_member(&fields, "member_dropped_stale");
 
     quote! {
         impl#generics stats::Mining for #name#generics {
@@ -198,6 +204,21 @@ fn impl_derive_mining_stats(ast: &DeriveInput, derive_name: &str) -> proc_macro2
             fn error_backend_diff(&self) -> &stats::Meter {
                 &self.#error_backend_diff
             }
+
+            #[inline]
+            fn rejected_job_diff(&self) -> &stats::Meter {
+                &self.#rejected_job_diff
+            }
+
+            #[inline]
+            fn found_blocks(&self) -> &stats::CounterU64 {
+                &self.#found_blocks
+            }
+
+            #[inline]
+            fn dropped_stale(&self) -> &stats::CounterU64 {
+                &self.#dropped_stale
+            }
         }
     }
 }
@@ -215,10 +236,18 @@ fn impl_derive_mining_stats(ast: &DeriveInput, derive_name: &str) -> proc_macro2
         member_accepted,
         member_rejected,
         member_stale,
+        member_difficulty_history,
+        member_job_history,
+        member_share_policy,
+        member_submissions,
+        member_lifetime,
         member_valid_network_diff,
         member_valid_job_diff,
         member_valid_backend_diff,
-        member_error_backend_diff
+        member_error_backend_diff,
+        member_rejected_job_diff,
+        member_found_blocks,
+        member_dropped_stale
     )
 )]
 pub fn derive_client_stats(input: TokenStream) -> TokenStream {
@@ -243,6 +272,11 @@ fn impl_derive_client_stats(
     let accepted = find_member(&fields, "member_accepted");
     let rejected = find_member(&fields, "member_rejected");
     let stale = find_member(&fields, "member_stale");
+    let difficulty_history = find_member(&fields, "member_difficulty_history");
+    let job_history = find_member(&fields, "member_job_history");
+    let share_policy = find_member(&fields, "member_share_policy");
+    let submissions = find_member(&fields, "member_submissions");
+    let lifetime = find_member(&fields, "member_lifetime");
 
     stream.extend(quote! {
         impl#generics stats::Client for #name#generics {
@@ -275,6 +309,31 @@ fn impl_derive_client_stats(
             fn stale(&self) -> &stats::Meter {
                 &self.#stale
             }
+
+            #[inline]
+            fn difficulty_history(&self) -> &stats::DifficultyHistory {
+                &self.#difficulty_history
+            }
+
+            #[inline]
+            fn job_history(&self) -> &stats::JobHistory {
+                &self.#job_history
+            }
+
+            #[inline]
+            fn share_policy(&self) -> &share_policy::Counters {
+                &self.#share_policy
+            }
+
+            #[inline]
+            fn submissions(&self) -> &submission::Counters {
+                &self.#submissions
+            }
+
+            #[inline]
+            fn lifetime(&self) -> &stats::Lifetime {
+                &self.#lifetime
+            }
         }
     });
     stream
@@ -287,12 +346,27 @@ fn impl_derive_client_stats(
         member_start_time,
         member_last_work_time,
         member_generated_work,
+        member_dispatched_work,
+        member_malformed_solutions,
+        member_hardware_errors,
+        member_expired_assignments,
+        member_work_progress,
+        member_job_latency,
+        member_solution_latency,
+        member_work_starvation,
+        member_quarantine,
+        member_pause,
+        member_removal,
+        member_device_health,
         member_last_share,
         member_best_share,
         member_valid_network_diff,
         member_valid_job_diff,
         member_valid_backend_diff,
-        member_error_backend_diff
+        member_error_backend_diff,
+        member_rejected_job_diff,
+        member_found_blocks,
+        member_dropped_stale
     )
 )]
 pub fn derive_work_solver_stats(input: TokenStream) -> TokenStream {
@@ -313,6 +387,18 @@ fn impl_derive_work_solver_stats(
     let fields = get_fields(&ast, derive_name);
     let last_work_time = find_member(&fields, "member_last_work_time");
     let generated_work = find_member(&fields, "member_generated_work");
+    let dispatched_work = find_member(&fields, "member_dispatched_work");
+    let malformed_solutions = find_member(&fields, "member_malformed_solutions");
+    let hardware_errors = find_member(&fields, "member_hardware_errors");
+    let expired_assignments = find_member(&fields, "member_expired_assignments");
+    let work_progress = find_member(&fields, "member_work_progress");
+    let job_latency = find_member(&fields, "member_job_latency");
+    let solution_latency = find_member(&fields, "member_solution_latency");
+    let work_starvation = find_member(&fields, "member_work_starvation");
+    let quarantine = find_member(&fields, "member_quarantine");
+    let pause = find_member(&fields, "member_pause");
+    let removal = find_member(&fields, "member_removal");
+    let device_health = find_member(&fields, "member_device_health");
 
     stream.extend(quote! {
         impl#generics stats::WorkSolver for #name#generics {
@@ -325,6 +411,66 @@ fn impl_derive_work_solver_stats(
             fn generated_work(&self) -> &stats::CounterU64 {
                 &self.#generated_work
             }
+
+            #[inline]
+            fn dispatched_work(&self) -> &stats::CounterU64 {
+                &self.#dispatched_work
+            }
+
+            #[inline]
+            fn malformed_solutions(&self) -> &stats::CounterU64 {
+                &self.#malformed_solutions
+            }
+
+            #[inline]
+            fn hardware_errors(&self) -> &stats::CounterU64 {
+                &self.#hardware_errors
+            }
+
+            #[inline]
+            fn expired_assignments(&self) -> &stats::CounterU64 {
+                &self.#expired_assignments
+            }
+
+            #[inline]
+            fn work_progress(&self) -> &stats::WorkProgress {
+                &self.#work_progress
+            }
+
+            #[inline]
+            fn job_latency(&self) -> &stats::JobLatency {
+                &self.#job_latency
+            }
+
+            #[inline]
+            fn solution_latency(&self) -> &stats::SolutionLatency {
+                &self.#solution_latency
+            }
+
+            #[inline]
+            fn work_starvation(&self) -> &stats::WorkStarvation {
+                &self.#work_starvation
+            }
+
+            #[inline]
+            fn quarantine(&self) -> &stats::Quarantine {
+                &self.#quarantine
+            }
+
+            #[inline]
+            fn pause(&self) -> &stats::Pause {
+                &self.#pause
+            }
+
+            #[inline]
+            fn removal(&self) -> &stats::Removal {
+                &self.#removal
+            }
+
+            #[inline]
+            fn device_health(&self) -> &stats::DeviceHealth {
+                &self.#device_health
+            }
         }
     });
     stream
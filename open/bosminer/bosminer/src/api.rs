@@ -28,6 +28,12 @@ use crate::hub;
 use std::sync::Arc;
 
 pub async fn run(core: Arc<hub::Core>, config: hal::FrontendConfig, signature: String) {
-    let addr = "0.0.0.0:4028".parse().unwrap();
-    cgminer::run(core, addr, config.cgminer_custom_commands, signature).await;
+    cgminer::run(
+        core,
+        config.api.listen_addr(),
+        config.api.allow_list(),
+        config.cgminer_custom_commands,
+        signature,
+    )
+    .await;
 }
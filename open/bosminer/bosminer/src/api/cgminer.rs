@@ -23,22 +23,41 @@
 //! This module implements CGMiner compatible API server to control BOSminer and to extract
 //! statistics from it.
 
-use crate::client;
+use ii_logging::macros::*;
+
+use crate::backend::query;
+use crate::client::{self, digest, probe};
+use crate::clock;
 use crate::error;
 use crate::hub;
+use crate::job;
 use crate::node::{self, Stats as _, WorkSolver, WorkSolverStats as _};
+use crate::shutdown;
 use crate::stats::{self, UnixTime as _};
 use crate::sync;
 use crate::version;
+use crate::work;
 
+use ii_cgminer_api::command::{
+    BLOCK_CANDIDATES, CHAIN_PAUSE, CHAIN_RESUME, DEVDETAILS, DIAG, DIFF_HISTORY, DIGEST,
+    JOB_HISTORY, LATENCY, NODES, NOTIFY, POOL_ADDRESSES, PROGRESS, QUARANTINE, RESTART,
+    SESSION_HISTORY, SHUTDOWN, TEST_POOL, WORK_AUDIT, WORK_HISTORY,
+};
 use ii_cgminer_api::support::ValueExt as _;
-use ii_cgminer_api::{command, json, response};
+use ii_cgminer_api::{command, commands, json, response};
+
+use serde::Serialize;
 
 use bosminer_config::{ClientDescriptor, ClientUserInfo};
 
+use futures::future;
+use ii_async_compat::{futures, tokio};
+use tokio::time::delay_for;
+
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time;
 
 use stats::TIME_MEAN_INTERVAL_15M as INTERVAL_15M;
@@ -50,13 +69,88 @@ use stats::TIME_MEAN_INTERVAL_5S as INTERVAL_5S;
 /// Default interval used for computation of default rolling average.
 const DEFAULT_LOG_INTERVAL: u32 = 5;
 
+/// Time given to the response of SHUTDOWN command to reach the client before the process exits
+const SHUTDOWN_DELAY: time::Duration = time::Duration::from_millis(100);
+
+/// Backend specific commands of the current backend. The API server keeps running when the
+/// backend is rebuilt on restart so the commands registered in the server only forward requests
+/// to the commands of the current backend.
+type BackendCommands = Arc<StdMutex<Arc<command::Map>>>;
+
+/// Backend independent details of the device followed by the backend specific values
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DevDetailInfo {
+    /// Uid of the node which is listed by `nodes` command
+    #[serde(rename = "UID")]
+    pub uid: u64,
+    #[serde(rename = "Hardware Revision")]
+    pub hw_rev: String,
+    #[serde(rename = "Firmware")]
+    pub fw_ver: String,
+    #[serde(rename = "Device ID")]
+    pub dev_id: String,
+    /// Backend specific fingerprint of the environment (kernel, device-tree, ...)
+    #[serde(rename = "Environment")]
+    pub environment: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub backend_stats: json::Map<String, json::Value>,
+}
+
+#[derive(Clone)]
 struct Handler {
     core: Arc<hub::Core>,
+    backend_commands: BackendCommands,
 }
 
 impl Handler {
     pub fn new(core: Arc<hub::Core>) -> Self {
-        Self { core }
+        Self {
+            core,
+            backend_commands: Default::default(),
+        }
+    }
+
+    pub fn with_backend_commands(self, backend_commands: command::Map) -> Self {
+        *self
+            .backend_commands
+            .lock()
+            .expect("BUG: cannot lock backend commands") = Arc::new(backend_commands);
+        self
+    }
+
+    /// Create commands forwarding requests to the backend specific commands with the same name
+    fn forward_backend_commands(&self) -> command::Map {
+        let backend_commands = self
+            .backend_commands
+            .lock()
+            .expect("BUG: cannot lock backend commands")
+            .clone();
+        backend_commands
+            .iter()
+            .map(|(&name, descriptor)| {
+                let current_commands = self.backend_commands.clone();
+                let call = move |parameter: Option<&json::Value>| -> command::AsyncHandler {
+                    let current_commands = current_commands
+                        .lock()
+                        .expect("BUG: cannot lock backend commands")
+                        .clone();
+                    match current_commands.get(name) {
+                        Some(descriptor) => descriptor.call(name, parameter),
+                        None => Box::pin(future::ready(Err(
+                            response::ErrorCode::InvalidCommand.into()
+                        ))),
+                    }
+                };
+                let handler = if descriptor.has_parameters() {
+                    command::HandlerType::Parameter(Box::new(call))
+                } else {
+                    command::HandlerType::ParameterLess(Box::new(move || call(None)))
+                };
+                let forward = command::Descriptor::new(name, handler, None)
+                    .with_privilege(descriptor.privilege());
+                (name, forward)
+            })
+            .collect()
     }
 
     async fn collect_data<C, F, T, U, V>(&self, container: C, base_idx: usize, f: F) -> Vec<T>
@@ -73,6 +167,29 @@ impl Handler {
         list
     }
 
+    /// Same as `collect_data` for all work solvers where the index passed to `f` is the stable
+    /// index of the work solver (indices of unregistered work solvers are skipped)
+    async fn collect_work_solver_data<F, T, V>(&self, f: F) -> Vec<T>
+    where
+        F: Fn(usize, Arc<dyn node::WorkSolver>) -> V,
+        V: Future<Output = T>,
+    {
+        let mut list = vec![];
+        for (idx, work_solver) in self.core.get_indexed_work_solvers().await {
+            list.push(f(idx, work_solver).await);
+        }
+        list
+    }
+
+    /// Return the work solver with the stable index `idx`
+    async fn get_work_solver(
+        &self,
+        idx: i32,
+    ) -> Result<Arc<dyn node::WorkSolver>, response::ErrorCode> {
+        let (work_solver, count) = self.core.get_work_solver(idx as usize).await;
+        work_solver.ok_or_else(|| response::ErrorCode::InvalidAscId(idx, count as i32 - 1))
+    }
+
     async fn get_pool_status(idx: usize, client: Arc<client::Handle>) -> response::Pool {
         let client_descriptor = client.descriptor().await;
         let last_job = client.get_last_job().await;
@@ -87,6 +204,7 @@ impl Handler {
         let last_share = client_stats.last_share().take_snapshot().await;
         let valid_backend_diff = client_stats.valid_backend_diff().take_snapshot().await;
         let best_share = client_stats.best_share().take_snapshot();
+        let dropped_stale = client_stats.dropped_stale().take_snapshot();
 
         let last_share_time = last_share
             .as_ref()
@@ -108,28 +226,47 @@ impl Handler {
             0.0
         };
 
+        // Effective target may be harder than the one assigned by the pool when the client
+        // overrides share difficulty (shares are still submitted with the pool target)
         let last_diff = last_job
             .as_ref()
-            .map(|job| job.target().get_difficulty() as f64)
+            .map(|job| {
+                bosminer_config::clamp_client_target(
+                    job.target(),
+                    client_descriptor.suggested_difficulty(),
+                )
+                .get_difficulty() as f64
+            })
             .unwrap_or(0.0);
+        let stratum_diff = client_stats
+            .difficulty_history()
+            .take_snapshot()
+            .last()
+            .map_or(last_diff, |change| change.new_difficulty as f64);
         let current_block_version = last_job.map(|job| job.version()).unwrap_or_default();
 
-        let (mut status, stratum_active) = match client.status() {
-            sync::Status::Running => (response::PoolStatus::Alive, true),
-            sync::Status::Created
+        let mut status = match client.status() {
+            sync::Status::Running
+            | sync::Status::Created
             | sync::Status::Starting
             | sync::Status::Stopping
             | sync::Status::Restarting
-            | sync::Status::Stopped => (response::PoolStatus::Alive, false),
+            | sync::Status::Stopped => response::PoolStatus::Alive,
             sync::Status::Failing
             | sync::Status::Declining
             | sync::Status::Retrying
             | sync::Status::Recovering
-            | sync::Status::Failed => (response::PoolStatus::Dead, false),
+            | sync::Status::Failed => response::PoolStatus::Dead,
         };
+        if client.is_soft_failed() {
+            status = response::PoolStatus::Rejecting;
+        }
         if !client.is_enabled() {
             status = response::PoolStatus::Disabled;
         }
+        // Health is available only after the first evaluation by the failover scheduler
+        let health = client.health();
+        let sample = health.as_ref().map(|(sample, _)| sample);
 
         response::Pool {
             idx: idx as i32,
@@ -145,8 +282,8 @@ impl Handler {
             accepted: accepted.solutions,
             rejected: rejected.solutions,
             works: *generated_work as i32,
-            // TODO: BOSminer does not account this information
-            discarded: 0,
+            // solutions of invalidated jobs dropped by the share policy
+            discarded: *dropped_stale as u32,
             stale: stale.solutions as u32,
             // TODO: account failures
             get_failures: 0,
@@ -163,13 +300,14 @@ impl Handler {
             last_share_difficulty,
             work_difficulty: last_diff,
             has_stratum: true,
-            stratum_active,
+            // Only the pool selected by failover supplies jobs of its group
+            stratum_active: client.is_active(),
             stratum_url: client_descriptor.get_url(false, true, false),
-            stratum_difficulty: last_diff,
+            stratum_difficulty: stratum_diff,
             // TODO: get actual value from client (Asic Boost)
             has_vmask: true,
             has_gbt: false,
-            best_share: best_share.map(|inner| *inner).unwrap_or_default() as u64,
+            best_share: best_share.map(|inner| *inner).unwrap_or_default(),
             pool_rejected_ratio,
             pool_stale_ratio,
             bad_work: *invalid_jobs as u64,
@@ -178,6 +316,18 @@ impl Handler {
             current_block_version,
             // TODO: get actual value from client
             asic_boost: true,
+            label: client_descriptor.label.clone(),
+            name: client.display_name(),
+            health_score: health.as_ref().map(|(_, score)| score.value),
+            job_age: sample
+                .and_then(|sample| sample.job_age)
+                .map(|age| age.as_secs()),
+            submit_latency_p95: sample
+                .and_then(|sample| sample.submit_latency_p95)
+                .map(|latency| latency.as_millis() as u64),
+            recent_rejected_ratio: sample
+                .and_then(|sample| sample.reject_rate)
+                .map(|rate| rate * 100.0),
         }
     }
 
@@ -196,9 +346,13 @@ impl Handler {
         let valid_job_diff = mining_stats.valid_job_diff().take_snapshot().await;
         let valid_backend_diff = mining_stats.valid_backend_diff().take_snapshot().await;
         let error_backend_diff = mining_stats.error_backend_diff().take_snapshot().await;
+        let rejected_job_diff = mining_stats.rejected_job_diff().take_snapshot().await;
+        let work_starvation = work_solver_stats.work_starvation().take_snapshot();
 
-        let now = time::Instant::now();
+        let now = clock::now();
         let elapsed = now.duration_since(*mining_stats.start_time());
+        // hashrate of paused work solver is frozen at the beginning of the pause
+        let now = work_solver_stats.pause().measurement_time(now);
 
         let last_work_time =
             last_work_time.map_or(0, |time| time.get_unix_time().unwrap_or_default());
@@ -216,16 +370,29 @@ impl Handler {
         } else {
             0.0
         };
+        let rejected_shares = rejected_job_diff.shares.as_f64();
+        let device_rejected_ratio = if backend_valid_solutions != 0 {
+            rejected_shares / backend_valid_solutions as f64 * 100.0
+        } else {
+            0.0
+        };
 
         response::Asc {
             idx: idx as i32,
             // TODO: get actual ASIC name from work solver
             name: "".to_string(),
             id: work_solver.get_id().unwrap_or(idx) as i32,
-            // TODO: get actual state from work solver
-            enabled: response::Bool::Y,
-            // TODO: get actual status from work solver
-            status: response::AscStatus::Alive,
+            // paused work solver is reported as disabled
+            enabled: if work_solver_stats.pause().is_paused() {
+                response::Bool::N
+            } else {
+                response::Bool::Y
+            },
+            status: match work_solver.get_mining_status().await {
+                node::Status::Ok | node::Status::Paused => response::AscStatus::Alive,
+                node::Status::Degraded => response::AscStatus::Sick,
+                node::Status::Failed | node::Status::Removed => response::AscStatus::Dead,
+            },
             // TODO: get actual temperature from work solver?
             temperature: 0.0,
             mhs_av: total_mega_hashes / elapsed.as_secs_f64(),
@@ -255,14 +422,15 @@ impl Handler {
             diff1_work: backend_valid_solutions,
             // TODO: BOSminer does not account accepted
             difficulty_accepted: 0.0,
-            // TODO: BOSminer does not account rejected
-            difficulty_rejected: 0.0,
+            difficulty_rejected: rejected_shares,
             last_share_difficulty,
             last_valid_work: last_work_time,
             device_hardware_ratio: backend_error_ratio,
-            // TODO: BOSminer does not account rejected
-            device_rejected_ratio: 0.0,
+            device_rejected_ratio,
             device_elapsed: elapsed.as_secs(),
+            mhs_24h: valid_backend_diff
+                .to_mega_hashes(*INTERVAL_24H, now)
+                .into_f64(),
             hardware_error_mhs_15m: error_backend_diff
                 .to_mega_hashes(*INTERVAL_15M, now)
                 .into_f64(),
@@ -271,12 +439,14 @@ impl Handler {
                 .await
                 .map(|hashrate| hashrate.into_mega_hashes().into_f64())
                 .unwrap_or_default(),
+            work_starvation_ms: work_starvation.duration.as_millis() as u64,
+            exhausted_engines: work_starvation.exhausted_engines,
         }
     }
 
     async fn collect_asc_statuses(&self) -> Vec<response::Asc> {
-        self.collect_data(self.core.get_work_solvers(), 0, |idx, work_solver| {
-            async move { Self::get_asc_status(idx, work_solver).await }
+        self.collect_work_solver_data(|idx, work_solver| async move {
+            Self::get_asc_status(idx, work_solver).await
         })
         .await
     }
@@ -284,8 +454,9 @@ impl Handler {
     async fn get_pool_stats(idx: usize, _client: Arc<client::Handle>) -> response::PoolStats {
         response::PoolStats {
             header: response::StatsHeader {
-                idx: idx as i32,
-                id: "".to_string(),
+                // sections are numbered when the response is serialized
+                idx: 0,
+                id: format!("POOL{}", idx),
                 elapsed: 0,
                 calls: 0,
                 wait: 0.0,
@@ -316,8 +487,8 @@ impl Handler {
         }
     }
 
-    async fn collect_pool_stats(&self, base_idx: usize) -> Vec<response::PoolStats> {
-        self.collect_data(self.get_clients(), base_idx, |idx, client| {
+    async fn collect_pool_stats(&self) -> Vec<response::PoolStats> {
+        self.collect_data(self.get_clients(), 0, |idx, client| {
             async move { Self::get_pool_stats(idx, client).await }
         })
         .await
@@ -325,30 +496,80 @@ impl Handler {
 
     async fn get_asc_stats(
         idx: usize,
-        _work_solver: Arc<dyn node::WorkSolver>,
+        work_solver: Arc<dyn node::WorkSolver>,
     ) -> response::AscStats {
+        let elapsed = clock::now().duration_since(*work_solver.mining_stats().start_time());
+        let backend_stats = Self::get_backend_stats(&work_solver).await;
+
         response::AscStats {
             header: response::StatsHeader {
-                idx: idx as i32,
-                id: "".to_string(),
-                elapsed: 0,
+                idx: 0,
+                id: format!("ASC{}", idx),
+                elapsed: elapsed.as_secs(),
                 calls: 0,
                 wait: 0.0,
                 max: 0.0,
                 min: 0.0,
             },
+            backend_stats,
         }
     }
 
-    async fn collect_asc_stats(&self, base_idx: usize) -> Vec<response::AscStats> {
-        self.collect_data(
-            self.core.get_work_solvers(),
-            base_idx,
-            |idx, work_solver| async move { Self::get_asc_stats(idx, work_solver).await },
-        )
+    async fn get_backend_stats(
+        work_solver: &Arc<dyn node::WorkSolver>,
+    ) -> json::Map<String, json::Value> {
+        match work_solver.backend_stats().await {
+            Some(json::Value::Object(backend_stats)) => backend_stats,
+            Some(_) => {
+                warn!("Work solver '{}' has invalid backend stats", work_solver);
+                Default::default()
+            }
+            None => Default::default(),
+        }
+    }
+
+    async fn collect_asc_stats(&self) -> Vec<response::AscStats> {
+        self.collect_work_solver_data(|idx, work_solver| async move {
+            Self::get_asc_stats(idx, work_solver).await
+        })
         .await
     }
 
+    /// Statistics of the frontend which precede statistics of devices
+    async fn get_miner_stats(&self) -> response::MinerStats {
+        let frontend = self.core.get_frontend();
+        let uptime = clock::now().duration_since(*frontend.mining_stats().start_time());
+
+        let mut valid_jobs = 0;
+        let mut invalid_jobs = 0;
+        for client in self.get_clients().await {
+            let client_stats = client.stats();
+            valid_jobs += *client_stats.valid_jobs().take_snapshot() as u64;
+            invalid_jobs += *client_stats.invalid_jobs().take_snapshot() as u64;
+        }
+
+        response::MinerStats {
+            header: response::StatsHeader {
+                idx: 0,
+                id: format!("{}0", crate::SIGNATURE),
+                elapsed: uptime.as_secs(),
+                calls: 0,
+                wait: 0.0,
+                max: 0.0,
+                min: 0.0,
+            },
+            uptime: uptime.as_secs(),
+            generated_work: frontend.get_generated_work(),
+            valid_jobs,
+            invalid_jobs,
+            live_jobs: self
+                .core
+                .get_job_registry()
+                .take_snapshot(clock::now())
+                .live_jobs as u32,
+        }
+    }
+
     /// Collects all clients from all groups into a single `Vec`
     async fn get_clients(&self) -> Vec<Arc<client::Handle>> {
         let mut clients = vec![];
@@ -370,6 +591,927 @@ impl Handler {
             .map(|client| (client, clients))
     }
 
+    /// Network difficulty of the last job from the first running client
+    async fn get_network_difficulty(&self) -> response::Difficulty {
+        for client in self.get_clients().await {
+            if !client.is_running() {
+                continue;
+            }
+            if let Some(job) = client.get_last_job().await {
+                let network = client.descriptor().await.network;
+                if let Ok(target) = network.target_from_compact(job.bits()) {
+                    return network.difficulty(&target);
+                }
+            }
+        }
+        0.0
+    }
+
+    async fn get_diff_changes(
+        pool_idx: usize,
+        client: Arc<client::Handle>,
+    ) -> Vec<response::ext::DiffChange> {
+        let client_stats = client.stats();
+        let changes = client_stats.difficulty_history().take_snapshot();
+        let accepted = client_stats.accepted().take_snapshot().await;
+
+        stats::DifficultyHistory::annotate(&changes, &accepted, clock::now())
+            .into_iter()
+            .map(|annotated| response::ext::DiffChange {
+                // the index is assigned when all pools are collected
+                idx: 0,
+                pool: pool_idx as i32,
+                when: annotated
+                    .change
+                    .system_time
+                    .get_unix_time()
+                    .unwrap_or_default(),
+                trigger: annotated.change.trigger.to_string(),
+                old_difficulty: annotated
+                    .change
+                    .old_difficulty
+                    .map(|difficulty| difficulty as f64),
+                new_difficulty: annotated.change.new_difficulty as f64,
+                accepted_rate_before: annotated.before.as_ref().map(|rate| rate.shares_per_minute),
+                accepted_rate_after: annotated.after.as_ref().map(|rate| rate.shares_per_minute),
+                mhs_before: annotated.before.map(|rate| rate.kilo_hashes / 1000.0),
+                mhs_after: annotated.after.map(|rate| rate.kilo_hashes / 1000.0),
+                dropped_samples: accepted.history_dropped(),
+            })
+            .collect()
+    }
+
+    async fn handle_diff_history(&self) -> command::Result<response::ext::DiffHistory> {
+        let mut list = vec![];
+        for (pool_idx, client) in self.get_clients().await.drain(..).enumerate() {
+            list.extend(Self::get_diff_changes(pool_idx, client).await);
+        }
+        for (idx, change) in list.iter_mut().enumerate() {
+            change.idx = idx as i32;
+        }
+        Ok(response::ext::DiffHistory { list })
+    }
+
+    async fn get_diag_solver(
+        idx: usize,
+        work_solver: Arc<dyn node::WorkSolver>,
+    ) -> response::ext::DiagSolver {
+        let progress = work_solver
+            .work_solver_stats()
+            .work_progress()
+            .take_snapshot();
+        let unix_time = |time: Option<time::SystemTime>| {
+            time.map_or(0, |time| time.get_unix_time().unwrap_or_default())
+        };
+
+        response::ext::DiagSolver {
+            idx: idx as i32,
+            id: work_solver.get_id().unwrap_or(idx) as i32,
+            name: work_solver.to_string(),
+            last_assignment_id: progress.last_assignment_id.unwrap_or_default(),
+            last_assignment_time: unix_time(progress.last_assignment_time),
+            last_nonce: progress.last_nonce,
+            last_solution_time: unix_time(progress.last_solution_time),
+            engine_generation: progress.engine_generation,
+            last_activity: work_solver
+                .get_last_activity()
+                .await
+                .map(|time| unix_time(Some(time))),
+        }
+    }
+
+    async fn handle_diag(&self) -> command::Result<response::ext::Diag> {
+        let list = self
+            .collect_work_solver_data(|idx, work_solver| async move {
+                Self::get_diag_solver(idx, work_solver).await
+            })
+            .await;
+        Ok(response::ext::Diag { list })
+    }
+
+    async fn get_latency_solver(
+        idx: usize,
+        work_solver: Arc<dyn node::WorkSolver>,
+    ) -> response::ext::LatencySolver {
+        let latency = work_solver
+            .work_solver_stats()
+            .solution_latency()
+            .take_snapshot();
+        let millis =
+            |latency: Option<time::Duration>| latency.map(|latency| latency.as_secs_f64() * 1000.0);
+
+        response::ext::LatencySolver {
+            idx: idx as i32,
+            id: work_solver.get_id().unwrap_or(idx) as i32,
+            name: work_solver.to_string(),
+            solutions: latency.solutions,
+            latency_p50: millis(latency.latency_p50),
+            latency_p95: millis(latency.latency_p95),
+            hw_latency_p50: millis(latency.hw_latency_p50),
+            hw_latency_p95: millis(latency.hw_latency_p95),
+        }
+    }
+
+    async fn handle_latency(&self) -> command::Result<response::ext::Latency> {
+        let list = self
+            .collect_work_solver_data(|idx, work_solver| async move {
+                Self::get_latency_solver(idx, work_solver).await
+            })
+            .await;
+        Ok(response::ext::Latency { list })
+    }
+
+    async fn handle_dev_details(&self) -> command::Result<response::DevDetails<DevDetailInfo>> {
+        let backend_info = self.core.get_backend_info().cloned().unwrap_or_default();
+        let model = self
+            .core
+            .get_root_hub()
+            .await
+            .map(|root_hub| root_hub.to_string())
+            .unwrap_or_default();
+        let kernel = backend_info
+            .environment
+            .get("kernel")
+            .cloned()
+            .unwrap_or_default();
+
+        let mut list = vec![];
+        for entry in self.core.get_nodes().await {
+            let idx = match entry.index {
+                Some(idx) => idx,
+                // work hubs are not devices
+                None => continue,
+            };
+            list.push(response::DevDetail {
+                idx: idx as i32,
+                name: entry.node.to_string(),
+                id: entry.node.get_id().unwrap_or(idx) as i32,
+                driver: crate::SIGNATURE.to_lowercase(),
+                kernel: kernel.clone(),
+                model: model.clone(),
+                device_path: entry.path,
+                info: DevDetailInfo {
+                    uid: entry.uid,
+                    hw_rev: backend_info.hw_rev.clone(),
+                    fw_ver: backend_info.fw_ver.clone(),
+                    dev_id: backend_info.dev_id.clone(),
+                    environment: backend_info.environment.clone(),
+                    backend_stats: Self::get_backend_stats(&entry.node).await,
+                },
+            });
+        }
+        // nodes are ordered by their uid so the devices are reordered to match the other commands
+        let order: Vec<_> = self
+            .core
+            .get_indexed_work_solvers()
+            .await
+            .into_iter()
+            .map(|(idx, _)| idx as i32)
+            .collect();
+        list.sort_by_key(|dev_detail| order.iter().position(|idx| *idx == dev_detail.idx));
+        Ok(response::DevDetails { list })
+    }
+
+    fn get_notify_device(
+        idx: usize,
+        work_solver: &Arc<dyn node::WorkSolver>,
+    ) -> response::NotifyDevice {
+        let health = work_solver
+            .work_solver_stats()
+            .device_health()
+            .take_snapshot();
+        let unix_time = |time: Option<time::SystemTime>| {
+            time.map_or(0, |time| time.get_unix_time().unwrap_or_default())
+        };
+
+        response::NotifyDevice {
+            idx: idx as i32,
+            name: work_solver.to_string(),
+            id: work_solver.get_id().unwrap_or(idx) as i32,
+            last_well: unix_time(health.last_well),
+            last_not_well: unix_time(health.last_not_well),
+            reason_not_well: match health.reason {
+                None => response::NotWellReason::None,
+                Some(stats::HealthIssue::ThreadFailInit) => response::NotWellReason::ThreadFailInit,
+                Some(stats::HealthIssue::ThreadZeroHash) => response::NotWellReason::ThreadZeroHash,
+                Some(stats::HealthIssue::ThreadFailQueue) => {
+                    response::NotWellReason::ThreadFailQueue
+                }
+                Some(stats::HealthIssue::SickIdle) => response::NotWellReason::SickIdle,
+                Some(stats::HealthIssue::DeadIdle) => response::NotWellReason::DeadIdle,
+                Some(stats::HealthIssue::NoStart) => response::NotWellReason::NoStart,
+                Some(stats::HealthIssue::OverHeat) => response::NotWellReason::OverHeat,
+                Some(stats::HealthIssue::ThermalCutoff) => response::NotWellReason::ThermalCutoff,
+                Some(stats::HealthIssue::CommsError) => response::NotWellReason::CommsError,
+                Some(stats::HealthIssue::Throttle) => response::NotWellReason::Throttle,
+            },
+            thread_fail_init: health.count(stats::HealthIssue::ThreadFailInit),
+            thread_zero_hash: health.count(stats::HealthIssue::ThreadZeroHash),
+            thread_fail_queue: health.count(stats::HealthIssue::ThreadFailQueue),
+            dev_sick_idle_60s: health.count(stats::HealthIssue::SickIdle),
+            dev_dead_idle_600s: health.count(stats::HealthIssue::DeadIdle),
+            dev_nostart: health.count(stats::HealthIssue::NoStart),
+            dev_over_heat: health.count(stats::HealthIssue::OverHeat),
+            dev_thermal_cutoff: health.count(stats::HealthIssue::ThermalCutoff),
+            dev_comms_error: health.count(stats::HealthIssue::CommsError),
+            dev_throttle: health.count(stats::HealthIssue::Throttle),
+        }
+    }
+
+    async fn handle_notify(&self) -> command::Result<response::Notify> {
+        let list = self
+            .core
+            .get_indexed_work_solvers()
+            .await
+            .iter()
+            .map(|(idx, work_solver)| Self::get_notify_device(*idx, work_solver))
+            .collect();
+        Ok(response::Notify { list })
+    }
+
+    async fn handle_block_candidates(&self) -> command::Result<response::ext::BlockCandidates> {
+        // archive times are kept in milliseconds
+        let unix_time = |millis: u64| (millis / 1000) as response::Time;
+        let list = self
+            .core
+            .get_block_archive()
+            .list()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, entry)| response::ext::BlockCandidate {
+                idx: idx as i32,
+                hash: entry.candidate.hash,
+                header: entry.candidate.header,
+                pool: entry.candidate.pool,
+                archived: unix_time(entry.candidate.archived),
+                response: match entry.response {
+                    job::archive::Response::Accepted => "accepted",
+                    job::archive::Response::Rejected => "rejected",
+                    job::archive::Response::Stale => "stale",
+                    job::archive::Response::Unknown => "unknown",
+                }
+                .to_string(),
+                response_time: entry.received.map(unix_time),
+            })
+            .collect();
+        Ok(response::ext::BlockCandidates { list })
+    }
+
+    /// The restart takes a while (e.g. hash chains have to be initialized again) so the command
+    /// is replied before it is finished. Failures are reported only to the log.
+    async fn handle_restart(&self) -> command::Result<response::ext::Restart> {
+        let core = self.core.clone();
+        let backend_commands = self.backend_commands.clone();
+        tokio::spawn(async move {
+            if let Ok(frontend_config) = core.restart(core.get_shutdown_grace_period()).await {
+                *backend_commands
+                    .lock()
+                    .expect("BUG: cannot lock backend commands") =
+                    Arc::new(frontend_config.cgminer_custom_commands.unwrap_or_default());
+            }
+        });
+        Ok(response::ext::Restart)
+    }
+
+    async fn handle_shutdown(&self) -> command::Result<response::ext::Shutdown> {
+        let core = self.core.clone();
+        tokio::spawn(async move {
+            delay_for(SHUTDOWN_DELAY).await;
+            shutdown::terminate(core).await;
+        });
+        Ok(response::ext::Shutdown)
+    }
+
+    /// Optional parameter of DIGEST command is either the number of the most recent days or
+    /// a list of filters e.g. "days=7,label=cc-a"
+    fn parse_digest_query(parameter: Option<&json::Value>) -> command::Result<digest::Query> {
+        match parameter {
+            None => Ok(Default::default()),
+            Some(value) => match value.to_i32() {
+                Some(days) if days > 0 => Ok(digest::Query {
+                    days: Some(days as usize),
+                    label: None,
+                }),
+                Some(_) => Err(response::ErrorCode::InvalidDigestDays(value.to_string()).into()),
+                None => value
+                    .as_str()
+                    .ok_or_else(|| "expected string".to_string())
+                    .and_then(|value| {
+                        digest::Query::parse(value, ii_cgminer_api::PARAMETER_DELIMITER)
+                    })
+                    .map_err(|reason| response::ErrorCode::InvalidDigestQuery(reason).into()),
+            },
+        }
+    }
+
+    fn check_digest(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_digest_query(*parameter).map(|_| ())
+    }
+
+    fn get_digest_counters(counters: &digest::Counters) -> response::ext::DigestCounters {
+        response::ext::DigestCounters {
+            accepted: counters.accepted,
+            rejected: counters.rejected,
+            stale: counters.stale,
+            accepted_difficulty: counters.accepted_difficulty,
+            rejected_difficulty: counters.rejected_difficulty,
+            unresolved: counters.unresolved,
+        }
+    }
+
+    async fn handle_digest(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::Digest> {
+        let query = Self::parse_digest_query(parameter).expect("BUG: invalid DIGEST parameter");
+        let records = match self.core.get_digest_store() {
+            Some(store) => query.execute(store),
+            // digest is disabled
+            None => vec![],
+        };
+
+        let list = records
+            .iter()
+            .enumerate()
+            .map(|(idx, record)| response::ext::DigestDay {
+                idx: idx as i32,
+                date: record.date.clone(),
+                partial: record.partial,
+                total: Self::get_digest_counters(&record.total),
+                pools: record
+                    .pools
+                    .iter()
+                    .map(|pool| response::ext::DigestPool {
+                        url: pool.id.url.clone(),
+                        user: pool.id.user.clone(),
+                        label: pool.label.clone(),
+                        counters: Self::get_digest_counters(&pool.counters),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(response::ext::Digest { list })
+    }
+
+    /// Optional parameter of NODES command is a list of filters e.g. "status=failed,limit=10"
+    fn parse_nodes_query(parameter: Option<&json::Value>) -> command::Result<query::Query> {
+        match parameter {
+            None => Ok(Default::default()),
+            Some(value) => value
+                .as_str()
+                .ok_or_else(|| "expected string".to_string())
+                .and_then(|value| query::Query::parse(value, ii_cgminer_api::PARAMETER_DELIMITER))
+                .map_err(|reason| response::ErrorCode::InvalidNodesQuery(reason).into()),
+        }
+    }
+
+    fn check_nodes(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_nodes_query(*parameter).map(|_| ())
+    }
+
+    async fn handle_nodes(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::Nodes> {
+        let query = Self::parse_nodes_query(parameter).expect("BUG: invalid NODES parameter");
+        let page = query::execute(self.core.get_nodes().await, &query).await;
+
+        let list = page
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(idx, item)| response::ext::Node {
+                idx: idx as i32,
+                uid: item.entry.uid,
+                asc: item.entry.index.map(|idx| idx as i32),
+                path: item.entry.path,
+                status: match item.status {
+                    node::Status::Ok => response::ext::NodeStatus::Ok,
+                    node::Status::Degraded => response::ext::NodeStatus::Degraded,
+                    node::Status::Failed => response::ext::NodeStatus::Failed,
+                    node::Status::Paused => response::ext::NodeStatus::Paused,
+                    node::Status::Removed => response::ext::NodeStatus::Removed,
+                },
+                mhs_1m: item.mhs_1m,
+                topology: item.topology.map(|topology| response::ext::NodeTopology {
+                    version: topology.version,
+                    chips: topology.chip_count as u32,
+                    domains: topology.domains.map(|domains| domains as u32),
+                    addressing: match topology.addressing {
+                        node::Addressing::Linear { step } => format!("linear/{}", step),
+                    },
+                }),
+            })
+            .collect();
+        Ok(response::ext::Nodes {
+            list,
+            next_cursor: page.next_cursor,
+        })
+    }
+
+    async fn handle_work_history(&self) -> command::Result<response::ext::WorkHistory> {
+        let list = self
+            .core
+            .get_work_history()
+            .records()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, record)| {
+                let mut event = response::ext::WorkEvent {
+                    idx: idx as i32,
+                    timestamp: record.timestamp,
+                    event: record.event.name().to_string(),
+                    generation: None,
+                    job_id: None,
+                    solver: None,
+                    duration: None,
+                    block: None,
+                    client: None,
+                    previous_version_mask: None,
+                    version_mask: None,
+                };
+                match record.event {
+                    work::history::Event::Broadcast { generation, job_id } => {
+                        event.generation = Some(generation);
+                        event.job_id = job_id;
+                    }
+                    work::history::Event::Invalidate => {}
+                    work::history::Event::Exhausted { solver }
+                    | work::history::Event::StarvationStart { solver } => {
+                        event.solver = Some(solver);
+                    }
+                    work::history::Event::StarvationEnd {
+                        solver,
+                        duration_ms,
+                    } => {
+                        event.solver = Some(solver);
+                        event.duration = Some(duration_ms);
+                    }
+                    work::history::Event::BlockChange { block, client } => {
+                        event.block = Some(block);
+                        event.client = Some(client);
+                    }
+                    work::history::Event::VersionMaskNarrowed {
+                        previous_mask,
+                        mask,
+                    } => {
+                        event.previous_version_mask = Some(previous_mask);
+                        event.version_mask = Some(mask);
+                    }
+                }
+                event
+            })
+            .collect();
+        Ok(response::ext::WorkHistory { list })
+    }
+
+    async fn handle_progress(&self) -> command::Result<response::ext::Progress> {
+        let progress = self.core.get_progress();
+        let list = progress
+            .snapshot()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (chain_idx, chain))| {
+                let (step, steps) = chain.phase.steps().unwrap_or_default();
+                response::ext::ChainProgress {
+                    idx: idx as i32,
+                    chain: chain_idx as i32,
+                    phase: chain.phase.name().to_string(),
+                    step: step as u32,
+                    steps: steps as u32,
+                    percent: chain.percent,
+                    last_change: chain.last_change.get_unix_time().unwrap_or_default(),
+                }
+            })
+            .collect();
+        Ok(response::ext::Progress {
+            finished: progress.is_finished(),
+            list,
+        })
+    }
+
+    /// Mandatory parameter of JOBHISTORY, SESSIONHISTORY and POOLADDRESSES commands is the index
+    /// of the pool
+    fn check_job_history(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        match parameter {
+            Some(value) if value.is_i32() => Ok(()),
+            _ => Err(response::ErrorCode::MissingPoolParameter.into()),
+        }
+    }
+
+    async fn handle_job_history(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::JobHistory> {
+        let idx = parameter
+            .expect("BUG: missing JOBHISTORY parameter")
+            .to_i32()
+            .expect("BUG: invalid JOBHISTORY parameter type");
+        let (client, _) = self.get_client(idx).await?;
+
+        let list = client
+            .stats()
+            .job_history()
+            .take_snapshot()
+            .iter()
+            .enumerate()
+            .map(|(record_idx, record)| response::ext::JobRecord {
+                idx: record_idx as i32,
+                pool: idx,
+                job_id: record.job_id,
+                previous_hash: record.previous_hash.to_string(),
+                ntime: record.time,
+                ntime_roll_limit: record.ntime_roll_limit,
+                when: record.system_time.get_unix_time().unwrap_or_default(),
+                duration: record.duration.as_secs_f64(),
+                assignments: record.assignments,
+                solutions: record.solutions,
+                submitted: record.submitted,
+                accepted: record.accepted,
+                rejected: record.rejected,
+            })
+            .collect();
+        Ok(response::ext::JobHistory { list })
+    }
+
+    async fn handle_session_history(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::SessionHistory> {
+        let idx = parameter
+            .expect("BUG: missing SESSIONHISTORY parameter")
+            .to_i32()
+            .expect("BUG: invalid SESSIONHISTORY parameter type");
+        let (client, _) = self.get_client(idx).await?;
+
+        let mut records: Vec<_> = client
+            .session_history()
+            .into_iter()
+            .map(|event| {
+                let security = event.security.as_ref();
+                let record = response::ext::SessionRecord {
+                    idx: 0,
+                    pool: idx,
+                    when: event.system_time.get_unix_time().unwrap_or_default(),
+                    outcome: event.outcome.to_string(),
+                    protocol: security.map(|security| security.protocol.to_string()),
+                    cipher: security.map(|security| security.cipher.to_string()),
+                    fingerprint: security.map(|security| security.fingerprint.clone()),
+                    valid_from: security
+                        .map(|security| security.valid_from.get_unix_time().unwrap_or_default()),
+                    expires: security.map(|security| {
+                        security.not_valid_after.get_unix_time().unwrap_or_default()
+                    }),
+                    pin_matched: security.and_then(|security| security.pin_matched),
+                    reason: None,
+                };
+                (event.system_time, record)
+            })
+            .collect();
+        // Interleave transitions of the reject breaker with the sessions in the order they
+        // happened (the sort is stable so records with the same time keep their order)
+        records.extend(client.reject_breaker_history().into_iter().map(|event| {
+            let record = response::ext::SessionRecord {
+                idx: 0,
+                pool: idx,
+                when: event.system_time.get_unix_time().unwrap_or_default(),
+                outcome: format!("breaker {}", event.transition),
+                protocol: None,
+                cipher: None,
+                fingerprint: None,
+                valid_from: None,
+                expires: None,
+                pin_matched: None,
+                reason: Some(event.reason),
+            };
+            (event.system_time, record)
+        }));
+        records.sort_by_key(|(system_time, _)| *system_time);
+
+        let list = records
+            .into_iter()
+            .enumerate()
+            .map(|(record_idx, (_, record))| response::ext::SessionRecord {
+                idx: record_idx as i32,
+                ..record
+            })
+            .collect();
+        Ok(response::ext::SessionHistory { list })
+    }
+
+    async fn handle_pool_addresses(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::PoolAddresses> {
+        let idx = parameter
+            .expect("BUG: missing POOLADDRESSES parameter")
+            .to_i32()
+            .expect("BUG: invalid POOLADDRESSES parameter type");
+        let (client, _) = self.get_client(idx).await?;
+
+        let list = client
+            .address_stats()
+            .into_iter()
+            .enumerate()
+            .map(|(address_idx, stats)| response::ext::AddressRecord {
+                idx: address_idx as i32,
+                pool: idx,
+                address: stats.address.to_string(),
+                attempts: stats.attempts,
+                failures: stats.failures,
+                last_attempt: stats.last_attempt.get_unix_time().unwrap_or_default(),
+                dead: stats.is_dead(),
+                last_error: stats.last_error,
+            })
+            .collect();
+        Ok(response::ext::PoolAddresses { list })
+    }
+
+    /// Optional parameter of QUARANTINE command in format `N,on|off` changes quarantine of
+    /// the work solver with index N
+    fn parse_quarantine(parameter: Option<&json::Value>) -> command::Result<Option<(usize, bool)>> {
+        let parameter = match parameter {
+            None => return Ok(None),
+            Some(value) => value,
+        };
+        let invalid = || response::ErrorCode::InvalidQuarantineParameter(parameter.to_string());
+        let value = parameter.as_str().ok_or_else(invalid)?;
+        let mut items = value.split(ii_cgminer_api::PARAMETER_DELIMITER);
+        let idx = items
+            .next()
+            .and_then(|idx| idx.trim().parse::<usize>().ok())
+            .ok_or_else(invalid)?;
+        let enabled = match items.next().map(|enabled| enabled.trim()) {
+            Some("on") => true,
+            Some("off") => false,
+            _ => Err(invalid())?,
+        };
+        if items.next().is_some() {
+            Err(invalid())?;
+        }
+        Ok(Some((idx, enabled)))
+    }
+
+    fn check_quarantine(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_quarantine(*parameter).map(|_| ())
+    }
+
+    fn get_quarantine_solver(
+        idx: usize,
+        work_solver: &Arc<dyn node::WorkSolver>,
+    ) -> response::ext::QuarantineSolver {
+        let quarantine = work_solver.work_solver_stats().quarantine().take_snapshot();
+
+        response::ext::QuarantineSolver {
+            idx: idx as i32,
+            id: work_solver.get_id().unwrap_or(idx) as i32,
+            name: work_solver.to_string(),
+            enabled: quarantine.enabled,
+            accepted: quarantine.valid,
+            hardware_errors: quarantine.invalid,
+            last_change: quarantine
+                .events
+                .last()
+                .map_or(0, |event| event.time.get_unix_time().unwrap_or_default()),
+        }
+    }
+
+    async fn handle_quarantine(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::Quarantine> {
+        let change = Self::parse_quarantine(parameter).expect("BUG: invalid QUARANTINE parameter");
+        if let Some((idx, enabled)) = change {
+            let work_solver = &self.get_work_solver(idx as i32).await?;
+            if work_solver
+                .work_solver_stats()
+                .quarantine()
+                .set_enabled(enabled)
+            {
+                if enabled {
+                    warn!("Work solver '{}' has been quarantined", work_solver);
+                } else {
+                    info!(
+                        "Work solver '{}' has been released from quarantine",
+                        work_solver
+                    );
+                }
+            }
+            return Ok(response::ext::Quarantine {
+                list: vec![Self::get_quarantine_solver(idx, work_solver)],
+            });
+        }
+
+        let list = self
+            .core
+            .get_indexed_work_solvers()
+            .await
+            .iter()
+            .map(|(idx, work_solver)| Self::get_quarantine_solver(*idx, work_solver))
+            .collect();
+        Ok(response::ext::Quarantine { list })
+    }
+
+    fn check_chain(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        match parameter {
+            Some(value) if value.is_i32() => Ok(()),
+            _ => Err(response::ErrorCode::MissingAscParameter.into()),
+        }
+    }
+
+    fn get_chain_pause_state(
+        idx: usize,
+        work_solver: &Arc<dyn node::WorkSolver>,
+    ) -> response::ext::ChainPauseState {
+        let pause = work_solver.work_solver_stats().pause().take_snapshot();
+
+        response::ext::ChainPauseState {
+            idx: idx as i32,
+            id: work_solver.get_id().unwrap_or(idx) as i32,
+            name: work_solver.to_string(),
+            paused: pause.paused,
+            pauses: pause.pauses,
+            paused_time: pause.duration.as_secs_f64(),
+            last_change: pause
+                .last_change
+                .map_or(0, |time| time.get_unix_time().unwrap_or_default()),
+        }
+    }
+
+    /// Pause or resume the work solver selected by the chain index in `parameter`
+    async fn set_chain_paused(
+        &self,
+        parameter: Option<&json::Value>,
+        paused: bool,
+    ) -> command::Result<response::ext::ChainPauseState> {
+        let idx = parameter
+            .expect("BUG: missing chain parameter")
+            .to_i32()
+            .expect("BUG: invalid chain parameter type");
+        let work_solver = &self.get_work_solver(idx).await?;
+
+        match work::set_paused(work_solver.as_ref(), paused).await {
+            Ok(true) if paused => info!("Work solver '{}' has been paused", work_solver),
+            Ok(true) => info!("Work solver '{}' has been resumed", work_solver),
+            Ok(false) => {}
+            Err(e) => {
+                warn!("Cannot change power of work solver '{}': {}", work_solver, e);
+                Err(response::ErrorCode::ChainPowerFailed(idx, e.to_string()))?
+            }
+        }
+        Ok(Self::get_chain_pause_state(idx as usize, work_solver))
+    }
+
+    async fn handle_chain_pause(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::ChainPause> {
+        let state = self.set_chain_paused(parameter, true).await?;
+        Ok(response::ext::ChainPause { state })
+    }
+
+    async fn handle_chain_resume(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::ChainResume> {
+        let state = self.set_chain_paused(parameter, false).await?;
+        Ok(response::ext::ChainResume { state })
+    }
+
+    /// Optional parameter of WORKAUDIT command is duration in seconds of a new audit
+    fn parse_work_audit(
+        parameter: Option<&json::Value>,
+    ) -> command::Result<Option<time::Duration>> {
+        let parameter = match parameter {
+            None => return Ok(None),
+            Some(value) => value,
+        };
+        match parameter.to_i32() {
+            Some(secs) if secs > 0 && secs as u64 <= work::audit::MAX_DURATION.as_secs() => {
+                Ok(Some(time::Duration::from_secs(secs as u64)))
+            }
+            _ => Err(response::ErrorCode::InvalidWorkAuditParameter(parameter.to_string()).into()),
+        }
+    }
+
+    fn check_work_audit(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_work_audit(*parameter).map(|_| ())
+    }
+
+    async fn handle_work_audit(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::WorkAudit> {
+        let duration = Self::parse_work_audit(parameter).expect("BUG: invalid WORKAUDIT parameter");
+        if let Some(duration) = duration {
+            self.core.start_work_audit(duration).await;
+        }
+
+        let mut list = vec![];
+        if let Some(report) = self.core.get_work_audit() {
+            for (pool, (url, row)) in report.clients.iter().zip(&report.work).enumerate() {
+                for ((uid, path), work) in report.solvers.iter().zip(row) {
+                    list.push(response::ext::WorkAuditCell {
+                        idx: list.len() as i32,
+                        pool: pool as i32,
+                        url: url.clone(),
+                        uid: *uid,
+                        path: path.clone(),
+                        work: *work,
+                        elapsed: report.elapsed.as_secs_f64(),
+                        duration: report.duration.as_secs_f64(),
+                        recording: report.recording,
+                    });
+                }
+            }
+        }
+        Ok(response::ext::WorkAudit { list })
+    }
+
+    /// Parameter of TESTPOOL command in format `N[,job]` selects the pool with index N and
+    /// optionally requests waiting for its first job
+    fn parse_test_pool(parameter: Option<&json::Value>) -> command::Result<(i32, bool)> {
+        let parameter = parameter.ok_or(response::ErrorCode::MissingPoolParameter)?;
+        if let Some(idx) = parameter.to_i32() {
+            return Ok((idx, false));
+        }
+        let invalid = || response::ErrorCode::InvalidTestPoolParameter(parameter.to_string());
+        let value = parameter.as_str().ok_or_else(invalid)?;
+        let mut items = value.split(ii_cgminer_api::PARAMETER_DELIMITER);
+        let idx = items
+            .next()
+            .and_then(|idx| idx.trim().parse::<i32>().ok())
+            .ok_or_else(invalid)?;
+        let wait_for_job = match items.next().map(|item| item.trim()) {
+            None => false,
+            Some("job") => true,
+            Some(_) => Err(invalid())?,
+        };
+        if items.next().is_some() {
+            Err(invalid())?;
+        }
+        Ok((idx, wait_for_job))
+    }
+
+    fn check_test_pool(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        Self::parse_test_pool(*parameter).map(|_| ())
+    }
+
+    /// Test connectivity of the pool with a temporary connection. The pool does not have to be
+    /// enabled and its client is not affected by the test.
+    async fn handle_test_pool(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<response::ext::PoolTest> {
+        let (idx, wait_for_job) =
+            Self::parse_test_pool(parameter).expect("BUG: invalid TESTPOOL parameter");
+        let (client, _) = self.get_client(idx).await?;
+        let descriptor = client.descriptor().await;
+
+        let options = probe::Options {
+            wait_for_job,
+            ..Default::default()
+        };
+        let report = self
+            .core
+            .get_client_manager()
+            .test_pool(&descriptor, options)
+            .await
+            .map_err(|e| response::ErrorCode::TestPoolUnavailable(idx, e.to_string()))?;
+
+        let secs = |duration: Option<time::Duration>| duration.map(|value| value.as_secs_f64());
+        Ok(response::ext::PoolTest {
+            idx,
+            url: descriptor.get_url(true, true, false),
+            success: report.is_ok(),
+            failed_stage: report
+                .failure
+                .as_ref()
+                .map(|failure| failure.stage.to_string()),
+            error: report.failure.as_ref().map(|failure| failure.reason.clone()),
+            resolve_time: secs(report.timings.resolve),
+            connect_time: secs(report.timings.connect),
+            handshake_time: secs(report.timings.handshake),
+            authorize_time: secs(report.timings.authorize),
+            address: report.server.address.map(|address| address.to_string()),
+            extra_nonce1: report.server.extra_nonce1.clone(),
+            extra_nonce2_size: report.server.extra_nonce2_size.map(|size| size as u32),
+            version_mask: report
+                .server
+                .version_mask
+                .map(|mask| format!("{:#010x}", mask)),
+            difficulty: report.server.difficulty,
+            job_id: report.job.as_ref().map(|job| job.job_id.clone()),
+            previous_hash: report.job.as_ref().map(|job| job.previous_hash.clone()),
+            ntime: report.job.as_ref().map(|job| job.ntime),
+            clean_jobs: report.job.as_ref().map(|job| job.clean_jobs),
+            job_time: report
+                .job
+                .as_ref()
+                .map(|job| job.received_after.as_secs_f64()),
+        })
+    }
+
     fn get_client_descriptor(&self, parameter: &str) -> Result<ClientDescriptor, ()> {
         let parameters: Vec<_> = parameter
             .split(ii_cgminer_api::PARAMETER_DELIMITER)
@@ -395,7 +1537,9 @@ impl Handler {
             Some(password)
         };
 
-        ClientDescriptor::create(url, &ClientUserInfo::new(user, password), true).map_err(|_| ())
+        ClientDescriptor::create(url, &ClientUserInfo::new(user, password), true)
+            .map(|descriptor| descriptor.with_network(self.core.get_client_manager().network()))
+            .map_err(|_| ())
     }
 }
 
@@ -418,7 +1562,7 @@ impl command::Handler for Handler {
     }
 
     async fn handle_summary(&self) -> command::Result<response::Summary> {
-        let frontend = self.core.frontend.clone();
+        let frontend = self.core.get_frontend().clone();
 
         let mining_stats = frontend.mining_stats();
         let work_solver_stats = frontend.work_solver_stats();
@@ -429,8 +1573,13 @@ impl command::Handler for Handler {
         let valid_backend_diff = mining_stats.valid_backend_diff().take_snapshot().await;
         let error_backend_diff = mining_stats.error_backend_diff().take_snapshot().await;
         let best_share = mining_stats.best_share().take_snapshot();
+        let dropped_stale = mining_stats.dropped_stale().take_snapshot();
+        let blocks = self.core.get_block_tracker().take_snapshot();
+        let live_jobs = self.core.get_job_registry().take_snapshot(clock::now());
+        // totals of previous runs of the miner
+        let lifetime = frontend.get_lifetime().take_snapshot();
 
-        let now = time::Instant::now();
+        let now = clock::now();
         let elapsed = now.duration_since(*mining_stats.start_time());
 
         let last_work_time =
@@ -439,13 +1588,6 @@ impl command::Handler for Handler {
         let total_mega_hashes = valid_job_diff.shares.into_mega_hashes().into_f64();
         let network_valid_solutions = valid_network_diff.solutions;
         let backend_valid_solutions = valid_backend_diff.solutions;
-        let backend_error_solutions = error_backend_diff.solutions;
-        let backend_all_solutions = backend_error_solutions + backend_valid_solutions;
-        let backend_error_ratio = if backend_all_solutions != 0 {
-            backend_error_solutions as f64 / backend_all_solutions as f64
-        } else {
-            0.0
-        } * 100.0;
         let work_utility = valid_backend_diff.shares.to_sharerate(elapsed) * 60.0;
 
         let mut pools_valid_jobs: u64 = 0;
@@ -490,12 +1632,34 @@ impl command::Handler for Handler {
             pools_accepted as f64
         } * 60.0;
 
+        // quarantined work solvers are not part of any statistics above
+        let mut quarantined = 0;
+        let mut quarantined_accepted = 0;
+        for work_solver in self.core.get_work_solvers().await {
+            let work_solver_stats = work_solver.work_solver_stats();
+            let quarantine = work_solver_stats.quarantine().take_snapshot();
+            if quarantine.enabled {
+                quarantined += 1;
+            }
+            quarantined_accepted += quarantine.valid;
+        }
+
+        let backend_error_solutions = error_backend_diff.solutions;
+        let backend_all_solutions = backend_error_solutions + backend_valid_solutions;
+        let backend_error_ratio = if backend_all_solutions != 0 {
+            backend_error_solutions as f64 / backend_all_solutions as f64
+        } else {
+            0.0
+        } * 100.0;
+
         let backend_rejected_ratio = if backend_valid_solutions != 0 {
             pools_rejected_shares as f64 / backend_valid_solutions as f64
         } else {
             0.0
         } * 100.0;
 
+        let best_share = best_share.map(|inner| *inner).unwrap_or_default();
+
         Ok(response::Summary {
             elapsed: elapsed.as_secs(),
             mhs_av: total_mega_hashes / elapsed.as_secs_f64(),
@@ -520,27 +1684,52 @@ impl command::Handler for Handler {
             rejected: pools_rejected,
             hardware_errors: backend_error_solutions as i32,
             utility: pools_utility,
-            // TODO: BOSminer does not account this information
-            discarded: 0,
+            // solutions of invalidated jobs dropped by the share policy
+            discarded: *dropped_stale as i64,
             stale: pools_stale,
             // TODO: BOSminer does not account this information
             get_failures: 0,
             local_work: *generated_work as u32,
             // TODO: BOSminer does not account this information
             remote_failures: 0,
-            // TODO: BOSminer does not account this information
-            network_blocks: 0,
+            network_blocks: blocks.changes as u32,
             total_mega_hashes,
             work_utility,
             difficulty_accepted: pools_accepted_shares,
             difficulty_rejected: pools_rejected_shares,
             difficulty_stale: pools_stale_shares,
-            best_share: best_share.map(|inner| *inner).unwrap_or_default() as u64,
+            best_share,
             device_hardware_ratio: backend_error_ratio,
             device_rejected_ratio: backend_rejected_ratio,
             pool_rejected_ratio: pools_rejected_ratio,
             pool_stale_ratio: pools_stale_ratio,
             last_getwork: last_work_time,
+            safe_mode: self
+                .core
+                .get_backend_info()
+                .map_or(false, |info| info.safe_mode),
+            quarantined,
+            quarantined_accepted,
+            network_difficulty: blocks.network_difficulty,
+            last_block_time: blocks
+                .last_change
+                .as_ref()
+                .map_or(0, |change| change.time.get_unix_time().unwrap_or_default()),
+            since_last_block: blocks
+                .since_last_change(clock::system_time())
+                .map_or(0, |duration| duration.as_secs()),
+            average_block_interval: blocks
+                .average_interval
+                .map_or(0.0, |interval| interval.as_secs_f64()),
+            live_jobs: live_jobs.live_jobs as u32,
+            oldest_job_age: live_jobs.oldest_age.map_or(0, |age| age.as_secs()),
+            leaked_jobs: live_jobs.leaked_jobs,
+            lifetime_elapsed: lifetime.elapsed + elapsed.as_secs(),
+            lifetime_accepted: lifetime.accepted + pools_accepted,
+            lifetime_rejected: lifetime.rejected + pools_rejected,
+            lifetime_difficulty_accepted: lifetime.accepted_difficulty + pools_accepted_shares,
+            lifetime_difficulty_rejected: lifetime.rejected_difficulty + pools_rejected_shares,
+            lifetime_best_share: lifetime.best_share.max(best_share),
         })
     }
 
@@ -567,13 +1756,19 @@ impl command::Handler for Handler {
             .expect("BUG: missing ENABLEPOOL parameter")
             .to_i32()
             .expect("BUG: invalid ENABLEPOOL parameter type");
-        let (client, _) = self.get_client(idx).await?;
+        let (client, clients) = self.get_client(idx).await?;
         let client_descriptor = client.descriptor().await;
         let url = client_descriptor.get_url(true, true, false);
 
-        client
-            .try_enable()
-            .map_err(|_| response::InfoCode::PoolAlreadyEnabled(idx, url.clone()))?;
+        let changed = self
+            .core
+            .get_client_manager()
+            .set_client_enabled(idx as usize, true)
+            .await
+            .map_err(|_| response::ErrorCode::InvalidPoolId(idx, clients.len() as i32 - 1))?;
+        if !changed {
+            Err(response::InfoCode::PoolAlreadyEnabled(idx, url.clone()))?;
+        }
 
         Ok(response::EnablePool {
             idx: idx as usize,
@@ -589,13 +1784,19 @@ impl command::Handler for Handler {
             .expect("BUG: missing DISABLEPOOL parameter")
             .to_i32()
             .expect("BUG: invalid DISABLEPOOL parameter type");
-        let (client, _) = self.get_client(idx).await?;
+        let (client, clients) = self.get_client(idx).await?;
         let client_descriptor = client.descriptor().await;
         let url = client_descriptor.get_url(true, true, false);
 
-        client
-            .try_disable()
-            .map_err(|_| response::InfoCode::PoolAlreadyDisabled(idx, url.clone()))?;
+        let changed = self
+            .core
+            .get_client_manager()
+            .set_client_enabled(idx as usize, false)
+            .await
+            .map_err(|_| response::ErrorCode::InvalidPoolId(idx, clients.len() as i32 - 1))?;
+        if !changed {
+            Err(response::InfoCode::PoolAlreadyDisabled(idx, url.clone()))?;
+        }
 
         Ok(response::DisablePool {
             idx: idx as usize,
@@ -616,19 +1817,12 @@ impl command::Handler for Handler {
             .get_client_descriptor(parameter)
             .map_err(|_| response::ErrorCode::InvalidAddPoolDetails(parameter.to_string()))?;
 
-        let group = self
+        let client = self
             .core
             .get_client_manager()
-            .create_or_get_default_group()
+            .add_client(client_descriptor.clone(), self.core.get_backend_info().cloned())
             .await;
-        let client = group
-            .push_client(client::Handle::new(
-                client_descriptor.clone(),
-                self.core.backend_info.clone(),
-                None,
-            ))
-            .await;
-        let clients = group.get_clients().await;
+        let clients = self.get_clients().await;
 
         // There is race for client index determination so use index out of range when the client
         // is missing after addition
@@ -652,22 +1846,19 @@ impl command::Handler for Handler {
             .to_i32()
             .expect("BUG: invalid REMOVEPOOL parameter type");
 
-        let client = match self.core.get_client_manager().get_default_group().await {
-            Some(group) => {
-                let client_len = group.len().await;
-                group
-                    .remove_client_at(idx as usize)
-                    .await
-                    .map_err(|e| match e {
-                        error::Client::Missing => {
-                            response::ErrorCode::InvalidPoolId(idx, client_len as i32 - 1)
-                        }
-                        _ => panic!("BUG: unexpected remove client error"),
-                    })?
-            }
-            None => Err(response::ErrorCode::InvalidPoolId(idx, -1))?,
-        };
+        let (client, clients) = self.get_client(idx).await?;
         let client_descriptor = client.descriptor().await;
+        self.core
+            .get_client_manager()
+            .remove_client(idx as usize)
+            .await
+            .map_err(|e| match e {
+                error::Client::LastClient => response::ErrorCode::RemoveLastPool(
+                    idx,
+                    client_descriptor.get_url(true, true, false),
+                ),
+                _ => response::ErrorCode::InvalidPoolId(idx, clients.len() as i32 - 1),
+            })?;
 
         Ok(response::RemovePool {
             idx: idx as usize,
@@ -708,31 +1899,34 @@ impl command::Handler for Handler {
     }
 
     async fn handle_stats(&self) -> command::Result<response::Stats> {
-        let asc_stats = self.collect_asc_stats(0).await;
-        let pool_stats = self.collect_pool_stats(asc_stats.len()).await;
         Ok(response::Stats {
-            asc_stats,
-            pool_stats,
+            miner_stats: Some(self.get_miner_stats().await),
+            asc_stats: self.collect_asc_stats().await,
+            pool_stats: self.collect_pool_stats().await,
         })
     }
 
     async fn handle_estats(&self) -> command::Result<response::Stats> {
         Ok(response::Stats {
-            asc_stats: self.collect_asc_stats(0).await,
+            miner_stats: None,
+            asc_stats: self.collect_asc_stats().await,
             pool_stats: vec![],
         })
     }
 
     async fn handle_coin(&self) -> command::Result<response::Coin> {
+        let current_block = self.core.get_block_tracker().take_snapshot().current_block;
         Ok(response::Coin {
             hash_method: "sha256".to_string(),
-            // TODO: get actual value from clients
-            current_block_time: 0.0,
-            // TODO: get actual value from clients
-            current_block_hash: "".to_string(),
+            current_block_time: current_block.map_or(0.0, |(_, time)| {
+                time.duration_since(time::UNIX_EPOCH)
+                    .map_or(0.0, |duration| duration.as_secs_f64())
+            }),
+            current_block_hash: current_block
+                .map(|(hash, _)| hash.to_string())
+                .unwrap_or_default(),
             lp: true,
-            // TODO: get actual value from clients
-            network_difficulty: 0.0,
+            network_difficulty: self.get_network_difficulty().await,
         })
     }
 
@@ -748,15 +1942,8 @@ impl command::Handler for Handler {
             .to_i32()
             .expect("BUG: invalid ASC parameter type");
 
-        let work_solvers = self.core.get_work_solvers().await;
-        let work_solver = work_solvers.get(idx as usize).cloned();
-
-        match work_solver {
-            Some(work_solver) => Ok(Self::get_asc_status(idx as usize, work_solver).await),
-            None => {
-                Err(response::ErrorCode::InvalidAscId(idx, work_solvers.len() as i32 - 1).into())
-            }
-        }
+        let work_solver = self.get_work_solver(idx).await?;
+        Ok(Self::get_asc_status(idx as usize, work_solver).await)
     }
 
     async fn handle_lcd(&self) -> command::Result<response::Lcd> {
@@ -778,21 +1965,368 @@ impl command::Handler for Handler {
     }
 }
 
+/// Extended commands which are implemented by the frontend itself
+fn create_extended_commands(handler: Arc<Handler>) -> command::Map {
+    let check_digest: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_digest(command, parameter));
+    let check_nodes: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_nodes(command, parameter));
+    let check_quarantine: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_quarantine(command, parameter));
+    let check_job_history: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_job_history(command, parameter));
+    let check_session_history: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_job_history(command, parameter));
+    let check_pool_addresses: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_job_history(command, parameter));
+    let check_test_pool: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_test_pool(command, parameter));
+    let check_chain_pause: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_chain(command, parameter));
+    let check_chain_resume: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_chain(command, parameter));
+    let check_work_audit: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_work_audit(command, parameter));
+
+    commands![
+        (DIFF_HISTORY: ParameterLess -> handler.handle_diff_history),
+        (DIAG: ParameterLess -> handler.handle_diag),
+        (DIGEST: Parameter(check_digest) -> handler.handle_digest),
+        (NODES: Parameter(check_nodes) -> handler.handle_nodes),
+        (WORK_HISTORY: ParameterLess -> handler.handle_work_history),
+        (QUARANTINE: Parameter(check_quarantine) -> handler.handle_quarantine, Write),
+        (PROGRESS: ParameterLess -> handler.handle_progress),
+        (JOB_HISTORY: Parameter(check_job_history) -> handler.handle_job_history),
+        (SESSION_HISTORY: Parameter(check_session_history) -> handler.handle_session_history),
+        (POOL_ADDRESSES: Parameter(check_pool_addresses) -> handler.handle_pool_addresses),
+        (TEST_POOL: Parameter(check_test_pool) -> handler.handle_test_pool, Write),
+        (CHAIN_PAUSE: Parameter(check_chain_pause) -> handler.handle_chain_pause, Write),
+        (CHAIN_RESUME: Parameter(check_chain_resume) -> handler.handle_chain_resume, Write),
+        (WORK_AUDIT: Parameter(check_work_audit) -> handler.handle_work_audit, Write),
+        (LATENCY: ParameterLess -> handler.handle_latency),
+        (BLOCK_CANDIDATES: ParameterLess -> handler.handle_block_candidates),
+        (DEVDETAILS: ParameterLess -> handler.handle_dev_details),
+        (NOTIFY: ParameterLess -> handler.handle_notify),
+        (RESTART: ParameterLess -> handler.handle_restart, Write),
+        (SHUTDOWN: ParameterLess -> handler.handle_shutdown, Write)
+    ]
+}
+
 pub async fn run(
     core: Arc<hub::Core>,
     listen_addr: SocketAddr,
+    allow_list: ii_cgminer_api::access::AllowList,
     custom_commands: Option<command::Map>,
     signature: String,
 ) {
-    let handler = Handler::new(core);
-    let command_receiver = command::Receiver::new(
-        handler,
-        signature,
-        version::STRING.to_string(),
-        custom_commands,
-    );
-
-    ii_cgminer_api::run(command_receiver, listen_addr)
+    if !allow_list.is_configured() {
+        warn!(
+            "API: 'allow' list is not configured, only clients connected from loopback can use \
+             write commands"
+        );
+    }
+    let handler = Handler::new(core).with_backend_commands(custom_commands.unwrap_or_default());
+    // Backend specific commands take precedence over the extended ones
+    let mut commands = create_extended_commands(Arc::new(handler.clone()));
+    commands.extend(handler.forward_backend_commands().into_iter());
+
+    let command_receiver =
+        command::Receiver::new(handler, signature, version::STRING.to_string(), commands);
+
+    ii_cgminer_api::run(command_receiver, listen_addr, allow_list)
         .await
         .unwrap();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::{self, HierarchyBuilder as _};
+    use crate::bus;
+    use crate::hal;
+    use crate::test_utils::TestWorkSolver;
+
+    use command::Handler as _;
+
+    fn create_handler_with_registry(registry: &Arc<backend::Registry>) -> Handler {
+        create_handler_with_backend_info(registry, None)
+    }
+
+    fn create_handler_with_backend_info(
+        registry: &Arc<backend::Registry>,
+        backend_info: Option<hal::BackendInfo>,
+    ) -> Handler {
+        let core = hub::Core::new(
+            1,
+            ii_bitcoin::Network::Mainnet,
+            registry,
+            backend_info,
+            None,
+            Default::default(),
+            None,
+        );
+        Handler::new(Arc::new(core))
+    }
+
+    fn create_handler() -> Handler {
+        create_handler_with_registry(&Arc::new(backend::Registry::new()))
+    }
+
+    async fn get_pool_urls(handler: &Handler) -> Vec<String> {
+        handler
+            .handle_pools()
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get pools"))
+            .list
+            .into_iter()
+            .map(|pool| pool.url)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_add_remove_pool() {
+        let handler = create_handler();
+        for url in &["drain://pool-a:3333", "drain://pool-b:3333"] {
+            let parameter = json::Value::String(format!("{},user,", url));
+            let add_pool = handler
+                .handle_add_pool(Some(&parameter))
+                .await
+                .unwrap_or_else(|_| panic!("BUG: cannot add pool"));
+            assert_eq!(add_pool.url, url.to_string());
+        }
+        assert_eq!(
+            get_pool_urls(&handler).await,
+            vec!["drain://pool-a:3333", "drain://pool-b:3333"]
+        );
+
+        let remove_pool = handler
+            .handle_remove_pool(Some(&json::Value::from(0)))
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot remove pool"));
+        assert_eq!(remove_pool.url, "drain://pool-a:3333");
+        assert_eq!(get_pool_urls(&handler).await, vec!["drain://pool-b:3333"]);
+
+        match handler
+            .handle_remove_pool(Some(&json::Value::from(0)))
+            .await
+        {
+            Ok(_) => panic!("BUG: the last pool has been removed"),
+            Err(e) => assert_eq!(e.msg(), "Cannot remove last pool 0:'drain://pool-b:3333'"),
+        }
+        assert_eq!(get_pool_urls(&handler).await, vec!["drain://pool-b:3333"]);
+    }
+
+    #[tokio::test]
+    async fn test_reject_breaker_trip() {
+        let handler = create_handler();
+        let parameter = json::Value::String("drain://pool-a:3333,user,".to_string());
+        handler
+            .handle_add_pool(Some(&parameter))
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot add pool"));
+        let (client, _) = handler
+            .get_client(0)
+            .await
+            .unwrap_or_else(|_| panic!("BUG: missing pool"));
+        let descriptor = client.descriptor().await;
+        client
+            .change_descriptor(descriptor.with_reject_breaker(
+                bosminer_config::ClientRejectBreaker {
+                    enabled: Some(true),
+                    threshold: Some(0.5),
+                    min_shares: Some(10),
+                    window: Some(60),
+                    cool_off: Some(300),
+                    probe_shares: Some(4),
+                },
+            ))
+            .await;
+        let mut subscriber = handler.core.get_bus().subscribe();
+
+        // the first check only takes the base of the window
+        assert!(client.check_reject_breaker().await);
+        let target = ii_bitcoin::Target::from_pool_difficulty(1);
+        for _ in 0..10 {
+            client
+                .stats()
+                .rejected()
+                .account_solution(&target, clock::now())
+                .await;
+        }
+        assert!(!client.check_reject_breaker().await);
+
+        let mut alert = None;
+        while let Some(event) = subscriber.try_recv() {
+            if let bus::Event::Alert(event_alert) = event {
+                alert = Some(event_alert);
+            }
+        }
+        assert_eq!(
+            alert,
+            Some(bus::Alert::PoolSoftFailed {
+                pool: client.display_name(),
+                reason: "10 of 10 shares rejected in 60 s".to_string(),
+            })
+        );
+
+        let pools = handler
+            .handle_pools()
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get pools"));
+        assert_eq!(pools.list[0].status, response::PoolStatus::Rejecting);
+        let session_history = handler
+            .handle_session_history(Some(&json::Value::from(0)))
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get session history"));
+        let breaker_records: Vec<_> = session_history
+            .list
+            .iter()
+            .filter(|record| record.reason.is_some())
+            .map(|record| (record.outcome.as_str(), record.reason.as_deref()))
+            .collect();
+        assert_eq!(
+            breaker_records,
+            vec![("breaker tripped", Some("10 of 10 shares rejected in 60 s"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let registry = Arc::new(backend::Registry::new());
+        let root_hub: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::new());
+        registry
+            .add_root(node::WorkSolverType::WorkHub(root_hub.clone()))
+            .await;
+        for id in &[7, 6] {
+            let work_solver = TestWorkSolver::with_id(*id)
+                .with_backend_stats(json::json!({ "Chain": id, "Chips": 63 }));
+            registry
+                .branch(
+                    root_hub.clone(),
+                    node::WorkSolverType::WorkSolver(Arc::new(work_solver)),
+                )
+                .await;
+        }
+        let handler = create_handler_with_registry(&registry);
+
+        let stats = handler
+            .handle_stats()
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get stats"));
+        assert_eq!(
+            stats.miner_stats.map(|stats| stats.header.id).as_deref(),
+            Some("BOSminer0")
+        );
+        let asc_stats: Vec<_> = stats
+            .asc_stats
+            .iter()
+            .map(|stats| {
+                (
+                    stats.header.id.as_str(),
+                    stats.backend_stats["Chain"].clone(),
+                )
+            })
+            .collect();
+        // work solvers are ordered by their id and indexed in order of registration
+        assert_eq!(
+            asc_stats,
+            vec![("ASC1", json::json!(6)), ("ASC0", json::json!(7))]
+        );
+        assert_eq!(stats.asc_stats[0].backend_stats["Chips"], json::json!(63));
+    }
+
+    #[tokio::test]
+    async fn test_notify_and_dev_details() {
+        let registry = Arc::new(backend::Registry::new());
+        let root_hub: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::new());
+        registry
+            .add_root(node::WorkSolverType::WorkHub(root_hub.clone()))
+            .await;
+        let mut work_solvers = vec![];
+        for id in &[1, 0] {
+            let work_solver: Arc<dyn node::WorkSolver> = Arc::new(
+                TestWorkSolver::with_id(*id).with_backend_stats(json::json!({ "Chips": 63 })),
+            );
+            registry
+                .branch(
+                    root_hub.clone(),
+                    node::WorkSolverType::WorkSolver(work_solver.clone()),
+                )
+                .await;
+            work_solvers.push(work_solver);
+        }
+        work_solvers[0].report_error(stats::HealthIssue::CommsError);
+        work_solvers[0].report_error(stats::HealthIssue::CommsError);
+        work_solvers[0].report_error(stats::HealthIssue::OverHeat);
+        let mut backend_info = hal::BackendInfo::default();
+        backend_info
+            .environment
+            .insert("kernel".to_string(), "4.19.0".to_string());
+        let handler = create_handler_with_backend_info(&registry, Some(backend_info));
+
+        let dev_details = handler
+            .handle_dev_details()
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get device details"))
+            .list;
+        assert_eq!(dev_details.len(), 2);
+        assert_eq!(dev_details[0].idx, 1);
+        assert_eq!(dev_details[0].name, "Test work solver 0");
+        assert_eq!(dev_details[0].id, 0);
+        assert_eq!(dev_details[0].model, "Test work solver");
+        assert_eq!(dev_details[1].name, "Test work solver 1");
+        assert_eq!(dev_details[1].info.backend_stats["Chips"], json::json!(63));
+        // environment fingerprint is part of the device details
+        assert_eq!(dev_details[0].kernel, "4.19.0");
+        assert_eq!(dev_details[0].info.environment["kernel"], "4.19.0");
+
+        let notify = handler
+            .handle_notify()
+            .await
+            .unwrap_or_else(|_| panic!("BUG: cannot get notify"))
+            .list;
+        assert_eq!(notify.len(), 2);
+        // work solver without any issue
+        assert_eq!(notify[0].name, "Test work solver 0");
+        assert_eq!(notify[0].last_not_well, 0);
+        assert_eq!(notify[0].reason_not_well, response::NotWellReason::None);
+        assert_eq!(notify[0].dev_comms_error, 0);
+        // the last reported issue is the reason
+        assert_eq!(notify[1].name, "Test work solver 1");
+        assert_ne!(notify[1].last_not_well, 0);
+        assert_eq!(notify[1].reason_not_well, response::NotWellReason::OverHeat);
+        assert_eq!(notify[1].dev_comms_error, 2);
+        assert_eq!(notify[1].dev_over_heat, 1);
+        assert_eq!(notify[1].dev_nostart, 0);
+    }
+
+    #[tokio::test]
+    async fn test_device_rejected() {
+        let work_solver: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::new());
+        let mining_stats = work_solver.mining_stats();
+        let backend_target = ii_bitcoin::Target::from_pool_difficulty(1);
+        let job_target = ii_bitcoin::Target::from_pool_difficulty(2);
+        let now = clock::now();
+        for _ in 0..8 {
+            mining_stats
+                .valid_backend_diff()
+                .account_solution(&backend_target, now)
+                .await;
+        }
+        mining_stats
+            .error_backend_diff()
+            .account_solution(&backend_target, now)
+            .await;
+        mining_stats
+            .rejected_job_diff()
+            .account_solution(&job_target, now)
+            .await;
+
+        let asc = Handler::get_asc_status(0, work_solver).await;
+        // hardware errors are taken from the solution path only once
+        assert_eq!(asc.hardware_errors, 1);
+        // rejected shares are weighted by job difficulty
+        assert_eq!(asc.difficulty_rejected, 2.0);
+        assert_eq!(asc.device_rejected_ratio, 25.0);
+    }
+}
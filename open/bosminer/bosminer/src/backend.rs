@@ -22,7 +22,10 @@
 
 //! This module contains dynamically built backend hierarchy
 
+pub mod query;
+
 use crate::node::{self, WorkSolverType};
+use crate::work;
 
 use async_trait::async_trait;
 use futures::lock::{Mutex, MutexGuard};
@@ -58,6 +61,12 @@ pub trait HierarchyBuilder: Send + Sync {
     ) {
         self.add_node(node).await;
     }
+
+    /// Detach the work solver from mining (e.g. unplugged hash board). It is only marked as
+    /// removed by default so that its statistics are kept and it is reported as dead.
+    async fn remove_work_solver(&self, work_solver: Arc<dyn node::WorkSolver>) {
+        work::set_removed(work_solver.as_ref());
+    }
 }
 
 /// This struct is intended mainly for tests to ignore backend hierarchy completely
@@ -66,55 +75,222 @@ pub struct IgnoreHierarchy;
 #[async_trait]
 impl HierarchyBuilder for IgnoreHierarchy {}
 
+/// Unique identifier of a node in the registry. Identifiers are assigned in ascending order and
+/// they are never reused so they can be used as a stable cursor for listings.
+pub type Uid = u64;
+
+/// Key which determines order of work hubs and work solvers independently of the timing of their
+/// registration. Nodes with id provided by the backend (e.g. index of hash chain connector) are
+/// ordered by it and they precede the nodes without id which are ordered by their uid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    Id(usize),
+    Uid(Uid),
+}
+
+impl SortKey {
+    fn new(node: &dyn node::WorkSolver, uid: Uid) -> Self {
+        match node.get_id() {
+            Some(id) => SortKey::Id(id),
+            None => SortKey::Uid(uid),
+        }
+    }
+}
+
+/// Registered node (work hub or work solver) with its location in the hierarchy
+#[derive(Clone)]
+pub struct Entry {
+    pub uid: Uid,
+    /// Names of all nodes from the root joined with '/'
+    pub path: String,
+    pub node: Arc<dyn node::WorkSolver>,
+    /// Index of the work solver used by CGMiner commands (`None` for work hubs)
+    pub index: Option<usize>,
+}
+
+/// All registered nodes ordered by their uid
+#[derive(Default)]
+struct Index {
+    next_uid: Uid,
+    entries: Vec<Entry>,
+}
+
+/// Registered node with its sort key. The slot is turned into a tombstone when its node is
+/// unregistered so that the indices of other nodes do not change. The tombstone is reused by
+/// a node with the same sort key (e.g. hash board plugged to the same connector again).
+struct Slot {
+    key: SortKey,
+    node: Option<Arc<dyn node::WorkSolver>>,
+}
+
+/// Nodes where index of each node is its position in the append-only list of slots. The order
+/// given by sort keys is kept in a separate view so that registration of a node with lower key
+/// never shifts indices of the already registered ones.
+#[derive(Default)]
+struct Slots {
+    slots: Vec<Slot>,
+    /// Indices of all slots ordered by their sort key (and index for the same keys)
+    sorted: Vec<usize>,
+}
+
+impl Slots {
+    /// Insert the `node` and return its index
+    fn insert(&mut self, key: SortKey, node: Arc<dyn node::WorkSolver>) -> usize {
+        assert!(
+            self.position(&node).is_none(),
+            "BUG: work solver already present in the registry"
+        );
+        if let Some(idx) = self
+            .slots
+            .iter()
+            .position(|slot| slot.key == key && slot.node.is_none())
+        {
+            self.slots[idx].node = Some(node);
+            return idx;
+        }
+        let idx = self.slots.len();
+        self.slots.push(Slot {
+            key,
+            node: Some(node),
+        });
+        // the new index is the greatest one so it follows all slots with the same key
+        let slots = &self.slots;
+        let pos = self
+            .sorted
+            .binary_search_by_key(&(key, idx), |&other| (slots[other].key, other))
+            .expect_err("BUG: slot index already present in the sorted view");
+        self.sorted.insert(pos, idx);
+        idx
+    }
+
+    fn remove(&mut self, node: &Arc<dyn node::WorkSolver>) {
+        if let Some(idx) = self.position(node) {
+            self.slots[idx].node = None;
+        }
+    }
+
+    fn position(&self, node: &Arc<dyn node::WorkSolver>) -> Option<usize> {
+        self.slots.iter().position(|slot| {
+            slot.node
+                .as_ref()
+                .map_or(false, |old| Arc::ptr_eq(old, node))
+        })
+    }
+
+    fn get(&self, idx: usize) -> Option<&Arc<dyn node::WorkSolver>> {
+        self.slots.get(idx).and_then(|slot| slot.node.as_ref())
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Iterate over registered nodes ordered by their sort key together with their indices
+    fn iter(&self) -> impl Iterator<Item = (usize, &Arc<dyn node::WorkSolver>)> {
+        self.sorted
+            .iter()
+            .filter_map(move |&idx| self.get(idx).map(|node| (idx, node)))
+    }
+}
+
 /// This structure contains list of backend nodes and is also the default hierarchy builder for the
 /// BOSminer. It collects all work solvers and work hubs (special case of solver which only routes
-/// work to its child nodes and is useful for statistics aggregation and group control)
+/// work to its child nodes and is useful for statistics aggregation and group control).
+///
+/// Work hubs and work solvers are ordered by their `SortKey` and each work solver keeps its index
+/// for its whole lifetime. Indices are assigned in order of registration so the index is not
+/// necessarily the same as position of the work solver in the ordered list.
 pub struct Registry {
     /// Special work hub which represents the whole backend
     root_hub: Mutex<Option<Arc<dyn node::WorkSolver>>>,
     /// List of all work hubs which are useful for statistics aggregation and group control
-    work_hubs: Mutex<Vec<Arc<dyn node::WorkSolver>>>,
+    work_hubs: Mutex<Slots>,
     /// List of work solvers which do real work and usually represents physical HW
-    work_solvers: Mutex<Vec<Arc<dyn node::WorkSolver>>>,
+    work_solvers: Mutex<Slots>,
+    /// Both work hubs and work solvers with their uids and paths
+    index: Mutex<Index>,
 }
 
 impl Registry {
     pub fn new() -> Self {
         Registry {
             root_hub: Mutex::new(None),
-            work_hubs: Mutex::new(vec![]),
-            work_solvers: Mutex::new(vec![]),
+            work_hubs: Mutex::new(Default::default()),
+            work_solvers: Mutex::new(Default::default()),
+            index: Mutex::new(Default::default()),
         }
     }
 
-    /// Helper method that puts a `work_solver` node into a specified `container`
-    fn push_work_solver(
-        &self,
-        container: &mut Vec<Arc<dyn node::WorkSolver>>,
-        work_solver: Arc<dyn node::WorkSolver>,
-    ) {
-        assert!(
-            container
-                .iter()
-                .find(|old| Arc::ptr_eq(old, &work_solver))
-                .is_none(),
-            "BUG: work solver already present in the registry"
-        );
-        container.push(work_solver);
-    }
-
     async fn register_root_hub(&self, root_hub: Arc<dyn node::WorkSolver>) {
         if let Some(_) = self.root_hub.lock().await.replace(root_hub) {
             panic!("BUG: root hub already present in the registry");
         }
     }
 
-    async fn register_work_hub(&self, work_hub: Arc<dyn node::WorkSolver>) {
-        self.push_work_solver(&mut *self.work_hubs.lock().await, work_hub);
+    async fn register_node(&self, node: WorkSolverType<Arc<dyn node::WorkSolver>>, path: String) {
+        let mut index = self.index.lock().await;
+        let uid = index.next_uid;
+        index.next_uid += 1;
+        let key = SortKey::new(&**node.as_ref(), uid);
+        match &node {
+            WorkSolverType::WorkHub(work_hub) => {
+                self.work_hubs.lock().await.insert(key, work_hub.clone())
+            }
+            WorkSolverType::WorkSolver(work_solver) => self
+                .work_solvers
+                .lock()
+                .await
+                .insert(key, work_solver.clone()),
+        };
+        index.entries.push(Entry {
+            uid,
+            path,
+            node: node.into_inner(),
+            index: None,
+        });
+    }
+
+    /// Remove a node (e.g. unplugged hash board) from the registry. Its children are kept and
+    /// have to be unregistered separately. Indices of other work solvers are not affected.
+    pub async fn unregister(&self, node: &Arc<dyn node::WorkSolver>) {
+        self.index
+            .lock()
+            .await
+            .entries
+            .retain(|entry| !Arc::ptr_eq(&entry.node, node));
+        self.work_hubs.lock().await.remove(node);
+        self.work_solvers.lock().await.remove(node);
+        let mut root_hub = self.root_hub.lock().await;
+        if root_hub
+            .as_ref()
+            .map_or(false, |root_hub| Arc::ptr_eq(root_hub, node))
+        {
+            root_hub.take();
+        }
     }
 
-    async fn register_work_solver(&self, work_solver: Arc<dyn node::WorkSolver>) {
-        self.push_work_solver(&mut *self.work_solvers.lock().await, work_solver);
+    /// Return all registered nodes ordered by their uid
+    pub async fn get_entries(&self) -> Vec<Entry> {
+        let index = self.index.lock().await;
+        let work_solvers = self.work_solvers.lock().await;
+        index
+            .entries
+            .iter()
+            .map(|entry| Entry {
+                index: work_solvers.position(&entry.node),
+                ..entry.clone()
+            })
+            .collect()
+    }
+
+    async fn get_path(&self, node: &Arc<dyn node::WorkSolver>) -> Option<String> {
+        self.index
+            .lock()
+            .await
+            .entries
+            .iter()
+            .find(|entry| Arc::ptr_eq(&entry.node, node))
+            .map(|entry| entry.path.clone())
     }
 
     #[inline]
@@ -122,25 +298,58 @@ impl Registry {
         self.root_hub.lock().await
     }
 
-    #[inline]
-    pub async fn lock_work_hubs<'a>(&'a self) -> MutexGuard<'a, Vec<Arc<dyn node::WorkSolver>>> {
-        self.work_hubs.lock().await
+    /// Return all work hubs ordered by their sort key
+    pub async fn get_work_hubs(&self) -> Vec<Arc<dyn node::WorkSolver>> {
+        self.work_hubs
+            .lock()
+            .await
+            .iter()
+            .map(|(_, work_hub)| work_hub.clone())
+            .collect()
     }
 
-    #[inline]
-    pub async fn lock_work_solvers<'a>(&'a self) -> MutexGuard<'a, Vec<Arc<dyn node::WorkSolver>>> {
-        self.work_solvers.lock().await
+    /// Return all work solvers ordered by their sort key
+    pub async fn get_work_solvers(&self) -> Vec<Arc<dyn node::WorkSolver>> {
+        self.get_indexed_work_solvers()
+            .await
+            .into_iter()
+            .map(|(_, work_solver)| work_solver)
+            .collect()
+    }
+
+    /// Return all work solvers ordered by their sort key together with their stable indices
+    pub async fn get_indexed_work_solvers(&self) -> Vec<(usize, Arc<dyn node::WorkSolver>)> {
+        self.work_solvers
+            .lock()
+            .await
+            .iter()
+            .map(|(idx, work_solver)| (idx, work_solver.clone()))
+            .collect()
+    }
+
+    /// Return work solver with the stable index `idx`
+    pub async fn get_work_solver(&self, idx: usize) -> Option<Arc<dyn node::WorkSolver>> {
+        self.work_solvers.lock().await.get(idx).cloned()
+    }
+
+    /// Number of indices which have been assigned to work solvers including the unregistered ones
+    pub async fn get_work_solver_index_count(&self) -> usize {
+        self.work_solvers.lock().await.len()
     }
 }
 
 #[async_trait]
 impl HierarchyBuilder for Registry {
     async fn add_work_hub(&self, work_hub: Arc<dyn node::WorkSolver>) {
-        self.register_work_hub(work_hub).await;
+        let path = work_hub.to_string();
+        self.register_node(WorkSolverType::WorkHub(work_hub), path)
+            .await;
     }
 
     async fn add_work_solver(&self, work_solver: Arc<dyn node::WorkSolver>) {
-        self.register_work_solver(work_solver).await;
+        let path = work_solver.to_string();
+        self.register_node(WorkSolverType::WorkSolver(work_solver), path)
+            .await;
     }
 
     async fn add_root(&self, node: WorkSolverType<Arc<dyn node::WorkSolver>>) {
@@ -149,4 +358,219 @@ impl HierarchyBuilder for Registry {
         // and add its actual type (work hub/solver)
         self.add_node(node).await;
     }
+
+    async fn branch(
+        &self,
+        parent_work_hub: Arc<dyn node::WorkSolver>,
+        node: WorkSolverType<Arc<dyn node::WorkSolver>>,
+    ) {
+        let name = node.as_ref().to_string();
+        let path = match self.get_path(&parent_work_hub).await {
+            Some(parent_path) => format!("{}/{}", parent_path, name),
+            None => name,
+        };
+        self.register_node(node, path).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::TestWorkSolver;
+
+    use ii_async_compat::tokio;
+
+    const ROOT_ID: usize = 1000;
+
+    async fn build_registry(ids: &[usize]) -> (Registry, Arc<dyn node::WorkSolver>) {
+        let registry = Registry::new();
+        let root_hub: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::with_id(ROOT_ID));
+        registry
+            .add_root(WorkSolverType::WorkHub(root_hub.clone()))
+            .await;
+        for id in ids {
+            add_work_solver(&registry, &root_hub, Some(*id)).await;
+        }
+        (registry, root_hub)
+    }
+
+    async fn add_work_solver(
+        registry: &Registry,
+        root_hub: &Arc<dyn node::WorkSolver>,
+        id: Option<usize>,
+    ) -> Arc<dyn node::WorkSolver> {
+        let work_solver: Arc<dyn node::WorkSolver> = Arc::new(match id {
+            Some(id) => TestWorkSolver::with_id(id),
+            None => TestWorkSolver::new(),
+        });
+        registry
+            .branch(
+                root_hub.clone(),
+                WorkSolverType::WorkSolver(work_solver.clone()),
+            )
+            .await;
+        work_solver
+    }
+
+    async fn get_ids(registry: &Registry) -> Vec<(usize, Option<usize>)> {
+        registry
+            .get_indexed_work_solvers()
+            .await
+            .iter()
+            .map(|(idx, work_solver)| (*idx, work_solver.get_id()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_registration_order() {
+        for ids in [[6, 7, 8], [8, 6, 7], [7, 8, 6], [8, 7, 6]].iter() {
+            let (registry, root_hub) = build_registry(&ids[..]).await;
+            // work solvers are ordered by their id and indexed in order of registration
+            let index = |id| ids.iter().position(|other| *other == id);
+            let mut expected: Vec<_> = [6, 7, 8]
+                .iter()
+                .map(|id| (index(*id).unwrap(), Some(*id)))
+                .collect();
+            assert_eq!(get_ids(&registry).await, expected);
+
+            // work solvers without id follow the ones with id in order of registration
+            add_work_solver(&registry, &root_hub, None).await;
+            add_work_solver(&registry, &root_hub, None).await;
+            add_work_solver(&registry, &root_hub, Some(9)).await;
+            expected.extend(vec![(5, Some(9)), (3, None), (4, None)]);
+            assert_eq!(get_ids(&registry).await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stable_index() {
+        let (registry, root_hub) = build_registry(&[6, 7, 8]).await;
+        let work_solvers = registry.get_work_solvers().await;
+
+        // removal of a work solver does not shift the following ones
+        registry.unregister(&work_solvers[1]).await;
+        assert_eq!(get_ids(&registry).await, vec![(0, Some(6)), (2, Some(8))]);
+        assert!(registry.get_work_solver(1).await.is_none());
+        assert_eq!(registry.get_work_solver_index_count().await, 3);
+        assert!(Arc::ptr_eq(
+            &registry.get_work_solver(2).await.unwrap(),
+            &work_solvers[2]
+        ));
+
+        // a new work solver does not take the index of a different one
+        add_work_solver(&registry, &root_hub, None).await;
+        assert_eq!(
+            get_ids(&registry).await,
+            vec![(0, Some(6)), (2, Some(8)), (3, None)]
+        );
+
+        // a work solver with the same id gets its old index back
+        registry.unregister(&work_solvers[0]).await;
+        let replugged = add_work_solver(&registry, &root_hub, Some(7)).await;
+        assert_eq!(
+            get_ids(&registry).await,
+            vec![(1, Some(7)), (2, Some(8)), (3, None)]
+        );
+        add_work_solver(&registry, &root_hub, Some(6)).await;
+        assert_eq!(
+            get_ids(&registry).await,
+            vec![(0, Some(6)), (1, Some(7)), (2, Some(8)), (3, None)]
+        );
+
+        // the index is reported for work solvers in the listing ordered by uid
+        let indices: Vec<_> = registry
+            .get_entries()
+            .await
+            .iter()
+            .map(|entry| (entry.node.get_id(), entry.index))
+            .collect();
+        assert_eq!(
+            indices,
+            vec![
+                (Some(ROOT_ID), None),
+                (Some(8), Some(2)),
+                (None, Some(3)),
+                (Some(7), Some(1)),
+                (Some(6), Some(0)),
+            ]
+        );
+        assert!(Arc::ptr_eq(
+            &registry.get_work_solver(1).await.unwrap(),
+            &replugged
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_index_churn() {
+        let (registry, root_hub) = build_registry(&[]).await;
+        let mut registered: Vec<(usize, Arc<dyn node::WorkSolver>)> = vec![];
+        // hash boards are unplugged and plugged again in varying order together with work
+        // solvers without id which always get a new index
+        let rounds: &[(&[usize], &[usize])] = &[
+            (&[], &[5, 3, 9]),
+            (&[3], &[1, 3]),
+            (&[9, 1], &[0, 9]),
+            (&[5, 0, 3], &[2, 5, 3, 0]),
+            (&[9, 2], &[9, 1, 2]),
+        ];
+        for (unplugged, plugged) in rounds {
+            for id in unplugged.iter() {
+                let pos = registered
+                    .iter()
+                    .position(|(_, work_solver)| work_solver.get_id() == Some(*id))
+                    .unwrap();
+                let (_, work_solver) = registered.remove(pos);
+                registry.unregister(&work_solver).await;
+            }
+            for id in plugged.iter() {
+                let present = registry
+                    .get_entries()
+                    .await
+                    .iter()
+                    .any(|entry| entry.node.get_id() == Some(*id));
+                assert!(!present, "BUG: work solver {} has not been unplugged", id);
+                add_work_solver(&registry, &root_hub, Some(*id)).await;
+                add_work_solver(&registry, &root_hub, None).await;
+                // indices of work solvers which have not been unplugged are kept
+                for (idx, work_solver) in registered.iter() {
+                    assert!(Arc::ptr_eq(
+                        &registry.get_work_solver(*idx).await.unwrap(),
+                        work_solver
+                    ));
+                }
+                registered = registry.get_indexed_work_solvers().await;
+            }
+
+            // listing is ordered by the sort key and each index is used only once
+            let ids = get_ids(&registry).await;
+            let keys: Vec<_> = ids.iter().filter_map(|(_, id)| *id).collect();
+            let mut sorted_keys = keys.clone();
+            sorted_keys.sort();
+            assert_eq!(keys, sorted_keys);
+            assert!(ids[keys.len()..].iter().all(|(_, id)| id.is_none()));
+            let mut indices: Vec<_> = ids.iter().map(|(idx, _)| *idx).collect();
+            indices.sort();
+            indices.dedup();
+            assert_eq!(indices.len(), ids.len());
+        }
+
+        // hash boards plugged to the same connector get their first index back
+        let indices: Vec<_> = get_ids(&registry)
+            .await
+            .into_iter()
+            .filter(|(_, id)| id.is_some())
+            .collect();
+        assert_eq!(
+            indices,
+            vec![
+                (9, Some(0)),
+                (6, Some(1)),
+                (12, Some(2)),
+                (2, Some(3)),
+                (0, Some(5)),
+                (4, Some(9))
+            ]
+        );
+        assert_eq!(registry.get_work_solver_index_count().await, 20);
+    }
 }
@@ -0,0 +1,507 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Filtering and pagination of registered nodes for listings of large hierarchies.
+//!
+//! Pages are addressed with a cursor which is the uid of the last node from the previous page.
+//! Because uids are never reused and new nodes always get greater uid, nodes registered or
+//! removed between two requests do not cause skipping or duplication of other nodes.
+
+use super::{Entry, Uid};
+
+use crate::clock;
+use crate::node::{self, Stats as _};
+use crate::stats;
+
+use std::time;
+
+/// Maximal number of nodes returned in one page when the limit is not specified
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Delimiter of key/value pair in the query string
+const VALUE_DELIMITER: char = '=';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub status: Option<node::Status>,
+    /// Glob pattern (`*` and `?` wildcards) matched against the whole node path
+    pub path: Option<String>,
+    /// Minimal hashrate in MH/s measured for the last minute
+    pub min_mhs: Option<f64>,
+    /// Return only nodes following the one with this uid
+    pub cursor: Option<Uid>,
+    pub limit: usize,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self {
+            status: None,
+            path: None,
+            min_mhs: None,
+            cursor: None,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl Query {
+    fn parse_status(value: &str) -> Option<node::Status> {
+        match value.to_ascii_lowercase().as_str() {
+            "ok" => Some(node::Status::Ok),
+            "degraded" => Some(node::Status::Degraded),
+            "failed" => Some(node::Status::Failed),
+            "paused" => Some(node::Status::Paused),
+            "removed" => Some(node::Status::Removed),
+            _ => None,
+        }
+    }
+
+    /// Parse query from `key=value` pairs separated with `delimiter` e.g.
+    /// "status=failed,path=*/Hash Chain ?,min_mhs=1000,cursor=12,limit=20"
+    pub fn parse(parameter: &str, delimiter: char) -> Result<Self, String> {
+        let mut query = Self::default();
+
+        for item in parameter.split(delimiter).filter(|item| !item.is_empty()) {
+            let mut pair = item.splitn(2, VALUE_DELIMITER);
+            let key = pair.next().expect("BUG: missing key").trim();
+            let value = pair
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", key))?;
+            let invalid_value = || format!("invalid value '{}' for '{}'", value, key);
+
+            match key {
+                "status" => {
+                    query.status = Some(Self::parse_status(value).ok_or_else(invalid_value)?)
+                }
+                "path" => query.path = Some(value.to_string()),
+                "min_mhs" => match value.parse::<f64>() {
+                    Ok(min_mhs) if min_mhs >= 0.0 => query.min_mhs = Some(min_mhs),
+                    _ => Err(invalid_value())?,
+                },
+                "cursor" => query.cursor = Some(value.parse().map_err(|_| invalid_value())?),
+                "limit" => match value.parse::<usize>() {
+                    Ok(limit) if limit > 0 => query.limit = limit,
+                    _ => Err(invalid_value())?,
+                },
+                _ => Err(format!("unknown filter '{}'", key))?,
+            }
+        }
+        Ok(query)
+    }
+}
+
+/// Node which passed all filters together with the values it was filtered by
+#[derive(Clone)]
+pub struct Item {
+    pub entry: Entry,
+    pub status: node::Status,
+    pub mhs_1m: f64,
+    pub topology: Option<node::Topology>,
+}
+
+pub struct Page {
+    pub items: Vec<Item>,
+    /// Cursor for the next page when there are more matching nodes
+    pub next_cursor: Option<Uid>,
+}
+
+/// Match `text` against glob `pattern` where `*` matches any sequence of characters (including
+/// path delimiter) and `?` matches exactly one character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // position of the last star in the pattern and text position it has been matched at
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // let the last star consume one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+async fn get_mhs_1m(entry: &Entry, now: time::Instant) -> f64 {
+    entry
+        .node
+        .mining_stats()
+        .valid_backend_diff()
+        .take_snapshot()
+        .await
+        .to_mega_hashes(*stats::TIME_MEAN_INTERVAL_1M, now)
+        .into_f64()
+}
+
+/// Select one page of nodes matching the `query` from `entries` ordered by uid
+pub async fn execute(entries: Vec<Entry>, query: &Query) -> Page {
+    let now = clock::now();
+    let mut items: Vec<Item> = vec![];
+
+    for entry in entries
+        .into_iter()
+        .filter(|entry| query.cursor.map_or(true, |cursor| entry.uid > cursor))
+    {
+        // cheap filters go first
+        if let Some(pattern) = query.path.as_ref() {
+            if !glob_match(pattern, &entry.path) {
+                continue;
+            }
+        }
+        let status = entry.node.get_mining_status().await;
+        if query.status.map_or(false, |expected| expected != status) {
+            continue;
+        }
+        let mhs_1m = get_mhs_1m(&entry, now).await;
+        if query.min_mhs.map_or(false, |min_mhs| mhs_1m < min_mhs) {
+            continue;
+        }
+
+        if items.len() == query.limit {
+            // there is at least one more matching node
+            return Page {
+                next_cursor: items.last().map(|item| item.entry.uid),
+                items,
+            };
+        }
+        let topology = entry.node.topology().await;
+        items.push(Item {
+            entry,
+            status,
+            mhs_1m,
+            topology,
+        });
+    }
+
+    Page {
+        items,
+        next_cursor: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::{HierarchyBuilder, Registry};
+    use crate::node::WorkSolverType;
+    use crate::test_utils::TestWorkSolver;
+
+    use ii_async_compat::tokio;
+
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    const SOLVER_COUNT: usize = 50;
+    const ROOT_ID: usize = 1000;
+
+    /// Build hierarchy with root hub and `SOLVER_COUNT` work solvers where each solver has
+    /// status and hashrate derived from its id
+    async fn build_registry() -> (Registry, Vec<Arc<TestWorkSolver>>) {
+        // the hashrate depends on the time elapsed since the solutions have been accounted
+        tokio::time::pause();
+        let registry = Registry::new();
+        let root_hub: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::with_id(ROOT_ID));
+        registry
+            .add_root(WorkSolverType::WorkHub(root_hub.clone()))
+            .await;
+
+        let mut solvers = vec![];
+        for id in 0..SOLVER_COUNT {
+            let solver = Arc::new(TestWorkSolver::with_id(id));
+            solver.set_status(match id % 5 {
+                0 => node::Status::Failed,
+                1 => node::Status::Degraded,
+                _ => node::Status::Ok,
+            });
+            // one share with difficulty 1 corresponds to ~42 MH/s one minute later
+            for _ in 0..id % 3 {
+                solver
+                    .mining_stats()
+                    .valid_backend_diff()
+                    .account_solution(&Default::default(), clock::now())
+                    .await;
+            }
+            registry
+                .branch(root_hub.clone(), WorkSolverType::WorkSolver(solver.clone()))
+                .await;
+            solvers.push(solver);
+        }
+        tokio::time::advance(time::Duration::from_secs(60)).await;
+        (registry, solvers)
+    }
+
+    async fn query_ids(registry: &Registry, parameter: &str) -> Vec<usize> {
+        let query = Query::parse(parameter, ',').expect("BUG: invalid query");
+        let page = execute(registry.get_entries().await, &query).await;
+        assert_eq!(page.next_cursor, None);
+        page.items
+            .iter()
+            .map(|item| item.entry.node.get_id().expect("BUG: missing id"))
+            .collect()
+    }
+
+    /// Read all pages of nodes with given page size
+    async fn collect_pages(registry: &Registry, limit: usize) -> Vec<Uid> {
+        let mut query = Query {
+            limit,
+            ..Default::default()
+        };
+        let mut uids = vec![];
+        loop {
+            let page = execute(registry.get_entries().await, &query).await;
+            assert!(page.items.len() <= limit);
+            uids.extend(page.items.iter().map(|item| item.entry.uid));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        uids
+    }
+
+    #[tokio::test]
+    async fn test_topology() {
+        let registry = Registry::new();
+        let root_hub: Arc<dyn node::WorkSolver> = Arc::new(TestWorkSolver::with_id(ROOT_ID));
+        registry
+            .add_root(WorkSolverType::WorkHub(root_hub.clone()))
+            .await;
+        let topology =
+            node::Topology::new(63, node::Addressing::Linear { step: 4 }).with_domains(21);
+        let solver = Arc::new(TestWorkSolver::with_id(0).with_topology(topology.clone()));
+        registry
+            .branch(root_hub, WorkSolverType::WorkSolver(solver))
+            .await;
+
+        let page = execute(registry.get_entries().await, &Default::default()).await;
+        assert_eq!(page.items.len(), 2);
+        // work hub without chips does not report any topology
+        assert!(page.items[0].topology.is_none());
+        let item = &page.items[1];
+        assert_eq!(item.topology, Some(topology));
+
+        assert_eq!(
+            serde_json::to_value(&item.topology).expect("BUG: cannot serialize topology"),
+            serde_json::json!({
+                "version": node::Topology::VERSION,
+                "chip_count": 63,
+                "domains": 21,
+                "addressing": { "linear": { "step": 4 } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "Backend/Hash Chain 6"));
+        assert!(glob_match("Backend/*", "Backend/Hash Chain 6"));
+        assert!(glob_match("*Chain ?", "Backend/Hash Chain 6"));
+        assert!(glob_match("*/*/*", "a/b/c"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("*Chain ?", "Backend/Hash Chain 10"));
+        assert!(!glob_match("Backend", "Backend/Hash Chain 6"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn test_query_parse() {
+        assert_eq!(Query::parse("", ','), Ok(Default::default()));
+        assert_eq!(
+            Query::parse(
+                "status=Failed,path=*/Hash Chain ?,min_mhs=10.5,cursor=3,limit=7",
+                ','
+            ),
+            Ok(Query {
+                status: Some(node::Status::Failed),
+                path: Some("*/Hash Chain ?".to_string()),
+                min_mhs: Some(10.5),
+                cursor: Some(3),
+                limit: 7,
+            })
+        );
+
+        assert!(Query::parse("status", ',').is_err());
+        assert!(Query::parse("status=broken", ',').is_err());
+        assert!(Query::parse("min_mhs=-1", ',').is_err());
+        assert!(Query::parse("cursor=x", ',').is_err());
+        assert!(Query::parse("limit=0", ',').is_err());
+        assert!(Query::parse("color=red", ',').is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        let (registry, _solvers) = build_registry().await;
+
+        let all: Vec<usize> = (0..SOLVER_COUNT).collect();
+        let mut expected = vec![ROOT_ID];
+        expected.extend(all.iter());
+        assert_eq!(query_ids(&registry, "limit=100").await, expected);
+
+        assert_eq!(
+            query_ids(&registry, "status=failed").await,
+            all.iter()
+                .cloned()
+                .filter(|id| id % 5 == 0)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            query_ids(&registry, "status=degraded").await,
+            all.iter()
+                .cloned()
+                .filter(|id| id % 5 == 1)
+                .collect::<Vec<_>>()
+        );
+        // the root hub has no status of its own and is reported as ok
+        assert_eq!(query_ids(&registry, "status=ok").await.len(), 31);
+
+        assert_eq!(
+            query_ids(&registry, "path=*/Test work solver 1?").await,
+            (10..20).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            query_ids(&registry, "path=Test work solver 1000").await,
+            vec![ROOT_ID]
+        );
+        assert_eq!(
+            query_ids(&registry, "path=Test work solver 1").await,
+            Vec::<usize>::new()
+        );
+
+        assert_eq!(
+            query_ids(&registry, "min_mhs=60").await,
+            all.iter()
+                .cloned()
+                .filter(|id| id % 3 == 2)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            query_ids(&registry, "status=ok,path=*solver 2?,min_mhs=1").await,
+            vec![22, 23, 24, 27, 28, 29]
+                .into_iter()
+                .filter(|id| id % 3 != 0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let (registry, _solvers) = build_registry().await;
+        let all_uids: Vec<Uid> = registry
+            .get_entries()
+            .await
+            .iter()
+            .map(|entry| entry.uid)
+            .collect();
+
+        for limit in vec![1, 7, 10, 51, 100] {
+            assert_eq!(collect_pages(&registry, limit).await, all_uids);
+        }
+
+        // filters are applied before pagination
+        let query = Query::parse("status=failed,limit=4", ',').unwrap();
+        let page = execute(registry.get_entries().await, &query).await;
+        assert_eq!(page.items.len(), 4);
+        let query = Query {
+            cursor: page.next_cursor,
+            ..query
+        };
+        let page = execute(registry.get_entries().await, &query).await;
+        let ids: Vec<_> = page
+            .items
+            .iter()
+            .map(|item| item.entry.node.get_id().unwrap())
+            .collect();
+        assert_eq!(ids, vec![20, 25, 30, 35]);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_stability() {
+        let (registry, _solvers) = build_registry().await;
+
+        let mut query = Query {
+            limit: 5,
+            ..Default::default()
+        };
+        let mut listed = vec![];
+        let mut removed_unseen = vec![];
+        let mut added = vec![];
+        loop {
+            let page = execute(registry.get_entries().await, &query).await;
+            listed.extend(page.items.iter().map(|item| item.entry.uid));
+            let cursor = match page.next_cursor {
+                Some(cursor) => cursor,
+                None => break,
+            };
+            query.cursor = Some(cursor);
+
+            // simulate churn between requests: remove one node which has been already listed,
+            // one which has not been listed yet and register a new one
+            registry.unregister(&page.items[0].entry.node).await;
+            if let Some(unseen) = registry
+                .get_entries()
+                .await
+                .into_iter()
+                .rev()
+                .find(|entry| entry.uid > cursor && !added.contains(&entry.uid))
+            {
+                registry.unregister(&unseen.node).await;
+                removed_unseen.push(unseen.uid);
+            }
+            let new_solver = Arc::new(TestWorkSolver::with_id(SOLVER_COUNT + added.len()));
+            registry.add_work_solver(new_solver).await;
+            added.push(registry.get_entries().await.last().unwrap().uid);
+        }
+
+        // every node is listed at most once and in the order of uids
+        assert!(listed.windows(2).all(|pair| pair[0] < pair[1]));
+        // nodes present during the whole listing and new nodes have not been skipped
+        let listed: HashSet<_> = listed.into_iter().collect();
+        for entry in registry.get_entries().await {
+            assert!(listed.contains(&entry.uid));
+        }
+        assert!(!added.is_empty());
+        assert!(added.iter().all(|uid| listed.contains(uid)));
+        // and nodes removed before reaching them are missing
+        assert!(!removed_unseen.is_empty());
+        assert!(removed_unseen.iter().all(|uid| !listed.contains(uid)));
+        // new node is registered without parent
+        assert!(registry
+            .get_entries()
+            .await
+            .iter()
+            .any(|entry| entry.path == format!("Test work solver {}", SOLVER_COUNT)));
+    }
+}
@@ -0,0 +1,569 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Synthetic benchmark of the frontend work pipeline which does not need any mining hardware nor
+//! pool connection. The pipeline is built from the same parts as in the full miner:
+//! job sender -> work engine -> work generator -> solution sender -> solution receiver.
+//! The backend is replaced with synthetic producer which turns each generated assignment into
+//! one solution and the client is replaced with consumer which only verifies received solutions.
+//! All stages run in dedicated threads so CPU time can be measured for each one separately.
+
+use crate::backend;
+use crate::hal;
+use crate::job;
+use crate::node::WorkSolverStats as _;
+use crate::test_utils;
+use crate::work;
+
+use bosminer_config::clap;
+
+use ii_bitcoin::MeetsTarget as _;
+
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use ii_async_compat::futures;
+
+use once_cell::sync::OnceCell;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc as std_mpsc, Arc, Weak};
+use std::thread;
+use std::time;
+
+/// Allocation counting is active only during measurement
+static ALLOCATION_COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Global allocator which is able to count allocations made during benchmark. It has to be
+/// registered in the binary with `#[global_allocator]` otherwise the allocations are not reported.
+/// When the benchmark is not running, the only overhead is one relaxed atomic load.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if ALLOCATION_COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if ALLOCATION_COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if ALLOCATION_COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// CPU time consumed by the calling thread
+#[cfg(unix)]
+fn thread_cpu_time() -> time::Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // the call cannot fail for the current thread clock and valid pointer
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time);
+    }
+    time::Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+/// CPU time of threads is not measured on platforms without POSIX clocks
+#[cfg(not(unix))]
+fn thread_cpu_time() -> time::Duration {
+    Default::default()
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// How long the pipeline is measured
+    pub duration: time::Duration,
+    /// Number of midstates in each generated assignment
+    pub midstate_count: usize,
+    /// The producer computes double hash of each solution before submission to include the cost
+    /// of solution verification done by real backends (otherwise the hash is computed only by
+    /// the consumer)
+    pub compute_hashes: bool,
+}
+
+impl Config {
+    pub const DEFAULT_DURATION: time::Duration = time::Duration::from_secs(10);
+    pub const DEFAULT_MIDSTATE_COUNT: usize = 4;
+
+    /// Parse arguments of `bench` sub-command
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let duration = match matches.value_of("duration") {
+            Some(value) => time::Duration::from_secs(
+                value
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid duration '{}': {}", value, e))?,
+            ),
+            None => Self::DEFAULT_DURATION,
+        };
+        if duration.as_secs() == 0 {
+            Err("duration has to be at least 1 second")?
+        }
+
+        let midstate_count = match matches.value_of("midstates") {
+            Some(value) => value
+                .parse::<usize>()
+                .map_err(|e| format!("invalid midstate count '{}': {}", value, e))?,
+            None => Self::DEFAULT_MIDSTATE_COUNT,
+        };
+        // version rolling requires the midstate count to divide the whole BIP320 range
+        if !midstate_count.is_power_of_two()
+            || midstate_count > ii_bitcoin::BIP320_VERSION_MAX as usize
+        {
+            Err(format!(
+                "midstate count {} is not a power of two",
+                midstate_count
+            ))?
+        }
+
+        Ok(Self {
+            duration,
+            midstate_count,
+            compute_hashes: matches.is_present("hash"),
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            duration: Self::DEFAULT_DURATION,
+            midstate_count: Self::DEFAULT_MIDSTATE_COUNT,
+            compute_hashes: false,
+        }
+    }
+}
+
+/// Results of the benchmark
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub config: Config,
+    /// Real duration of the measurement
+    pub elapsed: time::Duration,
+    /// Number of assignments taken from work generator
+    pub assignments: u64,
+    /// Number of solutions submitted by the synthetic producer
+    pub solutions_produced: u64,
+    /// Number of solutions verified and accounted by the consumer
+    pub solutions_routed: u64,
+    /// CPU time of the work generator stage
+    pub generator_cpu_time: time::Duration,
+    /// CPU time of the synthetic producer (backend) stage
+    pub producer_cpu_time: time::Duration,
+    /// CPU time of the solution receiver (client) stage
+    pub consumer_cpu_time: time::Duration,
+    /// Number of heap allocations during the measurement or `None` when `CountingAllocator` is not
+    /// registered as the global allocator
+    pub allocations: Option<u64>,
+}
+
+impl Report {
+    #[inline]
+    fn per_sec(&self, count: u64) -> f64 {
+        count as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn assignments_per_sec(&self) -> f64 {
+        self.per_sec(self.assignments)
+    }
+
+    pub fn solutions_per_sec(&self) -> f64 {
+        self.per_sec(self.solutions_routed)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "duration": self.elapsed.as_secs_f64(),
+            "midstate_count": self.config.midstate_count,
+            "compute_hashes": self.config.compute_hashes,
+            "assignments": self.assignments,
+            "assignments_per_sec": self.assignments_per_sec(),
+            "solutions_produced": self.solutions_produced,
+            "solutions_routed": self.solutions_routed,
+            "solutions_per_sec": self.solutions_per_sec(),
+            "cpu_time": {
+                "generator": self.generator_cpu_time.as_secs_f64(),
+                "producer": self.producer_cpu_time.as_secs_f64(),
+                "consumer": self.consumer_cpu_time.as_secs_f64(),
+            },
+            "allocations": self.allocations,
+        })
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "duration:            {:.3} s (midstates={}, hashes={})",
+            self.elapsed.as_secs_f64(),
+            self.config.midstate_count,
+            self.config.compute_hashes
+        )?;
+        writeln!(
+            f,
+            "assignments:         {} ({:.0}/s)",
+            self.assignments,
+            self.assignments_per_sec()
+        )?;
+        writeln!(
+            f,
+            "solutions routed:    {} ({:.0}/s, {} produced)",
+            self.solutions_routed,
+            self.solutions_per_sec(),
+            self.solutions_produced
+        )?;
+        writeln!(
+            f,
+            "cpu time generator:  {:.3} s",
+            self.generator_cpu_time.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "cpu time producer:   {:.3} s",
+            self.producer_cpu_time.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "cpu time consumer:   {:.3} s",
+            self.consumer_cpu_time.as_secs_f64()
+        )?;
+        match self.allocations {
+            Some(allocations) => write!(
+                f,
+                "allocations:         {} ({:.1}/assignment)",
+                allocations,
+                allocations as f64 / self.assignments.max(1) as f64
+            ),
+            None => write!(f, "allocations:         n/a"),
+        }
+    }
+}
+
+/// Synthetic solution which meets any backend target
+#[derive(Debug)]
+struct Solution {
+    nonce: u32,
+    midstate_idx: usize,
+    target: ii_bitcoin::Target,
+}
+
+impl hal::BackendSolution for Solution {
+    #[inline]
+    fn nonce(&self) -> u32 {
+        self.nonce
+    }
+
+    #[inline]
+    fn midstate_idx(&self) -> usize {
+        self.midstate_idx
+    }
+
+    #[inline]
+    fn solution_idx(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn target(&self) -> &ii_bitcoin::Target {
+        &self.target
+    }
+}
+
+/// Broadcast the benchmark job again when all work from its engine is exhausted so the generator
+/// never runs out of work
+#[derive(Debug)]
+struct Rescheduler {
+    job: Arc<dyn job::Bitcoin>,
+    engine_sender: Arc<OnceCell<Weak<work::EngineSender>>>,
+}
+
+impl work::ExhaustedHandler for Rescheduler {
+    fn handle_exhausted(&self, _engine: work::DynEngine, _job: Option<Arc<dyn job::Bitcoin>>) {
+        if let Some(engine_sender) = self.engine_sender.get().and_then(|sender| sender.upgrade()) {
+            engine_sender.broadcast_job(self.job.clone());
+        }
+    }
+}
+
+/// Maximal number of solutions which can wait for the consumer
+const MAX_SOLUTIONS_IN_FLIGHT: u64 = 4096;
+/// Number of assignments which can wait for the producer
+const WORK_QUEUE_SIZE: usize = 1024;
+
+/// Build the whole pipeline, run it at saturation for specified time and collect results
+pub fn run(config: Config) -> Report {
+    let job: Arc<dyn job::Bitcoin> = Arc::new(test_utils::TEST_BLOCKS[0]);
+    let engine_sender_cell = Arc::new(OnceCell::new());
+    let (engine_sender, engine_receiver) = work::engine_channel(Rescheduler {
+        job: job.clone(),
+        engine_sender: engine_sender_cell.clone(),
+    });
+    let engine_sender = Arc::new(engine_sender);
+    let midstate_count = config.midstate_count;
+    let _ = engine_sender.replace_engine_generator(Box::new(move |job| {
+        Arc::new(work::engine::VersionRolling::new(job, midstate_count))
+    }));
+    engine_sender_cell
+        .set(Arc::downgrade(&engine_sender))
+        .expect("BUG: engine sender already set");
+
+    let (solution_sender, solution_receiver) = mpsc::unbounded();
+    let job_solver = job::Solver::new(engine_sender.clone(), solution_receiver);
+    let work_solver_builder = work::SolverBuilder::new(
+        Arc::new(crate::Frontend::new()),
+        Arc::new(backend::IgnoreHierarchy),
+        engine_receiver,
+        solution_sender,
+    );
+
+    let mut work_generator = None;
+    let mut solution_sender = None;
+    let work_solver = block_on(work_solver_builder.create_work_solver(
+        |local_work_generator, local_solution_sender| {
+            work_generator = Some(local_work_generator);
+            solution_sender = Some(local_solution_sender);
+            test_utils::TestWorkSolver::new()
+        },
+    ));
+    // the builder keeps solution channel open so it must be dropped to allow consumer finish
+    drop(work_solver_builder);
+    let mut work_generator = work_generator.expect("BUG: missing work generator");
+    let solution_sender = solution_sender.expect("BUG: missing solution sender");
+
+    let running = Arc::new(AtomicBool::new(true));
+    let assignments = Arc::new(AtomicU64::new(0));
+    let solutions_produced = Arc::new(AtomicU64::new(0));
+    let solutions_routed = {
+        let work_solver = work_solver.clone();
+        move || {
+            block_on(
+                work_solver
+                    .work_solver_stats()
+                    .valid_backend_diff()
+                    .take_snapshot(),
+            )
+            .solutions
+        }
+    };
+
+    let (work_tx, work_rx) = std_mpsc::sync_channel(WORK_QUEUE_SIZE);
+    let generator_thread = {
+        let running = running.clone();
+        let assignments = assignments.clone();
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                match block_on(work_generator.generate()) {
+                    None => break,
+                    Some(work) => {
+                        assignments.fetch_add(1, Ordering::Relaxed);
+                        if work_tx.send(work).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            thread_cpu_time()
+        })
+    };
+
+    let producer_thread = {
+        let running = running.clone();
+        let solutions_produced = solutions_produced.clone();
+        let solutions_routed = solutions_routed.clone();
+        let compute_hashes = config.compute_hashes;
+        thread::spawn(move || {
+            let target = ii_bitcoin::Target::from_hex(
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            )
+            .expect("BUG: parse hex");
+            let mut nonce = 0u32;
+            let mut produced = 0;
+            for work in work_rx.iter() {
+                let midstate_idx = nonce as usize % midstate_count;
+                let solution = work::Solution::new(
+                    work,
+                    Solution {
+                        nonce,
+                        midstate_idx,
+                        target,
+                    },
+                    None,
+                );
+                if compute_hashes {
+                    assert!(solution.hash().meets(solution.backend_target()));
+                }
+                solution_sender.send(solution);
+                nonce = nonce.wrapping_add(1);
+                produced += 1;
+                solutions_produced.store(produced, Ordering::Relaxed);
+
+                // do not let the consumer fall behind too much
+                if produced % MAX_SOLUTIONS_IN_FLIGHT == 0 {
+                    while running.load(Ordering::Relaxed)
+                        && produced - solutions_routed() > MAX_SOLUTIONS_IN_FLIGHT
+                    {
+                        thread::yield_now();
+                    }
+                }
+            }
+            thread_cpu_time()
+        })
+    };
+
+    let consumer_thread = {
+        let mut solution_receiver = job_solver.solution_receiver;
+        thread::spawn(move || {
+            // synthetic solutions meet only backend target so they are just accounted to
+            // statistics and the receiver does not return them
+            while let Some(_) = block_on(solution_receiver.receive()) {}
+            thread_cpu_time()
+        })
+    };
+
+    // send job which is broadcasted again whenever its work is exhausted
+    job_solver.job_sender.send(job);
+
+    let start = time::Instant::now();
+    let allocations_start = ALLOCATIONS.load(Ordering::Relaxed);
+    ALLOCATION_COUNTING.store(true, Ordering::Relaxed);
+    thread::sleep(config.duration);
+    ALLOCATION_COUNTING.store(false, Ordering::Relaxed);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_start;
+    let assignments = assignments.load(Ordering::Relaxed);
+    let solutions_produced = solutions_produced.load(Ordering::Relaxed);
+    let solutions_routed = solutions_routed();
+    let elapsed = start.elapsed();
+
+    running.store(false, Ordering::Relaxed);
+    let generator_cpu_time = generator_thread.join().expect("BUG: generator thread");
+    let producer_cpu_time = producer_thread.join().expect("BUG: producer thread");
+    let consumer_cpu_time = consumer_thread.join().expect("BUG: consumer thread");
+
+    Report {
+        config,
+        elapsed,
+        assignments,
+        solutions_produced,
+        solutions_routed,
+        generator_cpu_time,
+        producer_cpu_time,
+        consumer_cpu_time,
+        // when the counting allocator is registered then there is always some allocation
+        allocations: if allocations > 0 {
+            Some(allocations)
+        } else {
+            None
+        },
+    }
+}
+
+/// Definition of `bench` sub-command
+pub fn sub_command<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::SubCommand::with_name("bench")
+        .about("Measure throughput of the frontend work pipeline without any hardware")
+        .arg(
+            clap::Arg::with_name("duration")
+                .long("duration")
+                .value_name("SECONDS")
+                .help("Duration of the measurement (default 10)")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("midstates")
+                .long("midstates")
+                .value_name("COUNT")
+                .help("Number of midstates in each assignment (default 4)")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("hash")
+                .long("hash")
+                .help("Compute real hashes of solutions in the producer")
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("json")
+                .long("json")
+                .help("Write results to stdout in JSON format")
+                .required(false)
+                .takes_value(false),
+        )
+}
+
+/// Handle `bench` sub-command and write results to stdout
+pub fn handle(matches: &clap::ArgMatches) -> Result<(), String> {
+    let config = Config::from_matches(matches)?;
+    let report = run(config);
+    if matches.is_present("json") {
+        println!("{}", report.to_json());
+    } else {
+        println!("{}", report);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bench_smoke() {
+        let report = run(Config {
+            duration: time::Duration::from_secs(1),
+            midstate_count: 4,
+            compute_hashes: true,
+        });
+
+        assert!(report.assignments > 0);
+        assert!(report.solutions_produced > 0);
+        assert!(report.solutions_routed > 0);
+        assert!(report.assignments_per_sec() > 0.0);
+        assert!(report.solutions_per_sec() > 0.0);
+        assert!(report.generator_cpu_time > time::Duration::from_secs(0));
+        assert_eq!(
+            report.to_json()["assignments"].as_u64(),
+            Some(report.assignments)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Internal bus decoupling hot paths of the miner (clients, job sender, status monitors) from
+//! modules observing them. The hot path only publishes a compact event to a bounded broadcast
+//! channel which never blocks: when a subscriber is too slow the oldest events are overwritten
+//! and counted as dropped. Each feature module subscribes on its own and does its work in its
+//! own task.
+//!
+//! Current subscribers are the work history recording block changes and the alert log (both
+//! started by the hub).
+
+use ii_logging::macros::*;
+
+use crate::job;
+use crate::log_id;
+use crate::stats;
+use crate::sync;
+
+use ii_async_compat::tokio;
+use tokio::sync::broadcast;
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Condition which should be brought to attention of the operator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+    /// Reject rate of the pool (display name) has significantly exceeded its baseline (rates
+    /// are in percent)
+    RejectRateRegressed {
+        pool: String,
+        rate: f64,
+        baseline: f64,
+    },
+    /// Stale rate of the pool (display name) has significantly exceeded its baseline (rates are
+    /// in percent)
+    StaleRateRegressed {
+        pool: String,
+        rate: f64,
+        baseline: f64,
+    },
+    /// Pool (display name) has been soft-failed by its reject breaker
+    PoolSoftFailed { pool: String, reason: String },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::RejectRateRegressed {
+                pool,
+                rate,
+                baseline,
+            } => write!(
+                f,
+                "reject rate of pool '{}' increased to {:.2}% (baseline {:.2}%)",
+                pool, rate, baseline
+            ),
+            Alert::StaleRateRegressed {
+                pool,
+                rate,
+                baseline,
+            } => write!(
+                f,
+                "stale rate of pool '{}' increased to {:.2}% (baseline {:.2}%)",
+                pool, rate, baseline
+            ),
+            Alert::PoolSoftFailed { pool, reason } => write!(
+                f,
+                "pool '{}' soft-failed due to rejected shares ({})",
+                pool, reason
+            ),
+        }
+    }
+}
+
+/// Events published on the bus. They are cloned for each subscriber so they are kept small.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Share has been accepted by the pool of the client. The difficulty is the one of the job
+    /// the share has been submitted for.
+    ShareAccepted {
+        client: log_id::ClientName,
+        difficulty: usize,
+    },
+    /// Share has been rejected by the pool of the client (`stale` when it has been rejected as
+    /// a share of an outdated job)
+    ShareRejected {
+        client: log_id::ClientName,
+        difficulty: usize,
+        stale: bool,
+    },
+    /// New job of the client has been broadcast to the work engines as `generation`
+    JobChanged {
+        client: log_id::ClientName,
+        generation: u64,
+    },
+    /// New block has been seen on the network for the first time
+    BlockChanged(job::block::BlockChange),
+    /// Client has finished a transition to the `status` (running, stopped or failed)
+    StatusChanged {
+        client: log_id::ClientName,
+        status: sync::Status,
+    },
+    /// Condition detected by some monitoring module
+    Alert(Alert),
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    published: stats::CounterU64,
+    dropped: stats::CounterU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Snapshot {
+    /// Number of events published on the bus
+    pub published: u64,
+    /// Number of events overwritten before some subscriber has received them (each event is
+    /// counted once for each subscriber that has missed it)
+    pub dropped: u64,
+}
+
+/// Bounded broadcast channel with drop-oldest semantics
+#[derive(Debug)]
+pub struct Bus {
+    sender: broadcast::Sender<Event>,
+    counters: Arc<Counters>,
+}
+
+impl Bus {
+    /// Number of events a subscriber may fall behind before the oldest ones are dropped
+    pub const CAPACITY: usize = 256;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        // Throwaway the receiver as subscribers are created on demand with `subscribe()`
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            counters: Default::default(),
+        }
+    }
+
+    /// Publish the `event` to all current subscribers. It is safe to be called on hot paths
+    /// because it never blocks nor awaits: the event is just written to the channel and the
+    /// oldest one is overwritten when the channel is full.
+    #[inline]
+    pub fn publish(&self, event: Event) {
+        self.counters.published.inc();
+        // there may be no subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive all events published after this call
+    pub fn subscribe(&self) -> Subscriber {
+        Subscriber {
+            receiver: self.sender.subscribe(),
+            counters: self.counters.clone(),
+        }
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot {
+        Snapshot {
+            published: *self.counters.published.take_snapshot(),
+            dropped: *self.counters.dropped.take_snapshot(),
+        }
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receiving end of one subscriber of the bus
+#[derive(Debug)]
+pub struct Subscriber {
+    receiver: broadcast::Receiver<Event>,
+    counters: Arc<Counters>,
+}
+
+impl Subscriber {
+    fn account_dropped(&self, count: u64) {
+        debug!("Bus: subscriber has missed {} events", count);
+        self.counters.dropped.add(count);
+    }
+
+    /// Wait for the next event. Events which have been overwritten before this subscriber has
+    /// received them are skipped and accounted as dropped. Returns `None` when the bus has been
+    /// dropped.
+    pub async fn recv(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::RecvError::Lagged(count)) => self.account_dropped(count),
+                Err(broadcast::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Take the next event without waiting for it
+    pub fn try_recv(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => return Some(event),
+                Err(broadcast::TryRecvError::Lagged(count)) => self.account_dropped(count),
+                Err(broadcast::TryRecvError::Empty) | Err(broadcast::TryRecvError::Closed) => {
+                    return None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn job_changed(client: log_id::ClientName, generation: u64) -> Event {
+        Event::JobChanged { client, generation }
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let bus = Bus::new();
+        let client = log_id::ClientName::next();
+        let mut journal = bus.subscribe();
+        let mut alerts = bus.subscribe();
+        let alert = Event::Alert(Alert::RejectRateRegressed {
+            pool: "braiins@pool.example.com".to_string(),
+            rate: 5.0,
+            baseline: 1.0,
+        });
+
+        bus.publish(job_changed(client, 1));
+        bus.publish(alert.clone());
+
+        for subscriber in [&mut journal, &mut alerts].iter_mut() {
+            assert_eq!(subscriber.try_recv(), Some(job_changed(client, 1)));
+            assert_eq!(subscriber.try_recv(), Some(alert.clone()));
+            assert_eq!(subscriber.try_recv(), None);
+        }
+        assert_eq!(
+            bus.take_snapshot(),
+            Snapshot {
+                published: 2,
+                dropped: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_slow_subscriber() {
+        let bus = Bus::with_capacity(4);
+        let client = log_id::ClientName::next();
+        let mut fast = bus.subscribe();
+        let mut slow = bus.subscribe();
+
+        for generation in 0..10 {
+            bus.publish(job_changed(client, generation));
+            // the fast subscriber keeps up with the publisher
+            assert_eq!(fast.try_recv(), Some(job_changed(client, generation)));
+        }
+
+        // the slow subscriber receives only the newest events and the oldest ones are dropped
+        for generation in 6..10 {
+            assert_eq!(slow.try_recv(), Some(job_changed(client, generation)));
+        }
+        assert_eq!(slow.try_recv(), None);
+        assert_eq!(fast.try_recv(), None);
+        let snapshot = bus.take_snapshot();
+        assert_eq!(snapshot.published, 10);
+        assert_eq!(snapshot.dropped, 6);
+    }
+
+    #[test]
+    fn test_publish_does_not_wait() {
+        let bus = Bus::with_capacity(1);
+        let client = log_id::ClientName::next();
+        // publishing without any subscriber is not an error
+        bus.publish(job_changed(client, 0));
+
+        // nobody reads the subscriber but each publish is just a single write to the channel
+        // which returns immediately even when the channel is full
+        let mut subscriber = bus.subscribe();
+        for generation in 1..=100 {
+            bus.publish(job_changed(client, generation));
+        }
+        assert_eq!(bus.take_snapshot().published, 101);
+        assert_eq!(subscriber.try_recv(), Some(job_changed(client, 100)));
+        assert_eq!(bus.take_snapshot().dropped, 99);
+    }
+
+    #[tokio::test]
+    async fn test_recv() {
+        let bus = Bus::new();
+        let client = log_id::ClientName::next();
+        let mut subscriber = bus.subscribe();
+
+        let handle = tokio::spawn(async move { subscriber.recv().await });
+        bus.publish(job_changed(client, 1));
+        assert_eq!(handle.await.unwrap(), Some(job_changed(client, 1)));
+
+        let mut subscriber = bus.subscribe();
+        drop(bus);
+        assert_eq!(subscriber.recv().await, None);
+    }
+}
@@ -23,16 +23,30 @@
 //! This module contains common functionality related to mining protocol client and allows
 //! executing a specific type of mining protocol client instance.
 
+pub mod baseline;
+pub mod breaker;
+pub mod digest;
+pub mod health;
+pub mod probe;
+pub mod resolver;
 mod scheduler;
+pub mod session;
+pub mod submission;
+pub mod tls;
 
 // Sub-modules with client implementation
 pub mod drain;
+pub mod stratum_v1;
 pub mod stratum_v2;
-pub mod stratum_v2_channels;
 
+use ii_logging::macros::*;
+
+use crate::bus;
+use crate::clock;
 use crate::error;
 use crate::hal;
 use crate::job;
+use crate::log_id;
 use crate::node;
 use crate::stats;
 use crate::sync::event;
@@ -43,16 +57,32 @@ pub use scheduler::JobExecutor;
 
 use bosminer_config::{
     ClientDescriptor, ClientProtocol, ClientUserInfo, GroupConfig, GroupDescriptor,
-    LoadBalanceStrategy,
+    GroupFailoverHealth, LoadBalanceStrategy, PoolConfig,
 };
 
 use futures::channel::mpsc;
 use futures::lock::Mutex;
 use ii_async_compat::futures;
 
+use std::collections::VecDeque;
 use std::slice;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Tests if the `solution` has been generated from a job of client `node`
+fn is_origin(node: &Arc<dyn node::Client>, solution: &work::Solution) -> bool {
+    solution
+        .origin()
+        .upgrade()
+        .map(|origin| Arc::ptr_eq(&node.clone().get_unique_ptr(), &origin.get_unique_ptr()))
+        .unwrap_or(false)
+}
+
+/// Creates engine generator which converts jobs into work with `midstate_count` midstates
+fn version_rolling_generator(midstate_count: usize) -> work::EngineGenerator {
+    Box::new(move |job| Arc::new(work::engine::VersionRolling::new(job, midstate_count)))
+}
 
 #[derive(Debug)]
 pub struct Handle {
@@ -60,67 +90,121 @@ pub struct Handle {
     descriptor: Arc<Mutex<ClientDescriptor>>,
     node: Arc<dyn node::Client>,
     enabled: AtomicBool,
+    /// The client has been selected by the scheduler to supply jobs of its group
+    active: AtomicBool,
     engine_sender: Arc<work::EngineSender>,
     solution_sender: mpsc::UnboundedSender<work::Solution>,
+    /// Soft-fails the client when the remote server rejects too many shares
+    reject_breaker: StdMutex<breaker::RejectBreaker>,
+    /// Bus of the mining core where the client publishes alerts of its reject breaker
+    bus: Arc<bus::Bus>,
+    /// Health of the client measured by the scheduler and used by failover
+    health: StdMutex<health::Monitor>,
+    /// Short name used in log records which does not change with the descriptor (it is shown
+    /// under the display name of the descriptor)
+    client_name: log_id::ClientName,
 }
 
 impl Handle {
+    /// `services` - components of the mining core shared with its other clients
     /// `channel` - endpoints for 2 channels so that stratum V2 client can communicate with an
     /// external client that implements some protocol extension
     pub fn new(
         descriptor: ClientDescriptor,
+        services: &job::Services,
         backend_info: Option<hal::BackendInfo>,
         channel: Option<(
             stratum_v2::ExtensionChannelToStratumReceiver,
             stratum_v2::ExtensionChannelFromStratumSender,
         )>,
     ) -> Self {
+        Self::with_node(
+            descriptor,
+            services,
+            |descriptor, job_solver| match &descriptor.protocol {
+                ClientProtocol::Drain => {
+                    assert!(
+                        channel.is_none(),
+                        "BUG: protocol 'Drain' does not support channel"
+                    );
+                    Arc::new(drain::Client::new(job_solver))
+                }
+                ClientProtocol::StratumV1 | ClientProtocol::StratumV1Tls => {
+                    assert!(
+                        channel.is_none(),
+                        "BUG: protocol 'Stratum V1' does not support channel"
+                    );
+                    Arc::new(stratum_v1::StratumClient::new(
+                        stratum_v1::ConnectionDetails::from_descriptor(descriptor),
+                        job_solver,
+                    ))
+                }
+                ClientProtocol::StratumV2(_)
+                | ClientProtocol::StratumV2Insecure
+                | ClientProtocol::StratumV2Tls => Arc::new(stratum_v2::StratumClient::new(
+                    stratum_v2::ConnectionDetails::from_descriptor(descriptor),
+                    backend_info,
+                    job_solver,
+                    channel,
+                )),
+            },
+        )
+    }
+
+    /// Create handle for client node built by `create_node` from the job solver connected to
+    /// this handle (it allows to plug in e.g. mock pool used in simulation)
+    pub fn with_node<F>(
+        descriptor: ClientDescriptor,
+        services: &job::Services,
+        create_node: F,
+    ) -> Self
+    where
+        F: FnOnce(&ClientDescriptor, job::Solver) -> Arc<dyn node::Client>,
+    {
         let (solution_sender, solution_receiver) = mpsc::unbounded();
         // Initially register new client without ability to send work
         let engine_sender = Arc::new(work::EngineSender::new(None));
 
-        let job_solver = job::Solver::new(engine_sender.clone(), solution_receiver);
-        let node: Arc<dyn node::Client> = match &descriptor.protocol {
-            ClientProtocol::Drain => {
-                assert!(
-                    channel.is_none(),
-                    "BUG: protocol 'Drain' does not support channel"
-                );
-                Arc::new(drain::Client::new(descriptor.get_full_url(), job_solver))
-            }
-            ClientProtocol::StratumV1 => {
-                assert!(
-                    channel.is_none(),
-                    "BUG: protocol 'Stratum V1' does not support channel"
-                );
-                Arc::new(stratum_v2_channels::StratumClient::new(
-                    stratum_v2_channels::ConnectionDetails::from_descriptor(&descriptor),
-                    job_solver,
-                ))
-            }
-            ClientProtocol::StratumV2(_) => Arc::new(stratum_v2::StratumClient::new(
-                stratum_v2::ConnectionDetails::from_descriptor(&descriptor),
-                backend_info,
-                job_solver,
-                channel,
-            )),
-            ClientProtocol::StratumV2Insecure => Arc::new(stratum_v2::StratumClient::new(
-                stratum_v2::ConnectionDetails::from_descriptor(&descriptor),
-                backend_info,
-                job_solver,
-                channel,
-            )),
-        };
+        let job_solver = job::Solver::new(engine_sender.clone(), solution_receiver)
+            .with_services(services)
+            .with_network(descriptor.network)
+            .with_share_policy(descriptor.share_policy.clone());
+        let client_name = job_solver.client_name();
+        client_name.set_display_name(&descriptor.display_name());
+        let node = create_node(&descriptor, job_solver);
 
+        let reject_breaker = breaker::RejectBreaker::new(descriptor.reject_breaker.clone());
         Self {
             descriptor: Arc::new(Mutex::new(descriptor)),
             node,
             enabled: AtomicBool::new(false),
+            active: AtomicBool::new(false),
             engine_sender,
             solution_sender,
+            reject_breaker: StdMutex::new(reject_breaker),
+            bus: services.bus.clone(),
+            health: StdMutex::new(health::Monitor::new()),
+            client_name,
         }
     }
 
+    #[inline]
+    pub fn client_name(&self) -> log_id::ClientName {
+        self.client_name
+    }
+
+    /// Unique name of the client shown in logs and API
+    #[inline]
+    pub fn display_name(&self) -> String {
+        self.client_name.to_string()
+    }
+
+    /// Prefix of log records related to this client
+    #[inline]
+    pub fn log_prefix(&self) -> log_id::Prefix {
+        log_id::Prefix::new().with_client(self.client_name)
+    }
+
     #[inline]
     pub async fn descriptor(&self) -> ClientDescriptor {
         self.descriptor.lock().await.clone()
@@ -131,9 +215,149 @@ impl Handle {
         let mut current_descriptor = self.descriptor.lock().await;
 
         self.node.change_connection_details(&descriptor);
+        *self.lock_reject_breaker() =
+            breaker::RejectBreaker::new(descriptor.reject_breaker.clone());
+        if descriptor.display_name() != current_descriptor.display_name() {
+            self.client_name
+                .set_display_name(&descriptor.display_name());
+        }
         *current_descriptor = descriptor;
     }
 
+    /// Change label of the client without touching connection, reject breaker or statistics so
+    /// shares accounted so far are attributed to the new label
+    pub async fn set_label(&self, label: Option<String>) {
+        self.descriptor.lock().await.label = label;
+    }
+
+    /// Change display name of the client without touching connection or statistics. Returns the
+    /// name which has been assigned (it is suffixed when another client uses the same one).
+    pub async fn set_name(&self, name: Option<String>) -> String {
+        let mut descriptor = self.descriptor.lock().await;
+        descriptor.name = name;
+        self.client_name
+            .set_display_name(&descriptor.display_name())
+            .to_string()
+    }
+
+    fn lock_reject_breaker(&self) -> StdMutexGuard<breaker::RejectBreaker> {
+        self.reject_breaker
+            .lock()
+            .expect("BUG: cannot lock reject breaker")
+    }
+
+    /// Feed the reject breaker with current share counters. Returns `false` when no work should
+    /// be generated for the client because it is soft-failed or because it is probed and
+    /// responses to the probe shares have not been received yet.
+    pub(crate) async fn check_reject_breaker(&self) -> bool {
+        let accepted = self.stats().accepted().take_snapshot().await.solutions;
+        let rejected = self.stats().rejected().take_snapshot().await.solutions;
+        let found = self
+            .stats()
+            .valid_job_diff()
+            .take_snapshot()
+            .await
+            .solutions;
+
+        let (event, accepts_work) = {
+            let mut reject_breaker = self.lock_reject_breaker();
+            let event = reject_breaker.update(accepted, rejected, found, clock::now());
+            (event, reject_breaker.accepts_work())
+        };
+        if let Some(event) = event {
+            let descriptor = self.descriptor().await;
+            let name = self.display_name();
+            let prefix = self.log_prefix();
+            match event.transition {
+                breaker::Transition::Tripped => {
+                    warn!(
+                        "{} Pool '{}' soft-failed due to rejected shares ({}), it will be retried \
+                         in {} s",
+                        prefix,
+                        name,
+                        event.reason,
+                        descriptor.reject_breaker.cool_off().as_secs()
+                    );
+                    self.bus
+                        .publish(bus::Event::Alert(bus::Alert::PoolSoftFailed {
+                            pool: name,
+                            reason: event.reason,
+                        }));
+                }
+                breaker::Transition::Probing => info!(
+                    "{} Probing soft-failed pool '{}' ({})",
+                    prefix, name, event.reason
+                ),
+                breaker::Transition::Restored => {
+                    info!("{} Pool '{}' restored ({})", prefix, name, event.reason)
+                }
+            }
+        }
+        accepts_work
+    }
+
+    /// Returns `true` when the client has been soft-failed by the reject breaker
+    #[inline]
+    pub fn is_soft_failed(&self) -> bool {
+        !self.lock_reject_breaker().is_available()
+    }
+
+    /// Returns history of the reject breaker transitions
+    pub fn reject_breaker_history(&self) -> Vec<breaker::Event> {
+        self.lock_reject_breaker().history()
+    }
+
+    fn lock_health(&self) -> StdMutexGuard<health::Monitor> {
+        self.health.lock().expect("BUG: cannot lock health monitor")
+    }
+
+    /// Measure current state of the client and compute its health score according to `config`.
+    /// The score is kept for statistics until the next update.
+    pub(crate) async fn update_health(&self, config: &GroupFailoverHealth) -> health::Score {
+        let accepted = self.stats().accepted().take_snapshot().await.solutions;
+        let rejected = self.stats().rejected().take_snapshot().await.solutions;
+        let submit_latency = self.stats().submissions().latency.take_snapshot();
+        let now = clock::now();
+        let job_age = self
+            .stats()
+            .job_history()
+            .current_start()
+            .map(|start| now.checked_duration_since(start).unwrap_or_default());
+
+        let mut monitor = self.lock_health();
+        let sample = health::Sample {
+            job_age,
+            submit_latency_p95: submit_latency.latency_p95,
+            reject_rate: monitor.reject_rate(accepted, rejected, now),
+            authorization: self.node.authorization(),
+        };
+        let score = health::score(&sample, config);
+        monitor.update(sample, score);
+        score
+    }
+
+    /// Returns the last measured state of the client with its health score or `None` when it
+    /// has not been scored yet
+    pub fn health(&self) -> Option<(health::Sample, health::Score)> {
+        self.lock_health().last()
+    }
+
+    /// Returns results of connection attempts to the addresses of the remote server
+    pub fn address_stats(&self) -> Vec<resolver::AddressStats> {
+        self.node.address_stats()
+    }
+
+    /// Returns history of sessions established with the remote server (whether they have been
+    /// resumed after reconnect)
+    pub fn session_history(&self) -> Vec<session::Event> {
+        self.node.session_history()
+    }
+
+    /// Returns security parameters of the current connection (only for secure protocols)
+    pub fn connection_security(&self) -> Option<session::Security> {
+        self.node.connection_security()
+    }
+
     pub fn replace_engine_generator(
         &self,
         engine_generator: work::EngineGenerator,
@@ -142,21 +366,47 @@ impl Handle {
             .replace_engine_generator(engine_generator)
     }
 
+    /// Switch the client to work with `midstate_count` midstates. The current job is immediately
+    /// converted into a new engine so no more work with previous midstate count is generated.
+    /// Already generated work keeps its own midstates so its solutions are still valid.
+    pub async fn set_midstate_count(&self, midstate_count: usize) {
+        let _ = self.replace_engine_generator(version_rolling_generator(midstate_count));
+        // Do not revive engine which has been already exhausted or invalidated
+        if !self.engine_sender.is_exhausted() {
+            if let Some(job) = self.get_last_job().await {
+                self.engine_sender.broadcast_job(job);
+            }
+        }
+    }
+
     /// Tests if solution should be delivered to this client
     /// NOTE: This comparison uses trait method `node::Info::get_unique_ptr` to unify dynamic
     /// objects to point to the same pointer otherwise direct comparison of self with other is never
     /// satisfied even if the dynamic objects are same.
     pub fn matching_solution(&self, solution: &work::Solution) -> bool {
-        solution
-            .origin()
-            .upgrade()
-            .map(|origin| {
-                Arc::ptr_eq(
-                    &self.node.clone().get_unique_ptr(),
-                    &origin.get_unique_ptr(),
-                )
-            })
-            .unwrap_or(false)
+        is_origin(&self.node, solution)
+    }
+
+    /// Tests if the solution of a removed client can be submitted by this client instead. The job
+    /// has to be of the same protocol as jobs of this client otherwise it cannot be submitted at
+    /// all. Solutions of `foreign` jobs are accepted only when it is allowed by the share policy.
+    pub(crate) async fn accepts_rerouted(&self, solution: &work::Solution, foreign: bool) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        if foreign && !self.descriptor().await.share_policy.accept_foreign() {
+            return false;
+        }
+        self.get_last_job().await.map_or(false, |job| {
+            (*job).as_any().type_id() == (**solution.dyn_job()).as_any().type_id()
+        })
+    }
+
+    /// Deliver solution of a removed client to be submitted by this client
+    pub(crate) fn send_rerouted(&self, solution: work::Solution) {
+        self.solution_sender
+            .unbounded_send(solution.reroute())
+            .expect("solution queue send failed");
     }
 
     #[inline]
@@ -181,6 +431,10 @@ impl Handle {
 
     #[inline]
     fn start(&self) {
+        // Reconnecting is pointless when the last failure cannot be resolved by retrying
+        if self.node.fatal_error().is_some() {
+            return;
+        }
         if self.node.status().initiate_starting() {
             // The client can be started safely
             self.node.clone().start();
@@ -188,7 +442,7 @@ impl Handle {
     }
 
     #[inline]
-    fn stop(&self) {
+    pub(crate) fn stop(&self) {
         if self.node.status().initiate_stopping() {
             // The client can be stopped safely
             self.node.clone().stop();
@@ -201,6 +455,18 @@ impl Handle {
         self.enabled.load(Ordering::Relaxed)
     }
 
+    /// Check if the client is the live one in its group which supplies jobs (the other clients
+    /// are either stopped or they are waiting for failover)
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn set_active(&self, active: bool) {
+        self.active.store(active, Ordering::Relaxed);
+    }
+
     /// Try to enable the client. Default client state should be disabled.
     pub fn try_enable(&self) -> Result<(), ()> {
         let was_enabled = self.enabled.swap(true, Ordering::Relaxed);
@@ -246,6 +512,11 @@ impl Handle {
         self.node.client_stats()
     }
 
+    #[inline]
+    pub(crate) fn node(&self) -> &Arc<dyn node::Client> {
+        &self.node
+    }
+
     #[inline]
     pub(crate) async fn get_last_job(&self) -> Option<Arc<dyn job::Bitcoin>> {
         self.node.get_last_job().await
@@ -255,6 +526,7 @@ impl Handle {
 impl Drop for Handle {
     fn drop(&mut self) {
         self.node.stop();
+        self.client_name.clear_display_name();
     }
 }
 
@@ -269,24 +541,65 @@ impl PartialEq for Handle {
 
 #[derive(Debug)]
 pub struct Group {
-    pub descriptor: GroupDescriptor,
+    descriptor: GroupDescriptor,
     scheduler_client_handles: Mutex<Vec<scheduler::ClientHandle>>,
     event_sender: event::Sender,
     /// All clients in the group must support the same amount of midstates
-    midstate_count: usize,
+    midstate_count: AtomicUsize,
+    /// Current quota which can be changed at runtime (unused for fixed share ratio)
+    quota: AtomicUsize,
+    /// Recently removed clients kept to recognize solutions of work generated for them
+    former_clients: StdMutex<VecDeque<Arc<dyn node::Client>>>,
 }
 
 impl Group {
+    /// Number of removed clients remembered by the group
+    const FORMER_CLIENTS_LENGTH: usize = 16;
+
     fn new(
         descriptor: GroupDescriptor,
         event_sender: event::Sender,
         midstate_count: usize,
     ) -> Self {
         Self {
+            quota: AtomicUsize::new(descriptor.get_quota().unwrap_or_default()),
             descriptor,
             scheduler_client_handles: Mutex::new(vec![]),
             event_sender,
-            midstate_count,
+            midstate_count: AtomicUsize::new(midstate_count),
+            former_clients: StdMutex::new(VecDeque::new()),
+        }
+    }
+
+    #[inline]
+    pub fn descriptor(&self) -> &GroupDescriptor {
+        &self.descriptor
+    }
+
+    /// Current quota of the group or `None` when the group has fixed share ratio
+    #[inline]
+    pub fn get_quota(&self) -> Option<usize> {
+        self.descriptor
+            .get_quota()
+            .map(|_| self.quota.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub fn midstate_count(&self) -> usize {
+        self.midstate_count.load(Ordering::Relaxed)
+    }
+
+    /// Change number of midstates for all clients in the group
+    pub async fn set_midstate_count(&self, midstate_count: usize) {
+        // NOTE: Keep client handles locked to prevent adding a client with previous midstate count
+        let scheduler_client_handles = self.scheduler_client_handles.lock().await;
+
+        self.midstate_count.store(midstate_count, Ordering::Relaxed);
+        for scheduler_client_handle in scheduler_client_handles.iter() {
+            scheduler_client_handle
+                .client_handle
+                .set_midstate_count(midstate_count)
+                .await;
         }
     }
 
@@ -311,10 +624,8 @@ impl Group {
     }
 
     pub async fn push_client(&self, client_handle: Handle) -> Arc<Handle> {
-        let midstate_count = self.midstate_count;
-        let _ = client_handle.replace_engine_generator(Box::new(move |job| {
-            Arc::new(work::engine::VersionRolling::new(job, midstate_count))
-        }));
+        let _ = client_handle
+            .replace_engine_generator(version_rolling_generator(self.midstate_count()));
         let _ = client_handle.try_disable();
         client_handle.set_event_sender(self.event_sender.clone());
 
@@ -351,12 +662,51 @@ impl Group {
             self.event_sender.notify();
             // Remove event sender not to notify about removed client status changes
             client_handle.take_event_sender();
+            client_handle.set_active(false);
             // Immediately disable client to force scheduler to select another client
             let _ = client_handle.try_disable();
+            self.remember_former_client(&client_handle);
             Ok(client_handle)
         }
     }
 
+    fn remember_former_client(&self, client_handle: &Handle) {
+        let mut former_clients = self
+            .former_clients
+            .lock()
+            .expect("BUG: cannot lock former clients");
+        if former_clients.len() >= Self::FORMER_CLIENTS_LENGTH {
+            former_clients.pop_front();
+        }
+        former_clients.push_back(client_handle.node.clone());
+    }
+
+    /// Tests if the solution has been generated for a client recently removed from the group
+    fn is_former_origin(&self, solution: &work::Solution) -> bool {
+        self.former_clients
+            .lock()
+            .expect("BUG: cannot lock former clients")
+            .iter()
+            .any(|node| is_origin(node, solution))
+    }
+
+    /// Removes the client regardless of its current position within the group
+    pub async fn remove_client(
+        &self,
+        client_handle: &Arc<Handle>,
+    ) -> Result<Arc<Handle>, error::Client> {
+        let index = self
+            .scheduler_client_handles
+            .lock()
+            .await
+            .iter()
+            .position(|scheduler_client_handle| {
+                Arc::ptr_eq(&scheduler_client_handle.client_handle, client_handle)
+            })
+            .ok_or(error::Client::Missing)?;
+        self.remove_client_at(index).await
+    }
+
     /// Changes the position of a client within the group
     pub async fn move_client_to(
         &self,
@@ -394,6 +744,16 @@ impl Group {
         Ok(client_handle)
     }
 
+    async fn position_of(&self, client_handle: &Arc<Handle>) -> Option<usize> {
+        self.scheduler_client_handles
+            .lock()
+            .await
+            .iter()
+            .position(|scheduler_client_handle| {
+                Arc::ptr_eq(&scheduler_client_handle.client_handle, client_handle)
+            })
+    }
+
     async fn find_client(&self, solution: &work::Solution) -> Option<Arc<Handle>> {
         self.scheduler_client_handles
             .lock()
@@ -408,6 +768,13 @@ impl Group {
     }
 }
 
+/// Gradual change of share ratios after quota change
+#[derive(Debug, Clone, Copy)]
+struct QuotaTransition {
+    start: time::Instant,
+    horizon: time::Duration,
+}
+
 /// Keeps track of all active clients
 pub struct GroupRegistry {
     list: Vec<scheduler::GroupHandle>,
@@ -415,6 +782,7 @@ pub struct GroupRegistry {
     total_quota: usize,
     fixed_share_ratio_count: usize,
     total_fixed_share_ratio: f64,
+    quota_transition: Option<QuotaTransition>,
 }
 
 impl GroupRegistry {
@@ -425,6 +793,7 @@ impl GroupRegistry {
             total_quota: 0,
             fixed_share_ratio_count: 0,
             total_fixed_share_ratio: 0.0,
+            quota_transition: None,
         }
     }
 
@@ -480,10 +849,90 @@ impl GroupRegistry {
         let scheduler_group_handle = scheduler::GroupHandle::new(group_handle.clone());
         self.list.push(scheduler_group_handle);
         self.recalculate_quotas(true);
+        self.finish_quota_transition(false);
 
         Ok(group_handle)
     }
 
+    /// Change quota of the `group` and return the previous one. Share ratios of all groups
+    /// converge to the new values over the `horizon` to prevent burst of work for the group with
+    /// increased quota. Zero `horizon` applies the new share ratios immediately.
+    pub fn set_quota(
+        &mut self,
+        group: &Arc<Group>,
+        quota: usize,
+        horizon: time::Duration,
+    ) -> Result<usize, error::Client> {
+        let old_quota = self
+            .list
+            .iter()
+            .find(|scheduler_group_handle| Arc::ptr_eq(&scheduler_group_handle.group_handle, group))
+            .ok_or(error::Client::Missing)?
+            .get_quota()
+            .ok_or(error::Client::FixedShareRatioGroup)?;
+        let total_quota = self.total_quota - old_quota + quota;
+        if total_quota == 0 {
+            Err(error::Client::ZeroTotalQuota)?;
+        }
+
+        // The new transition starts from share ratios reached by the interrupted one
+        let progress = self.quota_transition_progress();
+        for scheduler_group_handle in self.iter_mut() {
+            scheduler_group_handle.previous_share_ratio =
+                scheduler_group_handle.current_share_ratio(progress);
+        }
+
+        group.quota.store(quota, Ordering::Relaxed);
+        self.total_quota = total_quota;
+        self.recalculate_quotas(true);
+        if horizon == time::Duration::from_secs(0) {
+            self.finish_quota_transition(false);
+        } else {
+            self.quota_transition = Some(QuotaTransition {
+                start: clock::now(),
+                horizon,
+            });
+        }
+
+        Ok(old_quota)
+    }
+
+    /// Return progress (from 0.0 to 1.0) of the running quota transition. The transition is
+    /// finished when its horizon is reached.
+    fn quota_transition_progress(&mut self) -> Option<f64> {
+        let transition = self.quota_transition?;
+        let elapsed = clock::now().duration_since(transition.start);
+        if elapsed >= transition.horizon {
+            // Accepted shares are reset to stop compensating the ramp of share ratios
+            self.finish_quota_transition(true);
+            None
+        } else {
+            Some(elapsed.as_secs_f64() / transition.horizon.as_secs_f64())
+        }
+    }
+
+    fn finish_quota_transition(&mut self, reset_accepted_shares: bool) {
+        self.quota_transition = None;
+        for scheduler_group_handle in self.iter_mut() {
+            if reset_accepted_shares {
+                scheduler_group_handle.reset_accepted_shares();
+            }
+            scheduler_group_handle.previous_share_ratio = scheduler_group_handle.share_ratio;
+        }
+    }
+
+    /// Remove all groups including the private ones and return them
+    fn remove_groups(&mut self) -> Vec<Arc<Group>> {
+        self.total_quota = 0;
+        self.fixed_share_ratio_count = 0;
+        self.total_fixed_share_ratio = 0.0;
+        self.quota_transition = None;
+        self.list
+            .drain(..)
+            .map(|scheduler_group_handle| scheduler_group_handle.group_handle)
+            .collect()
+    }
+
     pub fn get_groups(&self) -> Vec<Arc<Group>> {
         self.list
             .iter()
@@ -513,7 +962,16 @@ impl GroupRegistry {
         None
     }
 
-    fn recalculate_quotas(&mut self, reset_generated_work: bool) {
+    /// Find group from which the client associated with given solution has been removed
+    fn find_former_group(&self, solution: &work::Solution) -> Option<Arc<Group>> {
+        self.list
+            .iter()
+            .map(|scheduler_group_handle| &scheduler_group_handle.group_handle)
+            .find(|group_handle| group_handle.is_former_origin(solution))
+            .cloned()
+    }
+
+    fn recalculate_quotas(&mut self, reset_accepted_shares: bool) {
         assert!(
             self.total_fixed_share_ratio < 1.0 && self.fixed_share_ratio_count < self.count(),
             "BUG: no share ratio left for common groups"
@@ -522,16 +980,20 @@ impl GroupRegistry {
         if self.is_empty() {
             return;
         }
-        // Precalculate remaining share ratio normalized per 1 quota unit
-        let share_ratio_per_quota_unit =
-            (1.0 - self.total_fixed_share_ratio) / self.total_quota as f64;
+        // Precalculate remaining share ratio normalized per 1 quota unit. Groups with zero quota
+        // get no share ratio and they are used only as a backup.
+        let share_ratio_per_quota_unit = if self.total_quota == 0 {
+            0.0
+        } else {
+            (1.0 - self.total_fixed_share_ratio) / self.total_quota as f64
+        };
 
         // Update all groups with newly calculated share ratio.
-        // Also reset generated work to prevent switching all future work to new group because
+        // Also reset accepted shares to prevent switching all future work to new group because
         // new group has zero shares and so maximal error.
         for mut scheduler_group_handle in self.list.iter_mut() {
-            if reset_generated_work {
-                scheduler_group_handle.reset_generated_work();
+            if reset_accepted_shares {
+                scheduler_group_handle.reset_accepted_shares();
             }
             if !scheduler_group_handle.has_fixed_share_ratio() {
                 scheduler_group_handle.share_ratio = share_ratio_per_quota_unit
@@ -541,25 +1003,138 @@ impl GroupRegistry {
             }
         }
     }
+
+    /// Share ratios expected by the scheduler with progress of the running quota transition
+    fn expected_share_ratios(&mut self) -> Vec<f64> {
+        let progress = self.quota_transition_progress();
+        self.iter()
+            .map(|scheduler_group_handle| scheduler_group_handle.expected_share_ratio(progress))
+            .collect()
+    }
+}
+
+/// Selects the client registered in the manager either by its index across all groups (pools
+/// are indexed in the same way as in the API) or by its descriptor
+#[derive(Debug, Clone, Copy)]
+pub enum ClientSelector<'a> {
+    Index(usize),
+    Descriptor(&'a ClientDescriptor),
+}
+
+impl<'a> From<usize> for ClientSelector<'a> {
+    fn from(idx: usize) -> Self {
+        ClientSelector::Index(idx)
+    }
+}
+
+impl<'a> From<&'a ClientDescriptor> for ClientSelector<'a> {
+    fn from(descriptor: &'a ClientDescriptor) -> Self {
+        ClientSelector::Descriptor(descriptor)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Manager {
     group_registry: Arc<Mutex<GroupRegistry>>,
     event_monitor: event::Monitor,
-    midstate_count: usize,
+    midstate_count: Arc<AtomicUsize>,
+    /// Default Bitcoin network for clients without explicit network
+    network: ii_bitcoin::Network,
+    /// Components of the mining core shared by all clients
+    services: job::Services,
 }
 
 impl Manager {
+    /// Default time over which the distribution of work converges to changed quota
+    pub const DEFAULT_QUOTA_HORIZON: time::Duration = time::Duration::from_secs(10 * 60);
+
     pub fn new(midstate_count: usize) -> Self {
         let event_monitor = event::Monitor::new();
         Self {
             group_registry: Arc::new(Mutex::new(GroupRegistry::new(event_monitor.clone()))),
             event_monitor,
-            midstate_count,
+            midstate_count: Arc::new(AtomicUsize::new(midstate_count)),
+            network: Default::default(),
+            services: job::Services::new(),
+        }
+    }
+
+    pub fn with_network(mut self, network: ii_bitcoin::Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Create clients with `services` of the mining core instead of the own ones
+    pub fn with_services(mut self, services: job::Services) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Components of the mining core which have to be passed to every new client
+    #[inline]
+    pub fn services(&self) -> &job::Services {
+        &self.services
+    }
+
+    #[inline]
+    pub fn network(&self) -> ii_bitcoin::Network {
+        self.network
+    }
+
+    #[inline]
+    pub fn midstate_count(&self) -> usize {
+        self.midstate_count.load(Ordering::Relaxed)
+    }
+
+    /// Change number of midstates used for conversion of jobs into work in all groups.
+    /// The backend should be quiesced before the change because it will receive work with new
+    /// number of midstates immediately.
+    pub async fn set_midstate_count(&self, midstate_count: usize) {
+        // NOTE: Keep registry locked to prevent creating a group with previous midstate count
+        let group_registry = self.group_registry.lock().await;
+
+        self.midstate_count.store(midstate_count, Ordering::Relaxed);
+        for scheduler_group_handle in group_registry.iter() {
+            scheduler_group_handle
+                .group_handle
+                .set_midstate_count(midstate_count)
+                .await;
         }
     }
 
+    /// Build client descriptors for all pools in the group configuration
+    fn create_client_descriptors(
+        &self,
+        pool_configs: Option<Vec<PoolConfig>>,
+        default_pool_enabled: bool,
+    ) -> error::Result<Vec<ClientDescriptor>> {
+        let mut descriptors = vec![];
+        for pool_config in pool_configs.unwrap_or_default() {
+            let network = match pool_config.network.as_ref() {
+                Some(network) => network.parse()?,
+                None => self.network,
+            };
+            let descriptor = ClientDescriptor::create(
+                pool_config.url.as_str(),
+                &ClientUserInfo::new(pool_config.user.as_str(), pool_config.password.as_deref()),
+                pool_config.enabled.unwrap_or(default_pool_enabled),
+            )
+            .map_err(|e| e.to_string())?
+            .with_network(network)
+            .with_label(pool_config.label)
+            .with_name(pool_config.name)
+            .with_reject_breaker(pool_config.reject_breaker.unwrap_or_default())
+            .with_coinbase_check(pool_config.coinbase_check)
+            .with_share_policy(pool_config.share_policy.unwrap_or_default())
+            .with_cert_expiry_warning(pool_config.cert_expiry_warning)
+            .with_tls(pool_config.ca_cert, pool_config.insecure)
+            .with_difficulty(pool_config.min_difficulty, pool_config.fixed_difficulty)
+            .with_dns_ttl(pool_config.dns_ttl);
+            descriptors.push(descriptor);
+        }
+        Ok(descriptors)
+    }
+
     pub async fn load_config<T>(
         &self,
         group_configs: T,
@@ -571,32 +1146,240 @@ impl Manager {
     {
         if let Some(group_configs) = group_configs.into() {
             for group_config in group_configs {
+                let descriptors =
+                    self.create_client_descriptors(group_config.pools, default_pool_enabled)?;
                 let group = self.create_group(group_config.descriptor).await?;
-                if let Some(pool_configs) = group_config.pools {
-                    for pool_config in pool_configs {
-                        let descriptor = ClientDescriptor::create(
-                            pool_config.url.as_str(),
-                            &ClientUserInfo::new(
-                                pool_config.user.as_str(),
-                                pool_config.password.as_deref(),
-                            ),
-                            pool_config.enabled.unwrap_or(default_pool_enabled),
-                        )
-                        .map_err(|e| e.to_string())?;
-                        let client_handle = Handle::new(descriptor, backend_info.cloned(), None);
-                        group.push_client(client_handle).await;
-                    }
+                for descriptor in descriptors {
+                    let client_handle =
+                        Handle::new(descriptor, &self.services, backend_info.cloned(), None);
+                    group.push_client(client_handle).await;
                 }
             }
         }
         Ok(())
     }
 
+    /// Apply changed group configuration to running clients. Groups are matched by name and
+    /// clients by their connection settings so unchanged clients keep their connection and
+    /// statistics. Changes which cannot be applied at runtime are only logged.
+    pub async fn reload_config<T>(
+        &self,
+        group_configs: T,
+        backend_info: Option<&hal::BackendInfo>,
+        default_pool_enabled: bool,
+    ) -> error::Result<()>
+    where
+        T: Into<Option<Vec<GroupConfig>>>,
+    {
+        let group_configs = group_configs.into().unwrap_or_default();
+        // Validate the whole configuration before touching any running client
+        let mut new_groups = vec![];
+        for group_config in group_configs {
+            let descriptors =
+                self.create_client_descriptors(group_config.pools, default_pool_enabled)?;
+            new_groups.push((group_config.descriptor, descriptors));
+        }
+
+        let groups = self.get_groups().await;
+        for group in groups.iter() {
+            if group.descriptor.private {
+                continue;
+            }
+            if !new_groups
+                .iter()
+                .any(|(descriptor, _)| descriptor.name == group.descriptor.name)
+            {
+                warn!(
+                    "Group '{}' has been removed from configuration: dropping its clients (the \
+                     group itself is removed after restart)",
+                    group.descriptor.name
+                );
+                self.reload_group_clients(group, vec![], backend_info).await;
+            }
+        }
+
+        for (descriptor, client_descriptors) in new_groups {
+            let group = match groups
+                .iter()
+                .find(|group| group.descriptor.name == descriptor.name)
+            {
+                Some(group) => {
+                    self.reload_group_strategy(group, &descriptor).await;
+                    group.clone()
+                }
+                None => {
+                    info!("Adding group '{}'", descriptor.name);
+                    self.create_group(descriptor).await?
+                }
+            };
+            self.reload_group_clients(&group, client_descriptors, backend_info)
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn reload_group_strategy(&self, group: &Arc<Group>, descriptor: &GroupDescriptor) {
+        match (group.get_quota(), descriptor.strategy()) {
+            (Some(old_quota), LoadBalanceStrategy::Quota(quota)) => {
+                if old_quota != quota {
+                    if let Err(e) = self
+                        .set_quota(group, quota, Self::DEFAULT_QUOTA_HORIZON)
+                        .await
+                    {
+                        warn!(
+                            "Cannot change quota of group '{}': {}",
+                            group.descriptor.name, e
+                        );
+                    }
+                }
+            }
+            (_, strategy) => {
+                if strategy != group.descriptor.strategy() {
+                    warn!(
+                        "Load balance strategy of group '{}' has been changed: requires restart",
+                        group.descriptor.name
+                    );
+                }
+            }
+        }
+    }
+
+    /// Replace clients of the `group` with clients described by `descriptors`. Running clients
+    /// with the same connection settings are kept and the order of clients follows `descriptors`.
+    async fn reload_group_clients(
+        &self,
+        group: &Arc<Group>,
+        descriptors: Vec<ClientDescriptor>,
+        backend_info: Option<&hal::BackendInfo>,
+    ) {
+        let mut old_clients = vec![];
+        for client_handle in group.get_clients().await {
+            let descriptor = client_handle.descriptor().await;
+            old_clients.push(Some((client_handle, descriptor)));
+        }
+
+        // Pair each new descriptor with a running client which has the same identity
+        let mut new_clients = vec![];
+        for descriptor in descriptors {
+            let kept_client = old_clients
+                .iter()
+                .position(|old_client| match old_client {
+                    Some((_, old_descriptor)) => old_descriptor.is_same_client(&descriptor),
+                    None => false,
+                })
+                .and_then(|index| old_clients[index].take());
+            new_clients.push((descriptor, kept_client));
+        }
+
+        for (client_handle, descriptor) in old_clients.into_iter().flatten() {
+            info!(
+                "{} Removing client {} from group '{}'",
+                client_handle.log_prefix(),
+                descriptor.get_labelled_url(),
+                group.descriptor.name
+            );
+            let index = group
+                .position_of(&client_handle)
+                .await
+                .expect("BUG: missing client");
+            group
+                .remove_client_at(index)
+                .await
+                .expect("BUG: cannot remove client");
+        }
+
+        let mut client_handles = vec![];
+        for (descriptor, kept_client) in new_clients {
+            let client_handle = match kept_client {
+                Some((client_handle, old_descriptor)) => {
+                    if descriptor.label != old_descriptor.label {
+                        client_handle.set_label(descriptor.label.clone()).await;
+                    }
+                    // The name is assigned again also when it has been suffixed because another
+                    // client has been using it
+                    if descriptor.name != old_descriptor.name
+                        || descriptor.display_name() != client_handle.display_name()
+                    {
+                        let old_name = client_handle.display_name();
+                        let name = client_handle.set_name(descriptor.name.clone()).await;
+                        if name != old_name {
+                            info!(
+                                "{} Pool '{}' has been renamed to '{}'",
+                                client_handle.log_prefix(),
+                                old_name,
+                                name
+                            );
+                        }
+                    }
+                    if descriptor.enabled != client_handle.is_enabled() {
+                        let _ = if descriptor.enabled {
+                            client_handle.try_enable()
+                        } else {
+                            client_handle.try_disable()
+                        };
+                    }
+                    client_handle
+                }
+                None => {
+                    let url = descriptor.get_labelled_url();
+                    let client_handle =
+                        Handle::new(descriptor, &self.services, backend_info.cloned(), None);
+                    info!(
+                        "{} Adding client {} to group '{}'",
+                        client_handle.log_prefix(),
+                        url,
+                        group.descriptor.name
+                    );
+                    group.push_client(client_handle).await
+                }
+            };
+            client_handles.push(client_handle);
+        }
+
+        for (index_to, client_handle) in client_handles.iter().enumerate() {
+            let index_from = group
+                .position_of(client_handle)
+                .await
+                .expect("BUG: missing client");
+            if index_from != index_to {
+                group
+                    .move_client_to(index_from, index_to)
+                    .await
+                    .expect("BUG: cannot move client");
+            }
+        }
+    }
+
     #[inline]
     pub fn subscribe_to_clients_status_changes(&self) -> event::Receiver {
         self.event_monitor.subscribe()
     }
 
+    /// Change quota of the `group` at runtime. The distribution of work converges to the new
+    /// quota over the `horizon`.
+    pub async fn set_quota(
+        &self,
+        group: &Arc<Group>,
+        quota: usize,
+        horizon: time::Duration,
+    ) -> Result<(), error::Client> {
+        let old_quota = self
+            .group_registry
+            .lock()
+            .await
+            .set_quota(group, quota, horizon)?;
+        info!(
+            "Quota of group '{}' changed from {} to {} (horizon {}s)",
+            group.descriptor.name,
+            old_quota,
+            quota,
+            horizon.as_secs()
+        );
+        // Immediately notify the scheduler about new share ratios
+        group.event_sender.notify();
+        Ok(())
+    }
+
     #[inline]
     pub async fn create_group(
         &self,
@@ -605,7 +1388,7 @@ impl Manager {
         self.group_registry
             .lock()
             .await
-            .create_group(descriptor, self.midstate_count)
+            .create_group(descriptor, self.midstate_count())
     }
 
     pub async fn create_or_get_default_group(&self) -> Arc<Group> {
@@ -613,7 +1396,7 @@ impl Manager {
         match group_registry.get_group(GroupDescriptor::DEFAULT_INDEX) {
             Some(group) => group,
             None => group_registry
-                .create_group(Default::default(), self.midstate_count)
+                .create_group(Default::default(), self.midstate_count())
                 .expect("BUG: cannot create default group"),
         }
     }
@@ -630,4 +1413,588 @@ impl Manager {
     pub async fn get_groups(&self) -> Vec<Arc<Group>> {
         self.group_registry.lock().await.get_groups()
     }
+
+    /// Stop all clients and unregister them together with their groups (including the private
+    /// ones). It is used when the backend is being rebuilt because it registers its clients again.
+    pub async fn remove_groups(&self) {
+        let groups = self.group_registry.lock().await.remove_groups();
+        for group in groups {
+            for client in group.get_clients().await {
+                client.stop();
+            }
+            info!("Group '{}' has been removed", group.descriptor.name);
+        }
+    }
+
+    /// Find the client selected by `selector` together with the group which it belongs to
+    async fn find_client(&self, selector: ClientSelector<'_>) -> Option<(Arc<Group>, Arc<Handle>)> {
+        let mut idx = 0;
+        for group in self.get_groups().await {
+            for client in group.get_clients().await {
+                let found = match selector {
+                    ClientSelector::Index(client_idx) => client_idx == idx,
+                    ClientSelector::Descriptor(descriptor) => {
+                        client.descriptor().await == *descriptor
+                    }
+                };
+                if found {
+                    return Some((group, client));
+                }
+                idx += 1;
+            }
+        }
+        None
+    }
+
+    /// Find the group which the origin client of the `solution` has belonged to before it has
+    /// been removed
+    pub async fn find_former_group(&self, solution: &work::Solution) -> Option<Arc<Group>> {
+        self.group_registry.lock().await.find_former_group(solution)
+    }
+
+    /// Enable or disable the client at runtime. Disabled client is stopped but it stays
+    /// registered so its statistics are still available. Returns `false` when the client has
+    /// already been in the requested state.
+    pub async fn set_client_enabled<'a, T>(
+        &self,
+        client: T,
+        enabled: bool,
+    ) -> Result<bool, error::Client>
+    where
+        T: Into<ClientSelector<'a>>,
+    {
+        let (group, client) = self
+            .find_client(client.into())
+            .await
+            .ok_or(error::Client::Missing)?;
+        let changed = if enabled {
+            client.try_enable().is_ok()
+        } else {
+            client.try_disable().is_ok()
+        };
+        if changed {
+            if !enabled {
+                // Work already distributed to backends must not be solved for the stopped client
+                client.engine_sender.invalidate();
+            }
+            info!(
+                "{} Pool '{}' has been {}",
+                client.log_prefix(),
+                client.display_name(),
+                if enabled { "enabled" } else { "disabled" }
+            );
+            // Immediately reschedule jobs to another enabled pool
+            group.event_sender.notify();
+        }
+        Ok(changed)
+    }
+
+    /// Register a new client in the default group. The client is started immediately when its
+    /// descriptor is enabled.
+    pub async fn add_client(
+        &self,
+        descriptor: ClientDescriptor,
+        backend_info: Option<hal::BackendInfo>,
+    ) -> Arc<Handle> {
+        let group = self.create_or_get_default_group().await;
+        let client = group
+            .push_client(Handle::new(descriptor, &self.services, backend_info, None))
+            .await;
+        info!(
+            "{} Pool '{}' has been added",
+            client.log_prefix(),
+            client.descriptor().await.get_labelled_url()
+        );
+        client
+    }
+
+    /// Unregister the client and stop it. The last remaining client cannot be removed because
+    /// the miner would have no source of jobs.
+    pub async fn remove_client<'a, T>(&self, client: T) -> Result<Arc<Handle>, error::Client>
+    where
+        T: Into<ClientSelector<'a>>,
+    {
+        let mut client_count = 0;
+        for group in self.get_groups().await {
+            client_count += group.len().await;
+        }
+        let (group, client) = self
+            .find_client(client.into())
+            .await
+            .ok_or(error::Client::Missing)?;
+        if client_count <= 1 {
+            Err(error::Client::LastClient)?;
+        }
+        let client = group.remove_client(&client).await?;
+        info!(
+            "{} Pool '{}' has been removed",
+            client.log_prefix(),
+            client.descriptor().await.get_labelled_url()
+        );
+        Ok(client)
+    }
+
+    /// Test connectivity of the pool described by `descriptor` without registering any client.
+    /// The test uses its own connection so it can run concurrently with mining even when the
+    /// pool is in use.
+    pub async fn test_pool(
+        &self,
+        descriptor: &ClientDescriptor,
+        options: probe::Options,
+    ) -> Result<probe::Report, error::Client> {
+        let report = match descriptor.protocol {
+            ClientProtocol::StratumV1 | ClientProtocol::StratumV1Tls => {
+                stratum_v1::probe(
+                    &stratum_v1::ConnectionDetails::from_descriptor(descriptor),
+                    options,
+                )
+                .await
+            }
+            _ => Err(error::Client::UnsupportedProbe)?,
+        };
+        info!(
+            "Connectivity test of pool '{}' {}",
+            descriptor.get_labelled_url(),
+            report
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use ii_async_compat::tokio;
+
+    fn next_work(engine_receiver: &mut work::EngineReceiver) -> work::Assignment {
+        let engine = futures::executor::block_on(engine_receiver.get_engine())
+            .expect("BUG: missing work engine");
+        engine.next_work().unwrap()
+    }
+
+    /// Verify that change of midstate count is propagated to the work generated from client jobs
+    /// and that solutions for work generated before the change are still processed correctly
+    #[tokio::test]
+    async fn test_set_midstate_count() {
+        let client_manager = Manager::new(4);
+        let group = client_manager.create_or_get_default_group().await;
+        let descriptor = ClientDescriptor::create(
+            "drain://localhost",
+            &ClientUserInfo::new("test", None),
+            false,
+        )
+        .expect("BUG: cannot create client descriptor");
+        let client_handle = group
+            .push_client(Handle::new(
+                descriptor,
+                client_manager.services(),
+                None,
+                None,
+            ))
+            .await;
+
+        // Redirect engines from the client to local receiver
+        let (engine_sender, mut engine_receiver) = work::engine_channel(work::IgnoreEvents);
+        client_handle.engine_sender.swap_sender(&engine_sender);
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        client_handle
+            .engine_sender
+            .broadcast_job(Arc::new(*test_block));
+        let old_work = next_work(&mut engine_receiver);
+        assert_eq!(old_work.midstates.len(), 4);
+
+        client_manager.set_midstate_count(1).await;
+        assert_eq!(client_manager.midstate_count(), 1);
+        assert_eq!(group.midstate_count(), 1);
+
+        client_handle
+            .engine_sender
+            .broadcast_job(Arc::new(*test_block));
+        let new_work = next_work(&mut engine_receiver);
+        assert_eq!(new_work.midstates.len(), 1);
+
+        // Stale solution carries its own midstates so it can still be processed
+        let stale_solution = work::Solution::new(
+            old_work,
+            test_utils::TestSolution::new(test_block).with_midstate_idx(3),
+            None,
+        );
+        assert!(!stale_solution.is_malformed());
+        let _ = stale_solution.get_block_header();
+
+        // The same midstate index does not exist in the work generated after the change
+        let solution = work::Solution::new(
+            new_work,
+            test_utils::TestSolution::new(test_block).with_midstate_idx(3),
+            None,
+        );
+        assert!(solution.is_malformed());
+    }
+
+    /// Clients share services of their own manager (core) only
+    #[tokio::test]
+    async fn test_separate_services() {
+        let client_managers = [Manager::new(1), Manager::new(1)];
+        for client_manager in client_managers.iter() {
+            let descriptor = ClientDescriptor::create(
+                "drain://localhost",
+                &ClientUserInfo::new("test", None),
+                false,
+            )
+            .expect("BUG: cannot create client descriptor");
+            let services = client_manager.services();
+            Handle::with_node(descriptor, services, |_, job_solver| {
+                assert!(Arc::ptr_eq(&job_solver.bus(), &services.bus));
+                assert!(Arc::ptr_eq(
+                    &job_solver.block_archive(),
+                    &services.block_archive
+                ));
+                Arc::new(drain::Client::new(job_solver))
+            });
+        }
+
+        let services: Vec<_> = client_managers
+            .iter()
+            .map(|client_manager| client_manager.services())
+            .collect();
+        assert!(!Arc::ptr_eq(&services[0].bus, &services[1].bus));
+        assert!(!Arc::ptr_eq(
+            &services[0].block_tracker,
+            &services[1].block_tracker
+        ));
+        assert!(!Arc::ptr_eq(
+            &services[0].block_archive,
+            &services[1].block_archive
+        ));
+        assert!(!Arc::ptr_eq(
+            &services[0].job_registry,
+            &services[1].job_registry
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_client_enabled() {
+        let client_manager = Manager::new(1);
+        let group = client_manager.create_or_get_default_group().await;
+        let mut descriptors = vec![];
+        for url in &["drain://pool-a", "drain://pool-b"] {
+            let descriptor =
+                ClientDescriptor::create(url, &ClientUserInfo::new("test", None), false)
+                    .expect("BUG: cannot create client descriptor");
+            group
+                .push_client(Handle::new(
+                    descriptor.clone(),
+                    client_manager.services(),
+                    None,
+                    None,
+                ))
+                .await;
+            descriptors.push(descriptor);
+        }
+        let clients = group.get_clients().await;
+        assert!(clients.iter().all(|client| !client.is_enabled()));
+
+        assert_eq!(
+            client_manager.set_client_enabled(1usize, true).await,
+            Ok(true)
+        );
+        assert_eq!(
+            client_manager.set_client_enabled(1usize, true).await,
+            Ok(false)
+        );
+        assert!(!clients[0].is_enabled());
+        assert!(clients[1].is_enabled());
+
+        // Disabled client is kept registered and its work is invalidated
+        assert_eq!(
+            client_manager
+                .set_client_enabled(&descriptors[1], false)
+                .await,
+            Ok(true)
+        );
+        assert!(!clients[1].is_enabled());
+        assert!(clients[1].engine_sender.is_exhausted());
+        assert_eq!(group.len().await, 2);
+        assert_eq!(
+            client_manager
+                .set_client_enabled(&descriptors[1], false)
+                .await,
+            Ok(false)
+        );
+
+        assert_eq!(
+            client_manager.set_client_enabled(2usize, true).await,
+            Err(error::Client::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_remove_client() {
+        let client_manager = Manager::new(1);
+        let mut descriptors = vec![];
+        for url in &["drain://pool-a", "drain://pool-b"] {
+            let descriptor =
+                ClientDescriptor::create(url, &ClientUserInfo::new("test", None), true)
+                    .expect("BUG: cannot create client descriptor");
+            let client = client_manager.add_client(descriptor.clone(), None).await;
+            assert!(client.is_enabled());
+            descriptors.push(descriptor);
+        }
+        let group = client_manager
+            .get_default_group()
+            .await
+            .expect("BUG: missing default group");
+        assert_eq!(group.len().await, 2);
+
+        let client = client_manager
+            .remove_client(&descriptors[0])
+            .await
+            .expect("BUG: cannot remove client");
+        assert_eq!(client.descriptor().await, descriptors[0]);
+        assert!(!client.is_enabled());
+        assert_eq!(group.len().await, 1);
+        assert_eq!(
+            client_manager.remove_client(&descriptors[0]).await,
+            Err(error::Client::Missing)
+        );
+
+        // The only remaining client has to be kept
+        assert_eq!(
+            client_manager.remove_client(0usize).await,
+            Err(error::Client::LastClient)
+        );
+        assert_eq!(group.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_quota() {
+        let client_manager = Manager::new(1);
+        let group_a = client_manager
+            .create_group(GroupDescriptor::new(
+                "A".to_string(),
+                false,
+                LoadBalanceStrategy::Quota(1),
+            ))
+            .await
+            .expect("BUG: cannot create group");
+        let group_b = client_manager
+            .create_group(GroupDescriptor::new(
+                "B".to_string(),
+                false,
+                LoadBalanceStrategy::Quota(3),
+            ))
+            .await
+            .expect("BUG: cannot create group");
+        let group_fixed = client_manager
+            .create_group(GroupDescriptor::new(
+                "Fixed".to_string(),
+                false,
+                LoadBalanceStrategy::FixedShareRatio(0.2),
+            ))
+            .await
+            .expect("BUG: cannot create group");
+
+        client_manager
+            .set_quota(&group_a, 0, Manager::DEFAULT_QUOTA_HORIZON)
+            .await
+            .expect("BUG: cannot set quota");
+        assert_eq!(group_a.get_quota(), Some(0));
+        assert_eq!(
+            client_manager
+                .set_quota(&group_b, 0, Manager::DEFAULT_QUOTA_HORIZON)
+                .await,
+            Err(error::Client::ZeroTotalQuota)
+        );
+        assert_eq!(group_b.get_quota(), Some(3));
+        assert_eq!(
+            client_manager
+                .set_quota(&group_fixed, 1, Manager::DEFAULT_QUOTA_HORIZON)
+                .await,
+            Err(error::Client::FixedShareRatioGroup)
+        );
+
+        // Group from other manager is not registered
+        let foreign_group = Manager::new(1).create_or_get_default_group().await;
+        assert_eq!(
+            client_manager
+                .set_quota(&foreign_group, 1, Manager::DEFAULT_QUOTA_HORIZON)
+                .await,
+            Err(error::Client::Missing)
+        );
+
+        // Share ratios are applied immediately without horizon
+        client_manager
+            .set_quota(&group_a, 1, time::Duration::from_secs(0))
+            .await
+            .expect("BUG: cannot set quota");
+        let group_registry = client_manager.group_registry.lock().await;
+        assert!(group_registry.quota_transition.is_none());
+        for (scheduler_group_handle, share_ratio) in group_registry.iter().zip(&[0.2, 0.6, 0.2]) {
+            assert!((scheduler_group_handle.share_ratio - share_ratio).abs() < 1e-9);
+            assert_eq!(
+                scheduler_group_handle.previous_share_ratio,
+                scheduler_group_handle.share_ratio
+            );
+        }
+    }
+
+    fn group_config(name: &str, quota: usize, pools: &[(&str, &str)]) -> GroupConfig {
+        GroupConfig {
+            descriptor: GroupDescriptor::new(
+                name.to_string(),
+                false,
+                LoadBalanceStrategy::Quota(quota),
+            ),
+            pools: Some(
+                pools
+                    .iter()
+                    .map(|(user, label)| PoolConfig {
+                        enabled: None,
+                        url: "drain://localhost".to_string(),
+                        user: user.to_string(),
+                        password: None,
+                        network: None,
+                        label: Some(label.to_string()),
+                        name: None,
+                        reject_breaker: None,
+                        coinbase_check: None,
+                        share_policy: None,
+                        cert_expiry_warning: None,
+                        ca_cert: None,
+                        insecure: None,
+                        min_difficulty: None,
+                        fixed_difficulty: None,
+                        dns_ttl: None,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    async fn get_users(group: &Group) -> Vec<String> {
+        let mut users = vec![];
+        for client_handle in group.get_clients().await {
+            users.push(client_handle.descriptor().await.user);
+        }
+        users
+    }
+
+    #[tokio::test]
+    async fn test_reload_config() {
+        let client_manager = Manager::new(1);
+        client_manager
+            .load_config(
+                vec![
+                    group_config("A", 1, &[("a1", "x"), ("a2", "x"), ("a3", "x")]),
+                    group_config("B", 1, &[("b1", "x")]),
+                ],
+                None,
+                false,
+            )
+            .await
+            .expect("BUG: cannot load config");
+        let groups = client_manager.get_groups().await;
+        let a2 = groups[0].get_clients().await[1].clone();
+
+        client_manager
+            .reload_config(
+                vec![
+                    group_config("A", 2, &[("a4", "x"), ("a2", "y"), ("a1", "x")]),
+                    group_config("C", 1, &[("c1", "x")]),
+                ],
+                None,
+                false,
+            )
+            .await
+            .expect("BUG: cannot reload config");
+
+        let groups = client_manager.get_groups().await;
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].get_quota(), Some(2));
+        assert_eq!(get_users(&groups[0]).await, vec!["a4", "a2", "a1"]);
+        // Client with changed label only is still the same running client
+        let client_handle = groups[0].get_clients().await[1].clone();
+        assert!(Arc::ptr_eq(&client_handle, &a2));
+        assert_eq!(
+            client_handle.descriptor().await.label,
+            Some("y".to_string())
+        );
+        // Removed group cannot be deleted at runtime but its clients are dropped
+        assert_eq!(groups[1].descriptor.name, "B");
+        assert!(groups[1].is_empty().await);
+        assert_eq!(groups[2].descriptor.name, "C");
+        assert_eq!(get_users(&groups[2]).await, vec!["c1"]);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_rename() {
+        // Display names are unique within the whole process so the host is unique to this test
+        let configs = |names: &[Option<&str>]| {
+            let mut config = group_config("R", 1, &[("r1", "x"), ("r2", "x"), ("r3", "x")]);
+            for (pool_config, name) in config.pools.iter_mut().flatten().zip(names) {
+                pool_config.url = "drain://rename.test".to_string();
+                pool_config.name = name.map(str::to_string);
+            }
+            vec![config]
+        };
+        async fn get_names(client_manager: &Manager) -> Vec<String> {
+            let mut names = vec![];
+            for client_handle in client_manager.get_groups().await[0].get_clients().await {
+                names.push(client_handle.display_name());
+            }
+            names
+        }
+
+        let client_manager = Manager::new(1);
+        client_manager
+            .load_config(configs(&[Some("Main"), Some("Main"), None]), None, false)
+            .await
+            .expect("BUG: cannot load config");
+        // duplicates are suffixed in the order of pools and the default name is shortened URL
+        assert_eq!(
+            get_names(&client_manager).await,
+            vec!["Main", "Main (2)", "r3@rename.test"]
+        );
+        let clients = client_manager.get_groups().await[0].get_clients().await;
+        clients[0].stats().submissions().unresolved.add(3);
+        clients[1].stats().submissions().unresolved.add(5);
+
+        client_manager
+            .reload_config(
+                configs(&[Some("Primary"), Some("Main"), Some("Backup")]),
+                None,
+                false,
+            )
+            .await
+            .expect("BUG: cannot reload config");
+        // renamed clients keep running and the released name is taken by the suffixed client
+        let renamed_clients = client_manager.get_groups().await[0].get_clients().await;
+        for (client, renamed_client) in clients.iter().zip(&renamed_clients) {
+            assert!(Arc::ptr_eq(client, renamed_client));
+        }
+        assert_eq!(
+            get_names(&client_manager).await,
+            vec!["Primary", "Main", "Backup"]
+        );
+        assert_eq!(clients[0].node().to_string(), "Primary");
+        assert_eq!(clients[0].log_prefix().to_string(), "[client=Primary]");
+
+        // statistics are reported under the new names without resetting counters
+        let sample = digest::take_sample(&client_manager).await;
+        let names_counters: Vec<_> = sample
+            .values()
+            .map(|pool| (pool.name.clone().unwrap(), pool.counters.unresolved))
+            .collect();
+        assert_eq!(
+            names_counters,
+            vec![
+                ("Primary".to_string(), 3),
+                ("Main".to_string(), 5),
+                ("Backup".to_string(), 0)
+            ]
+        );
+    }
 }
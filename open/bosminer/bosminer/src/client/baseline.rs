@@ -0,0 +1,563 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Detection of regressions of reject and stale rates of pools (e.g. after a firmware update).
+//!
+//! Share counters of the last few hours are compared against a long-horizon baseline computed
+//! from the persistent daily digest. The current rate is considered to be regressed only when it
+//! exceeds the baseline multiplied by a configurable factor and the difference is statistically
+//! significant according to a one-sided test of proportion.
+
+use super::digest::{Counters, DayRecord, PoolId, Sample};
+use crate::bus;
+
+use chrono::NaiveDate;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Time span of the current window compared against the baseline
+pub const WINDOW: time::Duration = time::Duration::from_secs(6 * 60 * 60);
+/// Number of finished days forming the baseline
+pub const BASELINE_DAYS: usize = 7;
+
+/// One-sided critical value of the standard normal distribution for 99% confidence
+const Z_CRITICAL: f64 = 2.326;
+/// The normal approximation of the binomial distribution is not valid when the expected number
+/// of failed or successful shares is lower
+const MIN_EXPECTED_SHARES: f64 = 5.0;
+/// Baseline with fewer shares does not say much about the usual rate of the pool
+const MIN_BASELINE_SHARES: u64 = 100;
+
+/// Number of failed (rejected or stale) shares out of all submitted shares
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rate {
+    pub failed: u64,
+    pub total: u64,
+}
+
+impl Rate {
+    fn total(counters: &Counters) -> u64 {
+        counters.accepted + counters.rejected + counters.stale
+    }
+
+    pub fn rejected(counters: &Counters) -> Self {
+        Self {
+            failed: counters.rejected,
+            total: Self::total(counters),
+        }
+    }
+
+    pub fn stale(counters: &Counters) -> Self {
+        Self {
+            failed: counters.stale,
+            total: Self::total(counters),
+        }
+    }
+
+    /// Return the rate in percent (zero when nothing has been submitted)
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.failed as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Result of comparison of the current rate against the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// There are not enough shares to establish significance either way
+    Insufficient,
+    Normal,
+    /// The current rate significantly exceeds the baseline multiplied by the factor
+    Regressed,
+}
+
+/// Test whether the `current` rate significantly exceeds the `baseline` rate multiplied by
+/// `factor`. The baseline rate is smoothed so that a pool without any failed share in the
+/// baseline does not make every single failure significant.
+pub fn compare(baseline: Rate, current: Rate, factor: f64) -> Verdict {
+    if baseline.total < MIN_BASELINE_SHARES || current.total == 0 {
+        return Verdict::Insufficient;
+    }
+    let baseline_ratio = (baseline.failed as f64 + 1.0) / (baseline.total as f64 + 2.0);
+    let threshold = (baseline_ratio * factor).min(1.0);
+    let total = current.total as f64;
+    if total * threshold < MIN_EXPECTED_SHARES || total * (1.0 - threshold) < MIN_EXPECTED_SHARES {
+        return Verdict::Insufficient;
+    }
+
+    let ratio = current.failed as f64 / total;
+    let z = (ratio - threshold) / (threshold * (1.0 - threshold) / total).sqrt();
+    if z > Z_CRITICAL {
+        Verdict::Regressed
+    } else {
+        Verdict::Normal
+    }
+}
+
+/// Sum counters of each pool over `BASELINE_DAYS` finished days preceding `today`
+pub fn baseline_counters(records: &[DayRecord], today: NaiveDate) -> BTreeMap<PoolId, Counters> {
+    let first_date = today - chrono::Duration::days(BASELINE_DAYS as i64);
+    let mut pools = BTreeMap::new();
+    for record in records {
+        match record.date() {
+            Some(date) if date >= first_date && date < today => {}
+            _ => continue,
+        }
+        for pool in &record.pools {
+            *pools
+                .entry(pool.id.clone())
+                .or_insert_with(Counters::default) += pool.counters;
+        }
+    }
+    pools
+}
+
+/// Sliding window of share counters built from samples of total counters
+#[derive(Debug, Default)]
+pub struct Window {
+    /// The last sampled total counters
+    last: BTreeMap<PoolId, Counters>,
+    /// Increments of counters between consecutive samples
+    increments: VecDeque<(time::Instant, BTreeMap<PoolId, Counters>)>,
+}
+
+impl Window {
+    /// Account new sample of total counters and drop increments older than `WINDOW`
+    pub fn update(&mut self, sample: &Sample, now: time::Instant) {
+        let mut increment = BTreeMap::new();
+        for (id, pool) in sample {
+            let counters = pool.counters;
+            let delta = match self.last.get(id) {
+                // the client has been reset and it starts from zero
+                Some(last) if counters.is_behind(last) => counters,
+                Some(last) => counters.saturating_sub(last),
+                None => counters,
+            };
+            increment.insert(id.clone(), delta);
+            self.last.insert(id.clone(), counters);
+        }
+        self.last.retain(|id, _| sample.contains_key(id));
+        self.increments.push_back((now, increment));
+
+        while let Some((time, _)) = self.increments.front() {
+            if now.saturating_duration_since(*time) < WINDOW {
+                break;
+            }
+            self.increments.pop_front();
+        }
+    }
+
+    /// Sum of counters of each pool over the window
+    pub fn counters(&self) -> BTreeMap<PoolId, Counters> {
+        let mut pools = BTreeMap::new();
+        for (_, increment) in &self.increments {
+            for (id, counters) in increment {
+                *pools.entry(id.clone()).or_insert_with(Counters::default) += *counters;
+            }
+        }
+        pools
+    }
+}
+
+/// Current window of one pool compared against its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub id: PoolId,
+    pub label: Option<String>,
+    pub baseline: Counters,
+    pub current: Counters,
+    pub reject: Verdict,
+    pub stale: Verdict,
+}
+
+impl Comparison {
+    pub fn new(
+        id: PoolId,
+        label: Option<String>,
+        baseline: Counters,
+        current: Counters,
+        factor: f64,
+    ) -> Self {
+        Self {
+            reject: compare(Rate::rejected(&baseline), Rate::rejected(&current), factor),
+            stale: compare(Rate::stale(&baseline), Rate::stale(&current), factor),
+            id,
+            label,
+            baseline,
+            current,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackerInner {
+    window: Window,
+    comparisons: Vec<Comparison>,
+}
+
+/// Keeps the current window of all pools and the result of the last comparison
+#[derive(Debug)]
+pub struct Tracker {
+    /// The current rate has to exceed the baseline by this factor to be considered regressed
+    factor: f64,
+    inner: StdMutex<TrackerInner>,
+    bus: Arc<bus::Bus>,
+}
+
+impl Tracker {
+    pub fn new(factor: f64) -> Self {
+        Self {
+            factor,
+            inner: Default::default(),
+            bus: Arc::new(bus::Bus::new()),
+        }
+    }
+
+    /// Publish alerts to the `bus` shared with other components instead of its own one
+    pub fn with_bus(mut self, bus: Arc<bus::Bus>) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    fn lock_inner(&self) -> StdMutexGuard<'_, TrackerInner> {
+        self.inner
+            .lock()
+            .expect("BUG: cannot lock baseline tracker")
+    }
+
+    /// Account new `sample` and compare the current window against the baseline computed from
+    /// digest `records` preceding `today`. An alert is published when a rate of some pool becomes
+    /// regressed.
+    pub fn update(
+        &self,
+        sample: &Sample,
+        records: &[DayRecord],
+        today: NaiveDate,
+        now: time::Instant,
+    ) {
+        let baseline = baseline_counters(records, today);
+        let mut inner = self.lock_inner();
+        inner.window.update(sample, now);
+
+        let comparisons: Vec<_> = inner
+            .window
+            .counters()
+            .into_iter()
+            .map(|(id, current)| {
+                let label = sample.get(&id).and_then(|pool| pool.label.clone());
+                let baseline = baseline.get(&id).cloned().unwrap_or_default();
+                Comparison::new(id, label, baseline, current, self.factor)
+            })
+            .collect();
+
+        // Alerts refer to pools by their display names
+        let pool_name = |id: &PoolId| {
+            sample
+                .get(id)
+                .and_then(|pool| pool.name.clone())
+                .unwrap_or_else(|| id.url.clone())
+        };
+        for comparison in &comparisons {
+            let previous = inner
+                .comparisons
+                .iter()
+                .find(|previous| previous.id == comparison.id);
+            let was_regressed = |verdict: fn(&Comparison) -> Verdict| {
+                previous.map_or(false, |previous| verdict(previous) == Verdict::Regressed)
+            };
+            if comparison.reject == Verdict::Regressed && !was_regressed(|c| c.reject) {
+                self.bus
+                    .publish(bus::Event::Alert(bus::Alert::RejectRateRegressed {
+                        pool: pool_name(&comparison.id),
+                        rate: Rate::rejected(&comparison.current).percent(),
+                        baseline: Rate::rejected(&comparison.baseline).percent(),
+                    }));
+            }
+            if comparison.stale == Verdict::Regressed && !was_regressed(|c| c.stale) {
+                self.bus
+                    .publish(bus::Event::Alert(bus::Alert::StaleRateRegressed {
+                        pool: pool_name(&comparison.id),
+                        rate: Rate::stale(&comparison.current).percent(),
+                        baseline: Rate::stale(&comparison.baseline).percent(),
+                    }));
+            }
+        }
+        inner.comparisons = comparisons;
+    }
+
+    /// Result of the last comparison of all pools
+    pub fn comparisons(&self) -> Vec<Comparison> {
+        self.lock_inner().comparisons.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::digest::{self, PoolRecord, PoolSample};
+
+    use std::iter;
+
+    fn pool(idx: usize) -> PoolId {
+        PoolId {
+            url: format!("stratum+tcp://pool{}.example.com:3333", idx),
+            user: "user".to_string(),
+        }
+    }
+
+    fn counters(accepted: u64, rejected: u64, stale: u64) -> Counters {
+        Counters {
+            accepted,
+            rejected,
+            stale,
+            ..Default::default()
+        }
+    }
+
+    fn sample(pools: &[(usize, Counters)]) -> Sample {
+        pools
+            .iter()
+            .map(|(idx, counters)| {
+                (
+                    pool(*idx),
+                    PoolSample {
+                        label: None,
+                        name: None,
+                        counters: *counters,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn day_record(date: NaiveDate, pools: &[(usize, Counters)]) -> DayRecord {
+        DayRecord {
+            date: date.format(digest::DATE_FORMAT).to_string(),
+            partial: false,
+            total: pools
+                .iter()
+                .fold(Counters::default(), |total, (_, counters)| {
+                    total + *counters
+                }),
+            pools: pools
+                .iter()
+                .map(|(idx, counters)| PoolRecord {
+                    id: pool(*idx),
+                    label: None,
+                    counters: *counters,
+                })
+                .collect(),
+        }
+    }
+
+    fn rate(failed: u64, total: u64) -> Rate {
+        Rate { failed, total }
+    }
+
+    #[test]
+    fn test_compare() {
+        // 1% baseline with the factor 2
+        let baseline = rate(10, 1000);
+        assert_eq!(compare(baseline, rate(50, 1000), 2.0), Verdict::Regressed);
+        // above the threshold but not significantly
+        assert_eq!(compare(baseline, rate(25, 1000), 2.0), Verdict::Normal);
+        assert_eq!(compare(baseline, rate(5, 1000), 2.0), Verdict::Normal);
+        // the same rate is regressed only with lower factor
+        assert_eq!(compare(baseline, rate(50, 1000), 5.0), Verdict::Normal);
+        assert_eq!(compare(baseline, rate(50, 1000), 1.0), Verdict::Regressed);
+
+        // all shares of the current window have failed
+        assert_eq!(compare(baseline, rate(1000, 1000), 2.0), Verdict::Regressed);
+        // the threshold cannot be exceeded
+        assert_eq!(
+            compare(rate(500, 1000), rate(1000, 1000), 3.0),
+            Verdict::Insufficient
+        );
+    }
+
+    #[test]
+    fn test_compare_low_volume() {
+        let baseline = rate(10, 1000);
+        // nothing submitted in the current window
+        assert_eq!(compare(baseline, rate(0, 0), 2.0), Verdict::Insufficient);
+        // 10% is a lot but there are too few shares to tell
+        assert_eq!(compare(baseline, rate(10, 100), 2.0), Verdict::Insufficient);
+        assert_eq!(compare(baseline, rate(1, 1), 2.0), Verdict::Insufficient);
+        // the same rate with enough shares
+        assert_eq!(compare(baseline, rate(100, 1000), 2.0), Verdict::Regressed);
+
+        // baseline is too short (e.g. the first day after installation)
+        assert_eq!(
+            compare(rate(0, 99), rate(500, 1000), 2.0),
+            Verdict::Insufficient
+        );
+        assert_eq!(
+            compare(rate(0, 0), rate(500, 1000), 2.0),
+            Verdict::Insufficient
+        );
+
+        // single failure is not significant for a pool without failures in the baseline
+        let clean = rate(0, 10_000);
+        assert_eq!(compare(clean, rate(1, 1000), 2.0), Verdict::Insufficient);
+        assert_eq!(compare(clean, rate(5, 30_000), 2.0), Verdict::Normal);
+        assert_eq!(compare(clean, rate(30, 30_000), 2.0), Verdict::Regressed);
+    }
+
+    #[test]
+    fn test_rate() {
+        let counters = counters(90, 6, 4);
+        assert_eq!(Rate::rejected(&counters), rate(6, 100));
+        assert_eq!(Rate::stale(&counters), rate(4, 100));
+        assert_eq!(Rate::rejected(&counters).percent(), 6.0);
+        assert_eq!(Rate::stale(&Counters::default()).percent(), 0.0);
+    }
+
+    #[test]
+    fn test_baseline_counters() {
+        let today = NaiveDate::from_ymd(2020, 1, 15);
+        let day = |offset: i64| today - chrono::Duration::days(offset);
+        let records: Vec<_> = (0..=8)
+            .map(|offset| day_record(day(offset), &[(0, counters(100, offset as u64, 1))]))
+            .chain(iter::once(day_record(day(3), &[(1, counters(10, 1, 0))])))
+            .collect();
+
+        let baseline = baseline_counters(&records, today);
+        // neither today nor the days older than a week are part of the baseline
+        assert_eq!(
+            baseline[&pool(0)],
+            counters(700, 1 + 2 + 3 + 4 + 5 + 6 + 7, 7)
+        );
+        assert_eq!(baseline[&pool(1)], counters(10, 1, 0));
+        assert_eq!(baseline.len(), 2);
+
+        assert!(baseline_counters(&records, day(9)).is_empty());
+    }
+
+    #[test]
+    fn test_window() {
+        const STEP: time::Duration = time::Duration::from_secs(10 * 60);
+
+        let start = time::Instant::now();
+        let mut window = Window::default();
+        let mut accepted = 0;
+        // each step submits 100 shares where one of them is rejected
+        for step in 0..72 {
+            accepted += 99;
+            let rejected = (step + 1) as u64;
+            window.update(
+                &sample(&[(0, counters(accepted, rejected, 0))]),
+                start + STEP * step,
+            );
+        }
+        // only the last 6 hours are kept
+        let steps = WINDOW.as_secs() / STEP.as_secs();
+        assert_eq!(window.counters()[&pool(0)], counters(99 * steps, steps, 0));
+
+        // the client has been reset and the new pool appears
+        window.update(
+            &sample(&[(0, counters(10, 1, 0)), (1, counters(5, 0, 5))]),
+            start + STEP * 72,
+        );
+        let current = window.counters();
+        assert_eq!(current[&pool(0)], counters(99 * (steps - 1) + 10, steps, 0));
+        assert_eq!(current[&pool(1)], counters(5, 0, 5));
+
+        // removed pools do not contribute to new increments
+        window.update(&sample(&[(1, counters(15, 0, 5))]), start + STEP * 73);
+        let current = window.counters();
+        assert_eq!(current[&pool(1)], counters(15, 0, 5));
+        assert_eq!(
+            current[&pool(0)],
+            counters(99 * (steps - 2) + 10, steps - 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_tracker() {
+        const STEP: time::Duration = time::Duration::from_secs(60);
+
+        let today = NaiveDate::from_ymd(2020, 1, 15);
+        // both pools had 1% of rejected and 1% of stale shares in the baseline
+        let records: Vec<_> = (1..=7)
+            .map(|offset| {
+                day_record(
+                    today - chrono::Duration::days(offset),
+                    &[(0, counters(980, 10, 10)), (1, counters(980, 10, 10))],
+                )
+            })
+            .collect();
+
+        let bus = Arc::new(bus::Bus::new());
+        let mut alerts = bus.subscribe();
+        let tracker = Tracker::new(2.0).with_bus(bus);
+        let start = time::Instant::now();
+        // the first pool gets 10% of rejected shares after the update
+        let mut pools = [counters(0, 0, 0), counters(0, 0, 0)];
+        for step in 0..30 {
+            pools[0] += counters(90, 10, 0);
+            pools[1] += counters(98, 1, 1);
+            tracker.update(
+                &sample(&[(0, pools[0]), (1, pools[1])]),
+                &records,
+                today,
+                start + STEP * step,
+            );
+            let comparisons = tracker.comparisons();
+            assert_eq!(comparisons.len(), 2);
+            assert_eq!(comparisons[0].baseline, counters(6860, 70, 70));
+            assert_eq!(comparisons[0].current, pools[0]);
+            assert_ne!(comparisons[0].stale, Verdict::Regressed);
+            assert_ne!(comparisons[1].reject, Verdict::Regressed);
+            assert_ne!(comparisons[1].stale, Verdict::Regressed);
+            // it takes a few samples to establish significance
+            if step == 0 {
+                assert_eq!(comparisons[0].reject, Verdict::Insufficient);
+            }
+        }
+        let comparisons = tracker.comparisons();
+        assert_eq!(comparisons[0].reject, Verdict::Regressed);
+        assert_eq!(comparisons[0].stale, Verdict::Normal);
+        assert_eq!(comparisons[1].reject, Verdict::Normal);
+        assert_eq!(comparisons[1].stale, Verdict::Normal);
+
+        // the regression is reported only once
+        match alerts.try_recv() {
+            Some(bus::Event::Alert(bus::Alert::RejectRateRegressed {
+                pool: name,
+                rate,
+                baseline,
+            })) => {
+                // pools without display name are referred to by URL
+                assert_eq!(name, pool(0).url);
+                assert!((rate - 10.0).abs() < 1e-9);
+                assert!((baseline - 1.0).abs() < 1e-9);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(alerts.try_recv(), None);
+    }
+}
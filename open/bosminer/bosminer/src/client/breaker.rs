@@ -0,0 +1,466 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Circuit breaker which soft-fails a pool rejecting too many shares. The breaker is driven by
+//! cumulative counters of accepted and rejected shares so it does not need any hook in the
+//! protocol implementation.
+
+use crate::clock;
+
+use bosminer_config::ClientRejectBreaker;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    /// The pool is used normally
+    Closed,
+    /// The pool has been soft-failed and it is not used until the cool-off period elapses
+    Open { until: time::Instant },
+    /// The pool is used again but it is fully restored only when the probe shares are not
+    /// rejected (base counters of the probe are stored)
+    Probing {
+        accepted: u64,
+        rejected: u64,
+        found: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Tripped,
+    Probing,
+    Restored,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Transition::Tripped => "tripped",
+            Transition::Probing => "probing",
+            Transition::Restored => "restored",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub system_time: time::SystemTime,
+    pub transition: Transition,
+    pub reason: String,
+}
+
+/// Cumulative share counters at a particular time
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    time: time::Instant,
+    accepted: u64,
+    rejected: u64,
+}
+
+#[derive(Debug)]
+pub struct RejectBreaker {
+    config: ClientRejectBreaker,
+    state: State,
+    /// Samples covering the rolling window (the first one is the base of the window)
+    samples: VecDeque<Sample>,
+    /// Bounded history of breaker transitions
+    history: VecDeque<Event>,
+    /// Number of shares found for the pool at the last update
+    found: u64,
+}
+
+impl RejectBreaker {
+    const HISTORY_LENGTH: usize = 32;
+
+    pub fn new(config: ClientRejectBreaker) -> Self {
+        Self {
+            config,
+            state: State::Closed,
+            samples: VecDeque::new(),
+            history: VecDeque::with_capacity(Self::HISTORY_LENGTH),
+            found: 0,
+        }
+    }
+
+    #[inline]
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Returns `false` when the pool is soft-failed and should not be used
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        match self.state {
+            State::Open { .. } => false,
+            State::Closed | State::Probing { .. } => true,
+        }
+    }
+
+    /// Returns `false` when no more work should be generated for the pool. It is either
+    /// soft-failed or all probe shares have already been found and the probe waits for
+    /// responses to them.
+    pub fn accepts_work(&self) -> bool {
+        match self.state {
+            State::Closed => true,
+            State::Open { .. } => false,
+            State::Probing { found, .. } => {
+                self.found.saturating_sub(found) < self.config.probe_shares()
+            }
+        }
+    }
+
+    pub fn history(&self) -> Vec<Event> {
+        self.history.iter().cloned().collect()
+    }
+
+    fn record(&mut self, transition: Transition, reason: String) -> Event {
+        let event = Event {
+            system_time: clock::system_time(),
+            transition,
+            reason,
+        };
+        if self.history.len() == Self::HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(event.clone());
+        event
+    }
+
+    fn trip(&mut self, reason: String, now: time::Instant) -> Event {
+        self.state = State::Open {
+            until: now + self.config.cool_off(),
+        };
+        self.samples.clear();
+        self.record(Transition::Tripped, reason)
+    }
+
+    fn is_over_threshold(&self, rejected: u64, total: u64) -> bool {
+        rejected as f64 > total as f64 * self.config.threshold()
+    }
+
+    /// Update the breaker with current cumulative counters of accepted and rejected shares and
+    /// of shares `found` for the pool (including those which still wait for a response).
+    /// Returns event describing transition of the breaker when its state has been changed.
+    pub fn update(
+        &mut self,
+        accepted: u64,
+        rejected: u64,
+        found: u64,
+        now: time::Instant,
+    ) -> Option<Event> {
+        self.found = found;
+        clock::check_instant(now);
+        if !self.config.is_enabled() {
+            return None;
+        }
+        match self.state {
+            State::Closed => {
+                let window = self.config.window();
+                self.samples.push_back(Sample {
+                    time: now,
+                    accepted,
+                    rejected,
+                });
+                // Keep the newest sample which is out of the window as a base
+                while self.samples.len() > 1 && now.duration_since(self.samples[1].time) >= window {
+                    self.samples.pop_front();
+                }
+                let base = *self.samples.front().expect("BUG: missing sample");
+                let window_rejected = rejected.saturating_sub(base.rejected);
+                let window_total = accepted.saturating_sub(base.accepted) + window_rejected;
+                if window_total >= self.config.min_shares()
+                    && self.is_over_threshold(window_rejected, window_total)
+                {
+                    let reason = format!(
+                        "{} of {} shares rejected in {} s",
+                        window_rejected,
+                        window_total,
+                        window.as_secs()
+                    );
+                    return Some(self.trip(reason, now));
+                }
+                None
+            }
+            State::Open { until } => {
+                if now < until {
+                    return None;
+                }
+                self.state = State::Probing {
+                    accepted,
+                    rejected,
+                    found,
+                };
+                let reason = format!(
+                    "cool-off period of {} s elapsed",
+                    self.config.cool_off().as_secs()
+                );
+                Some(self.record(Transition::Probing, reason))
+            }
+            State::Probing {
+                accepted: base_accepted,
+                rejected: base_rejected,
+                ..
+            } => {
+                let probe_shares = self.config.probe_shares();
+                let probe_rejected = rejected.saturating_sub(base_rejected);
+                let probe_total = accepted.saturating_sub(base_accepted) + probe_rejected;
+                let reason = format!(
+                    "{} of {} probe shares rejected",
+                    probe_rejected, probe_total
+                );
+                // Do not wait for the rest of the probe when the threshold is already exceeded
+                if self.is_over_threshold(probe_rejected, probe_shares) {
+                    Some(self.trip(reason, now))
+                } else if probe_total >= probe_shares {
+                    self.state = State::Closed;
+                    self.samples.push_back(Sample {
+                        time: now,
+                        accepted,
+                        rejected,
+                    });
+                    Some(self.record(Transition::Restored, reason))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_breaker() -> RejectBreaker {
+        RejectBreaker::new(ClientRejectBreaker {
+            enabled: Some(true),
+            threshold: Some(0.5),
+            min_shares: Some(10),
+            window: Some(60),
+            cool_off: Some(300),
+            probe_shares: Some(4),
+        })
+    }
+
+    /// Drive the breaker with a sequence of shares (`true` means accepted) submitted one per
+    /// second and return all transitions
+    fn submit(
+        breaker: &mut RejectBreaker,
+        counters: &mut (u64, u64),
+        shares: &[bool],
+        now: &mut time::Instant,
+    ) -> Vec<Transition> {
+        let mut transitions = vec![];
+        for accepted in shares {
+            if *accepted {
+                counters.0 += 1;
+            } else {
+                counters.1 += 1;
+            }
+            *now += time::Duration::from_secs(1);
+            let found = counters.0 + counters.1;
+            if let Some(event) = breaker.update(counters.0, counters.1, found, *now) {
+                transitions.push(event.transition);
+            }
+        }
+        transitions
+    }
+
+    fn trip(breaker: &mut RejectBreaker, counters: &mut (u64, u64), now: &mut time::Instant) {
+        // accepted shares do not trip the breaker even after long time
+        assert!(submit(breaker, counters, &[true; 100], now).is_empty());
+        assert_eq!(breaker.state(), State::Closed);
+        // all shares are rejected but the window contains also accepted ones
+        assert!(submit(breaker, counters, &[false; 30], now).is_empty());
+        assert_eq!(
+            submit(breaker, counters, &[false; 1], now),
+            vec![Transition::Tripped]
+        );
+        assert!(!breaker.is_available());
+    }
+
+    fn cool_off(breaker: &mut RejectBreaker, counters: &(u64, u64), now: &mut time::Instant) {
+        let found = counters.0 + counters.1;
+        *now += time::Duration::from_secs(299);
+        assert!(breaker
+            .update(counters.0, counters.1, found, *now)
+            .is_none());
+        assert!(!breaker.is_available());
+        *now += time::Duration::from_secs(1);
+        let event = breaker
+            .update(counters.0, counters.1, found, *now)
+            .expect("BUG: missing probing event");
+        assert_eq!(event.transition, Transition::Probing);
+        assert!(breaker.is_available());
+        assert!(breaker.accepts_work());
+    }
+
+    #[test]
+    fn test_reject_breaker_trip() {
+        let mut breaker = create_breaker();
+        let mut counters = (0, 0);
+        let mut now = time::Instant::now();
+
+        // the first update is the base of the window
+        assert!(breaker.update(0, 0, 0, now).is_none());
+        // minimal number of shares is required
+        assert!(submit(&mut breaker, &mut counters, &[false; 9], &mut now).is_empty());
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[false; 1], &mut now),
+            vec![Transition::Tripped]
+        );
+        assert_eq!(
+            breaker.history()[0].reason,
+            "10 of 10 shares rejected in 60 s"
+        );
+
+        let mut breaker = create_breaker();
+        trip(&mut breaker, &mut counters, &mut now);
+        // the pool stays soft-failed during cool-off period
+        assert!(submit(&mut breaker, &mut counters, &[true; 10], &mut now).is_empty());
+        assert!(!breaker.is_available());
+    }
+
+    #[test]
+    fn test_reject_breaker_probe_success() {
+        let mut breaker = create_breaker();
+        let mut counters = (0, 0);
+        let mut now = time::Instant::now();
+
+        trip(&mut breaker, &mut counters, &mut now);
+        cool_off(&mut breaker, &counters, &mut now);
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[true, false, true], &mut now),
+            vec![]
+        );
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[true], &mut now),
+            vec![Transition::Restored]
+        );
+        assert_eq!(breaker.state(), State::Closed);
+
+        // the window starts again after restoration
+        assert!(submit(&mut breaker, &mut counters, &[false; 9], &mut now).is_empty());
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[false; 1], &mut now),
+            vec![Transition::Tripped]
+        );
+        let transitions: Vec<_> = breaker
+            .history()
+            .iter()
+            .map(|event| event.transition)
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                Transition::Tripped,
+                Transition::Probing,
+                Transition::Restored,
+                Transition::Tripped
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reject_breaker_probe_failure() {
+        let mut breaker = create_breaker();
+        let mut counters = (0, 0);
+        let mut now = time::Instant::now();
+
+        trip(&mut breaker, &mut counters, &mut now);
+        cool_off(&mut breaker, &counters, &mut now);
+        // the probe fails as soon as more than half of probe shares is rejected
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[false, true, false], &mut now),
+            vec![]
+        );
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[false], &mut now),
+            vec![Transition::Tripped]
+        );
+        assert!(!breaker.is_available());
+        assert_eq!(
+            breaker.history().last().expect("BUG: empty history").reason,
+            "3 of 4 probe shares rejected"
+        );
+
+        // the next probe is started after another cool-off period
+        cool_off(&mut breaker, &counters, &mut now);
+        assert_eq!(
+            submit(&mut breaker, &mut counters, &[true; 4], &mut now),
+            vec![Transition::Restored]
+        );
+    }
+
+    #[test]
+    fn test_reject_breaker_probe_limit() {
+        let mut breaker = create_breaker();
+        let mut counters = (0, 0);
+        let mut now = time::Instant::now();
+
+        trip(&mut breaker, &mut counters, &mut now);
+        assert!(!breaker.accepts_work());
+        cool_off(&mut breaker, &counters, &mut now);
+        let found = counters.0 + counters.1;
+        // the work is stopped when all probe shares have been found...
+        now += time::Duration::from_secs(1);
+        assert!(breaker
+            .update(counters.0, counters.1, found + 3, now)
+            .is_none());
+        assert!(breaker.accepts_work());
+        now += time::Duration::from_secs(1);
+        assert!(breaker
+            .update(counters.0, counters.1, found + 4, now)
+            .is_none());
+        assert!(!breaker.accepts_work());
+        assert!(breaker.is_available());
+        // ... until the probe is resolved
+        now += time::Duration::from_secs(1);
+        let event = breaker
+            .update(counters.0 + 4, counters.1, found + 5, now)
+            .expect("BUG: missing restored event");
+        assert_eq!(event.transition, Transition::Restored);
+        assert!(breaker.accepts_work());
+    }
+
+    #[test]
+    fn test_reject_breaker_disabled() {
+        let mut breaker = RejectBreaker::new(ClientRejectBreaker {
+            enabled: Some(false),
+            ..Default::default()
+        });
+        let mut counters = (0, 0);
+        let mut now = time::Instant::now();
+
+        assert!(submit(&mut breaker, &mut counters, &[false; 1000], &mut now).is_empty());
+        assert!(breaker.is_available());
+
+        // the breaker is disabled by default
+        let mut breaker = RejectBreaker::new(Default::default());
+        assert!(submit(&mut breaker, &mut counters, &[false; 1000], &mut now).is_empty());
+        assert!(breaker.accepts_work());
+    }
+}
@@ -0,0 +1,1055 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Daily digest of shares submitted to pools intended for reconciliation with pool payouts.
+//!
+//! Share counters of all clients are sampled periodically and accumulated into a record of the
+//! current local day. The record is flushed to a JSONL file (one day per line) so that it
+//! survives restarts of the miner and it is finalized right after the local midnight. The flushes
+//! go to a staging copy on tmpfs and the file itself is synced only when a day is closed, on
+//! shutdown and periodically (see `storage::StagedFile`).
+//!
+//! Client statistics are not persistent and start from zero after each restart. Everything
+//! submitted between the last flush and the restart is therefore lost and the record of such
+//! day is marked as partial. Records created by multiple runs within the same day are merged.
+
+use ii_logging::macros::*;
+
+use crate::client::{self, baseline};
+use crate::clock;
+use crate::storage;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io;
+use std::ops;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Format of the date stored in the day record
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// Number of days from 0001-01-01 (the first day of Common Era) to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+/// Share counters of one pool
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counters {
+    pub accepted: u64,
+    pub rejected: u64,
+    pub stale: u64,
+    /// Sum of difficulties of accepted shares
+    pub accepted_difficulty: u64,
+    /// Sum of difficulties of rejected shares
+    pub rejected_difficulty: u64,
+    /// Submitted shares without any response which are neither accepted nor rejected
+    #[serde(default)]
+    pub unresolved: u64,
+}
+
+impl Counters {
+    /// Some counter is lower than in `other` which happens only when the source of the counters
+    /// has been reset (e.g. the client has been recreated)
+    pub(crate) fn is_behind(&self, other: &Self) -> bool {
+        self.accepted < other.accepted
+            || self.rejected < other.rejected
+            || self.stale < other.stale
+            || self.accepted_difficulty < other.accepted_difficulty
+            || self.rejected_difficulty < other.rejected_difficulty
+            || self.unresolved < other.unresolved
+    }
+
+    pub(crate) fn saturating_sub(&self, other: &Self) -> Self {
+        Self {
+            accepted: self.accepted.saturating_sub(other.accepted),
+            rejected: self.rejected.saturating_sub(other.rejected),
+            stale: self.stale.saturating_sub(other.stale),
+            accepted_difficulty: self
+                .accepted_difficulty
+                .saturating_sub(other.accepted_difficulty),
+            rejected_difficulty: self
+                .rejected_difficulty
+                .saturating_sub(other.rejected_difficulty),
+            unresolved: self.unresolved.saturating_sub(other.unresolved),
+        }
+    }
+}
+
+impl ops::Add for Counters {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            accepted: self.accepted + other.accepted,
+            rejected: self.rejected + other.rejected,
+            stale: self.stale + other.stale,
+            accepted_difficulty: self.accepted_difficulty + other.accepted_difficulty,
+            rejected_difficulty: self.rejected_difficulty + other.rejected_difficulty,
+            unresolved: self.unresolved + other.unresolved,
+        }
+    }
+}
+
+impl ops::AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// Pool is identified by its URL and user because client handles do not survive restarts.
+/// The label is intentionally not part of the identity so renaming it does not split counters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PoolId {
+    pub url: String,
+    pub user: String,
+}
+
+/// Counters of one pool together with its label and display name at the time of sampling
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PoolSample {
+    pub label: Option<String>,
+    /// Display name of the client (it is not known for pools restored from the digest file)
+    pub name: Option<String>,
+    pub counters: Counters,
+}
+
+/// Snapshot of total counters (since the client has been created) of all pools
+pub type Sample = BTreeMap<PoolId, PoolSample>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PoolRecord {
+    #[serde(flatten)]
+    pub id: PoolId,
+    /// The most recent label of the pool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+/// One line of the digest file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DayRecord {
+    /// Local date in `DATE_FORMAT`
+    pub date: String,
+    /// Shares submitted during some part of the day are missing in the record
+    pub partial: bool,
+    pub total: Counters,
+    pub pools: Vec<PoolRecord>,
+}
+
+impl DayRecord {
+    fn new(date: NaiveDate, partial: bool, pools: Sample) -> Self {
+        let total = pools
+            .values()
+            .fold(Counters::default(), |total, pool| total + pool.counters);
+        Self {
+            date: date.format(DATE_FORMAT).to_string(),
+            partial,
+            total,
+            pools: pools
+                .into_iter()
+                .map(|(id, pool)| PoolRecord {
+                    id,
+                    label: pool.label,
+                    counters: pool.counters,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, DATE_FORMAT).ok()
+    }
+
+    fn to_sample(&self) -> Sample {
+        let mut pools = Sample::new();
+        for record in &self.pools {
+            let pool = pools.entry(record.id.clone()).or_default();
+            pool.counters += record.counters;
+            if record.label.is_some() {
+                pool.label = record.label.clone();
+            }
+        }
+        pools
+    }
+
+    /// Merge record of the same day created by another (more recent) run of the miner
+    fn merge(&self, other: &DayRecord) -> Self {
+        let mut pools = self.to_sample();
+        for (id, other_pool) in other.to_sample() {
+            let pool = pools.entry(id).or_default();
+            pool.counters += other_pool.counters;
+            // the label of the current run wins even when it has been removed
+            pool.label = other_pool.label;
+        }
+        let date = self.date().expect("BUG: merging record with invalid date");
+        // both records cannot cover the whole day
+        Self::new(date, true, pools)
+    }
+
+    /// Keep only pools with given `label` and recompute the total of the day
+    fn filter_label(&self, label: &str) -> Self {
+        let pools: Vec<_> = self
+            .pools
+            .iter()
+            .filter(|pool| pool.label.as_deref() == Some(label))
+            .cloned()
+            .collect();
+        Self {
+            date: self.date.clone(),
+            partial: self.partial,
+            total: pools
+                .iter()
+                .fold(Counters::default(), |total, pool| total + pool.counters),
+            pools,
+        }
+    }
+}
+
+/// Convert time to the local date with time zone given by `utc_offset` in seconds (the offset is
+/// fixed, daylight saving time is not taken into account)
+fn local_date(utc_offset: i32, now: time::SystemTime) -> NaiveDate {
+    let days = local_seconds(utc_offset, now).div_euclid(SECONDS_PER_DAY);
+    NaiveDate::from_num_days_from_ce_opt((days + UNIX_EPOCH_DAYS_FROM_CE) as i32)
+        .expect("BUG: local date is out of range")
+}
+
+fn local_seconds(utc_offset: i32, now: time::SystemTime) -> i64 {
+    let unix_seconds = match now.duration_since(time::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    unix_seconds + utc_offset as i64
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    label: Option<String>,
+    /// Total counters at the beginning of the day
+    baseline: Counters,
+    /// The last sampled total counters
+    last: Counters,
+    /// Counters accumulated during the day before the client has been reset
+    carried: Counters,
+}
+
+impl PoolState {
+    fn update(&mut self, sample: &PoolSample) {
+        let counters = sample.counters;
+        self.label = sample.label.clone();
+        if counters.is_behind(&self.last) {
+            self.carried += self.last.saturating_sub(&self.baseline);
+            self.baseline = Default::default();
+        }
+        self.last = counters;
+    }
+
+    fn day_counters(&self) -> Counters {
+        self.carried + self.last.saturating_sub(&self.baseline)
+    }
+
+    fn start_day(&mut self) {
+        self.baseline = self.last;
+        self.carried = Default::default();
+    }
+}
+
+/// Accumulates samples of share counters into daily records
+#[derive(Debug)]
+pub struct Rollup {
+    /// Fixed offset of the local time zone in seconds
+    utc_offset: i32,
+    date: NaiveDate,
+    partial: bool,
+    /// Record of the current day stored by previous run of the miner
+    previous: Option<DayRecord>,
+    pools: BTreeMap<PoolId, PoolState>,
+}
+
+impl Rollup {
+    /// Create rollup of the current day with optional `previous` record of the same day loaded
+    /// from the digest file. The first day is always partial because statistics of the clients
+    /// are not persistent.
+    pub fn new(utc_offset: i32, now: time::SystemTime, previous: Option<DayRecord>) -> Self {
+        let date = local_date(utc_offset, now);
+        Self {
+            utc_offset,
+            date,
+            partial: true,
+            previous: previous.filter(|record| record.date() == Some(date)),
+            pools: Default::default(),
+        }
+    }
+
+    /// Time remaining to the next local midnight
+    pub fn until_midnight(&self, now: time::SystemTime) -> time::Duration {
+        let day_seconds = local_seconds(self.utc_offset, now).rem_euclid(SECONDS_PER_DAY);
+        time::Duration::from_secs((SECONDS_PER_DAY - day_seconds) as u64)
+    }
+
+    /// Account new sample of counters and return record of the finished day when the local
+    /// date has changed. Counters sampled right after midnight are accounted to the finished
+    /// day so the sampling should be aligned to the midnight.
+    pub fn update(&mut self, sample: &Sample, now: time::SystemTime) -> Option<DayRecord> {
+        clock::check_system_time(now);
+        for (id, pool) in sample {
+            self.pools.entry(id.clone()).or_default().update(pool);
+        }
+
+        let date = local_date(self.utc_offset, now);
+        if date <= self.date {
+            // ignore time going backwards and keep accumulating into the current day
+            return None;
+        }
+        // when some days have been skipped (e.g. time has been adjusted) the shares submitted
+        // since the last sample cannot be attributed to the right day
+        let contiguous = self.date.succ_opt() == Some(date);
+        let mut record = self.snapshot();
+        record.partial = self.partial || !contiguous;
+
+        // pools which are not present anymore are not part of the new day
+        self.pools.retain(|id, _| sample.contains_key(id));
+        for pool in self.pools.values_mut() {
+            pool.start_day();
+        }
+        self.date = date;
+        self.partial = false;
+        self.previous = None;
+
+        Some(record)
+    }
+
+    /// Record of the current day which is not finished yet
+    pub fn snapshot(&self) -> DayRecord {
+        let record = DayRecord::new(
+            self.date,
+            true,
+            self.pools
+                .iter()
+                .map(|(id, pool)| {
+                    (
+                        id.clone(),
+                        PoolSample {
+                            label: pool.label.clone(),
+                            name: None,
+                            counters: pool.day_counters(),
+                        },
+                    )
+                })
+                .collect(),
+        );
+        match &self.previous {
+            Some(previous) => previous.merge(&record),
+            None => record,
+        }
+    }
+}
+
+/// Persistent history of daily records with bounded retention
+#[derive(Debug)]
+pub struct Store {
+    file: storage::StagedFile,
+    /// Number of kept days
+    retention: u32,
+    /// Records sorted by date
+    records: StdMutex<Vec<DayRecord>>,
+}
+
+impl Store {
+    /// Load history from `path`. Missing file is not an error and malformed lines are skipped.
+    pub fn open<P: Into<PathBuf>>(path: P, retention: u32) -> Self {
+        let file = storage::StagedFile::new(path, storage::DEFAULT_SYNC_INTERVAL);
+        let mut records = vec![];
+        match file.read_to_string() {
+            Ok(content) => {
+                for (idx, line) in content.lines().enumerate() {
+                    let record = serde_json::from_str::<DayRecord>(line)
+                        .map_err(|e| e.to_string())
+                        .and_then(|record| match record.date() {
+                            Some(_) => Ok(record),
+                            None => Err(format!("invalid date '{}'", record.date)),
+                        });
+                    match record {
+                        Ok(record) => records.push(record),
+                        Err(e) => warn!(
+                            "Digest: skipping line {} of '{}': {}",
+                            idx + 1,
+                            file.path().display(),
+                            e
+                        ),
+                    }
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Digest: cannot read '{}': {}", file.path().display(), e),
+        }
+        // keep the order even when the file has been edited manually
+        records.sort_by_key(|record| record.date());
+
+        Self {
+            file,
+            retention,
+            records: StdMutex::new(records),
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    fn lock_records(&self) -> StdMutexGuard<'_, Vec<DayRecord>> {
+        self.records
+            .lock()
+            .expect("BUG: cannot lock digest records")
+    }
+
+    pub fn get(&self, date: NaiveDate) -> Option<DayRecord> {
+        self.lock_records()
+            .iter()
+            .find(|record| record.date() == Some(date))
+            .cloned()
+    }
+
+    /// Return records of the last `count` days starting with the most recent one
+    pub fn last_days(&self, count: usize) -> Vec<DayRecord> {
+        self.lock_records()
+            .iter()
+            .rev()
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    /// Store record replacing the previous version of the same day and drop records which are
+    /// out of retention
+    pub fn put(&self, record: DayRecord) -> io::Result<()> {
+        let date = record
+            .date()
+            .expect("BUG: storing record with invalid date");
+        let mut records = self.lock_records();
+
+        match records.binary_search_by_key(&Some(date), |record| record.date()) {
+            Ok(idx) => records[idx] = record,
+            Err(idx) => records.insert(idx, record),
+        }
+        let last_date = records
+            .last()
+            .and_then(|record| record.date())
+            .expect("BUG: missing last record");
+        let first_date = last_date - chrono::Duration::days(self.retention as i64 - 1);
+        records.retain(|record| record.date() >= Some(first_date));
+
+        self.write(&records, false)
+    }
+
+    /// Write all records to the persistent file immediately
+    pub fn sync(&self) -> io::Result<()> {
+        self.write(&self.lock_records(), true)
+    }
+
+    /// Rewrite the whole file. The persistent file is synced only periodically or when `sync` is
+    /// set.
+    fn write(&self, records: &[DayRecord], sync: bool) -> io::Result<()> {
+        let mut content = vec![];
+        for record in records {
+            serde_json::to_writer(&mut content, record)?;
+            content.push(b'\n');
+        }
+        self.file.write(&content, sync)
+    }
+}
+
+/// Delimiter of key/value pair in the query string
+const VALUE_DELIMITER: char = '=';
+
+/// Selection of records and pools from the digest history
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Number of the most recent days
+    pub days: Option<usize>,
+    /// Return only pools with this label and compute day totals from them
+    pub label: Option<String>,
+}
+
+impl Query {
+    /// Parse query from `key=value` pairs separated with `delimiter` e.g. "days=7,label=cc-a"
+    pub fn parse(parameter: &str, delimiter: char) -> Result<Self, String> {
+        let mut query = Self::default();
+
+        for item in parameter.split(delimiter).filter(|item| !item.is_empty()) {
+            let mut pair = item.splitn(2, VALUE_DELIMITER);
+            let key = pair.next().expect("BUG: missing key").trim();
+            let value = pair
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", key))?;
+
+            match key {
+                "days" => match value.parse::<usize>() {
+                    Ok(days) if days > 0 => query.days = Some(days),
+                    _ => Err(format!("invalid value '{}' for '{}'", value, key))?,
+                },
+                "label" => query.label = Some(value.to_string()),
+                _ => Err(format!("unknown filter '{}'", key))?,
+            }
+        }
+        Ok(query)
+    }
+
+    /// Select records from the `store` starting with the most recent one
+    pub fn execute(&self, store: &Store) -> Vec<DayRecord> {
+        let records = store.last_days(self.days.unwrap_or(std::usize::MAX));
+        match &self.label {
+            Some(label) => records
+                .into_iter()
+                .map(|record| record.filter_label(label))
+                .collect(),
+            None => records,
+        }
+    }
+}
+
+/// Collect total counters of all clients
+pub(crate) async fn take_sample(client_manager: &client::Manager) -> Sample {
+    let mut sample = Sample::new();
+    for group in client_manager.get_groups().await {
+        for client in group.get_clients().await {
+            let descriptor = client.descriptor().await;
+            let stats = client.stats();
+            let accepted = stats.accepted().take_snapshot().await;
+            let rejected = stats.rejected().take_snapshot().await;
+            let stale = stats.stale().take_snapshot().await;
+
+            let id = PoolId {
+                url: descriptor.get_full_url(),
+                user: descriptor.user,
+            };
+            let pool = sample.entry(id).or_default();
+            pool.label = descriptor.label;
+            pool.name = Some(client.display_name());
+            pool.counters += Counters {
+                accepted: accepted.solutions,
+                rejected: rejected.solutions,
+                stale: stale.solutions,
+                accepted_difficulty: accepted.shares.value(),
+                rejected_difficulty: rejected.shares.value(),
+                unresolved: *stats.submissions().unresolved.take_snapshot(),
+            };
+        }
+    }
+    sample
+}
+
+/// Interval of sampling client counters
+const SAMPLE_INTERVAL: time::Duration = time::Duration::from_secs(60);
+/// The current day is written to the file after this number of samples
+const FLUSH_SAMPLES: usize = 10;
+
+fn put_record(store: &Store, record: DayRecord) {
+    if let Err(e) = store.put(record) {
+        error!("Digest: cannot write '{}': {}", store.path().display(), e);
+    }
+}
+
+/// Write all records to the persistent file
+pub fn sync_store(store: &Store) {
+    if let Err(e) = store.sync() {
+        error!("Digest: cannot sync '{}': {}", store.path().display(), e);
+    }
+}
+
+/// Periodically sample counters of all clients and store daily records to `store`. The samples
+/// are also compared against the baseline of the last days by the `baseline` tracker.
+pub async fn run(
+    store: Arc<Store>,
+    client_manager: client::Manager,
+    utc_offset: i32,
+    baseline: Arc<baseline::Tracker>,
+) {
+    let now = clock::system_time();
+    let mut rollup = Rollup::new(utc_offset, now, store.get(local_date(utc_offset, now)));
+
+    let mut samples = 0;
+    loop {
+        let now = clock::system_time();
+        // wake up right after the midnight so the day is closed with accurate counters
+        delay_for(rollup.until_midnight(now).min(SAMPLE_INTERVAL)).await;
+
+        let sample = take_sample(&client_manager).await;
+        samples += 1;
+        let now = clock::system_time();
+        baseline.update(
+            &sample,
+            &store.last_days(baseline::BASELINE_DAYS + 1),
+            local_date(utc_offset, now),
+            clock::now(),
+        );
+        match rollup.update(&sample, now) {
+            Some(record) => {
+                info!(
+                    "Digest: day {} closed with {} accepted and {} rejected shares",
+                    record.date, record.total.accepted, record.total.rejected
+                );
+                put_record(&store, record);
+                put_record(&store, rollup.snapshot());
+                sync_store(&store);
+                samples = 0;
+            }
+            None if samples >= FLUSH_SAMPLES => {
+                put_record(&store, rollup.snapshot());
+                samples = 0;
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::simulation;
+
+    use std::fs;
+
+    use bosminer_config::{DigestConfig, GroupDescriptor, LoadBalanceStrategy};
+
+    /// Mock clock returning given local time of the test day (UTC offset +01:00)
+    const UTC_OFFSET: i32 = 60 * 60;
+    /// 2020-01-01 00:00:00 UTC
+    const TEST_EPOCH: u64 = 1_577_836_800;
+
+    fn clock(day: u64, hours: u64, minutes: u64) -> time::SystemTime {
+        time::UNIX_EPOCH
+            + time::Duration::from_secs(
+                TEST_EPOCH + day * SECONDS_PER_DAY as u64 + hours * 3600 + minutes * 60
+                    - UTC_OFFSET as u64,
+            )
+    }
+
+    fn pool(idx: usize) -> PoolId {
+        PoolId {
+            url: format!("stratum2+tcp://pool{}.example.com", idx),
+            user: "user.worker".to_string(),
+        }
+    }
+
+    fn counters(accepted: u64, rejected: u64) -> Counters {
+        Counters {
+            accepted,
+            rejected,
+            stale: 0,
+            accepted_difficulty: accepted * 1000,
+            rejected_difficulty: rejected * 1000,
+            unresolved: 0,
+        }
+    }
+
+    fn sample(pools: &[(usize, Counters)]) -> Sample {
+        pools
+            .iter()
+            .map(|(idx, counters)| {
+                (
+                    pool(*idx),
+                    PoolSample {
+                        label: None,
+                        name: None,
+                        counters: *counters,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn labelled_sample(pools: &[(usize, &str, Counters)]) -> Sample {
+        pools
+            .iter()
+            .map(|(idx, label, counters)| {
+                (
+                    pool(*idx),
+                    PoolSample {
+                        label: Some(label.to_string()),
+                        name: None,
+                        counters: *counters,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn pool_label(record: &DayRecord, idx: usize) -> Option<&str> {
+        record
+            .pools
+            .iter()
+            .find(|record| record.id == pool(idx))
+            .and_then(|record| record.label.as_deref())
+    }
+
+    fn pool_counters(record: &DayRecord, idx: usize) -> Option<Counters> {
+        record
+            .pools
+            .iter()
+            .find(|record| record.id == pool(idx))
+            .map(|record| record.counters)
+    }
+
+    struct TestFile(PathBuf);
+
+    impl TestFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "bosminer-digest-{}-{}.jsonl",
+                name,
+                std::process::id()
+            ));
+            let file = Self(path);
+            file.staged().remove();
+            file
+        }
+
+        fn staged(&self) -> storage::StagedFile {
+            storage::StagedFile::new(&self.0, storage::DEFAULT_SYNC_INTERVAL)
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            self.staged().remove();
+        }
+    }
+
+    #[test]
+    fn test_local_date() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2020, 1, 1 + day).unwrap();
+
+        assert_eq!(local_date(UTC_OFFSET, clock(0, 0, 0)), date(0));
+        assert_eq!(local_date(UTC_OFFSET, clock(0, 23, 59)), date(0));
+        assert_eq!(local_date(UTC_OFFSET, clock(1, 0, 0)), date(1));
+        // the same instant is still the previous day in UTC
+        assert_eq!(local_date(0, clock(1, 0, 0)), date(0));
+
+        let rollup = Rollup::new(UTC_OFFSET, clock(0, 22, 30), None);
+        assert_eq!(
+            rollup.until_midnight(clock(0, 22, 30)),
+            time::Duration::from_secs(90 * 60)
+        );
+    }
+
+    #[test]
+    fn test_rollup_days() {
+        // the miner is started during the first day
+        let mut rollup = Rollup::new(UTC_OFFSET, clock(0, 10, 0), None);
+        assert!(rollup
+            .update(&sample(&[(0, counters(0, 0))]), clock(0, 10, 0))
+            .is_none());
+        assert!(rollup
+            .update(&sample(&[(0, counters(10, 1))]), clock(0, 18, 0))
+            .is_none());
+        // new pool is added during the day
+        assert!(rollup
+            .update(
+                &sample(&[(0, counters(20, 2)), (1, counters(5, 0))]),
+                clock(0, 23, 59)
+            )
+            .is_none());
+
+        let first_day = rollup
+            .update(
+                &sample(&[(0, counters(21, 2)), (1, counters(5, 0))]),
+                clock(1, 0, 0),
+            )
+            .expect("BUG: first day has not been closed");
+        assert_eq!(first_day.date, "2020-01-01");
+        assert!(first_day.partial);
+        assert_eq!(pool_counters(&first_day, 0), Some(counters(21, 2)));
+        assert_eq!(pool_counters(&first_day, 1), Some(counters(5, 0)));
+        assert_eq!(first_day.total, counters(26, 2));
+
+        // the first pool is removed and the second one is reset during the second day
+        assert!(rollup
+            .update(&sample(&[(1, counters(8, 1))]), clock(1, 12, 0))
+            .is_none());
+        assert!(rollup
+            .update(&sample(&[(1, counters(2, 0))]), clock(1, 13, 0))
+            .is_none());
+        let second_day = rollup
+            .update(&sample(&[(1, counters(4, 0))]), clock(2, 0, 0))
+            .expect("BUG: second day has not been closed");
+        assert_eq!(second_day.date, "2020-01-02");
+        assert!(!second_day.partial);
+        assert_eq!(pool_counters(&second_day, 0), Some(counters(0, 0)));
+        assert_eq!(pool_counters(&second_day, 1), Some(counters(7, 1)));
+
+        // the removed pool is not part of the third day and skipped day makes record partial
+        let third_day = rollup
+            .update(&sample(&[(1, counters(6, 0))]), clock(4, 0, 0))
+            .expect("BUG: third day has not been closed");
+        assert_eq!(third_day.date, "2020-01-03");
+        assert!(third_day.partial);
+        assert_eq!(pool_counters(&third_day, 0), None);
+        assert_eq!(pool_counters(&third_day, 1), Some(counters(2, 0)));
+    }
+
+    #[test]
+    fn test_rollup_labels() {
+        let mut rollup = Rollup::new(UTC_OFFSET, clock(0, 10, 0), None);
+        rollup.update(
+            &labelled_sample(&[(0, "cc-a", counters(5, 0)), (1, "cc-b", counters(3, 1))]),
+            clock(0, 12, 0),
+        );
+        let record = rollup.snapshot();
+        assert_eq!(pool_label(&record, 0), Some("cc-a"));
+        assert_eq!(pool_label(&record, 1), Some("cc-b"));
+
+        // renamed label is used for the whole day and the counters are not reset
+        rollup.update(
+            &labelled_sample(&[(0, "cc-x", counters(8, 0)), (1, "cc-b", counters(4, 1))]),
+            clock(0, 18, 0),
+        );
+        let record = rollup.snapshot();
+        assert_eq!(pool_label(&record, 0), Some("cc-x"));
+        assert_eq!(pool_counters(&record, 0), Some(counters(8, 0)));
+        assert_eq!(pool_counters(&record, 1), Some(counters(4, 1)));
+
+        // label is stored in the digest file and records without it are still readable
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(line.contains("\"label\":\"cc-x\""));
+        assert_eq!(serde_json::from_str::<DayRecord>(&line).unwrap(), record);
+        let unlabelled =
+            DayRecord::new(record.date().unwrap(), true, sample(&[(0, counters(1, 0))]));
+        let line = serde_json::to_string(&unlabelled).unwrap();
+        assert!(!line.contains("label"));
+        assert_eq!(pool_label(&serde_json::from_str(&line).unwrap(), 0), None);
+
+        // the next run of the miner renames the label again and continues with the stored record
+        let mut rollup = Rollup::new(UTC_OFFSET, clock(0, 20, 0), Some(record));
+        rollup.update(
+            &labelled_sample(&[(0, "cc-y", counters(2, 0))]),
+            clock(0, 21, 0),
+        );
+        let record = rollup.snapshot();
+        assert_eq!(pool_label(&record, 0), Some("cc-y"));
+        assert_eq!(pool_counters(&record, 0), Some(counters(10, 0)));
+        assert_eq!(pool_label(&record, 1), Some("cc-b"));
+    }
+
+    #[test]
+    fn test_restarts() {
+        let file = TestFile::new("restarts");
+
+        // the first run flushes the current day and is killed
+        let store = Store::open(&file.0, 90);
+        let mut rollup = Rollup::new(UTC_OFFSET, clock(0, 8, 0), None);
+        rollup.update(&sample(&[(0, counters(10, 1))]), clock(0, 12, 0));
+        store.put(rollup.snapshot()).unwrap();
+        rollup.update(&sample(&[(0, counters(15, 1))]), clock(0, 13, 0));
+        drop(rollup);
+        drop(store);
+
+        // the second run continues with the record stored by the first one
+        let store = Store::open(&file.0, 90);
+        let today = local_date(UTC_OFFSET, clock(0, 14, 0));
+        let mut rollup = Rollup::new(UTC_OFFSET, clock(0, 14, 0), store.get(today));
+        rollup.update(&sample(&[(0, counters(3, 0))]), clock(0, 20, 0));
+        store.put(rollup.snapshot()).unwrap();
+        let first_day = rollup
+            .update(
+                &sample(&[(0, counters(6, 0)), (1, counters(1, 0))]),
+                clock(1, 0, 0),
+            )
+            .unwrap();
+        assert!(first_day.partial);
+        // shares submitted after the flush of the first run are lost
+        assert_eq!(pool_counters(&first_day, 0), Some(counters(16, 1)));
+        assert_eq!(pool_counters(&first_day, 1), Some(counters(1, 0)));
+        store.put(first_day.clone()).unwrap();
+
+        // the whole second day is covered by the second run
+        rollup.update(
+            &sample(&[(0, counters(10, 0)), (1, counters(2, 0))]),
+            clock(1, 12, 0),
+        );
+        store.put(rollup.snapshot()).unwrap();
+        let second_day = rollup
+            .update(
+                &sample(&[(0, counters(12, 0)), (1, counters(3, 1))]),
+                clock(2, 0, 0),
+            )
+            .unwrap();
+        assert!(!second_day.partial);
+        assert_eq!(second_day.total, counters(8, 1));
+        store.put(second_day.clone()).unwrap();
+        drop(store);
+
+        // the persistent file is written only when it is synced
+        let store = Store::open(&file.0, 90);
+        assert_eq!(store.last_days(10), vec![second_day, first_day]);
+        assert!(!file.0.exists());
+        store.sync().unwrap();
+
+        // the file contains exactly one record per day
+        let lines = fs::read_to_string(&file.0).unwrap().lines().count();
+        assert_eq!(lines, 2);
+    }
+
+    #[test]
+    fn test_store_retention() {
+        let file = TestFile::new("retention");
+        let date = |day: u32| NaiveDate::from_ymd_opt(2020, 1, 1 + day).unwrap();
+
+        let store = Store::open(&file.0, 3);
+        for day in 0..5 {
+            let record = DayRecord::new(date(day), false, sample(&[(0, counters(day as u64, 0))]));
+            store.put(record).unwrap();
+        }
+        let dates: Vec<_> = store
+            .last_days(10)
+            .into_iter()
+            .map(|record| record.date)
+            .collect();
+        assert_eq!(dates, vec!["2020-01-05", "2020-01-04", "2020-01-03"]);
+        assert_eq!(store.last_days(1)[0].total, counters(4, 0));
+
+        // malformed lines are skipped
+        let staging_path = file.staged().staging_path().to_path_buf();
+        let mut content = fs::read_to_string(&staging_path).unwrap();
+        content.push_str("{\"date\": \"broken\"\n");
+        fs::write(&staging_path, content).unwrap();
+        assert_eq!(Store::open(&file.0, 3).last_days(10).len(), 3);
+    }
+
+    /// Daily rollup driven by the sampling task of the simulated core running from the middle of
+    /// the first day until the third day begins
+    #[test]
+    fn test_rollup_simulation() {
+        let file = TestFile::new("simulation");
+        let mut config = simulation::Config::new(2e12);
+        config.epoch = clock(0, 10, 0);
+        config.digest = Some(DigestConfig {
+            enabled: Some(true),
+            path: Some(file.0.to_string_lossy().into_owned()),
+            utc_offset: Some(UTC_OFFSET / 60),
+            retention: None,
+            regression_factor: None,
+        });
+
+        let report =
+            simulation::simulate(config, time::Duration::from_secs(38 * 3600 + 300), vec![]);
+        let dates: Vec<_> = report
+            .digest
+            .iter()
+            .map(|record| record.date.as_str())
+            .collect();
+        assert_eq!(dates, vec!["2020-01-01", "2020-01-02", "2020-01-03"]);
+        // the miner has been started during the first day
+        assert!(report.digest[0].partial);
+        assert!(!report.digest[1].partial);
+        assert!(report.digest[1].total.accepted > report.digest[0].total.accepted);
+
+        // shares submitted after the last sample are not part of any record yet
+        let accepted: u64 = report
+            .digest
+            .iter()
+            .map(|record| record.total.accepted)
+            .sum();
+        assert!(accepted > 0);
+        assert!(accepted <= report.pools[0].accepted.solutions);
+    }
+
+    /// Labels of two pools are propagated to the client statistics and daily records and the
+    /// counters of the renamed pool continue to be accumulated into the same record
+    #[test]
+    fn test_labels_simulation() {
+        let file = TestFile::new("labels");
+        let mut config = simulation::Config::new(2e12);
+        config.groups = vec![
+            (
+                GroupDescriptor::new("A".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("B".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+        ];
+        config.labels.insert((0, 0), "cc-a".to_string());
+        config.labels.insert((1, 0), "cc-b".to_string());
+        config.digest = Some(DigestConfig {
+            enabled: Some(true),
+            path: Some(file.0.to_string_lossy().into_owned()),
+            utc_offset: Some(0),
+            retention: None,
+            regression_factor: None,
+        });
+
+        let report = simulation::simulate(
+            config,
+            time::Duration::from_secs(2 * 3600),
+            vec![(
+                time::Duration::from_secs(3600),
+                simulation::Event::SetLabel {
+                    group: 0,
+                    pool: 0,
+                    label: Some("cc-x".to_string()),
+                },
+            )],
+        );
+        let labels: Vec<_> = report
+            .pools
+            .iter()
+            .map(|pool| pool.label.as_deref())
+            .collect();
+        assert_eq!(labels, vec![Some("cc-x"), Some("cc-b")]);
+
+        let record = report.digest.last().expect("BUG: missing digest record");
+        let labels: Vec<_> = record
+            .pools
+            .iter()
+            .map(|pool| pool.label.as_deref())
+            .collect();
+        assert_eq!(labels, vec![Some("cc-x"), Some("cc-b")]);
+
+        // shares submitted before the rename are still accounted to the pool
+        let accepted = record.pools[0].counters.accepted;
+        let total_accepted = report.pools[0].accepted.solutions;
+        assert!(
+            accepted as f64 >= 0.8 * total_accepted as f64,
+            "{} of {} accepted shares are in the record",
+            accepted,
+            total_accepted
+        );
+
+        let query = Query::parse("days=1,label=cc-x", ',').unwrap();
+        assert_eq!(query.days, Some(1));
+        let filtered = record.filter_label(query.label.as_deref().unwrap());
+        assert_eq!(filtered.pools, vec![record.pools[0].clone()]);
+        assert_eq!(filtered.total, record.pools[0].counters);
+        assert!(record.filter_label("cc-a").pools.is_empty());
+    }
+}
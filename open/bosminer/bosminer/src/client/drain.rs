@@ -20,8 +20,10 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use crate::clock;
 use crate::error;
 use crate::job;
+use crate::log_id;
 use crate::node;
 use crate::stats;
 use crate::sync;
@@ -37,7 +39,6 @@ use futures::channel::mpsc;
 use futures::lock::Mutex;
 use ii_async_compat::prelude::*;
 use ii_async_compat::select;
-use tokio::time::delay_for;
 
 use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -182,6 +183,11 @@ impl DifficultyRegulator {
     const SOLUTIONS_INTERVAL: time::Duration = time::Duration::from_secs(120);
 
     async fn new(client: Arc<Client>, difficulty: Difficulty) -> Self {
+        client.stats.difficulty_history.record(
+            &difficulty.to_target(),
+            stats::DifficultyTrigger::Initial,
+            clock::now(),
+        );
         Self {
             difficulty,
             last_accepted: client.stats.accepted.take_snapshot().await,
@@ -212,13 +218,19 @@ impl DifficultyRegulator {
             self.difficulty.dec(solutions_per_sec < 0.1);
         }
 
+        // The history ignores targets which do not change the difficulty
+        self.client.stats.difficulty_history.record(
+            &self.difficulty.to_target(),
+            stats::DifficultyTrigger::Vardiff,
+            accepted.snapshot_time,
+        );
         self.last_accepted = accepted;
     }
 }
 
 #[derive(Debug, ClientNode)]
 pub struct Client {
-    description: String,
+    client_name: log_id::ClientName,
     #[member_status]
     status: sync::StatusMonitor,
     #[member_client_stats]
@@ -233,10 +245,10 @@ pub struct Client {
 impl Client {
     const NEW_JOB_INTERVAL: time::Duration = time::Duration::from_secs(10);
 
-    pub fn new(description: String, solver: job::Solver) -> Self {
+    pub fn new(solver: job::Solver) -> Self {
         let (stop_sender, stop_receiver) = mpsc::channel(1);
         Self {
-            description,
+            client_name: solver.client_name(),
             status: Default::default(),
             stats: Default::default(),
             stop_sender,
@@ -255,18 +267,22 @@ impl Client {
         self.last_job.lock().await.as_ref().map(|job| job.clone())
     }
 
-    async fn send_job_and_wait(self: Arc<Self>, difficulty: Difficulty, index: &mut u64) {
+    async fn send_job(self: Arc<Self>, difficulty: Difficulty, index: &mut u64) {
         let job = Arc::new(Job::new(self.clone(), difficulty, *index));
         *index += 1;
 
         self.update_last_job(job.clone()).await;
         self.job_sender.lock().await.send(job);
-
-        delay_for(Self::NEW_JOB_INTERVAL).await;
     }
 
     async fn account_solution(&self, solution: work::Solution) {
-        let now = std::time::Instant::now();
+        let now = clock::now();
+        // all shares are accepted immediately
+        for activity in &[stats::JobActivity::Submitted, stats::JobActivity::Accepted] {
+            self.stats
+                .job_history
+                .account(solution.dyn_job(), *activity);
+        }
         self.stats
             .accepted
             .account_solution(&solution.job_target(), now)
@@ -279,10 +295,16 @@ impl Client {
         let difficulty: Difficulty = Default::default();
         let mut regulator = DifficultyRegulator::new(self.clone(), difficulty.clone()).await;
         let mut index = 0;
+        let mut job_interval = tokio::time::interval(Self::NEW_JOB_INTERVAL);
 
         while !self.status.is_shutting_down() {
+            // All branches have to be cancellation safe because the futures of branches which
+            // have not completed are dropped. Waiting for the next job is done by the interval
+            // timer which does not restart when a solution is received in the meantime.
             select! {
-                _ = self.clone().send_job_and_wait(difficulty.clone(), &mut index).fuse() => {}
+                _ = job_interval.tick().fuse() => {
+                    self.clone().send_job(difficulty.clone(), &mut index).await;
+                }
                 solution = solution_receiver.receive().fuse() => {
                     match solution {
                         Some(solution) => self.account_solution(solution).await,
@@ -351,6 +373,6 @@ impl node::Client for Client {
 
 impl fmt::Display for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.description)
+        write!(f, "{}", self.client_name)
     }
 }
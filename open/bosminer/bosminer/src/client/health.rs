@@ -0,0 +1,521 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Composite health score of pools which drives failover between pools of a group. A connected
+//! pool is not necessarily usable: it may stop sending new jobs, respond slowly to submitted
+//! shares, reject them or refuse the user. The score combines these measurements so that the
+//! failover prefers a healthy pool with lower priority to a sick one. Scoring and selection of
+//! the pool are pure functions of the measured state.
+
+use crate::clock;
+
+use bosminer_config::GroupFailoverHealth;
+
+use std::collections::VecDeque;
+use std::time;
+
+/// Score of a component which has not been measured yet (a new pool is neither the best nor the
+/// worst one)
+pub const NEUTRAL_SCORE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    /// The pool has not responded to the authorization of the user yet
+    Unknown,
+    Authorized,
+    Rejected,
+}
+
+impl Default for Authorization {
+    fn default() -> Self {
+        Self::Unknown
+    }
+}
+
+/// Measured state of the pool (`None` when the value has not been measured yet)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    /// Time since the last job has been received from the pool
+    pub job_age: Option<time::Duration>,
+    /// 95th percentile of the time the pool takes to respond to submitted shares
+    pub submit_latency_p95: Option<time::Duration>,
+    /// Ratio of rejected shares in the recent window (0.0 to 1.0)
+    pub reject_rate: Option<f64>,
+    pub authorization: Authorization,
+}
+
+/// Composite score with scores of its components in range <0.0, 1.0> (`None` when the component
+/// has not been measured and it is considered neutral)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub value: f64,
+    pub job: Option<f64>,
+    pub latency: Option<f64>,
+    pub reject: Option<f64>,
+    pub authorization: Option<f64>,
+}
+
+/// Score which drops linearly from 1.0 to zero when the `value` reaches the `limit`
+fn linear(value: f64, limit: f64) -> f64 {
+    (1.0 - value / limit).max(0.0).min(1.0)
+}
+
+/// Compute score of the pool from its `sample`. It is the weighted geometric mean of scores of
+/// the components so any component at zero makes the whole score zero.
+pub fn score(sample: &Sample, config: &GroupFailoverHealth) -> Score {
+    let job = sample
+        .job_age
+        .map(|age| linear(age.as_secs_f64(), config.job_timeout().as_secs_f64()));
+    let latency = sample
+        .submit_latency_p95
+        .map(|latency| linear(latency.as_secs_f64(), config.latency_limit().as_secs_f64()));
+    let reject = sample
+        .reject_rate
+        .map(|reject_rate| linear(reject_rate, config.reject_limit()));
+    let authorization = match sample.authorization {
+        Authorization::Unknown => None,
+        Authorization::Authorized => Some(1.0),
+        Authorization::Rejected => Some(0.0),
+    };
+
+    let components = [
+        (job, config.job_weight()),
+        (latency, config.latency_weight()),
+        (reject, config.reject_weight()),
+        (authorization, config.authorization_weight()),
+    ];
+    let total_weight: f64 = components.iter().map(|(_, weight)| weight).sum();
+    let value = if total_weight > 0.0 {
+        let weighted_log: f64 = components
+            .iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(score, weight)| weight * score.unwrap_or(NEUTRAL_SCORE).ln())
+            .sum();
+        (weighted_log / total_weight).exp()
+    } else {
+        NEUTRAL_SCORE
+    };
+
+    Score {
+        value,
+        job,
+        latency,
+        reject,
+        authorization,
+    }
+}
+
+/// State of a pool relevant to the failover
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    /// The pool has not been soft-failed by the reject breaker and it is not waiting for
+    /// responses to all probe shares
+    pub available: bool,
+    /// The pool is connected
+    pub running: bool,
+    /// The pool has been connected for at least the hold-down period
+    pub settled: bool,
+    /// The pool is enabled and it is connected or it has been down for less than the failover
+    /// timeout
+    pub alive: bool,
+    pub score: f64,
+}
+
+/// Minimal score of the pool which keeps the work when it is `selected` or which takes over the
+/// work otherwise
+pub fn min_score(config: &GroupFailoverHealth, selected: bool) -> f64 {
+    if selected {
+        config.min_score()
+    } else {
+        config.min_score() + config.hysteresis()
+    }
+}
+
+/// Select the pool supplying jobs of the group from `candidates` ordered by their priority. The
+/// `selected` pool is kept while it is alive and its score does not drop under the minimal one.
+/// The pool with higher priority takes over the work when it has been connected for the
+/// hold-down period and its score exceeds the minimal one by the hysteresis. When the selected
+/// pool is replaced, the first connected pool with such score is used and when there is none,
+/// the connected pool with the best score (the one with higher priority on tie) is used.
+pub fn select(
+    candidates: &[Candidate],
+    selected: Option<usize>,
+    config: &GroupFailoverHealth,
+) -> Option<usize> {
+    let keep_score = min_score(config, true);
+    let take_over_score = min_score(config, false);
+
+    let selected = selected.filter(|idx| {
+        candidates.get(*idx).map_or(false, |candidate| {
+            candidate.available && candidate.alive && candidate.score >= keep_score
+        })
+    });
+    if let Some(selected) = selected {
+        return candidates[..selected]
+            .iter()
+            .position(|candidate| {
+                candidate.available && candidate.settled && candidate.score >= take_over_score
+            })
+            .or(Some(selected));
+    }
+
+    let connected = || {
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.available && candidate.running)
+    };
+    connected()
+        .find(|(_, candidate)| candidate.score >= take_over_score)
+        .or_else(|| {
+            connected().fold(None, |best, (idx, candidate)| match best {
+                Some((_, best_candidate)) if best_candidate.score >= candidate.score => best,
+                _ => Some((idx, candidate)),
+            })
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// Cumulative share counters at a particular time
+#[derive(Debug, Clone, Copy)]
+struct Shares {
+    time: time::Instant,
+    accepted: u64,
+    rejected: u64,
+}
+
+/// Measures the ratio of rejected shares of a pool in the rolling window and keeps the last
+/// score of the pool
+#[derive(Debug, Default)]
+pub struct Monitor {
+    /// Samples covering the rolling window (the first one is the base of the window)
+    shares: VecDeque<Shares>,
+    last: Option<(Sample, Score)>,
+}
+
+impl Monitor {
+    pub const REJECT_WINDOW: time::Duration = time::Duration::from_secs(10 * 60);
+    /// The reject rate of fewer shares is not significant
+    pub const MIN_SHARES: u64 = 10;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Update the window with cumulative counters of accepted and rejected shares and return
+    /// the ratio of rejected shares in the window or `None` when there are not enough shares
+    pub fn reject_rate(&mut self, accepted: u64, rejected: u64, now: time::Instant) -> Option<f64> {
+        clock::check_instant(now);
+        self.shares.push_back(Shares {
+            time: now,
+            accepted,
+            rejected,
+        });
+        // Keep the newest sample which is out of the window as a base
+        while self.shares.len() > 1
+            && now.duration_since(self.shares[1].time) >= Self::REJECT_WINDOW
+        {
+            self.shares.pop_front();
+        }
+        let base = *self.shares.front().expect("BUG: missing shares");
+        let window_rejected = rejected.saturating_sub(base.rejected);
+        let window_total = accepted.saturating_sub(base.accepted) + window_rejected;
+        if window_total < Self::MIN_SHARES {
+            return None;
+        }
+        Some(window_rejected as f64 / window_total as f64)
+    }
+
+    /// Remember the last `sample` of the pool with its `score`
+    pub fn update(&mut self, sample: Sample, score: Score) {
+        self.last = Some((sample, score));
+    }
+
+    /// Return the last sample and score of the pool or `None` before the first update
+    pub fn last(&self) -> Option<(Sample, Score)> {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECOND: time::Duration = time::Duration::from_secs(1);
+    const EPSILON: f64 = 1e-9;
+
+    fn healthy() -> Sample {
+        Sample {
+            job_age: Some(time::Duration::from_secs(0)),
+            submit_latency_p95: Some(time::Duration::from_millis(0)),
+            reject_rate: Some(0.0),
+            authorization: Authorization::Authorized,
+        }
+    }
+
+    fn assert_score(score: f64, expected: f64) {
+        assert!(
+            (score - expected).abs() < EPSILON,
+            "unexpected score {} (expected {})",
+            score,
+            expected
+        );
+    }
+
+    fn candidate(score: f64) -> Candidate {
+        Candidate {
+            available: true,
+            running: true,
+            settled: true,
+            alive: true,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_neutral_score() {
+        let config = GroupFailoverHealth::default();
+        // new pool without any history is neither the best nor the worst one
+        let new = score(&Sample::default(), &config);
+        assert_score(new.value, NEUTRAL_SCORE);
+        assert_eq!(new.job, None);
+        assert_eq!(new.latency, None);
+        assert_eq!(new.reject, None);
+        assert_eq!(new.authorization, None);
+
+        assert_score(score(&healthy(), &config).value, 1.0);
+        let mut dead = healthy();
+        dead.job_age = Some(config.job_timeout());
+        assert_score(score(&dead, &config).value, 0.0);
+        assert!(score(&dead, &config).value < new.value);
+    }
+
+    #[test]
+    fn test_component_limits() {
+        let config = GroupFailoverHealth::default();
+
+        let mut sample = healthy();
+        sample.job_age = Some(config.job_timeout() / 2);
+        assert_eq!(score(&sample, &config).job, Some(0.5));
+
+        let mut sample = healthy();
+        sample.submit_latency_p95 = Some(config.latency_limit() / 2);
+        assert_eq!(score(&sample, &config).latency, Some(0.5));
+
+        let mut sample = healthy();
+        sample.reject_rate = Some(config.reject_limit() / 2.0);
+        assert_score(
+            score(&sample, &config).reject.expect("BUG: missing score"),
+            0.5,
+        );
+
+        // values over the limits are clamped
+        sample.job_age = Some(config.job_timeout() * 10);
+        sample.submit_latency_p95 = Some(config.latency_limit() * 10);
+        sample.reject_rate = Some(1.0);
+        sample.authorization = Authorization::Rejected;
+        let worst = score(&sample, &config);
+        assert_eq!(worst.job, Some(0.0));
+        assert_eq!(worst.latency, Some(0.0));
+        assert_eq!(worst.reject, Some(0.0));
+        assert_eq!(worst.authorization, Some(0.0));
+        assert_score(worst.value, 0.0);
+    }
+
+    #[test]
+    fn test_stale_job_is_unhealthy() {
+        let config = GroupFailoverHealth::default();
+        // connected and authorized pool without any job for 10 minutes
+        let mut stale = healthy();
+        stale.job_age = Some(time::Duration::from_secs(10 * 60));
+        let stale = score(&stale, &config);
+        assert!(stale.value < min_score(&config, true));
+
+        // a single rejected authorization makes the pool unhealthy as well
+        let mut rejected = healthy();
+        rejected.authorization = Authorization::Rejected;
+        assert_score(score(&rejected, &config).value, 0.0);
+    }
+
+    #[test]
+    fn test_missing_components_are_neutral() {
+        let config = GroupFailoverHealth::default();
+        // fresh job of a pool which has not received any response to shares yet
+        let sample = Sample {
+            job_age: Some(SECOND),
+            ..Default::default()
+        };
+        let partial = score(&sample, &config);
+        assert!(partial.value > NEUTRAL_SCORE);
+        assert!(partial.value < 1.0);
+        assert!(partial.value > min_score(&config, false));
+
+        // the same pool with slow responses gets lower score than the one not measured yet
+        let mut slow = sample;
+        slow.submit_latency_p95 = Some(config.latency_limit() * 9 / 10);
+        assert!(score(&slow, &config).value < partial.value);
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut config = GroupFailoverHealth::default();
+        let mut sample = healthy();
+        sample.submit_latency_p95 = Some(config.latency_limit());
+        assert_score(score(&sample, &config).value, 0.0);
+
+        // component with zero weight is ignored
+        config.latency_weight = Some(0.0);
+        assert_score(score(&sample, &config).value, 1.0);
+        assert_eq!(score(&sample, &config).latency, Some(0.0));
+
+        // only the relative weights matter
+        let mut sample = healthy();
+        sample.job_age = Some(config.job_timeout() / 2);
+        let base = score(&sample, &config).value;
+        config.job_weight = Some(config.job_weight() * 10.0);
+        config.reject_weight = Some(config.reject_weight() * 10.0);
+        config.authorization_weight = Some(config.authorization_weight() * 10.0);
+        assert_score(score(&sample, &config).value, base);
+
+        // all weights are zero
+        config.job_weight = Some(0.0);
+        config.reject_weight = Some(0.0);
+        config.authorization_weight = Some(0.0);
+        assert_score(score(&sample, &config).value, NEUTRAL_SCORE);
+    }
+
+    #[test]
+    fn test_select_priority() {
+        let config = GroupFailoverHealth::default();
+        // the first healthy pool is selected
+        let candidates = [candidate(0.9), candidate(0.9)];
+        assert_eq!(select(&candidates, None, &config), Some(0));
+
+        // a connected pool without any job is skipped
+        let candidates = [candidate(0.0), candidate(0.9)];
+        assert_eq!(select(&candidates, None, &config), Some(1));
+        assert_eq!(select(&candidates, Some(0), &config), Some(1));
+
+        // pools which are not connected or available cannot be selected
+        let mut candidates = [candidate(0.9), candidate(0.9), candidate(0.9)];
+        candidates[0].running = false;
+        candidates[1].available = false;
+        assert_eq!(select(&candidates, None, &config), Some(2));
+        candidates[2].running = false;
+        assert_eq!(select(&candidates, None, &config), None);
+        assert_eq!(select(&[], None, &config), None);
+    }
+
+    #[test]
+    fn test_select_hysteresis() {
+        let config = GroupFailoverHealth::default();
+        let keep_score = min_score(&config, true);
+        let take_over_score = min_score(&config, false);
+        let between = (keep_score + take_over_score) / 2.0;
+
+        // the selected pool is kept with the score between the thresholds
+        let candidates = [candidate(between), candidate(0.9)];
+        assert_eq!(select(&candidates, Some(0), &config), Some(0));
+        // but such pool does not take over the work from the selected one
+        assert_eq!(select(&candidates, Some(1), &config), Some(1));
+        // while a healthy one does
+        let candidates = [candidate(0.9), candidate(between)];
+        assert_eq!(select(&candidates, Some(1), &config), Some(0));
+
+        // the pool is replaced only when it drops under the minimal score
+        let candidates = [candidate(keep_score - EPSILON), candidate(0.9)];
+        assert_eq!(select(&candidates, Some(0), &config), Some(1));
+        let candidates = [candidate(keep_score), candidate(0.9)];
+        assert_eq!(select(&candidates, Some(0), &config), Some(0));
+    }
+
+    #[test]
+    fn test_select_hold_down() {
+        let config = GroupFailoverHealth::default();
+        let mut candidates = [candidate(0.9), candidate(0.9)];
+        candidates[0].settled = false;
+        // recovered pool with higher priority waits for the hold-down period
+        assert_eq!(select(&candidates, Some(1), &config), Some(1));
+        candidates[0].settled = true;
+        assert_eq!(select(&candidates, Some(1), &config), Some(0));
+
+        // the selected pool is kept while it is down for less than the failover timeout
+        let mut candidates = [candidate(0.9), candidate(0.9)];
+        candidates[0].running = false;
+        candidates[0].settled = false;
+        assert_eq!(select(&candidates, Some(0), &config), Some(0));
+        candidates[0].alive = false;
+        assert_eq!(select(&candidates, Some(0), &config), Some(1));
+    }
+
+    #[test]
+    fn test_select_ties() {
+        let config = GroupFailoverHealth::default();
+        // healthy pools with the same score are used by priority
+        let candidates = [candidate(0.5), candidate(0.5), candidate(0.5)];
+        assert_eq!(select(&candidates, None, &config), Some(0));
+        // the selected pool is not replaced by a pool with lower priority and the same score
+        assert_eq!(select(&candidates, Some(1), &config), Some(0));
+        let mut candidates = candidates;
+        candidates[0].settled = false;
+        assert_eq!(select(&candidates, Some(1), &config), Some(1));
+
+        // when no pool is healthy the best one is used and ties are resolved by priority
+        let candidates = [candidate(0.05), candidate(0.1), candidate(0.1)];
+        assert_eq!(select(&candidates, None, &config), Some(1));
+        let candidates = [candidate(0.0), candidate(0.0)];
+        assert_eq!(select(&candidates, None, &config), Some(0));
+    }
+
+    #[test]
+    fn test_reject_rate() {
+        let mut monitor = Monitor::new();
+        let mut now = time::Instant::now();
+        // not enough shares to be significant
+        assert_eq!(monitor.reject_rate(0, 0, now), None);
+        now += SECOND;
+        assert_eq!(monitor.reject_rate(5, 3, now), None);
+        now += SECOND;
+        assert_eq!(monitor.reject_rate(6, 3, now), None);
+        now += SECOND;
+        assert_eq!(monitor.reject_rate(8, 6, now), Some(6.0 / 14.0));
+
+        // the rejected shares fall out of the window
+        now += Monitor::REJECT_WINDOW;
+        assert_eq!(monitor.reject_rate(18, 6, now), Some(0.0));
+        now += Monitor::REJECT_WINDOW;
+        assert_eq!(monitor.reject_rate(18, 6, now), None);
+    }
+
+    #[test]
+    fn test_monitor_last() {
+        let config = GroupFailoverHealth::default();
+        let mut monitor = Monitor::new();
+        assert_eq!(monitor.last(), None);
+
+        let sample = healthy();
+        monitor.update(sample, score(&sample, &config));
+        let (last_sample, last_score) = monitor.last().expect("BUG: missing score");
+        assert_eq!(last_sample, sample);
+        assert_score(last_score.value, 1.0);
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Dry-run connectivity test of a pool. The test opens a temporary connection which is never
+//! registered for work, performs the protocol handshake with authorization and optionally waits
+//! for the first job. The outcome of every stage is collected in the `Report`.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::time;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Wait for the first job notification after successful authorization
+    pub wait_for_job: bool,
+    /// Limit for the whole test including all stages
+    pub timeout: time::Duration,
+}
+
+impl Options {
+    pub const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            wait_for_job: false,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Stages of the test in the order of their execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Resolve,
+    Connect,
+    Handshake,
+    Authorize,
+    Job,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Resolve => "resolve",
+            Stage::Connect => "connect",
+            Stage::Handshake => "handshake",
+            Stage::Authorize => "authorize",
+            Stage::Job => "job",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Duration of all finished stages
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timings {
+    pub resolve: Option<time::Duration>,
+    pub connect: Option<time::Duration>,
+    pub handshake: Option<time::Duration>,
+    pub authorize: Option<time::Duration>,
+}
+
+/// Information reported by the remote server
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerInfo {
+    /// Address used for the connection
+    pub address: Option<SocketAddr>,
+    /// Extranonce 1 assigned by the pool (hex encoded)
+    pub extra_nonce1: Option<String>,
+    pub extra_nonce2_size: Option<usize>,
+    /// Mask of version bits which can be rolled
+    pub version_mask: Option<u32>,
+    /// The last difficulty set by the pool
+    pub difficulty: Option<f64>,
+}
+
+/// The first job received from the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobSummary {
+    pub job_id: String,
+    pub previous_hash: String,
+    pub version: u32,
+    pub bits: u32,
+    pub ntime: u32,
+    pub clean_jobs: bool,
+    pub merkle_branch_len: usize,
+    /// Time since the start of the test
+    pub received_after: time::Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub stage: Stage,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub timings: Timings,
+    pub server: ServerInfo,
+    pub job: Option<JobSummary>,
+    pub failure: Option<Failure>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// The first stage which has not been finished yet
+    pub fn pending_stage(&self) -> Stage {
+        if self.timings.resolve.is_none() {
+            Stage::Resolve
+        } else if self.timings.connect.is_none() {
+            Stage::Connect
+        } else if self.timings.handshake.is_none() {
+            Stage::Handshake
+        } else if self.timings.authorize.is_none() {
+            Stage::Authorize
+        } else {
+            Stage::Job
+        }
+    }
+
+    /// Mark the pending stage as failed
+    pub fn fail<T: Into<String>>(&mut self, reason: T) {
+        self.failure = Some(Failure {
+            stage: self.pending_stage(),
+            reason: reason.into(),
+        });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            None => write!(f, "succeeded"),
+            Some(failure) => write!(f, "failed at {} stage: {}", failure.stage, failure.reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report_stages() {
+        let mut report = Report::new();
+        assert_eq!(report.pending_stage(), Stage::Resolve);
+        report.timings.resolve = Some(time::Duration::from_millis(1));
+        report.timings.connect = Some(time::Duration::from_millis(2));
+        assert_eq!(report.pending_stage(), Stage::Handshake);
+        assert!(report.is_ok());
+
+        report.fail("timeout");
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.failure,
+            Some(Failure {
+                stage: Stage::Handshake,
+                reason: "timeout".to_string()
+            })
+        );
+        assert_eq!(report.to_string(), "failed at handshake stage: timeout");
+
+        report.timings.handshake = Some(time::Duration::from_millis(3));
+        report.timings.authorize = Some(time::Duration::from_millis(4));
+        assert_eq!(report.pending_stage(), Stage::Job);
+    }
+}
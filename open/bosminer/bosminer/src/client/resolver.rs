@@ -0,0 +1,343 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Resolution of the pool hostname and connection to its addresses. The hostname is resolved on
+//! every connection attempt and all returned IPv4 and IPv6 addresses are tried in turn before the
+//! attempt fails, so a dead server behind a round-robin DNS record is skipped. The address of the
+//! last established connection is tried first on reconnect but only within the DNS TTL since the
+//! connection has been opened. Later the order of the fresh resolution is used to follow changes
+//! of the record. Results of connection attempts are kept per address for diagnostics.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+use crate::error;
+
+use failure::ResultExt;
+use ii_async_compat::prelude::*;
+use tokio::net::{self, TcpStream};
+
+use std::net::SocketAddr;
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Results of connection attempts to a single resolved address
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressStats {
+    pub address: SocketAddr,
+    /// Number of connection attempts
+    pub attempts: u64,
+    /// Number of failed connection attempts
+    pub failures: u64,
+    /// Time of the last connection attempt
+    pub last_attempt: time::SystemTime,
+    /// Error of the last connection attempt (`None` when it has succeeded)
+    pub last_error: Option<String>,
+}
+
+impl AddressStats {
+    fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            attempts: 0,
+            failures: 0,
+            last_attempt: time::UNIX_EPOCH,
+            last_error: None,
+        }
+    }
+
+    /// The last connection attempt to the address has failed
+    #[inline]
+    pub fn is_dead(&self) -> bool {
+        self.last_error.is_some()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Host and port of the last resolution, the state is reset when they are changed
+    target: Option<(String, u16)>,
+    /// Address of the last established connection and the time when it has been opened
+    preferred: Option<(SocketAddr, time::Instant)>,
+    /// Statistics of addresses in the order in which they have been resolved for the first time
+    stats: Vec<AddressStats>,
+}
+
+impl State {
+    /// Order of `addresses` in which they are tried. Duplicates are removed and the preferred
+    /// address is moved to the front when the connection to it is younger than `dns_ttl`.
+    fn order(
+        &self,
+        addresses: impl IntoIterator<Item = SocketAddr>,
+        dns_ttl: time::Duration,
+        now: time::Instant,
+    ) -> Vec<SocketAddr> {
+        let mut ordered: Vec<SocketAddr> = Vec::new();
+        // Resolver may return the same address for several socket types
+        for address in addresses {
+            if !ordered.contains(&address) {
+                ordered.push(address);
+            }
+        }
+        if let Some((preferred, since)) = self.preferred {
+            if now.duration_since(since) < dns_ttl {
+                if let Some(pos) = ordered.iter().position(|address| *address == preferred) {
+                    ordered[..=pos].rotate_right(1);
+                }
+            }
+        }
+        ordered
+    }
+
+    fn account(&mut self, address: SocketAddr, result: Result<(), String>) {
+        let pos = match self.stats.iter().position(|stats| stats.address == address) {
+            Some(pos) => pos,
+            None => {
+                if self.stats.len() >= Resolver::MAX_ADDRESSES {
+                    self.stats.remove(0);
+                }
+                self.stats.push(AddressStats::new(address));
+                self.stats.len() - 1
+            }
+        };
+        let stats = &mut self.stats[pos];
+        stats.attempts += 1;
+        stats.last_attempt = clock::system_time();
+        stats.last_error = result.err();
+        if stats.last_error.is_some() {
+            stats.failures += 1;
+        }
+    }
+}
+
+/// Resolves the pool hostname and keeps results of connection attempts to its addresses
+#[derive(Debug, Default)]
+pub struct Resolver {
+    state: StdMutex<State>,
+}
+
+impl Resolver {
+    /// Maximal number of addresses with kept statistics (the oldest ones are dropped)
+    const MAX_ADDRESSES: usize = 16;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock_state(&self) -> StdMutexGuard<State> {
+        self.state.lock().expect("BUG: cannot lock resolver state")
+    }
+
+    /// Resolve all addresses of the `host` in the order in which they should be tried
+    pub async fn resolve(
+        &self,
+        host: &str,
+        port: u16,
+        dns_ttl: time::Duration,
+    ) -> error::Result<Vec<SocketAddr>> {
+        let addresses = net::lookup_host((host, port))
+            .await
+            .context("Cannot resolve server address")?;
+
+        let mut state = self.lock_state();
+        let target = Some((host.to_string(), port));
+        if state.target != target {
+            // Addresses of the previous server are meaningless
+            *state = State {
+                target,
+                ..Default::default()
+            };
+        }
+        let addresses = state.order(addresses, dns_ttl, clock::now());
+        if addresses.is_empty() {
+            Err("Cannot resolve any IP address")?;
+        }
+        Ok(addresses)
+    }
+
+    /// Connect to the first reachable address from `addresses`. Each address is given the whole
+    /// `timeout` so that unreachable addresses cannot exhaust the time of the following ones.
+    pub async fn connect_to(
+        &self,
+        addresses: &[SocketAddr],
+        timeout: time::Duration,
+    ) -> error::Result<(TcpStream, SocketAddr)> {
+        let mut last_error = String::new();
+        for address in addresses {
+            let error = match TcpStream::connect(address).timeout(timeout).await {
+                Ok(Ok(stream)) => {
+                    let mut state = self.lock_state();
+                    state.account(*address, Ok(()));
+                    state.preferred = Some((*address, clock::now()));
+                    return Ok((stream, *address));
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => "connection timeout".to_string(),
+            };
+            debug!("Resolver: cannot connect to {}: {}", address, error);
+            self.lock_state().account(*address, Err(error.clone()));
+            last_error = error;
+        }
+        Err(error::ErrorKind::Connection(format!(
+            "cannot connect to any of {} address(es): {}",
+            addresses.len(),
+            last_error
+        ))
+        .into())
+    }
+
+    /// Resolve the `host` and connect to the first reachable address
+    pub async fn connect(
+        &self,
+        host: &str,
+        port: u16,
+        dns_ttl: time::Duration,
+        timeout: time::Duration,
+    ) -> error::Result<(TcpStream, SocketAddr)> {
+        let addresses = self.resolve(host, port, dns_ttl).await?;
+        self.connect_to(&addresses, timeout).await
+    }
+
+    /// Results of connection attempts to all addresses resolved for the current host
+    pub fn stats(&self) -> Vec<AddressStats> {
+        self.lock_state().stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::error::Classify;
+
+    use tokio::net::TcpListener;
+
+    const TIMEOUT: time::Duration = time::Duration::from_secs(1);
+
+    fn address(addr: &str) -> SocketAddr {
+        addr.parse().expect("BUG: invalid address")
+    }
+
+    /// Return address without any listening server
+    async fn dead_address() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("BUG: cannot bind");
+        listener.local_addr().expect("BUG: missing local address")
+    }
+
+    #[test]
+    fn test_order() {
+        let now = clock::now();
+        let ttl = time::Duration::from_secs(60);
+        let first = address("10.0.0.1:3333");
+        let second = address("[2001:db8::1]:3333");
+        let third = address("10.0.0.3:3333");
+        let mut state = State::default();
+
+        // duplicates are removed and the order of the resolution is kept
+        assert_eq!(
+            state.order(vec![first, second, first, third], ttl, now),
+            vec![first, second, third]
+        );
+
+        // the address of the last connection is tried first within the TTL
+        state.preferred = Some((third, now));
+        assert_eq!(
+            state.order(vec![first, second, third], ttl, now + ttl / 2),
+            vec![third, first, second]
+        );
+        assert_eq!(
+            state.order(vec![first, second, third], ttl, now + ttl),
+            vec![first, second, third]
+        );
+        // the preferred address is not used when it is no longer resolved
+        assert_eq!(
+            state.order(vec![second, first], ttl, now),
+            vec![second, first]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_literal() {
+        let resolver = Resolver::new();
+        let ttl = time::Duration::from_secs(60);
+        assert_eq!(
+            resolver.resolve("127.0.0.1", 3333, ttl).await.unwrap(),
+            vec![address("127.0.0.1:3333")]
+        );
+        // IPv6 address is resolved without brackets
+        assert_eq!(
+            resolver.resolve("::1", 3336, ttl).await.unwrap(),
+            vec![address("[::1]:3336")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_failover() {
+        let resolver = Resolver::new();
+        let mut listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("BUG: cannot bind");
+        let alive = listener.local_addr().expect("BUG: missing local address");
+        let dead = dead_address().await;
+        tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+
+        // the dead address is skipped and the attempt succeeds with the following one
+        let (_stream, connected) = resolver
+            .connect_to(&[dead, alive], TIMEOUT)
+            .await
+            .expect("BUG: cannot connect");
+        assert_eq!(connected, alive);
+
+        let stats = resolver.stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].address, dead);
+        assert_eq!((stats[0].attempts, stats[0].failures), (1, 1));
+        assert!(stats[0].is_dead());
+        assert_eq!(stats[1].address, alive);
+        assert_eq!((stats[1].attempts, stats[1].failures), (1, 0));
+        assert!(!stats[1].is_dead());
+
+        // the address of the last connection is preferred
+        let state = resolver.lock_state();
+        let ttl = time::Duration::from_secs(60);
+        assert_eq!(
+            state.order(vec![dead, alive], ttl, clock::now()),
+            vec![alive, dead]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_all_dead() {
+        let resolver = Resolver::new();
+        let dead = dead_address().await;
+
+        let error = resolver
+            .connect_to(&[dead, dead], TIMEOUT)
+            .await
+            .expect_err("BUG: connected to dead address");
+        // the client keeps reconnecting
+        assert!(error.is_transient());
+        assert_eq!(resolver.stats()[0].failures, 2);
+    }
+}
@@ -20,14 +20,19 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
-use crate::client;
-use crate::sync::event;
+use ii_logging::macros::*;
+
+use crate::client::{self, health};
+use crate::clock;
+use crate::sync::heartbeat::Heartbeat;
+use crate::sync::{self, event};
 use crate::work;
 
 use futures::channel::mpsc;
 use futures::lock::{Mutex, MutexGuard};
 use ii_async_compat::{futures, FutureExt};
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time;
 
@@ -37,14 +42,22 @@ use std::time;
 #[derive(Debug, Clone)]
 pub struct ClientHandle {
     pub client_handle: Arc<client::Handle>,
-    last_generated_work: u64,
+    /// Accepted shares seen by the last update. It is unknown until the first update because the
+    /// statistics can be read only asynchronously.
+    last_accepted_shares: Option<u64>,
+    /// Time since the client is continuously running
+    running_since: Option<time::Instant>,
+    /// Time since the client is continuously down (disconnected or failing)
+    down_since: Option<time::Instant>,
 }
 
 impl ClientHandle {
     pub fn new(client_handle: Arc<client::Handle>) -> Self {
         Self {
-            last_generated_work: Self::get_generated_work(&client_handle),
             client_handle,
+            last_accepted_shares: None,
+            running_since: None,
+            down_since: None,
         }
     }
 
@@ -53,6 +66,41 @@ impl ClientHandle {
         self.client_handle.is_running()
     }
 
+    fn update_health(&mut self, now: time::Instant) {
+        match self.client_handle.status() {
+            sync::Status::Running => {
+                self.down_since = None;
+                self.running_since.get_or_insert(now);
+            }
+            // Client which has not been started yet cannot be considered as down
+            sync::Status::Created | sync::Status::Stopped => {
+                self.running_since = None;
+                self.down_since = None;
+            }
+            _ => {
+                self.running_since = None;
+                self.down_since.get_or_insert(now);
+            }
+        }
+    }
+
+    /// Returns `true` when the client has been running for at least `period`
+    fn is_running_for(&self, period: time::Duration, now: time::Instant) -> bool {
+        self.running_since
+            .map(|since| now.duration_since(since) >= period)
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` when the enabled client is running or it has been down for less than
+    /// `timeout` so it can still reconnect before it is replaced
+    fn is_alive(&self, timeout: time::Duration, now: time::Instant) -> bool {
+        self.client_handle.is_enabled()
+            && self
+                .down_since
+                .map(|since| now.duration_since(since) < timeout)
+                .unwrap_or(true)
+    }
+
     #[inline]
     fn try_start(&self) -> Result<(), ()> {
         if self.client_handle.is_enabled() {
@@ -74,24 +122,29 @@ impl ClientHandle {
         }
     }
 
-    fn get_generated_work(client_handle: &Arc<client::Handle>) -> u64 {
-        *client_handle
+    async fn get_accepted_shares(&self) -> u64 {
+        self.client_handle
             .node
             .client_stats()
-            .generated_work()
+            .accepted()
             .take_snapshot()
+            .await
+            .shares
+            .value()
     }
 
-    pub fn get_delta_and_update_generated_work(&mut self) -> u64 {
-        let next_generated_work = Self::get_generated_work(&self.client_handle);
+    /// Return difficulty of shares accepted by the pool since the last call
+    pub async fn get_delta_and_update_accepted_shares(&mut self) -> u64 {
+        let next_accepted_shares = self.get_accepted_shares().await;
+        let last_accepted_shares = self
+            .last_accepted_shares
+            .replace(next_accepted_shares)
+            .unwrap_or(next_accepted_shares);
         assert!(
-            next_generated_work >= self.last_generated_work,
-            "generated work must be monotonic"
+            next_accepted_shares >= last_accepted_shares,
+            "accepted shares must be monotonic"
         );
-
-        let delta = next_generated_work - self.last_generated_work;
-        self.last_generated_work = next_generated_work;
-        delta
+        next_accepted_shares - last_accepted_shares
     }
 }
 
@@ -106,21 +159,32 @@ impl PartialEq for ClientHandle {
 pub struct GroupHandle {
     pub group_handle: Arc<client::Group>,
     active_client: Option<Arc<client::Handle>>,
-    generated_work: u64,
+    /// Client selected by failover to supply jobs of the group. It differs from the active
+    /// client while it is down and the failover timeout has not expired yet.
+    failover_client: Option<Arc<client::Handle>>,
+    /// Difficulty of shares accepted by pools of this group since the last reset. The work is
+    /// balanced by the accepted shares so a pool which is slow to accept them is not starved.
+    accepted_shares: u64,
     /// Current ratio of hashrate that this group has been allocated to. This number
     /// changes based on newly added/removed groups.
     pub share_ratio: f64,
+    /// Share ratio before the last change of quotas which is used during the transition to the
+    /// current one
+    pub previous_share_ratio: f64,
 }
 
 impl GroupHandle {
     pub fn new(group_handle: Arc<client::Group>) -> Self {
+        let share_ratio = group_handle
+            .descriptor
+            .get_fixed_share_ratio()
+            .unwrap_or_default();
         Self {
             active_client: None,
-            generated_work: 0,
-            share_ratio: group_handle
-                .descriptor
-                .get_fixed_share_ratio()
-                .unwrap_or_default(),
+            failover_client: None,
+            accepted_shares: 0,
+            share_ratio,
+            previous_share_ratio: share_ratio,
             group_handle,
         }
     }
@@ -140,36 +204,147 @@ impl GroupHandle {
 
     #[inline]
     pub fn get_quota(&self) -> Option<usize> {
-        self.group_handle.descriptor.get_quota()
+        self.group_handle.get_quota()
+    }
+
+    /// Share ratio at given `progress` of quota transition. It grows linearly from the previous
+    /// share ratio to the current one.
+    pub fn current_share_ratio(&self, progress: Option<f64>) -> f64 {
+        match progress {
+            Some(progress) => {
+                self.previous_share_ratio
+                    + (self.share_ratio - self.previous_share_ratio) * progress
+            }
+            None => self.share_ratio,
+        }
+    }
+
+    /// Ratio of shares accepted since the start of quota transition which is expected at given
+    /// `progress`. It is the average of the linear ramp so the instantaneous share ratio does not
+    /// jump when the scheduler compensates accumulated error.
+    pub fn expected_share_ratio(&self, progress: Option<f64>) -> f64 {
+        match progress {
+            Some(progress) => {
+                self.previous_share_ratio
+                    + (self.share_ratio - self.previous_share_ratio) * progress / 2.0
+            }
+            None => self.share_ratio,
+        }
     }
 
     async fn update_status(&mut self) {
         let mut scheduler_client_handles = self.group_handle.scheduler_client_handles.lock().await;
-        let mut generated_work_delta = 0;
+        let failover = self.group_handle.descriptor.get_failover();
+        let health_config = failover.health();
+        let now = clock::now();
+        let mut accepted_shares_delta = 0;
 
-        self.active_client = None;
+        let mut candidates = Vec::with_capacity(scheduler_client_handles.len());
+        let mut soft_failed = Vec::with_capacity(scheduler_client_handles.len());
         for scheduler_client_handle in scheduler_client_handles.iter_mut() {
-            generated_work_delta += scheduler_client_handle.get_delta_and_update_generated_work();
-            match self.active_client {
-                None => {
-                    if scheduler_client_handle.is_running() {
-                        self.active_client = Some(scheduler_client_handle.client_handle.clone());
-                    } else {
-                        let _ = scheduler_client_handle.try_start();
-                    }
-                }
-                Some(_) => {
-                    let _ = scheduler_client_handle.try_delayed_stop();
-                }
+            accepted_shares_delta += scheduler_client_handle
+                .get_delta_and_update_accepted_shares()
+                .await;
+            scheduler_client_handle.update_health(now);
+            let client_handle = &scheduler_client_handle.client_handle;
+            // Soft-failed or probed client is skipped so the next one in the group takes over
+            // its work
+            let available = client_handle.check_reject_breaker().await;
+            soft_failed.push(client_handle.is_soft_failed());
+            candidates.push(health::Candidate {
+                available,
+                running: scheduler_client_handle.is_running(),
+                settled: scheduler_client_handle.is_running_for(failover.hold_down(), now),
+                alive: scheduler_client_handle.is_alive(failover.timeout(), now),
+                score: client_handle.update_health(&health_config).await.value,
+            });
+        }
+        self.accepted_shares += accepted_shares_delta;
+
+        // The reject breaker never soft-fails the last enabled client of the group because
+        // mining with rejected shares is still better than not mining at all
+        let bypass_breaker = !scheduler_client_handles.iter().zip(&candidates).any(
+            |(scheduler_client_handle, candidate)| {
+                candidate.available && scheduler_client_handle.client_handle.is_enabled()
+            },
+        );
+        if bypass_breaker {
+            candidates
+                .iter_mut()
+                .for_each(|candidate| candidate.available = true);
+            soft_failed
+                .iter_mut()
+                .for_each(|soft_failed| *soft_failed = false);
+        }
+
+        // The selected client is kept during the failover timeout while it is healthy and the
+        // clients with higher priority take over only after they have been running for the
+        // hold-down period
+        let selected_idx = self.failover_client.as_ref().and_then(|failover_client| {
+            scheduler_client_handles
+                .iter()
+                .position(|scheduler_client_handle| {
+                    Arc::ptr_eq(&scheduler_client_handle.client_handle, failover_client)
+                })
+        });
+        let next_idx = health::select(&candidates, selected_idx, &health_config);
+        let next_client = next_idx.map(|idx| scheduler_client_handles[idx].client_handle.clone());
+
+        if self.failover_client != next_client {
+            if let Some(failover_client) = self.failover_client.as_ref() {
+                // Stale work of the replaced client must not be mined anymore
+                failover_client.engine_sender.invalidate();
+            }
+            if let Some(next_idx) = next_idx {
+                info!(
+                    "Group '{}' switched to pool '{}' (health score {:.2})",
+                    self.group_handle.descriptor.name,
+                    scheduler_client_handles[next_idx]
+                        .client_handle
+                        .descriptor()
+                        .await
+                        .get_labelled_url(),
+                    candidates[next_idx].score
+                );
             }
         }
 
-        self.generated_work += generated_work_delta;
+        // Clients with higher priority than the selected one are kept connected to detect their
+        // recovery. The following client is started only when the previous one has been down
+        // for longer than the failover timeout or when it is not healthy.
+        let mut standby = true;
+        for (idx, scheduler_client_handle) in scheduler_client_handles.iter().enumerate() {
+            let preceding = next_idx.map(|next_idx| idx < next_idx).unwrap_or(false);
+            scheduler_client_handle
+                .client_handle
+                .set_active(Some(idx) == next_idx && scheduler_client_handle.is_running());
+            if soft_failed[idx] {
+                let _ = scheduler_client_handle.try_delayed_stop();
+                continue;
+            }
+            if standby || preceding {
+                let _ = scheduler_client_handle.try_start();
+            } else {
+                let _ = scheduler_client_handle.try_delayed_stop();
+            }
+            if !preceding
+                && candidates[idx].alive
+                && candidates[idx].score >= health::min_score(&health_config, true)
+            {
+                standby = false;
+            }
+        }
+
+        self.active_client = next_client
+            .as_ref()
+            .filter(|next_client| next_client.is_running())
+            .cloned();
+        self.failover_client = next_client;
     }
 
     #[inline]
-    pub fn reset_generated_work(&mut self) {
-        self.generated_work = 0;
+    pub fn reset_accepted_shares(&mut self) {
+        self.accepted_shares = 0;
     }
 }
 
@@ -259,33 +434,48 @@ impl JobDispatcher {
         }
     }
 
-    async fn select_client(&self, generated_work_delta: u64) -> Option<Arc<client::Handle>> {
+    /// Select the active client of the group which is most behind its share ratio. Groups with
+    /// zero share ratio (zero quota) are used only as a backup when no other group has any
+    /// active client.
+    async fn select_client(&self) -> Option<Arc<client::Handle>> {
         let mut group_registry = self.group_registry.lock().await;
         if group_registry.is_empty() {
             return None;
         }
 
-        let mut total_generated_work = 0;
+        // NOTE: accepted shares can be reset here when quota transition has just finished
+        let share_ratios = group_registry.expected_share_ratios();
+        let mut total_accepted_shares = 0;
         for scheduler_group_handle in group_registry.iter_mut() {
             scheduler_group_handle.update_status().await;
-            total_generated_work += scheduler_group_handle.generated_work;
+            total_accepted_shares += scheduler_group_handle.accepted_shares;
         }
 
+        let candidates: Vec<_> = group_registry
+            .iter()
+            .zip(share_ratios)
+            .filter_map(|(scheduler_group_handle, share_ratio)| {
+                scheduler_group_handle
+                    .active_client
+                    .as_ref()
+                    .map(|active_client| (scheduler_group_handle, active_client, share_ratio))
+            })
+            .collect();
+        let backup_only = candidates
+            .iter()
+            .all(|(_, _, share_ratio)| *share_ratio <= 0.0);
+
         let mut next_client = None;
-        for scheduler_group_handle in group_registry.iter() {
-            let group_generated_work = scheduler_group_handle.generated_work;
-            let next_group_share_ratio = (group_generated_work + generated_work_delta) as f64
-                / (total_generated_work + generated_work_delta) as f64;
-            let next_error = (scheduler_group_handle.share_ratio - next_group_share_ratio).abs();
-            if let Some(active_client) = scheduler_group_handle.active_client.as_ref().cloned() {
-                match next_client {
-                    None => next_client = Some((active_client, next_error)),
-                    Some((_, min_error)) => {
-                        if min_error >= next_error {
-                            next_client = Some((active_client, next_error));
-                        }
-                    }
-                }
+        for (scheduler_group_handle, active_client, share_ratio) in candidates {
+            if share_ratio <= 0.0 && !backup_only {
+                continue;
+            }
+            // Difficulty of shares the group is missing to reach its share ratio
+            let deficit = share_ratio * total_accepted_shares as f64
+                - scheduler_group_handle.accepted_shares as f64;
+            match next_client {
+                Some((_, max_deficit)) if max_deficit >= deficit => {}
+                _ => next_client = Some((active_client.clone(), deficit)),
             }
         }
         next_client.map(|(next_client, _)| next_client)
@@ -301,10 +491,20 @@ impl JobDispatcher {
             }
             _ => {}
         }
-        if let Some(next_client) = self.select_client(generated_work_delta).await {
+        if let Some(next_client) = self.select_client().await {
             self.switch_client(next_client);
         }
     }
+
+    /// Disconnect the active client from backends and invalidate the work engine which has been
+    /// broadcast to them
+    fn detach(&mut self) {
+        let engine_sender = Arc::new(work::EngineSender::new(None));
+        // new jobs of the last active client are broadcast to nowhere from now on
+        engine_sender.swap_sender(self.active_client.get_engine_sender());
+        engine_sender.invalidate();
+        self.active_client = ActiveClient::None(engine_sender);
+    }
 }
 
 /// Responsible for dispatching new clients and planning generated jobs to be solved
@@ -313,6 +513,10 @@ pub struct JobExecutor {
     group_registry: Arc<Mutex<client::GroupRegistry>>,
     event_monitor: Mutex<Option<event::Monitor>>,
     dispatcher: Mutex<JobDispatcher>,
+    /// Scheduling is stopped for good when the miner is being shut down
+    halted: AtomicBool,
+    /// Scheduling is paused while the backend is being rebuilt
+    suspended: AtomicBool,
 }
 
 impl JobExecutor {
@@ -331,6 +535,8 @@ impl JobExecutor {
                 engine_sender,
                 client_manager.group_registry,
             )),
+            halted: AtomicBool::new(false),
+            suspended: AtomicBool::new(false),
         }
     }
 
@@ -363,7 +569,9 @@ impl JobExecutor {
         client.map(|client| client.solution_sender.clone())
     }
 
-    pub async fn run(self: Arc<Self>) {
+    /// Schedule clients until the miner is halted. The `heartbeat` is beaten at least once per
+    /// `SCHEDULE_INTERVAL`.
+    pub async fn run(self: Arc<Self>, heartbeat: Arc<Heartbeat>) {
         let mut event_receiver = self
             .event_monitor
             .lock()
@@ -373,6 +581,7 @@ impl JobExecutor {
             .subscribe();
 
         loop {
+            heartbeat.beat();
             let last_generated_work = self.frontend.get_generated_work();
 
             // Interrupt waiting when client status changes
@@ -389,10 +598,287 @@ impl JobExecutor {
             // Determine how much work has been generated from last run
             let generated_work_delta = self.frontend.get_generated_work() - last_generated_work;
 
-            self.lock_dispatcher()
-                .await
-                .schedule(generated_work_delta)
-                .await;
+            let mut dispatcher = self.lock_dispatcher().await;
+            if self.halted.load(Ordering::Relaxed) {
+                break;
+            }
+            if !self.suspended.load(Ordering::Relaxed) {
+                dispatcher.schedule(generated_work_delta).await;
+            }
         }
     }
+
+    /// Stop scheduling of clients and cut off backends from any work
+    pub async fn halt(&self) {
+        let mut dispatcher = self.lock_dispatcher().await;
+        self.halted.store(true, Ordering::Relaxed);
+        dispatcher.detach();
+    }
+
+    #[inline]
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::Relaxed)
+    }
+
+    /// Cut off backends from any work until the scheduling is resumed
+    pub async fn suspend(&self) {
+        let mut dispatcher = self.lock_dispatcher().await;
+        self.suspended.store(true, Ordering::Relaxed);
+        dispatcher.detach();
+    }
+
+    /// Resume scheduling of clients after `suspend`. The active client is selected again in the
+    /// next scheduling round.
+    pub fn resume(&self) {
+        self.suspended.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils;
+    use crate::test_utils::simulation::{self, Event};
+
+    use bosminer_config::{GroupDescriptor, GroupFailover, LoadBalanceStrategy};
+
+    use std::time;
+
+    const HOUR: time::Duration = time::Duration::from_secs(3600);
+    const MINUTE: time::Duration = time::Duration::from_secs(60);
+
+    /// Work is split among groups according to their quotas and the remaining group takes over
+    /// all work when the other one loses its only pool
+    #[test]
+    fn test_quota_simulation() {
+        let mut config = simulation::Config::new(2e12);
+        config.vardiff = false;
+        config.groups = vec![
+            (
+                GroupDescriptor::new("A".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("B".to_string(), false, LoadBalanceStrategy::Quota(3)),
+                1,
+            ),
+        ];
+        let report = simulation::simulate(
+            config,
+            24 * HOUR,
+            vec![(12 * HOUR, Event::DisablePool { group: 1, pool: 0 })],
+        );
+        assert_eq!(report.events.len(), 1);
+
+        let total: u64 = report.pools.iter().map(|pool| pool.generated_work).sum();
+        let ratio = report.pools[0].generated_work as f64 / total as f64;
+        // a quarter of work during the first half of the day and all work during the second one
+        assert!(
+            (ratio - 0.625).abs() < 0.05,
+            "unexpected share ratio {}",
+            ratio
+        );
+    }
+
+    /// Audited work is split among groups according to their quotas and every work solver
+    /// mines for both of them
+    #[test]
+    fn test_quota_audit_simulation() {
+        let mut config = simulation::Config::new(2e12);
+        config.vardiff = false;
+        config.groups = vec![
+            (
+                GroupDescriptor::new("A".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("B".to_string(), false, LoadBalanceStrategy::Quota(3)),
+                1,
+            ),
+        ];
+        let report = simulation::simulate(
+            config,
+            12 * HOUR,
+            vec![(
+                HOUR,
+                Event::StartAudit {
+                    duration: 10 * HOUR,
+                },
+            )],
+        );
+
+        let audit = report.work_audit.expect("BUG: missing work audit");
+        assert!(!audit.recording);
+        assert_eq!(audit.unmatched, 0);
+        test_utils::assert_distribution(&audit.client_work(), &[1.0, 3.0], 0.05);
+        assert!(audit.work.iter().flatten().all(|work| *work > 0));
+    }
+
+    /// Group with zero quota is a backup which gets work only when no other group has any
+    /// running pool
+    #[test]
+    fn test_zero_quota_simulation() {
+        let mut config = simulation::Config::new(2e12);
+        config.vardiff = false;
+        config.groups = vec![
+            (
+                GroupDescriptor::new("A".to_string(), false, LoadBalanceStrategy::Quota(4)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("B".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("Backup".to_string(), false, LoadBalanceStrategy::Quota(0)),
+                1,
+            ),
+        ];
+        let report = simulation::simulate(
+            config,
+            12 * HOUR,
+            vec![
+                (6 * HOUR, Event::DisablePool { group: 0, pool: 0 }),
+                (8 * HOUR, Event::DisablePool { group: 1, pool: 0 }),
+            ],
+        );
+        assert_eq!(report.events.len(), 2);
+
+        let checkpoints = &report.event_generated_work;
+        let ratio = checkpoints[0][0] as f64 / (checkpoints[0][0] + checkpoints[0][1]) as f64;
+        // work is split by accepted shares according to the quotas
+        assert!(
+            (ratio - 0.8).abs() < 0.05,
+            "unexpected share ratio {}",
+            ratio
+        );
+        // the backup group is not used while any other group is mining
+        assert_eq!(checkpoints[1][2], 0);
+
+        let generated_work: Vec<_> = report
+            .pools
+            .iter()
+            .map(|pool| pool.generated_work)
+            .collect();
+        // all work goes to the backup group when the others are disabled
+        assert!(generated_work[0] - checkpoints[1][0] < generated_work[2] / 100);
+        assert!(generated_work[1] - checkpoints[1][1] < generated_work[2] / 100);
+        assert!(generated_work[2] > 0);
+    }
+
+    /// Work distribution converges gradually to the changed quotas and then follows them
+    #[test]
+    fn test_quota_change_simulation() {
+        let mut config = simulation::Config::new(2e12);
+        config.vardiff = false;
+        config.groups = vec![
+            (
+                GroupDescriptor::new("A".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+            (
+                GroupDescriptor::new("B".to_string(), false, LoadBalanceStrategy::Quota(1)),
+                1,
+            ),
+        ];
+        let report = simulation::simulate(
+            config,
+            12 * HOUR,
+            vec![
+                (2 * HOUR, Event::Checkpoint),
+                (
+                    4 * HOUR,
+                    Event::SetQuota {
+                        group: 1,
+                        quota: 4,
+                        horizon: 2 * HOUR,
+                    },
+                ),
+                (6 * HOUR, Event::Checkpoint),
+            ],
+        );
+        assert_eq!(report.events.len(), 3);
+
+        let mut checkpoints = report.event_generated_work.clone();
+        checkpoints.push(
+            report
+                .pools
+                .iter()
+                .map(|pool| pool.generated_work)
+                .collect(),
+        );
+        let ratio = |from: usize, to: usize| {
+            let a = checkpoints[to][0] - checkpoints[from][0];
+            let b = checkpoints[to][1] - checkpoints[from][1];
+            a as f64 / (a + b) as f64
+        };
+        let assert_ratio = |ratio: f64, expected: f64| {
+            assert!(
+                (ratio - expected).abs() < 0.05,
+                "unexpected share ratio {} (expected {})",
+                ratio,
+                expected
+            );
+        };
+        // even split before the change
+        assert_ratio(ratio(0, 1), 0.5);
+        // no burst of work during the horizon, share ratio goes linearly from 1/2 to 1/5
+        assert_ratio(ratio(1, 2), 0.35);
+        // the new quotas are followed after the horizon
+        assert_ratio(ratio(2, 3), 0.2);
+    }
+
+    /// The backup pool takes over the work only after the failover timeout and the primary pool
+    /// gets it back when it has been online for the hold-down period
+    #[test]
+    fn test_failover_simulation() {
+        let mut descriptor = GroupDescriptor::default();
+        descriptor.failover = Some(GroupFailover {
+            timeout: Some(MINUTE.as_secs()),
+            hold_down: Some(10 * MINUTE.as_secs()),
+            health: None,
+        });
+        let mut config = simulation::Config::new(2e12);
+        config.vardiff = false;
+        config.groups = vec![(descriptor, 2)];
+        let report = simulation::simulate(
+            config,
+            4 * HOUR,
+            vec![
+                (
+                    HOUR,
+                    Event::SetPoolOnline {
+                        group: 0,
+                        pool: 0,
+                        online: false,
+                    },
+                ),
+                (HOUR + 30 * MINUTE, Event::Checkpoint),
+                (
+                    2 * HOUR,
+                    Event::SetPoolOnline {
+                        group: 0,
+                        pool: 0,
+                        online: true,
+                    },
+                ),
+                (2 * HOUR + 5 * MINUTE, Event::Checkpoint),
+            ],
+        );
+        assert_eq!(report.events.len(), 4);
+
+        let checkpoints = &report.event_generated_work;
+        // nothing is mined for the unreachable primary pool
+        assert!(checkpoints[1][0] - checkpoints[0][0] < checkpoints[1][1] / 100);
+        // the recovered primary pool does not get any work during the hold-down period
+        assert_eq!(checkpoints[3][0], checkpoints[2][0]);
+
+        let total: u64 = report.pools.iter().map(|pool| pool.generated_work).sum();
+        let ratio = report.pools[1].generated_work as f64 / total as f64;
+        // the backup pool is used from the failover timeout to the end of hold-down period
+        assert!(
+            (ratio - 69.0 / 240.0).abs() < 0.05,
+            "unexpected share ratio {}",
+            ratio
+        );
+    }
 }
@@ -0,0 +1,364 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Tracking of the pool session across reconnects. The session is identified by the channel and
+//! extranonce prefix assigned by the pool. When the pool assigns the same identity after
+//! reconnect, the session has been resumed and work generated from the previous connection is
+//! still valid so it does not have to be invalidated.
+//! Security parameters of the secure connection (TLS or the noise protocol of Stratum V2) are
+//! recorded with each established session.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+
+use ii_stratum::v2::noise::SessionInfo;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::time;
+
+/// Identity of the session assigned by the pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    channel_id: u32,
+    extranonce_prefix: Vec<u8>,
+}
+
+impl Token {
+    pub fn new(channel_id: u32, extranonce_prefix: &[u8]) -> Self {
+        Self {
+            channel_id,
+            extranonce_prefix: extranonce_prefix.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// There has been no previous session
+    Fresh,
+    /// The pool has resumed the previous session
+    Resumed,
+    /// The pool has started a new session instead of resuming the previous one
+    Refused,
+}
+
+impl Outcome {
+    /// Returns `true` when work from the previous connection cannot be used in the new session
+    pub fn invalidates_work(&self) -> bool {
+        match self {
+            Outcome::Fresh | Outcome::Refused => true,
+            Outcome::Resumed => false,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Fresh => "fresh",
+            Outcome::Resumed => "resumed",
+            Outcome::Refused => "refused",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Security parameters of the secure connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct Security {
+    /// Negotiated TLS version or name of the noise protocol
+    pub protocol: String,
+    pub cipher: String,
+    /// Hex encoded SHA-256 digest of the server certificate (TLS) or of the static public key of
+    /// the server (noise)
+    pub fingerprint: String,
+    pub valid_from: time::SystemTime,
+    pub not_valid_after: time::SystemTime,
+    /// The server certificate has been verified against the pinned authority which is the CA
+    /// certificate configured for TLS or the authority key of the Stratum V2 URL. It is `None`
+    /// when there is no pinned authority (TLS with built-in root certificates).
+    pub pin_matched: Option<bool>,
+}
+
+impl From<SessionInfo> for Security {
+    fn from(session_info: SessionInfo) -> Self {
+        Self {
+            protocol: session_info.protocol.to_string(),
+            cipher: session_info.cipher.to_string(),
+            fingerprint: session_info.fingerprint,
+            valid_from: session_info.valid_from,
+            not_valid_after: session_info.not_valid_after,
+            // the noise handshake fails unless the static key of the server is certified by the
+            // pinned authority so the pin of an established session always matches
+            pin_matched: Some(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub system_time: time::SystemTime,
+    pub outcome: Outcome,
+    /// Security parameters of the connection (only for secure protocols)
+    pub security: Option<Security>,
+}
+
+/// Returns `true` when the certificate of the secure connection expires within `window`
+pub fn is_expiring(security: &Security, window: time::Duration, now: time::SystemTime) -> bool {
+    security
+        .not_valid_after
+        .duration_since(now)
+        .map_or(true, |remaining| remaining <= window)
+}
+
+/// Warn when the certificate of the secure connection to `server` expires within `window`
+pub fn check_expiry(security: &Security, window: time::Duration, server: &dyn fmt::Display) {
+    if is_expiring(security, window, clock::system_time()) {
+        warn!(
+            "Stratum: certificate {} of {} expires at {}",
+            security.fingerprint,
+            server,
+            chrono::DateTime::<chrono::Utc>::from(security.not_valid_after)
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct Tracker {
+    /// Identity of the last established session
+    token: Option<Token>,
+    /// The session is established on the current connection
+    connected: bool,
+    /// Invalidation of work has been deferred until it is known whether the session is resumed
+    pending: bool,
+    /// Bounded history of established sessions
+    history: VecDeque<Event>,
+}
+
+impl Tracker {
+    const HISTORY_LENGTH: usize = 32;
+
+    pub fn new() -> Self {
+        Self {
+            token: None,
+            connected: false,
+            pending: false,
+            history: VecDeque::with_capacity(Self::HISTORY_LENGTH),
+        }
+    }
+
+    /// Returns `true` when work from the disconnected session is kept for possible resumption
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    pub fn history(&self) -> Vec<Event> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Security parameters of the current connection
+    pub fn security(&self) -> Option<Security> {
+        if !self.connected {
+            return None;
+        }
+        self.history.back().and_then(|event| event.security.clone())
+    }
+
+    /// Account a session established on a new connection and determine whether it resumes the
+    /// previous one
+    pub fn establish(&mut self, token: Token) -> Outcome {
+        self.establish_secure(token, None)
+    }
+
+    /// Same as `establish` but it also records security parameters of the connection
+    pub fn establish_secure(&mut self, token: Token, security: Option<Security>) -> Outcome {
+        let outcome = match self.token.replace(token.clone()) {
+            None => Outcome::Fresh,
+            Some(previous) if previous == token => Outcome::Resumed,
+            Some(_) => Outcome::Refused,
+        };
+        self.connected = true;
+        self.pending = false;
+
+        if self.history.len() == Self::HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(Event {
+            system_time: clock::system_time(),
+            outcome,
+            security,
+        });
+        outcome
+    }
+
+    /// Account termination of the connection. Returns `true` when the current work can be kept
+    /// because the established session may be resumed after reconnect. Failed connection
+    /// attempt does not keep the work.
+    pub fn disconnect(&mut self) -> bool {
+        self.pending = mem::replace(&mut self.connected, false) && self.token.is_some();
+        self.pending
+    }
+
+    /// Forget the session so that it is not resumed (e.g. the client has been stopped)
+    pub fn close(&mut self) {
+        self.token = None;
+        self.connected = false;
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_session_resumed() {
+        let mut tracker = Tracker::new();
+        let token = Token::new(0, &[0x6c, 0x6f, 0x01]);
+
+        assert_eq!(tracker.establish(token.clone()), Outcome::Fresh);
+        // connection dropped, the work is kept until reconnect
+        assert!(tracker.disconnect());
+        assert!(tracker.is_pending());
+
+        let outcome = tracker.establish(token.clone());
+        assert_eq!(outcome, Outcome::Resumed);
+        assert!(!outcome.invalidates_work());
+        assert!(!tracker.is_pending());
+
+        let outcomes: Vec<_> = tracker
+            .history()
+            .iter()
+            .map(|event| event.outcome)
+            .collect();
+        assert_eq!(outcomes, vec![Outcome::Fresh, Outcome::Resumed]);
+    }
+
+    #[test]
+    fn test_session_refused() {
+        let mut tracker = Tracker::new();
+
+        tracker.establish(Token::new(0, &[0x6c, 0x6f, 0x01]));
+        assert!(tracker.disconnect());
+        // different extranonce prefix means a new session
+        let outcome = tracker.establish(Token::new(0, &[0xde, 0xad]));
+        assert_eq!(outcome, Outcome::Refused);
+        assert!(outcome.invalidates_work());
+        // different channel as well
+        assert!(tracker.disconnect());
+        assert_eq!(
+            tracker.establish(Token::new(1, &[0xde, 0xad])),
+            Outcome::Refused
+        );
+    }
+
+    #[test]
+    fn test_work_invalidation() {
+        let mut tracker = Tracker::new();
+
+        // connection without established session invalidates work
+        assert!(!tracker.disconnect());
+        assert_eq!(tracker.establish(Token::new(0, &[])), Outcome::Fresh);
+        assert!(tracker.disconnect());
+        // failed reconnect attempt does not keep the work any longer but the session may be
+        // still resumed later
+        assert!(!tracker.disconnect());
+        assert!(!tracker.is_pending());
+        assert_eq!(tracker.establish(Token::new(0, &[])), Outcome::Resumed);
+
+        // stopped client does not resume the session
+        tracker.close();
+        assert!(!tracker.disconnect());
+        assert_eq!(tracker.establish(Token::new(0, &[])), Outcome::Fresh);
+    }
+
+    fn build_security(not_valid_after: time::SystemTime) -> Security {
+        SessionInfo {
+            protocol: "Noise_NX_25519_ChaChaPoly_BLAKE2s",
+            cipher: "ChaChaPoly",
+            fingerprint: "00".repeat(32),
+            valid_from: time::UNIX_EPOCH,
+            not_valid_after,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_session_security() {
+        let mut tracker = Tracker::new();
+        let security = build_security(time::UNIX_EPOCH + time::Duration::from_secs(100));
+
+        tracker.establish_secure(Token::new(0, &[]), Some(security.clone()));
+        assert_eq!(tracker.security(), Some(security.clone()));
+        // security of the closed connection is kept only in the history
+        tracker.disconnect();
+        assert_eq!(tracker.security(), None);
+        tracker.establish(Token::new(0, &[]));
+        assert_eq!(tracker.security(), None);
+
+        let history: Vec<_> = tracker
+            .history()
+            .into_iter()
+            .map(|event| event.security)
+            .collect();
+        assert_eq!(history, vec![Some(security), None]);
+    }
+
+    #[test]
+    fn test_certificate_expiry() {
+        let now = time::UNIX_EPOCH + time::Duration::from_secs(1000);
+        let window = time::Duration::from_secs(100);
+
+        assert!(!is_expiring(
+            &build_security(now + time::Duration::from_secs(101)),
+            window,
+            now
+        ));
+        assert!(is_expiring(
+            &build_security(now + time::Duration::from_secs(100)),
+            window,
+            now
+        ));
+        // already expired certificate
+        assert!(is_expiring(
+            &build_security(now - time::Duration::from_secs(1)),
+            window,
+            now
+        ));
+    }
+
+    #[test]
+    fn test_history_bound() {
+        let mut tracker = Tracker::new();
+        for _ in 0..Tracker::HISTORY_LENGTH * 2 {
+            tracker.establish(Token::new(0, &[]));
+            tracker.disconnect();
+        }
+        let history = tracker.history();
+        assert_eq!(history.len(), Tracker::HISTORY_LENGTH);
+        assert_eq!(history[0].outcome, Outcome::Resumed);
+    }
+}
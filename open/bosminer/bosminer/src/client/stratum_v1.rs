@@ -0,0 +1,2659 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Native Stratum V1 client which talks the JSON-RPC protocol directly to the pool. Jobs are
+//! built from `mining.notify` notifications by completing the coinbase transaction with
+//! extranonce 1 assigned by the pool and extranonce 2 rolled by the client.
+
+use ii_logging::macros::*;
+
+use super::health;
+use super::probe;
+use super::resolver;
+use super::session;
+use super::submission;
+use super::tls;
+use crate::bus;
+use crate::clock;
+use crate::error::{self, Classify};
+use crate::job;
+use crate::log_id;
+use crate::node;
+use crate::stats;
+use crate::sync;
+use crate::work;
+
+use failure::ResultExt;
+
+use ii_bitcoin::HashTrait;
+
+use bosminer_config::{client_url_host, ClientCoinbaseCheck, ClientDescriptor};
+use bosminer_macros::ClientNode;
+
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::lock::Mutex;
+use futures::stream::{SplitSink, SplitStream};
+use ii_async_compat::prelude::*;
+use ii_async_compat::select;
+
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
+use std::time;
+
+use ii_stratum::v1;
+use ii_stratum::v1::messages::{
+    Authorize, BooleanResult, Configure, ExtranonceSubscribe, JobId, Notify, SetDifficulty,
+    SetExtranonce, SetVersionMask, Submit, Subscribe, SubscribeResult, SuggestDifficulty,
+    VersionMask, VersionRolling,
+};
+use ii_stratum::v1::rpc::{Request, RequestPayload, StratumError, StratumResult};
+use ii_stratum::v1::{build_message_from_frame, Handler};
+use ii_stratum_proxy::translation::{CoinbaseCheck, CoinbaseVerifier};
+use tokio::net::TcpStream;
+
+type Framed = tls::Framed<v1::Codec>;
+type FramedSink = SplitSink<Framed, v1::Frame>;
+type FramedStream = SplitStream<Framed>;
+
+/// Error code used by pools for solutions of a job which is no longer valid
+const STALE_JOB_ERROR_CODE: i32 = 21;
+/// Error code used by pools for solutions which have been already submitted
+const DUPLICATE_SHARE_ERROR_CODE: i32 = 22;
+
+#[derive(Debug)]
+pub struct ConnectionDetails {
+    pub user: String,
+    pub password: Option<String>,
+    pub host: String,
+    pub port: u16,
+    pub fragment: Option<String>,
+    pub coinbase_check: Option<ClientCoinbaseCheck>,
+    /// Share difficulty suggested to the pool (shares meeting the pool target are still submitted)
+    pub suggested_difficulty: Option<usize>,
+    /// Settings of TLS session wrapping the connection (if any)
+    pub tls: Option<tls::Settings>,
+    /// Warn when the certificate of the upstream server expires within this window
+    pub cert_expiry_warning: time::Duration,
+    /// Address of an established connection is preferred on reconnect only within this time
+    pub dns_ttl: time::Duration,
+}
+
+impl ConnectionDetails {
+    pub fn from_descriptor(descriptor: &ClientDescriptor) -> Self {
+        Self {
+            user: descriptor.user.clone(),
+            password: descriptor.password.clone(),
+            host: descriptor.host.clone(),
+            port: descriptor.port(),
+            fragment: descriptor.fragment.clone(),
+            coinbase_check: descriptor.coinbase_check.clone(),
+            suggested_difficulty: descriptor.suggested_difficulty(),
+            tls: tls::Settings::from_descriptor(descriptor),
+            cert_expiry_warning: descriptor.cert_expiry_warning,
+            dns_ttl: descriptor.dns_ttl,
+        }
+    }
+
+    fn get_host_and_port(&self) -> String {
+        format!("{}:{}", client_url_host(&self.host), self.port)
+    }
+
+    fn try_enable_xnsub(&self) -> bool {
+        self.host.find(".nicehash.com").is_some()
+            || self
+                .fragment
+                .as_ref()
+                .and_then(|fragment| fragment.find("xnsub"))
+                .is_some()
+    }
+}
+
+/// Convert pool difficulty to target. Difficulties lower than 1 are rounded up to 1 because
+/// solutions of difficulty 1 are valid for them, too. Fractional difficulties are not truncated
+/// because the pool would reject solutions meeting only the integer part of the difficulty.
+fn target_from_difficulty(difficulty: f32) -> ii_bitcoin::Target {
+    if difficulty > 1.0 {
+        ii_bitcoin::Target::from_precise_difficulty(difficulty as f64)
+    } else {
+        Default::default()
+    }
+}
+
+/// Encode extranonce 2 `value` as little endian number of `size` bytes
+fn extra_nonce2_bytes(value: u32, size: usize) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes().to_vec();
+    bytes.resize(size, 0);
+    bytes
+}
+
+/// Complete coinbase transaction of the job with extranonces
+fn build_coinbase(notify: &Notify, extra_nonce1: &v1::ExtraNonce1, extra_nonce2: &[u8]) -> Vec<u8> {
+    [
+        notify.coin_base_1(),
+        extra_nonce1.0.as_ref().as_slice(),
+        extra_nonce2,
+        notify.coin_base_2(),
+    ]
+    .concat()
+}
+
+/// Compute merkle root of the block from coinbase transaction and merkle branch of the job
+fn merkle_root(coinbase: &[u8], merkle_branch: &[v1::HexBytes]) -> ii_bitcoin::DHash {
+    merkle_branch
+        .iter()
+        .fold(ii_bitcoin::DHash::hash(coinbase), |root, tx_hash| {
+            ii_bitcoin::DHash::hash(&[&root.into_inner()[..], tx_hash.as_ref().as_slice()].concat())
+        })
+}
+
+/// Build frame of the request with `id` which is used for pairing with the response
+fn build_request_frame<M>(id: u32, message: M) -> error::Result<v1::Frame>
+where
+    M: TryInto<RequestPayload, Error = ii_stratum::error::Error>,
+{
+    let rpc: v1::rpc::Rpc = Request {
+        id: Some(id),
+        payload: message.try_into()?,
+    }
+    .into();
+    Ok(v1::Frame::try_from(rpc)?)
+}
+
+/// Key of the configure response with the maximal number of seconds the ntime of any job may be
+/// rolled forward
+const NTIME_ROLL_LIMIT_KEY: &str = "ntime-rolling.max-offset";
+
+/// Return BIP320 version bits the pool allows to roll. Zero mask is returned when the pool does
+/// not support version rolling and jobs have to be mined with their own version.
+fn parse_version_rolling(result: &StratumResult) -> error::Result<u32> {
+    if result.0["version-rolling"].as_bool() != Some(true) {
+        return Ok(0);
+    }
+    let mask: VersionMask = serde_json::from_value(result.0["version-rolling.mask"].clone())
+        .context("Cannot parse version rolling mask")?;
+    Ok((mask.0).0 & ii_stratum::BIP320_N_VERSION_MASK)
+}
+
+/// Return the maximal ntime roll advertised by the pool in the configure response. The limit is
+/// optional and zero is returned when the pool does not advertise it.
+fn parse_ntime_roll_limit(result: &StratumResult) -> u32 {
+    result.0[NTIME_ROLL_LIMIT_KEY]
+        .as_u64()
+        .map_or(0, |limit| limit.min(u32::MAX as u64) as u32)
+}
+
+/// Open connection to the stratum server over connected TCP `stream` (the stream is wrapped in
+/// TLS session when it is required by the protocol)
+async fn open_connection(
+    connection_details: &ConnectionDetails,
+    stream: TcpStream,
+) -> error::Result<Framed> {
+    let stream = tls::Stream::connect(
+        &connection_details.host,
+        stream,
+        connection_details.tls.as_ref(),
+    )
+    .await?;
+    Ok(Framed::new(stream, Default::default()))
+}
+
+#[derive(Debug, Clone)]
+pub struct StratumJob {
+    client: Weak<StratumClient>,
+    id: u32,
+    /// Identifier of the job assigned by the pool
+    job_id: JobId,
+    extra_nonce2: Vec<u8>,
+    /// Coinbase transaction completed with the extranonces
+    coinbase: Vec<u8>,
+    merkle_branch: Vec<v1::HexBytes>,
+    /// The job is valid until the pool requests cleaning of all jobs
+    epoch: u32,
+    version: u32,
+    /// Version bits allowed by the pool at the time the job has been received
+    version_mask: u32,
+    /// Ntime roll limit advertised by the pool (zero when there is no limit)
+    ntime_roll_limit: u32,
+    prev_hash: ii_bitcoin::DHash,
+    merkle_root: ii_bitcoin::DHash,
+    time: u32,
+    bits: u32,
+    target: ii_bitcoin::Target,
+}
+
+impl job::Bitcoin for StratumJob {
+    fn origin(&self) -> Weak<dyn node::Client> {
+        self.client.clone()
+    }
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn version_mask(&self) -> u32 {
+        self.version_mask
+    }
+
+    fn previous_hash(&self) -> &ii_bitcoin::DHash {
+        &self.prev_hash
+    }
+
+    fn merkle_root(&self) -> &ii_bitcoin::DHash {
+        &self.merkle_root
+    }
+
+    fn time(&self) -> u32 {
+        self.time
+    }
+
+    fn ntime_roll_limit(&self) -> u32 {
+        self.ntime_roll_limit
+    }
+
+    fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    fn target(&self) -> ii_bitcoin::Target {
+        self.target
+    }
+
+    fn id(&self) -> Option<u32> {
+        Some(self.id)
+    }
+
+    fn coinbase(&self) -> Option<job::Coinbase> {
+        Some(job::Coinbase {
+            tx: self.coinbase.clone(),
+            merkle_branch: self
+                .merkle_branch
+                .iter()
+                .map(|tx_hash| tx_hash.as_ref().clone())
+                .collect(),
+        })
+    }
+
+    fn is_valid(&self) -> bool {
+        self.client
+            .upgrade()
+            .map(|client| client.epoch.load(Ordering::Relaxed) == self.epoch)
+            .unwrap_or(false)
+    }
+}
+
+/// Request waiting for the response from the pool
+#[derive(Debug)]
+enum PendingRequest {
+    Configure,
+    Subscribe,
+    ExtranonceSubscribe,
+    SuggestDifficulty,
+    Authorize,
+    Submit(submission::Key),
+}
+
+/// Extranonce assigned by the pool
+#[derive(Debug, Clone)]
+struct Subscription {
+    extra_nonce1: v1::ExtraNonce1,
+    extra_nonce2_size: usize,
+}
+
+/// Helper task for `StratumClient` that implements Stratum V1 visitor which processes incoming
+/// messages from remote server. It also sends all requests so that the responses can be paired
+/// with them.
+struct StratumEventHandler {
+    client: Arc<StratumClient>,
+    connection_tx: FramedSink,
+    next_request_id: u32,
+    requests: HashMap<u32, PendingRequest>,
+    /// The pool has answered the configure request (even when it has refused version rolling)
+    configured: bool,
+    /// BIP320 bits allowed by the pool for rolling of the next job (zero without version rolling)
+    version_mask: u32,
+    /// Ntime roll limit advertised by the pool for all jobs
+    ntime_roll_limit: u32,
+    subscription: Option<Subscription>,
+    /// Extranonce changed by the pool which is used since the next job
+    next_subscription: Option<Subscription>,
+    authorized: bool,
+    /// Last job notification which is used again with the next extranonce 2 when rolling the job
+    last_notify: Option<Notify>,
+    /// Time when the last job has been sent for solving
+    last_job_time: time::Instant,
+    /// Mining target for the next job that is to be solved
+    current_target: Option<ii_bitcoin::Target>,
+    /// The next difficulty change is attributed to the difficulty suggested by the client
+    suggestion_pending: bool,
+    /// Result of the last visited message
+    status: Option<error::Result<()>>,
+    /// Security parameters of the TLS connection recorded when the session is established
+    security: Option<session::Security>,
+}
+
+impl StratumEventHandler {
+    fn new(
+        client: Arc<StratumClient>,
+        connection_tx: FramedSink,
+        security: Option<session::Security>,
+    ) -> Self {
+        Self {
+            client,
+            connection_tx,
+            next_request_id: 0,
+            requests: HashMap::new(),
+            configured: false,
+            version_mask: ii_stratum::BIP320_N_VERSION_MASK,
+            ntime_roll_limit: 0,
+            subscription: None,
+            next_subscription: None,
+            authorized: false,
+            last_notify: None,
+            last_job_time: clock::now(),
+            current_target: None,
+            suggestion_pending: false,
+            status: None,
+            security,
+        }
+    }
+
+    /// Mining session is established when all initial requests have been successful
+    fn is_operational(&self) -> bool {
+        self.configured && self.subscription.is_some() && self.authorized
+    }
+
+    /// Mine jobs only with their own version when the pool does not allow version rolling
+    fn disable_version_rolling(&mut self, reason: &str) {
+        warn!(
+            "Stratum: pool does not allow version rolling ({}), jobs are mined with their own \
+             version",
+            reason
+        );
+        self.version_mask = 0;
+    }
+
+    fn set_status(&mut self, status: error::Result<()>) {
+        // keep the first error
+        if let Some(Err(_)) = self.status {
+            return;
+        }
+        self.status = Some(status);
+    }
+
+    async fn send_request<M>(&mut self, message: M, request: PendingRequest) -> error::Result<()>
+    where
+        M: TryInto<RequestPayload, Error = ii_stratum::error::Error>,
+    {
+        let id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+
+        let frame = build_request_frame(id, message)?;
+        match self
+            .connection_tx
+            .send(frame)
+            .timeout(StratumClient::SEND_TIMEOUT)
+            .await
+        {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => Err(e)?,
+            Err(_) => Err("Cannot send message due to timeout")?,
+        }
+        self.requests.insert(id, request);
+        Ok(())
+    }
+
+    async fn handle_frame(&mut self, frame: v1::Frame) -> error::Result<()> {
+        let message = build_message_from_frame(frame)?;
+        self.status = None;
+        message.accept(self).await;
+        self.status.take().unwrap_or(Ok(()))
+    }
+
+    /// Send all requests needed for opening the mining session and wait for their responses
+    async fn init_mining_session(&mut self, connection_rx: &mut FramedStream) -> error::Result<()> {
+        let mut configure = Configure::new();
+        configure.add_feature(VersionRolling::new(
+            ii_stratum::BIP320_N_VERSION_MASK,
+            ii_stratum::BIP320_N_VERSION_MAX_BITS,
+        ))?;
+        self.send_request(configure, PendingRequest::Configure)
+            .await
+            .context("Cannot send stratum configure")?;
+
+        let subscribe = Subscribe(
+            Some(crate::version::STRING.to_string()),
+            // Ask the pool to resume the session of the previous connection
+            self.client.lock_extra_nonce1().clone(),
+            Some(self.client.connection_details.get_host_and_port()),
+            None,
+        );
+        self.send_request(subscribe, PendingRequest::Subscribe)
+            .await
+            .context("Cannot send stratum subscribe")?;
+
+        if self.client.connection_details.try_enable_xnsub() {
+            self.send_request(ExtranonceSubscribe(), PendingRequest::ExtranonceSubscribe)
+                .await
+                .context("Cannot send stratum extranonce subscribe")?;
+        }
+
+        if let Some(difficulty) = self.client.connection_details.suggested_difficulty {
+            self.send_request(
+                SuggestDifficulty::new(difficulty as f32),
+                PendingRequest::SuggestDifficulty,
+            )
+            .await
+            .context("Cannot send stratum suggest difficulty")?;
+            self.suggestion_pending = true;
+        }
+
+        let authorize = Authorize(
+            self.client.connection_details.user.clone(),
+            self.client
+                .connection_details
+                .password
+                .clone()
+                .unwrap_or_default(),
+        );
+        self.send_request(authorize, PendingRequest::Authorize)
+            .await
+            .context("Cannot send stratum authorize")?;
+
+        while !self.is_operational() {
+            let frame = connection_rx
+                .next()
+                .await
+                .ok_or("The remote stratum server was disconnected prematurely")??;
+            self.handle_frame(frame).await?;
+        }
+
+        let subscription = self
+            .subscription
+            .as_ref()
+            .expect("BUG: missing subscription");
+        self.client
+            .lock_extra_nonce1()
+            .replace(subscription.extra_nonce1.clone());
+        self.client
+            .establish_session(
+                session::Token::new(0, subscription.extra_nonce1.0.as_ref().as_slice()),
+                self.security.take(),
+            )
+            .await;
+        // Submissions are abandoned when the session has not been resumed
+        self.retransmit_submissions().await?;
+        // Start mining the job received during initialization
+        self.update_job().await;
+        Ok(())
+    }
+
+    /// Build a new job from the last notification with the next extranonce 2 and send it down
+    /// the line for solving
+    async fn update_job(&mut self) {
+        let notify = match self.last_notify.clone() {
+            Some(notify) => notify,
+            None => return,
+        };
+        let target = match self.current_target {
+            Some(target) => target,
+            None => {
+                warn!(
+                    "Stratum: job '{}' received before difficulty",
+                    notify.job_id()
+                );
+                self.set_target(Default::default());
+                Default::default()
+            }
+        };
+        if let Some(subscription) = self.next_subscription.take() {
+            self.subscription.replace(subscription);
+        }
+        let subscription = self
+            .subscription
+            .as_ref()
+            .expect("BUG: missing subscription");
+
+        let extra_nonce2 = extra_nonce2_bytes(
+            self.client.extra_nonce2.fetch_add(1, Ordering::Relaxed),
+            subscription.extra_nonce2_size,
+        );
+        let coinbase = build_coinbase(&notify, &subscription.extra_nonce1, &extra_nonce2);
+        if let Some(coinbase_verifier) = self.client.coinbase_verifier.as_ref() {
+            if let Err(rejection) = coinbase_verifier.verify(&coinbase) {
+                warn!(
+                    "Stratum: job '{}' is not mined: {}",
+                    notify.job_id(),
+                    rejection
+                );
+                // The job cannot be rolled anymore
+                self.last_notify = None;
+                return;
+            }
+        }
+
+        let job = Arc::new(StratumJob {
+            client: Arc::downgrade(&self.client),
+            id: self.client.next_job_id.fetch_add(1, Ordering::Relaxed),
+            job_id: JobId::from_str(notify.job_id()),
+            extra_nonce2,
+            epoch: self.client.epoch.load(Ordering::Relaxed),
+            version: notify.version(),
+            version_mask: self.version_mask,
+            ntime_roll_limit: self.ntime_roll_limit,
+            prev_hash: ii_bitcoin::DHash::from_slice(notify.prev_hash())
+                .expect("BUG: Stratum: incorrect size of prev hash"),
+            merkle_root: merkle_root(&coinbase, notify.merkle_branch()),
+            coinbase,
+            merkle_branch: notify.merkle_branch().clone(),
+            time: notify.time(),
+            bits: notify.bits(),
+            target,
+        });
+        self.client.update_last_job(job.clone()).await;
+        self.client.job_sender.lock().await.send(job);
+        self.last_job_time = clock::now();
+    }
+
+    /// Roll the current job with the next extranonce 2 when the pool has not sent a new one for
+    /// a long time
+    async fn roll_job(&mut self) {
+        if clock::now().duration_since(self.last_job_time) >= StratumClient::JOB_ROLL_INTERVAL {
+            self.update_job().await;
+        }
+    }
+
+    /// Set `target` assigned by the pool. Jobs keep the pool target because every share meeting
+    /// it is accepted by the pool even when it is below the suggested difficulty.
+    fn set_target(&mut self, target: ii_bitcoin::Target) {
+        let trigger = match self.current_target.replace(target) {
+            None => stats::DifficultyTrigger::Initial,
+            Some(_) if self.suggestion_pending => stats::DifficultyTrigger::SuggestResponse,
+            Some(_) => stats::DifficultyTrigger::Vardiff,
+        };
+        self.suggestion_pending = false;
+        self.client
+            .client_stats
+            .difficulty_history
+            .record(&target, trigger, clock::now());
+    }
+
+    async fn process_solution(&mut self, solution: work::Solution) -> error::Result<()> {
+        self.client
+            .client_stats
+            .job_history
+            .account(solution.dyn_job(), stats::JobActivity::Submitted);
+        let key = self.client.lock_submissions().submit(
+            solution.clone(),
+            clock::now(),
+            &self.client.client_stats.submissions,
+        );
+        self.send_submit(key, &solution).await
+    }
+
+    /// Send one transmission of the submission identified by `key`
+    async fn send_submit(
+        &mut self,
+        key: submission::Key,
+        solution: &work::Solution,
+    ) -> error::Result<()> {
+        let job: &StratumJob = solution.job();
+        let submit = Submit::new(
+            self.client.connection_details.user.clone(),
+            job.job_id.clone(),
+            &job.extra_nonce2,
+            solution.time(),
+            solution.nonce(),
+            // only the rolled bits are submitted
+            solution.version() & ii_stratum::BIP320_N_VERSION_MASK,
+        );
+        // the response is handled together with other messages from the server
+        self.send_request(submit, PendingRequest::Submit(key))
+            .await
+            .context("Cannot send submit to stratum server")?;
+        Ok(())
+    }
+
+    /// Send again all submissions whose responses have been lost together with the previous
+    /// connection. The pool has resumed the session so they are still valid.
+    async fn retransmit_submissions(&mut self) -> error::Result<()> {
+        let submissions = self
+            .client
+            .lock_submissions()
+            .retransmit(&self.client.client_stats.submissions);
+        for (key, solution) in submissions {
+            debug!(
+                "{} Stratum: retransmitting submission {}",
+                self.client.log_prefix(&solution),
+                key
+            );
+            self.send_submit(key, &solution).await?;
+        }
+        Ok(())
+    }
+
+    /// Only the first response to any transmission of the submission is accounted
+    fn resolve_submission(&self, key: submission::Key) -> Option<submission::Resolved> {
+        let submission = self.client.lock_submissions().resolve(key);
+        match submission.as_ref() {
+            Some(submission) => {
+                if let Some(latency) = submission.latency(clock::now()) {
+                    self.client
+                        .client_stats
+                        .submissions
+                        .latency
+                        .account(latency);
+                }
+            }
+            None => debug!("Stratum: ignoring response to resolved submission {}", key),
+        }
+        submission
+    }
+
+    async fn account_accepted(&self, solution: work::Solution) {
+        info!(
+            "{} Stratum: accepted solution with nonce={:08x}",
+            self.client.log_prefix(&solution),
+            solution.nonce()
+        );
+        self.client
+            .client_stats
+            .accepted
+            .account_solution(&solution.job_target(), clock::now())
+            .await;
+        self.client
+            .client_stats
+            .job_history
+            .account(solution.dyn_job(), stats::JobActivity::Accepted);
+        self.client.bus.publish(bus::Event::ShareAccepted {
+            client: self.client.client_name,
+            difficulty: solution.job_target().get_difficulty(),
+        });
+        self.client
+            .block_archive
+            .record_response(&solution, job::archive::Response::Accepted);
+    }
+
+    async fn account_rejected(&self, solution: work::Solution, stale: bool) {
+        info!(
+            "{} Stratum: rejected {}solution with nonce={:08x}!",
+            self.client.log_prefix(&solution),
+            if stale { "stale " } else { "" },
+            solution.nonce()
+        );
+        self.client.block_archive.record_response(
+            &solution,
+            if stale {
+                job::archive::Response::Stale
+            } else {
+                job::archive::Response::Rejected
+            },
+        );
+        if solution.is_rerouted() {
+            // The pool cannot be blamed for rejecting solution of a job from another pool
+            return;
+        }
+        let now = clock::now();
+        if stale {
+            self.client
+                .client_stats
+                .stale
+                .account_solution(&solution.job_target(), now)
+                .await;
+        } else {
+            self.client
+                .client_stats
+                .rejected
+                .account_solution(&solution.job_target(), now)
+                .await;
+            stats::account_rejected_job_diff(&solution.path(), &solution.job_target(), now).await;
+        }
+        self.client
+            .client_stats
+            .job_history
+            .account(solution.dyn_job(), stats::JobActivity::Rejected);
+        self.client.bus.publish(bus::Event::ShareRejected {
+            client: self.client.client_name,
+            difficulty: solution.job_target().get_difficulty(),
+            stale,
+        });
+    }
+
+    async fn handle_result(
+        &mut self,
+        request: PendingRequest,
+        result: &StratumResult,
+    ) -> error::Result<()> {
+        match request {
+            PendingRequest::Configure => {
+                self.version_mask = parse_version_rolling(result)?;
+                if self.version_mask == 0 {
+                    self.disable_version_rolling("no version bits allowed");
+                }
+                self.configured = true;
+                self.ntime_roll_limit = parse_ntime_roll_limit(result);
+                if self.ntime_roll_limit != 0 {
+                    info!(
+                        "Stratum: pool allows rolling ntime by at most {} s",
+                        self.ntime_roll_limit
+                    );
+                }
+            }
+            PendingRequest::Subscribe => {
+                let subscribe_result = SubscribeResult::try_from(result)?;
+                self.subscription.replace(Subscription {
+                    extra_nonce1: subscribe_result.extra_nonce_1().clone(),
+                    extra_nonce2_size: subscribe_result.extra_nonce_2_size(),
+                });
+            }
+            PendingRequest::ExtranonceSubscribe => {
+                if BooleanResult::try_from(result)?.0 {
+                    info!("Stratum: support for #xnsub enabled");
+                } else {
+                    warn!("Stratum: pool refused to enable #xnsub");
+                }
+            }
+            PendingRequest::SuggestDifficulty => {
+                // The pool answers the suggestion with `mining.set_difficulty` (if ever)
+            }
+            PendingRequest::Authorize => {
+                if !BooleanResult::try_from(result)?.0 {
+                    self.client
+                        .set_authorization(health::Authorization::Rejected);
+                    Err("Stratum authorization failed")?;
+                }
+                self.client
+                    .set_authorization(health::Authorization::Authorized);
+                self.authorized = true;
+            }
+            PendingRequest::Submit(key) => {
+                if let Some(submission) = self.resolve_submission(key) {
+                    match BooleanResult::try_from(result) {
+                        Ok(BooleanResult(true)) => self.account_accepted(submission.solution).await,
+                        _ => self.account_rejected(submission.solution, false).await,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_error(
+        &mut self,
+        request: PendingRequest,
+        stratum_error: &StratumError,
+    ) -> error::Result<()> {
+        match request {
+            PendingRequest::Configure => {
+                self.disable_version_rolling(&stratum_error.1);
+                self.configured = true;
+            }
+            PendingRequest::Subscribe => {
+                Err(format!("Stratum subscribe error: {}", stratum_error.1))?
+            }
+            PendingRequest::ExtranonceSubscribe => {
+                warn!("Stratum: cannot enable #xnsub: {}", stratum_error.1);
+            }
+            PendingRequest::SuggestDifficulty => {
+                warn!(
+                    "Stratum: pool refused suggested difficulty: {}",
+                    stratum_error.1
+                );
+                self.suggestion_pending = false;
+            }
+            PendingRequest::Authorize => {
+                self.client
+                    .set_authorization(health::Authorization::Rejected);
+                Err(format!("Stratum authorize error: {}", stratum_error.1))?
+            }
+            PendingRequest::Submit(key) => {
+                if let Some(submission) = self.resolve_submission(key) {
+                    if submission.retransmitted && stratum_error.0 == DUPLICATE_SHARE_ERROR_CODE {
+                        // The lost response to the original transmission has been positive
+                        self.account_accepted(submission.solution).await
+                    } else {
+                        self.account_rejected(
+                            submission.solution,
+                            stratum_error.0 == STALE_JOB_ERROR_CODE,
+                        )
+                        .await
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn take_request(&mut self, id: &v1::MessageId) -> Option<PendingRequest> {
+        let request = id.and_then(|id| self.requests.remove(&id));
+        if request.is_none() {
+            warn!("Stratum: response to unknown request {:?}", id);
+        }
+        request
+    }
+}
+
+#[async_trait]
+impl Handler for StratumEventHandler {
+    async fn visit_stratum_result(&mut self, id: &v1::MessageId, payload: &StratumResult) {
+        if let Some(request) = self.take_request(id) {
+            let status = self.handle_result(request, payload).await;
+            self.set_status(status);
+        }
+    }
+
+    async fn visit_stratum_error(&mut self, id: &v1::MessageId, payload: &StratumError) {
+        if let Some(request) = self.take_request(id) {
+            let status = self.handle_error(request, payload).await;
+            self.set_status(status);
+        }
+    }
+
+    async fn visit_set_extranonce(&mut self, _id: &v1::MessageId, payload: &SetExtranonce) {
+        // The new extranonce is used since the next job
+        self.next_subscription.replace(Subscription {
+            extra_nonce1: payload.extra_nonce_1().clone(),
+            extra_nonce2_size: payload.extra_nonce_2_size(),
+        });
+    }
+
+    async fn visit_set_difficulty(&mut self, _id: &v1::MessageId, payload: &SetDifficulty) {
+        let new_target = target_from_difficulty(payload.value());
+        info!(
+            "Stratum: changing target to {} diff={}",
+            new_target,
+            payload.value()
+        );
+        // The target is used since the next job
+        self.set_target(new_target);
+    }
+
+    async fn visit_notify(&mut self, _id: &v1::MessageId, payload: &Notify) {
+        if payload.clean_jobs() {
+            // All previous jobs are invalid and their solutions are not submitted anymore
+            self.client.epoch.fetch_add(1, Ordering::Relaxed);
+        }
+        self.last_notify.replace(payload.clone());
+        // Jobs received during initialization are mined once the session is established
+        if self.is_operational() {
+            self.update_job().await;
+        }
+    }
+
+    async fn visit_set_version_mask(&mut self, _id: &v1::MessageId, payload: &SetVersionMask) {
+        let mask = payload.value() & ii_stratum::BIP320_N_VERSION_MASK;
+        if mask == 0 {
+            self.disable_version_rolling(&format!("mask set to {:#010x}", payload.value()));
+        }
+        let previous_mask = std::mem::replace(&mut self.version_mask, mask);
+        if previous_mask & !mask != 0 {
+            info!(
+                "Stratum: version mask narrowed from {:#010x} to {:#010x}",
+                previous_mask, mask
+            );
+            // The current job must not be rolled with bits which are not allowed anymore so it is
+            // sent again with the new mask
+            if self.is_operational() {
+                self.update_job().await;
+            }
+        }
+    }
+}
+
+#[derive(Debug, ClientNode)]
+pub struct StratumClient {
+    connection_details: ConnectionDetails,
+    #[member_status]
+    status: sync::StatusMonitor,
+    #[member_client_stats]
+    client_stats: stats::BasicClient,
+    stop_sender: mpsc::Sender<()>,
+    stop_receiver: Mutex<mpsc::Receiver<()>>,
+    // Last job has to be week reference to prevent circular reference (the `StratumJob` keeps
+    // reference to `StratumClient`)
+    last_job: Mutex<Option<Weak<StratumJob>>>,
+    job_sender: Mutex<job::Sender>,
+    solution_receiver: Mutex<job::SolutionReceiver>,
+    client_name: log_id::ClientName,
+    /// Incremented whenever the pool cleans all jobs
+    epoch: AtomicU32,
+    next_job_id: AtomicU32,
+    /// Extranonce 2 of the next job, it is not reset on reconnect so that resumed session does
+    /// not mine the same work again
+    extra_nonce2: AtomicU32,
+    /// Session which may be resumed after reconnect
+    session: StdMutex<session::Tracker>,
+    /// Extranonce 1 of the last session which is requested on reconnect
+    extra_nonce1: StdMutex<Option<v1::ExtraNonce1>>,
+    /// Submissions waiting for a response which are kept across reconnects
+    submissions: StdMutex<submission::Tracker>,
+    /// Verifier of coinbase transactions shared by all connections
+    coinbase_verifier: Option<CoinbaseVerifier>,
+    bus: Arc<bus::Bus>,
+    /// Archive of block candidates where responses of the pool are recorded
+    block_archive: Arc<job::archive::Archive>,
+    /// Error which prevents the client from reconnecting (e.g. rejected server certificate)
+    fatal_error: StdMutex<Option<error::ErrorKind>>,
+    /// Response of the pool to the last authorization of the user
+    authorization: StdMutex<health::Authorization>,
+    /// Addresses of the pool are resolved again on every reconnect
+    resolver: resolver::Resolver,
+}
+
+impl StratumClient {
+    const CONNECTION_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+    const EVENT_TIMEOUT: time::Duration = time::Duration::from_secs(60);
+    const SEND_TIMEOUT: time::Duration = time::Duration::from_secs(2);
+    /// The current job is rolled with the next extranonce 2 when the pool does not send a new
+    /// one in this interval so that the rolled version and ntime do not run out
+    const JOB_ROLL_INTERVAL: time::Duration = time::Duration::from_secs(30);
+    const JOB_ROLL_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
+    pub fn new(connection_details: ConnectionDetails, solver: job::Solver) -> Self {
+        let (stop_sender, stop_receiver) = mpsc::channel(1);
+        let coinbase_verifier = connection_details
+            .coinbase_check
+            .as_ref()
+            .map(|coinbase_check| {
+                CoinbaseVerifier::new(CoinbaseCheck {
+                    marker: coinbase_check.marker(),
+                    payout_script: coinbase_check
+                        .payout_script()
+                        .expect("BUG: invalid coinbase payout script"),
+                    alert_threshold: coinbase_check.alert_threshold(),
+                })
+            });
+        let bus = solver.bus();
+        let block_archive = solver.block_archive();
+        Self {
+            connection_details,
+            status: sync::StatusMonitor::for_client(solver.client_name(), bus.clone()),
+            client_stats: Default::default(),
+            stop_sender,
+            stop_receiver: Mutex::new(stop_receiver),
+            last_job: Mutex::new(None),
+            client_name: solver.client_name(),
+            job_sender: Mutex::new(solver.job_sender),
+            solution_receiver: Mutex::new(solver.solution_receiver),
+            epoch: AtomicU32::new(0),
+            next_job_id: AtomicU32::new(0),
+            extra_nonce2: AtomicU32::new(0),
+            session: StdMutex::new(session::Tracker::new()),
+            extra_nonce1: StdMutex::new(None),
+            submissions: Default::default(),
+            coinbase_verifier,
+            bus,
+            block_archive,
+            fatal_error: StdMutex::new(None),
+            authorization: Default::default(),
+            resolver: resolver::Resolver::new(),
+        }
+    }
+
+    /// Prefix of log records related to the `solution` submitted by this client
+    fn log_prefix(&self, solution: &work::Solution) -> log_id::Prefix {
+        log_id::Prefix::solution(solution).with_client(self.client_name)
+    }
+
+    async fn update_last_job(&self, job: Arc<StratumJob>) {
+        self.last_job.lock().await.replace(Arc::downgrade(&job));
+    }
+
+    fn lock_session(&self) -> StdMutexGuard<session::Tracker> {
+        self.session
+            .lock()
+            .expect("BUG: cannot lock session tracker")
+    }
+
+    fn lock_extra_nonce1(&self) -> StdMutexGuard<Option<v1::ExtraNonce1>> {
+        self.extra_nonce1
+            .lock()
+            .expect("BUG: cannot lock extranonce 1")
+    }
+
+    fn lock_submissions(&self) -> StdMutexGuard<submission::Tracker> {
+        self.submissions
+            .lock()
+            .expect("BUG: cannot lock submission tracker")
+    }
+
+    fn lock_fatal_error(&self) -> StdMutexGuard<Option<error::ErrorKind>> {
+        self.fatal_error
+            .lock()
+            .expect("BUG: cannot lock fatal error")
+    }
+
+    fn set_authorization(&self, authorization: health::Authorization) {
+        *self
+            .authorization
+            .lock()
+            .expect("BUG: cannot lock authorization") = authorization;
+    }
+
+    /// Prevent restarting of the client when the `error` cannot be resolved by reconnecting
+    fn check_fatal_error(&self, error: &error::Error) {
+        if !error.is_transient() {
+            error!(
+                "Client {} will not reconnect until its connection details are changed: {}",
+                self.connection_details.get_host_and_port(),
+                error.chain()
+            );
+            self.lock_fatal_error().replace(error.kind());
+        }
+    }
+
+    /// Give up waiting for responses which have not been received in time
+    fn expire_submissions(&self) {
+        let expired = self
+            .lock_submissions()
+            .expire(clock::now(), &self.client_stats.submissions);
+        if expired > 0 {
+            warn!(
+                "Stratum: {} submission(s) to {} without response",
+                expired, self
+            );
+        }
+    }
+
+    async fn invalidate_work(&self) {
+        // Invalidate current job to stop working on it
+        self.job_sender.lock().await.invalidate();
+        // Solutions of the previous jobs cannot be submitted anymore
+        self.epoch.fetch_add(1, Ordering::Relaxed);
+        // Flush all unprocessed solutions to empty buffer
+        // TODO: Count as a discarded solution?
+        self.solution_receiver.lock().await.flush();
+        // Submissions without response cannot be transmitted again
+        let abandoned = self
+            .lock_submissions()
+            .abandon(&self.client_stats.submissions);
+        if abandoned > 0 {
+            warn!(
+                "Stratum: {} submission(s) to {} abandoned without response",
+                abandoned, self
+            );
+        }
+    }
+
+    /// Work from the previous connection is invalidated unless the remote server has resumed
+    /// its session
+    async fn establish_session(&self, token: session::Token, security: Option<session::Security>) {
+        let outcome = self.lock_session().establish_secure(token, security);
+        info!("Stratum: {} session with {}", outcome, self);
+        if outcome.invalidates_work() {
+            self.invalidate_work().await;
+        }
+    }
+
+    /// Every resolved address is given the whole connection timeout and the TLS handshake is
+    /// limited by it separately
+    async fn connect(&self) -> error::Result<Framed> {
+        let (stream, _) = self
+            .resolver
+            .connect(
+                &self.connection_details.host,
+                self.connection_details.port,
+                self.connection_details.dns_ttl,
+                Self::CONNECTION_TIMEOUT,
+            )
+            .await
+            .context("Cannot connect to stratum server")?;
+        open_connection(&self.connection_details, stream)
+            .timeout(Self::CONNECTION_TIMEOUT)
+            .await
+            .map_err(|_| error::ErrorKind::Connection("TLS handshake timeout".to_string()))?
+    }
+
+    async fn main_loop(
+        &self,
+        mut connection_rx: FramedStream,
+        event_handler: &mut StratumEventHandler,
+    ) -> error::Result<()> {
+        let mut solution_receiver = self.solution_receiver.lock().await;
+        let mut job_roll_timer = tokio::time::interval(Self::JOB_ROLL_CHECK_INTERVAL);
+        // The deadline is moved only by a received frame. The other branches win the race
+        // periodically (e.g. the job roll timer) so the timeout must not be restarted with them.
+        let mut event_deadline = clock::now() + Self::EVENT_TIMEOUT;
+
+        while !self.status.is_shutting_down() {
+            // NOTE: futures of the branches which lose the race are dropped so none of them may
+            // hold a received item across an await point (`SolutionReceiver::receive` keeps its
+            // solution in flight internally)
+            select! {
+                frame = connection_rx.next().fuse() => {
+                    match frame {
+                        Some(frame) => {
+                            event_deadline = clock::now() + Self::EVENT_TIMEOUT;
+                            event_handler.handle_frame(frame?).await?
+                        }
+                        None => {
+                            Err("The remote stratum server was disconnected prematurely")?;
+                        }
+                    }
+                },
+                _ = tokio::time::delay_until(event_deadline.into()).fuse() => {
+                    Err("The remote stratum server has not sent any message in time")?;
+                },
+                solution = solution_receiver.receive().fuse() => {
+                    match solution {
+                        Some(solution) => event_handler.process_solution(solution).await?,
+                        None => {
+                            // TODO: initiate Destroying and remove error
+                            Err("Standard application shutdown")?;
+                        }
+                    }
+                },
+                _ = job_roll_timer.tick().fuse() => {
+                    event_handler.roll_job().await;
+                    self.expire_submissions();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(self: Arc<Self>) {
+        match self.connect().await {
+            Ok(framed_connection) => {
+                let security = framed_connection.get_ref().security();
+                if let Some(security) = security.as_ref() {
+                    session::check_expiry(
+                        security,
+                        self.connection_details.cert_expiry_warning,
+                        &self,
+                    );
+                }
+                let (connection_tx, mut connection_rx) = framed_connection.split();
+                let mut event_handler =
+                    StratumEventHandler::new(self.clone(), connection_tx, security);
+                match event_handler
+                    .init_mining_session(&mut connection_rx)
+                    .timeout(Self::CONNECTION_TIMEOUT)
+                    .await
+                {
+                    Ok(Ok(())) => {
+                        if self.status.initiate_running() {
+                            if let Err(e) = self.main_loop(connection_rx, &mut event_handler).await
+                            {
+                                info!("Stratum: connection to {} failed: {}", self, e);
+                                self.status.initiate_failing();
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        info!("Stratum: cannot open session with {}: {}", self, e);
+                        self.status.initiate_failing();
+                    }
+                    Err(_) => self.status.initiate_failing(),
+                }
+            }
+            Err(e) => {
+                info!("Stratum: cannot connect to {}: {}", self, e.chain());
+                self.check_fatal_error(&e);
+                self.status.initiate_failing();
+            }
+        }
+    }
+
+    /// Keep mining the current job when the session may be resumed after reconnect, the
+    /// decision is made when the next session is established. The session of `stopped` client
+    /// is never resumed.
+    async fn disconnect(&self, stopped: bool) {
+        let keep_work = {
+            let mut session = self.lock_session();
+            if stopped {
+                session.close();
+                self.lock_extra_nonce1().take();
+            }
+            session.disconnect()
+        };
+        if !keep_work {
+            self.invalidate_work().await;
+        }
+    }
+
+    async fn main_task(self: Arc<Self>) {
+        // TODO: Count as a discarded solution?
+        // Flush all obsolete solutions from previous run unless they can be submitted to
+        // resumed session
+        let pending = self.lock_session().is_pending();
+        if !pending {
+            self.solution_receiver.lock().await.flush();
+        }
+
+        loop {
+            let mut stop_receiver = self.stop_receiver.lock().await;
+            let stopped = select! {
+                _ = self.clone().run().fuse() => false,
+                _ = stop_receiver.next() => true,
+            };
+
+            self.disconnect(stopped).await;
+
+            if self.status.can_stop() {
+                // NOTE: it is not safe to add here any code!
+                // The reason is that at this point the main task can be executed in parallel again
+                break;
+            }
+            // Restarting
+        }
+    }
+}
+
+#[async_trait]
+impl node::Client for StratumClient {
+    fn start(self: Arc<Self>) {
+        tokio::spawn(self.clone().main_task());
+    }
+
+    fn stop(&self) {
+        if let Err(e) = self.stop_sender.clone().try_send(()) {
+            assert!(
+                e.is_full(),
+                "BUG: Unexpected error in stop sender: {}",
+                e.to_string()
+            );
+        }
+    }
+
+    async fn get_last_job(&self) -> Option<Arc<dyn job::Bitcoin>> {
+        self.last_job
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|job| job.upgrade().map(|job| job as Arc<dyn job::Bitcoin>))
+    }
+
+    fn session_history(&self) -> Vec<session::Event> {
+        self.lock_session().history()
+    }
+
+    fn fatal_error(&self) -> Option<error::ErrorKind> {
+        self.lock_fatal_error().clone()
+    }
+
+    fn authorization(&self) -> health::Authorization {
+        *self
+            .authorization
+            .lock()
+            .expect("BUG: cannot lock authorization")
+    }
+
+    fn address_stats(&self) -> Vec<resolver::AddressStats> {
+        self.resolver.stats()
+    }
+}
+
+/// Client is shown under its display name (full URL is available in its descriptor)
+impl fmt::Display for StratumClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.client_name)
+    }
+}
+
+/// Return time elapsed since `stage_start` and start the next stage
+fn finish_stage(stage_start: &mut time::Instant) -> Option<time::Duration> {
+    let now = clock::now();
+    let elapsed = now.duration_since(*stage_start);
+    *stage_start = now;
+    Some(elapsed)
+}
+
+/// Helper for `probe` which collects information reported by the remote server. Unlike
+/// `StratumEventHandler` it is not connected to any client so the received jobs are never mined.
+struct ProbeHandler {
+    next_request_id: u32,
+    requests: HashMap<u32, PendingRequest>,
+    version_mask: Option<u32>,
+    subscription: Option<Subscription>,
+    authorized: Option<bool>,
+    difficulty: Option<f32>,
+    /// The first job notification with the time of its reception
+    first_notify: Option<(Notify, time::Instant)>,
+    /// Result of the last visited message
+    status: Option<error::Result<()>>,
+}
+
+impl ProbeHandler {
+    fn new() -> Self {
+        Self {
+            next_request_id: 0,
+            requests: HashMap::new(),
+            version_mask: None,
+            subscription: None,
+            authorized: None,
+            difficulty: None,
+            first_notify: None,
+            status: None,
+        }
+    }
+
+    async fn send_request<M>(
+        &mut self,
+        connection: &mut Framed,
+        message: M,
+        request: PendingRequest,
+    ) -> error::Result<()>
+    where
+        M: TryInto<RequestPayload, Error = ii_stratum::error::Error>,
+    {
+        let id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+
+        connection.send(build_request_frame(id, message)?).await?;
+        self.requests.insert(id, request);
+        Ok(())
+    }
+
+    /// Process messages from the remote server until all requests are answered
+    async fn wait_for_responses(&mut self, connection: &mut Framed) -> error::Result<()> {
+        while !self.requests.is_empty() {
+            self.receive(connection).await?;
+        }
+        Ok(())
+    }
+
+    async fn receive(&mut self, connection: &mut Framed) -> error::Result<()> {
+        let frame = connection
+            .next()
+            .await
+            .ok_or("The remote stratum server was disconnected prematurely")??;
+        let message = build_message_from_frame(frame)?;
+        self.status = None;
+        message.accept(self).await;
+        self.status.take().unwrap_or(Ok(()))
+    }
+
+    fn handle_result(
+        &mut self,
+        request: PendingRequest,
+        result: &StratumResult,
+    ) -> error::Result<()> {
+        match request {
+            PendingRequest::Configure => {
+                self.version_mask.replace(parse_version_rolling(result)?);
+            }
+            PendingRequest::Subscribe => {
+                let subscribe_result = SubscribeResult::try_from(result)?;
+                self.subscription.replace(Subscription {
+                    extra_nonce1: subscribe_result.extra_nonce_1().clone(),
+                    extra_nonce2_size: subscribe_result.extra_nonce_2_size(),
+                });
+            }
+            PendingRequest::Authorize => {
+                self.authorized.replace(BooleanResult::try_from(result)?.0);
+            }
+            // These requests are never sent during the test
+            PendingRequest::ExtranonceSubscribe
+            | PendingRequest::SuggestDifficulty
+            | PendingRequest::Submit(_) => {}
+        }
+        Ok(())
+    }
+
+    fn handle_error(
+        &mut self,
+        request: PendingRequest,
+        stratum_error: &StratumError,
+    ) -> error::Result<()> {
+        match request {
+            // The pool is still usable without version rolling
+            PendingRequest::Configure => {
+                self.version_mask.replace(0);
+            }
+            PendingRequest::Subscribe => {
+                Err(format!("Stratum subscribe error: {}", stratum_error.1))?
+            }
+            PendingRequest::Authorize => {
+                Err(format!("Stratum authorize error: {}", stratum_error.1))?
+            }
+            PendingRequest::ExtranonceSubscribe
+            | PendingRequest::SuggestDifficulty
+            | PendingRequest::Submit(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Fill the `report` with the information received since the `start` of the test
+    fn update_report(&self, report: &mut probe::Report, start: time::Instant) {
+        if let Some(subscription) = self.subscription.as_ref() {
+            report.server.extra_nonce1 = Some(subscription.extra_nonce1.0.clone().into());
+            report.server.extra_nonce2_size = Some(subscription.extra_nonce2_size);
+        }
+        report.server.version_mask = self.version_mask;
+        report.server.difficulty = self.difficulty.map(|difficulty| difficulty as f64);
+        report.job = self
+            .first_notify
+            .as_ref()
+            .map(|(notify, time)| probe::JobSummary {
+                job_id: notify.job_id().to_string(),
+                previous_hash: ii_bitcoin::DHash::from_slice(notify.prev_hash())
+                    .map(|hash| hash.to_string())
+                    .unwrap_or_default(),
+                version: notify.version(),
+                bits: notify.bits(),
+                ntime: notify.time(),
+                clean_jobs: notify.clean_jobs(),
+                merkle_branch_len: notify.merkle_branch().len(),
+                received_after: time.duration_since(start),
+            });
+    }
+}
+
+#[async_trait]
+impl Handler for ProbeHandler {
+    async fn visit_stratum_result(&mut self, id: &v1::MessageId, payload: &StratumResult) {
+        if let Some(request) = id.and_then(|id| self.requests.remove(&id)) {
+            self.status = Some(self.handle_result(request, payload));
+        }
+    }
+
+    async fn visit_stratum_error(&mut self, id: &v1::MessageId, payload: &StratumError) {
+        if let Some(request) = id.and_then(|id| self.requests.remove(&id)) {
+            self.status = Some(self.handle_error(request, payload));
+        }
+    }
+
+    async fn visit_set_extranonce(&mut self, _id: &v1::MessageId, payload: &SetExtranonce) {
+        self.subscription.replace(Subscription {
+            extra_nonce1: payload.extra_nonce_1().clone(),
+            extra_nonce2_size: payload.extra_nonce_2_size(),
+        });
+    }
+
+    async fn visit_set_difficulty(&mut self, _id: &v1::MessageId, payload: &SetDifficulty) {
+        self.difficulty.replace(payload.value());
+    }
+
+    async fn visit_notify(&mut self, _id: &v1::MessageId, payload: &Notify) {
+        if self.first_notify.is_none() {
+            self.first_notify.replace((payload.clone(), clock::now()));
+        }
+    }
+
+    async fn visit_set_version_mask(&mut self, _id: &v1::MessageId, payload: &SetVersionMask) {
+        self.version_mask.replace(payload.value());
+    }
+}
+
+async fn run_probe(
+    connection_details: &ConnectionDetails,
+    options: probe::Options,
+    report: &mut probe::Report,
+    handler: &mut ProbeHandler,
+) -> error::Result<()> {
+    let mut stage_start = clock::now();
+    // The test does not share addresses with the mining client
+    let resolver = resolver::Resolver::new();
+    let addresses = resolver
+        .resolve(
+            &connection_details.host,
+            connection_details.port,
+            connection_details.dns_ttl,
+        )
+        .await?;
+    report.timings.resolve = finish_stage(&mut stage_start);
+
+    let (stream, socket_addr) = resolver
+        .connect_to(&addresses, options.timeout)
+        .await
+        .context("Cannot connect to stratum server")?;
+    report.server.address = Some(socket_addr);
+    let mut connection = open_connection(connection_details, stream).await?;
+    report.timings.connect = finish_stage(&mut stage_start);
+
+    let mut configure = Configure::new();
+    configure.add_feature(VersionRolling::new(
+        ii_stratum::BIP320_N_VERSION_MASK,
+        ii_stratum::BIP320_N_VERSION_MAX_BITS,
+    ))?;
+    handler
+        .send_request(&mut connection, configure, PendingRequest::Configure)
+        .await
+        .context("Cannot send stratum configure")?;
+    let subscribe = Subscribe(
+        Some(crate::version::STRING.to_string()),
+        None,
+        Some(connection_details.get_host_and_port()),
+        None,
+    );
+    handler
+        .send_request(&mut connection, subscribe, PendingRequest::Subscribe)
+        .await
+        .context("Cannot send stratum subscribe")?;
+    handler.wait_for_responses(&mut connection).await?;
+    report.timings.handshake = finish_stage(&mut stage_start);
+
+    let authorize = Authorize(
+        connection_details.user.clone(),
+        connection_details.password.clone().unwrap_or_default(),
+    );
+    handler
+        .send_request(&mut connection, authorize, PendingRequest::Authorize)
+        .await
+        .context("Cannot send stratum authorize")?;
+    handler.wait_for_responses(&mut connection).await?;
+    if handler.authorized != Some(true) {
+        Err("Stratum authorization failed")?;
+    }
+    report.timings.authorize = finish_stage(&mut stage_start);
+
+    if options.wait_for_job {
+        while handler.first_notify.is_none() {
+            handler.receive(&mut connection).await?;
+        }
+    }
+    // The connection is closed when it is dropped
+    Ok(())
+}
+
+/// Test connectivity of the pool with a temporary connection which is closed when the test
+/// finishes. It is independent of the client mining with the same pool.
+pub async fn probe(
+    connection_details: &ConnectionDetails,
+    options: probe::Options,
+) -> probe::Report {
+    let start = clock::now();
+    let mut report = probe::Report::new();
+    let mut handler = ProbeHandler::new();
+
+    let result = run_probe(connection_details, options, &mut report, &mut handler)
+        .timeout(options.timeout)
+        .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => report.fail(e.to_string()),
+        Err(_) => report.fail("Test timed out"),
+    }
+    handler.update_report(&mut report, start);
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+    use ii_bitcoin::{FromHex, MeetsTarget as _};
+    use ii_stratum::test_utils::v1 as v1_test;
+    use ii_stratum::v1::rpc::{Method, Response, ResponsePayload, Rpc};
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_merkle_root() {
+        let notify = v1_test::build_mining_notify();
+        let subscribe_result = v1_test::build_subscribe_ok_result();
+
+        let extra_nonce2 = extra_nonce2_bytes(0, subscribe_result.extra_nonce_2_size());
+        let coinbase = build_coinbase(&notify, subscribe_result.extra_nonce_1(), &extra_nonce2);
+        assert_eq!(
+            merkle_root(&coinbase, notify.merkle_branch()),
+            ii_bitcoin::DHash::from_hex(v1_test::MINING_NOTIFY_MERKLE_ROOT)
+                .expect("BUG: invalid merkle root")
+        );
+
+        // rolled extranonce 2 changes the merkle root
+        let extra_nonce2 = extra_nonce2_bytes(1, subscribe_result.extra_nonce_2_size());
+        let coinbase = build_coinbase(&notify, subscribe_result.extra_nonce_1(), &extra_nonce2);
+        assert_ne!(
+            merkle_root(&coinbase, notify.merkle_branch()),
+            ii_bitcoin::DHash::from_hex(v1_test::MINING_NOTIFY_MERKLE_ROOT)
+                .expect("BUG: invalid merkle root")
+        );
+    }
+
+    #[test]
+    fn test_extra_nonce2_bytes() {
+        assert_eq!(extra_nonce2_bytes(0x01020304, 4), vec![4, 3, 2, 1]);
+        assert_eq!(extra_nonce2_bytes(0x01020304, 2), vec![4, 3]);
+        assert_eq!(
+            extra_nonce2_bytes(0x01020304, 8),
+            vec![4, 3, 2, 1, 0, 0, 0, 0]
+        );
+        assert_eq!(extra_nonce2_bytes(1, 0), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_parse_version_rolling() {
+        let parse = |result| {
+            parse_version_rolling(&StratumResult(result)).expect("BUG: cannot parse result")
+        };
+        assert_eq!(
+            parse(serde_json::json!({
+                "version-rolling": true,
+                "version-rolling.mask": "ffffffff"
+            })),
+            ii_stratum::BIP320_N_VERSION_MASK
+        );
+        // pools without version rolling are mined with the version of their jobs
+        assert_eq!(parse(serde_json::json!({ "version-rolling": false })), 0);
+        assert_eq!(parse(serde_json::json!({})), 0);
+        assert_eq!(
+            parse(serde_json::json!({
+                "version-rolling": true,
+                "version-rolling.mask": "00001fff"
+            })),
+            0
+        );
+        assert!(parse_version_rolling(&StratumResult(serde_json::json!({
+            "version-rolling": true,
+            "version-rolling.mask": 1
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_target_from_difficulty() {
+        assert_eq!(target_from_difficulty(0.001), Default::default());
+        assert_eq!(target_from_difficulty(1.0), Default::default());
+        assert_eq!(
+            target_from_difficulty(4.0),
+            ii_bitcoin::Target::from_pool_difficulty(4)
+        );
+        let target = target_from_difficulty(2.5);
+        assert!(target < ii_bitcoin::Target::from_pool_difficulty(2));
+        assert!(target > ii_bitcoin::Target::from_pool_difficulty(3));
+    }
+
+    #[test]
+    fn test_parse_ntime_roll_limit() {
+        let parse = |result| parse_ntime_roll_limit(&StratumResult(result));
+        assert_eq!(
+            parse(serde_json::json!({
+                "version-rolling": true,
+                "version-rolling.mask": "1fffe000"
+            })),
+            0
+        );
+        assert_eq!(
+            parse(serde_json::json!({
+                "version-rolling": true,
+                "version-rolling.mask": "1fffe000",
+                "ntime-rolling.max-offset": 60
+            })),
+            60
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "ntime-rolling.max-offset": 1u64 << 40 })),
+            u32::MAX
+        );
+        // malformed limit is ignored
+        assert_eq!(
+            parse(serde_json::json!({ "ntime-rolling.max-offset": "60" })),
+            0
+        );
+    }
+
+    /// Mock pool serving a single connection. It answers the authorization request with
+    /// `authorize` result or it ignores the request when the result is missing. Methods of all
+    /// received requests are returned when the connection is closed.
+    async fn run_mock_pool(
+        listener: std::net::TcpListener,
+        authorize: Option<bool>,
+    ) -> Vec<Method> {
+        let mut listener =
+            tokio::net::TcpListener::from_std(listener).expect("BUG: cannot create listener");
+        let (stream, _) = listener
+            .accept()
+            .await
+            .expect("BUG: cannot accept connection");
+        let mut connection = ii_wire::Connection::<v1::Framing>::new(stream).into_inner();
+        let mut methods = vec![];
+
+        while let Some(frame) = connection.next().await {
+            let request = match Rpc::try_from(frame.expect("BUG: cannot receive frame")) {
+                Ok(Rpc::Request(request)) => request,
+                _ => panic!("BUG: unexpected message"),
+            };
+            let result = match &request.payload.method {
+                Method::Configure => serde_json::json!({
+                    "version-rolling": true,
+                    "version-rolling.mask": "1fffe000"
+                }),
+                Method::Subscribe => serde_json::to_value(v1_test::build_subscribe_ok_result())
+                    .expect("BUG: cannot serialize subscribe result"),
+                Method::Authorize => match authorize {
+                    Some(authorized) => serde_json::json!(authorized),
+                    None => continue,
+                },
+                Method::SuggestDifficulty | Method::Submit => serde_json::json!(true),
+                method => panic!("BUG: unexpected method {:?}", method),
+            };
+            let mut messages = vec![Rpc::from(Response {
+                id: request.id.expect("BUG: missing request id"),
+                payload: ResponsePayload {
+                    result: Some(StratumResult(result)),
+                    error: None,
+                },
+            })];
+            if request.payload.method == Method::Authorize {
+                messages.push(v1_test::build_set_difficulty_request_message());
+                messages.push(v1_test::build_mining_notify_request_message());
+            }
+            for message in messages {
+                connection
+                    .send(v1::Frame::try_from(message).expect("BUG: cannot build frame"))
+                    .await
+                    .expect("BUG: cannot send frame");
+            }
+            methods.push(request.payload.method);
+        }
+        methods
+    }
+
+    /// Run connectivity test against the mock pool with given `authorize` result
+    async fn probe_mock_pool(authorize: Option<bool>) -> (SocketAddr, probe::Report) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind");
+        let address = listener.local_addr().expect("BUG: missing local address");
+        tokio::spawn(run_mock_pool(listener, authorize));
+
+        let connection_details = ConnectionDetails {
+            user: "braiins.worker0".to_string(),
+            password: None,
+            host: address.ip().to_string(),
+            port: address.port(),
+            fragment: None,
+            coinbase_check: None,
+            suggested_difficulty: None,
+            tls: None,
+            cert_expiry_warning: time::Duration::from_secs(0),
+            dns_ttl: time::Duration::from_secs(60),
+        };
+        let options = probe::Options {
+            wait_for_job: true,
+            timeout: time::Duration::from_secs(1),
+        };
+        (address, probe(&connection_details, options).await)
+    }
+
+    #[tokio::test]
+    async fn test_probe_success() {
+        let (address, report) = probe_mock_pool(Some(true)).await;
+        assert!(report.is_ok(), "unexpected failure: {}", report);
+
+        assert!(report.timings.resolve.is_some());
+        assert!(report.timings.connect.is_some());
+        assert!(report.timings.handshake.is_some());
+        assert!(report.timings.authorize.is_some());
+
+        let subscribe_result = v1_test::build_subscribe_ok_result();
+        assert_eq!(report.server.address, Some(address));
+        assert_eq!(
+            report.server.extra_nonce1,
+            Some(subscribe_result.extra_nonce_1().0.clone().into())
+        );
+        assert_eq!(
+            report.server.extra_nonce2_size,
+            Some(subscribe_result.extra_nonce_2_size())
+        );
+        assert_eq!(
+            report.server.version_mask,
+            Some(ii_stratum::BIP320_N_VERSION_MASK)
+        );
+        assert_eq!(report.server.difficulty, Some(4.0));
+
+        let notify = v1_test::build_mining_notify();
+        let job = report.job.expect("BUG: missing job summary");
+        assert_eq!(job.job_id, v1_test::MINING_NOTIFY_JOB_ID);
+        assert_eq!(job.version, notify.version());
+        assert_eq!(job.bits, notify.bits());
+        assert_eq!(job.ntime, notify.time());
+        assert_eq!(job.clean_jobs, notify.clean_jobs());
+        assert_eq!(job.merkle_branch_len, 0);
+    }
+
+    #[tokio::test]
+    async fn test_probe_authorization_failure() {
+        let (_, report) = probe_mock_pool(Some(false)).await;
+        let failure = report.failure.expect("BUG: missing failure");
+        assert_eq!(failure.stage, probe::Stage::Authorize);
+        assert_eq!(
+            failure.reason,
+            "General error: Stratum authorization failed"
+        );
+
+        // the handshake has been finished before the failure
+        assert!(report.timings.handshake.is_some());
+        assert!(report.timings.authorize.is_none());
+        assert!(report.server.extra_nonce1.is_some());
+        assert!(report.job.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout() {
+        let (_, report) = probe_mock_pool(None).await;
+        let failure = report.failure.expect("BUG: missing failure");
+        assert_eq!(failure.stage, probe::Stage::Authorize);
+        assert_eq!(failure.reason, "Test timed out");
+
+        assert!(report.timings.connect.is_some());
+        assert!(report.timings.handshake.is_some());
+        assert!(report.timings.authorize.is_none());
+        assert_eq!(
+            report.server.version_mask,
+            Some(ii_stratum::BIP320_N_VERSION_MASK)
+        );
+    }
+
+    /// Client of the pool at `address` whose engine sender keeps the current job alive so that
+    /// it can be inspected by the test
+    fn create_client(
+        address: SocketAddr,
+        suggested_difficulty: Option<usize>,
+    ) -> (Arc<StratumClient>, mpsc::UnboundedSender<work::Solution>) {
+        let connection_details = ConnectionDetails {
+            user: "braiins.worker0".to_string(),
+            password: None,
+            host: address.ip().to_string(),
+            port: address.port(),
+            fragment: None,
+            coinbase_check: None,
+            suggested_difficulty,
+            tls: None,
+            cert_expiry_warning: time::Duration::from_secs(0),
+            dns_ttl: time::Duration::from_secs(60),
+        };
+        let (solution_sender, solution_receiver) = mpsc::unbounded();
+        let engine_sender = Arc::new(work::EngineSender::new(None));
+        let _ = engine_sender.replace_engine_generator(crate::client::version_rolling_generator(1));
+        let solver = job::Solver::new(engine_sender, solution_receiver);
+        (
+            Arc::new(StratumClient::new(connection_details, solver)),
+            solution_sender,
+        )
+    }
+
+    /// Solution of the `job` with `nonce` (its hash is not checked against any target)
+    fn build_solution(job: Arc<dyn job::Bitcoin>, nonce: u32) -> work::Solution {
+        let block_chunk1 = ii_bitcoin::BlockHeader {
+            version: job.version(),
+            previous_hash: job.previous_hash().into_inner(),
+            merkle_root: job.merkle_root().into_inner(),
+            ..Default::default()
+        };
+        let midstate = work::Midstate {
+            version: job.version(),
+            state: block_chunk1.midstate(),
+        };
+        let time = job.time();
+        work::Solution::new(
+            work::Assignment::new(job, vec![midstate], time),
+            test_utils::TestSolution::new(&test_utils::TEST_BLOCKS[0]).with_nonce(nonce),
+            None,
+        )
+    }
+
+    /// Suggested difficulty is sent on connect while jobs keep the target assigned by the pool
+    #[tokio::test]
+    async fn test_suggest_difficulty() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind");
+        let address = listener.local_addr().expect("BUG: missing local address");
+        let pool = tokio::spawn(run_mock_pool(listener, Some(true)));
+
+        let (client, _solution_sender) = create_client(address, Some(1024));
+
+        let (connection_tx, mut connection_rx) =
+            client.connect().await.expect("BUG: cannot connect").split();
+        let mut event_handler = StratumEventHandler::new(client.clone(), connection_tx, None);
+        event_handler
+            .init_mining_session(&mut connection_rx)
+            .await
+            .expect("BUG: cannot open session");
+        // difficulty and job are sent by the pool after the authorization
+        for _ in 0..2 {
+            let frame = connection_rx
+                .next()
+                .await
+                .expect("BUG: connection closed")
+                .expect("BUG: cannot receive frame");
+            event_handler
+                .handle_frame(frame)
+                .await
+                .expect("BUG: cannot handle frame");
+        }
+
+        // the pool has assigned difficulty 4 which is lower than the suggested one
+        assert_eq!(
+            event_handler.current_target,
+            Some(ii_bitcoin::Target::from_pool_difficulty(4))
+        );
+        let job = node::Client::get_last_job(&*client)
+            .await
+            .expect("BUG: missing job");
+        assert_eq!(job.target(), ii_bitcoin::Target::from_pool_difficulty(4));
+        let changes = client.client_stats.difficulty_history.take_snapshot();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new_difficulty, 4);
+
+        drop(event_handler);
+        drop(connection_rx);
+        let methods = pool.await.expect("BUG: mock pool failed");
+        assert_eq!(
+            methods,
+            vec![
+                Method::Configure,
+                Method::Subscribe,
+                Method::SuggestDifficulty,
+                Method::Authorize
+            ]
+        );
+    }
+
+    /// Share which meets the target of the pool is submitted even when it is below the suggested
+    /// difficulty
+    #[tokio::test]
+    async fn test_submit_below_suggested_difficulty() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind");
+        let address = listener.local_addr().expect("BUG: missing local address");
+        let pool = tokio::spawn(run_mock_pool(listener, Some(true)));
+
+        let (client, solution_sender) = create_client(address, Some(1024));
+
+        let (connection_tx, mut connection_rx) =
+            client.connect().await.expect("BUG: cannot connect").split();
+        let mut event_handler = StratumEventHandler::new(client.clone(), connection_tx, None);
+        event_handler
+            .init_mining_session(&mut connection_rx)
+            .await
+            .expect("BUG: cannot open session");
+        for _ in 0..2 {
+            let frame = connection_rx
+                .next()
+                .await
+                .expect("BUG: connection closed")
+                .expect("BUG: cannot receive frame");
+            event_handler
+                .handle_frame(frame)
+                .await
+                .expect("BUG: cannot handle frame");
+        }
+
+        // the easiest possible pool target is met by any share so it does not have to be mined
+        event_handler.set_target(ii_bitcoin::Target::from([0xff; 32]));
+        event_handler.update_job().await;
+        let job = node::Client::get_last_job(&*client)
+            .await
+            .expect("BUG: missing job");
+        let solution = build_solution(job, 0);
+        assert!(solution.hash().meets(solution.job_target()));
+        assert!(!solution
+            .hash()
+            .meets(&ii_bitcoin::Target::from_pool_difficulty(1024)));
+
+        solution_sender
+            .unbounded_send(solution)
+            .expect("BUG: cannot send solution");
+        drop(solution_sender);
+        let solution = client
+            .solution_receiver
+            .lock()
+            .await
+            .receive()
+            .await
+            .expect("BUG: share below suggested difficulty has not been submitted");
+        event_handler
+            .process_solution(solution)
+            .await
+            .expect("BUG: cannot submit solution");
+        let frame = connection_rx
+            .next()
+            .await
+            .expect("BUG: connection closed")
+            .expect("BUG: cannot receive frame");
+        event_handler
+            .handle_frame(frame)
+            .await
+            .expect("BUG: cannot handle frame");
+        assert_eq!(
+            client.client_stats.accepted.take_snapshot().await.solutions,
+            1
+        );
+
+        drop(event_handler);
+        drop(connection_rx);
+        let methods = pool.await.expect("BUG: mock pool failed");
+        assert_eq!(methods.last(), Some(&Method::Submit));
+    }
+
+    /// Mock pool whose side of the connection is driven by the test step by step
+    struct MockPool {
+        listener: tokio::net::TcpListener,
+        connection: Option<ii_wire::Connection<v1::Framing>>,
+    }
+
+    impl MockPool {
+        fn bind() -> (Self, SocketAddr) {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("BUG: cannot bind");
+            let address = listener.local_addr().expect("BUG: missing local address");
+            let listener =
+                tokio::net::TcpListener::from_std(listener).expect("BUG: cannot create listener");
+            let pool = Self {
+                listener,
+                connection: None,
+            };
+            (pool, address)
+        }
+
+        /// Accept the next connection of the client (the previous one is closed)
+        async fn accept(&mut self) {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .await
+                .expect("BUG: cannot accept connection");
+            self.connection.replace(ii_wire::Connection::new(stream));
+        }
+
+        fn close(&mut self) {
+            self.connection.take();
+        }
+
+        fn connection(&mut self) -> &mut ii_wire::Connection<v1::Framing> {
+            self.connection
+                .as_mut()
+                .expect("BUG: client is not connected")
+        }
+
+        async fn receive(&mut self) -> Request {
+            let frame = self
+                .connection()
+                .next()
+                .await
+                .expect("BUG: connection closed")
+                .expect("BUG: cannot receive frame");
+            match Rpc::try_from(frame) {
+                Ok(Rpc::Request(request)) => request,
+                _ => panic!("BUG: unexpected message"),
+            }
+        }
+
+        async fn send(&mut self, message: Rpc) {
+            self.connection()
+                .send(v1::Frame::try_from(message).expect("BUG: cannot build frame"))
+                .await
+                .expect("BUG: cannot send frame");
+        }
+
+        async fn respond(&mut self, request: &Request, payload: ResponsePayload) {
+            self.send(Rpc::from(Response {
+                id: request.id.expect("BUG: missing request id"),
+                payload,
+            }))
+            .await;
+        }
+
+        async fn respond_result(&mut self, request: &Request, result: serde_json::Value) {
+            self.respond(
+                request,
+                ResponsePayload {
+                    result: Some(StratumResult(result)),
+                    error: None,
+                },
+            )
+            .await;
+        }
+
+        async fn respond_error(&mut self, request: &Request, code: i32) {
+            self.respond(
+                request,
+                ResponsePayload {
+                    result: None,
+                    error: Some(StratumError(code, "Test error".to_string(), None)),
+                },
+            )
+            .await;
+        }
+
+        /// Answer requests which open the mining session with `subscribe_result` and return the
+        /// subscription requested by the client
+        async fn open_session(&mut self, subscribe_result: SubscribeResult) -> Subscribe {
+            let mut subscribe = None;
+            loop {
+                let request = self.receive().await;
+                match request.payload.method {
+                    Method::Configure => {
+                        self.respond_result(
+                            &request,
+                            serde_json::json!({
+                                "version-rolling": true,
+                                "version-rolling.mask": "1fffe000"
+                            }),
+                        )
+                        .await
+                    }
+                    Method::Subscribe => {
+                        self.respond_result(
+                            &request,
+                            serde_json::to_value(&subscribe_result)
+                                .expect("BUG: cannot serialize subscribe result"),
+                        )
+                        .await;
+                        subscribe.replace(
+                            Subscribe::try_from(request).expect("BUG: invalid subscribe request"),
+                        );
+                    }
+                    Method::Authorize => {
+                        self.respond_result(&request, serde_json::json!(true)).await;
+                        break;
+                    }
+                    method => panic!("BUG: unexpected method {:?}", method),
+                }
+            }
+            subscribe.expect("BUG: missing subscribe request")
+        }
+    }
+
+    /// Notification sent by the pool outside of any request
+    fn build_notification<M>(message: M) -> Rpc
+    where
+        M: TryInto<RequestPayload, Error = ii_stratum::error::Error>,
+    {
+        Rpc::from(Request {
+            id: None,
+            payload: message.try_into().expect("BUG: cannot build notification"),
+        })
+    }
+
+    /// Sample job notification with another `job_id` and `clean_jobs` flag
+    fn build_notify(job_id: &str, clean_jobs: bool) -> Rpc {
+        v1_test::MINING_NOTIFY_JSON
+            .replace(v1_test::MINING_NOTIFY_JOB_ID, job_id)
+            .replace("false]", &format!("{}]", clean_jobs))
+            .parse()
+            .expect("BUG: cannot parse notify")
+    }
+
+    /// Connect the `client` to the mock `pool` and open the mining session with
+    /// `subscribe_result`
+    async fn open_session(
+        client: &Arc<StratumClient>,
+        pool: &mut MockPool,
+        subscribe_result: SubscribeResult,
+    ) -> (StratumEventHandler, FramedStream, Subscribe) {
+        let connection = client.connect().await.expect("BUG: cannot connect");
+        pool.accept().await;
+        let (connection_tx, mut connection_rx) = connection.split();
+        let mut event_handler = StratumEventHandler::new(client.clone(), connection_tx, None);
+        let (status, subscribe) = ii_async_compat::join!(
+            event_handler.init_mining_session(&mut connection_rx),
+            pool.open_session(subscribe_result)
+        );
+        status.expect("BUG: cannot open session");
+        (event_handler, connection_rx, subscribe)
+    }
+
+    /// Handle the next message sent by the pool
+    async fn handle_next(
+        event_handler: &mut StratumEventHandler,
+        connection_rx: &mut FramedStream,
+    ) {
+        let frame = connection_rx
+            .next()
+            .await
+            .expect("BUG: connection closed")
+            .expect("BUG: cannot receive frame");
+        event_handler
+            .handle_frame(frame)
+            .await
+            .expect("BUG: cannot handle frame");
+    }
+
+    /// The last job sent for solving by the `client`
+    async fn last_job(client: &StratumClient) -> Arc<StratumJob> {
+        client
+            .last_job
+            .lock()
+            .await
+            .as_ref()
+            .and_then(Weak::upgrade)
+            .expect("BUG: missing job")
+    }
+
+    /// Job is built from the notification and rolled with the next extranonce 2 when the pool
+    /// does not send a new one
+    #[tokio::test]
+    async fn test_notify_job() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, mut connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        pool.send(v1_test::build_mining_notify_request_message())
+            .await;
+        for _ in 0..2 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+
+        let notify = v1_test::build_mining_notify();
+        let job = last_job(&client).await;
+        assert_eq!(job.job_id, JobId::from_str(v1_test::MINING_NOTIFY_JOB_ID));
+        assert_eq!(job.extra_nonce2, vec![0; 4]);
+        assert_eq!(
+            job.merkle_root,
+            ii_bitcoin::DHash::from_hex(v1_test::MINING_NOTIFY_MERKLE_ROOT)
+                .expect("BUG: invalid merkle root")
+        );
+        assert_eq!(job.version, notify.version());
+        assert_eq!(job.version_mask, ii_stratum::BIP320_N_VERSION_MASK);
+        assert_eq!(job.bits, notify.bits());
+        assert_eq!(job.time, notify.time());
+        assert_eq!(job.target, ii_bitcoin::Target::from_pool_difficulty(4));
+        assert!(job::Bitcoin::is_valid(&*job));
+
+        // the job is not rolled before the interval elapses
+        tokio::time::pause();
+        event_handler.roll_job().await;
+        assert!(Arc::ptr_eq(&last_job(&client).await, &job));
+
+        tokio::time::advance(StratumClient::JOB_ROLL_INTERVAL).await;
+        event_handler.roll_job().await;
+        let rolled_job = last_job(&client).await;
+        assert_eq!(rolled_job.job_id, job.job_id);
+        assert_eq!(rolled_job.extra_nonce2, vec![1, 0, 0, 0]);
+        assert_ne!(rolled_job.merkle_root, job.merkle_root);
+        assert_eq!(rolled_job.target, job.target);
+        // solutions of the previous extranonce 2 are still submitted
+        assert!(job::Bitcoin::is_valid(&*job));
+    }
+
+    /// Pool which stops sending messages is detected even though the job roll timer keeps
+    /// waking the main loop
+    #[tokio::test]
+    async fn test_silent_pool() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+
+        tokio::time::pause();
+        let main_loop = client.main_loop(connection_rx, &mut event_handler);
+        let clock_driver = async {
+            let mut elapsed = time::Duration::from_secs(0);
+            while elapsed <= 2 * StratumClient::EVENT_TIMEOUT {
+                tokio::time::advance(StratumClient::JOB_ROLL_CHECK_INTERVAL).await;
+                elapsed += StratumClient::JOB_ROLL_CHECK_INTERVAL;
+            }
+        };
+        select! {
+            result = main_loop.fuse() => assert!(result.is_err()),
+            _ = clock_driver.fuse() => panic!("BUG: silent pool has not been detected"),
+        }
+    }
+
+    /// Notification with `clean_jobs` invalidates all previous jobs
+    #[tokio::test]
+    async fn test_clean_jobs() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, mut connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        handle_next(&mut event_handler, &mut connection_rx).await;
+        let mut jobs = vec![];
+        for (job_id, clean_jobs) in &[("1", false), ("2", false), ("3", true)] {
+            pool.send(build_notify(job_id, *clean_jobs)).await;
+            handle_next(&mut event_handler, &mut connection_rx).await;
+            let job = last_job(&client).await;
+            assert_eq!(job.job_id, JobId::from_str(job_id));
+            jobs.push(job);
+        }
+
+        assert!(!job::Bitcoin::is_valid(&*jobs[0]));
+        assert!(!job::Bitcoin::is_valid(&*jobs[1]));
+        assert!(job::Bitcoin::is_valid(&*jobs[2]));
+        assert!(!build_solution(jobs[1].clone(), 0).has_valid_job());
+        assert!(build_solution(jobs[2].clone(), 0).has_valid_job());
+    }
+
+    /// Difficulty changed in the middle of the job is used since the next job
+    #[tokio::test]
+    async fn test_set_difficulty_mid_job() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, mut connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        pool.send(build_notify("1", false)).await;
+        for _ in 0..2 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+        let job = last_job(&client).await;
+
+        pool.send(build_notification(SetDifficulty([8.0]))).await;
+        handle_next(&mut event_handler, &mut connection_rx).await;
+        assert!(Arc::ptr_eq(&last_job(&client).await, &job));
+        assert_eq!(job.target, ii_bitcoin::Target::from_pool_difficulty(4));
+        assert_eq!(
+            event_handler.current_target,
+            Some(ii_bitcoin::Target::from_pool_difficulty(8))
+        );
+
+        pool.send(build_notify("2", false)).await;
+        handle_next(&mut event_handler, &mut connection_rx).await;
+        let next_job = last_job(&client).await;
+        assert_eq!(next_job.job_id, JobId::from_str("2"));
+        assert_eq!(next_job.target, ii_bitcoin::Target::from_pool_difficulty(8));
+        // the difficulty change does not invalidate the current job
+        assert!(job::Bitcoin::is_valid(&*job));
+
+        let changes = client.client_stats.difficulty_history.take_snapshot();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].old_difficulty, Some(4));
+        assert_eq!(changes[1].new_difficulty, 8);
+        assert_eq!(changes[1].trigger, stats::DifficultyTrigger::Vardiff);
+    }
+
+    /// Every response of the pool to a submission is accounted by its outcome
+    #[tokio::test]
+    async fn test_submit_accounting() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, mut connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        pool.send(v1_test::build_mining_notify_request_message())
+            .await;
+        for _ in 0..2 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+        let job = last_job(&client).await;
+
+        for nonce in 1..=3 {
+            event_handler
+                .process_solution(build_solution(job.clone(), nonce))
+                .await
+                .expect("BUG: cannot submit solution");
+        }
+        assert_eq!(client.lock_submissions().len(), 3);
+
+        for nonce in 1..=3 {
+            let request = pool.receive().await;
+            assert_eq!(request.payload.method, Method::Submit);
+            match nonce {
+                1 => pool.respond_result(&request, serde_json::json!(true)).await,
+                2 => pool.respond_error(&request, STALE_JOB_ERROR_CODE).await,
+                _ => {
+                    pool.respond_result(&request, serde_json::json!(false))
+                        .await
+                }
+            }
+            let submit = Submit::try_from(request).expect("BUG: invalid submit request");
+            assert_eq!(submit.user_name(), "braiins.worker0");
+            assert_eq!(submit.job_id(), v1_test::MINING_NOTIFY_JOB_ID);
+            assert_eq!(submit.extra_nonce_2(), &[0; 4]);
+            assert_eq!(submit.time(), job.time);
+            assert_eq!(submit.nonce(), nonce);
+        }
+        for _ in 0..3 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+
+        assert!(client.lock_submissions().is_empty());
+        let stats = &client.client_stats;
+        assert_eq!(stats.accepted.take_snapshot().await.solutions, 1);
+        assert_eq!(stats.stale.take_snapshot().await.solutions, 1);
+        assert_eq!(stats.rejected.take_snapshot().await.solutions, 1);
+        assert_eq!(*stats.submissions.unresolved.take_snapshot(), 0);
+    }
+
+    /// Stratum V1 client shares the group with a Stratum V2 client and mines jobs with the
+    /// midstate count of the group
+    #[tokio::test]
+    async fn test_mixed_group() {
+        let (mut pool, address) = MockPool::bind();
+        let user_info = bosminer_config::ClientUserInfo::new("braiins.worker0", None);
+        let client_manager = crate::client::Manager::new(1);
+        let group = client_manager.create_or_get_default_group().await;
+
+        let mut client = None;
+        let descriptor =
+            ClientDescriptor::create(&format!("stratum+tcp://{}", address), &user_info, false)
+                .expect("BUG: cannot create client descriptor");
+        let v1_handle = group
+            .push_client(crate::client::Handle::with_node(
+                descriptor,
+                client_manager.services(),
+                |descriptor, job_solver| {
+                    let node = Arc::new(StratumClient::new(
+                        ConnectionDetails::from_descriptor(descriptor),
+                        job_solver,
+                    ));
+                    client.replace(node.clone());
+                    node
+                },
+            ))
+            .await;
+        let client = client.expect("BUG: missing stratum V1 client");
+        let descriptor =
+            ClientDescriptor::create("stratum2+tcp+insecure://127.0.0.1:1", &user_info, false)
+                .expect("BUG: cannot create client descriptor");
+        let v2_handle = group
+            .push_client(crate::client::Handle::new(
+                descriptor,
+                client_manager.services(),
+                None,
+                None,
+            ))
+            .await;
+
+        let clients = group.get_clients().await;
+        assert_eq!(clients.len(), 2);
+        assert!(v1_handle
+            .node()
+            .clone()
+            .get_unique_ptr()
+            .is::<StratumClient>());
+        assert!(v2_handle
+            .node()
+            .clone()
+            .get_unique_ptr()
+            .is::<crate::client::stratum_v2::StratumClient>());
+
+        // Redirect engines from the stratum V1 client to local receiver
+        let (engine_sender, mut engine_receiver) = work::engine_channel(work::IgnoreEvents);
+        v1_handle.engine_sender.swap_sender(&engine_sender);
+
+        let (mut event_handler, mut connection_rx, _) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        pool.send(v1_test::build_mining_notify_request_message())
+            .await;
+        for _ in 0..2 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+
+        let engine = engine_receiver
+            .get_engine()
+            .await
+            .expect("BUG: missing work engine");
+        let work = engine.next_work().unwrap();
+        assert_eq!(work.midstates.len(), 1);
+        let origin = work.origin().upgrade().expect("BUG: missing job origin");
+        assert!(Arc::ptr_eq(
+            &origin.get_unique_ptr(),
+            &v1_handle.node().clone().get_unique_ptr()
+        ));
+        assert_eq!(
+            work.job::<StratumJob>().job_id,
+            JobId::from_str(v1_test::MINING_NOTIFY_JOB_ID)
+        );
+    }
+
+    /// Client disconnected from the mock `pool` while it has been waiting for the response to
+    /// its submission. The job of the submission is returned.
+    async fn disconnect_with_submission(
+        pool: &mut MockPool,
+        address: SocketAddr,
+    ) -> (Arc<StratumClient>, Arc<StratumJob>) {
+        let (client, _solution_sender) = create_client(address, None);
+        let (mut event_handler, mut connection_rx, subscribe) =
+            open_session(&client, pool, v1_test::build_subscribe_ok_result()).await;
+        // there is no previous session to be resumed
+        assert_eq!(subscribe.extra_nonce1(), None);
+
+        pool.send(v1_test::build_set_difficulty_request_message())
+            .await;
+        pool.send(v1_test::build_mining_notify_request_message())
+            .await;
+        for _ in 0..2 {
+            handle_next(&mut event_handler, &mut connection_rx).await;
+        }
+        let job = last_job(&client).await;
+        event_handler
+            .process_solution(build_solution(job.clone(), 1))
+            .await
+            .expect("BUG: cannot submit solution");
+        assert_eq!(pool.receive().await.payload.method, Method::Submit);
+
+        drop(event_handler);
+        drop(connection_rx);
+        pool.close();
+        client.disconnect(false).await;
+        // the work is kept until the next session is established
+        assert!(job::Bitcoin::is_valid(&*job));
+        assert_eq!(client.lock_submissions().len(), 1);
+        (client, job)
+    }
+
+    fn session_outcomes(client: &StratumClient) -> Vec<session::Outcome> {
+        node::Client::session_history(client)
+            .iter()
+            .map(|event| event.outcome)
+            .collect()
+    }
+
+    /// Session resumed by the pool keeps the work and the submission without response is sent
+    /// again
+    #[tokio::test]
+    async fn test_session_resumed() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, job) = disconnect_with_submission(&mut pool, address).await;
+
+        let (_event_handler, _connection_rx, subscribe) =
+            open_session(&client, &mut pool, v1_test::build_subscribe_ok_result()).await;
+        assert_eq!(
+            subscribe.extra_nonce1(),
+            Some(v1_test::build_subscribe_ok_result().extra_nonce_1())
+        );
+        assert_eq!(
+            session_outcomes(&client),
+            vec![session::Outcome::Fresh, session::Outcome::Resumed]
+        );
+        assert!(job::Bitcoin::is_valid(&*job));
+
+        let submit = Submit::try_from(pool.receive().await).expect("BUG: invalid submit request");
+        assert_eq!(submit.nonce(), 1);
+        assert_eq!(
+            *client
+                .client_stats
+                .submissions
+                .retransmitted
+                .take_snapshot(),
+            1
+        );
+        assert_eq!(client.lock_submissions().len(), 1);
+    }
+
+    /// Session which the pool has refused to resume invalidates the work and abandons the
+    /// submission without response
+    #[tokio::test]
+    async fn test_session_refused() {
+        let (mut pool, address) = MockPool::bind();
+        let (client, job) = disconnect_with_submission(&mut pool, address).await;
+
+        let mut subscribe_result = v1_test::build_subscribe_ok_result();
+        subscribe_result.1 =
+            v1::ExtraNonce1(v1::HexBytes::try_from("6c6f0100000d").expect("BUG: invalid hex"));
+        let (_event_handler, _connection_rx, subscribe) =
+            open_session(&client, &mut pool, subscribe_result).await;
+        assert_eq!(
+            subscribe.extra_nonce1(),
+            Some(v1_test::build_subscribe_ok_result().extra_nonce_1())
+        );
+        assert_eq!(
+            session_outcomes(&client),
+            vec![session::Outcome::Fresh, session::Outcome::Refused]
+        );
+        assert!(!job::Bitcoin::is_valid(&*job));
+
+        assert!(client.lock_submissions().is_empty());
+        assert_eq!(
+            *client
+                .client_stats
+                .submissions
+                .retransmitted
+                .take_snapshot(),
+            0
+        );
+        assert_eq!(
+            *client.client_stats.submissions.unresolved.take_snapshot(),
+            1
+        );
+    }
+
+    /// Client is not reconnected to the pool whose certificate cannot be verified
+    #[tokio::test]
+    async fn test_rejected_certificate() {
+        let certificate = tls::test::TestCertificate::new("stratum-v1");
+        let address = tls::test::run_server(&certificate).await;
+
+        let connection_details = ConnectionDetails {
+            user: "braiins.worker0".to_string(),
+            password: None,
+            host: "localhost".to_string(),
+            port: address.port(),
+            fragment: None,
+            coinbase_check: None,
+            suggested_difficulty: None,
+            // the self-signed certificate is not trusted by built-in root certificates
+            tls: Some(tls::Settings {
+                ca_cert: None,
+                insecure: false,
+            }),
+            cert_expiry_warning: time::Duration::from_secs(0),
+            dns_ttl: time::Duration::from_secs(60),
+        };
+        let (_solution_sender, solution_receiver) = mpsc::unbounded();
+        let solver = job::Solver::new(Arc::new(work::EngineSender::new(None)), solution_receiver);
+        let client = Arc::new(StratumClient::new(connection_details, solver));
+
+        assert!(client.status.initiate_starting());
+        client.clone().run().await;
+        match node::Client::fatal_error(&*client) {
+            Some(error::ErrorKind::Certificate(_)) => {}
+            error => panic!("BUG: unexpected fatal error {:?}", error),
+        }
+        // the client has failed before it started running
+        assert_eq!(client.status.status(), sync::Status::Declining);
+    }
+}
@@ -25,9 +25,16 @@ pub mod telemetry;
 
 use ii_logging::macros::*;
 
-use crate::error;
+use super::health;
+use super::resolver;
+use super::session;
+use super::tls;
+use crate::bus;
+use crate::clock;
+use crate::error::{self, Classify};
 use crate::hal;
 use crate::job;
+use crate::log_id;
 use crate::node;
 use crate::stats;
 use crate::sync;
@@ -37,7 +44,7 @@ use failure::ResultExt;
 
 use ii_bitcoin::HashTrait;
 
-use bosminer_config::{ClientDescriptor, ClientProtocol};
+use bosminer_config::{client_url_host, ClientDescriptor, ClientProtocol};
 use bosminer_macros::ClientNode;
 
 use async_trait::async_trait;
@@ -48,9 +55,8 @@ use ii_async_compat::select;
 
 use std::collections::VecDeque;
 use std::fmt;
-use std::str::FromStr;
-use std::sync::Mutex as StdMutex;
 use std::sync::{Arc, Weak};
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard};
 use std::time;
 
 use ii_stratum::v2::messages::{
@@ -65,12 +71,15 @@ use ii_stratum::v2::{
     framing::{Framing, Header},
 };
 use ii_stratum::v2::{build_message_from_frame, extensions, Handler};
+use tokio::net::TcpStream;
 
 use std::collections::HashMap;
 
 // TODO: move it to the stratum crate
 const VERSION_MASK: u32 = 0x1fffe000;
 
+type Framed = tls::Framed<v2::Codec>;
+
 #[derive(Debug, Clone)]
 pub struct ConnectionDetails {
     /// TODO temporary field that denotes the protocol, it will be replaced by a `Connector`
@@ -79,6 +88,14 @@ pub struct ConnectionDetails {
     pub user: String,
     pub host: String,
     pub port: u16,
+    /// Warn when the certificate of the upstream server expires within this window
+    pub cert_expiry_warning: time::Duration,
+    /// Share difficulty suggested to the pool (shares meeting the pool target are still submitted)
+    pub suggested_difficulty: Option<usize>,
+    /// Settings of TLS session wrapping the connection (if any)
+    pub tls: Option<tls::Settings>,
+    /// Address of an established connection is preferred on reconnect only within this time
+    pub dns_ttl: time::Duration,
 }
 
 impl ConnectionDetails {
@@ -88,11 +105,22 @@ impl ConnectionDetails {
             user: descriptor.user.clone(),
             host: descriptor.host.clone(),
             port: descriptor.port(),
+            cert_expiry_warning: descriptor.cert_expiry_warning,
+            suggested_difficulty: descriptor.suggested_difficulty(),
+            tls: tls::Settings::from_descriptor(descriptor),
+            dns_ttl: descriptor.dns_ttl,
         }
     }
 
+    /// The easiest target accepted by the client when opening a channel
+    fn max_target(&self) -> ii_bitcoin::Target {
+        self.suggested_difficulty
+            .map(ii_bitcoin::Target::from_pool_difficulty)
+            .unwrap_or_default()
+    }
+
     fn get_host_and_port(&self) -> String {
-        format!("{}:{}", self.host, self.port)
+        format!("{}:{}", client_url_host(&self.host), self.port)
     }
 }
 
@@ -165,6 +193,10 @@ impl job::Bitcoin for StratumJob {
         self.target
     }
 
+    fn id(&self) -> Option<u32> {
+        Some(self.id)
+    }
+
     fn is_valid(&self) -> bool {
         // TODO: currently there is no easy way to detect the job is valid -> we have to check
         //  its presence in the registry. The inequality below was possible in the previous
@@ -178,7 +210,43 @@ impl job::Bitcoin for StratumJob {
 /// to keep the sequence number monotonic so that we as a stratum V2 client can easily process bulk
 /// acknowledgements. The sequence number type has been selected as u32 to match
 /// up with the protocol.
-type SolutionQueue = Mutex<VecDeque<(work::Solution, u32)>>;
+#[derive(Debug, Default)]
+struct SolutionQueue {
+    solutions: VecDeque<(work::Solution, u32)>,
+}
+
+impl SolutionQueue {
+    /// Maximal number of solutions waiting for acknowledgement. The server which does not
+    /// acknowledge anything must not make the client hold solutions (and their jobs) forever.
+    const MAX_LENGTH: usize = 1024;
+
+    /// Append a solution with the next sequence number and return the number of the oldest
+    /// solutions which have been dropped to make room for it
+    fn push(&mut self, solution: work::Solution, seq_num: u32) -> usize {
+        let mut dropped = 0;
+        while self.solutions.len() >= Self::MAX_LENGTH {
+            self.solutions.pop_front();
+            dropped += 1;
+        }
+        self.solutions.push_back((solution, seq_num));
+        dropped
+    }
+
+    /// Take the oldest solution when it is covered by an acknowledgement up to `seq_num`
+    fn pop_acknowledged(&mut self, seq_num: u32) -> Option<(work::Solution, u32)> {
+        match self.solutions.front() {
+            Some((_, oldest)) if *oldest <= seq_num => self.solutions.pop_front(),
+            _ => None,
+        }
+    }
+
+    /// Remove all solutions and return their number
+    fn clear(&mut self) -> usize {
+        let count = self.solutions.len();
+        self.solutions.clear();
+        count
+    }
+}
 
 /// Helper task for `StratumClient` that implements Stratum V2 visitor which processes incoming
 /// messages from remote server.
@@ -191,12 +259,17 @@ struct StratumEventHandler {
 }
 
 impl StratumEventHandler {
-    pub fn new(client: Arc<StratumClient>, current_target: ii_bitcoin::Target) -> Self {
+    pub fn new(client: Arc<StratumClient>, init_target: ii_bitcoin::Target) -> Self {
+        client.client_stats.difficulty_history.record(
+            &init_target,
+            stats::DifficultyTrigger::Initial,
+            clock::now(),
+        );
         Self {
             client,
             all_jobs: Default::default(),
             current_prevhash_msg: None,
-            current_target,
+            current_target: init_target,
         }
     }
 
@@ -223,14 +296,28 @@ impl StratumEventHandler {
             new_target,
             new_target.get_difficulty()
         );
+        self.client.client_stats.difficulty_history.record(
+            &new_target,
+            stats::DifficultyTrigger::Vardiff,
+            clock::now(),
+        );
+        // Shares meeting the target of the pool are submitted even when they are below the
+        // suggested difficulty
         self.current_target = new_target;
     }
 
     async fn process_accepted_shares(&self, success_msg: &SubmitSharesSuccess) {
-        let now = std::time::Instant::now();
-        while let Some((solution, seq_num)) = self.client.solutions.lock().await.pop_front() {
+        let now = clock::now();
+        while let Some((solution, seq_num)) = self
+            .client
+            .solutions
+            .lock()
+            .await
+            .pop_acknowledged(success_msg.last_seq_num)
+        {
             info!(
-                "Stratum: accepted solution #{} with nonce={:08x}",
+                "{} Stratum: accepted solution #{} with nonce={:08x}",
+                self.client.log_prefix(&solution),
                 seq_num,
                 solution.nonce()
             );
@@ -239,6 +326,11 @@ impl StratumEventHandler {
                 .accepted
                 .account_solution(&solution.job_target(), now)
                 .await;
+            self.client
+                .client_stats
+                .job_history
+                .account(solution.dyn_job(), stats::JobActivity::Accepted);
+            self.client.publish_accepted(&solution);
             if success_msg.last_seq_num == seq_num {
                 // all accepted solutions have been found
                 return;
@@ -251,26 +343,51 @@ impl StratumEventHandler {
     }
 
     async fn process_rejected_shares(&self, error_msg: &SubmitSharesError) {
-        let now = std::time::Instant::now();
-        while let Some((solution, seq_num)) = self.client.solutions.lock().await.pop_front() {
+        let now = clock::now();
+        while let Some((solution, seq_num)) = self
+            .client
+            .solutions
+            .lock()
+            .await
+            .pop_acknowledged(error_msg.seq_num)
+        {
             if error_msg.seq_num == seq_num {
                 info!(
-                    "Stratum: rejected solution #{} with nonce={:08x}!",
+                    "{} Stratum: rejected solution #{} with nonce={:08x}!",
+                    self.client.log_prefix(&solution),
                     seq_num,
                     solution.nonce()
                 );
                 self.client
-                    .client_stats
-                    .rejected
-                    .account_solution(&solution.job_target(), now)
-                    .await;
+                    .block_archive
+                    .record_response(&solution, job::archive::Response::Rejected);
+                // Rejected foreign job must not count against this client (e.g. reject breaker)
+                if !solution.is_rerouted() {
+                    self.client
+                        .client_stats
+                        .rejected
+                        .account_solution(&solution.job_target(), now)
+                        .await;
+                    stats::account_rejected_job_diff(&solution.path(), &solution.job_target(), now)
+                        .await;
+                    self.client
+                        .client_stats
+                        .job_history
+                        .account(solution.dyn_job(), stats::JobActivity::Rejected);
+                    self.client.bus.publish(bus::Event::ShareRejected {
+                        client: self.client.client_name,
+                        difficulty: solution.job_target().get_difficulty(),
+                        stale: false,
+                    });
+                }
                 // the rejected solution has been found
                 return;
             } else {
                 // TODO: this is currently not according to stratum V2 specification
                 // preceding solutions are treated as accepted
                 info!(
-                    "Stratum: accepted solution #{} with nonce={}",
+                    "{} Stratum: accepted solution #{} with nonce={}",
+                    self.client.log_prefix(&solution),
                     seq_num,
                     solution.nonce()
                 );
@@ -279,6 +396,11 @@ impl StratumEventHandler {
                     .accepted
                     .account_solution(&solution.job_target(), now)
                     .await;
+                self.client
+                    .client_stats
+                    .job_history
+                    .account(solution.dyn_job(), stats::JobActivity::Accepted);
+                self.client.publish_accepted(&solution);
                 warn!(
                     "Stratum: the solution #{} precedes rejected solution #{}!",
                     seq_num, error_msg.seq_num
@@ -437,12 +559,23 @@ where
             ntime: solution.time(),
             version: solution.version(),
         };
-        // store solution with sequence number for future server acknowledge
         self.client
-            .solutions
-            .lock()
-            .await
-            .push_back((solution, seq_num));
+            .client_stats
+            .job_history
+            .account(solution.dyn_job(), stats::JobActivity::Submitted);
+        // store solution with sequence number for future server acknowledge
+        let dropped = self.client.solutions.lock().await.push(solution, seq_num);
+        if dropped > 0 {
+            warn!(
+                "Stratum: {} solutions without acknowledgement have been dropped",
+                dropped
+            );
+            self.client
+                .client_stats
+                .submissions
+                .unresolved
+                .add(dropped as u64);
+        }
         // send solutions back to the stratum server
         StratumClient::send_msg(&self.connection_tx, share_msg)
             .await
@@ -455,6 +588,10 @@ where
 struct StratumConnectionHandler {
     client: Arc<StratumClient>,
     init_target: ii_bitcoin::Target,
+    /// Identity of the session assigned by the remote server
+    session_token: Option<session::Token>,
+    /// Security parameters of the secure connection
+    security: Option<session::Security>,
     status: Option<error::Result<()>>,
 }
 
@@ -463,6 +600,8 @@ impl StratumConnectionHandler {
         Self {
             client,
             init_target: Default::default(),
+            session_token: None,
+            security: None,
             status: None,
         }
     }
@@ -511,18 +650,18 @@ impl StratumConnectionHandler {
         R: FrameStream,
         S: FrameSink,
     {
+        let connection_details = self.client.connection_details();
         let channel_msg = OpenStandardMiningChannel {
             req_id: 10, // TODO? come up with request ID sequencing
-            user: self
-                .client
-                .connection_details()
+            user: connection_details
                 .user
                 .clone()
                 .try_into()
                 .expect("BUG: cannot convert 'OpenStandardMiningChannel::user'"),
             nominal_hashrate: 1e9,
-            // Maximum bitcoin target is 0xffff << 208 (= difficulty 1 share)
-            max_target: ii_bitcoin::Target::default().into(),
+            // Maximum bitcoin target is 0xffff << 208 (= difficulty 1 share) unless the client
+            // suggests higher difficulty
+            max_target: connection_details.max_target().into(),
         };
 
         StratumClient::send_msg(&connection_tx, channel_msg)
@@ -541,13 +680,34 @@ impl StratumConnectionHandler {
             .unwrap_or(Err("Unexpected response for stratum open channel".into()))
     }
 
-    async fn connect(&self) -> error::Result<v2::Framed> {
+    /// Every resolved address is given the whole connection timeout and the handshake of the
+    /// secure protocol is limited by it separately
+    async fn connect(&mut self) -> error::Result<Framed> {
         let connection_details = self.client.connection_details();
-        let addr = ii_wire::Address::from_str(connection_details.get_host_and_port().as_str())?;
-        let mut client = ii_wire::Client::new(addr);
-        // Attempt only once to connect (as the stratum client is being managed externally)
-        let connection = client.next().await?;
+        // Attempt only once to connect to each address (as the stratum client is being managed
+        // externally)
+        let (connection, _) = self
+            .client
+            .resolver
+            .connect(
+                &connection_details.host,
+                connection_details.port,
+                connection_details.dns_ttl,
+                StratumClient::CONNECTION_TIMEOUT,
+            )
+            .await?;
+        self.handshake(connection, connection_details)
+            .timeout(StratumClient::CONNECTION_TIMEOUT)
+            .await
+            .map_err(|_| error::ErrorKind::General("Connection timeout".to_string()))?
+    }
 
+    /// Wrap the TCP `connection` in the transport required by the protocol
+    async fn handshake(
+        &mut self,
+        connection: TcpStream,
+        connection_details: ConnectionDetails,
+    ) -> error::Result<Framed> {
         // TODO this will be replaced by a 'connector' that will be set when building stratum
         // client instance
         let client_framed_stream = match connection_details.protocol {
@@ -556,15 +716,36 @@ impl StratumConnectionHandler {
                 let noise_initiator =
                     v2::noise::Initiator::new(upstream_authority_public_key.into_inner());
                 // Successful noise initiator handshake results in a stream/sink for V2 frames
-                noise_initiator.connect(connection).await?
+                let (framed_stream, security) =
+                    noise_initiator.connect_with_info(connection).await?;
+                self.security = Some(security.into());
+                tls::Stream::from_tcp_framed(framed_stream)
             }
             // V2 insecure connector
             ClientProtocol::StratumV2Insecure => {
-                ii_wire::Connection::<v2::Framing>::new(connection).into_inner()
+                Framed::new(tls::Stream::Tcp(connection), Default::default())
+            }
+            // V2 connector secured by TLS session instead of noise protocol
+            ClientProtocol::StratumV2Tls => {
+                let stream = tls::Stream::connect(
+                    &connection_details.host,
+                    connection,
+                    connection_details.tls.as_ref(),
+                )
+                .await?;
+                self.security = stream.security();
+                Framed::new(stream, Default::default())
             }
             // Anything else is considered a bug
             _ => panic!("BUG: client supports only stratum V2 protocols!"),
         };
+        if let Some(security) = self.security.as_ref() {
+            session::check_expiry(
+                security,
+                connection_details.cert_expiry_warning,
+                &self.client,
+            );
+        }
 
         Ok(client_framed_stream)
     }
@@ -585,6 +766,14 @@ impl StratumConnectionHandler {
         self.open_channel(connection_rx, connection_tx)
             .await
             .context("Cannot open stratum channel")?;
+        self.client
+            .establish_session(
+                self.session_token
+                    .take()
+                    .expect("BUG: missing session token"),
+                self.security.take(),
+            )
+            .await;
 
         Ok(self.init_target)
     }
@@ -615,6 +804,12 @@ impl Handler for StratumConnectionHandler {
         success_msg: &OpenStandardMiningChannelSuccess,
     ) {
         self.init_target = success_msg.target.into();
+        self.client
+            .set_authorization(health::Authorization::Authorized);
+        self.session_token = Some(session::Token::new(
+            success_msg.channel_id,
+            success_msg.extranonce_prefix.as_ref(),
+        ));
         self.status = Ok(()).into();
     }
 
@@ -623,6 +818,8 @@ impl Handler for StratumConnectionHandler {
         _header: &Header,
         error_msg: &OpenStandardMiningChannelError,
     ) {
+        self.client
+            .set_authorization(health::Authorization::Rejected);
         self.status =
             Err(format!("Open channel error: {}", error_msg.code.to_string()).into()).into();
     }
@@ -662,14 +859,26 @@ pub struct StratumClient {
     // Last job has to be weak reference to prevent circular reference (the `StratumJob` keeps
     // reference to `StratumClient`)
     last_job: Mutex<Option<Arc<StratumJob>>>,
-    solutions: SolutionQueue,
+    solutions: Mutex<SolutionQueue>,
     job_sender: Mutex<job::Sender>,
     solution_receiver: Mutex<job::SolutionReceiver>,
+    client_name: log_id::ClientName,
+    /// Session which may be resumed after reconnect
+    session: StdMutex<session::Tracker>,
+    /// The last failure which cannot be resolved by reconnecting to the same server
+    fatal_error: StdMutex<Option<error::ErrorKind>>,
+    /// Response of the server to the last request to open a mining channel for the user
+    authorization: StdMutex<health::Authorization>,
+    /// Addresses of the server are resolved again on every reconnect
+    resolver: resolver::Resolver,
     /// Frames received from this channel will be forwarded to the network connection
     extension_channel_receiver: Mutex<ExtensionChannelToStratumReceiver>,
     /// Frames intended for the specified extension will be forwarded into this channel (wrapped
     /// into ExtensionChannelMsg
     extension_channel_sender: Mutex<ExtensionChannelFromStratumSender>,
+    bus: Arc<bus::Bus>,
+    /// Archive of block candidates where responses of the pool are recorded
+    block_archive: Arc<job::archive::Archive>,
 }
 
 impl StratumClient {
@@ -736,22 +945,45 @@ impl StratumClient {
             Self::start_dummy_extension_task(connection_details.clone())
         });
 
+        let bus = solver.bus();
+        let block_archive = solver.block_archive();
         Self {
             connection_details: Arc::new(StdMutex::new(connection_details)),
             backend_info,
-            status: Default::default(),
+            status: sync::StatusMonitor::for_client(solver.client_name(), bus.clone()),
             client_stats: Default::default(),
             stop_sender: stop_sender,
             stop_receiver: Mutex::new(stop_receiver),
             last_job: Mutex::new(None),
-            solutions: Mutex::new(VecDeque::new()),
+            solutions: Default::default(),
+            client_name: solver.client_name(),
             job_sender: Mutex::new(solver.job_sender),
             solution_receiver: Mutex::new(solver.solution_receiver),
+            session: StdMutex::new(session::Tracker::new()),
+            fatal_error: StdMutex::new(None),
+            authorization: Default::default(),
+            resolver: resolver::Resolver::new(),
             extension_channel_receiver: Mutex::new(extension_channel_receiver),
             extension_channel_sender: Mutex::new(extension_channel_sender),
+            bus,
+            block_archive,
         }
     }
 
+    /// Prefix of log records related to the `solution` submitted by this client
+    fn log_prefix(&self, solution: &work::Solution) -> log_id::Prefix {
+        log_id::Prefix::solution(solution).with_client(self.client_name)
+    }
+
+    fn publish_accepted(&self, solution: &work::Solution) {
+        self.bus.publish(bus::Event::ShareAccepted {
+            client: self.client_name,
+            difficulty: solution.job_target().get_difficulty(),
+        });
+        self.block_archive
+            .record_response(solution, job::archive::Response::Accepted);
+    }
+
     fn connection_details(&self) -> ConnectionDetails {
         self.connection_details
             .lock()
@@ -763,6 +995,55 @@ impl StratumClient {
         self.last_job.lock().await.replace(job);
     }
 
+    fn lock_session(&self) -> StdMutexGuard<session::Tracker> {
+        self.session
+            .lock()
+            .expect("BUG: cannot lock session tracker")
+    }
+
+    fn lock_fatal_error(&self) -> StdMutexGuard<Option<error::ErrorKind>> {
+        self.fatal_error
+            .lock()
+            .expect("BUG: cannot lock fatal error")
+    }
+
+    fn set_authorization(&self, authorization: health::Authorization) {
+        *self
+            .authorization
+            .lock()
+            .expect("BUG: cannot lock authorization") = authorization;
+    }
+
+    /// Prevent restarting of the client when the `error` cannot be resolved by reconnecting
+    fn check_fatal_error(&self, error: &error::Error) {
+        if !error.is_transient() {
+            error!(
+                "Client {} will not reconnect until its connection details are changed: {}",
+                self.connection_details().get_host_and_port(),
+                error.chain()
+            );
+            self.lock_fatal_error().replace(error.kind());
+        }
+    }
+
+    async fn invalidate_work(&self) {
+        // Invalidate current job to stop working on it
+        self.job_sender.lock().await.invalidate();
+        // Flush all unprocessed solutions to empty buffer
+        // TODO: Count as a discarded solution?
+        self.solution_receiver.lock().await.flush();
+    }
+
+    /// Work from the previous connection is invalidated unless the remote server has resumed
+    /// its session
+    async fn establish_session(&self, token: session::Token, security: Option<session::Security>) {
+        let outcome = self.lock_session().establish_secure(token, security);
+        info!("Stratum: {} session with {}", outcome, self);
+        if outcome.invalidates_work() {
+            self.invalidate_work().await;
+        }
+    }
+
     /// Send a message down a specified Tx Sink
     /// TODO: temporarily, this became an associated method so that we don't have to generalize
     ///  with type parameters the full StratumClient struct. Once this is done, we will use the
@@ -853,6 +1134,8 @@ impl StratumClient {
                 .expect("BUG: stratum extension channel not available for start");
         }
         while !self.status.is_shutting_down() {
+            // Only cancellation safe futures can be raced here: a frame or solution is consumed
+            // by its branch only when the branch wins
             select! {
                 frame = connection_rx.next().timeout(Self::EVENT_TIMEOUT).fuse() => {
                     match frame {
@@ -904,18 +1187,13 @@ impl StratumClient {
     }
 
     async fn run(self: Arc<Self>) {
-        let connection_handler = StratumConnectionHandler::new(self.clone());
+        let mut connection_handler = StratumConnectionHandler::new(self.clone());
         let connection_details = connection_handler.client.connection_details();
         let host_and_port = connection_details.get_host_and_port();
         let user = connection_details.user.clone();
 
-        match connection_handler
-            .connect()
-            .timeout(Self::CONNECTION_TIMEOUT)
-            .await
-            .map_err(|_| error::ErrorKind::General("Connection timeout".to_string()).into())
-        {
-            Ok(Ok(framed_connection)) => {
+        match connection_handler.connect().await {
+            Ok(framed_connection) => {
                 let (framed_sink, mut framed_stream) = framed_connection.split();
                 let framed_sink = Arc::new(Mutex::new(framed_sink));
                 match connection_handler
@@ -934,20 +1212,26 @@ impl StratumClient {
                     }
                     Ok(Err(e)) | Err(e) => {
                         info!(
-                            "Failed to negotiation initial V2 target: at {}, user={} ({:?}",
-                            host_and_port, user, e
+                            "Failed to negotiation initial V2 target: at {}, user={} ({})",
+                            host_and_port,
+                            user,
+                            e.chain()
                         );
+                        self.check_fatal_error(&e);
                         // TODO consolidate this, so that we have exactly 1 place where we
                         //  initiate failing
                         self.status.initiate_failing();
                     }
                 }
             }
-            Ok(Err(e)) | Err(e) => {
+            Err(e) => {
                 info!(
-                    "Failed to connect to {}, user={} {:?}",
-                    host_and_port, user, e
+                    "Failed to connect to {}, user={} ({})",
+                    host_and_port,
+                    user,
+                    e.chain()
                 );
+                self.check_fatal_error(&e);
                 self.status.initiate_failing()
             }
         }
@@ -955,15 +1239,19 @@ impl StratumClient {
 
     async fn main_task(self: Arc<Self>) {
         // TODO: Count as a discarded solution?
-        // Flush all obsolete solutions from previous run
-        self.solution_receiver.lock().await.flush();
+        // Flush all obsolete solutions from previous run unless they can be submitted to
+        // resumed session
+        let pending = self.lock_session().is_pending();
+        if !pending {
+            self.solution_receiver.lock().await.flush();
+        }
 
         loop {
             let mut stop_receiver = self.stop_receiver.lock().await;
-            select! {
-                _ = self.clone().run().fuse() => {}
-                _ = stop_receiver.next() => {}
-            }
+            let stopped = select! {
+                _ = self.clone().run().fuse() => false,
+                _ = stop_receiver.next() => true,
+            };
 
             // Notify the other end that uses the extension channel that it should restart its
             // operation
@@ -982,12 +1270,25 @@ impl StratumClient {
                     e
                 );
             }
-            // Invalidate current job to stop working on it
-            self.job_sender.lock().await.invalidate();
-            // Flush all unprocessed solutions to empty buffer
-            // TODO: Count as a discarded solution?
-            self.solution_receiver.lock().await.flush();
-            self.solutions.lock().await.clear();
+            // Keep mining the current job when the session may be resumed after reconnect, the
+            // decision is made when the next session is established
+            let keep_work = {
+                let mut session = self.lock_session();
+                if stopped {
+                    session.close();
+                }
+                session.disconnect()
+            };
+            if !keep_work {
+                self.invalidate_work().await;
+            }
+            // Submitted solutions are not acknowledged on the next connection so they remain
+            // unresolved
+            let unresolved = self.solutions.lock().await.clear();
+            self.client_stats
+                .submissions
+                .unresolved
+                .add(unresolved as u64);
 
             if self.status.can_stop() {
                 // NOTE: it is not safe to add here any code!
@@ -1030,16 +1331,72 @@ impl node::Client for StratumClient {
             .lock()
             .expect("BUG: cannot lock connection details") =
             ConnectionDetails::from_descriptor(descriptor);
+        // Session of the previous server cannot be resumed
+        self.lock_session().close();
+        // New connection details may resolve the previous failure
+        self.lock_fatal_error().take();
+        // The user may have been changed
+        self.set_authorization(health::Authorization::Unknown);
+    }
+
+    fn session_history(&self) -> Vec<session::Event> {
+        self.lock_session().history()
+    }
+
+    fn connection_security(&self) -> Option<session::Security> {
+        self.lock_session().security()
+    }
+
+    fn fatal_error(&self) -> Option<error::ErrorKind> {
+        self.lock_fatal_error().clone()
+    }
+
+    fn authorization(&self) -> health::Authorization {
+        *self
+            .authorization
+            .lock()
+            .expect("BUG: cannot lock authorization")
+    }
+
+    fn address_stats(&self) -> Vec<resolver::AddressStats> {
+        self.resolver.stats()
     }
 }
 
+/// Client is shown under its display name (full URL is available in its descriptor)
 impl fmt::Display for StratumClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let connection_details = self.connection_details();
-        write!(
-            f,
-            "{}://{}@{}",
-            connection_details.protocol, connection_details.host, connection_details.user
-        )
+        write!(f, "{}", self.client_name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    fn solution() -> work::Solution {
+        (&test_utils::TEST_BLOCKS[0]).into()
+    }
+
+    #[test]
+    fn test_solution_queue() {
+        let mut queue = SolutionQueue::default();
+        for seq_num in 0..SolutionQueue::MAX_LENGTH as u32 {
+            assert_eq!(queue.push(solution(), seq_num), 0);
+        }
+        // the server does not acknowledge anything and only the newest solutions are kept
+        let last_seq_num = SolutionQueue::MAX_LENGTH as u32;
+        assert_eq!(queue.push(solution(), last_seq_num), 1);
+        assert_eq!(queue.solutions.len(), SolutionQueue::MAX_LENGTH);
+
+        // acknowledgement of the dropped solution does not take any newer solution
+        assert!(queue.pop_acknowledged(0).is_none());
+        let (_, seq_num) = queue.pop_acknowledged(1).expect("BUG: missing solution");
+        assert_eq!(seq_num, 1);
+        assert!(queue.pop_acknowledged(1).is_none());
+
+        assert_eq!(queue.clear(), SolutionQueue::MAX_LENGTH - 1);
+        assert!(queue.pop_acknowledged(last_seq_num).is_none());
     }
 }
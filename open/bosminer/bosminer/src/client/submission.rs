@@ -0,0 +1,307 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Bookkeeping of shares submitted to the pool which survives loss of responses. Every
+//! submission gets a locally generated key which is kept until a response is received or until
+//! the submission times out. Submissions without response are sent again under the same key
+//! when the pool resumes the session after reconnect so the share is accounted only once no
+//! matter how many times it has been transmitted. Submissions which never get any response are
+//! neither accepted nor rejected and they are accounted as unresolved instead. The number of
+//! submissions waiting for a response is bounded so that a pool which never responds cannot make
+//! the tracker hold solutions (and their jobs) without limit.
+
+use crate::stats;
+use crate::work;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time;
+
+/// Locally generated key which identifies all transmissions of one submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key(u64);
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    solution: work::Solution,
+    /// Time of the first transmission
+    time: time::Instant,
+    /// Number of transmissions
+    attempts: u32,
+}
+
+/// Submission resolved by the first response received for it
+#[derive(Debug)]
+pub struct Resolved {
+    pub solution: work::Solution,
+    /// The response may belong to any transmission of the submission
+    pub retransmitted: bool,
+    /// Time of the first transmission
+    pub time: time::Instant,
+}
+
+impl Resolved {
+    /// Time the pool has taken to respond or `None` when the response may belong to another
+    /// transmission than the first one
+    pub fn latency(&self, now: time::Instant) -> Option<time::Duration> {
+        if self.retransmitted {
+            return None;
+        }
+        now.checked_duration_since(self.time)
+    }
+}
+
+/// Accounting of submissions which is not covered by share counters
+#[derive(Debug, Default)]
+pub struct Counters {
+    /// Transmissions of already submitted shares to a resumed session
+    pub retransmitted: stats::CounterU64,
+    /// Submissions without any response (due to timeout or because the session has been lost)
+    pub unresolved: stats::CounterU64,
+    /// Time the pool takes to respond to submissions which have been transmitted only once
+    pub latency: stats::SubmitLatency,
+}
+
+/// Submissions waiting for a response
+#[derive(Debug)]
+pub struct Tracker {
+    next_key: u64,
+    timeout: time::Duration,
+    /// Maximal number of submissions waiting for a response
+    capacity: usize,
+    entries: BTreeMap<Key, Entry>,
+}
+
+impl Tracker {
+    /// Submission without response within this time is unresolved
+    pub const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(2 * 60);
+    /// Submissions over this limit push out the oldest ones which become unresolved
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(timeout: time::Duration) -> Self {
+        Self::with_capacity(timeout, Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(timeout: time::Duration, capacity: usize) -> Self {
+        assert!(capacity > 0, "BUG: submission tracker without capacity");
+        Self {
+            next_key: 0,
+            timeout,
+            capacity,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Register the first transmission of a new submission. When the tracker is full the oldest
+    /// submission is given up and accounted as unresolved.
+    pub fn submit(
+        &mut self,
+        solution: work::Solution,
+        now: time::Instant,
+        counters: &Counters,
+    ) -> Key {
+        while self.entries.len() >= self.capacity {
+            let oldest = *self
+                .entries
+                .keys()
+                .next()
+                .expect("BUG: full tracker without entries");
+            self.entries.remove(&oldest);
+            counters.unresolved.inc();
+        }
+        let key = Key(self.next_key);
+        self.next_key += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                solution,
+                time: now,
+                attempts: 1,
+            },
+        );
+        key
+    }
+
+    /// Resolve the submission by a response. Only the first response of all transmissions
+    /// resolves the submission, the other ones (or responses received after timeout) return
+    /// `None` and they must not be accounted.
+    pub fn resolve(&mut self, key: Key) -> Option<Resolved> {
+        self.entries.remove(&key).map(|entry| Resolved {
+            solution: entry.solution,
+            retransmitted: entry.attempts > 1,
+            time: entry.time,
+        })
+    }
+
+    /// Return all submissions without response in the order of their submission so that they
+    /// can be transmitted again to the resumed session
+    pub fn retransmit(&mut self, counters: &Counters) -> Vec<(Key, work::Solution)> {
+        self.entries
+            .iter_mut()
+            .map(|(key, entry)| {
+                entry.attempts += 1;
+                counters.retransmitted.inc();
+                (*key, entry.solution.clone())
+            })
+            .collect()
+    }
+
+    /// Give up waiting for responses which have not been received in time and return the
+    /// number of such submissions
+    pub fn expire(&mut self, now: time::Instant, counters: &Counters) -> usize {
+        let timeout = self.timeout;
+        let count = self.entries.len();
+        self.entries.retain(|_, entry| {
+            now.checked_duration_since(entry.time)
+                .map_or(true, |age| age < timeout)
+        });
+        let expired = count - self.entries.len();
+        counters.unresolved.add(expired as u64);
+        expired
+    }
+
+    /// Give up all submissions when they cannot be transmitted anymore (the session has not
+    /// been resumed) and return their number
+    pub fn abandon(&mut self, counters: &Counters) -> usize {
+        let abandoned = self.entries.len();
+        self.entries.clear();
+        counters.unresolved.add(abandoned as u64);
+        abandoned
+    }
+
+    /// Number of submissions waiting for a response
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    fn solution(idx: usize) -> work::Solution {
+        (&test_utils::TEST_BLOCKS[idx]).into()
+    }
+
+    /// Resolve submission by a response and return `true` when it is accounted
+    fn respond(tracker: &mut Tracker, key: Key) -> bool {
+        tracker.resolve(key).is_some()
+    }
+
+    #[test]
+    fn test_response_loss_retransmitted() {
+        let now = time::Instant::now();
+        let counters = Counters::default();
+        let mut tracker = Tracker::default();
+
+        let first = tracker.submit(solution(0), now, &counters);
+        let second = tracker.submit(solution(1), now, &counters);
+        assert_ne!(first, second);
+
+        // connection has been lost before any response and the session has been resumed
+        let submissions = tracker.retransmit(&counters);
+        let keys: Vec<_> = submissions.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, vec![first, second]);
+        assert_eq!(submissions[0].1.nonce(), solution(0).nonce());
+        assert_eq!(*counters.retransmitted.take_snapshot(), 2);
+
+        // the shares are accounted only once even when the response to the lost transmission
+        // is received as well
+        let mut accepted = 0;
+        for &key in &[first, first, second, second] {
+            if respond(&mut tracker, key) {
+                accepted += 1;
+            }
+        }
+        assert_eq!(accepted, 2);
+        assert!(tracker.is_empty());
+        assert_eq!(*counters.unresolved.take_snapshot(), 0);
+    }
+
+    #[test]
+    fn test_response_loss_unresolved() {
+        let now = time::Instant::now();
+        let counters = Counters::default();
+        let mut tracker = Tracker::default();
+
+        let resolved = tracker.submit(solution(0), now, &counters);
+        let lost = tracker.submit(solution(1), now, &counters);
+        let submission = tracker.resolve(resolved).expect("BUG: missing submission");
+        assert!(!submission.retransmitted);
+        let latency = time::Duration::from_millis(150);
+        assert_eq!(submission.latency(now + latency), Some(latency));
+
+        // the session has not been resumed so the submission cannot be transmitted again
+        assert_eq!(tracker.abandon(&counters), 1);
+        assert!(!respond(&mut tracker, lost));
+        assert_eq!(*counters.retransmitted.take_snapshot(), 0);
+        assert_eq!(*counters.unresolved.take_snapshot(), 1);
+
+        // no response is received over a live connection
+        let timeout = Tracker::DEFAULT_TIMEOUT;
+        let late = tracker.submit(solution(2), now, &counters);
+        assert_eq!(
+            tracker.expire(now + timeout - time::Duration::from_millis(1), &counters),
+            0
+        );
+        assert_eq!(tracker.expire(now + timeout, &counters), 1);
+        assert!(!respond(&mut tracker, late));
+        assert_eq!(*counters.unresolved.take_snapshot(), 2);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let now = time::Instant::now();
+        let counters = Counters::default();
+        let capacity = 2;
+        let mut tracker = Tracker::with_capacity(Tracker::DEFAULT_TIMEOUT, capacity);
+
+        let oldest = tracker.submit(solution(0), now, &counters);
+        let keys: Vec<_> = (0..3)
+            .map(|_| tracker.submit(solution(1), now, &counters))
+            .collect();
+        // the pool does not respond at all and only the newest submissions are kept
+        assert_eq!(tracker.len(), capacity);
+        assert_eq!(*counters.unresolved.take_snapshot(), 2);
+        assert!(!respond(&mut tracker, oldest));
+        assert!(!respond(&mut tracker, keys[0]));
+        assert!(respond(&mut tracker, keys[1]));
+        assert!(respond(&mut tracker, keys[2]));
+    }
+}
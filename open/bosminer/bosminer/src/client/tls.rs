@@ -0,0 +1,632 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! TLS transport of stratum connections. The TCP stream of pools with `stratum+ssl` or
+//! `stratum2+ssl` URL is wrapped in a TLS session before the stratum protocol is started on top
+//! of it. The host name of the URL is sent as SNI and the server certificate is verified against
+//! it. Failed verification is reported as a fatal error so the client is not reconnected over
+//! and over again and the failover can switch to another pool instead.
+//! Security parameters of the established TLS session are recorded in the session history.
+
+use ii_logging::macros::*;
+
+use crate::client::session;
+use crate::error;
+
+use bosminer_config::ClientDescriptor;
+
+use ii_async_compat::prelude::*;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{self, Session as _};
+use tokio_rustls::webpki;
+use tokio_util::codec::{Framed as CodecFramed, FramedParts};
+
+use chrono::{DateTime, Utc};
+use ring::digest;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll};
+use std::time;
+
+/// Stratum frames transported over plain TCP or TLS stream
+pub type Framed<C> = CodecFramed<Stream, C>;
+
+/// Settings of TLS connection to the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Path to PEM file with trusted CA certificates (built-in root certificates are used when
+    /// it is missing)
+    pub ca_cert: Option<String>,
+    /// Accept any server certificate
+    pub insecure: bool,
+}
+
+impl Settings {
+    /// TLS settings of the client or `None` when its protocol is not wrapped in TLS
+    pub fn from_descriptor(descriptor: &ClientDescriptor) -> Option<Self> {
+        if descriptor.protocol.is_tls() {
+            Some(Self {
+                ca_cert: descriptor.ca_cert.clone(),
+                insecure: descriptor.insecure,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn client_config(&self) -> error::Result<(rustls::ClientConfig, Arc<Verifier>)> {
+        let mut config = rustls::ClientConfig::new();
+        match &self.ca_cert {
+            Some(path) => {
+                let file = fs::File::open(path).map_err(|e| {
+                    error::ErrorKind::Certificate(format!(
+                        "cannot open CA certificate '{}': {}",
+                        path, e
+                    ))
+                })?;
+                match config
+                    .root_store
+                    .add_pem_file(&mut io::BufReader::new(file))
+                {
+                    Ok((valid, _)) if valid > 0 => {}
+                    _ => Err(error::ErrorKind::Certificate(format!(
+                        "no valid CA certificate in '{}'",
+                        path
+                    )))?,
+                }
+            }
+            None if self.insecure => {}
+            None => config
+                .root_store
+                .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS),
+        }
+        let verifier = Arc::new(Verifier {
+            insecure: self.insecure,
+            pinned: self.ca_cert.is_some(),
+            pin_matched: StdMutex::new(None),
+        });
+        config
+            .dangerous()
+            .set_certificate_verifier(verifier.clone());
+        Ok((config, verifier))
+    }
+}
+
+/// Verifier of the server certificate which records whether the certificate has been issued by
+/// the configured CA certificate. Pools configured as `insecure` accept any server certificate
+/// but the result of the verification against the CA certificate is still recorded.
+struct Verifier {
+    insecure: bool,
+    /// The CA certificate is configured for the pool
+    pinned: bool,
+    pin_matched: StdMutex<Option<bool>>,
+}
+
+impl Verifier {
+    fn pin_matched(&self) -> Option<bool> {
+        *self
+            .pin_matched
+            .lock()
+            .expect("BUG: cannot lock pin result")
+    }
+}
+
+impl rustls::ServerCertVerifier for Verifier {
+    fn verify_server_cert(
+        &self,
+        roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        dns_name: webpki::DNSNameRef,
+        ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        if self.insecure && !self.pinned {
+            return Ok(rustls::ServerCertVerified::assertion());
+        }
+        let result = rustls::WebPKIVerifier::new().verify_server_cert(
+            roots,
+            presented_certs,
+            dns_name,
+            ocsp_response,
+        );
+        if self.pinned {
+            *self
+                .pin_matched
+                .lock()
+                .expect("BUG: cannot lock pin result") = Some(result.is_ok());
+        }
+        if self.insecure {
+            Ok(rustls::ServerCertVerified::assertion())
+        } else {
+            result
+        }
+    }
+}
+
+/// Parse validity period (`notBefore` and `notAfter`) of DER encoded X.509 certificate
+fn parse_validity(der: &[u8]) -> Option<(time::SystemTime, time::SystemTime)> {
+    let read_time = |reader: yasna::BERReader<'_, '_>| -> yasna::ASN1Result<time::SystemTime> {
+        let time: DateTime<Utc> = if reader.lookahead_tag()? == yasna::tags::TAG_UTCTIME {
+            *reader.read_utctime()?.datetime()
+        } else {
+            *reader.read_generalized_time()?.datetime()
+        };
+        Ok(time.into())
+    };
+    yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            let validity = reader.next().read_sequence(|reader| {
+                // version
+                reader.read_optional(|reader| {
+                    reader.read_tagged(yasna::Tag::context(0), |reader| reader.read_der())
+                })?;
+                // serial number, signature algorithm and issuer
+                for _ in 0..3 {
+                    reader.next().read_der()?;
+                }
+                let validity = reader.next().read_sequence(|reader| {
+                    let not_before = read_time(reader.next())?;
+                    let not_after = read_time(reader.next())?;
+                    Ok((not_before, not_after))
+                })?;
+                // subject, public key and optional identifiers and extensions
+                while reader.read_optional(|reader| reader.read_der())?.is_some() {}
+                Ok(validity)
+            })?;
+            // signature algorithm and signature
+            reader.next().read_der()?;
+            reader.next().read_der()?;
+            Ok(validity)
+        })
+    })
+    .ok()
+}
+
+/// Security parameters of the established TLS `session`
+fn session_security(
+    session: &rustls::ClientSession,
+    verifier: &Verifier,
+) -> Option<session::Security> {
+    let certificate = session.get_peer_certificates()?.into_iter().next()?;
+    let (valid_from, not_valid_after) = match parse_validity(&certificate.0) {
+        Some(validity) => validity,
+        None => {
+            warn!("TLS: cannot parse validity of the server certificate");
+            return None;
+        }
+    };
+    Some(session::Security {
+        protocol: format!("{:?}", session.get_protocol_version()?),
+        cipher: format!("{:?}", session.get_negotiated_ciphersuite()?.suite),
+        fingerprint: hex::encode(digest::digest(&digest::SHA256, &certificate.0)),
+        valid_from,
+        not_valid_after,
+        pin_matched: verifier.pin_matched(),
+    })
+}
+
+/// Convert failed TLS handshake to an error which is fatal when the server certificate has not
+/// been accepted (reconnecting does not help until the settings are changed)
+fn handshake_error(e: io::Error) -> error::Error {
+    let is_certificate_error = e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::TLSError>())
+        .map_or(false, |tls_error| match tls_error {
+            rustls::TLSError::WebPKIError(_) | rustls::TLSError::NoCertificatesPresented => true,
+            _ => false,
+        });
+    if is_certificate_error {
+        error::ErrorKind::Certificate(e.to_string()).into()
+    } else {
+        e.into()
+    }
+}
+
+/// Connection to the remote server which is either plain TCP or TLS session on top of it
+pub enum Stream {
+    Tcp(TcpStream),
+    /// TLS session together with its security parameters
+    Tls(
+        Box<tokio_rustls::client::TlsStream<TcpStream>>,
+        Option<session::Security>,
+    ),
+}
+
+impl Stream {
+    /// Wrap the TCP `stream` connected to the `host` in a TLS session when there are TLS
+    /// `settings`. The host has to be a DNS name because it is used for SNI and for verification
+    /// of the server certificate.
+    pub async fn connect(
+        host: &str,
+        stream: TcpStream,
+        settings: Option<&Settings>,
+    ) -> error::Result<Self> {
+        let settings = match settings {
+            Some(settings) => settings,
+            None => return Ok(Stream::Tcp(stream)),
+        };
+        let dns_name = webpki::DNSNameRef::try_from_ascii_str(host).map_err(|_| {
+            error::ErrorKind::Address(format!(
+                "TLS connection requires host name instead of '{}'",
+                host
+            ))
+        })?;
+        let (config, verifier) = settings.client_config()?;
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+        let stream = connector
+            .connect(dns_name, stream)
+            .await
+            .map_err(handshake_error)?;
+        let security = session_security(stream.get_ref().1, &verifier);
+        Ok(Stream::Tls(Box::new(stream), security))
+    }
+
+    /// Replace the TCP stream of `framed` connection keeping the codec with all buffered data
+    pub fn from_tcp_framed<C>(framed: CodecFramed<TcpStream, C>) -> Framed<C> {
+        let parts = framed.into_parts();
+        let mut stream_parts = FramedParts::new(Stream::Tcp(parts.io), parts.codec);
+        stream_parts.read_buf = parts.read_buf;
+        stream_parts.write_buf = parts.write_buf;
+        CodecFramed::from_parts(stream_parts)
+    }
+
+    #[inline]
+    pub fn is_tls(&self) -> bool {
+        match self {
+            Stream::Tcp(_) => false,
+            Stream::Tls(..) => true,
+        }
+    }
+
+    /// Security parameters of the TLS session
+    pub fn security(&self) -> Option<session::Security> {
+        match self {
+            Stream::Tcp(_) => None,
+            Stream::Tls(_, security) => security.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Tcp(stream) => f.debug_tuple("Tcp").field(stream).finish(),
+            Stream::Tls(stream, _) => f.debug_tuple("Tls").field(stream.get_ref().0).finish(),
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Stream::Tls(stream, _) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Stream::Tls(stream, _) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Stream::Tls(stream, _) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Stream::Tls(stream, _) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::error::Classify;
+
+    const MESSAGE: &[u8] = b"{\"id\":1}\n";
+
+    /// Self-signed certificate of the test server issued for `localhost`
+    pub(crate) struct TestCertificate {
+        der: Vec<u8>,
+        key_der: Vec<u8>,
+        pem_path: std::path::PathBuf,
+    }
+
+    impl TestCertificate {
+        pub fn new(name: &str) -> Self {
+            let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+                .expect("BUG: cannot generate certificate");
+            let pem_path = std::env::temp_dir().join(format!(
+                "bosminer-tls-{}-{}.pem",
+                std::process::id(),
+                name
+            ));
+            fs::write(
+                &pem_path,
+                cert.serialize_pem()
+                    .expect("BUG: cannot serialize certificate"),
+            )
+            .expect("BUG: cannot write certificate");
+            Self {
+                der: cert
+                    .serialize_der()
+                    .expect("BUG: cannot serialize certificate"),
+                key_der: cert.serialize_private_key_der(),
+                pem_path,
+            }
+        }
+
+        fn ca_cert(&self) -> Option<String> {
+            Some(self.pem_path.to_string_lossy().into_owned())
+        }
+    }
+
+    impl Drop for TestCertificate {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.pem_path);
+        }
+    }
+
+    /// Local TLS server with the self-signed `certificate` which echoes a single message
+    pub(crate) async fn run_server(certificate: &TestCertificate) -> std::net::SocketAddr {
+        let mut config = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+        config
+            .set_single_cert(
+                vec![rustls::Certificate(certificate.der.clone())],
+                rustls::PrivateKey(certificate.key_der.clone()),
+            )
+            .expect("BUG: invalid certificate");
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+        let mut listener = tokio::net::TcpListener::bind("localhost:0")
+            .await
+            .expect("BUG: cannot bind");
+        let address = listener.local_addr().expect("BUG: missing local address");
+
+        tokio::spawn(async move {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .expect("BUG: cannot accept connection");
+            // the handshake fails when the client rejects the certificate
+            if let Ok(mut stream) = acceptor.accept(stream).await {
+                let mut buf = vec![0; MESSAGE.len()];
+                if stream.read_exact(&mut buf).await.is_ok() {
+                    let _ = stream.write_all(&buf).await;
+                    let _ = stream.flush().await;
+                }
+            }
+        });
+        address
+    }
+
+    async fn connect(
+        certificate: &TestCertificate,
+        host: &str,
+        settings: Settings,
+    ) -> error::Result<Stream> {
+        let address = run_server(certificate).await;
+        let stream = TcpStream::connect(address)
+            .await
+            .expect("BUG: cannot connect");
+        Stream::connect(host, stream, Some(&settings)).await
+    }
+
+    async fn echo(mut stream: Stream) {
+        assert!(stream.is_tls());
+        stream.write_all(MESSAGE).await.expect("BUG: cannot write");
+        stream.flush().await.expect("BUG: cannot flush");
+        let mut buf = vec![0; MESSAGE.len()];
+        stream.read_exact(&mut buf).await.expect("BUG: cannot read");
+        assert_eq!(buf, MESSAGE);
+    }
+
+    /// Validity of certificates generated by `rcgen` (1975-01-01 encoded as UTC time and
+    /// 4096-01-01 encoded as generalized time)
+    const VALID_FROM: u64 = 157_766_400;
+    const NOT_VALID_AFTER: u64 = 67_090_118_400;
+
+    fn assert_security(stream: &Stream, certificate: &TestCertificate, pin_matched: Option<bool>) {
+        let security = stream
+            .security()
+            .expect("BUG: missing TLS security parameters");
+        assert_eq!(security.protocol, "TLSv1_3");
+        assert!(security.cipher.starts_with("TLS13_"));
+        assert_eq!(
+            security.fingerprint,
+            hex::encode(digest::digest(&digest::SHA256, &certificate.der))
+        );
+        assert_eq!(
+            security.valid_from,
+            time::UNIX_EPOCH + time::Duration::from_secs(VALID_FROM)
+        );
+        assert_eq!(
+            security.not_valid_after,
+            time::UNIX_EPOCH + time::Duration::from_secs(NOT_VALID_AFTER)
+        );
+        assert_eq!(security.pin_matched, pin_matched);
+    }
+
+    fn assert_certificate_error(result: error::Result<Stream>) {
+        let e = result.expect_err("BUG: certificate has been accepted");
+        match e.kind() {
+            error::ErrorKind::Certificate(_) => {}
+            kind => panic!("BUG: unexpected error {:?}", kind),
+        }
+        // the client must not be reconnected
+        assert!(!e.is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_ca_cert() {
+        let certificate = TestCertificate::new("trusted");
+        let settings = Settings {
+            ca_cert: certificate.ca_cert(),
+            insecure: false,
+        };
+        let stream = connect(&certificate, "localhost", settings)
+            .await
+            .expect("BUG: cannot connect to TLS server");
+        assert_security(&stream, &certificate, Some(true));
+        echo(stream).await;
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_certificate() {
+        let certificate = TestCertificate::new("untrusted");
+        // self-signed certificate is not issued by any of the built-in root certificates
+        let settings = Settings {
+            ca_cert: None,
+            insecure: false,
+        };
+        assert_certificate_error(connect(&certificate, "localhost", settings).await);
+    }
+
+    #[tokio::test]
+    async fn test_host_mismatch() {
+        let certificate = TestCertificate::new("mismatch");
+        let settings = Settings {
+            ca_cert: certificate.ca_cert(),
+            insecure: false,
+        };
+        // the certificate is trusted but it is not issued for the host of the URL
+        assert_certificate_error(connect(&certificate, "pool.example.com", settings).await);
+    }
+
+    #[tokio::test]
+    async fn test_insecure() {
+        let certificate = TestCertificate::new("insecure");
+        let settings = Settings {
+            ca_cert: None,
+            insecure: true,
+        };
+        let stream = connect(&certificate, "pool.example.com", settings)
+            .await
+            .expect("BUG: cannot connect to TLS server");
+        // there is no pinned CA certificate
+        assert_security(&stream, &certificate, None);
+        echo(stream).await;
+    }
+
+    #[tokio::test]
+    async fn test_insecure_pin_mismatch() {
+        let certificate = TestCertificate::new("pin-mismatch");
+        let other_certificate = TestCertificate::new("pin-other");
+        let settings = Settings {
+            ca_cert: other_certificate.ca_cert(),
+            insecure: true,
+        };
+        // the certificate is accepted but it has not been issued by the pinned CA certificate
+        let stream = connect(&certificate, "localhost", settings)
+            .await
+            .expect("BUG: cannot connect to TLS server");
+        assert_security(&stream, &certificate, Some(false));
+        echo(stream).await;
+    }
+
+    #[test]
+    fn test_parse_validity() {
+        let certificate = TestCertificate::new("validity");
+        assert_eq!(
+            parse_validity(&certificate.der),
+            Some((
+                time::UNIX_EPOCH + time::Duration::from_secs(VALID_FROM),
+                time::UNIX_EPOCH + time::Duration::from_secs(NOT_VALID_AFTER)
+            ))
+        );
+        assert_eq!(parse_validity(&certificate.der[..100]), None);
+    }
+
+    #[tokio::test]
+    async fn test_plain() {
+        let certificate = TestCertificate::new("plain");
+        let address = run_server(&certificate).await;
+        let stream = TcpStream::connect(address)
+            .await
+            .expect("BUG: cannot connect");
+        let stream = Stream::connect("localhost", stream, None)
+            .await
+            .expect("BUG: cannot wrap TCP stream");
+        assert!(!stream.is_tls());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_settings() {
+        let stream = |address| async move {
+            TcpStream::connect(address)
+                .await
+                .expect("BUG: cannot connect")
+        };
+        let certificate = TestCertificate::new("invalid");
+        let address = run_server(&certificate).await;
+
+        // IP address cannot be used for SNI
+        let result = Stream::connect(
+            "127.0.0.1",
+            stream(address).await,
+            Some(&Settings {
+                ca_cert: None,
+                insecure: true,
+            }),
+        )
+        .await;
+        match result
+            .expect_err("BUG: IP address has been accepted")
+            .kind()
+        {
+            error::ErrorKind::Address(_) => {}
+            kind => panic!("BUG: unexpected error {:?}", kind),
+        }
+
+        let address = run_server(&certificate).await;
+        let result = Stream::connect(
+            "localhost",
+            stream(address).await,
+            Some(&Settings {
+                ca_cert: Some("/nonexistent/ca.pem".to_string()),
+                insecure: false,
+            }),
+        )
+        .await;
+        assert_certificate_error(result);
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Source of time for the whole core. All code which measures time has to use `now` and
+//! `system_time` instead of `Instant::now` and `SystemTime::now` so the core can be also run
+//! in simulation where time is virtual. Virtual time follows the paused tokio clock, which jumps
+//! to the next timer whenever all tasks are idle, so a day of mining is simulated in seconds.
+
+use ii_async_compat::tokio;
+
+use std::cell::Cell;
+use std::time;
+
+/// Virtual instants are shifted ahead of the real ones by this amount to make instants obtained
+/// directly from the system distinguishable in simulation
+pub const SIMULATION_SHIFT: time::Duration = time::Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy)]
+struct Simulation {
+    /// Wall clock time corresponding to `start`
+    epoch: time::SystemTime,
+    /// Virtual instant when the simulation has been entered
+    start: time::Instant,
+}
+
+thread_local! {
+    /// Simulation is bound to the thread running tokio basic scheduler with paused clock
+    static SIMULATION: Cell<Option<Simulation>> = Cell::new(None);
+}
+
+fn simulation() -> Option<Simulation> {
+    SIMULATION.with(|simulation| simulation.get())
+}
+
+/// Current monotonic time
+#[inline]
+pub fn now() -> time::Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+/// Current wall clock time (it starts at the epoch passed to `enter_simulation` in simulation)
+pub fn system_time() -> time::SystemTime {
+    match simulation() {
+        Some(simulation) => simulation.epoch + now().duration_since(simulation.start),
+        None => time::SystemTime::now(),
+    }
+}
+
+#[inline]
+pub fn is_simulated() -> bool {
+    simulation().is_some()
+}
+
+/// Leaves simulation mode when dropped
+#[cfg(test)]
+#[derive(Debug)]
+pub struct SimulationGuard(());
+
+#[cfg(test)]
+impl Drop for SimulationGuard {
+    fn drop(&mut self) {
+        SIMULATION.with(|simulation| simulation.set(None));
+    }
+}
+
+/// Switch the current thread to simulation with virtual wall clock starting at `epoch`.
+/// It has to be called from a task running on tokio basic scheduler before any part of the core
+/// is created. The `epoch` has to lie in the past to detect leaked system time (see
+/// `check_system_time`). Pausing of the tokio clock is available only in tests.
+#[cfg(test)]
+pub async fn enter_simulation(epoch: time::SystemTime) -> SimulationGuard {
+    assert!(!is_simulated(), "BUG: simulation has been already entered");
+    tokio::time::pause();
+    tokio::time::advance(SIMULATION_SHIFT).await;
+
+    let start = now();
+    SIMULATION.with(|simulation| simulation.set(Some(Simulation { epoch, start })));
+    SimulationGuard(())
+}
+
+/// Debug assertion which panics when the instant has not been obtained from this module in
+/// simulation (i.e. somebody called `Instant::now` directly)
+#[inline]
+pub fn check_instant(instant: time::Instant) {
+    if cfg!(debug_assertions) {
+        if let Some(simulation) = simulation() {
+            assert!(
+                instant >= simulation.start,
+                "BUG: real time instant leaked into simulation"
+            );
+        }
+    }
+}
+
+/// Debug assertion which panics when the system time has not been obtained from this module in
+/// simulation (i.e. somebody called `SystemTime::now` directly)
+#[inline]
+pub fn check_system_time(system_time: time::SystemTime) {
+    if cfg!(debug_assertions) {
+        if let Some(simulation) = simulation() {
+            assert!(
+                system_time >= simulation.epoch && system_time <= self::system_time(),
+                "BUG: real system time leaked into simulation"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 2020-01-01 00:00:00 UTC
+    const TEST_EPOCH: u64 = 1_577_836_800;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("BUG: cannot build runtime")
+    }
+
+    #[test]
+    fn test_virtual_time() {
+        let epoch = time::UNIX_EPOCH + time::Duration::from_secs(TEST_EPOCH);
+        runtime().block_on(async move {
+            let guard = enter_simulation(epoch).await;
+            let real_start = time::Instant::now();
+            let start = now();
+            assert_eq!(system_time(), epoch);
+
+            // the clock is advanced immediately because there is nothing else to do (the timer
+            // has millisecond resolution)
+            let day = time::Duration::from_secs(24 * 60 * 60);
+            tokio::time::delay_for(day).await;
+            let elapsed = now().duration_since(start);
+            assert!(elapsed >= day && elapsed < day + time::Duration::from_millis(10));
+            assert_eq!(system_time(), epoch + elapsed);
+            assert!(real_start.elapsed() < time::Duration::from_secs(60));
+
+            check_instant(now());
+            check_system_time(system_time());
+
+            drop(guard);
+            assert!(!is_simulated());
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "real time instant leaked into simulation")]
+    fn test_leaked_instant() {
+        let epoch = time::UNIX_EPOCH + time::Duration::from_secs(TEST_EPOCH);
+        runtime().block_on(async move {
+            let _guard = enter_simulation(epoch).await;
+            check_instant(time::Instant::now());
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "real system time leaked into simulation")]
+    fn test_leaked_system_time() {
+        let epoch = time::UNIX_EPOCH + time::Duration::from_secs(TEST_EPOCH);
+        runtime().block_on(async move {
+            let _guard = enter_simulation(epoch).await;
+            check_system_time(time::SystemTime::now());
+        });
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! CPU solver scanning nonces on the host processor with the scalar SHA256d implementation.
+//!
+//! **Not for production.** The solver exists only for functional testing of the whole share path
+//! (e.g. with a testnet pool) on machines without any mining hardware. A few MH/s it achieves are
+//! enough for shares of difficulty 1.
+
+use ii_logging::macros::*;
+
+use crate::client;
+use crate::clock;
+use crate::error;
+use crate::hal;
+use crate::node;
+use crate::stats;
+use crate::work;
+
+use bosminer_config::ClientDescriptor;
+use bosminer_macros::WorkSolverNode;
+
+use ii_bitcoin::{HashTrait as _, MeetsTarget as _};
+
+use futures::executor::block_on;
+use ii_async_compat::{futures, tokio};
+use tokio::task;
+
+use async_trait::async_trait;
+
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time;
+
+pub const ASYNC_LOGGER_DRAIN_CHANNEL_SIZE: usize = 128;
+
+/// Number of nonces scanned between checks whether the assignment should be replaced
+const BATCH_SIZE: u32 = 4096;
+/// Maximal time spent on one assignment so that a new job is picked up quickly
+const ASSIGNMENT_TIME: time::Duration = time::Duration::from_millis(500);
+/// Position of the nonce in serialized block header
+const NONCE_OFFSET: usize = ii_bitcoin::BLOCK_HEADER_SIZE - mem::size_of::<u32>();
+
+/// Default number of threads leaves at least half of the cores to the rest of the system
+pub fn default_thread_count() -> usize {
+    let cores = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if cores < 2 {
+        1
+    } else {
+        cores as usize / 2
+    }
+}
+
+/// Serialized block header of one assignment which is hashed with different nonces
+struct Header {
+    bytes: [u8; ii_bitcoin::BLOCK_HEADER_SIZE],
+    /// The first nonce of the scan
+    nonce_start: u32,
+}
+
+impl Header {
+    fn new(work: &work::Assignment) -> Self {
+        let job = work.dyn_job();
+        let header = ii_bitcoin::BlockHeader {
+            version: work.midstates[0].version,
+            previous_hash: job.previous_hash().into_inner(),
+            merkle_root: job.merkle_root().into_inner(),
+            time: work.ntime,
+            bits: work.bits(),
+            nonce: 0,
+        };
+        Self {
+            bytes: header.into_bytes(),
+            nonce_start: work.nonce_start(),
+        }
+    }
+
+    fn hash(&mut self, nonce: u32) -> ii_bitcoin::DHash {
+        self.bytes[NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
+        ii_bitcoin::DHash::hash(&self.bytes)
+    }
+
+    /// Return all nonces from the range of the scan starting at position `first` with `count`
+    /// nonces whose hash meets the `target`. The scan wraps around when it does not start at zero.
+    fn scan(&mut self, first: u32, count: u32, target: &ii_bitcoin::Target) -> Vec<u32> {
+        let last = first.saturating_add(count.saturating_sub(1));
+        let nonce_start = self.nonce_start;
+        (first..=last)
+            .map(|position| nonce_start.wrapping_add(position))
+            .filter(|nonce| self.hash(*nonce).meets(target))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Solution {
+    nonce: u32,
+    target: ii_bitcoin::Target,
+}
+
+impl hal::BackendSolution for Solution {
+    #[inline]
+    fn nonce(&self) -> u32 {
+        self.nonce
+    }
+
+    #[inline]
+    fn midstate_idx(&self) -> usize {
+        // the solver works with one midstate only
+        0
+    }
+
+    #[inline]
+    fn solution_idx(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn target(&self) -> &ii_bitcoin::Target {
+        &self.target
+    }
+}
+
+/// Single work solver shared by all threads scanning nonces
+#[derive(Debug, WorkSolverNode)]
+pub struct Backend {
+    #[member_work_solver_stats]
+    work_solver_stats: stats::BasicWorkSolver,
+    work_generator: StdMutex<work::Generator>,
+    solution_sender: work::SolutionSender,
+    /// Target of the solutions reported to the frontend
+    target: ii_bitcoin::Target,
+}
+
+impl Backend {
+    fn new(
+        mut work_generator: work::Generator,
+        solution_sender: work::SolutionSender,
+        target: ii_bitcoin::Target,
+    ) -> Self {
+        // nonces are scanned in software so the start can be anywhere
+        work_generator.set_nonce_offset(true);
+        Self {
+            work_solver_stats: Default::default(),
+            work_generator: StdMutex::new(work_generator),
+            solution_sender,
+            target,
+        }
+    }
+
+    /// Each thread takes its own assignment so the threads never scan the same nonces
+    fn next_work(&self) -> Option<work::Assignment> {
+        let mut work_generator = self
+            .work_generator
+            .lock()
+            .expect("BUG: cannot lock work generator");
+        let work = block_on(work_generator.generate())?;
+        block_on(work_generator.account_dispatched(&work));
+        Some(work)
+    }
+
+    /// Scan assignments until there is no more work
+    fn mine(&self) {
+        while let Some(work) = self.next_work() {
+            let work = Arc::new(work);
+            let mut header = Header::new(&work);
+            let start = clock::now();
+            let mut first = 0;
+            loop {
+                for nonce in header.scan(first, BATCH_SIZE, &self.target) {
+                    self.solution_sender.send(work::Solution::new(
+                        work.clone(),
+                        Solution {
+                            nonce,
+                            target: self.target,
+                        },
+                        None,
+                    ));
+                }
+                first = match first.checked_add(BATCH_SIZE) {
+                    Some(next) if clock::now().duration_since(start) < ASSIGNMENT_TIME => next,
+                    // the nonce range is exhausted or the assignment is too old
+                    _ => break,
+                };
+            }
+        }
+    }
+
+    fn enable(self: Arc<Self>, thread_count: usize) {
+        for _ in 0..thread_count {
+            let backend = self.clone();
+            // scanning nonces is blocking so it cannot run on the regular threadpool
+            task::spawn_blocking(move || backend.mine());
+        }
+    }
+}
+
+#[async_trait]
+impl node::WorkSolver for Backend {
+    async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
+        None
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CPU solver")
+    }
+}
+
+#[async_trait]
+impl hal::Backend for Backend {
+    type Type = Self;
+    type Config = Config;
+
+    const DEFAULT_HASHRATE_INTERVAL: time::Duration = time::Duration::from_secs(60);
+    const JOB_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+    fn create(config: &mut Config) -> hal::WorkNode<Self> {
+        let target = config.target;
+        node::WorkSolverType::WorkSolver(Box::new(move |work_generator, solution_sender| {
+            Self::new(work_generator, solution_sender, target)
+        }))
+    }
+
+    async fn init_work_hub(
+        _config: Config,
+        _work_hub: work::SolverBuilder<Self>,
+    ) -> error::Result<hal::FrontendConfig> {
+        panic!("BUG: called `init_work_hub`");
+    }
+
+    async fn init_work_solver(
+        config: Config,
+        work_solver: Arc<Self>,
+    ) -> error::Result<hal::FrontendConfig> {
+        warn!(
+            "CPU solver is intended only for functional testing, do not use it in production \
+             (threads={})",
+            config.thread_count
+        );
+        work_solver.enable(config.thread_count);
+
+        // Create initial client configuration
+        config.init_client().await;
+
+        Ok(hal::FrontendConfig {
+            cgminer_custom_commands: None,
+            api: Default::default(),
+        })
+    }
+}
+
+/// Configuration of the CPU solver
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Number of threads scanning nonces
+    pub thread_count: usize,
+    /// Target of the solutions reported to the frontend (difficulty 1 by default)
+    pub target: ii_bitcoin::Target,
+    client_descriptor: Option<ClientDescriptor>,
+    client_manager: Option<client::Manager>,
+}
+
+impl Config {
+    pub fn new(client_descriptor: ClientDescriptor) -> Self {
+        Self {
+            thread_count: default_thread_count(),
+            target: Default::default(),
+            client_descriptor: Some(client_descriptor),
+            client_manager: None,
+        }
+    }
+
+    async fn init_client(self) {
+        if let Some(client_descriptor) = self.client_descriptor {
+            let client_manager = self.client_manager.expect("BUG: missing client manager");
+            let group = client_manager.create_or_get_default_group().await;
+
+            group
+                .push_client(client::Handle::new(
+                    client_descriptor,
+                    client_manager.services(),
+                    None,
+                    None,
+                ))
+                .await;
+        }
+    }
+}
+
+impl hal::BackendConfig for Config {
+    #[inline]
+    fn midstate_count(&self) -> usize {
+        1
+    }
+
+    fn set_client_manager(&mut self, client_manager: client::Manager) {
+        self.client_manager.replace(client_manager);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend;
+    use crate::test_utils;
+
+    use futures::channel::mpsc;
+    use futures::stream::StreamExt;
+
+    use std::thread;
+
+    /// Target which is met by one of 256 hashes on average
+    const RELAXED_TARGET: &str = "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+    /// Generous limit for tests running in debug mode on a slow machine
+    const TIME_LIMIT: time::Duration = time::Duration::from_secs(10);
+
+    #[test]
+    fn test_known_nonce() {
+        for block in test_utils::TEST_BLOCKS.iter() {
+            let mut header = Header::new(&block.into());
+            assert_eq!(header.hash(block.nonce), block.hash);
+
+            // search the neighbourhood of the known nonce with difficulty 1 target
+            let start = time::Instant::now();
+            let nonces = header.scan(
+                block.nonce.saturating_sub(BATCH_SIZE),
+                2 * BATCH_SIZE,
+                &Default::default(),
+            );
+            assert!(start.elapsed() < TIME_LIMIT);
+            assert_eq!(nonces, vec![block.nonce]);
+        }
+    }
+
+    /// Scan starting at an offset finds the known nonce at the wrapped position and its solution
+    /// is verified with the actual nonce
+    #[test]
+    fn test_nonce_start() {
+        for block in test_utils::TEST_BLOCKS.iter() {
+            // the known nonce is found right after the scan wraps around
+            let nonce_start = block.nonce.wrapping_sub(BATCH_SIZE);
+            let work = work::Assignment::from(block).with_nonce_start(nonce_start);
+            let mut header = Header::new(&work);
+            let nonces = header.scan(0, 2 * BATCH_SIZE, &Default::default());
+            assert_eq!(nonces, vec![block.nonce]);
+
+            let solution = work::Solution::new(
+                work,
+                Solution {
+                    nonce: nonces[0],
+                    target: Default::default(),
+                },
+                None,
+            );
+            assert_eq!(*solution.hash(), block.hash);
+            assert!(solution.hash().meets(solution.backend_target()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mining() {
+        const SOLUTION_COUNT: usize = 8;
+
+        let target = ii_bitcoin::Target::from_hex(RELAXED_TARGET).expect("BUG: parse hex");
+        let (engine_sender, engine_receiver) = work::engine_channel(work::IgnoreEvents);
+        let _ = engine_sender.replace_engine_generator(Box::new(|job| {
+            Arc::new(work::engine::VersionRolling::new(job, 1))
+        }));
+        let (solution_sender, mut solution_receiver) = mpsc::unbounded();
+        let work_solver_builder = work::SolverBuilder::new(
+            Arc::new(crate::Frontend::new()),
+            Arc::new(backend::IgnoreHierarchy),
+            engine_receiver,
+            solution_sender,
+        );
+        let backend = work_solver_builder
+            .create_work_solver(|work_generator, solution_sender| {
+                Backend::new(work_generator, solution_sender, target)
+            })
+            .await;
+
+        engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[0]));
+        let miner = thread::spawn(move || backend.mine());
+
+        for _ in 0..SOLUTION_COUNT {
+            let solution = tokio::time::timeout(TIME_LIMIT, solution_receiver.next())
+                .await
+                .expect("BUG: no solution found in time")
+                .expect("BUG: solution channel closed");
+            assert!(solution.hash().meets(&target));
+            assert!(solution.hash().meets(solution.backend_target()));
+        }
+
+        // the solver stops when the work generator is closed
+        drop(engine_sender);
+        drop(work_solver_builder);
+        miner.join().expect("BUG: miner thread failed");
+    }
+}
@@ -23,41 +23,282 @@
 //! This module provides top level functionality to build the BOSminer core and use it to connect
 //! the frontend and hardware specific backend.
 
+use ii_logging::macros::*;
+use ii_logging::{FlushGuard, LoggingConfig};
+
 use crate::api;
 use crate::backend;
+use crate::client;
+use crate::error;
 use crate::hal::{self, BackendConfig as _};
 use crate::hub;
+use crate::job;
+use crate::progress;
+use crate::shutdown;
 use crate::stats;
+use crate::sync::event;
+#[cfg(all(feature = "systemd", unix))]
+use crate::systemd;
+use crate::work;
 
 use ii_async_compat::tokio;
+use tokio::sync::broadcast;
 
 use std::sync::Arc;
 
-pub async fn main<T: hal::Backend>(backend_config: T::Config, signature: String) {
-    let backend_registry = Arc::new(backend::Registry::new());
-    // Get frontend specific settings from backend config
-    let backend_info = backend_config.info();
+/// Number of work history events printed when the miner panics
+const CRASH_REPORT_EVENTS: usize = 32;
 
-    // Initialize hub core which manages all resources
-    let core = Arc::new(hub::Core::new(
-        backend_config.midstate_count(),
-        &backend_registry,
-        backend_info.clone(),
-    ));
+/// Panic hook as returned by `std::panic::take_hook`
+type PanicHook = Box<dyn Fn(&std::panic::PanicInfo<'_>) + Sync + Send + 'static>;
+
+/// Exit status of the miner binaries which allows init scripts and provisioning tools to tell
+/// the reason why the miner has not started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    /// Invalid command line or any other failure without its own code
+    Failure = 1,
+    /// Configuration cannot be parsed or it is invalid
+    Config = 2,
+    /// There is no pool to mine on
+    NoPools = 3,
+    /// The backend or its hardware cannot be initialized
+    BackendInit = 4,
+    /// No usable hardware has been detected
+    NoHardware = 5,
+}
+
+impl ExitCode {
+    /// Terminate the process. All loggers have to be flushed before.
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+/// Ownership of the global logger used by BOSminer
+pub enum LoggingMode {
+    /// BOSminer sets up its own logger with given configuration
+    OwnLogger(LoggingConfig),
+    /// BOSminer never sets up any logger and all records are routed to the logger installed by
+    /// the embedding application with the `log` facade
+    External,
+}
+
+/// Set up logging according to `mode`. Without the mode the logger is expected to be already
+/// configured by the caller. The returned guard flushes own logger when dropped.
+pub fn setup_logging(mode: Option<LoggingMode>) -> Option<FlushGuard> {
+    match mode? {
+        LoggingMode::OwnLogger(config) => Some(ii_logging::setup(config)),
+        LoggingMode::External => {
+            if ii_logging::try_set_logger_config(LoggingConfig::external()).is_none() {
+                warn!("Logger has been already instantiated and cannot be routed to external one");
+            }
+            None
+        }
+    }
+}
+
+/// Builder of the mining core connected to the backend `T`. It is the entry point for
+/// applications embedding BOSminer which provide their own control instead of the API server.
+pub struct Runner<T: hal::Backend> {
+    backend_config: T::Config,
+}
+
+impl<T: hal::Backend> Runner<T> {
+    pub fn new(backend_config: T::Config) -> Self {
+        Self { backend_config }
+    }
+
+    /// Initialize the backend and start all mining tasks. The logging has to be set up before.
+    pub async fn start(self) -> error::Result<RunningMiner> {
+        let backend_config = self.backend_config;
+        // Initialize hub core which manages all resources
+        let core = Arc::new(hub::Core::new(
+            backend_config.midstate_count(),
+            backend_config.network(),
+            &Arc::new(backend::Registry::new()),
+            backend_config.info(),
+            backend_config.digest(),
+            backend_config.work_history(),
+            backend_config.fan_out(),
+        ));
+        core.get_orphan_routing()
+            .set_policy(backend_config.solution_routing());
+        core.set_assignment_max_age(backend_config.assignment_max_age());
+        if let Some(dir) = backend_config.block_archive() {
+            core.set_block_archive_dir(&dir);
+        }
+        if let Some(config) = backend_config.solution_export() {
+            core.set_solution_export(&config);
+        }
+        if let Some(path) = backend_config.stats_persistence_path() {
+            core.set_stats_persistence(&path, backend_config.stats_persistence_interval());
+        }
+
+        // Create and initialize the backend
+        let frontend_config = core.build_backend::<T>(backend_config).await?;
+
+        tokio::spawn(core.clone().run());
+        // start statistics processing
+        tokio::spawn(stats::mining_task(
+            core.get_frontend().clone(),
+            T::DEFAULT_HASHRATE_INTERVAL,
+        ));
+
+        Ok(RunningMiner {
+            core,
+            frontend_config,
+        })
+    }
+}
+
+/// Handle to the miner started by `Runner`
+pub struct RunningMiner {
+    core: Arc<hub::Core>,
+    frontend_config: hal::FrontendConfig,
+}
 
-    // Create and initialize the backend
-    let frontend_config = core
-        .build_backend::<T>(backend_config)
-        .await
-        .expect("Backend initialization failed");
-
-    tokio::spawn(core.clone().run());
-    // start statistics processing
-    tokio::spawn(stats::mining_task(
-        core.frontend.clone(),
-        T::DEFAULT_HASHRATE_INTERVAL,
+impl RunningMiner {
+    pub async fn summary(&self) -> hub::CoreSummary {
+        self.core.summary().await
+    }
+
+    #[inline]
+    pub fn client_manager(&self) -> &client::Manager {
+        self.core.get_client_manager()
+    }
+
+    /// Stream of notifications about any change of client status
+    pub fn client_events(&self) -> event::Receiver {
+        self.core
+            .get_client_manager()
+            .subscribe_to_clients_status_changes()
+    }
+
+    #[inline]
+    pub fn progress(&self) -> &Arc<progress::Registry> {
+        self.core.get_progress()
+    }
+
+    #[inline]
+    pub fn work_history(&self) -> &Arc<work::history::Recorder> {
+        self.core.get_work_history()
+    }
+
+    /// Stream of new blocks seen in jobs of all clients
+    pub fn block_changes(&self) -> broadcast::Receiver<job::block::BlockChange> {
+        self.core.get_block_tracker().subscribe()
+    }
+
+    /// Tear down all clients and the backend and build them again with the same configuration.
+    /// The returned `hal::FrontendConfig` belongs to the new backend and its custom commands
+    /// replace the ones of the previous backend which must not be used anymore.
+    pub async fn restart(&self) -> error::Result<hal::FrontendConfig> {
+        self.core
+            .restart(self.core.get_shutdown_grace_period())
+            .await
+    }
+
+    /// Stop mining gracefully with the grace period from the backend configuration
+    pub async fn shutdown(self) {
+        self.core
+            .shutdown(self.core.get_shutdown_grace_period())
+            .await;
+    }
+}
+
+/// Panic hook which prints the most recent engine lifecycle events before it chains to the
+/// `default_hook`. The events must be printed first because the chained hook may abort the
+/// process (see `ii_async_compat::setup_panic_handling`).
+fn crash_report_hook(
+    work_history: Arc<work::history::Recorder>,
+    default_hook: PanicHook,
+) -> PanicHook {
+    Box::new(move |info| {
+        eprintln!(
+            "Last work engine events:\n{}",
+            work_history.dump(CRASH_REPORT_EVENTS)
+        );
+        default_hook(info);
+    })
+}
+
+/// Run the miner until it is stopped with the API. Returns only when the miner cannot be
+/// started or when the API server has ended.
+pub async fn main<T: hal::Backend>(
+    backend_config: T::Config,
+    signature: String,
+    logging: Option<LoggingMode>,
+) -> ExitCode {
+    let _log_guard = setup_logging(logging);
+
+    let miner = match Runner::<T>::new(backend_config).start().await {
+        Ok(miner) => miner,
+        Err(e) => {
+            error!("Backend initialization failed: {}", e);
+            return ExitCode::BackendInit;
+        }
+    };
+
+    // include the most recent engine lifecycle events in crash reports
+    std::panic::set_hook(crash_report_hook(
+        miner.work_history().clone(),
+        std::panic::take_hook(),
     ));
 
+    tokio::spawn(shutdown::run(miner.core.clone()));
+    #[cfg(all(feature = "systemd", unix))]
+    tokio::spawn(systemd::run(miner.core.clone()));
+
     // the bosminer is controlled with API which also controls when the miner will end
-    api::run(core, frontend_config, signature).await;
+    api::run(miner.core, miner.frontend_config, signature).await;
+    ExitCode::Success
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::process;
+
+    /// The test runs itself in a child process with this variable set because the panic hook
+    /// aborts the process
+    const CRASH_CHILD_ENV: &str = "BOSMINER_TEST_CRASH_REPORT_CHILD";
+
+    /// Work history is part of the crash report even when the chained hook aborts the process
+    #[test]
+    fn test_crash_report_hook() {
+        if std::env::var_os(CRASH_CHILD_ENV).is_some() {
+            let work_history = Arc::new(work::history::Recorder::new(CRASH_REPORT_EVENTS));
+            work_history.record(work::history::Event::Broadcast {
+                generation: 7,
+                job_id: Some(42),
+            });
+            std::panic::set_hook(crash_report_hook(
+                work_history,
+                Box::new(|_| process::abort()),
+            ));
+            panic!("crash");
+        }
+
+        let output =
+            process::Command::new(std::env::current_exe().expect("BUG: missing test executable"))
+                .args(&[
+                    "--exact",
+                    "entry::test::test_crash_report_hook",
+                    "--nocapture",
+                ])
+                .env(CRASH_CHILD_ENV, "1")
+                .output()
+                .expect("BUG: cannot run child test");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Last work engine events:"), "{}", stderr);
+        assert!(
+            stderr.contains("Broadcast { generation: 7, job_id: Some(42) }"),
+            "{}",
+            stderr
+        );
+    }
 }
@@ -30,6 +30,7 @@ use ii_async_compat::prelude::*;
 
 use failure::{Backtrace, Context, Fail};
 use std::fmt::{self, Debug, Display};
+use std::time;
 
 use std::io;
 
@@ -58,6 +59,59 @@ pub enum ErrorKind {
     /// Error related to clients
     #[fail(display = "Client error: {}", _0)]
     Client(Client),
+
+    /// Remote server address cannot be parsed
+    #[fail(display = "Address error: {}", _0)]
+    Address(String),
+
+    /// Connection to remote server cannot be established
+    #[fail(display = "Connection error: {}", _0)]
+    Connection(String),
+
+    /// Certificate of remote server cannot be verified
+    #[fail(display = "Certificate error: {}", _0)]
+    Certificate(String),
+}
+
+/// Describes whether repeating of an operation which failed with an error makes sense
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Severity {
+    /// The operation may succeed when it is attempted again later
+    Transient,
+    /// The operation cannot succeed without an external intervention (e.g. change of
+    /// configuration or missing hardware)
+    Fatal,
+}
+
+/// Errors with retryability metadata which are consulted by retry loops
+pub trait Classify {
+    fn severity(&self) -> Severity;
+
+    #[inline]
+    fn is_transient(&self) -> bool {
+        self.severity() == Severity::Transient
+    }
+}
+
+impl Classify for Client {
+    fn severity(&self) -> Severity {
+        // All client errors are caused by inconsistent registration or configuration of clients
+        Severity::Fatal
+    }
+}
+
+impl Classify for ErrorKind {
+    fn severity(&self) -> Severity {
+        match self {
+            ErrorKind::Io(_)
+            | ErrorKind::General(_)
+            | ErrorKind::Backend(_)
+            | ErrorKind::Stratum(_)
+            | ErrorKind::Connection(_) => Severity::Transient,
+            ErrorKind::Address(_) | ErrorKind::Certificate(_) => Severity::Fatal,
+            ErrorKind::Client(client) => client.severity(),
+        }
+    }
 }
 
 /// Implement Fail trait instead of use Derive to get more control over custom type.
@@ -90,6 +144,28 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.inner.get_context().clone()
     }
+
+    /// Returns description of the error followed by all its causes so that the original reason
+    /// is not lost when the error is logged
+    pub fn chain(&self) -> String {
+        let mut chain = self.to_string();
+        for cause in (self as &dyn Fail).iter_causes() {
+            let cause = cause.to_string();
+            // Error kinds usually copy description of the error they have been converted from
+            if !chain.ends_with(&cause) {
+                chain.push_str(": ");
+                chain.push_str(&cause);
+            }
+        }
+        chain
+    }
+}
+
+impl Classify for Error {
+    #[inline]
+    fn severity(&self) -> Severity {
+        self.inner.get_context().severity()
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -102,19 +178,28 @@ impl From<ErrorKind> for Error {
 
 impl From<ii_stratum::error::Error> for Error {
     fn from(stratum: ii_stratum::error::Error) -> Self {
-        ErrorKind::Stratum(stratum.to_string()).into()
+        let msg = stratum.to_string();
+        Self {
+            inner: stratum.context(ErrorKind::Stratum(msg)),
+        }
     }
 }
 
 impl From<ii_wire::AddressParseError> for Error {
     fn from(address_error: ii_wire::AddressParseError) -> Self {
-        ErrorKind::General(address_error.to_string()).into()
+        let msg = address_error.to_string();
+        Self {
+            inner: address_error.context(ErrorKind::Address(msg)),
+        }
     }
 }
 
 impl From<ii_wire::AttemptError> for Error {
     fn from(attempt_error: ii_wire::AttemptError) -> Self {
-        ErrorKind::General(attempt_error.to_string()).into()
+        let msg = attempt_error.to_string();
+        Self {
+            inner: attempt_error.context(ErrorKind::Connection(msg)),
+        }
     }
 }
 
@@ -239,3 +324,125 @@ pub mod backend {
 
 /// A specialized `Result` type bound to [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Run `attempt` until it succeeds but at most `tries` times with `delay` between the attempts.
+/// Retrying stops immediately when the error is not transient. The closure is given the number
+/// of tries which are left after the current one.
+pub async fn retry<T, E, F, R>(
+    tries: usize,
+    delay: time::Duration,
+    mut attempt: F,
+) -> std::result::Result<T, E>
+where
+    E: Classify,
+    F: FnMut(usize) -> R,
+    R: Future<Output = std::result::Result<T, E>>,
+{
+    assert!(tries > 0, "BUG: at least one try is required");
+    let mut tries_left = tries;
+    loop {
+        tries_left -= 1;
+        match attempt(tries_left).await {
+            Err(e) if e.is_transient() && tries_left > 0 => tokio::time::delay_for(delay).await,
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let transient: Vec<Error> = vec![
+            io::Error::new(io::ErrorKind::ConnectionReset, "reset").into(),
+            "general".into(),
+            backend::from_error_kind("backend"),
+            ErrorKind::Stratum("stratum".to_string()).into(),
+            ErrorKind::Connection("connection".to_string()).into(),
+        ];
+        for error in transient {
+            assert_eq!(error.severity(), Severity::Transient, "{}", error);
+            assert!(error.is_transient());
+        }
+
+        let fatal: Vec<Error> = vec![
+            ii_wire::AddressParseError.into(),
+            Client::Missing.into(),
+            Client::OnlyFixedShareRatio.into(),
+            Client::ZeroTotalQuota.into(),
+        ];
+        for error in fatal {
+            assert_eq!(error.severity(), Severity::Fatal, "{}", error);
+            assert!(!error.is_transient());
+        }
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let error: Error = io::Error::new(io::ErrorKind::NotFound, "no such file")
+            .context("cannot read configuration")
+            .into();
+        assert_eq!(
+            error.kind(),
+            ErrorKind::General("cannot read configuration".to_string())
+        );
+        assert_eq!(
+            error.chain(),
+            "General error: cannot read configuration: no such file"
+        );
+
+        // Description of the cause is not repeated when it has been copied to the error kind
+        let error: Error = io::Error::new(io::ErrorKind::NotFound, "no such file").into();
+        assert_eq!(error.chain(), "IO error: no such file");
+    }
+
+    async fn run_retry(tries: usize, errors: Vec<Error>) -> (Result<()>, usize) {
+        let mut errors = errors.into_iter();
+        let mut attempts = 0;
+        let result = retry(tries, time::Duration::from_millis(1), |_| {
+            attempts += 1;
+            futures::future::ready(errors.next().map_or(Ok(()), Err))
+        })
+        .await;
+        (result, attempts)
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        // Transient errors are retried until the operation succeeds
+        let (result, attempts) = run_retry(
+            5,
+            vec![
+                "first".into(),
+                ErrorKind::Connection("second".to_string()).into(),
+            ],
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+
+        // The last error is returned when all tries are exhausted
+        let (result, attempts) =
+            run_retry(2, vec!["first".into(), "second".into(), "third".into()]).await;
+        assert_eq!(
+            result.unwrap_err().kind(),
+            ErrorKind::General("second".to_string())
+        );
+        assert_eq!(attempts, 2);
+
+        // Fatal error stops the retry loop immediately
+        let (result, attempts) = run_retry(
+            5,
+            vec![
+                "first".into(),
+                ii_wire::AddressParseError.into(),
+                "third".into(),
+            ],
+        )
+        .await;
+        assert!(!result.unwrap_err().is_transient());
+        assert_eq!(attempts, 2);
+    }
+}
@@ -32,4 +32,12 @@ pub enum ErrorKind {
     OnlyFixedShareRatio,
     #[fail(display = "total fixed share ratio is greater than or equal to 1.0")]
     FixedShareRatioOverflow,
+    #[fail(display = "the client group has fixed share ratio instead of quota")]
+    FixedShareRatioGroup,
+    #[fail(display = "the last remaining client cannot be removed")]
+    LastClient,
+    #[fail(display = "total quota of all client groups is zero")]
+    ZeroTotalQuota,
+    #[fail(display = "connectivity test is not supported by the client protocol")]
+    UnsupportedProbe,
 }
@@ -0,0 +1,83 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Stable API for applications embedding BOSminer as a library (enabled with the `lib-api`
+//! feature).
+//!
+//! Only the items re-exported here are guarded by semantic versioning. All other public modules
+//! of this crate are internals shared with the mining backends and they change without notice.
+//! The list of re-exported items is checked by `tests/facade_api.rs` against the golden file
+//! `tests/facade_api.txt` which has to be updated together with any intended change of the
+//! facade.
+//!
+//! The miner is started with `Runner` which returns `RunningMiner` used for its control.
+
+// miner lifecycle
+pub use crate::entry::setup_logging;
+pub use crate::entry::LoggingMode;
+pub use crate::entry::Runner;
+pub use crate::entry::RunningMiner;
+pub use crate::hub::CoreSummary;
+
+// client management
+pub use crate::client::probe::Options as PoolTestOptions;
+pub use crate::client::probe::Report as PoolTestReport;
+pub use crate::client::Group as ClientGroup;
+pub use crate::client::Handle as ClientHandle;
+pub use crate::client::Manager as ClientManager;
+pub use bosminer_config::ClientDescriptor;
+pub use bosminer_config::GroupDescriptor;
+
+// statistics snapshots
+pub use crate::client::baseline::Comparison as ShareBaselineComparison;
+pub use crate::client::baseline::Verdict as ShareBaselineVerdict;
+pub use crate::job::block::Snapshot as BlockSnapshot;
+pub use crate::job::registry::Snapshot as LiveJobSnapshot;
+pub use crate::stats::JobLatencySnapshot;
+pub use crate::stats::LastShareSnapshot;
+pub use crate::stats::MeterSnapshot;
+pub use crate::stats::PauseSnapshot;
+pub use crate::stats::QuarantineEvent;
+pub use crate::stats::QuarantineSnapshot;
+pub use crate::stats::Snapshot;
+pub use crate::stats::SolutionLatencySnapshot;
+pub use crate::stats::WorkProgressSnapshot;
+pub use crate::stats::WorkStarvationSnapshot;
+
+// event streams
+pub use crate::job::block::BlockChange;
+pub use crate::progress::Phase as ProgressPhase;
+pub use crate::progress::Registry as Progress;
+pub use crate::sync::event::Receiver as EventReceiver;
+pub use crate::work::history::Event as WorkEvent;
+pub use crate::work::history::Record as WorkRecord;
+pub use crate::work::history::Recorder as WorkHistory;
+
+// backend interface
+pub use crate::async_trait;
+pub use crate::hal::Backend;
+pub use crate::hal::BackendConfig;
+pub use crate::hal::BackendInfo;
+pub use crate::hal::FrontendConfig;
+
+pub use crate::error::Error;
+pub use crate::error::Result;
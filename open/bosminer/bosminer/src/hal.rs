@@ -23,11 +23,19 @@
 use crate::client;
 use crate::error;
 use crate::node;
+use crate::progress;
+use crate::shutdown;
+use crate::stats;
 use crate::work;
 
+use bosminer_config::{
+    ApiConfig, DigestConfig, FanOutConfig, SolutionExportConfig, SolutionRoutingPolicy,
+    WorkHistoryConfig,
+};
 use ii_cgminer_api::command;
 use ii_stratum::v2::types::DeviceInfo;
 
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -46,6 +54,20 @@ pub trait BackendSolution: Debug + Send + Sync {
     /// Backend target used for finding this nonce
     /// This information is used mainly for detecting HW errors
     fn target(&self) -> &ii_bitcoin::Target;
+    /// Upper bound of `solution_idx` when the backend knows it
+    fn solution_count(&self) -> Option<usize> {
+        None
+    }
+    /// How long the solution has been waiting in hardware buffers before it has been fetched
+    /// when the backend is able to estimate it
+    fn age_hint(&self) -> Option<Duration> {
+        None
+    }
+    /// Time since the work has been dispatched to the hardware until the solution has been found
+    /// when the backend is able to measure it (it does not include latency of the driver)
+    fn hw_timestamp(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Enum returned from `Backend::create` is intended for choosing type of backend root node (work
@@ -61,6 +83,10 @@ pub struct BackendInfo {
     pub hw_rev: String,
     pub fw_ver: String,
     pub dev_id: String,
+    /// Backend specific fingerprint of the environment (kernel, device-tree, ...)
+    pub environment: BTreeMap<String, String>,
+    /// Backend runs with conservative configuration after repeated crashes
+    pub safe_mode: bool,
 }
 
 impl Default for BackendInfo {
@@ -74,6 +100,8 @@ impl Default for BackendInfo {
                 crate::version::STRING.to_string()
             ),
             dev_id: Default::default(),
+            environment: Default::default(),
+            safe_mode: false,
         }
     }
 }
@@ -101,19 +129,76 @@ impl From<BackendInfo> for DeviceInfo {
     }
 }
 
-pub trait BackendConfig: Debug + Send + Sync {
+/// Configuration is cloned and kept by the core so that the backend can be rebuilt on restart
+pub trait BackendConfig: Debug + Clone + Send + Sync + 'static {
     /// Number of midstates that backend is able to solve at once
     fn midstate_count(&self) -> usize;
+    /// All numbers of midstates the backend can be configured to
+    fn supported_midstate_counts(&self) -> Vec<usize> {
+        vec![self.midstate_count()]
+    }
+    /// Default Bitcoin network for all clients
+    fn network(&self) -> ii_bitcoin::Network {
+        Default::default()
+    }
     /// Pass client manager to backend to get access to its functionality
     fn set_client_manager(&mut self, _client_manager: client::Manager) {}
+    /// Pass registry to which backend reports progress of its initialization
+    fn set_progress(&mut self, _progress: Arc<progress::Registry>) {}
     /// Optional information about backend
     fn info(&self) -> Option<BackendInfo> {
         None
     }
+    /// Settings of the daily share digest when it should be collected
+    fn digest(&self) -> Option<DigestConfig> {
+        None
+    }
+    /// Settings of the log of work engine lifecycle events
+    fn work_history(&self) -> WorkHistoryConfig {
+        Default::default()
+    }
+    /// Settings of staggered distribution of new jobs to work solvers (all are woken at once by
+    /// default)
+    fn fan_out(&self) -> Option<FanOutConfig> {
+        None
+    }
+    /// How long to wait for solutions of the last work when the miner is being shut down
+    fn shutdown_grace_period(&self) -> Duration {
+        shutdown::DEFAULT_GRACE_PERIOD
+    }
+    /// Handling of solutions whose origin client has been removed (they are dropped by default)
+    fn solution_routing(&self) -> SolutionRoutingPolicy {
+        Default::default()
+    }
+    /// Maximal age of work submitted to the hardware (older work is skipped)
+    fn assignment_max_age(&self) -> Option<Duration> {
+        None
+    }
+    /// Directory where solutions meeting the network target are archived (block candidates are
+    /// not archived by default)
+    fn block_archive(&self) -> Option<String> {
+        None
+    }
+    /// Ring buffer in shared memory where all solutions are exported for a co-located process
+    /// (solutions are not exported by default)
+    fn solution_export(&self) -> Option<SolutionExportConfig> {
+        None
+    }
+    /// File where lifetime statistics are persisted across restarts (they are not persisted by
+    /// default)
+    fn stats_persistence_path(&self) -> Option<String> {
+        None
+    }
+    /// How often the lifetime statistics are saved
+    fn stats_persistence_interval(&self) -> Duration {
+        stats::persist::DEFAULT_INTERVAL
+    }
 }
 
 pub struct FrontendConfig {
     pub cgminer_custom_commands: Option<command::Map>,
+    /// Listen address and access restrictions of the CGMiner API
+    pub api: ApiConfig,
 }
 
 /// Minimal interface for running compatible backend with BOSminer crate
@@ -154,4 +239,16 @@ pub trait Backend: Send + Sync + 'static {
         backend_config: Self::Config,
         work_solver: Arc<Self::Type>,
     ) -> error::Result<FrontendConfig>;
+
+    /// Function is called as the last step of the miner shutdown when all clients have been
+    /// stopped. Backend should power down its hardware here because the process exits right
+    /// after.
+    async fn shutdown(_work_node: Arc<Self::Type>) {}
+
+    /// Function is called when all clients have been stopped before the backend is built again
+    /// on restart. Backend should power down its hardware and end all its tasks but, unlike
+    /// `shutdown`, the process keeps running. The default implementation is `shutdown`.
+    async fn stop(work_node: Arc<Self::Type>) {
+        Self::shutdown(work_node).await
+    }
 }
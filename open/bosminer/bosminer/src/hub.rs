@@ -26,18 +26,47 @@
 use ii_logging::macros::*;
 
 use crate::backend;
-use crate::client;
+use crate::bus;
+use crate::client::{self, baseline, digest};
+use crate::clock;
 use crate::error;
 use crate::hal::{self, BackendConfig};
+use crate::job;
+use crate::log_id;
 use crate::node;
+use crate::progress;
+use crate::shutdown;
+use crate::stats;
+use crate::sync::heartbeat::{self, Heartbeat};
 use crate::work;
 
+use bosminer_config::{
+    DigestConfig, FanOutConfig, SolutionExportConfig, SolutionRoutingPolicy, WorkHistoryConfig,
+};
+
 use futures::channel::mpsc;
+use futures::future::BoxFuture;
 use futures::lock::Mutex;
 use futures::stream::StreamExt;
-use ii_async_compat::{futures, tokio};
+use ii_async_compat::{futures, tokio, FutureExt};
+use tokio::time::delay_for;
+
+#[cfg(unix)]
+use once_cell::sync::OnceCell;
+
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time;
 
-use std::sync::{Arc, Weak};
+/// Solutions are considered flushed during shutdown when none has been routed for this time
+const SOLUTION_FLUSH_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+/// The solution router beats at least this often even when there is no solution to route
+const HEARTBEAT_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+/// Main loops of the core are considered wedged when they have not beaten for this time
+const LOOP_STALL_TIMEOUT: time::Duration = time::Duration::from_secs(30);
 
 /// Handle external events. Currently it is used only wor handling exhausted work from work engine.
 /// It usually signals some serious problem in backend.
@@ -45,68 +74,363 @@ use std::sync::{Arc, Weak};
 struct EventHandler;
 
 impl work::ExhaustedHandler for EventHandler {
-    fn handle_exhausted(&self, _engine: work::DynEngine) {
-        warn!("No more work available for current job!");
+    fn handle_exhausted(&self, _engine: work::DynEngine, job: Option<Arc<dyn job::Bitcoin>>) {
+        match job {
+            Some(job) => warn!(
+                "{} no more work available for the job!",
+                log_id::Prefix::job(job.as_ref())
+            ),
+            None => warn!("No more work available for current job!"),
+        }
+    }
+}
+
+/// Policy and statistics of routing solutions whose origin client has been removed (orphans)
+#[derive(Debug, Default)]
+pub struct OrphanRouting {
+    policy: StdMutex<SolutionRoutingPolicy>,
+    /// Orphans discarded by the policy or because no client could take them over
+    pub dropped: stats::CounterU64,
+    /// Orphans delivered to another client of the same group
+    pub rerouted_same_group: stats::CounterU64,
+    /// Orphans delivered to a client accepting solutions of foreign jobs
+    pub rerouted_active: stats::CounterU64,
+}
+
+impl OrphanRouting {
+    pub fn policy(&self) -> SolutionRoutingPolicy {
+        *self
+            .policy
+            .lock()
+            .expect("BUG: cannot lock solution routing policy")
+    }
+
+    /// Change the policy at runtime and return the previous one
+    pub fn set_policy(&self, policy: SolutionRoutingPolicy) -> SolutionRoutingPolicy {
+        let mut current = self
+            .policy
+            .lock()
+            .expect("BUG: cannot lock solution routing policy");
+        mem::replace(&mut *current, policy)
+    }
+
+    fn account(&self, policy: SolutionRoutingPolicy, rerouted: bool) {
+        match (policy, rerouted) {
+            (SolutionRoutingPolicy::RerouteSameGroup, true) => self.rerouted_same_group.inc(),
+            (SolutionRoutingPolicy::RerouteActive, true) => self.rerouted_active.inc(),
+            _ => self.dropped.inc(),
+        }
     }
 }
 
 /// Responsible for delivering work solution to the client from which the work has been generated
 struct SolutionRouter {
     job_executor: Arc<client::JobExecutor>,
+    client_manager: client::Manager,
     solution_receiver: mpsc::UnboundedReceiver<work::Solution>,
+    /// Solution received from the channel which has not been handed over to any client yet
+    pending: Option<work::Solution>,
+    /// Number of all received solutions
+    solution_count: Arc<AtomicU64>,
+    orphan_routing: Arc<OrphanRouting>,
+    /// Ring buffer receiving copies of all solutions when the export is enabled
+    #[cfg(unix)]
+    solution_export: Arc<OnceCell<work::export::RingBuffer>>,
+    heartbeat: Arc<Heartbeat>,
 }
 
 impl SolutionRouter {
     fn new(
         job_executor: Arc<client::JobExecutor>,
+        client_manager: client::Manager,
         solution_receiver: mpsc::UnboundedReceiver<work::Solution>,
+        solution_count: Arc<AtomicU64>,
+        orphan_routing: Arc<OrphanRouting>,
+        #[cfg(unix)] solution_export: Arc<OnceCell<work::export::RingBuffer>>,
     ) -> Self {
         Self {
             job_executor,
+            client_manager,
             solution_receiver,
+            pending: None,
+            solution_count,
+            orphan_routing,
+            #[cfg(unix)]
+            solution_export,
+            heartbeat: Arc::new(Heartbeat::new("solution router", LOOP_STALL_TIMEOUT)),
         }
     }
 
-    async fn run(mut self) {
-        while let Some(solution) = self.solution_receiver.next().await {
-            // NOTE: all solutions targeting to removed clients are discarded
-            if let Some(solution_sender) = self.job_executor.get_solution_sender(&solution).await {
-                solution_sender
-                    .unbounded_send(solution)
-                    .expect("solution queue send failed");
-            } else {
-                warn!("Hub: solution has been discarded because client does not exist anymore");
+    /// Find a running client which can submit the solution of removed client instead of it
+    async fn find_orphan_destination(
+        &self,
+        policy: SolutionRoutingPolicy,
+        solution: &work::Solution,
+    ) -> Option<Arc<client::Handle>> {
+        let (mut clients, foreign) = match policy {
+            SolutionRoutingPolicy::Drop => return None,
+            SolutionRoutingPolicy::RerouteSameGroup => {
+                let group = self.client_manager.find_former_group(solution).await?;
+                (group.get_clients().await, false)
+            }
+            SolutionRoutingPolicy::RerouteActive => {
+                let mut clients = vec![];
+                for group in self.client_manager.get_groups().await {
+                    clients.extend(group.get_clients().await);
+                }
+                (clients, true)
             }
+        };
+        // Prefer clients which currently supply jobs of their groups
+        clients.sort_by_key(|client| !client.is_active());
+        for client in clients {
+            if client.accepts_rerouted(solution, foreign).await {
+                return Some(client);
+            }
+        }
+        None
+    }
+
+    /// Handle solution whose origin client does not exist anymore according to the routing
+    /// policy. Returns the client which has taken over the solution.
+    async fn route_orphan(&self, solution: work::Solution) -> Option<Arc<client::Handle>> {
+        let policy = self.orphan_routing.policy();
+        let destination = self.find_orphan_destination(policy, &solution).await;
+        self.hand_over_orphan(policy, solution, destination).await
+    }
+
+    /// Deliver the orphan to the `destination` found by the routing `policy`. The solution is
+    /// handed over before the first await point.
+    async fn hand_over_orphan(
+        &self,
+        policy: SolutionRoutingPolicy,
+        solution: work::Solution,
+        destination: Option<Arc<client::Handle>>,
+    ) -> Option<Arc<client::Handle>> {
+        self.orphan_routing.account(policy, destination.is_some());
+        let prefix = log_id::Prefix::solution(&solution);
+        match destination.as_ref() {
+            Some(client) => {
+                client.send_rerouted(solution);
+                info!(
+                    "{} Hub: solution of removed client has been rerouted to '{}'",
+                    prefix.with_client(client.client_name()),
+                    client.display_name()
+                );
+            }
+            None => {
+                warn!(
+                    "{} Hub: solution has been discarded because client does not exist anymore",
+                    prefix
+                );
+            }
+        }
+        destination
+    }
+
+    /// Route the pending solution to its client or to the destination of orphans
+    async fn route_pending(&mut self) {
+        let solution = self
+            .pending
+            .as_ref()
+            .expect("BUG: missing pending solution");
+        if let Some(solution_sender) = self.job_executor.get_solution_sender(solution).await {
+            let solution = self.pending.take().expect("BUG: missing pending solution");
+            solution_sender
+                .unbounded_send(solution)
+                .expect("solution queue send failed");
+        } else {
+            let policy = self.orphan_routing.policy();
+            let destination = self.find_orphan_destination(policy, solution).await;
+            let solution = self.pending.take().expect("BUG: missing pending solution");
+            self.hand_over_orphan(policy, solution, destination).await;
+        }
+    }
+
+    /// Route all received solutions until the channel is closed.
+    ///
+    /// The routing is cancellation safe. Looking up the destination of a solution may be
+    /// suspended so the received solution is kept in `pending` and it is taken out of it only
+    /// right before it is handed over without any await point in between. When the future is
+    /// dropped, the next call of this method routes the pending solution first.
+    async fn run(&mut self) {
+        loop {
+            self.heartbeat.beat();
+            if self.pending.is_none() {
+                match self
+                    .solution_receiver
+                    .next()
+                    .timeout(HEARTBEAT_INTERVAL)
+                    .await
+                {
+                    Ok(Some(solution)) => {
+                        self.solution_count.fetch_add(1, Ordering::Relaxed);
+                        #[cfg(unix)]
+                        {
+                            if let Some(ring_buffer) = self.solution_export.get() {
+                                ring_buffer.export(&solution);
+                            }
+                        }
+                        self.pending = Some(solution);
+                    }
+                    Ok(None) => break,
+                    // beat even when there is no solution to route
+                    Err(_) => continue,
+                }
+            }
+            self.route_pending().await;
         }
     }
 }
 
-pub struct Core {
+/// Record new blocks to the work history so that periods without any work can be correlated with
+/// block changes
+async fn record_block_changes(
+    mut subscriber: bus::Subscriber,
+    history: Arc<work::history::Recorder>,
+) {
+    while let Some(event) = subscriber.recv().await {
+        if let bus::Event::BlockChanged(change) = event {
+            history.record(work::history::Event::BlockChange {
+                block: change.previous_hash.to_string(),
+                client: change.client_name.to_string(),
+            });
+        }
+    }
+}
+
+/// Log all alerts published by monitoring modules
+async fn log_alerts(mut subscriber: bus::Subscriber) {
+    while let Some(event) = subscriber.recv().await {
+        if let bus::Event::Alert(alert) = event {
+            warn!("Alert: {}", alert);
+        }
+    }
+}
+
+/// Overview of the mining core used by applications embedding BOSminer
+#[derive(Debug, Clone)]
+pub struct CoreSummary {
     pub backend_info: Option<hal::BackendInfo>,
-    // NOTE: Weak reference must be released first!
-    backend_registry: Weak<backend::Registry>,
-    pub frontend: Arc<crate::Frontend>,
+    /// Amount of work generated for all backends
+    pub generated_work: u64,
+    pub work_hubs: usize,
+    pub work_solvers: usize,
+    /// Number of all clients in all groups
+    pub clients: usize,
+    /// Number of clients with established connection to their remote server
+    pub running_clients: usize,
+    /// Initialization of all reporting hash chains has finished either way
+    pub initialized: bool,
+    /// Reject and stale rates of pools in the last hours compared against their baseline
+    pub share_baseline: Vec<baseline::Comparison>,
+    /// Network difficulty and blocks seen in jobs of all clients
+    pub blocks: job::block::Snapshot,
+    /// Jobs of all clients which are still referenced
+    pub live_jobs: job::registry::Snapshot,
+    /// Events published on the internal bus and dropped for slow subscribers
+    pub bus: bus::Snapshot,
+}
+
+impl CoreSummary {
+    /// The miner is considered healthy when all hash chains have been initialized and it is
+    /// connected to at least one pool so it has work for the hardware
+    pub fn is_healthy(&self) -> bool {
+        self.initialized && self.work_solvers > 0 && self.running_clients > 0
+    }
+}
+
+/// Build of the backend with the configuration it has been originally built with
+type BackendBuilder = Box<
+    dyn for<'a> Fn(&'a Core) -> BoxFuture<'a, error::Result<hal::FrontendConfig>> + Send + Sync,
+>;
+
+fn backend_builder<F>(build: F) -> BackendBuilder
+where
+    F: for<'a> Fn(&'a Core) -> BoxFuture<'a, error::Result<hal::FrontendConfig>>
+        + Send
+        + Sync
+        + 'static,
+{
+    Box::new(build)
+}
+
+/// Backend specific parts of the shutdown and of the restart
+struct BackendTeardown {
+    shutdown: BoxFuture<'static, ()>,
+    stop: BoxFuture<'static, ()>,
+}
+
+pub struct Core {
+    backend_info: Option<hal::BackendInfo>,
+    /// Registry of the current backend nodes which is replaced when the backend is rebuilt
+    backend_registry: StdMutex<Arc<backend::Registry>>,
+    /// The lock is also held for the whole restart and shutdown to serialize them
+    backend_builder: Mutex<Option<BackendBuilder>>,
+    frontend: Arc<crate::Frontend>,
     job_executor: Arc<client::JobExecutor>,
     engine_receiver: work::EngineReceiver,
     solution_sender: mpsc::UnboundedSender<work::Solution>,
     solution_router: Mutex<Option<SolutionRouter>>,
+    solution_count: Arc<AtomicU64>,
+    orphan_routing: Arc<OrphanRouting>,
+    /// Ring buffer in shared memory which is set at most once when the export is configured
+    #[cfg(unix)]
+    solution_export: Arc<OnceCell<work::export::RingBuffer>>,
     /// Registry of clients that are able to supply new jobs for mining
     client_manager: client::Manager,
+    /// History of daily share digests with offset of the local time zone used for rollup
+    digest: Option<(Arc<digest::Store>, i32)>,
+    /// Detection of regressions of reject and stale rates fed by the digest samples
+    share_baseline: Option<Arc<baseline::Tracker>>,
+    /// Progress of backend initialization reported by its hash chains
+    progress: Arc<progress::Registry>,
+    /// Blocks seen in jobs of all clients
+    block_tracker: Arc<job::block::Tracker>,
+    /// Solutions of all clients which meet the network target
+    block_archive: Arc<job::archive::Archive>,
+    /// Snapshot of lifetime statistics with the period of saving it
+    stats_persistence: StdMutex<Option<(Arc<stats::persist::Store>, time::Duration)>>,
+    /// Jobs of all clients which are still referenced
+    job_registry: Arc<job::registry::Registry>,
+    /// Heartbeats of the solution router and the job executor
+    heartbeats: heartbeat::Registry,
+    /// Events published by clients and monitoring modules
+    bus: Arc<bus::Bus>,
+    /// Backend specific part of the shutdown
+    backend_teardown: Mutex<Option<BackendTeardown>>,
+    /// Time given to solutions of the last work to reach their clients on shutdown and restart
+    shutdown_grace_period: StdMutex<time::Duration>,
 }
 
 /// Concentrates handles to all nodes associated with mining (backends, clients, work solvers)
 impl Core {
     pub fn new(
         midstate_count: usize,
+        network: ii_bitcoin::Network,
         backend_registry: &Arc<backend::Registry>,
         backend_info: Option<hal::BackendInfo>,
+        digest_config: Option<DigestConfig>,
+        work_history_config: WorkHistoryConfig,
+        fan_out_config: Option<FanOutConfig>,
     ) -> Self {
         let frontend = Arc::new(crate::Frontend::new());
 
-        let (engine_sender, engine_receiver) = work::engine_channel(EventHandler);
+        let work_history = Arc::new(Self::create_work_history(&work_history_config));
+        let (engine_sender, engine_receiver) =
+            work::engine_channel_with_history(EventHandler, work_history);
+        engine_receiver.set_fan_out(fan_out_config.as_ref().map(Into::into));
         let (solution_sender, solution_receiver) = mpsc::unbounded();
+        let solution_count = Arc::new(AtomicU64::new(0));
+        let orphan_routing = Arc::new(OrphanRouting::default());
+        #[cfg(unix)]
+        let solution_export = Arc::new(OnceCell::new());
 
-        let client_manager = client::Manager::new(midstate_count);
+        // Components shared by clients of this core only
+        let services = job::Services::new();
+        let client_manager = client::Manager::new(midstate_count)
+            .with_network(network)
+            .with_services(services.clone());
         let job_executor = Arc::new(client::JobExecutor::new(
             frontend.clone(),
             engine_sender,
@@ -115,38 +439,117 @@ impl Core {
 
         Self {
             backend_info,
-            backend_registry: Arc::downgrade(backend_registry),
+            backend_registry: StdMutex::new(backend_registry.clone()),
+            backend_builder: Mutex::new(None),
             frontend,
             job_executor: job_executor.clone(),
             engine_receiver,
             solution_sender,
-            solution_router: Mutex::new(Some(SolutionRouter::new(job_executor, solution_receiver))),
+            solution_router: Mutex::new(Some(SolutionRouter::new(
+                job_executor,
+                client_manager.clone(),
+                solution_receiver,
+                solution_count.clone(),
+                orphan_routing.clone(),
+                #[cfg(unix)]
+                solution_export.clone(),
+            ))),
+            solution_count,
+            orphan_routing,
+            #[cfg(unix)]
+            solution_export,
             client_manager,
+            share_baseline: digest_config.as_ref().map(|config| {
+                Arc::new(
+                    baseline::Tracker::new(config.regression_factor())
+                        .with_bus(services.bus.clone()),
+                )
+            }),
+            digest: digest_config.map(|config| {
+                (
+                    Arc::new(digest::Store::open(config.path(), config.retention())),
+                    config.utc_offset(),
+                )
+            }),
+            progress: Arc::new(progress::Registry::new()),
+            block_tracker: services.block_tracker,
+            block_archive: services.block_archive,
+            stats_persistence: StdMutex::new(None),
+            job_registry: services.job_registry,
+            heartbeats: heartbeat::Registry::new(),
+            bus: services.bus,
+            backend_teardown: Mutex::new(None),
+            shutdown_grace_period: StdMutex::new(shutdown::DEFAULT_GRACE_PERIOD),
         }
     }
 
+    fn create_work_history(config: &WorkHistoryConfig) -> work::history::Recorder {
+        let length = config.length();
+        match config.mirror() {
+            Some(path) => work::history::Recorder::new(length)
+                .with_mirror(path)
+                .unwrap_or_else(|e| {
+                    warn!("Cannot mirror work history to '{}': {}", path, e);
+                    work::history::Recorder::new(length)
+                }),
+            None => work::history::Recorder::new(length),
+        }
+    }
+
+    fn backend_registry(&self) -> Arc<backend::Registry> {
+        self.backend_registry
+            .lock()
+            .expect("BUG: cannot lock backend registry")
+            .clone()
+    }
+
     /// Builds a new backend for a specified `backend_config`.
     /// The resulting `hal::FrontendConfig` is then available for starting additional BOSminer
-    /// components
+    /// components. The configuration is kept to build the backend again on restart.
     pub async fn build_backend<T: hal::Backend>(
+        &self,
+        backend_config: T::Config,
+    ) -> error::Result<hal::FrontendConfig> {
+        *self
+            .shutdown_grace_period
+            .lock()
+            .expect("BUG: cannot lock shutdown grace period") =
+            backend_config.shutdown_grace_period();
+        let restart_config = backend_config.clone();
+        self.backend_builder
+            .lock()
+            .await
+            .replace(backend_builder(move |core| {
+                Box::pin(core.init_backend::<T>(restart_config.clone()))
+            }));
+        self.init_backend::<T>(backend_config).await
+    }
+
+    async fn init_backend<T: hal::Backend>(
         &self,
         mut backend_config: T::Config,
     ) -> error::Result<hal::FrontendConfig> {
         let work_solver_builder = work::SolverBuilder::new(
             self.frontend.clone(),
-            self.backend_registry
-                .upgrade()
-                .expect("BUG: missing backend registry"),
+            self.backend_registry(),
             self.engine_receiver.clone(),
             self.solution_sender.clone(),
         );
 
         backend_config.set_client_manager(self.get_client_manager().clone());
+        backend_config.set_progress(self.progress.clone());
         // call backend create to determine the preferred hierarchy
         match T::create(&mut backend_config) {
             // the generic tree hierarchy where the backend consists of multiple devices
             node::WorkSolverType::WorkHub(create) => {
-                let work_hub = work_solver_builder.create_work_hub(create).await;
+                let mut work_hub = work_solver_builder.create_work_hub(create).await;
+                // work solvers registered under the hub divide each engine among themselves
+                // instead of contending for the shared one
+                work_hub.set_partitioned(true);
+                self.backend_teardown.lock().await.replace(BackendTeardown {
+                    shutdown: T::shutdown(work_hub.to_node().clone()),
+                    stop: T::stop(work_hub.to_node().clone()),
+                });
                 // Initialization of backend hierarchy is done dynamically with provided work hub
                 // which can be used for registration of another work hubs or work solvers. The
                 // hierarchy has no limitation but is restricted only with tree structure.
@@ -155,6 +558,10 @@ impl Core {
             // the simplest hierarchy where the backend is single device
             node::WorkSolverType::WorkSolver(create) => {
                 let work_solver = work_solver_builder.create_work_solver(create).await;
+                self.backend_teardown.lock().await.replace(BackendTeardown {
+                    shutdown: T::shutdown(work_solver.clone()),
+                    stop: T::stop(work_solver.clone()),
+                });
                 T::init_work_solver(backend_config, work_solver).await
             }
         }
@@ -162,45 +569,342 @@ impl Core {
 
     #[inline]
     pub async fn get_root_hub(&self) -> Option<Arc<dyn node::WorkSolver>> {
-        self.backend_registry
-            .upgrade()?
-            .lock_root_hub()
-            .await
-            .clone()
+        self.backend_registry().lock_root_hub().await.clone()
     }
 
+    /// Return all work hubs ordered by their sort key
     #[inline]
     pub async fn get_work_hubs(&self) -> Vec<Arc<dyn node::WorkSolver>> {
-        if let Some(backend_registry) = self.backend_registry.upgrade() {
-            backend_registry
-                .lock_work_hubs()
-                .await
-                .iter()
-                .cloned()
-                .collect()
-        } else {
-            vec![]
-        }
+        self.backend_registry().get_work_hubs().await
     }
 
+    /// Return all work solvers ordered by their sort key (see `get_indexed_work_solvers` for
+    /// their indices)
     #[inline]
     pub async fn get_work_solvers(&self) -> Vec<Arc<dyn node::WorkSolver>> {
-        if let Some(backend_registry) = self.backend_registry.upgrade() {
-            backend_registry
-                .lock_work_solvers()
-                .await
-                .iter()
-                .cloned()
-                .collect()
-        } else {
-            vec![]
-        }
+        self.backend_registry().get_work_solvers().await
+    }
+
+    /// Return all work solvers with indices which are stable for the whole lifetime of each work
+    /// solver
+    pub async fn get_indexed_work_solvers(&self) -> Vec<(usize, Arc<dyn node::WorkSolver>)> {
+        self.backend_registry().get_indexed_work_solvers().await
+    }
+
+    /// Return the work solver with index `idx` together with the number of assigned indices
+    pub async fn get_work_solver(&self, idx: usize) -> (Option<Arc<dyn node::WorkSolver>>, usize) {
+        let backend_registry = self.backend_registry();
+        (
+            backend_registry.get_work_solver(idx).await,
+            backend_registry.get_work_solver_index_count().await,
+        )
+    }
+
+    /// Return all work hubs and work solvers ordered by their uid
+    pub async fn get_nodes(&self) -> Vec<backend::Entry> {
+        self.backend_registry().get_entries().await
+    }
+
+    #[inline]
+    pub fn get_backend_info(&self) -> Option<&hal::BackendInfo> {
+        self.backend_info.as_ref()
+    }
+
+    #[inline]
+    pub fn get_frontend(&self) -> &Arc<crate::Frontend> {
+        &self.frontend
     }
 
     pub fn get_client_manager(&self) -> &client::Manager {
         &self.client_manager
     }
 
+    /// Routing of solutions whose origin client has been removed
+    #[inline]
+    pub fn get_orphan_routing(&self) -> &Arc<OrphanRouting> {
+        &self.orphan_routing
+    }
+
+    /// Limit age of work submitted to the hardware by all work solvers
+    pub fn set_assignment_max_age(&self, max_age: Option<time::Duration>) {
+        self.engine_receiver.set_assignment_max_age(max_age);
+    }
+
+    #[inline]
+    pub fn get_digest_store(&self) -> Option<&Arc<digest::Store>> {
+        self.digest.as_ref().map(|(store, _)| store)
+    }
+
+    #[inline]
+    pub fn get_progress(&self) -> &Arc<progress::Registry> {
+        &self.progress
+    }
+
+    #[inline]
+    pub fn get_work_history(&self) -> &Arc<work::history::Recorder> {
+        self.engine_receiver.history()
+    }
+
+    #[inline]
+    pub fn get_block_tracker(&self) -> &Arc<job::block::Tracker> {
+        &self.block_tracker
+    }
+
+    /// Start archiving block candidates of all clients to `dir`
+    pub fn set_block_archive_dir(&self, dir: &str) {
+        if let Err(e) = self.block_archive.set_dir(dir) {
+            warn!("Cannot archive block candidates to '{}': {}", dir, e);
+        }
+    }
+
+    /// Export all solutions to the ring buffer in shared memory described by `config`
+    #[cfg(unix)]
+    pub fn set_solution_export(&self, config: &SolutionExportConfig) {
+        match work::export::RingBuffer::create(&config.path, config.size()) {
+            Ok(ring_buffer) => {
+                if self.solution_export.set(ring_buffer).is_err() {
+                    warn!("Solution export has already been set");
+                }
+            }
+            Err(e) => warn!("Cannot export solutions to '{}': {}", config.path, e),
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn set_solution_export(&self, config: &SolutionExportConfig) {
+        warn!(
+            "Cannot export solutions to '{}': not supported on this platform",
+            config.path
+        );
+    }
+
+    #[inline]
+    pub fn get_block_archive(&self) -> &Arc<job::archive::Archive> {
+        &self.block_archive
+    }
+
+    /// Restore lifetime statistics from snapshot at `path` and save them every `interval` once
+    /// the core is running
+    pub fn set_stats_persistence(&self, path: &str, interval: time::Duration) {
+        self.stats_persistence
+            .lock()
+            .expect("BUG: cannot lock stats persistence")
+            .replace((
+                Arc::new(stats::persist::Store::open(path, interval)),
+                interval,
+            ));
+    }
+
+    /// Grace period of the shutdown and of the restart configured by the backend
+    pub fn get_shutdown_grace_period(&self) -> time::Duration {
+        *self
+            .shutdown_grace_period
+            .lock()
+            .expect("BUG: cannot lock shutdown grace period")
+    }
+
+    pub fn get_stats_persistence(&self) -> Option<Arc<stats::persist::Store>> {
+        self.stats_persistence
+            .lock()
+            .expect("BUG: cannot lock stats persistence")
+            .as_ref()
+            .map(|(store, _)| store.clone())
+    }
+
+    #[inline]
+    pub fn get_job_registry(&self) -> &Arc<job::registry::Registry> {
+        &self.job_registry
+    }
+
+    #[inline]
+    pub fn get_heartbeats(&self) -> &heartbeat::Registry {
+        &self.heartbeats
+    }
+
+    #[inline]
+    pub fn get_bus(&self) -> &Arc<bus::Bus> {
+        &self.bus
+    }
+
+    /// Start audit of work delivered from all current clients to all current work solvers for
+    /// `duration`. Clients are ordered the same way as pools in the API.
+    pub async fn start_work_audit(&self, duration: time::Duration) {
+        let mut clients = Vec::new();
+        for group in self.client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                clients.push(work::audit::Client {
+                    label: client.descriptor().await.get_full_url(),
+                    node: client.node().clone(),
+                });
+            }
+        }
+        let work_solvers = self.get_work_solvers().await;
+        let solvers = self
+            .get_nodes()
+            .await
+            .into_iter()
+            .filter(|entry| {
+                work_solvers
+                    .iter()
+                    .any(|work_solver| Arc::ptr_eq(work_solver, &entry.node))
+            })
+            .map(|entry| work::audit::Solver {
+                uid: entry.uid,
+                path: entry.path,
+                node: entry.node,
+            })
+            .collect();
+        self.engine_receiver
+            .audit()
+            .start(clients, solvers, duration);
+    }
+
+    #[inline]
+    pub fn get_work_audit(&self) -> Option<work::audit::Report> {
+        self.engine_receiver.audit().report()
+    }
+
+    pub async fn summary(&self) -> CoreSummary {
+        let mut clients = 0;
+        let mut running_clients = 0;
+        for group in self.client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                clients += 1;
+                if client.is_running() {
+                    running_clients += 1;
+                }
+            }
+        }
+
+        CoreSummary {
+            backend_info: self.backend_info.clone(),
+            generated_work: self.frontend.get_generated_work(),
+            work_hubs: self.get_work_hubs().await.len(),
+            work_solvers: self.get_work_solvers().await.len(),
+            clients,
+            running_clients,
+            initialized: self.progress.is_finished(),
+            share_baseline: self
+                .share_baseline
+                .as_ref()
+                .map(|tracker| tracker.comparisons())
+                .unwrap_or_default(),
+            blocks: self.block_tracker.take_snapshot(),
+            live_jobs: self.job_registry.take_snapshot(clock::now()),
+            bus: self.bus.take_snapshot(),
+        }
+    }
+
+    /// Wait until no solution has been received for `SOLUTION_FLUSH_INTERVAL` but at most
+    /// `grace_period`
+    async fn flush_solutions(&self, grace_period: time::Duration) {
+        let deadline = clock::now() + grace_period;
+        let mut last_count = self.solution_count.load(Ordering::Relaxed);
+        loop {
+            let now = clock::now();
+            if now >= deadline {
+                warn!("Hub: grace period has expired before all solutions have been flushed");
+                break;
+            }
+            delay_for(SOLUTION_FLUSH_INTERVAL.min(deadline - now)).await;
+            let count = self.solution_count.load(Ordering::Relaxed);
+            if count == last_count {
+                break;
+            }
+            last_count = count;
+        }
+    }
+
+    /// Stop mining gracefully. Backends are cut off from any new work first and the solutions
+    /// which are still being solved are given `grace_period` to reach their clients. Then all
+    /// clients are stopped and the backend is asked to power down its hardware.
+    pub async fn shutdown(&self, grace_period: time::Duration) {
+        // Wait for the restart in progress and prevent any other one
+        let _backend_builder = self.backend_builder.lock().await;
+        info!("Hub: shutting down...");
+        self.job_executor.halt().await;
+        self.flush_solutions(grace_period).await;
+
+        for group in self.client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                client.stop();
+            }
+        }
+        if let Some(store) = self.get_stats_persistence() {
+            store
+                .flush(&self.frontend, &self.client_manager, true)
+                .await;
+        }
+        if let Some(store) = self.get_digest_store() {
+            digest::sync_store(store);
+        }
+
+        let backend_teardown = self.backend_teardown.lock().await.take();
+        if let Some(backend_teardown) = backend_teardown {
+            backend_teardown.shutdown.await;
+        }
+        info!("Hub: shutdown finished");
+    }
+
+    /// Tear down all clients and the backend and build the backend again with the configuration
+    /// it has been built with. The backend registers its clients again during initialization.
+    /// Backend nodes are registered to a new registry but statistics of the frontend and all
+    /// services of the core are kept. The returned `hal::FrontendConfig` belongs to the new
+    /// backend.
+    pub async fn restart(
+        &self,
+        grace_period: time::Duration,
+    ) -> error::Result<hal::FrontendConfig> {
+        let backend_builder = self.backend_builder.lock().await;
+        let build = match backend_builder.as_ref() {
+            Some(build) => build,
+            None => Err("backend has not been built yet")?,
+        };
+        if self.job_executor.is_halted() {
+            Err("miner has been shut down")?;
+        }
+        info!("Hub: restarting...");
+        self.job_executor.suspend().await;
+        self.flush_solutions(grace_period).await;
+
+        // Lifetime totals of the removed clients are kept by the store and restored into the
+        // clients registered again by the new backend
+        let stats_persistence = self.get_stats_persistence();
+        if let Some(store) = stats_persistence.as_ref() {
+            store
+                .flush(&self.frontend, &self.client_manager, false)
+                .await;
+        }
+        self.client_manager.remove_groups().await;
+
+        let backend_teardown = self.backend_teardown.lock().await.take();
+        if let Some(backend_teardown) = backend_teardown {
+            backend_teardown.stop.await;
+        }
+        // Work solvers of the old backend must not take any work from the new clients
+        let backend_registry = mem::replace(
+            &mut *self
+                .backend_registry
+                .lock()
+                .expect("BUG: cannot lock backend registry"),
+            Arc::new(backend::Registry::new()),
+        );
+        for work_solver in backend_registry.get_work_solvers().await {
+            work::set_removed(work_solver.as_ref());
+        }
+
+        let result = build(self).await;
+        if let Some(store) = stats_persistence {
+            store.restore(&self.frontend, &self.client_manager).await;
+        }
+        self.job_executor.resume();
+        match &result {
+            Ok(_) => info!("Hub: restart finished"),
+            Err(e) => error!("Hub: restart failed: {}", e),
+        }
+        result
+    }
+
     pub async fn run(self: Arc<Self>) {
         let solution_router = self
             .solution_router
@@ -209,8 +913,43 @@ impl Core {
             .take()
             .expect("missing solution router");
 
-        tokio::spawn(solution_router.run());
-        self.job_executor.clone().run().await;
+        self.heartbeats.add(&solution_router.heartbeat);
+        tokio::spawn(async move {
+            let mut solution_router = solution_router;
+            solution_router.run().await
+        });
+        tokio::spawn(record_block_changes(
+            self.bus.subscribe(),
+            self.get_work_history().clone(),
+        ));
+        tokio::spawn(log_alerts(self.bus.subscribe()));
+        tokio::spawn(self.job_registry.clone().run());
+        if let (Some((store, utc_offset)), Some(share_baseline)) =
+            (self.digest.clone(), self.share_baseline.clone())
+        {
+            tokio::spawn(digest::run(
+                store,
+                self.client_manager.clone(),
+                utc_offset,
+                share_baseline,
+            ));
+        }
+        let stats_persistence = self
+            .stats_persistence
+            .lock()
+            .expect("BUG: cannot lock stats persistence")
+            .clone();
+        if let Some((store, interval)) = stats_persistence {
+            tokio::spawn(stats::persist::run(
+                store,
+                self.frontend.clone(),
+                self.client_manager.clone(),
+                interval,
+            ));
+        }
+        let heartbeat = Arc::new(Heartbeat::new("job executor", LOOP_STALL_TIMEOUT));
+        self.heartbeats.add(&heartbeat);
+        self.job_executor.clone().run(heartbeat).await;
     }
 }
 
@@ -218,10 +957,20 @@ impl Core {
 pub mod test {
     use super::*;
     use crate::job;
-    use crate::test_utils;
+    use crate::node::{ClientStats as _, WorkSolverStats as _};
+    use crate::stats;
+    use crate::test_utils::{self, simulation};
     use crate::Frontend;
 
-    use std::sync::Arc;
+    use bosminer_config::{
+        ClientDescriptor, ClientSharePolicy, ClientUserInfo, GroupDescriptor, LoadBalanceStrategy,
+    };
+
+    use futures::future::FutureExt;
+    use ii_async_compat::select;
+    use ii_bitcoin::MeetsTarget as _;
+
+    use std::sync::{Arc, Weak};
 
     /// Create job solver for frontend (pool) and work solver builder for backend (as we expect a
     /// hierarchical structure in backends)
@@ -290,4 +1039,521 @@ pub mod test {
         drop(job_solver);
         assert!(work_generator.generate().await.is_some());
     }
+
+    /// Test block with its own client so that its statistics are not shared with other tests
+    #[derive(Debug)]
+    struct ClientJob {
+        client: Arc<test_utils::TestClient>,
+        block: test_utils::TestBlock,
+        /// The job has not been invalidated by the client
+        valid: bool,
+    }
+
+    impl job::Bitcoin for ClientJob {
+        fn origin(&self) -> Weak<dyn node::Client> {
+            Arc::downgrade(&(self.client.clone() as Arc<dyn node::Client>))
+        }
+
+        fn version(&self) -> u32 {
+            self.block.version
+        }
+
+        fn version_mask(&self) -> u32 {
+            ii_bitcoin::BIP320_VERSION_MASK
+        }
+
+        fn previous_hash(&self) -> &ii_bitcoin::DHash {
+            &self.block.previous_hash
+        }
+
+        fn merkle_root(&self) -> &ii_bitcoin::DHash {
+            &self.block.merkle_root
+        }
+
+        fn time(&self) -> u32 {
+            self.block.time
+        }
+
+        fn bits(&self) -> u32 {
+            self.block.bits
+        }
+
+        fn target(&self) -> ii_bitcoin::Target {
+            self.block.target
+        }
+
+        fn is_valid(&self) -> bool {
+            self.valid
+        }
+    }
+
+    /// Run two jobs with known number of assignments and shares through the job executor and
+    /// check records of the jobs finalized when they have been superseded
+    #[tokio::test]
+    async fn test_job_history() {
+        let (mut job_solver, work_solver_builder) = build_solvers();
+        let client = Arc::new(test_utils::TestClient::new());
+
+        let mut work_generator = None;
+        let mut solution_sender = None;
+        work_solver_builder
+            .create_work_solver(|local_work_generator, local_solution_sender| {
+                work_generator = Some(local_work_generator);
+                solution_sender = Some(local_solution_sender);
+                Arc::new(test_utils::TestWorkSolver::new())
+            })
+            .await;
+        let mut work_generator = work_generator.unwrap();
+        let solution_sender = solution_sender.unwrap();
+
+        // (assignments, share accepted by the pool)
+        let activity = [(3, true), (2, false)];
+        for (block, (assignments, accepted)) in test_utils::TEST_BLOCKS.iter().zip(&activity) {
+            job_solver.job_sender.send(Arc::new(ClientJob {
+                client: client.clone(),
+                block: *block,
+                valid: true,
+            }));
+            // the first work is generated from the original version of the test block
+            let work = work_generator.generate().await.unwrap();
+            for _ in 1..*assignments {
+                work_generator.generate().await.unwrap();
+            }
+            solution_sender.send(work::Solution::new(
+                work,
+                test_utils::TestSolution::new(block),
+                None,
+            ));
+            let solution = job_solver.solution_receiver.receive().await.unwrap();
+
+            // simulate the protocol which submits the share to the pool
+            let job_history = client.client_stats().job_history();
+            job_history.account(solution.dyn_job(), stats::JobActivity::Submitted);
+            job_history.account(
+                solution.dyn_job(),
+                if *accepted {
+                    stats::JobActivity::Accepted
+                } else {
+                    stats::JobActivity::Rejected
+                },
+            );
+        }
+        // the last job is finalized by the next one
+        job_solver.job_sender.send(Arc::new(ClientJob {
+            client: client.clone(),
+            block: test_utils::TEST_BLOCKS[2],
+            valid: true,
+        }));
+
+        let records = client.client_stats().job_history().take_snapshot();
+        assert_eq!(records.len(), 2);
+        for (record, block) in records.iter().zip(test_utils::TEST_BLOCKS.iter()) {
+            assert_eq!(record.previous_hash, block.previous_hash);
+            assert_eq!(record.time, block.time);
+            assert_eq!(record.solutions, 1);
+            assert_eq!(record.submitted, 1);
+        }
+        assert_eq!(
+            (
+                records[0].assignments,
+                records[0].accepted,
+                records[0].rejected
+            ),
+            (3, 1, 0)
+        );
+        assert_eq!(
+            (
+                records[1].assignments,
+                records[1].accepted,
+                records[1].rejected
+            ),
+            (2, 0, 1)
+        );
+    }
+
+    /// All test blocks are real blocks so each solution must be accounted as a found block in
+    /// every node on its path
+    #[tokio::test]
+    async fn test_found_blocks() {
+        let (mut job_solver, work_solver_builder) = build_solvers();
+        let client = Arc::new(test_utils::TestClient::new());
+
+        let mut work_generator = None;
+        let mut solution_sender = None;
+        let work_solver = work_solver_builder
+            .create_work_solver(|local_work_generator, local_solution_sender| {
+                work_generator = Some(local_work_generator);
+                solution_sender = Some(local_solution_sender);
+                Arc::new(test_utils::TestWorkSolver::new())
+            })
+            .await;
+        let mut work_generator = work_generator.unwrap();
+        let solution_sender = solution_sender.unwrap();
+
+        for block in test_utils::TEST_BLOCKS.iter() {
+            let network_target = ii_bitcoin::Network::default()
+                .target_from_compact(block.bits)
+                .unwrap();
+            assert!(block.hash.meets(&network_target));
+            job_solver.job_sender.send(Arc::new(ClientJob {
+                client: client.clone(),
+                block: *block,
+                valid: true,
+            }));
+            let work = work_generator.generate().await.unwrap();
+            solution_sender.send(work::Solution::new(
+                work,
+                test_utils::TestSolution::new(block),
+                None,
+            ));
+            job_solver.solution_receiver.receive().await.unwrap();
+        }
+
+        let found_blocks = test_utils::TEST_BLOCKS.len() as u64;
+        assert_eq!(
+            *client.client_stats().found_blocks().take_snapshot(),
+            found_blocks
+        );
+        assert_eq!(
+            *work_solver
+                .work_solver_stats()
+                .found_blocks()
+                .take_snapshot(),
+            found_blocks
+        );
+    }
+
+    /// Solution not meeting the backend target is counted as hardware error of its work solver
+    /// and accounted as backend error in every node on its path but it is never submitted
+    #[tokio::test]
+    async fn test_hardware_errors() {
+        let (mut job_solver, work_solver_builder) = build_solvers();
+        let client = Arc::new(test_utils::TestClient::new());
+
+        let mut work_generator = None;
+        let mut solution_sender = None;
+        let work_solver = work_solver_builder
+            .create_work_solver(|local_work_generator, local_solution_sender| {
+                work_generator = Some(local_work_generator);
+                solution_sender = Some(local_solution_sender);
+                Arc::new(test_utils::TestWorkSolver::new())
+            })
+            .await;
+        let mut work_generator = work_generator.unwrap();
+        let solution_sender = solution_sender.unwrap();
+
+        let block = &test_utils::TEST_BLOCKS[0];
+        job_solver.job_sender.send(Arc::new(ClientJob {
+            client: client.clone(),
+            block: *block,
+            valid: true,
+        }));
+        let work = work_generator.generate().await.unwrap();
+        solution_sender.send(work::Solution::new(
+            work.clone(),
+            test_utils::TestSolution::new(block).with_nonce(block.nonce ^ 0x0100_0000),
+            None,
+        ));
+        solution_sender.send(work::Solution::new(
+            work,
+            test_utils::TestSolution::new(block),
+            None,
+        ));
+        // only the valid solution is submitted
+        let solution = job_solver.solution_receiver.receive().await.unwrap();
+        assert_eq!(solution.nonce(), block.nonce);
+
+        assert_eq!(
+            *work_solver
+                .work_solver_stats()
+                .hardware_errors()
+                .take_snapshot(),
+            1
+        );
+        let error_backend_diff = work_solver
+            .work_solver_stats()
+            .error_backend_diff()
+            .take_snapshot()
+            .await;
+        assert_eq!(error_backend_diff.solutions, 1);
+        let error_backend_diff = client
+            .client_stats()
+            .error_backend_diff()
+            .take_snapshot()
+            .await;
+        assert_eq!(error_backend_diff.solutions, 1);
+    }
+
+    /// Solutions of invalidated jobs are dropped and accounted in all nodes on their path unless
+    /// the share policy of the client allows submitting them
+    #[tokio::test]
+    async fn test_dropped_stale() {
+        let (mut job_solver, work_solver_builder) = build_solvers();
+        let frontend = work_solver_builder.to_node().clone();
+        let client = Arc::new(test_utils::TestClient::new());
+
+        let mut work_generator = None;
+        let mut solution_sender = None;
+        let work_solver = work_solver_builder
+            .create_work_solver(|local_work_generator, local_solution_sender| {
+                work_generator = Some(local_work_generator);
+                solution_sender = Some(local_solution_sender);
+                Arc::new(test_utils::TestWorkSolver::new())
+            })
+            .await;
+        let mut work_generator = work_generator.unwrap();
+        let solution_sender = solution_sender.unwrap();
+        let dropped_stale = || {
+            [
+                *client.client_stats().dropped_stale().take_snapshot(),
+                *work_solver
+                    .work_solver_stats()
+                    .dropped_stale()
+                    .take_snapshot(),
+                *frontend.work_solver_stats().dropped_stale().take_snapshot(),
+            ]
+        };
+
+        // the same block is used for all jobs so none of them is superseded by a clean job
+        let block = &test_utils::TEST_BLOCKS[0];
+        for valid in [false, true].iter() {
+            job_solver.job_sender.send(Arc::new(ClientJob {
+                client: client.clone(),
+                block: *block,
+                valid: *valid,
+            }));
+            let work = work_generator.generate().await.unwrap();
+            solution_sender.send(work::Solution::new(
+                work,
+                test_utils::TestSolution::new(block),
+                None,
+            ));
+        }
+        // only the solution of the valid job passes
+        let solution = job_solver.solution_receiver.receive().await.unwrap();
+        assert!(solution.has_valid_job());
+        assert_eq!(dropped_stale(), [1, 1, 1]);
+
+        let mut job_solver = job_solver.with_share_policy(ClientSharePolicy {
+            submit_stale: Some(true),
+            ..Default::default()
+        });
+        job_solver.job_sender.send(Arc::new(ClientJob {
+            client: client.clone(),
+            block: *block,
+            valid: false,
+        }));
+        let work = work_generator.generate().await.unwrap();
+        solution_sender.send(work::Solution::new(
+            work,
+            test_utils::TestSolution::new(block),
+            None,
+        ));
+        let solution = job_solver.solution_receiver.receive().await.unwrap();
+        assert!(!solution.has_valid_job());
+        assert_eq!(dropped_stale(), [1, 1, 1]);
+    }
+
+    /// Create enabled client connected to a mock pool
+    fn create_pool(url: &str, accept_foreign: bool, services: &job::Services) -> client::Handle {
+        let mut descriptor =
+            ClientDescriptor::create(url, &ClientUserInfo::new("test", None), true)
+                .expect("BUG: cannot create client descriptor");
+        descriptor.share_policy.accept_foreign = Some(accept_foreign);
+        client::Handle::with_node(descriptor, services, |descriptor, job_solver| {
+            Arc::new(simulation::Pool::new(
+                descriptor.get_full_url(),
+                job_solver,
+                true,
+            ))
+        })
+    }
+
+    async fn wait_for_job(client: &client::Handle) {
+        for _ in 0..1000 {
+            if client.is_running() && client.get_last_job().await.is_some() {
+                return;
+            }
+            delay_for(time::Duration::from_millis(1)).await;
+        }
+        panic!("BUG: client has not received any job");
+    }
+
+    async fn accepted_solutions(client: &client::Handle) -> u64 {
+        client.stats().accepted().take_snapshot().await.solutions
+    }
+
+    /// Build router of solutions for group 'A' with pools 'a1' and 'a2' and group 'B' with pool
+    /// 'b1' which is the only one accepting solutions of foreign jobs
+    async fn build_router(
+        policy: SolutionRoutingPolicy,
+    ) -> (SolutionRouter, Vec<Arc<client::Handle>>) {
+        let client_manager = client::Manager::new(1);
+        let mut clients = vec![];
+        for (name, pools) in &[
+            ("A", vec![("drain://a1", false), ("drain://a2", false)]),
+            ("B", vec![("drain://b1", true)]),
+        ] {
+            let group = client_manager
+                .create_group(GroupDescriptor::new(
+                    name.to_string(),
+                    false,
+                    LoadBalanceStrategy::Quota(1),
+                ))
+                .await
+                .expect("BUG: cannot create group");
+            for (url, accept_foreign) in pools {
+                let client = group
+                    .push_client(create_pool(url, *accept_foreign, client_manager.services()))
+                    .await;
+                wait_for_job(&client).await;
+                clients.push(client);
+            }
+        }
+
+        let (engine_sender, _) = work::engine_channel(EventHandler);
+        let (_, solution_receiver) = mpsc::unbounded();
+        let orphan_routing = Arc::new(OrphanRouting::default());
+        orphan_routing.set_policy(policy);
+        let router = SolutionRouter::new(
+            Arc::new(client::JobExecutor::new(
+                Arc::new(Frontend::new()),
+                engine_sender,
+                client_manager.clone(),
+            )),
+            client_manager,
+            solution_receiver,
+            Arc::new(AtomicU64::new(0)),
+            orphan_routing,
+            Default::default(),
+        );
+        (router, clients)
+    }
+
+    /// Remove pool 'a1' with a solution of its job in flight and check where the solution ends up
+    #[tokio::test]
+    async fn test_orphan_routing() {
+        // policy, index of the destination client, counters (dropped, same group, active)
+        let cases = [
+            (SolutionRoutingPolicy::Drop, None, (1, 0, 0)),
+            (SolutionRoutingPolicy::RerouteSameGroup, Some(1), (0, 1, 0)),
+            (SolutionRoutingPolicy::RerouteActive, Some(2), (0, 0, 1)),
+        ];
+        for (policy, destination_idx, counters) in cases.iter() {
+            let (router, clients) = build_router(*policy).await;
+
+            let block = &test_utils::TEST_BLOCKS[0];
+            let job = clients[0]
+                .get_last_job()
+                .await
+                .expect("BUG: missing pool job");
+            let midstate = work::Midstate {
+                version: block.version,
+                state: block.midstate,
+            };
+            let solution = work::Solution::new(
+                work::Assignment::new(job, vec![midstate], block.time),
+                test_utils::TestSolution::new(block),
+                None,
+            );
+
+            let descriptor = clients[0].descriptor().await;
+            router
+                .client_manager
+                .remove_client(&descriptor)
+                .await
+                .expect("BUG: cannot remove client");
+            assert!(router
+                .job_executor
+                .get_solution_sender(&solution)
+                .await
+                .is_none());
+
+            let destination = router.route_orphan(solution).await;
+            let routing = &router.orphan_routing;
+            assert_eq!(
+                (
+                    *routing.dropped.take_snapshot(),
+                    *routing.rerouted_same_group.take_snapshot(),
+                    *routing.rerouted_active.take_snapshot()
+                ),
+                *counters,
+                "{:?}",
+                policy
+            );
+            let destination_idx = match destination_idx {
+                Some(idx) => *idx,
+                None => {
+                    assert!(destination.is_none());
+                    continue;
+                }
+            };
+            let destination = destination.expect("BUG: solution has not been rerouted");
+            assert!(Arc::ptr_eq(&destination, &clients[destination_idx]));
+
+            // the mock pool accepts all submitted solutions
+            for _ in 0..1000 {
+                if accepted_solutions(&destination).await > 0 {
+                    break;
+                }
+                delay_for(time::Duration::from_millis(1)).await;
+            }
+            assert_eq!(accepted_solutions(&destination).await, 1);
+        }
+    }
+
+    /// Race cancellation of the router against delivery of solutions and check that every
+    /// received solution is routed exactly once
+    #[tokio::test]
+    async fn test_router_cancellation() {
+        const SOLUTIONS: u64 = 1000;
+
+        let (mut router, clients) = build_router(SolutionRoutingPolicy::Drop).await;
+        let (solution_sender, solution_receiver) = mpsc::unbounded();
+        router.solution_receiver = solution_receiver;
+
+        let block = &test_utils::TEST_BLOCKS[0];
+        let job = clients[0]
+            .get_last_job()
+            .await
+            .expect("BUG: missing pool job");
+        let midstate = work::Midstate {
+            version: block.version,
+            state: block.midstate,
+        };
+        let solution = work::Solution::new(
+            work::Assignment::new(job, vec![midstate], block.time),
+            test_utils::TestSolution::new(block),
+            None,
+        );
+        // all solutions are orphans which are counted when the policy drops them
+        let descriptor = clients[0].descriptor().await;
+        router
+            .client_manager
+            .remove_client(&descriptor)
+            .await
+            .expect("BUG: cannot remove client");
+
+        for i in 0..SOLUTIONS {
+            solution_sender
+                .unbounded_send(solution.clone())
+                .expect("BUG: cannot send solution");
+            // cancel the router after a varying number of yields
+            let yields = async {
+                for _ in 0..i % 4 {
+                    tokio::task::yield_now().await;
+                }
+            };
+            select! {
+                _ = router.run().fuse() => panic!("BUG: router has finished prematurely"),
+                _ = yields.fuse() => {}
+            }
+        }
+        drop(solution_sender);
+        router.run().await;
+
+        assert!(router.pending.is_none());
+        assert_eq!(router.solution_count.load(Ordering::Relaxed), SOLUTIONS);
+        assert_eq!(*router.orphan_routing.dropped.take_snapshot(), SOLUTIONS);
+    }
 }
@@ -20,26 +20,46 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+pub mod archive;
+pub mod block;
+pub mod registry;
+
 use ii_logging::macros::*;
 
 use ii_bitcoin::{HashTrait as _, MeetsTarget};
 
+use crate::bus;
+use crate::clock;
 use crate::job;
+use crate::log_id;
 use crate::node;
+use crate::share_policy;
 use crate::stats::{self, DiffTargetType};
 use crate::work;
 
+use bosminer_config::ClientSharePolicy;
+
 use futures::channel::mpsc;
+use futures::future::BoxFuture;
 use futures::stream::StreamExt;
 use ii_async_compat::futures;
 
 use std::convert::TryInto;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::mem;
 use std::sync::{Arc, Weak};
 
 use downcast_rs::{impl_downcast, Downcast};
 
+/// Transactions data from which the merkle root of the job has been computed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coinbase {
+    /// Complete serialized coinbase transaction
+    pub tx: Vec<u8>,
+    /// Hashes of transactions which are combined with the coinbase into the merkle root
+    pub merkle_branch: Vec<Vec<u8>>,
+}
+
 /// Represents interface for Bitcoin job with access to block header from which the new work will be
 /// generated. The trait is bound to Downcast which enables connect work solution with original job
 /// and hide protocol specific details.
@@ -60,13 +80,30 @@ pub trait Bitcoin: Debug + Downcast + Send + Sync {
     fn max_time(&self) -> u32 {
         self.time()
     }
+    /// Maximal number of seconds the timestamp may be rolled forward from `time` as advertised
+    /// by the pool. Zero means that the pool has not advertised any limit.
+    fn ntime_roll_limit(&self) -> u32 {
+        0
+    }
     /// Current network target in compact format (network difficulty)
     /// https://en.bitcoin.it/wiki/Difficulty
     fn bits(&self) -> u32;
-    /// Current pool/protocol target used for solution checking
+    /// Pool/protocol target used for solution checking. It has to be fixed when the job is
+    /// constructed and a difficulty change has to be applied to new jobs only. The target is
+    /// snapshotted into each assignment when the work is generated and a target which changes
+    /// afterwards would classify solutions of the same job inconsistently.
     fn target(&self) -> ii_bitcoin::Target;
     /// Checks if job is still valid for mining
     fn is_valid(&self) -> bool;
+    /// Identifier of the job assigned by the remote server
+    fn id(&self) -> Option<u32> {
+        None
+    }
+    /// Coinbase transaction and merkle branch of the job when the protocol provides them (it is
+    /// used only for archiving of block candidates)
+    fn coinbase(&self) -> Option<Coinbase> {
+        None
+    }
 
     /// Extract least-significant word of merkle root that goes to chunk2 of SHA256
     /// The word is interpreted as a little endian number.
@@ -82,6 +119,37 @@ pub trait Bitcoin: Debug + Downcast + Send + Sync {
 }
 impl_downcast!(Bitcoin);
 
+/// Components shared by all clients of one mining core. Every core creates its own ones so that
+/// jobs, blocks and events of different cores are never mixed.
+#[derive(Debug, Clone)]
+pub struct Services {
+    pub bus: Arc<bus::Bus>,
+    /// Blocks seen in jobs of all clients
+    pub block_tracker: Arc<block::Tracker>,
+    /// Solutions of all clients which meet the network target
+    pub block_archive: Arc<archive::Archive>,
+    /// Jobs of all clients which are still referenced
+    pub job_registry: Arc<registry::Registry>,
+}
+
+impl Services {
+    pub fn new() -> Self {
+        let bus = Arc::new(bus::Bus::new());
+        Self {
+            block_tracker: Arc::new(block::Tracker::new().with_bus(bus.clone())),
+            block_archive: Arc::new(archive::Archive::new()),
+            job_registry: Arc::new(registry::Registry::new()),
+            bus,
+        }
+    }
+}
+
+impl Default for Services {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Compound object for job submission and solution reception intended to be passed to
 /// protocol handler
 pub struct Solver {
@@ -94,33 +162,118 @@ impl Solver {
         engine_sender: Arc<work::EngineSender>,
         solution_receiver: mpsc::UnboundedReceiver<work::Solution>,
     ) -> Self {
+        // both halves of the solver belong to the same client
+        let client_name = log_id::ClientName::next();
         Self {
-            job_sender: Sender::new(engine_sender),
-            solution_receiver: SolutionReceiver::new(solution_receiver),
+            job_sender: Sender::new(engine_sender, client_name),
+            solution_receiver: SolutionReceiver::new(solution_receiver, client_name),
         }
     }
+
+    /// Short name of the client the solver has been created for
+    #[inline]
+    pub fn client_name(&self) -> log_id::ClientName {
+        self.job_sender.client_name
+    }
+
+    /// Use constants of specified Bitcoin `network` for checking jobs and solutions
+    pub fn with_network(mut self, network: ii_bitcoin::Network) -> Self {
+        self.job_sender.network = network;
+        self.solution_receiver.network = network;
+        self
+    }
+
+    /// Share `services` of the core with its other clients
+    pub fn with_services(self, services: &Services) -> Self {
+        let mut solver = self
+            .with_block_tracker(services.block_tracker.clone())
+            .with_job_registry(services.job_registry.clone())
+            .with_block_archive(services.block_archive.clone());
+        solver.job_sender.bus = services.bus.clone();
+        solver
+    }
+
+    /// Bus where the client publishes its events
+    #[inline]
+    pub fn bus(&self) -> Arc<bus::Bus> {
+        self.job_sender.bus.clone()
+    }
+
+    /// Archive where the client records responses to its block candidates
+    #[inline]
+    pub fn block_archive(&self) -> Arc<archive::Archive> {
+        self.solution_receiver.block_archive.clone()
+    }
+
+    /// Detect block changes in the jobs of this client with the `block_tracker` instead of its
+    /// own one
+    pub fn with_block_tracker(mut self, block_tracker: Arc<block::Tracker>) -> Self {
+        self.job_sender.block_tracker = block_tracker;
+        self
+    }
+
+    /// Account live jobs of this client in the `job_registry` instead of its own one
+    pub fn with_job_registry(mut self, job_registry: Arc<registry::Registry>) -> Self {
+        self.job_sender.job_registry = job_registry;
+        self
+    }
+
+    /// Archive block candidates of this client to the `block_archive` instead of its own one
+    pub fn with_block_archive(mut self, block_archive: Arc<archive::Archive>) -> Self {
+        self.solution_receiver.block_archive = block_archive;
+        self
+    }
+
+    /// Decide about submission of solutions of outdated work according to `share_policy`
+    pub fn with_share_policy(mut self, share_policy: ClientSharePolicy) -> Self {
+        self.solution_receiver.solution_filter =
+            work::SolutionFilter::new(share_policy.duplicate_window());
+        self.solution_receiver.share_policy = share_policy;
+        self
+    }
 }
 
 /// This is the entrypoint for new jobs and updates into processing.
 /// Typically the mining protocol handler will inject new jobs through it
 pub struct Sender {
     engine_sender: Arc<work::EngineSender>,
+    network: ii_bitcoin::Network,
+    client_name: log_id::ClientName,
+    /// Tracker of blocks seen in jobs of all clients
+    block_tracker: Arc<block::Tracker>,
+    /// Registry of live jobs of all clients
+    job_registry: Arc<registry::Registry>,
+    bus: Arc<bus::Bus>,
 }
 
 impl Sender {
-    pub fn new(engine_sender: Arc<work::EngineSender>) -> Self {
-        Self { engine_sender }
+    pub fn new(engine_sender: Arc<work::EngineSender>, client_name: log_id::ClientName) -> Self {
+        Self {
+            engine_sender,
+            network: Default::default(),
+            client_name,
+            block_tracker: Arc::new(block::Tracker::new()),
+            job_registry: Arc::new(registry::Registry::new()),
+            bus: Arc::new(bus::Bus::new()),
+        }
+    }
+
+    #[inline]
+    pub fn client_name(&self) -> log_id::ClientName {
+        self.client_name
     }
 
     /// Check if the job has valid attributes
     fn job_sanity_check(
+        &self,
         job: &Arc<dyn job::Bitcoin>,
         origin: &Option<Arc<dyn node::Client>>,
     ) -> bool {
         let mut valid = true;
-        if let Err(msg) = ii_bitcoin::Target::from_compact(job.bits()) {
+        if let Err(msg) = self.network.target_from_compact(job.bits()) {
             error!(
-                "Invalid job's nBits ({}) received from '{}'",
+                "{} Invalid job's nBits ({}) received from '{}'",
+                log_id::Prefix::job(job.as_ref()).with_client(self.client_name),
                 msg,
                 origin
                     .as_ref()
@@ -134,19 +287,34 @@ impl Sender {
 
     pub fn send(&self, job: Arc<dyn job::Bitcoin>) {
         let origin = job.origin().upgrade();
-        if !Self::job_sanity_check(&job, &origin) {
+        if !self.job_sanity_check(&job, &origin) {
             origin.map(|origin| origin.client_stats().invalid_jobs().inc());
             return;
         }
 
         // send only jobs with correct data
+        let prefix = log_id::Prefix::job(job.as_ref()).with_client(self.client_name);
         if let Some(origin) = origin {
-            origin.client_stats().valid_jobs().inc();
-            info!("--- broadcasting new job ---");
-            self.engine_sender.broadcast_job(job);
+            let client_stats = origin.client_stats();
+            client_stats.valid_jobs().inc();
+            self.block_tracker
+                .observe(self.client_name, job.as_ref(), self.network);
+            // the new job supersedes the previous one
+            let now = clock::now();
+            client_stats.job_history().start(&job, now);
+            self.job_registry.register(self.client_name, &job, now);
+            let (_, generation) = self.engine_sender.broadcast_job(job);
+            self.bus.publish(bus::Event::JobChanged {
+                client: self.client_name,
+                generation,
+            });
+            info!(
+                "{} --- broadcasting new job ---",
+                prefix.with_generation(generation)
+            );
         } else {
             // Origin has been removed and no one will receive any solution
-            info!("--- discarding job ---");
+            info!("{} --- discarding job ---", prefix);
         }
     }
 
@@ -156,21 +324,70 @@ impl Sender {
     }
 }
 
+/// Solution taken out of the channel by `SolutionReceiver` whose accounting has not finished
+struct InFlight {
+    solution: work::Solution,
+    /// The solution meets the job target so it is a candidate for submission
+    submit: bool,
+    accounting: BoxFuture<'static, ()>,
+}
+
+impl Debug for InFlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InFlight")
+            .field("solution", &self.solution)
+            .field("submit", &self.submit)
+            .finish()
+    }
+}
+
 /// Receives `work::Solution` via a channel and filters only solutions that meet the client/pool
 /// specified target
 #[derive(Debug)]
 pub struct SolutionReceiver {
     solution_channel: mpsc::UnboundedReceiver<work::Solution>,
+    network: ii_bitcoin::Network,
+    share_policy: ClientSharePolicy,
+    /// Recently submitted solutions of this client
+    solution_filter: work::SolutionFilter,
+    client_name: log_id::ClientName,
+    /// Archive of solutions which meet the network target
+    block_archive: Arc<archive::Archive>,
+    /// Solution received from the channel which has not been accounted yet
+    in_flight: Option<InFlight>,
 }
 
 impl SolutionReceiver {
-    pub fn new(solution_channel: mpsc::UnboundedReceiver<work::Solution>) -> Self {
-        Self { solution_channel }
+    pub fn new(
+        solution_channel: mpsc::UnboundedReceiver<work::Solution>,
+        client_name: log_id::ClientName,
+    ) -> Self {
+        let share_policy = ClientSharePolicy::default();
+        Self {
+            solution_channel,
+            network: Default::default(),
+            solution_filter: work::SolutionFilter::new(share_policy.duplicate_window()),
+            share_policy,
+            client_name,
+            block_archive: Arc::new(archive::Archive::new()),
+            in_flight: None,
+        }
+    }
+
+    #[inline]
+    pub fn client_name(&self) -> log_id::ClientName {
+        self.client_name
+    }
+
+    /// Prefix of log records related to the `solution` received by this client
+    fn prefix(&self, solution: &work::Solution) -> log_id::Prefix {
+        log_id::Prefix::solution(solution).with_client(self.client_name)
     }
 
-    fn trace_share(solution: &work::Solution, target: &ii_bitcoin::Target) {
+    fn trace_share(&self, solution: &work::Solution, target: &ii_bitcoin::Target) {
         info!(
-            "----- Found share within current job's difficulty (diff={}) target range -----",
+            "{} ----- Found share within current job's difficulty (diff={}) target range -----",
+            self.prefix(solution),
             target.get_difficulty()
         );
         info!(
@@ -186,39 +403,161 @@ impl SolutionReceiver {
         );
     }
 
+    fn trace_block(&self, solution: &work::Solution, hash: &ii_bitcoin::DHash) {
+        info!("{} ===== BLOCK FOUND =====", self.prefix(solution));
+        info!(
+            "header={}",
+            hex::encode(&solution.get_block_header().into_bytes()[..])
+        );
+        info!("hash={:x}", hash);
+        info!(
+            "origin={:?}",
+            solution.origin().upgrade().map(|x| x.to_string())
+        );
+    }
+
+    /// Classify the solution taken out of the channel and start its accounting
+    fn start_accounting(&self, solution: work::Solution) -> InFlight {
+        let path = solution.path();
+        let time = solution.timestamp();
+        let hash = solution.hash();
+        let job_target = solution.job_target();
+        let network_target = solution.network_target(self.network);
+
+        // compare block hash for given solution with all targets
+        // NOTE: network target is not always harder than job target (e.g. on regtest network)
+        // TODO: create tests for solution validation with all difficulty variants
+        let (met_diff_target_type, submit) = if hash.meets(&network_target) {
+            self.trace_block(&solution, hash);
+            self.block_archive.archive(&solution, &network_target);
+            (Some(DiffTargetType::Network), true)
+        } else if hash.meets(job_target) {
+            (Some(DiffTargetType::Job), true)
+        } else if hash.meets(solution.backend_target()) {
+            // skip submitting the solution as we've only met backend difficulty
+            (Some(DiffTargetType::Backend), false)
+        } else {
+            // skip submitting the solution as this is a backend error
+            (None, false)
+        };
+
+        let accounted_solution = solution.clone();
+        let accounting = Box::pin(async move {
+            match met_diff_target_type {
+                Some(met_diff_target_type) => {
+                    stats::account_valid_solution(
+                        &path,
+                        &accounted_solution,
+                        time,
+                        met_diff_target_type,
+                    )
+                    .await
+                }
+                None => {
+                    stats::account_error_backend_diff(
+                        &path,
+                        accounted_solution.backend_target(),
+                        time,
+                    )
+                    .await
+                }
+            }
+        });
+        InFlight {
+            solution,
+            submit,
+            accounting,
+        }
+    }
+
+    /// Apply the share policy and filter of duplicate shares to the accounted solution
+    fn filter(&mut self, solution: work::Solution) -> Option<work::Solution> {
+        let time = solution.timestamp();
+
+        // All decisions about submission of outdated work are made by the share policy
+        let origin = solution.origin().upgrade();
+        let job_history = origin
+            .as_ref()
+            .map(|origin| origin.client_stats().job_history());
+        if let Some(job_history) = job_history {
+            job_history.account(solution.dyn_job(), stats::JobActivity::Solution);
+        }
+        // Staleness is judged by the time when the solution has been found
+        let context = share_policy::Context::new(&solution, job_history, time);
+        let decision = share_policy::decide(&context, &self.share_policy);
+        if let Some(origin) = origin.as_ref() {
+            origin.client_stats().share_policy().account(decision);
+        }
+        match decision {
+            share_policy::Decision::Submit => {}
+            share_policy::Decision::SubmitFlagged(reason) => {
+                info!(
+                    "{} Submitting share despite {} ({:?})",
+                    self.prefix(&solution),
+                    reason,
+                    context
+                );
+            }
+            share_policy::Decision::Drop(reason) => {
+                if reason == share_policy::Reason::Stale {
+                    for node in solution.path().iter() {
+                        node.mining_stats().dropped_stale().inc();
+                    }
+                }
+                debug!(
+                    "{} Dropping share due to {} ({:?})",
+                    self.prefix(&solution),
+                    reason,
+                    context
+                );
+                return None;
+            }
+        }
+        if !self.solution_filter.insert(&solution) {
+            let decision = share_policy::Decision::Drop(share_policy::Reason::Duplicate);
+            if let Some(origin) = origin.as_ref() {
+                origin.client_stats().share_policy().account(decision);
+            }
+            warn!(
+                "{} Dropping duplicate share (nonce={:08x}, ntime={:08x}, version={:08x})",
+                self.prefix(&solution),
+                solution.nonce(),
+                solution.time(),
+                solution.version()
+            );
+            return None;
+        }
+        self.trace_share(&solution, solution.job_target());
+        Some(solution)
+    }
+
+    /// Returns the next solution which should be submitted to the remote server.
+    ///
+    /// The method is cancellation safe. It is usually polled in `select!` together with other
+    /// events and its future is dropped whenever another branch completes first. The solution
+    /// taken out of the channel is kept in `in_flight` together with its pending accounting
+    /// which is resumed by the next call. The solution leaves `in_flight` only after the
+    /// accounting has finished and there is no await point between that and returning it.
     pub async fn receive(&mut self) -> Option<work::Solution> {
-        while let Some(solution) = self.solution_channel.next().await {
-            let path = solution.path();
-            let time = solution.timestamp();
-            let hash = solution.hash();
-            let job_target = solution.job_target();
-
-            // compare block hash for given solution with all targets
-            // TODO: create tests for solution validation with all difficulty variants
-            assert!(&solution.network_target() <= job_target);
-            if hash.meets(&solution.network_target()) {
-                stats::account_valid_solution(&path, &solution, time, DiffTargetType::Network)
-                    .await;
-            } else if hash.meets(&job_target) {
-                stats::account_valid_solution(&path, &solution, time, DiffTargetType::Job).await;
-            } else if hash.meets(solution.backend_target()) {
-                stats::account_valid_solution(&path, &solution, time, DiffTargetType::Backend)
-                    .await;
-                // skip submitting the solution as we've only met backend difficulty
-                continue;
-            } else {
-                stats::account_error_backend_diff(&path, &solution.backend_target(), time).await;
-                // skip submitting the solution as this is a backend error
+        loop {
+            if self.in_flight.is_none() {
+                let solution = self.solution_channel.next().await?;
+                self.in_flight = Some(self.start_accounting(solution));
+            }
+            if let Some(in_flight) = self.in_flight.as_mut() {
+                in_flight.accounting.as_mut().await;
+            }
+            let in_flight = self
+                .in_flight
+                .take()
+                .expect("BUG: missing solution in flight");
+            if !in_flight.submit {
                 continue;
             }
-
-            if solution.has_valid_job() {
-                // TODO: Account solution to Discard meter
-                Self::trace_share(&solution, &job_target);
+            if let Some(solution) = self.filter(in_flight.solution) {
                 return Some(solution);
             }
         }
-        None
     }
 
     /// Empty all buffered solutions without blocking. This is to prevent the client from submitting
@@ -226,6 +565,177 @@ impl SolutionReceiver {
     /// TODO: We should review this regularly as there may be extensions in the mining protocol that
     /// may allow resume a mining session
     pub fn flush(&mut self) {
+        self.in_flight.take();
         while let Ok(Some(_)) = self.solution_channel.try_next() {}
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{self, TestBlockBuilder as _};
+
+    use futures::future::FutureExt;
+    use ii_async_compat::{select, tokio};
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Accounting of the solution in flight which is suspended `yields` times before it finishes
+    fn build_accounting(yields: usize, finished: Arc<AtomicUsize>) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            for _ in 0..yields {
+                tokio::task::yield_now().await;
+            }
+            finished.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+
+    /// Race cancellation of `receive` against delivery of solutions. No solution can be lost
+    /// and the accounting of the solution in flight cannot be restarted.
+    #[tokio::test]
+    async fn test_receive_cancellation() {
+        const ITERATIONS: usize = 1000;
+
+        for iteration in 0..ITERATIONS {
+            let (solution_sender, solution_channel) = mpsc::unbounded();
+            let mut receiver = SolutionReceiver::new(solution_channel, log_id::ClientName::next());
+            let finished = Arc::new(AtomicUsize::new(0));
+
+            receiver.in_flight = Some(InFlight {
+                solution: (&test_utils::TEST_BLOCKS[0]).into(),
+                submit: true,
+                accounting: build_accounting(iteration % 8, finished.clone()),
+            });
+            for block in test_utils::TEST_BLOCKS[1..].iter() {
+                solution_sender
+                    .unbounded_send(block.into())
+                    .expect("BUG: cannot send solution");
+            }
+            drop(solution_sender);
+
+            let mut received = 0;
+            loop {
+                // the yielding branch cancels `receive` whenever it has been suspended
+                select! {
+                    solution = receiver.receive().fuse() => match solution {
+                        Some(_) => received += 1,
+                        None => break,
+                    },
+                    _ = tokio::task::yield_now().fuse() => {}
+                }
+            }
+            assert_eq!(received, test_utils::TEST_BLOCKS.len());
+            assert_eq!(finished.load(Ordering::Relaxed), 1);
+            assert!(receiver.in_flight.is_none());
+        }
+    }
+
+    /// Solution meeting the network target is archived before it is returned for submission
+    /// and the response of the pool is added to the archive later
+    #[tokio::test]
+    async fn test_block_candidate_archive() {
+        let dir = std::env::temp_dir().join(format!("bosminer-candidates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let block_archive = Arc::new(archive::Archive::new());
+        block_archive
+            .set_dir(&dir)
+            .expect("BUG: cannot create archive");
+
+        let (solution_sender, solution_channel) = mpsc::unbounded();
+        let mut solver = Solver::new(Arc::new(work::EngineSender::new(None)), solution_channel)
+            .with_block_archive(block_archive.clone());
+        // real block mined for the relaxed job target (difficulty 1) meets the network target
+        let block = test_utils::TEST_BLOCKS[0].change_target(Default::default());
+        solution_sender
+            .unbounded_send((&block).into())
+            .expect("BUG: cannot send solution");
+        let solution = solver
+            .solution_receiver
+            .receive()
+            .await
+            .expect("BUG: missing solution");
+
+        let entries = block_archive.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].candidate.hash, format!("{:x}", block.hash));
+        assert_eq!(
+            entries[0].candidate.header,
+            hex::encode(&block.header_bytes[..])
+        );
+        assert_eq!(entries[0].response, archive::Response::Unknown);
+
+        // the connection has been lost before the response
+        drop(solution_sender);
+        assert!(solver.solution_receiver.receive().await.is_none());
+        assert_eq!(block_archive.list()[0].response, archive::Response::Unknown);
+
+        block_archive.record_response(&solution, archive::Response::Accepted);
+        assert_eq!(
+            block_archive.list()[0].response,
+            archive::Response::Accepted
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Two clients receive the same sequence of jobs and each block change is reported only by
+    /// the faster one
+    #[test]
+    fn test_block_tracking() {
+        let block_tracker = Arc::new(block::Tracker::new());
+        let mut events = block_tracker.subscribe();
+        let solvers: Vec<_> = (0..2)
+            .map(|_| {
+                let (_, solution_channel) = mpsc::unbounded();
+                Solver::new(Arc::new(work::EngineSender::new(None)), solution_channel)
+                    .with_block_tracker(block_tracker.clone())
+            })
+            .collect();
+
+        for block in test_utils::TEST_BLOCKS.iter() {
+            for solver in solvers.iter() {
+                solver.job_sender.send(Arc::new(*block));
+            }
+        }
+        // the first block only starts the session
+        for block in test_utils::TEST_BLOCKS[1..].iter() {
+            let change = events.try_recv().expect("missing block change");
+            assert_eq!(change.previous_hash, block.previous_hash);
+            assert_eq!(change.client_name, solvers[0].client_name());
+        }
+        assert!(events.try_recv().is_err());
+        assert_eq!(
+            block_tracker.take_snapshot().changes,
+            test_utils::TEST_BLOCKS.len() as u64 - 1
+        );
+    }
+
+    /// Superseded job kept in a channel which is never read is reported as leaked while the
+    /// other superseded jobs are released
+    #[test]
+    fn test_leaked_job() {
+        let job_registry = Arc::new(registry::Registry::new());
+        let (_, solution_channel) = mpsc::unbounded();
+        let solver = Solver::new(Arc::new(work::EngineSender::new(None)), solution_channel)
+            .with_job_registry(job_registry.clone());
+
+        let (leak_sender, _forgotten_receiver) = mpsc::unbounded();
+        let leaked_job: Arc<dyn Bitcoin> = Arc::new(test_utils::TEST_BLOCKS[0]);
+        leak_sender
+            .unbounded_send(leaked_job.clone())
+            .expect("BUG: cannot send job");
+        solver.job_sender.send(leaked_job);
+        for block in test_utils::TEST_BLOCKS[1..].iter() {
+            solver.job_sender.send(Arc::new(*block));
+        }
+
+        let now = clock::now() + registry::Registry::LEAK_THRESHOLD;
+        let leaks = job_registry.reap(now);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].client_name, solver.client_name());
+        let snapshot = job_registry.take_snapshot(now);
+        // the engine sender does not keep any job so only the leaked one is referenced
+        assert_eq!(snapshot.live_jobs, 1);
+        assert_eq!(snapshot.superseded_jobs, 1);
+        assert_eq!(snapshot.leaked_jobs, 1);
+    }
+}
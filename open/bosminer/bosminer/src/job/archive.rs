@@ -0,0 +1,582 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Cold-storage archive of block candidates (solutions which meet the network target).
+//!
+//! The complete context of the candidate is archived as soon as the solution is received from
+//! the backend and before it is submitted so the record does not depend on the pool at all.
+//! Every event is written to its own file in the archive directory and the files are never
+//! modified afterwards: the candidate is written first and the response of the pool is written
+//! to another file once it is received. A candidate without any response file has been lost
+//! together with the connection (or the miner has been restarted) and its response is unknown.
+//! Each file is synced to the disk together with the directory entry by a blocking task so that
+//! the solution is never delayed by the disk. Records which have not been written yet are kept in
+//! memory and they are listed together with the archived ones.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+use crate::work;
+
+use ii_async_compat::tokio;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Version of the format of archived records
+pub const FORMAT_VERSION: u32 = 1;
+
+const CANDIDATE_SUFFIX: &str = ".candidate.json";
+const RESPONSE_SUFFIX: &str = ".response.json";
+
+fn unix_millis(time: time::SystemTime) -> u64 {
+    time.duration_since(time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Response of the pool to the submitted candidate
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Accepted,
+    Rejected,
+    /// Rejected as a share of an outdated job
+    Stale,
+    /// There has not been any response (it is never written to the archive)
+    Unknown,
+}
+
+/// Complete context of the block candidate. Binary data are hex encoded and hashes and targets
+/// are written in the usual (big endian) notation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub format: u32,
+    pub hash: String,
+    /// Serialized block header (80 bytes)
+    pub header: String,
+    /// Identification of the pool which has supplied the job
+    pub pool: String,
+    /// Local identifier of the job
+    pub job_id: Option<u32>,
+    /// Coinbase transaction when the protocol provides it
+    pub coinbase: Option<String>,
+    #[serde(default)]
+    pub merkle_branch: Vec<String>,
+    pub network_target: String,
+    pub job_target: String,
+    pub backend_target: String,
+    /// Timestamp of the block header
+    pub ntime: u32,
+    /// Unix time in milliseconds when the candidate has been archived
+    pub archived: u64,
+}
+
+impl Candidate {
+    fn new(solution: &work::Solution, network_target: &ii_bitcoin::Target) -> Self {
+        let job = solution.dyn_job();
+        let coinbase = job.coinbase();
+
+        Self {
+            format: FORMAT_VERSION,
+            hash: format!("{:x}", solution.hash()),
+            header: hex::encode(&solution.get_block_header().into_bytes()[..]),
+            pool: solution
+                .origin()
+                .upgrade()
+                .map(|origin| origin.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            job_id: job.id(),
+            coinbase: coinbase.as_ref().map(|coinbase| hex::encode(&coinbase.tx)),
+            merkle_branch: coinbase
+                .map(|coinbase| coinbase.merkle_branch.iter().map(hex::encode).collect())
+                .unwrap_or_default(),
+            network_target: format!("{:x}", network_target),
+            job_target: format!("{:x}", solution.job_target()),
+            backend_target: format!("{:x}", solution.backend_target()),
+            ntime: solution.time(),
+            archived: unix_millis(clock::system_time()),
+        }
+    }
+}
+
+/// Response of the pool to the candidate with `hash`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ResponseRecord {
+    format: u32,
+    hash: String,
+    response: Response,
+    /// Unix time in milliseconds when the response has been received
+    received: u64,
+}
+
+/// Archived candidate with the response of the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub candidate: Candidate,
+    pub response: Response,
+    /// Unix time in milliseconds when the response has been received
+    pub received: Option<u64>,
+}
+
+/// Candidate archived by this process until its response is written
+#[derive(Debug)]
+struct PendingRecord {
+    candidate: Candidate,
+    /// The candidate file has been written and the response can be written right away
+    written: bool,
+    /// Response which has not been written yet. When it is received before the candidate has
+    /// been written, it is back-filled by the task writing the candidate.
+    response: Option<ResponseRecord>,
+}
+
+/// Directory with archived block candidates. The archive is disabled until its directory is set.
+#[derive(Debug, Default)]
+pub struct Archive {
+    dir: StdMutex<Option<PathBuf>>,
+    /// Candidates archived by this process until their response is written (indexed by hash)
+    pending: Arc<StdMutex<HashMap<String, PendingRecord>>>,
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock_dir(&self) -> StdMutexGuard<'_, Option<PathBuf>> {
+        self.dir.lock().expect("BUG: cannot lock archive directory")
+    }
+
+    fn lock_pending(
+        pending: &StdMutex<HashMap<String, PendingRecord>>,
+    ) -> StdMutexGuard<'_, HashMap<String, PendingRecord>> {
+        pending.lock().expect("BUG: cannot lock pending candidates")
+    }
+
+    /// Enable archiving to `dir` which is created when it does not exist
+    pub fn set_dir<P: Into<PathBuf>>(&self, dir: P) -> io::Result<()> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        self.lock_dir().replace(dir);
+        Ok(())
+    }
+
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.lock_dir().clone()
+    }
+
+    /// Create a new file `name` with serialized `record` and make sure that both its content and
+    /// its directory entry reach the disk. Existing files are never overwritten.
+    fn write_record<T: Serialize>(dir: &Path, name: &str, record: &T) -> io::Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(name))?;
+        serde_json::to_writer_pretty(&mut file, record)?;
+        file.write_all(b"\n")?;
+        file.sync_all()?;
+        fs::File::open(dir)?.sync_all()
+    }
+
+    /// Run blocking `write` of records out of the async runtime when it is available
+    fn spawn_write<F>(write: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match tokio::runtime::Handle::try_current() {
+            Ok(_) => {
+                tokio::task::spawn_blocking(write);
+            }
+            Err(_) => write(),
+        }
+    }
+
+    /// Write the response and forget the candidate which is complete then
+    fn write_response(
+        dir: &Path,
+        record: &ResponseRecord,
+        pending: &StdMutex<HashMap<String, PendingRecord>>,
+    ) {
+        let name = format!("{}{}", record.hash, RESPONSE_SUFFIX);
+        if let Err(e) = Self::write_record(dir, &name, record) {
+            error!(
+                "Cannot archive response to block candidate '{}': {}",
+                record.hash, e
+            );
+        }
+        Self::lock_pending(pending).remove(&record.hash);
+    }
+
+    /// Write the `candidate` and the response which has been received in the meantime
+    fn write_candidate(
+        dir: &Path,
+        candidate: &Candidate,
+        pending: &StdMutex<HashMap<String, PendingRecord>>,
+    ) {
+        let name = format!("{}{}", candidate.hash, CANDIDATE_SUFFIX);
+        match Self::write_record(dir, &name, candidate) {
+            Ok(()) => info!(
+                "Block candidate archived to '{}'",
+                dir.join(&name).display()
+            ),
+            Err(e) => {
+                if e.kind() == io::ErrorKind::AlreadyExists {
+                    debug!(
+                        "Block candidate '{}' has been already archived",
+                        candidate.hash
+                    );
+                } else {
+                    error!(
+                        "Cannot archive block candidate '{}' to '{}': {}",
+                        candidate.hash,
+                        dir.display(),
+                        e
+                    );
+                }
+                // the response is never recorded without the candidate of this run
+                Self::lock_pending(pending).remove(&candidate.hash);
+                return;
+            }
+        }
+        let response = Self::lock_pending(pending)
+            .get_mut(&candidate.hash)
+            .and_then(|record| {
+                record.written = true;
+                record.response.clone()
+            });
+        if let Some(response) = response {
+            Self::write_response(dir, &response, pending);
+        }
+    }
+
+    /// Archive the `solution` which meets the `network_target`. The candidate is recorded in
+    /// memory before the solution is submitted and it is written to the disk by a blocking task.
+    pub fn archive(&self, solution: &work::Solution, network_target: &ii_bitcoin::Target) {
+        let dir = match self.dir() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let candidate = Candidate::new(solution, network_target);
+        {
+            let mut pending = Self::lock_pending(&self.pending);
+            if pending.contains_key(&candidate.hash) {
+                debug!(
+                    "Block candidate '{}' has been already archived",
+                    candidate.hash
+                );
+                return;
+            }
+            pending.insert(
+                candidate.hash.clone(),
+                PendingRecord {
+                    candidate: candidate.clone(),
+                    written: false,
+                    response: None,
+                },
+            );
+        }
+        let pending = self.pending.clone();
+        Self::spawn_write(move || Self::write_candidate(&dir, &candidate, &pending));
+    }
+
+    /// Record the `response` of the pool to the `solution`. It is called for all shares so it
+    /// returns immediately when no candidate is waiting for its response.
+    pub fn record_response(&self, solution: &work::Solution, response: Response) {
+        let record = {
+            let mut pending = Self::lock_pending(&self.pending);
+            if pending.is_empty() {
+                return;
+            }
+            let hash = format!("{:x}", solution.hash());
+            let pending_record = match pending.get_mut(&hash) {
+                // only the first response is recorded
+                Some(pending_record) if pending_record.response.is_none() => pending_record,
+                _ => return,
+            };
+            let record = ResponseRecord {
+                format: FORMAT_VERSION,
+                hash,
+                response,
+                received: unix_millis(clock::system_time()),
+            };
+            pending_record.response.replace(record.clone());
+            if !pending_record.written {
+                return;
+            }
+            record
+        };
+        let dir = match self.dir() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let pending = self.pending.clone();
+        Self::spawn_write(move || Self::write_response(&dir, &record, &pending));
+    }
+
+    fn read_record<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    }
+
+    /// Return all archived candidates sorted by the time of archiving. Unreadable files are
+    /// skipped.
+    pub fn list(&self) -> Vec<Entry> {
+        let dir = match self.dir() {
+            Some(dir) => dir,
+            None => return vec![],
+        };
+        let dir_entries = match fs::read_dir(&dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(e) => {
+                warn!("Cannot read block archive '{}': {}", dir.display(), e);
+                return vec![];
+            }
+        };
+
+        let mut entries = vec![];
+        for path in dir_entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) if name.ends_with(CANDIDATE_SUFFIX) => name,
+                _ => continue,
+            };
+            let candidate: Candidate = match Self::read_record(&path) {
+                Ok(candidate) => candidate,
+                Err(e) => {
+                    warn!("Skipping archived candidate '{}': {}", path.display(), e);
+                    continue;
+                }
+            };
+            let response_path = dir.join(name.replace(CANDIDATE_SUFFIX, RESPONSE_SUFFIX).as_str());
+            let response = if response_path.exists() {
+                Self::read_record::<ResponseRecord>(&response_path)
+                    .map_err(|e| {
+                        warn!(
+                            "Skipping archived response '{}': {}",
+                            response_path.display(),
+                            e
+                        )
+                    })
+                    .ok()
+            } else {
+                None
+            };
+            entries.push(Entry {
+                candidate,
+                response: response
+                    .as_ref()
+                    .map_or(Response::Unknown, |record| record.response),
+                received: response.map(|record| record.received),
+            });
+        }
+        // candidates and responses which have not been written yet
+        for record in Self::lock_pending(&self.pending).values() {
+            match entries
+                .iter_mut()
+                .find(|entry| entry.candidate.hash == record.candidate.hash)
+            {
+                Some(entry) => {
+                    if let Some(response) = record.response.as_ref() {
+                        entry.response = response.response;
+                        entry.received = Some(response.received);
+                    }
+                }
+                None => entries.push(Entry {
+                    candidate: record.candidate.clone(),
+                    response: record
+                        .response
+                        .as_ref()
+                        .map_or(Response::Unknown, |response| response.response),
+                    received: record.response.as_ref().map(|response| response.received),
+                }),
+            }
+        }
+        entries.sort_by_key(|entry| entry.candidate.archived);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "bosminer-archive-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn network_target(solution: &work::Solution) -> ii_bitcoin::Target {
+        solution.network_target(Default::default())
+    }
+
+    #[test]
+    fn test_disabled() {
+        let archive = Archive::new();
+        let solution: work::Solution = (&test_utils::TEST_BLOCKS[0]).into();
+        archive.archive(&solution, &network_target(&solution));
+        archive.record_response(&solution, Response::Accepted);
+        assert!(archive.list().is_empty());
+    }
+
+    #[test]
+    fn test_archive() {
+        let dir = TestDir::new("archive");
+        let archive = Archive::new();
+        archive.set_dir(&dir.0).expect("BUG: cannot create archive");
+
+        let block = &test_utils::TEST_BLOCKS[0];
+        let solution: work::Solution = block.into();
+        archive.archive(&solution, &network_target(&solution));
+        // the same candidate is never overwritten
+        archive.archive(&solution, &network_target(&solution));
+
+        let hash = format!("{:x}", block.hash);
+        let content = fs::read_to_string(dir.0.join(format!("{}{}", hash, CANDIDATE_SUFFIX)))
+            .expect("BUG: missing archived candidate");
+        let candidate: Candidate = serde_json::from_str(&content).unwrap();
+        assert_eq!(candidate.format, FORMAT_VERSION);
+        assert_eq!(candidate.hash, hash);
+        assert_eq!(candidate.header, hex::encode(&block.header_bytes[..]));
+        assert_eq!(candidate.pool, test_utils::TEST_CLIENT.to_string());
+        assert_eq!(candidate.coinbase, None);
+        assert!(candidate.merkle_branch.is_empty());
+        assert_eq!(candidate.job_target, format!("{:x}", block.target));
+        assert_eq!(candidate.ntime, block.time);
+
+        let entries = archive.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].candidate, candidate);
+        assert_eq!(entries[0].response, Response::Unknown);
+        assert_eq!(entries[0].received, None);
+
+        // unrelated shares do not touch the archive
+        let share: work::Solution = (&test_utils::TEST_BLOCKS[1]).into();
+        archive.record_response(&share, Response::Rejected);
+        assert_eq!(archive.list()[0].response, Response::Unknown);
+
+        archive.record_response(&solution, Response::Accepted);
+        // only the first response is recorded
+        archive.record_response(&solution, Response::Rejected);
+        let entries = archive.list();
+        assert_eq!(entries[0].response, Response::Accepted);
+        assert!(entries[0].received.is_some());
+    }
+
+    #[test]
+    fn test_corrupted_file() {
+        let dir = TestDir::new("corrupted");
+        let archive = Archive::new();
+        archive.set_dir(&dir.0).expect("BUG: cannot create archive");
+
+        fs::write(dir.0.join(format!("0{}", CANDIDATE_SUFFIX)), "{ broken").unwrap();
+        fs::write(dir.0.join("notes.txt"), "not a candidate").unwrap();
+        let solution: work::Solution = (&test_utils::TEST_BLOCKS[0]).into();
+        archive.archive(&solution, &network_target(&solution));
+
+        let entries = archive.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].candidate.hash, format!("{:x}", solution.hash()));
+    }
+
+    /// Response received before the candidate has been written is written after it
+    #[test]
+    fn test_back_fill() {
+        let dir = TestDir::new("back-fill");
+        let archive = Archive::new();
+        archive.set_dir(&dir.0).expect("BUG: cannot create archive");
+
+        let solution: work::Solution = (&test_utils::TEST_BLOCKS[0]).into();
+        let candidate = Candidate::new(&solution, &network_target(&solution));
+        let hash = candidate.hash.clone();
+        Archive::lock_pending(&archive.pending).insert(
+            hash.clone(),
+            PendingRecord {
+                candidate: candidate.clone(),
+                written: false,
+                response: None,
+            },
+        );
+        archive.record_response(&solution, Response::Stale);
+
+        // nothing has been written yet but the candidate is listed with its response
+        assert!(!dir.0.join(format!("{}{}", hash, CANDIDATE_SUFFIX)).exists());
+        let entries = archive.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].candidate, candidate);
+        assert_eq!(entries[0].response, Response::Stale);
+
+        Archive::write_candidate(&dir.0, &candidate, &archive.pending);
+        assert!(dir.0.join(format!("{}{}", hash, RESPONSE_SUFFIX)).exists());
+        assert!(Archive::lock_pending(&archive.pending).is_empty());
+        let entries = archive.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].response, Response::Stale);
+    }
+
+    /// Records are written by blocking tasks when the archive is used from the runtime
+    #[tokio::test]
+    async fn test_background_write() {
+        let dir = TestDir::new("background");
+        let archive = Archive::new();
+        archive.set_dir(&dir.0).expect("BUG: cannot create archive");
+
+        let solution: work::Solution = (&test_utils::TEST_BLOCKS[0]).into();
+        archive.archive(&solution, &network_target(&solution));
+        archive.record_response(&solution, Response::Accepted);
+        // the pending records are listed immediately
+        assert_eq!(archive.list()[0].response, Response::Accepted);
+
+        let response_path = dir
+            .0
+            .join(format!("{:x}{}", solution.hash(), RESPONSE_SUFFIX));
+        for _ in 0..100 {
+            if Archive::lock_pending(&archive.pending).is_empty() {
+                break;
+            }
+            tokio::time::delay_for(time::Duration::from_millis(10)).await;
+        }
+        assert!(response_path.exists());
+        assert_eq!(archive.list()[0].response, Response::Accepted);
+    }
+}
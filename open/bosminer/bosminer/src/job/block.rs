@@ -0,0 +1,348 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Detection of blocks found on the network as seen by the miner. The blocks are not queried from
+//! any node but they are derived from jobs received from all clients: a new block is recognized
+//! by a change of the previous hash in the jobs of any client. The same change seen by multiple
+//! clients is reported only once.
+
+use ii_logging::macros::*;
+
+use crate::bus;
+use crate::clock;
+use crate::job;
+use crate::log_id;
+
+use ii_async_compat::tokio;
+use tokio::sync::broadcast;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// New block recognized by a change of the previous hash in a job
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockChange {
+    /// Hash of the new block (the previous hash of the job)
+    pub previous_hash: ii_bitcoin::DHash,
+    /// Client which has delivered the job with the new block first
+    pub client_name: log_id::ClientName,
+    /// The time the new block has been seen by the miner
+    pub time: time::SystemTime,
+    /// Time since the previous block change (it is unknown for the first change of the session)
+    pub interval: Option<time::Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Network difficulty from nBits of the last received job
+    pub network_difficulty: f64,
+    /// Hash of the current block and the time it has been seen for the first time
+    pub current_block: Option<(ii_bitcoin::DHash, time::SystemTime)>,
+    /// The last block change seen in the session
+    pub last_change: Option<BlockChange>,
+    /// Number of block changes seen in the session
+    pub changes: u64,
+    /// Average interval between consecutive block changes
+    pub average_interval: Option<time::Duration>,
+}
+
+impl Snapshot {
+    /// Time since the last block change seen by the miner
+    pub fn since_last_change(&self, now: time::SystemTime) -> Option<time::Duration> {
+        self.last_change
+            .as_ref()
+            .map(|change| now.duration_since(change.time).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// The last previous hash received from each client
+    clients: HashMap<log_id::ClientName, ii_bitcoin::DHash>,
+    /// Recently seen previous hashes used for deduplication of changes seen by multiple clients
+    /// (the newest one is at the back)
+    recent: VecDeque<ii_bitcoin::DHash>,
+    network_difficulty: f64,
+    current_block: Option<(ii_bitcoin::DHash, time::SystemTime)>,
+    first_change: Option<time::SystemTime>,
+    last_change: Option<BlockChange>,
+    changes: u64,
+}
+
+/// Tracks changes of blocks and the network difficulty in jobs received from all clients and
+/// publishes each block change to subscribers and to the bus
+#[derive(Debug)]
+pub struct Tracker {
+    inner: StdMutex<Inner>,
+    event_sender: broadcast::Sender<BlockChange>,
+    bus: Arc<bus::Bus>,
+}
+
+impl Tracker {
+    /// Number of previous hashes remembered for deduplication. Clients lagging behind by more
+    /// blocks would report them again.
+    const RECENT_LENGTH: usize = 16;
+    /// Capacity of the channel for subscribers of block changes
+    const EVENT_CAPACITY: usize = 16;
+
+    pub fn new() -> Self {
+        let (event_sender, _) = broadcast::channel(Self::EVENT_CAPACITY);
+        Self {
+            inner: Default::default(),
+            event_sender,
+            bus: Arc::new(bus::Bus::new()),
+        }
+    }
+
+    /// Publish block changes to the `bus` shared with other components instead of its own one
+    pub fn with_bus(mut self, bus: Arc<bus::Bus>) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    fn lock_inner(&self) -> StdMutexGuard<Inner> {
+        self.inner.lock().expect("BUG: cannot lock block tracker")
+    }
+
+    /// Receive all block changes observed after this call
+    pub fn subscribe(&self) -> broadcast::Receiver<BlockChange> {
+        self.event_sender.subscribe()
+    }
+
+    /// Observe a valid `job` received from the client. Returns the block change when the job
+    /// brings a block which has not been seen by any client yet.
+    pub fn observe(
+        &self,
+        client_name: log_id::ClientName,
+        job: &dyn job::Bitcoin,
+        network: ii_bitcoin::Network,
+    ) -> Option<BlockChange> {
+        self.observe_at(client_name, job, network, clock::system_time())
+    }
+
+    fn observe_at(
+        &self,
+        client_name: log_id::ClientName,
+        job: &dyn job::Bitcoin,
+        network: ii_bitcoin::Network,
+        time: time::SystemTime,
+    ) -> Option<BlockChange> {
+        let previous_hash = *job.previous_hash();
+        let mut inner = self.lock_inner();
+        if let Ok(target) = network.target_from_compact(job.bits()) {
+            inner.network_difficulty = network.difficulty(&target);
+        }
+        if inner.clients.insert(client_name, previous_hash) == Some(previous_hash) {
+            return None;
+        }
+        if inner.recent.contains(&previous_hash) {
+            // the block has been already seen by another client
+            return None;
+        }
+        if inner.recent.len() >= Self::RECENT_LENGTH {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(previous_hash);
+        if inner.current_block.replace((previous_hash, time)).is_none() {
+            // the time the first block of the session has been found is unknown
+            return None;
+        }
+
+        let change = BlockChange {
+            previous_hash,
+            client_name,
+            time,
+            interval: inner
+                .last_change
+                .as_ref()
+                .map(|last_change| time.duration_since(last_change.time).unwrap_or_default()),
+        };
+        inner.first_change.get_or_insert(time);
+        inner.last_change = Some(change.clone());
+        inner.changes += 1;
+        drop(inner);
+
+        info!(
+            "{} New block {} seen on the network",
+            log_id::Prefix::new().with_client(client_name),
+            previous_hash
+        );
+        // there may be no subscribers
+        let _ = self.event_sender.send(change.clone());
+        self.bus.publish(bus::Event::BlockChanged(change.clone()));
+        Some(change)
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot {
+        let inner = self.lock_inner();
+        let average_interval = match (&inner.first_change, &inner.last_change) {
+            (Some(first_change), Some(last_change)) if inner.changes > 1 => Some(
+                last_change
+                    .time
+                    .duration_since(*first_change)
+                    .unwrap_or_default()
+                    / (inner.changes - 1) as u32,
+            ),
+            _ => None,
+        };
+        Snapshot {
+            network_difficulty: inner.network_difficulty,
+            current_block: inner.current_block,
+            last_change: inner.last_change.clone(),
+            changes: inner.changes,
+            average_interval,
+        }
+    }
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use ii_bitcoin::HashTrait as _;
+
+    const BITS: u32 = 0x1b0404cb;
+
+    fn get_block_hash(height: u8) -> ii_bitcoin::DHash {
+        ii_bitcoin::DHash::hash(&[height])
+    }
+
+    /// Job building on top of the block at `height`
+    fn get_job(height: u8, bits: u32) -> test_utils::TestBlock {
+        let mut job = test_utils::TEST_BLOCKS[0];
+        job.previous_hash = get_block_hash(height);
+        job.bits = bits;
+        job
+    }
+
+    fn get_time(secs: u64) -> time::SystemTime {
+        time::UNIX_EPOCH + time::Duration::from_secs(1_600_000_000 + secs)
+    }
+
+    #[test]
+    fn test_network_difficulty() {
+        let tracker = Tracker::new();
+        let client_name = log_id::ClientName::next();
+        let network = ii_bitcoin::Network::default();
+        assert_eq!(tracker.take_snapshot().network_difficulty, 0.0);
+
+        tracker.observe(client_name, &get_job(0, 0x1d00ffff), network);
+        assert_eq!(tracker.take_snapshot().network_difficulty, 1.0);
+        tracker.observe(client_name, &get_job(0, BITS), network);
+        assert!((tracker.take_snapshot().network_difficulty - 16307.420938523983).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_block_changes() {
+        let bus = Arc::new(bus::Bus::new());
+        let mut bus_events = bus.subscribe();
+        let tracker = Tracker::new().with_bus(bus);
+        let mut events = tracker.subscribe();
+        let client_a = log_id::ClientName::next();
+        let client_b = log_id::ClientName::next();
+        let network = ii_bitcoin::Network::default();
+        let observe = |client_name, height, secs| {
+            tracker.observe_at(client_name, &get_job(height, BITS), network, get_time(secs))
+        };
+
+        // the time the first block has been found is unknown
+        assert_eq!(observe(client_a, 0, 0), None);
+        assert_eq!(observe(client_b, 0, 1), None);
+        let snapshot = tracker.take_snapshot();
+        assert_eq!(
+            snapshot.current_block,
+            Some((get_block_hash(0), get_time(0)))
+        );
+        assert_eq!(snapshot.last_change, None);
+        assert_eq!(snapshot.since_last_change(get_time(10)), None);
+
+        // the new block is seen by both clients but it is reported only once
+        let first_change = BlockChange {
+            previous_hash: get_block_hash(1),
+            client_name: client_a,
+            time: get_time(600),
+            interval: None,
+        };
+        assert_eq!(observe(client_a, 1, 600), Some(first_change.clone()));
+        assert_eq!(observe(client_a, 1, 610), None);
+        assert_eq!(observe(client_b, 1, 602), None);
+
+        // the other client can be faster
+        let second_change = BlockChange {
+            previous_hash: get_block_hash(2),
+            client_name: client_b,
+            time: get_time(1500),
+            interval: Some(time::Duration::from_secs(900)),
+        };
+        assert_eq!(observe(client_b, 2, 1500), Some(second_change.clone()));
+        assert_eq!(observe(client_a, 2, 1501), None);
+        // client lagging behind does not report the old block again
+        assert_eq!(observe(client_a, 1, 1502), None);
+
+        let third_change = BlockChange {
+            previous_hash: get_block_hash(3),
+            client_name: client_a,
+            time: get_time(1800),
+            interval: Some(time::Duration::from_secs(300)),
+        };
+        assert_eq!(observe(client_a, 3, 1800), Some(third_change.clone()));
+        assert_eq!(observe(client_b, 3, 1810), None);
+
+        // each change is published exactly once
+        assert_eq!(events.try_recv().ok(), Some(first_change.clone()));
+        assert_eq!(events.try_recv().ok(), Some(second_change.clone()));
+        assert_eq!(events.try_recv().ok(), Some(third_change.clone()));
+        assert!(events.try_recv().is_err());
+        // the same changes are published to the bus
+        for change in [first_change, second_change, third_change.clone()].iter() {
+            assert_eq!(
+                bus_events.try_recv(),
+                Some(bus::Event::BlockChanged(change.clone()))
+            );
+        }
+        assert_eq!(bus_events.try_recv(), None);
+
+        let snapshot = tracker.take_snapshot();
+        assert_eq!(snapshot.changes, 3);
+        assert_eq!(
+            snapshot.current_block,
+            Some((get_block_hash(3), get_time(1800)))
+        );
+        assert_eq!(snapshot.last_change, Some(third_change));
+        assert_eq!(
+            snapshot.average_interval,
+            Some(time::Duration::from_secs(600))
+        );
+        assert_eq!(
+            snapshot.since_last_change(get_time(1860)),
+            Some(time::Duration::from_secs(60))
+        );
+    }
+}
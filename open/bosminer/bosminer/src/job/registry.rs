@@ -0,0 +1,281 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Accounting of jobs which are still referenced anywhere in the miner. Every job sent to the
+//! work engines is registered here and the registry remembers when it has been superseded by
+//! a newer job of the same client. Superseded jobs are expected to be released shortly after all
+//! work generated from them has been solved or dropped so a job which stays alive much longer is
+//! most probably leaked by someone who keeps it in a forgotten queue or channel.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+use crate::job;
+use crate::log_id;
+use crate::stats;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
+use std::time;
+
+#[derive(Debug)]
+struct Entry {
+    job: Weak<dyn job::Bitcoin>,
+    client_name: log_id::ClientName,
+    created: time::Instant,
+    /// The time a newer job of the same client has been registered
+    superseded: Option<time::Instant>,
+    /// The job has been already reported as leaked
+    flagged: bool,
+}
+
+impl Entry {
+    #[inline]
+    fn is_alive(&self) -> bool {
+        self.job.upgrade().is_some()
+    }
+}
+
+/// Superseded job which is still alive after `Registry::LEAK_THRESHOLD`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leak {
+    pub client_name: log_id::ClientName,
+    /// Identifier of the job assigned by the remote server
+    pub job_id: Option<u32>,
+    /// Time since the job has been registered
+    pub age: time::Duration,
+    /// Time since the job has been superseded
+    pub superseded_for: time::Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// Number of jobs which are still referenced
+    pub live_jobs: usize,
+    /// Number of live jobs already superseded by a newer job of their client
+    pub superseded_jobs: usize,
+    /// Age of the oldest live job
+    pub oldest_age: Option<time::Duration>,
+    /// Number of jobs reported as leaked in the session
+    pub leaked_jobs: u64,
+}
+
+/// Registry of live jobs with weak references to them
+#[derive(Debug, Default)]
+pub struct Registry {
+    entries: StdMutex<Vec<Entry>>,
+    leaked_jobs: stats::CounterU64,
+}
+
+impl Registry {
+    /// Superseded job alive for longer time is considered to be leaked
+    pub const LEAK_THRESHOLD: time::Duration = time::Duration::from_secs(10 * 60);
+    /// Interval between periodic checks of leaked jobs
+    const REAP_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock_entries(&self) -> StdMutexGuard<Vec<Entry>> {
+        self.entries.lock().expect("BUG: cannot lock job registry")
+    }
+
+    /// Register a new job of the client. All previous jobs of the same client are superseded by
+    /// it.
+    pub fn register(
+        &self,
+        client_name: log_id::ClientName,
+        job: &Arc<dyn job::Bitcoin>,
+        now: time::Instant,
+    ) {
+        let mut entries = self.lock_entries();
+        // released jobs are pruned here so the registry does not grow even without the reaper
+        entries.retain(Entry::is_alive);
+        for entry in entries
+            .iter_mut()
+            .filter(|entry| entry.client_name == client_name)
+        {
+            entry.superseded.get_or_insert(now);
+        }
+        entries.push(Entry {
+            job: Arc::downgrade(job),
+            client_name,
+            created: now,
+            superseded: None,
+            flagged: false,
+        });
+    }
+
+    /// Flag superseded jobs which are still alive after `LEAK_THRESHOLD` and return the ones
+    /// which have not been reported yet
+    pub fn reap(&self, now: time::Instant) -> Vec<Leak> {
+        let mut entries = self.lock_entries();
+        entries.retain(Entry::is_alive);
+        let mut leaks = Vec::new();
+        for entry in entries.iter_mut().filter(|entry| !entry.flagged) {
+            let superseded_for = match entry.superseded {
+                Some(superseded) => now.saturating_duration_since(superseded),
+                None => continue,
+            };
+            if superseded_for < Self::LEAK_THRESHOLD {
+                continue;
+            }
+            entry.flagged = true;
+            let leak = Leak {
+                client_name: entry.client_name,
+                job_id: entry.job.upgrade().and_then(|job| job.id()),
+                age: now.saturating_duration_since(entry.created),
+                superseded_for,
+            };
+            warn!(
+                "{} Job {:?} is still alive {}s after it has been superseded",
+                log_id::Prefix::new().with_client(leak.client_name),
+                leak.job_id,
+                leak.superseded_for.as_secs()
+            );
+            leaks.push(leak);
+        }
+        self.leaked_jobs.add(leaks.len() as u64);
+        leaks
+    }
+
+    pub fn take_snapshot(&self, now: time::Instant) -> Snapshot {
+        let entries = self.lock_entries();
+        let mut snapshot = Snapshot {
+            leaked_jobs: *self.leaked_jobs.take_snapshot(),
+            ..Default::default()
+        };
+        for entry in entries.iter().filter(|entry| entry.is_alive()) {
+            snapshot.live_jobs += 1;
+            if entry.superseded.is_some() {
+                snapshot.superseded_jobs += 1;
+            }
+            let age = now.saturating_duration_since(entry.created);
+            snapshot.oldest_age = Some(snapshot.oldest_age.map_or(age, |oldest| oldest.max(age)));
+        }
+        snapshot
+    }
+
+    /// Periodically check the registry for leaked jobs
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            delay_for(Self::REAP_INTERVAL).await;
+            self.reap(clock::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use futures::channel::mpsc;
+    use ii_async_compat::futures;
+
+    fn get_job() -> Arc<dyn job::Bitcoin> {
+        Arc::new(test_utils::TEST_BLOCKS[0])
+    }
+
+    /// Superseded job forgotten in a channel which is never read is reported as leaked until
+    /// the channel is dropped
+    #[test]
+    fn test_registry() {
+        let registry = Registry::new();
+        let clients = [log_id::ClientName::next(), log_id::ClientName::next()];
+        let now = time::Instant::now();
+        let second = time::Duration::from_secs(1);
+
+        // the first job is referenced only by the channel
+        let (job_sender, forgotten_receiver) = mpsc::unbounded();
+        let first = get_job();
+        registry.register(clients[0], &first, now);
+        job_sender
+            .unbounded_send(first)
+            .expect("BUG: cannot send job");
+        drop(job_sender);
+        // job of another client which has been already released does not supersede anything
+        registry.register(clients[1], &get_job(), now);
+        assert_eq!(
+            registry.take_snapshot(now + second),
+            Snapshot {
+                live_jobs: 1,
+                oldest_age: Some(second),
+                ..Default::default()
+            }
+        );
+
+        let current = get_job();
+        registry.register(clients[0], &current, now + second);
+        assert_eq!(
+            registry.take_snapshot(now + 2 * second),
+            Snapshot {
+                live_jobs: 2,
+                superseded_jobs: 1,
+                oldest_age: Some(2 * second),
+                leaked_jobs: 0,
+            }
+        );
+        assert!(registry.reap(now + Registry::LEAK_THRESHOLD).is_empty());
+
+        // only the superseded job can leak and it is reported just once
+        let later = now + second + Registry::LEAK_THRESHOLD;
+        let leaks = registry.reap(later);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].client_name, clients[0]);
+        assert_eq!(leaks[0].age, second + Registry::LEAK_THRESHOLD);
+        assert_eq!(leaks[0].superseded_for, Registry::LEAK_THRESHOLD);
+        assert!(registry.reap(later).is_empty());
+        assert_eq!(
+            registry.take_snapshot(later),
+            Snapshot {
+                live_jobs: 2,
+                superseded_jobs: 1,
+                oldest_age: Some(second + Registry::LEAK_THRESHOLD),
+                leaked_jobs: 1,
+            }
+        );
+
+        // the leaked job is released together with the channel
+        drop(forgotten_receiver);
+        assert_eq!(
+            registry.take_snapshot(later),
+            Snapshot {
+                live_jobs: 1,
+                superseded_jobs: 0,
+                oldest_age: Some(Registry::LEAK_THRESHOLD),
+                leaked_jobs: 1,
+            }
+        );
+        drop(current);
+        assert_eq!(
+            registry.take_snapshot(later),
+            Snapshot {
+                leaked_jobs: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -20,29 +20,49 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+//! BOSminer core connecting the pools with hardware specific mining backends.
+//!
+//! Applications embedding BOSminer should use only the `facade` module available with the
+//! `lib-api` feature. The other modules are unstable internals.
+
 // Increase recursion limit as e.g. `select!` macro  and other complex macros quickly run out of
 // the default recursion limit if more complex statements are used
 #![recursion_limit = "256"]
 
 mod api;
 pub mod backend;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod bus;
 pub mod client;
+pub mod clock;
 pub mod config;
+#[cfg(feature = "cpu-solver")]
+pub mod cpu;
 pub mod entry;
 pub mod error;
+#[cfg(feature = "lib-api")]
+pub mod facade;
 pub mod hal;
 pub mod hub;
 pub mod job;
+pub mod log_id;
 pub mod node;
+pub mod progress;
+pub mod share_policy;
+pub mod shutdown;
 pub mod stats;
+pub mod storage;
 pub mod sync;
+#[cfg(all(feature = "systemd", unix))]
+pub mod systemd;
 pub mod version;
 pub mod work;
 
 pub mod test_utils;
 
 // reexport main function from `entry` module
-pub use entry::main;
+pub use entry::{main, ExitCode, LoggingMode};
 // reexport `Result` which is used in hal interface
 pub use error::Result;
 
@@ -65,18 +85,26 @@ pub const VENDOR: &'static str = "Braiins";
 pub struct Frontend {
     #[member_work_solver_stats]
     work_solver_stats: stats::BasicWorkSolver,
+    /// Totals of all pools in previous runs of the miner
+    lifetime: stats::Lifetime,
 }
 
 impl Frontend {
     pub fn new() -> Self {
         Self {
             work_solver_stats: Default::default(),
+            lifetime: Default::default(),
         }
     }
 
     pub fn get_generated_work(&self) -> u64 {
         *self.work_solver_stats().generated_work().take_snapshot()
     }
+
+    #[inline]
+    pub fn get_lifetime(&self) -> &stats::Lifetime {
+        &self.lifetime
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,290 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Short identifiers prefixed to log lines so that all records related to one job or share
+//! (e.g. its broadcast, the solution and the response of the pool) can be cross-referenced
+//! without relying on timestamps. The prefix has a fixed form `[job=ab12 gen=47 client=pool0]`
+//! and it is formatted directly to the log record without any allocation. Clients registered by
+//! the client manager are shown under their display name instead of the sequence number.
+
+use crate::job;
+use crate::work;
+
+use once_cell::sync::Lazy;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Sequence number of the last client created in this process
+static CLIENT_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Display names of clients which are unique within the process
+static CLIENT_DISPLAY_NAMES: Lazy<RwLock<HashMap<ClientName, Arc<str>>>> =
+    Lazy::new(Default::default);
+
+/// Append the first free suffix ` (2)`, ` (3)`, ... to the `name` when it is `taken` already
+fn unique_name<F>(name: &str, taken: F) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|suffix| format!("{} ({})", name, suffix))
+        .find(|name| !taken(name))
+        .expect("BUG: no free display name")
+}
+
+/// Stable short identifier of a client which does not change with its URL, label or name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientName(usize);
+
+impl ClientName {
+    /// Allocate a new name which is unique within the process
+    pub fn next() -> Self {
+        Self(CLIENT_SEQUENCE.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Show the client under the display `name` instead of its sequence number. A name used by
+    /// another client is suffixed deterministically so the first client keeps the plain name.
+    /// Returns the name which has been assigned.
+    pub fn set_display_name(self, name: &str) -> Arc<str> {
+        let mut names = CLIENT_DISPLAY_NAMES
+            .write()
+            .expect("BUG: cannot lock client display names");
+        names.remove(&self);
+        let name: Arc<str> = unique_name(name, |candidate| {
+            names.values().any(|name| name.as_ref() == candidate)
+        })
+        .into();
+        names.insert(self, name.clone());
+        name
+    }
+
+    /// Release the display name (e.g. when the client is dropped) so it can be reused
+    pub fn clear_display_name(self) {
+        CLIENT_DISPLAY_NAMES
+            .write()
+            .expect("BUG: cannot lock client display names")
+            .remove(&self);
+    }
+
+    pub fn display_name(self) -> Option<Arc<str>> {
+        CLIENT_DISPLAY_NAMES
+            .read()
+            .expect("BUG: cannot lock client display names")
+            .get(&self)
+            .cloned()
+    }
+}
+
+impl fmt::Display for ClientName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.display_name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "pool{}", self.0),
+        }
+    }
+}
+
+/// Short hex identifier of a job. It is the identifier assigned to the job by the protocol and
+/// jobs without it are identified by the tail of their merkle root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobId(u32);
+
+impl JobId {
+    pub fn new(job: &dyn job::Bitcoin) -> Self {
+        Self(job.id().unwrap_or_else(|| job.merkle_root_tail()))
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}", self.0)
+    }
+}
+
+/// Display adapter of the log line prefix. Identifiers which are not known at the call site are
+/// left out and nothing is printed when no identifier is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Prefix {
+    job: Option<JobId>,
+    generation: Option<u64>,
+    client: Option<ClientName>,
+}
+
+impl Prefix {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Prefix of records related to the `job`
+    pub fn job(job: &dyn job::Bitcoin) -> Self {
+        Self::new().with_job(job)
+    }
+
+    /// Prefix of records related to the work assignment with its job and engine generation
+    pub fn assignment(work: &work::Assignment) -> Self {
+        let prefix = Self::job(work.dyn_job().as_ref());
+        match work.generation() {
+            Some(generation) => prefix.with_generation(generation),
+            None => prefix,
+        }
+    }
+
+    /// Prefix of records related to the `solution` with its job and engine generation
+    pub fn solution(solution: &work::Solution) -> Self {
+        let prefix = Self::job(solution.dyn_job().as_ref());
+        match solution.generation() {
+            Some(generation) => prefix.with_generation(generation),
+            None => prefix,
+        }
+    }
+
+    pub fn with_job(mut self, job: &dyn job::Bitcoin) -> Self {
+        self.job = Some(JobId::new(job));
+        self
+    }
+
+    /// Sequence number of the job broadcast to the work engines
+    pub fn with_generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+
+    pub fn with_client(mut self, client: ClientName) -> Self {
+        self.client = Some(client);
+        self
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == Self::default() {
+            return Ok(());
+        }
+        let mut separator = "";
+        write!(f, "[")?;
+        if let Some(job) = self.job {
+            write!(f, "job={}", job)?;
+            separator = " ";
+        }
+        if let Some(generation) = self.generation {
+            write!(f, "{}gen={}", separator, generation)?;
+            separator = " ";
+        }
+        if let Some(client) = self.client {
+            write!(f, "{}client={}", separator, client)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    #[test]
+    fn test_prefix_format() {
+        let block = &test_utils::TEST_BLOCKS[0];
+        // test block does not have any identifier assigned by the protocol
+        let job_id = format!("{:04x}", job::Bitcoin::merkle_root_tail(block));
+
+        assert_eq!(Prefix::new().to_string(), "");
+        assert_eq!(Prefix::new().with_generation(47).to_string(), "[gen=47]");
+        assert_eq!(Prefix::job(block).to_string(), format!("[job={}]", job_id));
+        assert_eq!(
+            Prefix::job(block)
+                .with_generation(47)
+                .with_client(ClientName(0))
+                .to_string(),
+            format!("[job={} gen=47 client=pool0]", job_id)
+        );
+        assert_eq!(
+            Prefix::new().with_client(ClientName(12)).to_string(),
+            "[client=pool12]"
+        );
+
+        // solution without generation is prefixed only with its job
+        let solution: work::Solution = block.into();
+        assert_eq!(Prefix::solution(&solution), Prefix::job(block));
+    }
+
+    #[test]
+    fn test_job_id() {
+        assert_eq!(JobId(0xab12).to_string(), "ab12");
+        assert_eq!(JobId(0x7).to_string(), "0007");
+        assert_eq!(JobId(0x1234_5678).to_string(), "12345678");
+    }
+
+    #[test]
+    fn test_client_name() {
+        let first = ClientName::next();
+        let second = ClientName::next();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_unique_name() {
+        let taken = ["pool", "pool (2)", "other"];
+        let is_taken = |name: &str| taken.contains(&name);
+        assert_eq!(unique_name("free", is_taken), "free");
+        assert_eq!(unique_name("other", is_taken), "other (2)");
+        assert_eq!(unique_name("pool", is_taken), "pool (3)");
+    }
+
+    #[test]
+    fn test_display_name() {
+        // the registry is shared by the whole process so the names are unique to this test
+        let clients: Vec<_> = (0..3).map(|_| ClientName::next()).collect();
+        let name = "log-id-test";
+        let names: Vec<_> = clients
+            .iter()
+            .map(|client| client.set_display_name(name).to_string())
+            .collect();
+        assert_eq!(names, vec![name, "log-id-test (2)", "log-id-test (3)"]);
+        assert_eq!(clients[1].to_string(), "log-id-test (2)");
+        assert_eq!(
+            Prefix::new().with_client(clients[2]).to_string(),
+            "[client=log-id-test (3)]"
+        );
+
+        // renaming the client to its own name does not suffix it
+        assert_eq!(
+            clients[1].set_display_name("log-id-test (2)").as_ref(),
+            names[1]
+        );
+        // released name is reused by the next client asking for it
+        clients[0].clear_display_name();
+        assert_eq!(clients[0].to_string(), format!("pool{}", clients[0].0));
+        assert_eq!(clients[2].set_display_name(name).as_ref(), name);
+        assert_eq!(clients[0].display_name(), None);
+
+        for client in clients {
+            client.clear_display_name();
+        }
+    }
+}
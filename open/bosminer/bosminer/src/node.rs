@@ -20,6 +20,9 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use crate::client::{health, resolver, session};
+use crate::clock;
+use crate::error;
 use crate::job;
 use crate::stats;
 use crate::sync;
@@ -27,8 +30,10 @@ use crate::sync;
 use std::any::Any;
 use std::fmt::{Debug, Display};
 use std::sync::Arc;
+use std::time;
 
 use async_trait::async_trait;
+use serde::Serialize;
 
 /// Generic trait for providing information about unique location of a "node" which is abstraction
 /// for all elements that somehow transform or provide jobs/work.
@@ -60,6 +65,36 @@ pub trait Client: Info + ClientStats {
     async fn get_last_job(&self) -> Option<Arc<dyn job::Bitcoin>>;
     /// FIXME: Do not allow dynamic descriptor changes
     fn change_connection_details(&self, _descriptor: &bosminer_config::ClientDescriptor) {}
+    /// Return history of sessions established with the remote server
+    fn session_history(&self) -> Vec<session::Event> {
+        vec![]
+    }
+    /// Return security parameters of the current connection when a secure protocol is used
+    fn connection_security(&self) -> Option<session::Security> {
+        None
+    }
+    /// Return error which prevents the client from being started again until its connection
+    /// details are changed
+    fn fatal_error(&self) -> Option<error::ErrorKind> {
+        None
+    }
+    /// Return whether the remote server has authorized the user of the client
+    fn authorization(&self) -> health::Authorization {
+        health::Authorization::Unknown
+    }
+    /// Return results of connection attempts to the resolved addresses of the remote server
+    fn address_stats(&self) -> Vec<resolver::AddressStats> {
+        vec![]
+    }
+    /// Return hashrate of accepted shares averaged over one of the time mean intervals kept by
+    /// the statistics (`stats::TIME_MEAN_INTERVAL_*`)
+    async fn get_hashrate(&self, interval: time::Duration) -> ii_bitcoin::HashesUnit {
+        self.client_stats()
+            .accepted()
+            .take_snapshot()
+            .await
+            .to_kilo_hashes(interval, clock::now())
+    }
 }
 
 pub trait ClientStats: Stats {
@@ -91,6 +126,58 @@ where
     }
 }
 
+/// Health of a work solver as reported by the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// The work solver is mining with reduced performance (e.g. some chips are missing)
+    Degraded,
+    /// The work solver is not able to mine at all
+    Failed,
+    /// The work solver does not get any work because mining has been paused on request
+    Paused,
+    /// The work solver has been removed from the backend hierarchy and it will not get any work
+    Removed,
+}
+
+/// Scheme used by the hardware for addressing individual chips
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Addressing {
+    /// Chips are addressed by their position in the chain multiplied by the `step`
+    Linear { step: usize },
+}
+
+/// Layout of chips behind a work solver
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
+    /// Version of the structure which is incremented on incompatible changes
+    pub version: u32,
+    /// Number of chips which have been enumerated
+    pub chip_count: usize,
+    /// Number of voltage domains the chips are split into (when the hardware has them)
+    pub domains: Option<usize>,
+    pub addressing: Addressing,
+}
+
+impl Topology {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(chip_count: usize, addressing: Addressing) -> Self {
+        Self {
+            version: Self::VERSION,
+            chip_count,
+            domains: None,
+            addressing,
+        }
+    }
+
+    pub fn with_domains(mut self, domains: usize) -> Self {
+        self.domains = Some(domains);
+        self
+    }
+}
+
 /// Common interface for nodes with ability to solve generated work and providing common interface
 /// for mining control
 #[async_trait]
@@ -101,6 +188,90 @@ pub trait WorkSolver: Info + WorkSolverStats {
     }
     /// Return nominal/expected hashrate in hashes per second
     async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit>;
+    /// Return hashrate of valid solutions averaged over one of the time mean intervals kept by
+    /// the statistics (`stats::TIME_MEAN_INTERVAL_*`). The time means are frozen while the work
+    /// solver is paused.
+    async fn get_hashrate(&self, interval: time::Duration) -> ii_bitcoin::HashesUnit {
+        let now = self
+            .work_solver_stats()
+            .pause()
+            .measurement_time(clock::now());
+        self.mining_stats()
+            .valid_backend_diff()
+            .take_snapshot()
+            .await
+            .to_kilo_hashes(interval, now)
+    }
+    /// Optionally return the time of the last activity observed by the backend itself (e.g. any
+    /// response received from the hardware)
+    async fn get_last_activity(&self) -> Option<time::SystemTime> {
+        None
+    }
+    /// Return current health of the work solver
+    async fn get_status(&self) -> Status {
+        Status::Ok
+    }
+    /// Return `Status::Removed` for removed work solver, `Status::Paused` while mining is paused
+    /// on request or the health reported by `get_status` otherwise
+    async fn get_mining_status(&self) -> Status {
+        let work_solver_stats = self.work_solver_stats();
+        if work_solver_stats.removal().is_removed() {
+            Status::Removed
+        } else if work_solver_stats.pause().is_paused() {
+            Status::Paused
+        } else {
+            self.get_status().await
+        }
+    }
+    /// Optionally return layout of chips when the work solver represents real hardware
+    async fn topology(&self) -> Option<Topology> {
+        None
+    }
+    /// Optionally return backend specific key/value statistics (e.g. frequency, voltage or
+    /// temperatures of hash chain) which are merged into the section of the work solver in the
+    /// CGMiner `stats` command. Only JSON object is expected.
+    async fn backend_stats(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// Report an issue of the device (e.g. communication error) which is listed by CGMiner
+    /// `notify` command. The device is considered well again when it finds a valid solution.
+    fn report_error(&self, issue: stats::HealthIssue) {
+        self.work_solver_stats()
+            .device_health()
+            .account_issue(issue, clock::system_time());
+    }
+    /// Lower power consumption of the hardware (e.g. by gating its clocks) when the work solver
+    /// is paused and restore its previous operating point when it is resumed. The work is
+    /// withheld by the generator in any case so the backend does not have to support it.
+    async fn set_power_saving(&self, _enabled: bool) -> error::Result<()> {
+        Ok(())
+    }
+    /// Pause mining (e.g. when the hardware overheats) and return false when the work solver
+    /// has been already paused. Its generator stops providing work and the backend is asked to
+    /// lower power consumption of the hardware.
+    async fn pause(&self) -> error::Result<bool> {
+        if !self.work_solver_stats().pause().set_paused(true) {
+            return Ok(false);
+        }
+        self.set_power_saving(true).await?;
+        Ok(true)
+    }
+    /// Resume paused mining and return false when the work solver has not been paused. The time
+    /// spent in the pause is excluded from hashrate time means. The work solver stays paused
+    /// when the backend fails to restore its operating point.
+    async fn resume(&self) -> error::Result<bool> {
+        let pause = self.work_solver_stats().pause();
+        let since = match pause.since() {
+            Some(since) => since,
+            None => return Ok(false),
+        };
+        self.set_power_saving(false).await?;
+        if !pause.set_paused(false) {
+            return Ok(false);
+        }
+        stats::exclude_period(self.mining_stats(), since, clock::now()).await;
+        Ok(true)
+    }
 }
 
 pub trait WorkSolverStats: Stats {
@@ -138,6 +309,46 @@ impl<T: ?Sized + WorkSolver> WorkSolver for Arc<T> {
     async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
         self.as_ref().get_nominal_hashrate().await
     }
+
+    async fn get_hashrate(&self, interval: time::Duration) -> ii_bitcoin::HashesUnit {
+        self.as_ref().get_hashrate(interval).await
+    }
+
+    async fn get_last_activity(&self) -> Option<time::SystemTime> {
+        self.as_ref().get_last_activity().await
+    }
+
+    async fn get_status(&self) -> Status {
+        self.as_ref().get_status().await
+    }
+
+    async fn get_mining_status(&self) -> Status {
+        self.as_ref().get_mining_status().await
+    }
+
+    async fn topology(&self) -> Option<Topology> {
+        self.as_ref().topology().await
+    }
+
+    async fn backend_stats(&self) -> Option<serde_json::Value> {
+        self.as_ref().backend_stats().await
+    }
+
+    fn report_error(&self, issue: stats::HealthIssue) {
+        self.as_ref().report_error(issue)
+    }
+
+    async fn set_power_saving(&self, enabled: bool) -> error::Result<()> {
+        self.as_ref().set_power_saving(enabled).await
+    }
+
+    async fn pause(&self) -> error::Result<bool> {
+        self.as_ref().pause().await
+    }
+
+    async fn resume(&self) -> error::Result<bool> {
+        self.as_ref().resume().await
+    }
 }
 
 impl<T: ?Sized + WorkSolverStats> WorkSolverStats for Arc<T> {
@@ -0,0 +1,368 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Progress of the hash chain initialization reported by backends while they are starting up.
+//! It is intended for boot screens which would otherwise show nothing until the first share.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time;
+
+/// Phase of the hash chain initialization
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Phase {
+    /// The hash chain is being reset and powered up
+    Probing,
+    /// Chip `chip` out of `chips` detected ones has been assigned its address
+    Enumerating { chip: usize, chips: usize },
+    /// Frequency has been set in step `step` out of `steps`
+    SettingFrequency { step: usize, steps: usize },
+    /// The hash chain has been initialized and it is mining
+    Ready,
+    /// All attempts to initialize the hash chain have failed
+    Failed,
+}
+
+impl Phase {
+    /// Starting percentage and weight of each phase which is split evenly between its steps
+    const PROBING: (f64, f64) = (0.0, 10.0);
+    const ENUMERATING: (f64, f64) = (10.0, 40.0);
+    const SETTING_FREQUENCY: (f64, f64) = (50.0, 40.0);
+
+    fn partial((start, weight): (f64, f64), step: usize, steps: usize) -> f64 {
+        if steps == 0 {
+            start
+        } else {
+            start + weight * step.min(steps) as f64 / steps as f64
+        }
+    }
+
+    /// Percentage of the whole initialization which has been done when the phase is reached.
+    /// Returns `None` for the failure which does not move the progress anywhere.
+    pub fn percent(&self) -> Option<f64> {
+        match *self {
+            Self::Probing => Some(Self::PROBING.0),
+            Self::Enumerating { chip, chips } => {
+                Some(Self::partial(Self::ENUMERATING, chip, chips))
+            }
+            Self::SettingFrequency { step, steps } => {
+                Some(Self::partial(Self::SETTING_FREQUENCY, step, steps))
+            }
+            Self::Ready => Some(100.0),
+            Self::Failed => None,
+        }
+    }
+
+    /// Short name of the phase without its steps
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Probing => "Probing",
+            Self::Enumerating { .. } => "Enumerating",
+            Self::SettingFrequency { .. } => "Setting Frequency",
+            Self::Ready => "Ready",
+            Self::Failed => "Failed",
+        }
+    }
+
+    /// Returns `(step, steps)` for phases which are split into steps
+    pub fn steps(&self) -> Option<(usize, usize)> {
+        match *self {
+            Self::Enumerating { chip, chips } => Some((chip, chips)),
+            Self::SettingFrequency { step, steps } => Some((step, steps)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Probing => write!(f, "probing chain"),
+            Self::Enumerating { chip, chips } => write!(f, "enumerating chips {}/{}", chip, chips),
+            Self::SettingFrequency { step, steps } => {
+                write!(f, "setting frequency step {}/{}", step, steps)
+            }
+            Self::Ready => write!(f, "chain ready"),
+            Self::Failed => write!(f, "chain failed"),
+        }
+    }
+}
+
+/// Last reported progress of one hash chain
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Chain {
+    pub phase: Phase,
+    /// Percentage of the initialization which never decreases during one start of the chain even
+    /// when the initialization is retried
+    pub percent: f64,
+    pub last_change: time::SystemTime,
+}
+
+impl Chain {
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.phase == Phase::Ready
+    }
+}
+
+/// Shared progress of all hash chains indexed by their hashboard index
+#[derive(Debug, Default)]
+pub struct Registry {
+    chains: StdMutex<BTreeMap<usize, Chain>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create context for reporting of progress of hash chain `chain_idx`
+    pub fn reporter(self: &Arc<Self>, chain_idx: usize) -> Reporter {
+        Reporter {
+            registry: self.clone(),
+            chain_idx,
+        }
+    }
+
+    /// Return progress of all chains which have already reported something
+    pub fn snapshot(&self) -> Vec<(usize, Chain)> {
+        self.chains
+            .lock()
+            .expect("BUG: cannot lock progress")
+            .iter()
+            .map(|(idx, chain)| (*idx, *chain))
+            .collect()
+    }
+
+    /// Check that initialization of all reporting chains has finished either way
+    pub fn is_finished(&self) -> bool {
+        self.chains
+            .lock()
+            .expect("BUG: cannot lock progress")
+            .values()
+            .all(|chain| chain.phase == Phase::Ready || chain.phase == Phase::Failed)
+    }
+
+    fn update(&self, chain_idx: usize, phase: Phase, restart: bool) {
+        let mut chains = self.chains.lock().expect("BUG: cannot lock progress");
+        let last_change = clock::system_time();
+        let chain = chains.entry(chain_idx).or_insert(Chain {
+            phase,
+            percent: 0.0,
+            last_change,
+        });
+        if restart {
+            chain.percent = 0.0;
+        }
+        if let Some(percent) = phase.percent() {
+            chain.percent = chain.percent.max(percent);
+        }
+        chain.phase = phase;
+        chain.last_change = last_change;
+        debug!(
+            "Chain {}: {} ({:.0}%)",
+            chain_idx, chain.phase, chain.percent
+        );
+    }
+}
+
+/// Context passed through the initialization of one hash chain
+#[derive(Debug, Clone)]
+pub struct Reporter {
+    registry: Arc<Registry>,
+    chain_idx: usize,
+}
+
+impl Reporter {
+    #[inline]
+    pub fn chain_idx(&self) -> usize {
+        self.chain_idx
+    }
+
+    /// Start new initialization of the chain and reset its percentage
+    pub fn begin(&self) {
+        self.registry.update(self.chain_idx, Phase::Probing, true);
+    }
+
+    pub fn report(&self, phase: Phase) {
+        self.registry.update(self.chain_idx, phase, false);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CHIPS: usize = 63;
+
+    /// Mocked hash chain initialization which follows the order of phases of a real one.
+    /// The `observe` callback is called after each reported phase.
+    fn mock_init(
+        reporter: &Reporter,
+        chips: usize,
+        steps: usize,
+        observe: &mut impl FnMut(&Reporter),
+    ) {
+        reporter.report(Phase::Probing);
+        observe(reporter);
+        for chip in 1..=chips {
+            reporter.report(Phase::Enumerating { chip, chips });
+            observe(reporter);
+        }
+        for step in 1..=steps {
+            reporter.report(Phase::SettingFrequency { step, steps });
+            observe(reporter);
+        }
+    }
+
+    fn get_chain(registry: &Registry, chain_idx: usize) -> Chain {
+        registry
+            .snapshot()
+            .into_iter()
+            .find(|(idx, _)| *idx == chain_idx)
+            .map(|(_, chain)| chain)
+            .expect("BUG: missing chain")
+    }
+
+    fn assert_monotonic(stream: &[(Phase, f64)]) {
+        for pair in stream.windows(2) {
+            assert!(
+                pair[0].1 <= pair[1].1,
+                "progress decreased from {:?} to {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_phase_stream() {
+        let registry = Arc::new(Registry::new());
+        let reporter = registry.reporter(6);
+        let mut stream = vec![];
+        let mut observe = |reporter: &Reporter| {
+            let chain = get_chain(&registry, reporter.chain_idx());
+            stream.push((chain.phase, chain.percent));
+        };
+
+        reporter.begin();
+        observe(&reporter);
+        mock_init(&reporter, CHIPS, CHIPS, &mut observe);
+        reporter.report(Phase::Ready);
+        observe(&reporter);
+
+        assert_eq!(stream.len(), 2 * CHIPS + 3);
+        assert_eq!(stream[0], (Phase::Probing, 0.0));
+        assert_eq!(
+            stream[2].0,
+            Phase::Enumerating {
+                chip: 1,
+                chips: CHIPS
+            }
+        );
+        assert_eq!(
+            stream[CHIPS + 1],
+            (
+                Phase::Enumerating {
+                    chip: CHIPS,
+                    chips: CHIPS
+                },
+                50.0
+            )
+        );
+        assert_eq!(
+            stream[2 * CHIPS + 1],
+            (
+                Phase::SettingFrequency {
+                    step: CHIPS,
+                    steps: CHIPS
+                },
+                90.0
+            )
+        );
+        assert_eq!(stream.last(), Some(&(Phase::Ready, 100.0)));
+        assert_monotonic(&stream);
+        assert!(registry.is_finished());
+    }
+
+    #[test]
+    fn test_retried_init() {
+        let registry = Arc::new(Registry::new());
+        let reporter = registry.reporter(7);
+        let mut stream = vec![];
+        let mut observe = |reporter: &Reporter| {
+            let chain = get_chain(&registry, reporter.chain_idx());
+            stream.push((chain.phase, chain.percent));
+        };
+
+        reporter.begin();
+        // the first attempt fails after enumeration of incomplete chain
+        mock_init(&reporter, 60, 0, &mut observe);
+        assert!(!registry.is_finished());
+        // the second attempt starts from the beginning but the percentage does not go back
+        mock_init(&reporter, CHIPS, 1, &mut observe);
+
+        let retry = stream
+            .iter()
+            .rposition(|(phase, _)| *phase == Phase::Probing)
+            .expect("BUG: missing retry");
+        assert!(retry > 0);
+        assert_eq!(stream[retry].1, stream[retry - 1].1);
+        assert_monotonic(&stream);
+        assert_eq!(get_chain(&registry, 7).percent, 90.0);
+
+        // failure keeps the percentage
+        reporter.report(Phase::Failed);
+        let chain = get_chain(&registry, 7);
+        assert_eq!(chain.phase, Phase::Failed);
+        assert_eq!(chain.percent, 90.0);
+        assert!(registry.is_finished());
+
+        // new start of the chain resets the percentage
+        reporter.begin();
+        assert_eq!(get_chain(&registry, 7).percent, 0.0);
+        assert!(!registry.is_finished());
+    }
+
+    #[test]
+    fn test_phase_display() {
+        assert_eq!(Phase::Probing.to_string(), "probing chain");
+        assert_eq!(
+            Phase::Enumerating { chip: 5, chips: 63 }.to_string(),
+            "enumerating chips 5/63"
+        );
+        assert_eq!(
+            Phase::SettingFrequency { step: 2, steps: 3 }.to_string(),
+            "setting frequency step 2/3"
+        );
+        assert_eq!(Phase::Ready.to_string(), "chain ready");
+        assert_eq!(
+            Phase::SettingFrequency { step: 0, steps: 0 }.percent(),
+            Some(50.0)
+        );
+    }
+}
@@ -0,0 +1,670 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Single place deciding whether a solution of possibly outdated work is submitted to the pool.
+//! The decision is a pure function of the solution context and the policy of the pool so the
+//! rules can be audited and tested without any client.
+
+use ii_bitcoin::MeetsTarget;
+
+use crate::stats;
+use crate::work;
+
+use bosminer_config::ClientSharePolicy;
+
+use std::fmt;
+use std::time;
+
+/// Reason why the solution has not been submitted as a regular share
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The job has been invalidated by the client (e.g. after reconnect)
+    Stale,
+    /// The job has been superseded by a job building on another block
+    CleanJob,
+    /// Timestamp of the solution is out of the range allowed by the policy
+    NtimeOutOfRange,
+    /// Timestamp of the solution has been rolled beyond the limit advertised by the pool
+    NtimeRollLimit,
+    /// The solution meets only the difficulty in effect when the work has been generated
+    OldDifficulty,
+    /// The same solution has been already submitted (e.g. replayed by the backend)
+    Duplicate,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Reason::Stale => "stale job",
+            Reason::CleanJob => "job superseded by clean job",
+            Reason::NtimeOutOfRange => "ntime out of range",
+            Reason::NtimeRollLimit => "ntime roll limit exceeded",
+            Reason::OldDifficulty => "old difficulty",
+            Reason::Duplicate => "duplicate solution",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Submit,
+    /// Submit the solution even though the pool may consider it stale
+    SubmitFlagged(Reason),
+    Drop(Reason),
+}
+
+impl Decision {
+    #[inline]
+    pub fn is_submitted(&self) -> bool {
+        match self {
+            Decision::Submit | Decision::SubmitFlagged(_) => true,
+            Decision::Drop(_) => false,
+        }
+    }
+}
+
+/// Everything known about the solution which is relevant to the decision
+#[derive(Debug, Clone, PartialEq)]
+pub struct Context {
+    /// The client still considers the job valid for mining
+    pub job_valid: bool,
+    /// Time elapsed since the job has been superseded by the following one (`None` for the
+    /// current job or when the job is no longer tracked)
+    pub superseded_for: Option<time::Duration>,
+    /// The job has been superseded by a job building on another block
+    pub clean_superseded: bool,
+    /// Pool difficulty in effect when the work has been generated
+    pub generation_difficulty: usize,
+    /// Pool difficulty of the current job
+    pub current_difficulty: usize,
+    /// The solution meets the target of the current job
+    pub meets_current_difficulty: bool,
+    /// Difference in seconds between timestamp of the solution and timestamp of its job
+    pub ntime_delta: i64,
+    /// Ntime roll limit advertised by the pool for the job (zero when there is no limit)
+    pub ntime_roll_limit: u32,
+}
+
+impl Context {
+    /// Collect context of the `solution` from its job and the `job_history` of its client
+    pub fn new(
+        solution: &work::Solution,
+        job_history: Option<&stats::JobHistory>,
+        now: time::Instant,
+    ) -> Self {
+        let job = solution.dyn_job();
+        let supersession = job_history.and_then(|job_history| job_history.supersession(job));
+        let generation_target = *solution.job_target();
+        let current_target = job_history
+            .and_then(|job_history| job_history.current_target())
+            .unwrap_or(generation_target);
+
+        Self {
+            job_valid: solution.has_valid_job(),
+            superseded_for: supersession.map(|supersession| {
+                now.checked_duration_since(supersession.time)
+                    .unwrap_or_default()
+            }),
+            clean_superseded: supersession.map_or(false, |supersession| supersession.clean),
+            generation_difficulty: generation_target.get_difficulty(),
+            current_difficulty: current_target.get_difficulty(),
+            meets_current_difficulty: solution.hash().meets(&current_target),
+            ntime_delta: solution.time() as i64 - job.time() as i64,
+            ntime_roll_limit: job.ntime_roll_limit(),
+        }
+    }
+}
+
+/// Decide about the solution with `context` according to the `policy` of its pool. The rules are
+/// evaluated in this order:
+/// - solution with timestamp out of range is always dropped because the pool cannot accept it
+/// - solution with timestamp rolled beyond the limit advertised by the pool is always dropped
+/// - solution of a job superseded by a clean job is dropped unless the policy allows submitting
+///   it within the grace period
+/// - solution of an invalidated job is submitted only within the grace period unless the policy
+///   allows submitting stale solutions
+/// - solution meeting only the difficulty of the work generation is submitted when allowed
+pub fn decide(context: &Context, policy: &ClientSharePolicy) -> Decision {
+    if context.ntime_delta < 0 || context.ntime_delta > policy.max_ntime_delta() as i64 {
+        return Decision::Drop(Reason::NtimeOutOfRange);
+    }
+    if context.ntime_roll_limit != 0 && context.ntime_delta > context.ntime_roll_limit as i64 {
+        return Decision::Drop(Reason::NtimeRollLimit);
+    }
+
+    let within_grace_period = context.superseded_for.map_or(false, |superseded_for| {
+        superseded_for < policy.grace_period()
+    });
+    if context.clean_superseded {
+        return if policy.submit_clean_jobs() && within_grace_period {
+            Decision::SubmitFlagged(Reason::CleanJob)
+        } else {
+            Decision::Drop(Reason::CleanJob)
+        };
+    }
+    if !context.job_valid {
+        return if within_grace_period || policy.submit_stale() {
+            Decision::SubmitFlagged(Reason::Stale)
+        } else {
+            Decision::Drop(Reason::Stale)
+        };
+    }
+
+    if context.current_difficulty > context.generation_difficulty
+        && !context.meets_current_difficulty
+    {
+        return if policy.submit_old_difficulty() {
+            Decision::SubmitFlagged(Reason::OldDifficulty)
+        } else {
+            Decision::Drop(Reason::OldDifficulty)
+        };
+    }
+    Decision::Submit
+}
+
+/// Number of solutions flagged or dropped for a particular reason
+#[derive(Debug, Default)]
+pub struct ReasonCounters {
+    pub flagged: stats::CounterU64,
+    pub dropped: stats::CounterU64,
+}
+
+/// Accounting of all decisions other than regular submission
+#[derive(Debug, Default)]
+pub struct Counters {
+    stale: ReasonCounters,
+    clean_job: ReasonCounters,
+    ntime_out_of_range: ReasonCounters,
+    ntime_roll_limit: ReasonCounters,
+    old_difficulty: ReasonCounters,
+    duplicate: ReasonCounters,
+}
+
+impl Counters {
+    pub fn get(&self, reason: Reason) -> &ReasonCounters {
+        match reason {
+            Reason::Stale => &self.stale,
+            Reason::CleanJob => &self.clean_job,
+            Reason::NtimeOutOfRange => &self.ntime_out_of_range,
+            Reason::NtimeRollLimit => &self.ntime_roll_limit,
+            Reason::OldDifficulty => &self.old_difficulty,
+            Reason::Duplicate => &self.duplicate,
+        }
+    }
+
+    pub fn account(&self, decision: Decision) {
+        match decision {
+            Decision::Submit => {}
+            Decision::SubmitFlagged(reason) => self.get(reason).flagged.inc(),
+            Decision::Drop(reason) => self.get(reason).dropped.inc(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MILLISECOND: time::Duration = time::Duration::from_millis(1);
+    const SECOND: time::Duration = time::Duration::from_secs(1);
+
+    /// Solution of the current job found at the difficulty which is still in effect
+    fn current() -> Context {
+        Context {
+            job_valid: true,
+            superseded_for: None,
+            clean_superseded: false,
+            generation_difficulty: 1024,
+            current_difficulty: 1024,
+            meets_current_difficulty: true,
+            ntime_delta: 0,
+            ntime_roll_limit: 0,
+        }
+    }
+
+    fn with(f: impl FnOnce(&mut Context)) -> Context {
+        let mut context = current();
+        f(&mut context);
+        context
+    }
+
+    fn policy(
+        grace_period: u64,
+        submit_clean_jobs: bool,
+        submit_old_difficulty: bool,
+    ) -> ClientSharePolicy {
+        ClientSharePolicy {
+            grace_period: Some(grace_period),
+            submit_clean_jobs: Some(submit_clean_jobs),
+            submit_stale: None,
+            max_ntime_delta: Some(600),
+            submit_old_difficulty: Some(submit_old_difficulty),
+            duplicate_window: None,
+            accept_foreign: None,
+        }
+    }
+
+    #[test]
+    fn test_decision_table() {
+        let strict = policy(0, false, false);
+        let lenient = policy(1000, true, true);
+        let no_clean_jobs = policy(1000, false, true);
+        let stale = ClientSharePolicy {
+            submit_stale: Some(true),
+            ..policy(0, false, false)
+        };
+        let default = ClientSharePolicy::default();
+
+        let table = vec![
+            ("current job", current(), &strict, Decision::Submit),
+            ("current job", current(), &lenient, Decision::Submit),
+            ("current job", current(), &default, Decision::Submit),
+            (
+                "superseded job which is still valid",
+                with(|c| c.superseded_for = Some(60 * SECOND)),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "invalid job without grace period",
+                with(|c| {
+                    c.job_valid = false;
+                    c.superseded_for = Some(MILLISECOND);
+                }),
+                &strict,
+                Decision::Drop(Reason::Stale),
+            ),
+            (
+                "invalid job within grace period",
+                with(|c| {
+                    c.job_valid = false;
+                    c.superseded_for = Some(999 * MILLISECOND);
+                }),
+                &lenient,
+                Decision::SubmitFlagged(Reason::Stale),
+            ),
+            (
+                "invalid job at the end of grace period",
+                with(|c| {
+                    c.job_valid = false;
+                    c.superseded_for = Some(SECOND);
+                }),
+                &lenient,
+                Decision::Drop(Reason::Stale),
+            ),
+            (
+                "invalid job which has not been superseded",
+                with(|c| c.job_valid = false),
+                &lenient,
+                Decision::Drop(Reason::Stale),
+            ),
+            (
+                "invalid job with default policy",
+                with(|c| {
+                    c.job_valid = false;
+                    c.superseded_for = Some(Default::default());
+                }),
+                &default,
+                Decision::Drop(Reason::Stale),
+            ),
+            (
+                "invalid job with stale solutions allowed",
+                with(|c| c.job_valid = false),
+                &stale,
+                Decision::SubmitFlagged(Reason::Stale),
+            ),
+            (
+                "clean job with stale solutions allowed",
+                with(|c| {
+                    c.job_valid = false;
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(MILLISECOND);
+                }),
+                &stale,
+                Decision::Drop(Reason::CleanJob),
+            ),
+            (
+                "clean job not allowed",
+                with(|c| {
+                    c.job_valid = false;
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(MILLISECOND);
+                }),
+                &no_clean_jobs,
+                Decision::Drop(Reason::CleanJob),
+            ),
+            (
+                "clean job within grace period",
+                with(|c| {
+                    c.job_valid = false;
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(MILLISECOND);
+                }),
+                &lenient,
+                Decision::SubmitFlagged(Reason::CleanJob),
+            ),
+            (
+                "clean job after grace period",
+                with(|c| {
+                    c.job_valid = false;
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(2 * SECOND);
+                }),
+                &lenient,
+                Decision::Drop(Reason::CleanJob),
+            ),
+            (
+                "clean job which is still considered valid by the client",
+                with(|c| {
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(MILLISECOND);
+                }),
+                &strict,
+                Decision::Drop(Reason::CleanJob),
+            ),
+            (
+                "clean job with default policy",
+                with(|c| {
+                    c.clean_superseded = true;
+                    c.superseded_for = Some(Default::default());
+                }),
+                &default,
+                Decision::Drop(Reason::CleanJob),
+            ),
+            (
+                "ntime rolled backwards",
+                with(|c| c.ntime_delta = -1),
+                &lenient,
+                Decision::Drop(Reason::NtimeOutOfRange),
+            ),
+            (
+                "ntime at the limit",
+                with(|c| c.ntime_delta = 600),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "ntime over the limit",
+                with(|c| c.ntime_delta = 601),
+                &lenient,
+                Decision::Drop(Reason::NtimeOutOfRange),
+            ),
+            (
+                "ntime over the limit takes precedence over stale job",
+                with(|c| {
+                    c.job_valid = false;
+                    c.ntime_delta = 601;
+                }),
+                &strict,
+                Decision::Drop(Reason::NtimeOutOfRange),
+            ),
+            (
+                "ntime within default limit",
+                with(|c| c.ntime_delta = 7200),
+                &default,
+                Decision::Submit,
+            ),
+            (
+                "ntime without roll limit of the pool",
+                with(|c| c.ntime_delta = 600),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "ntime at small roll limit of the pool",
+                with(|c| {
+                    c.ntime_roll_limit = 1;
+                    c.ntime_delta = 1;
+                }),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "ntime over small roll limit of the pool",
+                with(|c| {
+                    c.ntime_roll_limit = 1;
+                    c.ntime_delta = 2;
+                }),
+                &lenient,
+                Decision::Drop(Reason::NtimeRollLimit),
+            ),
+            (
+                "ntime over roll limit takes precedence over stale job",
+                with(|c| {
+                    c.job_valid = false;
+                    c.ntime_roll_limit = 1;
+                    c.ntime_delta = 2;
+                }),
+                &stale,
+                Decision::Drop(Reason::NtimeRollLimit),
+            ),
+            (
+                "ntime within large roll limit of the pool",
+                with(|c| {
+                    c.ntime_roll_limit = 7200;
+                    c.ntime_delta = 600;
+                }),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "policy is stricter than large roll limit of the pool",
+                with(|c| {
+                    c.ntime_roll_limit = 7200;
+                    c.ntime_delta = 601;
+                }),
+                &strict,
+                Decision::Drop(Reason::NtimeOutOfRange),
+            ),
+            (
+                "old difficulty submitted",
+                with(|c| {
+                    c.current_difficulty = 2048;
+                    c.meets_current_difficulty = false;
+                }),
+                &lenient,
+                Decision::SubmitFlagged(Reason::OldDifficulty),
+            ),
+            (
+                "old difficulty dropped",
+                with(|c| {
+                    c.current_difficulty = 2048;
+                    c.meets_current_difficulty = false;
+                }),
+                &strict,
+                Decision::Drop(Reason::OldDifficulty),
+            ),
+            (
+                "old difficulty with default policy",
+                with(|c| {
+                    c.current_difficulty = 2048;
+                    c.meets_current_difficulty = false;
+                }),
+                &default,
+                Decision::SubmitFlagged(Reason::OldDifficulty),
+            ),
+            (
+                "old difficulty met by the solution anyway",
+                with(|c| c.current_difficulty = 2048),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "decreased difficulty",
+                with(|c| {
+                    c.current_difficulty = 512;
+                    c.meets_current_difficulty = true;
+                }),
+                &strict,
+                Decision::Submit,
+            ),
+            (
+                "stale job takes precedence over old difficulty",
+                with(|c| {
+                    c.job_valid = false;
+                    c.current_difficulty = 2048;
+                    c.meets_current_difficulty = false;
+                }),
+                &lenient,
+                Decision::Drop(Reason::Stale),
+            ),
+        ];
+
+        for (name, context, policy, expected) in table {
+            assert_eq!(decide(&context, policy), expected, "{}", name);
+        }
+    }
+
+    /// Check properties of the decisions over all combinations of context and policy
+    #[test]
+    fn test_decision_matrix() {
+        let superseded_fors = [
+            None,
+            Some(Default::default()),
+            Some(999 * MILLISECOND),
+            Some(SECOND),
+            Some(60 * SECOND),
+        ];
+        // generation difficulty, current difficulty, solution meets the current one
+        let difficulties = [
+            (1024, 1024, true),
+            (1024, 2048, true),
+            (1024, 2048, false),
+            (2048, 1024, true),
+        ];
+        let ntime_deltas = [-1, 0, 1, 2, 600, 601];
+        let ntime_roll_limits = [0, 1, 7200];
+
+        let mut contexts = vec![];
+        for &job_valid in &[false, true] {
+            for &clean_superseded in &[false, true] {
+                for &superseded_for in &superseded_fors {
+                    for &(generation, current, meets) in &difficulties {
+                        for &ntime_delta in &ntime_deltas {
+                            for &ntime_roll_limit in &ntime_roll_limits {
+                                contexts.push(Context {
+                                    job_valid,
+                                    superseded_for,
+                                    clean_superseded,
+                                    generation_difficulty: generation,
+                                    current_difficulty: current,
+                                    meets_current_difficulty: meets,
+                                    ntime_delta,
+                                    ntime_roll_limit,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let mut policies = vec![];
+        for &grace_period in &[0, 1000] {
+            for &submit_clean_jobs in &[false, true] {
+                for &submit_old_difficulty in &[false, true] {
+                    policies.push(policy(
+                        grace_period,
+                        submit_clean_jobs,
+                        submit_old_difficulty,
+                    ));
+                }
+            }
+        }
+
+        let counters = Counters::default();
+        let mut not_submitted = 0;
+        for policy in &policies {
+            for context in &contexts {
+                let decision = decide(context, policy);
+                counters.account(decision);
+
+                let ntime_in_range = context.ntime_delta >= 0 && context.ntime_delta <= 600;
+                let ntime_valid = ntime_in_range
+                    && (context.ntime_roll_limit == 0
+                        || context.ntime_delta <= context.ntime_roll_limit as i64);
+                let within_grace_period = context.superseded_for.map_or(false, |superseded_for| {
+                    superseded_for < policy.grace_period()
+                });
+                let old_difficulty = context.current_difficulty > context.generation_difficulty
+                    && !context.meets_current_difficulty;
+                let regular = ntime_valid
+                    && context.job_valid
+                    && !context.clean_superseded
+                    && !old_difficulty;
+
+                assert_eq!(decision == Decision::Submit, regular, "{:?}", context);
+                match decision {
+                    Decision::Submit => {}
+                    Decision::SubmitFlagged(reason) => {
+                        not_submitted += 1;
+                        assert!(ntime_valid, "{:?}", context);
+                        let allowed = match reason {
+                            Reason::CleanJob => policy.submit_clean_jobs() && within_grace_period,
+                            Reason::Stale => !context.job_valid && within_grace_period,
+                            Reason::OldDifficulty => {
+                                policy.submit_old_difficulty() && old_difficulty
+                            }
+                            Reason::NtimeOutOfRange
+                            | Reason::NtimeRollLimit
+                            | Reason::Duplicate => false,
+                        };
+                        assert!(allowed, "{:?} {:?}", reason, context);
+                    }
+                    Decision::Drop(reason) => {
+                        not_submitted += 1;
+                        if !ntime_in_range {
+                            assert_eq!(reason, Reason::NtimeOutOfRange);
+                        } else if !ntime_valid {
+                            assert_eq!(reason, Reason::NtimeRollLimit);
+                        }
+                        assert!(!decision.is_submitted());
+                    }
+                }
+            }
+        }
+        assert_eq!(policies.len() * contexts.len(), 8 * 2 * 2 * 5 * 4 * 6 * 3);
+
+        // every flagged or dropped solution is accounted exactly once
+        let reasons = [
+            Reason::Stale,
+            Reason::CleanJob,
+            Reason::NtimeOutOfRange,
+            Reason::NtimeRollLimit,
+            Reason::OldDifficulty,
+        ];
+        let accounted: u64 = reasons
+            .iter()
+            .map(|reason| {
+                let reason_counters = counters.get(*reason);
+                *reason_counters.flagged.take_snapshot() + *reason_counters.dropped.take_snapshot()
+            })
+            .sum();
+        assert_eq!(accounted, not_submitted);
+        for &reason in &[Reason::NtimeOutOfRange, Reason::NtimeRollLimit] {
+            assert_eq!(*counters.get(reason).flagged.take_snapshot(), 0);
+        }
+        assert!(reasons
+            .iter()
+            .all(|reason| *counters.get(*reason).dropped.take_snapshot() > 0));
+    }
+}
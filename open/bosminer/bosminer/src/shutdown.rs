@@ -0,0 +1,74 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Graceful termination of the miner when the process receives a termination signal
+
+use ii_logging::macros::*;
+
+use crate::hub;
+
+#[cfg(unix)]
+use futures::stream::{self, StreamExt};
+#[cfg(unix)]
+use ii_async_compat::futures;
+use ii_async_compat::tokio;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time given to in-flight solutions to reach their clients
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Wait for `SIGINT` or `SIGTERM` (`SIGHUP` is reserved for configuration reload)
+#[cfg(unix)]
+async fn wait_for_signal() {
+    let signals = vec![SignalKind::interrupt(), SignalKind::terminate()]
+        .into_iter()
+        .map(|signal_type| signal(signal_type).expect("BUG: failed hooking signal"));
+    stream::select_all(signals).next().await;
+}
+
+/// Wait for Ctrl-C which is the only termination signal available on all platforms
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("BUG: failed hooking Ctrl-C");
+}
+
+/// Shut down the miner gracefully with the grace period configured by the backend and exit the
+/// process. It is used both for termination signals and for the API command.
+pub async fn terminate(core: Arc<hub::Core>) {
+    #[cfg(all(feature = "systemd", unix))]
+    crate::systemd::notify_stopping();
+    core.shutdown(core.get_shutdown_grace_period()).await;
+    std::process::exit(0);
+}
+
+/// Shut down the miner gracefully after receiving a termination signal and exit the process
+pub async fn run(core: Arc<hub::Core>) {
+    wait_for_signal().await;
+    info!("Termination signal received");
+    terminate(core).await;
+}
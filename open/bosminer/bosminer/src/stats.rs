@@ -20,22 +20,31 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+pub mod persist;
+
 use ii_logging::macros::*;
 
+use crate::client::submission;
+use crate::clock;
+use crate::job;
 use crate::node;
+use crate::share_policy;
 use crate::stats;
 use crate::work;
 
 use bosminer_macros::{ClientStats, MiningStats, WorkSolverStats};
 
-use ii_stats::WindowedTimeMean;
+use ii_stats::{ExponentialTimeMean, TimeBuckets};
 
 use futures::lock::Mutex;
 use ii_async_compat::{futures, tokio};
+use tokio::sync::watch;
 use tokio::time::delay_for;
 
-use std::fmt::Debug;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
 use std::time;
 
 use once_cell::sync::Lazy;
@@ -50,6 +59,11 @@ pub static TIME_MEAN_INTERVAL_15M: Lazy<time::Duration> =
 pub static TIME_MEAN_INTERVAL_24H: Lazy<time::Duration> =
     Lazy::new(|| time::Duration::from_secs(24 * 60 * 60));
 
+/// Width of a single bucket of meter history used for time range queries
+pub const METER_HISTORY_BUCKET: time::Duration = time::Duration::from_secs(10);
+/// Number of buckets in meter history (the history spans one hour)
+pub const METER_HISTORY_LENGTH: usize = 360;
+
 static DEFAULT_TIME_MEAN_INTERVALS: Lazy<Vec<time::Duration>> = Lazy::new(|| {
     vec![
         *TIME_MEAN_INTERVAL_5S,
@@ -69,7 +83,7 @@ pub struct Snapshot<T> {
 impl<T> Snapshot<T> {
     pub fn new(inner: T) -> Self {
         Self {
-            snapshot_time: time::Instant::now(),
+            snapshot_time: clock::now(),
             inner,
         }
     }
@@ -90,12 +104,14 @@ pub struct MeterSnapshot {
     pub solutions: u64,
     /// All shares measured from the beginning of the mining
     pub shares: ii_bitcoin::Shares,
-    /// Approximate arithmetic mean of hashes within given time intervals (in kH/time)
-    time_means: Vec<WindowedTimeMean>,
+    /// Exponentially decaying mean of hashes with given time intervals (in kH/time)
+    time_means: Vec<ExponentialTimeMean>,
+    /// Optional history of hashes (in kH) for time range queries
+    history: Option<TimeBuckets>,
 }
 
 impl MeterSnapshot {
-    fn get_time_mean(&self, interval: time::Duration) -> &WindowedTimeMean {
+    fn get_time_mean(&self, interval: time::Duration) -> &ExponentialTimeMean {
         self.time_means
             .iter()
             .find(|time_mean| time_mean.interval() == interval)
@@ -146,6 +162,38 @@ impl MeterSnapshot {
     ) -> ii_bitcoin::HashesUnit {
         self.to_kilo_hashes(interval, now).into_pretty_hashes()
     }
+
+    /// Return `true` when the meter keeps history which covers given time
+    pub fn history_covers(&self, time: time::Instant, now: time::Instant) -> bool {
+        self.history
+            .as_ref()
+            .map_or(false, |history| history.covers(time, now))
+    }
+
+    /// Number of solutions which have been accounted too late to be kept in the history. It is
+    /// available only for meters with history.
+    pub fn history_dropped(&self) -> Option<u64> {
+        self.history.as_ref().map(TimeBuckets::dropped)
+    }
+
+    /// Number of solutions accounted in the interval `<from, to)`. It is available only for
+    /// meters with history.
+    pub fn solutions_between(&self, from: time::Instant, to: time::Instant) -> Option<f64> {
+        self.history
+            .as_ref()
+            .map(|history| history.between(from, to).0)
+    }
+
+    /// Hashrate in the interval `<from, to)`. It is available only for meters with history.
+    pub fn to_kilo_hashes_between(
+        &self,
+        from: time::Instant,
+        to: time::Instant,
+    ) -> Option<ii_bitcoin::HashesUnit> {
+        self.history
+            .as_ref()
+            .map(|history| ii_bitcoin::HashesUnit::KiloHashes(history.rate_between(from, to)))
+    }
 }
 
 #[derive(Debug)]
@@ -154,24 +202,46 @@ pub struct Meter {
 }
 
 impl Meter {
-    pub fn new(intervals: &Vec<time::Duration>) -> Self {
+    fn with_optional_history(
+        intervals: &Vec<time::Duration>,
+        history: Option<TimeBuckets>,
+    ) -> Self {
+        let now = clock::now();
         Self {
             inner: Mutex::new(MeterSnapshot {
                 solutions: 0,
                 shares: Default::default(),
                 time_means: intervals
                     .iter()
-                    .map(|&interval| WindowedTimeMean::new(interval))
+                    .map(|&interval| ExponentialTimeMean::new(interval, now))
                     .collect(),
+                history,
             }),
         }
     }
 
+    pub fn new(intervals: &Vec<time::Duration>) -> Self {
+        Self::with_optional_history(intervals, None)
+    }
+
+    /// Create meter which also keeps history of accounted solutions for time range queries
+    pub fn with_history(
+        intervals: &Vec<time::Duration>,
+        bucket_width: time::Duration,
+        bucket_count: usize,
+    ) -> Self {
+        Self::with_optional_history(
+            intervals,
+            Some(TimeBuckets::new(bucket_width, bucket_count)),
+        )
+    }
+
     pub async fn take_snapshot(&self) -> Snapshot<MeterSnapshot> {
         Snapshot::new(self.inner.lock().await.clone())
     }
 
     pub(crate) async fn account_solution(&self, target: &ii_bitcoin::Target, time: time::Instant) {
+        clock::check_instant(time);
         let mut meter = self.inner.lock().await;
         let kilo_hashes = ii_bitcoin::Shares::new(target)
             .into_kilo_hashes()
@@ -183,6 +253,18 @@ impl Meter {
         for time_mean in &mut meter.time_means {
             time_mean.insert(kilo_hashes, time);
         }
+        if let Some(history) = &mut meter.history {
+            history.insert(kilo_hashes, time);
+        }
+    }
+
+    /// Exclude the period `<since, now)` from the time means so they do not decay while nothing
+    /// is measured on purpose. The history for time range queries is not affected.
+    pub(crate) async fn exclude(&self, since: time::Instant, now: time::Instant) {
+        let mut meter = self.inner.lock().await;
+        for time_mean in &mut meter.time_means {
+            time_mean.exclude(since, now);
+        }
     }
 }
 
@@ -234,14 +316,15 @@ impl Default for LastShare {
     }
 }
 
+/// Highest difficulty of share hashes (it is usually higher than the difficulty of the target)
 #[derive(Debug)]
 pub struct BestShare {
-    inner: AtomicUsize,
+    inner: AtomicU64,
 }
 
 impl BestShare {
-    const INVALID_DIFFICULTY: usize = 0;
-    pub fn take_snapshot(&self) -> Option<Snapshot<usize>> {
+    const INVALID_DIFFICULTY: u64 = 0;
+    pub fn take_snapshot(&self) -> Option<Snapshot<u64>> {
         let difficulty = self.inner.load(Ordering::Relaxed);
         if difficulty == Self::INVALID_DIFFICULTY {
             None
@@ -250,8 +333,9 @@ impl BestShare {
         }
     }
 
-    pub(crate) fn account_solution(&self, target: &ii_bitcoin::Target) {
-        let new_diff = target.get_difficulty();
+    pub(crate) fn account_solution(&self, hash: &ii_bitcoin::DHash) {
+        // the difficulty is saturated for extremely small hashes
+        let new_diff = ii_bitcoin::Target::from(*hash).get_precise_difficulty() as u64;
         let mut old_diff = self.inner.load(Ordering::Relaxed);
 
         while old_diff < new_diff {
@@ -270,8 +354,40 @@ impl BestShare {
 impl Default for BestShare {
     fn default() -> Self {
         Self {
-            inner: AtomicUsize::new(Self::INVALID_DIFFICULTY),
+            inner: AtomicU64::new(Self::INVALID_DIFFICULTY),
+        }
+    }
+}
+
+/// Totals of previous runs of the miner restored from the persisted snapshot. They are kept
+/// apart from the other statistics which always start from zero in each process.
+#[derive(Debug, Default)]
+pub struct Lifetime {
+    inner: StdMutex<Option<persist::Totals>>,
+}
+
+impl Lifetime {
+    fn lock_inner(&self) -> StdMutexGuard<Option<persist::Totals>> {
+        self.inner.lock().expect("BUG: cannot lock lifetime totals")
+    }
+
+    /// Set totals of previous runs and return false when they have been already restored
+    pub fn restore(&self, totals: persist::Totals) -> bool {
+        let mut inner = self.lock_inner();
+        if inner.is_some() {
+            return false;
         }
+        inner.replace(totals);
+        true
+    }
+
+    pub fn is_restored(&self) -> bool {
+        self.lock_inner().is_some()
+    }
+
+    /// Return totals of previous runs (zero when nothing has been restored)
+    pub fn take_snapshot(&self) -> persist::Totals {
+        self.lock_inner().unwrap_or_default()
     }
 }
 
@@ -383,7 +499,7 @@ impl Timestamp {
         self.inner
             .lock()
             .await
-            .replace(time.into().unwrap_or_else(|| time::SystemTime::now()));
+            .replace(time.into().unwrap_or_else(|| clock::system_time()));
     }
 }
 
@@ -393,6 +509,1081 @@ impl Default for Timestamp {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct WorkProgressSnapshot {
+    /// Sequence number of the last assignment delivered to the work solver
+    pub last_assignment_id: Option<u64>,
+    pub last_assignment_time: Option<time::SystemTime>,
+    /// Nonce of the last solution received from the work solver
+    pub last_nonce: Option<u32>,
+    pub last_solution_time: Option<time::SystemTime>,
+    /// Number of distinct work engines observed by the work generator
+    pub engine_generation: u64,
+}
+
+/// Progress of work passing through a work solver intended for debugging of stuck chains.
+/// All values are plain atomics because they are updated for every generated work and solution.
+#[derive(Debug, Default)]
+pub struct WorkProgress {
+    assignments: AtomicU64,
+    /// Unix time in milliseconds (zero when no assignment has been delivered yet)
+    assignment_time: AtomicU64,
+    solutions: AtomicU64,
+    last_nonce: AtomicU32,
+    /// Unix time in milliseconds (zero when no solution has been received yet)
+    solution_time: AtomicU64,
+    engine_generation: AtomicU64,
+}
+
+impl WorkProgress {
+    fn to_unix_millis(time: time::SystemTime) -> u64 {
+        time.duration_since(time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64)
+    }
+
+    fn from_unix_millis(millis: u64) -> Option<time::SystemTime> {
+        if millis == 0 {
+            None
+        } else {
+            Some(time::UNIX_EPOCH + time::Duration::from_millis(millis))
+        }
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<WorkProgressSnapshot> {
+        let assignments = self.assignments.load(Ordering::Relaxed);
+        let solutions = self.solutions.load(Ordering::Relaxed);
+        Snapshot::new(WorkProgressSnapshot {
+            last_assignment_id: Some(assignments).filter(|id| *id > 0),
+            last_assignment_time: Self::from_unix_millis(
+                self.assignment_time.load(Ordering::Relaxed),
+            ),
+            last_nonce: if solutions > 0 {
+                Some(self.last_nonce.load(Ordering::Relaxed))
+            } else {
+                None
+            },
+            last_solution_time: Self::from_unix_millis(self.solution_time.load(Ordering::Relaxed)),
+            engine_generation: self.engine_generation.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Account delivered work assignment and return its sequence number
+    pub(crate) fn account_assignment(&self, engine_generation: u64, time: time::SystemTime) -> u64 {
+        clock::check_system_time(time);
+        self.engine_generation
+            .store(engine_generation, Ordering::Relaxed);
+        self.assignment_time
+            .store(Self::to_unix_millis(time), Ordering::Relaxed);
+        self.assignments.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn account_solution(&self, nonce: u32, time: time::SystemTime) {
+        clock::check_system_time(time);
+        self.last_nonce.store(nonce, Ordering::Relaxed);
+        self.solution_time
+            .store(Self::to_unix_millis(time), Ordering::Relaxed);
+        self.solutions.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Append `sample` to the window of the most recent `capacity` samples
+fn push_sample(samples: &mut VecDeque<time::Duration>, sample: time::Duration, capacity: usize) {
+    if samples.len() == capacity {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+/// Return percentile `p` (0..=100) using the nearest-rank method
+fn percentile(sorted_samples: &[time::Duration], p: usize) -> Option<time::Duration> {
+    if sorted_samples.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted_samples.len() + 99) / 100;
+    Some(sorted_samples[rank.max(1) - 1])
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JobLatencySnapshot {
+    /// Number of first assignments delivered from new jobs
+    pub deliveries: u64,
+    /// Time from job receipt to the first assignment delivered from it
+    pub latency_p50: Option<time::Duration>,
+    pub latency_p95: Option<time::Duration>,
+    /// Difference between the first assignments of the fastest and the slowest work solver
+    /// delivered from the same job
+    pub spread_p50: Option<time::Duration>,
+    pub spread_p95: Option<time::Duration>,
+    /// Time from job receipt to the first assignment of the slowest work solver (the duration
+    /// of the whole fan-out of the job)
+    pub fan_out_p50: Option<time::Duration>,
+    pub fan_out_p95: Option<time::Duration>,
+    /// Fan-out duration of the most recent job broadcast
+    pub last_fan_out: Option<time::Duration>,
+}
+
+/// Deliveries accounted so far for one job broadcast
+#[derive(Debug, Clone, Copy)]
+struct BroadcastDelivery {
+    generation: u64,
+    /// The largest lag behind the fastest work solver
+    spread: time::Duration,
+    /// The largest latency of all work solvers
+    fan_out: time::Duration,
+}
+
+#[derive(Debug, Default)]
+struct JobLatencyInner {
+    deliveries: u64,
+    latencies: VecDeque<time::Duration>,
+    spreads: VecDeque<time::Duration>,
+    fan_outs: VecDeque<time::Duration>,
+    /// The last job broadcast which can still receive deliveries
+    current_broadcast: Option<BroadcastDelivery>,
+}
+
+/// Internal job propagation latency measured from the moment the client passes a new job to
+/// the engine sender to the moment the work generator yields the first assignment from it.
+/// Only the most recent samples are kept for computation of percentiles.
+#[derive(Debug, Default)]
+pub struct JobLatency {
+    inner: StdMutex<JobLatencyInner>,
+}
+
+impl JobLatency {
+    pub const SAMPLE_COUNT: usize = 1000;
+
+    fn lock_inner(&self) -> StdMutexGuard<JobLatencyInner> {
+        self.inner.lock().expect("BUG: cannot lock job latency")
+    }
+
+    fn push_sample(samples: &mut VecDeque<time::Duration>, sample: time::Duration) {
+        push_sample(samples, sample, Self::SAMPLE_COUNT);
+    }
+
+    /// Account the first assignment delivered from job broadcast with sequence number
+    /// `generation`. The `lag` is the delay behind the fastest work solver for the same job.
+    pub(crate) fn account(&self, generation: u64, latency: time::Duration, lag: time::Duration) {
+        let mut inner = self.lock_inner();
+        inner.deliveries += 1;
+        Self::push_sample(&mut inner.latencies, latency);
+
+        match inner.current_broadcast.as_mut() {
+            Some(current) if current.generation == generation => {
+                current.spread = current.spread.max(lag);
+                current.fan_out = current.fan_out.max(latency);
+            }
+            // delivery for an older job after a newer one has been seen cannot be used for
+            // the spread
+            Some(current) if current.generation > generation => {}
+            _ => {
+                if let Some(previous) = inner.current_broadcast.take() {
+                    Self::push_sample(&mut inner.spreads, previous.spread);
+                    Self::push_sample(&mut inner.fan_outs, previous.fan_out);
+                }
+                inner.current_broadcast = Some(BroadcastDelivery {
+                    generation,
+                    spread: lag,
+                    fan_out: latency,
+                });
+            }
+        }
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<JobLatencySnapshot> {
+        let inner = self.lock_inner();
+        let mut latencies: Vec<_> = inner.latencies.iter().cloned().collect();
+        let mut spreads: Vec<_> = inner.spreads.iter().cloned().collect();
+        let mut fan_outs: Vec<_> = inner.fan_outs.iter().cloned().collect();
+        // the spread of the last job is still growing but it is better than no information
+        let last_fan_out = inner.current_broadcast.map(|current| {
+            spreads.push(current.spread);
+            fan_outs.push(current.fan_out);
+            current.fan_out
+        });
+        latencies.sort();
+        spreads.sort();
+        fan_outs.sort();
+
+        Snapshot::new(JobLatencySnapshot {
+            deliveries: inner.deliveries,
+            latency_p50: percentile(&latencies, 50),
+            latency_p95: percentile(&latencies, 95),
+            spread_p50: percentile(&spreads, 50),
+            spread_p95: percentile(&spreads, 95),
+            fan_out_p50: percentile(&fan_outs, 50),
+            fan_out_p95: percentile(&fan_outs, 95),
+            last_fan_out,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SolutionLatencySnapshot {
+    /// Number of solutions with known latency
+    pub solutions: u64,
+    /// Time from dispatch of the work to the hardware to receipt of its solution
+    pub latency_p50: Option<time::Duration>,
+    pub latency_p95: Option<time::Duration>,
+    /// The same latency as measured by the backend when it is able to do so
+    pub hw_latency_p50: Option<time::Duration>,
+    pub hw_latency_p95: Option<time::Duration>,
+}
+
+#[derive(Debug, Default)]
+struct SolutionLatencyInner {
+    solutions: u64,
+    latencies: VecDeque<time::Duration>,
+    hw_latencies: VecDeque<time::Duration>,
+}
+
+/// Latency of solutions measured from the moment the work has been dispatched to the hardware
+/// to the moment the solution has been received from it (including the latency of the driver).
+/// Only the most recent samples are kept for computation of percentiles.
+#[derive(Debug, Default)]
+pub struct SolutionLatency {
+    inner: StdMutex<SolutionLatencyInner>,
+}
+
+impl SolutionLatency {
+    pub const SAMPLE_COUNT: usize = 1000;
+
+    fn lock_inner(&self) -> StdMutexGuard<SolutionLatencyInner> {
+        self.inner
+            .lock()
+            .expect("BUG: cannot lock solution latency")
+    }
+
+    /// Account latency of one solution together with the latency reported by the backend
+    pub(crate) fn account(&self, latency: time::Duration, hw_latency: Option<time::Duration>) {
+        let mut inner = self.lock_inner();
+        inner.solutions += 1;
+        push_sample(&mut inner.latencies, latency, Self::SAMPLE_COUNT);
+        if let Some(hw_latency) = hw_latency {
+            push_sample(&mut inner.hw_latencies, hw_latency, Self::SAMPLE_COUNT);
+        }
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<SolutionLatencySnapshot> {
+        let inner = self.lock_inner();
+        let mut latencies: Vec<_> = inner.latencies.iter().cloned().collect();
+        let mut hw_latencies: Vec<_> = inner.hw_latencies.iter().cloned().collect();
+        latencies.sort();
+        hw_latencies.sort();
+
+        Snapshot::new(SolutionLatencySnapshot {
+            solutions: inner.solutions,
+            latency_p50: percentile(&latencies, 50),
+            latency_p95: percentile(&latencies, 95),
+            hw_latency_p50: percentile(&hw_latencies, 50),
+            hw_latency_p95: percentile(&hw_latencies, 95),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubmitLatencySnapshot {
+    /// Number of submissions with known latency
+    pub submissions: u64,
+    /// Time from the submission of a share to the response of the pool
+    pub latency_p50: Option<time::Duration>,
+    pub latency_p95: Option<time::Duration>,
+}
+
+#[derive(Debug, Default)]
+struct SubmitLatencyInner {
+    submissions: u64,
+    latencies: VecDeque<time::Duration>,
+}
+
+/// Latency of the pool measured from the submission of a share to the response to it. Only the
+/// most recent samples are kept for computation of percentiles.
+#[derive(Debug, Default)]
+pub struct SubmitLatency {
+    inner: StdMutex<SubmitLatencyInner>,
+}
+
+impl SubmitLatency {
+    pub const SAMPLE_COUNT: usize = 100;
+
+    fn lock_inner(&self) -> StdMutexGuard<SubmitLatencyInner> {
+        self.inner.lock().expect("BUG: cannot lock submit latency")
+    }
+
+    pub(crate) fn account(&self, latency: time::Duration) {
+        let mut inner = self.lock_inner();
+        inner.submissions += 1;
+        push_sample(&mut inner.latencies, latency, Self::SAMPLE_COUNT);
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<SubmitLatencySnapshot> {
+        let inner = self.lock_inner();
+        let mut latencies: Vec<_> = inner.latencies.iter().cloned().collect();
+        latencies.sort();
+
+        Snapshot::new(SubmitLatencySnapshot {
+            submissions: inner.submissions,
+            latency_p50: percentile(&latencies, 50),
+            latency_p95: percentile(&latencies, 95),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkStarvationSnapshot {
+    /// Total time spent waiting for an engine which is able to provide some work
+    pub duration: time::Duration,
+    /// Number of such waits
+    pub waits: u64,
+    /// Number of engines which have been exhausted by the work solver
+    pub exhausted_engines: u64,
+}
+
+#[derive(Debug, Default)]
+struct WorkStarvationInner {
+    duration: time::Duration,
+    waits: u64,
+    /// Start of the interval used for detection of excessive starvation
+    interval_start: Option<time::Instant>,
+    /// Starvation accumulated in the current interval
+    interval_duration: time::Duration,
+    /// Excessive starvation has already been reported in the current interval
+    reported: bool,
+}
+
+/// Time the work solver has been starving for work because all engines were exhausted
+#[derive(Debug, Default)]
+pub struct WorkStarvation {
+    inner: StdMutex<WorkStarvationInner>,
+    exhausted_engines: CounterU64,
+}
+
+impl WorkStarvation {
+    /// Interval used for detection of excessive starvation
+    pub const INTERVAL: time::Duration = time::Duration::from_secs(60);
+    /// Starvation within one interval longer than this limit is excessive
+    pub const THRESHOLD: time::Duration = time::Duration::from_secs(10);
+
+    fn lock_inner(&self) -> StdMutexGuard<WorkStarvationInner> {
+        self.inner.lock().expect("BUG: cannot lock work starvation")
+    }
+
+    /// Account one wait for an engine which has finished at `now`. Returns starvation
+    /// accumulated in the current interval when it has just exceeded the `THRESHOLD` so that
+    /// it is reported only once per interval.
+    pub(crate) fn account(
+        &self,
+        duration: time::Duration,
+        now: time::Instant,
+    ) -> Option<time::Duration> {
+        let mut inner = self.lock_inner();
+        inner.duration += duration;
+        inner.waits += 1;
+
+        if inner
+            .interval_start
+            .map_or(true, |start| now.duration_since(start) >= Self::INTERVAL)
+        {
+            inner.interval_start = Some(now);
+            inner.interval_duration = time::Duration::from_secs(0);
+            inner.reported = false;
+        }
+        inner.interval_duration += duration;
+        if inner.reported || inner.interval_duration <= Self::THRESHOLD {
+            return None;
+        }
+        inner.reported = true;
+        Some(inner.interval_duration)
+    }
+
+    #[inline]
+    pub(crate) fn account_exhausted(&self) {
+        self.exhausted_engines.inc();
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<WorkStarvationSnapshot> {
+        let inner = self.lock_inner();
+        Snapshot::new(WorkStarvationSnapshot {
+            duration: inner.duration,
+            waits: inner.waits,
+            exhausted_engines: *self.exhausted_engines.take_snapshot(),
+        })
+    }
+}
+
+/// Entering or leaving quarantine
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuarantineEvent {
+    pub time: time::SystemTime,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QuarantineSnapshot {
+    pub enabled: bool,
+    /// Withheld solutions which meet the backend target
+    pub valid: u64,
+    /// Withheld solutions which have failed the local verification
+    pub invalid: u64,
+    pub events: Vec<QuarantineEvent>,
+}
+
+/// Quarantine of work solver suspected of producing corrupted solutions. Its solutions are
+/// verified locally and accounted only here so they never reach pools nor the share statistics.
+#[derive(Debug, Default)]
+pub struct Quarantine {
+    enabled: AtomicBool,
+    valid: CounterU64,
+    invalid: CounterU64,
+    events: StdMutex<VecDeque<QuarantineEvent>>,
+}
+
+impl Quarantine {
+    /// Maximal number of transitions kept in the history
+    const EVENTS_LENGTH: usize = 32;
+
+    fn lock_events(&self) -> StdMutexGuard<VecDeque<QuarantineEvent>> {
+        self.events
+            .lock()
+            .expect("BUG: cannot lock quarantine events")
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enter or leave quarantine and return true when the state has been changed
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        // hold the lock so that the recorded transitions are consistent with the state
+        let mut events = self.lock_events();
+        if self.enabled.swap(enabled, Ordering::Relaxed) == enabled {
+            return false;
+        }
+        if events.len() >= Self::EVENTS_LENGTH {
+            events.pop_front();
+        }
+        events.push_back(QuarantineEvent {
+            time: clock::system_time(),
+            enabled,
+        });
+        true
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<QuarantineSnapshot> {
+        let events = self.lock_events();
+        Snapshot::new(QuarantineSnapshot {
+            enabled: self.is_enabled(),
+            valid: *self.valid.take_snapshot(),
+            invalid: *self.invalid.take_snapshot(),
+            events: events.iter().cloned().collect(),
+        })
+    }
+
+    pub(crate) fn account_solution(&self, valid: bool) {
+        if valid {
+            self.valid.inc();
+        } else {
+            self.invalid.inc();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PauseSnapshot {
+    pub paused: bool,
+    /// Number of times the work solver has been paused
+    pub pauses: u64,
+    /// Total time spent in pause including the ongoing one
+    pub duration: time::Duration,
+    /// Time of the last pause or resume
+    pub last_change: Option<time::SystemTime>,
+}
+
+#[derive(Debug, Default)]
+struct PauseInner {
+    /// Start of the ongoing pause
+    since: Option<time::Instant>,
+    pauses: u64,
+    /// Time spent in all finished pauses
+    duration: time::Duration,
+    last_change: Option<time::SystemTime>,
+}
+
+/// Pause of work solver requested by the user. Its generator does not provide any work until
+/// the work solver is resumed.
+#[derive(Debug)]
+pub struct Pause {
+    inner: StdMutex<PauseInner>,
+    sender: watch::Sender<bool>,
+    receiver: watch::Receiver<bool>,
+}
+
+impl Pause {
+    fn lock_inner(&self) -> StdMutexGuard<PauseInner> {
+        self.inner.lock().expect("BUG: cannot lock pause")
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Return the time when the ongoing pause has started
+    pub fn since(&self) -> Option<time::Instant> {
+        self.lock_inner().since
+    }
+
+    /// Return the time the hashrate should be measured at. The time means are frozen at the
+    /// beginning of the ongoing pause so the paused time is not accounted in them.
+    pub fn measurement_time(&self, now: time::Instant) -> time::Instant {
+        self.since().map_or(now, |since| since.min(now))
+    }
+
+    /// Pause or resume the work solver and return true when the state has been changed
+    pub fn set_paused(&self, paused: bool) -> bool {
+        let mut inner = self.lock_inner();
+        if inner.since.is_some() == paused {
+            return false;
+        }
+        let now = clock::now();
+        if paused {
+            inner.since = Some(now);
+            inner.pauses += 1;
+        } else if let Some(since) = inner.since.take() {
+            inner.duration += now.duration_since(since);
+        }
+        inner.last_change = Some(clock::system_time());
+        self.sender
+            .broadcast(paused)
+            .expect("BUG: pause receiver has been dropped");
+        true
+    }
+
+    /// Wait until the work solver is resumed (returns immediately when it is not paused)
+    pub(crate) async fn wait_for_resume(&self) {
+        let mut receiver = self.receiver.clone();
+        while *receiver.borrow() {
+            if receiver.recv().await.is_none() {
+                break;
+            }
+        }
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<PauseSnapshot> {
+        let inner = self.lock_inner();
+        let ongoing = inner.since.map_or(time::Duration::from_secs(0), |since| {
+            clock::now().duration_since(since)
+        });
+        Snapshot::new(PauseSnapshot {
+            paused: inner.since.is_some(),
+            pauses: inner.pauses,
+            duration: inner.duration + ongoing,
+            last_change: inner.last_change,
+        })
+    }
+}
+
+impl Default for Pause {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Self {
+            inner: Default::default(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+/// Removal of the work solver from the backend hierarchy (e.g. unplugged or overheated hash
+/// board). It is final and the removed work solver keeps its statistics.
+#[derive(Debug)]
+pub struct Removal {
+    /// Time of the removal
+    time: StdMutex<Option<time::SystemTime>>,
+    sender: watch::Sender<bool>,
+    receiver: watch::Receiver<bool>,
+}
+
+impl Removal {
+    #[inline]
+    pub fn is_removed(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    pub fn removed_at(&self) -> Option<time::SystemTime> {
+        *self.time.lock().expect("BUG: cannot lock removal")
+    }
+
+    /// Mark the work solver as removed and return false when it has been already removed
+    pub fn set_removed(&self) -> bool {
+        let mut time = self.time.lock().expect("BUG: cannot lock removal");
+        if time.is_some() {
+            return false;
+        }
+        *time = Some(clock::system_time());
+        self.sender
+            .broadcast(true)
+            .expect("BUG: removal receiver has been dropped");
+        true
+    }
+
+    /// Wait until the work solver is removed
+    pub(crate) async fn wait_for_removal(&self) {
+        let mut receiver = self.receiver.clone();
+        while !*receiver.borrow() {
+            if receiver.recv().await.is_none() {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Removal {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Self {
+            time: Default::default(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+/// Condition which prevents the device from working well (it corresponds to the reasons
+/// reported by CGMiner `notify` command)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthIssue {
+    ThreadFailInit,
+    ThreadZeroHash,
+    ThreadFailQueue,
+    /// The device has not returned any result for 60 seconds
+    SickIdle,
+    /// The device has not returned any result for 600 seconds
+    DeadIdle,
+    /// The device has failed to start
+    NoStart,
+    OverHeat,
+    /// The device has been shut down because of dangerous temperature
+    ThermalCutoff,
+    /// Communication with the device has failed
+    CommsError,
+    Throttle,
+}
+
+impl HealthIssue {
+    pub const COUNT: usize = 10;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeviceHealthSnapshot {
+    /// The last time the device has been working well
+    pub last_well: Option<time::SystemTime>,
+    /// The last time an issue has been reported
+    pub last_not_well: Option<time::SystemTime>,
+    /// The last reported issue
+    pub reason: Option<HealthIssue>,
+    counts: [u64; HealthIssue::COUNT],
+}
+
+impl DeviceHealthSnapshot {
+    /// Number of times the `issue` has been reported
+    pub fn count(&self, issue: HealthIssue) -> u64 {
+        self.counts[issue as usize]
+    }
+}
+
+/// Record of issues reported by the backend for the device
+#[derive(Debug, Default)]
+pub struct DeviceHealth {
+    inner: StdMutex<DeviceHealthSnapshot>,
+}
+
+impl DeviceHealth {
+    fn lock_inner(&self) -> StdMutexGuard<DeviceHealthSnapshot> {
+        self.inner.lock().expect("BUG: cannot lock device health")
+    }
+
+    pub fn account_well(&self, time: time::SystemTime) {
+        self.lock_inner().last_well = Some(time);
+    }
+
+    pub fn account_issue(&self, issue: HealthIssue, time: time::SystemTime) {
+        let mut inner = self.lock_inner();
+        inner.last_not_well = Some(time);
+        inner.reason = Some(issue);
+        inner.counts[issue as usize] += 1;
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<DeviceHealthSnapshot> {
+        Snapshot::new(self.lock_inner().clone())
+    }
+}
+
+/// What caused a change of client difficulty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyTrigger {
+    /// Difficulty set when the mining session has been established
+    Initial,
+    /// Difficulty adjusted by the pool on its own (variable difficulty)
+    Vardiff,
+    /// Difficulty set by the pool in response to difficulty suggested by the client
+    SuggestResponse,
+}
+
+impl fmt::Display for DifficultyTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DifficultyTrigger::Initial => "initial",
+            DifficultyTrigger::Vardiff => "vardiff",
+            DifficultyTrigger::SuggestResponse => "suggest-response",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DifficultyChange {
+    pub time: time::Instant,
+    pub system_time: time::SystemTime,
+    /// Previous difficulty or `None` for the very first one
+    pub old_difficulty: Option<usize>,
+    pub new_difficulty: usize,
+    pub trigger: DifficultyTrigger,
+}
+
+/// Accepted shares measured within a time window
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptedRate {
+    /// Number of accepted shares per minute
+    pub shares_per_minute: f64,
+    /// Hashrate computed from accepted shares (in kH/s)
+    pub kilo_hashes: f64,
+}
+
+impl AcceptedRate {
+    fn measure(accepted: &MeterSnapshot, from: time::Instant, to: time::Instant) -> Option<Self> {
+        let minutes = to.checked_duration_since(from)?.as_secs_f64() / 60.0;
+        Some(Self {
+            shares_per_minute: accepted.solutions_between(from, to)? / minutes,
+            kilo_hashes: accepted.to_kilo_hashes_between(from, to)?.into_f64(),
+        })
+    }
+}
+
+/// Difficulty change annotated with its effect on accepted shares
+#[derive(Debug, Clone)]
+pub struct AnnotatedDifficultyChange {
+    pub change: DifficultyChange,
+    /// Accepted shares in the window preceding the change. It is `None` when the window is no
+    /// longer covered by the meter history.
+    pub before: Option<AcceptedRate>,
+    /// Accepted shares in the window following the change. It is `None` until the whole window
+    /// elapses.
+    pub after: Option<AcceptedRate>,
+}
+
+/// Bounded audit trail of client difficulty changes
+#[derive(Debug)]
+pub struct DifficultyHistory {
+    capacity: usize,
+    inner: StdMutex<VecDeque<DifficultyChange>>,
+}
+
+impl DifficultyHistory {
+    pub const DEFAULT_CAPACITY: usize = 100;
+    /// Size of windows before and after the change used for annotation
+    pub const ANNOTATION_WINDOW: time::Duration = time::Duration::from_secs(5 * 60);
+
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            inner: StdMutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Record new client target. Targets with unchanged difficulty are ignored unless they
+    /// start a new mining session.
+    pub fn record(
+        &self,
+        target: &ii_bitcoin::Target,
+        trigger: DifficultyTrigger,
+        time: time::Instant,
+    ) {
+        clock::check_instant(time);
+        let new_difficulty = target.get_difficulty();
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("BUG: cannot lock difficulty history");
+
+        let old_difficulty = inner.back().map(|change| change.new_difficulty);
+        if old_difficulty == Some(new_difficulty) && trigger != DifficultyTrigger::Initial {
+            return;
+        }
+        if inner.len() == self.capacity {
+            inner.pop_front();
+        }
+        inner.push_back(DifficultyChange {
+            time,
+            system_time: clock::system_time(),
+            old_difficulty,
+            new_difficulty,
+            trigger,
+        });
+    }
+
+    pub fn take_snapshot(&self) -> Snapshot<Vec<DifficultyChange>> {
+        let inner = self
+            .inner
+            .lock()
+            .expect("BUG: cannot lock difficulty history");
+        Snapshot::new(inner.iter().cloned().collect())
+    }
+
+    /// Annotate difficulty changes with accepted shares before and after each change. The rates
+    /// are computed lazily from `accepted` meter history at the time of reading.
+    pub fn annotate(
+        changes: &Vec<DifficultyChange>,
+        accepted: &MeterSnapshot,
+        now: time::Instant,
+    ) -> Vec<AnnotatedDifficultyChange> {
+        changes
+            .iter()
+            .map(|change| {
+                let before = change
+                    .time
+                    .checked_sub(Self::ANNOTATION_WINDOW)
+                    .filter(|from| accepted.history_covers(*from, now))
+                    .and_then(|from| AcceptedRate::measure(accepted, from, change.time));
+                let to = change.time + Self::ANNOTATION_WINDOW;
+                let after = if to <= now && accepted.history_covers(change.time, now) {
+                    AcceptedRate::measure(accepted, change.time, to)
+                } else {
+                    None
+                };
+                AnnotatedDifficultyChange {
+                    change: change.clone(),
+                    before,
+                    after,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for DifficultyHistory {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// Activity accounted to the job the work has been generated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobActivity {
+    /// Assignment generated from the job
+    Assignment,
+    /// Solution which meets the job target
+    Solution,
+    /// Share submitted to remote server
+    Submitted,
+    /// Share accepted by remote server
+    Accepted,
+    /// Share rejected by remote server
+    Rejected,
+}
+
+/// Summary of work and shares of a single job which has been superseded by another one
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobRecord {
+    /// Identifier of the job assigned by the remote server
+    pub job_id: Option<u32>,
+    pub previous_hash: ii_bitcoin::DHash,
+    /// Block timestamp of the job as seconds since 1970-01-01T00:00 UTC
+    pub time: u32,
+    /// Ntime roll limit advertised by the pool for the job (zero when there is no limit)
+    pub ntime_roll_limit: u32,
+    /// The time the job has been received
+    pub system_time: time::SystemTime,
+    /// How long the job has been mined before it has been superseded
+    pub duration: time::Duration,
+    pub assignments: u64,
+    pub solutions: u64,
+    pub submitted: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+impl JobRecord {
+    fn account(&mut self, activity: JobActivity) {
+        let counter = match activity {
+            JobActivity::Assignment => &mut self.assignments,
+            JobActivity::Solution => &mut self.solutions,
+            JobActivity::Submitted => &mut self.submitted,
+            JobActivity::Accepted => &mut self.accepted,
+            JobActivity::Rejected => &mut self.rejected,
+        };
+        *counter += 1;
+    }
+}
+
+/// Circumstances under which a job has been superseded by the following one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Supersession {
+    pub time: time::Instant,
+    /// The following job builds on another block so solutions of the superseded job are useless
+    pub clean: bool,
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    /// Weak reference keeps the job address unique for as long as the entry exists
+    job: Weak<dyn job::Bitcoin>,
+    start: time::Instant,
+    target: ii_bitcoin::Target,
+    record: JobRecord,
+}
+
+impl JobEntry {
+    fn new(job: &Arc<dyn job::Bitcoin>, start: time::Instant) -> Self {
+        Self {
+            job: Arc::downgrade(job),
+            start,
+            target: job.target(),
+            record: JobRecord {
+                job_id: job.id(),
+                previous_hash: *job.previous_hash(),
+                time: job.time(),
+                ntime_roll_limit: job.ntime_roll_limit(),
+                system_time: clock::system_time(),
+                duration: Default::default(),
+                assignments: 0,
+                solutions: 0,
+                submitted: 0,
+                accepted: 0,
+                rejected: 0,
+            },
+        }
+    }
+
+    fn is_job(&self, job: &Arc<dyn job::Bitcoin>) -> bool {
+        Weak::ptr_eq(&self.job, &Arc::downgrade(job))
+    }
+}
+
+#[derive(Debug)]
+struct JobHistoryInner {
+    current: Option<JobEntry>,
+    finished: VecDeque<JobEntry>,
+}
+
+/// Bounded history of jobs received from remote server with work generated from each of them
+/// and shares found in it. It serves as evidence of the work done for a particular job when it
+/// is disputed by the pool.
+#[derive(Debug)]
+pub struct JobHistory {
+    capacity: usize,
+    inner: StdMutex<JobHistoryInner>,
+}
+
+impl JobHistory {
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            inner: StdMutex::new(JobHistoryInner {
+                current: None,
+                finished: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+
+    fn lock_inner(&self) -> StdMutexGuard<JobHistoryInner> {
+        self.inner.lock().expect("BUG: cannot lock job history")
+    }
+
+    /// Start tracking of a new `job` and finalize the record of the job it supersedes
+    pub fn start(&self, job: &Arc<dyn job::Bitcoin>, time: time::Instant) {
+        clock::check_instant(time);
+        let mut inner = self.lock_inner();
+
+        if inner
+            .current
+            .as_ref()
+            .map_or(false, |entry| entry.is_job(job))
+        {
+            return;
+        }
+        if let Some(mut entry) = inner.current.replace(JobEntry::new(job, time)) {
+            entry.record.duration = time.checked_duration_since(entry.start).unwrap_or_default();
+            if inner.finished.len() == self.capacity {
+                inner.finished.pop_front();
+            }
+            inner.finished.push_back(entry);
+        }
+    }
+
+    /// Account `activity` to the record of `job`. Shares resolved after the job has been
+    /// superseded are still accounted to its finalized record while it is kept in the history.
+    pub fn account(&self, job: &Arc<dyn job::Bitcoin>, activity: JobActivity) {
+        let mut inner = self.lock_inner();
+        let JobHistoryInner { current, finished } = &mut *inner;
+
+        if let Some(entry) = current
+            .iter_mut()
+            .chain(finished.iter_mut().rev())
+            .find(|entry| entry.is_job(job))
+        {
+            entry.record.account(activity);
+        }
+    }
+
+    /// Returns how the `job` has been superseded or `None` when it is the current job or it is no
+    /// longer in the history
+    pub fn supersession(&self, job: &Arc<dyn job::Bitcoin>) -> Option<Supersession> {
+        let inner = self.lock_inner();
+
+        let idx = inner.finished.iter().position(|entry| entry.is_job(job))?;
+        let entry = &inner.finished[idx];
+        let next_entry = inner.finished.get(idx + 1).or(inner.current.as_ref())?;
+        Some(Supersession {
+            time: entry.start + entry.record.duration,
+            clean: entry.record.previous_hash != next_entry.record.previous_hash,
+        })
+    }
+
+    /// Returns target of the current job
+    pub fn current_target(&self) -> Option<ii_bitcoin::Target> {
+        self.lock_inner().current.as_ref().map(|entry| entry.target)
+    }
+
+    /// Returns the time when the current job has been received
+    pub fn current_start(&self) -> Option<time::Instant> {
+        self.lock_inner().current.as_ref().map(|entry| entry.start)
+    }
+
+    /// Returns records of superseded jobs from the oldest one
+    pub fn take_snapshot(&self) -> Snapshot<Vec<JobRecord>> {
+        let inner = self.lock_inner();
+        Snapshot::new(
+            inner
+                .finished
+                .iter()
+                .map(|entry| entry.record.clone())
+                .collect(),
+        )
+    }
+}
+
+impl Default for JobHistory {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
 pub trait UnixTime {
     fn get_unix_time(&self) -> Result<u32, String>;
 }
@@ -419,6 +1610,12 @@ pub trait Mining: Send + Sync {
     fn valid_backend_diff(&self) -> &Meter;
     /// Statistics for all invalid work on backend difficulty (backend/HW error)
     fn error_backend_diff(&self) -> &Meter;
+    /// Statistics for all shares on job/pool difficulty rejected by remote server
+    fn rejected_job_diff(&self) -> &Meter;
+    /// Number of solutions meeting network difficulty (found blocks)
+    fn found_blocks(&self) -> &CounterU64;
+    /// Number of solutions of invalidated jobs dropped without submitting them to the pool
+    fn dropped_stale(&self) -> &CounterU64;
 }
 
 pub trait Client: Mining {
@@ -434,6 +1631,16 @@ pub trait Client: Mining {
     fn rejected(&self) -> &Meter;
     /// Valid shares rejected by remote server or discarded due to some error
     fn stale(&self) -> &Meter;
+    /// Audit trail of difficulty changes requested by remote server
+    fn difficulty_history(&self) -> &DifficultyHistory;
+    /// Work and shares of jobs superseded by newer ones
+    fn job_history(&self) -> &JobHistory;
+    /// Solutions flagged or dropped by the share policy for each reason
+    fn share_policy(&self) -> &share_policy::Counters;
+    /// Submissions which have been retransmitted or which have never got any response
+    fn submissions(&self) -> &submission::Counters;
+    /// Totals of previous runs of the miner
+    fn lifetime(&self) -> &Lifetime;
 }
 
 pub trait WorkSolver: Mining {
@@ -441,6 +1648,30 @@ pub trait WorkSolver: Mining {
     fn last_work_time(&self) -> &Timestamp;
     /// Number of work generated from jobs by rolling or with extra nonce
     fn generated_work(&self) -> &CounterU64;
+    /// Number of generated work which has been actually submitted to the hardware
+    fn dispatched_work(&self) -> &CounterU64;
+    /// Number of solutions dropped because they refer to non-existent part of the work
+    fn malformed_solutions(&self) -> &CounterU64;
+    /// Number of solutions dropped because their hash does not meet the backend target
+    fn hardware_errors(&self) -> &CounterU64;
+    /// Number of assignments skipped because they were too old to be submitted to the hardware
+    fn expired_assignments(&self) -> &CounterU64;
+    /// Last work delivered to the device and the last solution returned by it
+    fn work_progress(&self) -> &WorkProgress;
+    /// Time it takes to deliver the first assignment from a new job
+    fn job_latency(&self) -> &JobLatency;
+    /// Time it takes the hardware to return solutions of dispatched work
+    fn solution_latency(&self) -> &SolutionLatency;
+    /// Time spent waiting for new work and number of exhausted engines
+    fn work_starvation(&self) -> &WorkStarvation;
+    /// Solutions withheld from pools while the work solver is quarantined
+    fn quarantine(&self) -> &Quarantine;
+    /// Pause of mining requested by the user together with the time spent in it
+    fn pause(&self) -> &Pause;
+    /// Removal of the work solver from the backend hierarchy
+    fn removal(&self) -> &Removal;
+    /// Issues of the device reported by the backend
+    fn device_health(&self) -> &DeviceHealth;
 }
 
 #[derive(Debug, MiningStats)]
@@ -459,6 +1690,12 @@ pub struct BasicMining {
     pub valid_backend_diff: Meter,
     #[member_error_backend_diff]
     pub error_backend_diff: Meter,
+    #[member_rejected_job_diff]
+    pub rejected_job_diff: Meter,
+    #[member_found_blocks]
+    pub found_blocks: CounterU64,
+    #[member_dropped_stale]
+    pub dropped_stale: CounterU64,
 }
 
 impl BasicMining {
@@ -471,13 +1708,16 @@ impl BasicMining {
             valid_job_diff: Meter::new(&intervals),
             valid_backend_diff: Meter::new(&intervals),
             error_backend_diff: Meter::new(&intervals),
+            rejected_job_diff: Meter::new(&intervals),
+            found_blocks: Default::default(),
+            dropped_stale: Default::default(),
         }
     }
 }
 
 impl Default for BasicMining {
     fn default() -> Self {
-        Self::new(time::Instant::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
+        Self::new(clock::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
     }
 }
 
@@ -501,6 +1741,16 @@ pub struct BasicClient {
     pub rejected: stats::Meter,
     #[member_stale]
     pub stale: stats::Meter,
+    #[member_difficulty_history]
+    pub difficulty_history: DifficultyHistory,
+    #[member_job_history]
+    pub job_history: JobHistory,
+    #[member_share_policy]
+    pub share_policy: share_policy::Counters,
+    #[member_submissions]
+    pub submissions: submission::Counters,
+    #[member_lifetime]
+    pub lifetime: Lifetime,
     #[member_valid_network_diff]
     pub valid_network_diff: Meter,
     #[member_valid_job_diff]
@@ -509,6 +1759,12 @@ pub struct BasicClient {
     pub valid_backend_diff: Meter,
     #[member_error_backend_diff]
     pub error_backend_diff: Meter,
+    #[member_rejected_job_diff]
+    pub rejected_job_diff: Meter,
+    #[member_found_blocks]
+    pub found_blocks: CounterU64,
+    #[member_dropped_stale]
+    pub dropped_stale: CounterU64,
 }
 
 impl BasicClient {
@@ -520,20 +1776,28 @@ impl BasicClient {
             generated_work: Default::default(),
             last_share: Default::default(),
             best_share: Default::default(),
-            accepted: Meter::new(&intervals),
+            accepted: Meter::with_history(&intervals, METER_HISTORY_BUCKET, METER_HISTORY_LENGTH),
             rejected: Meter::new(&intervals),
             stale: Default::default(),
+            difficulty_history: Default::default(),
+            job_history: Default::default(),
+            share_policy: Default::default(),
+            submissions: Default::default(),
+            lifetime: Default::default(),
             valid_network_diff: Meter::new(&intervals),
             valid_job_diff: Meter::new(&intervals),
             valid_backend_diff: Meter::new(&intervals),
             error_backend_diff: Meter::new(&intervals),
+            rejected_job_diff: Meter::new(&intervals),
+            found_blocks: Default::default(),
+            dropped_stale: Default::default(),
         }
     }
 }
 
 impl Default for BasicClient {
     fn default() -> Self {
-        Self::new(time::Instant::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
+        Self::new(clock::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
     }
 }
 
@@ -545,6 +1809,30 @@ pub struct BasicWorkSolver {
     pub last_work_time: Timestamp,
     #[member_generated_work]
     pub generated_work: CounterU64,
+    #[member_dispatched_work]
+    pub dispatched_work: CounterU64,
+    #[member_malformed_solutions]
+    pub malformed_solutions: CounterU64,
+    #[member_hardware_errors]
+    pub hardware_errors: CounterU64,
+    #[member_expired_assignments]
+    pub expired_assignments: CounterU64,
+    #[member_work_progress]
+    pub work_progress: WorkProgress,
+    #[member_job_latency]
+    pub job_latency: JobLatency,
+    #[member_solution_latency]
+    pub solution_latency: SolutionLatency,
+    #[member_work_starvation]
+    pub work_starvation: WorkStarvation,
+    #[member_quarantine]
+    pub quarantine: Quarantine,
+    #[member_pause]
+    pub pause: Pause,
+    #[member_removal]
+    pub removal: Removal,
+    #[member_device_health]
+    pub device_health: DeviceHealth,
     #[member_last_share]
     pub last_share: LastShare,
     #[member_best_share]
@@ -557,6 +1845,12 @@ pub struct BasicWorkSolver {
     pub valid_backend_diff: Meter,
     #[member_error_backend_diff]
     pub error_backend_diff: Meter,
+    #[member_rejected_job_diff]
+    pub rejected_job_diff: Meter,
+    #[member_found_blocks]
+    pub found_blocks: CounterU64,
+    #[member_dropped_stale]
+    pub dropped_stale: CounterU64,
 }
 
 impl BasicWorkSolver {
@@ -567,17 +1861,32 @@ impl BasicWorkSolver {
             best_share: Default::default(),
             last_work_time: Default::default(),
             generated_work: Default::default(),
+            dispatched_work: Default::default(),
+            malformed_solutions: Default::default(),
+            hardware_errors: Default::default(),
+            expired_assignments: Default::default(),
+            work_progress: Default::default(),
+            job_latency: Default::default(),
+            solution_latency: Default::default(),
+            work_starvation: Default::default(),
+            quarantine: Default::default(),
+            pause: Default::default(),
+            removal: Default::default(),
+            device_health: Default::default(),
             valid_network_diff: Meter::new(&intervals),
             valid_job_diff: Meter::new(&intervals),
             valid_backend_diff: Meter::new(&intervals),
             error_backend_diff: Meter::new(&intervals),
+            rejected_job_diff: Meter::new(&intervals),
+            found_blocks: Default::default(),
+            dropped_stale: Default::default(),
         }
     }
 }
 
 impl Default for BasicWorkSolver {
     fn default() -> Self {
-        Self::new(time::Instant::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
+        Self::new(clock::now(), DEFAULT_TIME_MEAN_INTERVALS.as_ref())
     }
 }
 
@@ -605,6 +1914,7 @@ account_impl!(account_valid_network_diff, valid_network_diff);
 account_impl!(account_valid_job_diff, valid_job_diff);
 account_impl!(account_valid_backend_diff, valid_backend_diff);
 account_impl!(account_error_backend_diff, error_backend_diff);
+account_impl!(account_rejected_job_diff, rejected_job_diff);
 
 /// Describes which difficulty target a particular solution has met.
 /// It also determines in which statistics a particular solution should be accounted.
@@ -636,20 +1946,37 @@ pub async fn account_valid_solution(
                 DiffTargetType::Network,
                 "BUG: unexpected difficulty target type"
             );
+            for node in path {
+                node.mining_stats().found_blocks().inc();
+            }
         }
         // use only job difficulty for accounting the last share even if a hash of the solution
-        // meets higher difficulties
+        // meets higher difficulties (the best share is given by the hash itself)
         for node in path {
             let mining_stats = node.mining_stats();
             mining_stats
                 .last_share()
-                .account_solution(target, time::SystemTime::now())
+                .account_solution(target, clock::system_time())
                 .await;
-            mining_stats.best_share().account_solution(target);
+            mining_stats.best_share().account_solution(solution.hash());
         }
     }
 }
 
+/// Exclude the period `<since, now)` from all hashrate time means of the node (e.g. pause of
+/// the work solver requested by the user)
+pub(crate) async fn exclude_period(
+    mining_stats: &dyn Mining,
+    since: time::Instant,
+    now: time::Instant,
+) {
+    mining_stats.valid_network_diff().exclude(since, now).await;
+    mining_stats.valid_job_diff().exclude(since, now).await;
+    mining_stats.valid_backend_diff().exclude(since, now).await;
+    mining_stats.error_backend_diff().exclude(since, now).await;
+    mining_stats.rejected_job_diff().exclude(since, now).await;
+}
+
 pub async fn mining_task(node: node::DynInfo, interval: time::Duration) {
     loop {
         delay_for(time::Duration::from_secs(1)).await;
@@ -664,8 +1991,257 @@ pub async fn mining_task(node: node::DynInfo, interval: time::Duration) {
             "Hash rate ({} s avg.) for '{}' @ backend diff.: {}/s, job diff.: {}/s",
             interval.as_secs(),
             node,
-            valid_backend_diff.to_pretty_hashes(interval, time::Instant::now()),
-            valid_job_diff.to_pretty_hashes(interval, time::Instant::now()),
+            valid_backend_diff.to_pretty_hashes(interval, clock::now()),
+            valid_job_diff.to_pretty_hashes(interval, clock::now()),
         );
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use std::time::{Duration, Instant};
+
+    fn target(difficulty: usize) -> ii_bitcoin::Target {
+        ii_bitcoin::Target::from_pool_difficulty(difficulty)
+    }
+
+    fn assert_rate(rate: &AcceptedRate, shares_per_minute: f64, kilo_hashes: f64) {
+        assert!((rate.shares_per_minute - shares_per_minute).abs() < 1e-9);
+        assert!((rate.kilo_hashes - kilo_hashes).abs() / kilo_hashes < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_difficulty_history_annotation() {
+        let start = Instant::now();
+        let accepted = Meter::with_history(
+            DEFAULT_TIME_MEAN_INTERVALS.as_ref(),
+            METER_HISTORY_BUCKET,
+            METER_HISTORY_LENGTH,
+        );
+        let history = DifficultyHistory::default();
+
+        history.record(&target(1024), DifficultyTrigger::Initial, start);
+        // 10 shares per minute for 10 minutes
+        for i in 0..100 {
+            accepted
+                .account_solution(&target(1024), start + Duration::from_secs(i * 6))
+                .await;
+        }
+        let change_time = start + Duration::from_secs(600);
+        history.record(&target(2048), DifficultyTrigger::Vardiff, change_time);
+        // the same hashrate with doubled difficulty results in 5 shares per minute
+        for i in 0..50 {
+            accepted
+                .account_solution(&target(2048), change_time + Duration::from_secs(i * 12))
+                .await;
+        }
+
+        let changes = history.take_snapshot();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].old_difficulty, Some(1024));
+        assert_eq!(changes[1].new_difficulty, 2048);
+        assert_eq!(changes[1].trigger, DifficultyTrigger::Vardiff);
+
+        let kilo_hashes = ii_bitcoin::Shares::new(&target(1024))
+            .into_kilo_hashes()
+            .into_f64()
+            * 10.0
+            / 60.0;
+
+        // the annotation is computed when the history is read
+        let now = start + Duration::from_secs(1200);
+        let annotated =
+            DifficultyHistory::annotate(&changes, &*accepted.take_snapshot().await, now);
+        // there is no history before the initial difficulty
+        assert!(annotated[0].before.is_none());
+        assert_rate(annotated[0].after.as_ref().unwrap(), 10.0, kilo_hashes);
+        assert_rate(annotated[1].before.as_ref().unwrap(), 10.0, kilo_hashes);
+        assert_rate(annotated[1].after.as_ref().unwrap(), 5.0, kilo_hashes);
+
+        // the window after the change has not elapsed yet
+        let now = change_time + Duration::from_secs(60);
+        let annotated =
+            DifficultyHistory::annotate(&changes, &*accepted.take_snapshot().await, now);
+        assert!(annotated[1].before.is_some());
+        assert!(annotated[1].after.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_meter_decay() {
+        tokio::time::pause();
+        let meter = Meter::default();
+        let kilo_hashes = ii_bitcoin::Shares::new(&target(1))
+            .into_kilo_hashes()
+            .into_f64();
+
+        // one share per second for 10 minutes
+        let busy = 600.0;
+        for _ in 0..busy as usize {
+            tokio::time::advance(Duration::from_secs(1)).await;
+            meter.account_solution(&target(1), clock::now()).await;
+        }
+        let intervals = [
+            *TIME_MEAN_INTERVAL_1M,
+            *TIME_MEAN_INTERVAL_5M,
+            *TIME_MEAN_INTERVAL_15M,
+            *TIME_MEAN_INTERVAL_24H,
+        ];
+        // the meter is read without inserting samples after the given idle time
+        let assert_decay = |snapshot: &MeterSnapshot, idle: f64| {
+            for &interval in intervals.iter() {
+                let tau = interval.as_secs_f64();
+                let weight = |age: f64| 1.0 - (-age / tau).exp();
+                let expected =
+                    kilo_hashes / (tau * weight(1.0)) * (-idle / tau).exp() * weight(busy)
+                        / weight(busy + idle);
+                let measured = snapshot.to_kilo_hashes(interval, clock::now()).into_f64();
+                assert!(
+                    (measured - expected).abs() <= expected * 1e-9,
+                    "unexpected hashrate {} for interval {:?}",
+                    measured,
+                    interval
+                );
+            }
+        };
+
+        let snapshot = meter.take_snapshot().await;
+        assert_decay(&*snapshot, 0.0);
+        // all averages are close to the real hashrate after the warm up
+        for &interval in intervals.iter() {
+            let measured = snapshot.to_kilo_hashes(interval, clock::now()).into_f64();
+            assert!((measured - kilo_hashes).abs() / kilo_hashes < 0.01);
+        }
+
+        // idle meter decays when it is read
+        tokio::time::advance(Duration::from_secs(5 * 60)).await;
+        assert_decay(&*meter.take_snapshot().await, 5.0 * 60.0);
+        tokio::time::advance(Duration::from_secs(24 * 60 * 60)).await;
+        assert_decay(
+            &*meter.take_snapshot().await,
+            5.0 * 60.0 + 24.0 * 60.0 * 60.0,
+        );
+    }
+
+    #[test]
+    fn test_difficulty_history_bound() {
+        let start = Instant::now();
+        let history = DifficultyHistory::new(100);
+
+        history.record(&target(1), DifficultyTrigger::Initial, start);
+        for difficulty in 2..=150 {
+            history.record(&target(difficulty), DifficultyTrigger::Vardiff, start);
+            // unchanged difficulty is not a change
+            history.record(&target(difficulty), DifficultyTrigger::Vardiff, start);
+        }
+        // reconnection is always recorded
+        history.record(&target(150), DifficultyTrigger::Initial, start);
+
+        let changes = history.take_snapshot();
+        assert_eq!(changes.len(), 100);
+        assert_eq!(changes[0].old_difficulty, Some(51));
+        assert_eq!(changes[0].new_difficulty, 52);
+        assert_eq!(changes[99].old_difficulty, Some(150));
+        assert_eq!(changes[99].trigger, DifficultyTrigger::Initial);
+    }
+
+    #[test]
+    fn test_best_share() {
+        let best_share = BestShare::default();
+        assert!(best_share.take_snapshot().is_none());
+
+        for block in test_utils::TEST_BLOCKS.iter() {
+            best_share.account_solution(&block.hash);
+        }
+        let best_difficulty = test_utils::TEST_BLOCKS
+            .iter()
+            .map(|block| ii_bitcoin::Target::from(block.hash).get_difficulty() as u64)
+            .max();
+        assert_eq!(
+            best_share.take_snapshot().map(|snapshot| *snapshot),
+            best_difficulty
+        );
+    }
+
+    #[test]
+    fn test_job_history_bound() {
+        let start = Instant::now();
+        let history = JobHistory::new(4);
+        let jobs: Vec<Arc<dyn job::Bitcoin>> = (0..6)
+            .map(|i| -> Arc<dyn job::Bitcoin> {
+                Arc::new(test_utils::TEST_BLOCKS[i % test_utils::TEST_BLOCKS.len()])
+            })
+            .collect();
+
+        for (i, job) in jobs.iter().enumerate() {
+            history.start(job, start + Duration::from_secs(i as u64 * 10));
+            // repeated job does not supersede itself
+            history.start(job, start + Duration::from_secs(i as u64 * 10 + 5));
+            for _ in 0..i {
+                history.account(job, JobActivity::Assignment);
+            }
+        }
+        // the share of a superseded job is resolved late
+        history.account(&jobs[4], JobActivity::Accepted);
+        // the job is no longer in the history
+        history.account(&jobs[0], JobActivity::Accepted);
+
+        let records = history.take_snapshot();
+        // the current job is not finalized yet
+        assert_eq!(records.len(), 4);
+        // the current job has started when it has been received for the first time
+        assert_eq!(
+            history.current_start(),
+            Some(start + Duration::from_secs(50))
+        );
+        let assignments: Vec<_> = records.iter().map(|record| record.assignments).collect();
+        assert_eq!(assignments, vec![1, 2, 3, 4]);
+        assert!(records
+            .iter()
+            .all(|record| record.duration == Duration::from_secs(10)));
+        assert_eq!(records[3].accepted, 1);
+        assert_eq!(records[0].accepted, 0);
+
+        // the job has been superseded when the following one has started
+        let supersession = history
+            .supersession(&jobs[4])
+            .expect("BUG: missing supersession");
+        assert_eq!(supersession.time, start + Duration::from_secs(50));
+        assert_eq!(
+            supersession.clean,
+            jobs[4].previous_hash() != jobs[5].previous_hash()
+        );
+        // neither the current job nor the evicted one has been superseded
+        assert_eq!(history.supersession(&jobs[5]), None);
+        assert_eq!(history.supersession(&jobs[0]), None);
+        assert_eq!(history.current_target(), Some(jobs[5].target()));
+    }
+
+    #[test]
+    fn test_work_starvation_threshold() {
+        let start = Instant::now();
+        let starvation = WorkStarvation::default();
+        let wait = Duration::from_secs(4);
+
+        assert_eq!(starvation.account(wait, start), None);
+        assert_eq!(starvation.account(wait, start + wait), None);
+        // the threshold is exceeded and reported only once in the interval
+        assert_eq!(starvation.account(wait, start + 2 * wait), Some(3 * wait));
+        assert_eq!(starvation.account(wait, start + 3 * wait), None);
+        // a new interval starts with clean state
+        let next = start + WorkStarvation::INTERVAL;
+        assert_eq!(starvation.account(wait, next), None);
+        assert_eq!(
+            starvation.account(WorkStarvation::THRESHOLD, next + wait),
+            Some(WorkStarvation::THRESHOLD + wait)
+        );
+
+        starvation.account_exhausted();
+        let snapshot = starvation.take_snapshot();
+        assert_eq!(snapshot.duration, 5 * wait + WorkStarvation::THRESHOLD);
+        assert_eq!(snapshot.waits, 6);
+        assert_eq!(snapshot.exhausted_engines, 1);
+    }
+}
@@ -0,0 +1,530 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Persistence of run-time statistics across restarts of the miner.
+//!
+//! Statistics of the frontend and of each client are periodically saved into a small versioned
+//! JSON snapshot. The snapshot is saved to a staging copy on tmpfs and the file itself is synced
+//! at most once per snapshot interval and once more when the miner is shut down gracefully (see
+//! `storage::StagedFile`) so a power loss loses at most one interval. The totals from the
+//! snapshot found on startup are restored into `stats::Lifetime` of the frontend and of clients
+//! and the counters of the new process are added to them. Clients are identified by their URL
+//! and user because client handles do not survive restarts. Totals of clients which are not
+//! configured anymore are kept in the snapshot so that they are not lost when the client is
+//! only temporarily removed.
+//!
+//! Missing, corrupted or incompatible snapshots are ignored and the lifetime statistics start
+//! from zero.
+
+use ii_logging::macros::*;
+
+use crate::client::{self, digest::PoolId};
+use crate::clock;
+use crate::node::Stats as _;
+use crate::stats;
+use crate::storage;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io;
+use std::ops;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Version of the snapshot format. Snapshots with any other version are ignored.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Default period of saving the snapshot
+pub const DEFAULT_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+/// Statistics accumulated over runs of the miner
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub accepted: u64,
+    pub rejected: u64,
+    /// Sum of difficulties of accepted shares
+    pub accepted_difficulty: f64,
+    /// Sum of difficulties of rejected shares
+    pub rejected_difficulty: f64,
+    /// The best share ever found (it is not summed)
+    pub best_share: u64,
+    /// Time spent mining in seconds
+    pub elapsed: u64,
+}
+
+impl ops::Add for Totals {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            accepted: self.accepted + other.accepted,
+            rejected: self.rejected + other.rejected,
+            accepted_difficulty: self.accepted_difficulty + other.accepted_difficulty,
+            rejected_difficulty: self.rejected_difficulty + other.rejected_difficulty,
+            best_share: self.best_share.max(other.best_share),
+            elapsed: self.elapsed + other.elapsed,
+        }
+    }
+}
+
+impl ops::AddAssign for Totals {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClientRecord {
+    #[serde(flatten)]
+    pub id: PoolId,
+    #[serde(flatten)]
+    pub totals: Totals,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Unix time when the snapshot has been taken
+    pub time: u64,
+    pub frontend: Totals,
+    #[serde(default)]
+    pub clients: Vec<ClientRecord>,
+}
+
+/// Statistics of the client counted by this process only
+async fn take_client_totals(client_stats: &dyn stats::Client, now: time::Instant) -> Totals {
+    let accepted = client_stats.accepted().take_snapshot().await;
+    let rejected = client_stats.rejected().take_snapshot().await;
+
+    Totals {
+        accepted: accepted.solutions,
+        rejected: rejected.solutions,
+        accepted_difficulty: accepted.shares.as_f64(),
+        rejected_difficulty: rejected.shares.as_f64(),
+        best_share: client_stats
+            .best_share()
+            .take_snapshot()
+            .map_or(0, |best_share| *best_share),
+        elapsed: now.duration_since(*client_stats.start_time()).as_secs(),
+    }
+}
+
+/// Lifetime statistics of the frontend computed from totals of previous runs and the current
+/// statistics of all clients
+pub async fn take_frontend_totals(
+    frontend: &crate::Frontend,
+    client_manager: &client::Manager,
+    now: time::Instant,
+) -> Totals {
+    let mining_stats = frontend.mining_stats();
+    let mut totals = Totals {
+        best_share: mining_stats
+            .best_share()
+            .take_snapshot()
+            .map_or(0, |best_share| *best_share),
+        elapsed: now.duration_since(*mining_stats.start_time()).as_secs(),
+        ..Default::default()
+    };
+    for group in client_manager.get_groups().await {
+        for client in group.get_clients().await {
+            let client_totals = take_client_totals(client.stats(), now).await;
+            // only shares are summed as the time and best share are taken from the frontend
+            totals += Totals {
+                best_share: 0,
+                elapsed: 0,
+                ..client_totals
+            };
+        }
+    }
+    frontend.get_lifetime().take_snapshot() + totals
+}
+
+/// Snapshot file together with totals of clients loaded from it
+#[derive(Debug)]
+pub struct Store {
+    file: storage::StagedFile,
+    frontend: Totals,
+    clients: StdMutex<BTreeMap<PoolId, Totals>>,
+}
+
+impl Store {
+    /// Load the snapshot from `path`. Missing file is not an error and a corrupted one or
+    /// a snapshot of unknown version is skipped with a warning. The file is synced at most once
+    /// per `sync_interval` (usually the snapshot interval).
+    pub fn open<P: Into<PathBuf>>(path: P, sync_interval: time::Duration) -> Self {
+        let file = storage::StagedFile::new(path, sync_interval);
+        let path = file.path();
+        let snapshot = match file.read_to_string() {
+            Ok(content) => match serde_json::from_str::<Snapshot>(&content) {
+                Ok(snapshot) if snapshot.version == FORMAT_VERSION => Some(snapshot),
+                Ok(snapshot) => {
+                    warn!(
+                        "Statistics: ignoring snapshot '{}' with unsupported version {}",
+                        path.display(),
+                        snapshot.version
+                    );
+                    None
+                }
+                Err(e) => {
+                    warn!(
+                        "Statistics: ignoring corrupted snapshot '{}': {}",
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!("Statistics: cannot read '{}': {}", path.display(), e);
+                None
+            }
+        };
+        let (frontend, clients) = match snapshot {
+            Some(snapshot) => (
+                snapshot.frontend,
+                snapshot
+                    .clients
+                    .into_iter()
+                    .map(|record| (record.id, record.totals))
+                    .collect(),
+            ),
+            None => Default::default(),
+        };
+
+        Self {
+            file,
+            frontend,
+            clients: StdMutex::new(clients),
+        }
+    }
+
+    fn lock_clients(&self) -> StdMutexGuard<'_, BTreeMap<PoolId, Totals>> {
+        self.clients
+            .lock()
+            .expect("BUG: cannot lock persisted clients")
+    }
+
+    /// Restore totals of previous runs into the frontend and into all clients which have not
+    /// been restored yet. It is safe to call it repeatedly to catch clients added later.
+    pub async fn restore(&self, frontend: &crate::Frontend, client_manager: &client::Manager) {
+        if frontend.get_lifetime().restore(self.frontend) && self.frontend.elapsed > 0 {
+            info!(
+                "Statistics: restored lifetime totals ({} accepted, {} rejected shares)",
+                self.frontend.accepted, self.frontend.rejected
+            );
+        }
+        for group in client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                let lifetime = client.stats().lifetime();
+                if lifetime.is_restored() {
+                    continue;
+                }
+                let descriptor = client.descriptor().await;
+                let id = PoolId {
+                    url: descriptor.get_full_url(),
+                    user: descriptor.user,
+                };
+                lifetime.restore(self.lock_clients().get(&id).cloned().unwrap_or_default());
+            }
+        }
+    }
+
+    /// Take the snapshot of lifetime statistics of the frontend and all clients
+    pub async fn take_snapshot(
+        &self,
+        frontend: &crate::Frontend,
+        client_manager: &client::Manager,
+    ) -> Snapshot {
+        // totals of previous runs have to be restored before the client is saved
+        self.restore(frontend, client_manager).await;
+
+        let now = clock::now();
+        let mut current = BTreeMap::new();
+        for group in client_manager.get_groups().await {
+            for client in group.get_clients().await {
+                let descriptor = client.descriptor().await;
+                let id = PoolId {
+                    url: descriptor.get_full_url(),
+                    user: descriptor.user,
+                };
+                let client_stats = client.stats();
+                let totals = client_stats.lifetime().take_snapshot()
+                    + take_client_totals(client_stats, now).await;
+                // the same pool may be configured in several groups
+                *current.entry(id).or_default() += totals;
+            }
+        }
+        let frontend = take_frontend_totals(frontend, client_manager, now).await;
+
+        // the lock must not be held across await points
+        let mut clients = self.lock_clients();
+        clients.extend(current.into_iter());
+
+        Snapshot {
+            version: FORMAT_VERSION,
+            time: clock::system_time()
+                .duration_since(time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            frontend,
+            clients: clients
+                .iter()
+                .map(|(id, totals)| ClientRecord {
+                    id: id.clone(),
+                    totals: *totals,
+                })
+                .collect(),
+        }
+    }
+
+    /// Rewrite the whole file. The persistent file is synced only periodically or when `sync` is
+    /// set.
+    pub fn save(&self, snapshot: &Snapshot, sync: bool) -> io::Result<()> {
+        let mut content = serde_json::to_vec(snapshot)?;
+        content.push(b'\n');
+        self.file.write(&content, sync)
+    }
+
+    /// Take the snapshot and save it
+    pub async fn flush(
+        &self,
+        frontend: &crate::Frontend,
+        client_manager: &client::Manager,
+        sync: bool,
+    ) {
+        let snapshot = self.take_snapshot(frontend, client_manager).await;
+        if let Err(e) = self.save(&snapshot, sync) {
+            warn!(
+                "Statistics: cannot save snapshot to '{}': {}",
+                self.file.path().display(),
+                e
+            );
+        }
+    }
+}
+
+/// Restore statistics from the `store` and save them every `interval`
+pub async fn run(
+    store: Arc<Store>,
+    frontend: Arc<crate::Frontend>,
+    client_manager: client::Manager,
+    interval: time::Duration,
+) {
+    store.restore(&frontend, &client_manager).await;
+    loop {
+        delay_for(interval).await;
+        store.flush(&frontend, &client_manager, false).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bosminer_config::{ClientDescriptor, ClientUserInfo};
+
+    use std::fs;
+
+    const SYNC_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+    struct TestFile(PathBuf);
+
+    impl TestFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "bosminer-stats-{}-{}.json",
+                name,
+                std::process::id()
+            ));
+            let file = Self(path);
+            file.staged().remove();
+            file
+        }
+
+        fn staged(&self) -> storage::StagedFile {
+            storage::StagedFile::new(&self.0, storage::DEFAULT_SYNC_INTERVAL)
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            self.staged().remove();
+        }
+    }
+
+    async fn create_client(client_manager: &client::Manager, url: &str) -> Arc<client::Handle> {
+        let descriptor = ClientDescriptor::create(url, &ClientUserInfo::new("test", None), false)
+            .expect("BUG: cannot create client descriptor");
+        client_manager.add_client(descriptor, None).await
+    }
+
+    async fn accept_shares(client: &client::Handle, count: usize) {
+        for _ in 0..count {
+            client
+                .stats()
+                .accepted()
+                .account_solution(&Default::default(), clock::now())
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart() {
+        let file = TestFile::new("restart");
+
+        // the first run of the miner
+        let frontend = crate::Frontend::new();
+        let client_manager = client::Manager::new(1);
+        let client_a = create_client(&client_manager, "drain://pool-a").await;
+        let client_b = create_client(&client_manager, "drain://pool-b").await;
+        let store = Store::open(&file.0, SYNC_INTERVAL);
+        store.restore(&frontend, &client_manager).await;
+        accept_shares(&client_a, 3).await;
+        accept_shares(&client_b, 1).await;
+        store.flush(&frontend, &client_manager, false).await;
+
+        // the second run where only the first client is configured
+        let frontend = crate::Frontend::new();
+        let client_manager = client::Manager::new(1);
+        let client_a = create_client(&client_manager, "drain://pool-a").await;
+        let store = Store::open(&file.0, SYNC_INTERVAL);
+        store.restore(&frontend, &client_manager).await;
+        assert_eq!(frontend.get_lifetime().take_snapshot().accepted, 4);
+        assert_eq!(client_a.stats().lifetime().take_snapshot().accepted, 3);
+        accept_shares(&client_a, 2).await;
+
+        let snapshot = store.take_snapshot(&frontend, &client_manager).await;
+        assert_eq!(snapshot.version, FORMAT_VERSION);
+        assert_eq!(snapshot.frontend.accepted, 6);
+        assert_eq!(snapshot.frontend.accepted_difficulty, 6.0);
+        let accepted: Vec<_> = snapshot
+            .clients
+            .iter()
+            .map(|record| (record.id.url.as_str(), record.totals.accepted))
+            .collect();
+        // the removed client is kept
+        assert_eq!(
+            accepted,
+            vec![("drain://test@pool-a", 5), ("drain://test@pool-b", 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_late_client() {
+        let file = TestFile::new("late");
+        let frontend = crate::Frontend::new();
+        let client_manager = client::Manager::new(1);
+        let client = create_client(&client_manager, "drain://pool-a").await;
+        let store = Store::open(&file.0, SYNC_INTERVAL);
+        accept_shares(&client, 2).await;
+        store.flush(&frontend, &client_manager, false).await;
+
+        let frontend = crate::Frontend::new();
+        let client_manager = client::Manager::new(1);
+        let store = Store::open(&file.0, SYNC_INTERVAL);
+        store.restore(&frontend, &client_manager).await;
+        // the client is added after the totals have been restored
+        let client = create_client(&client_manager, "drain://pool-a").await;
+        assert!(!client.stats().lifetime().is_restored());
+        let snapshot = store.take_snapshot(&frontend, &client_manager).await;
+        assert_eq!(client.stats().lifetime().take_snapshot().accepted, 2);
+        assert_eq!(snapshot.clients[0].totals.accepted, 2);
+    }
+
+    #[test]
+    fn test_invalid_snapshot() {
+        let file = TestFile::new("invalid");
+        let valid = Snapshot {
+            version: FORMAT_VERSION,
+            time: 0,
+            frontend: Totals {
+                accepted: 10,
+                best_share: 1000,
+                ..Default::default()
+            },
+            clients: vec![],
+        };
+
+        fs::write(&file.0, "{ \"version\": 1, \"frontend\": ").unwrap();
+        assert_eq!(
+            Store::open(&file.0, SYNC_INTERVAL).frontend,
+            Totals::default()
+        );
+
+        let unknown_version = Snapshot {
+            version: FORMAT_VERSION + 1,
+            ..valid.clone()
+        };
+        fs::write(&file.0, serde_json::to_string(&unknown_version).unwrap()).unwrap();
+        assert_eq!(
+            Store::open(&file.0, SYNC_INTERVAL).frontend,
+            Totals::default()
+        );
+
+        let store = Store::open(&file.0, SYNC_INTERVAL);
+        store
+            .save(&valid, false)
+            .expect("BUG: cannot save snapshot");
+        assert_eq!(Store::open(&file.0, SYNC_INTERVAL).frontend, valid.frontend);
+
+        // the staging copy is lost on reboot and the persistent file is used
+        store.save(&valid, true).expect("BUG: cannot save snapshot");
+        fs::remove_file(file.staged().staging_path()).unwrap();
+        assert_eq!(Store::open(&file.0, SYNC_INTERVAL).frontend, valid.frontend);
+    }
+
+    #[test]
+    fn test_totals() {
+        let previous = Totals {
+            accepted: 10,
+            rejected: 1,
+            accepted_difficulty: 100.0,
+            rejected_difficulty: 10.0,
+            best_share: 5000,
+            elapsed: 3600,
+        };
+        let current = Totals {
+            accepted: 2,
+            rejected: 0,
+            accepted_difficulty: 20.0,
+            rejected_difficulty: 0.0,
+            best_share: 300,
+            elapsed: 60,
+        };
+        assert_eq!(
+            previous + current,
+            Totals {
+                accepted: 12,
+                rejected: 1,
+                accepted_difficulty: 120.0,
+                rejected_difficulty: 10.0,
+                best_share: 5000,
+                elapsed: 3660,
+            }
+        );
+    }
+}
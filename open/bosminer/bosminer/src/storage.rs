@@ -0,0 +1,242 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Files which are rewritten frequently and which have to survive restarts of the miner.
+//!
+//! Every write goes to a staging copy in the temporary directory (tmpfs on the miner) which
+//! survives restarts of the miner but not reboots. The persistent file (usually on flash memory)
+//! is rewritten and synced only when the last sync is older than the sync interval or when the
+//! sync is requested explicitly (e.g. on shutdown) to prevent wearing the flash memory. A power
+//! loss therefore loses at most the writes of one sync interval.
+//!
+//! The staging copy is named after the full path of the persistent file and the newer of both
+//! copies is read so that a stale staging copy never overrides the persistent file.
+
+use crate::clock;
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex as StdMutex;
+use std::time;
+
+/// Default period of syncing the persistent file
+pub const DEFAULT_SYNC_INTERVAL: time::Duration = time::Duration::from_secs(6 * 60 * 60);
+
+const STAGING_SUFFIX: &str = ".staged";
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path: OsString = path.into();
+    path.push(suffix);
+    path.into()
+}
+
+/// Name of the staging copy which is unique for the full `path` of the persistent file
+fn staging_file_name(path: &Path) -> OsString {
+    let path = std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf());
+    let mut name = path
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('/', "%2F");
+    name.push_str(STAGING_SUFFIX);
+    name.into()
+}
+
+/// Modification time of the file at `path` or `None` when it does not exist
+fn modified(path: &Path) -> io::Result<Option<time::SystemTime>> {
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.modified().map(Some),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replace file at `path` atomically to prevent corruption when the miner is killed
+fn replace(path: &Path, content: &[u8], sync: bool) -> io::Result<()> {
+    let tmp_path = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(content)?;
+    if sync {
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+#[derive(Debug)]
+pub struct StagedFile {
+    /// Persistent file
+    path: PathBuf,
+    /// Working copy in the temporary directory
+    staging_path: PathBuf,
+    sync_interval: time::Duration,
+    /// Time of the last sync of the persistent file
+    last_sync: StdMutex<time::Instant>,
+}
+
+impl StagedFile {
+    pub fn new<P: Into<PathBuf>>(path: P, sync_interval: time::Duration) -> Self {
+        let path = path.into();
+        let staging_path = std::env::temp_dir().join(staging_file_name(&path));
+        Self {
+            path,
+            staging_path,
+            sync_interval,
+            last_sync: StdMutex::new(clock::now()),
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn staging_path(&self) -> &Path {
+        &self.staging_path
+    }
+
+    /// Read the newer of the staging copy (the miner has been restarted) and the persistent file
+    pub fn read_to_string(&self) -> io::Result<String> {
+        match (modified(&self.staging_path)?, modified(&self.path)?) {
+            (Some(staged), Some(persistent)) if persistent > staged => {
+                fs::read_to_string(&self.path)
+            }
+            (Some(_), _) => fs::read_to_string(&self.staging_path),
+            (None, _) => fs::read_to_string(&self.path),
+        }
+    }
+
+    /// Write `content` to the staging copy and to the persistent file when it is due or when
+    /// the `sync` is requested
+    pub fn write(&self, content: &[u8], sync: bool) -> io::Result<()> {
+        replace(&self.staging_path, content, false)?;
+        let now = clock::now();
+        let mut last_sync = self
+            .last_sync
+            .lock()
+            .expect("BUG: cannot lock last sync time");
+        if sync || now.duration_since(*last_sync) >= self.sync_interval {
+            replace(&self.path, content, true)?;
+            *last_sync = now;
+        }
+        Ok(())
+    }
+
+    /// Remove both the persistent file and the staging copy
+    pub fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_file(&self.staging_path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ii_async_compat::tokio;
+
+    const SYNC_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+    fn create_file(name: &str) -> StagedFile {
+        let file = StagedFile::new(
+            std::env::temp_dir().join(format!("bosminer-staged-{}-{}", name, std::process::id())),
+            SYNC_INTERVAL,
+        );
+        file.remove();
+        file
+    }
+
+    #[tokio::test]
+    async fn test_sync() {
+        tokio::time::pause();
+        let file = create_file("sync");
+        assert_eq!(
+            file.read_to_string().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // only the staging copy is written before the sync is due
+        file.write(b"first", false).unwrap();
+        assert_eq!(file.read_to_string().unwrap(), "first");
+        assert!(!file.path().exists());
+
+        file.write(b"second", true).unwrap();
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "second");
+
+        file.write(b"third", false).unwrap();
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "second");
+        tokio::time::advance(SYNC_INTERVAL).await;
+        file.write(b"fourth", false).unwrap();
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), "fourth");
+
+        // the persistent file is used after reboot
+        fs::remove_file(file.staging_path()).unwrap();
+        fs::write(file.path(), "persistent").unwrap();
+        assert_eq!(file.read_to_string().unwrap(), "persistent");
+        file.remove();
+    }
+
+    #[test]
+    fn test_staging_path() {
+        let dir = std::env::temp_dir();
+        let file = StagedFile::new(dir.join("a").join("stats.json"), SYNC_INTERVAL);
+        let other = StagedFile::new(dir.join("b").join("stats.json"), SYNC_INTERVAL);
+        assert_ne!(file.staging_path(), other.staging_path());
+        assert_eq!(file.staging_path().parent(), Some(dir.as_path()));
+
+        // relative path is resolved against the current directory
+        let relative = StagedFile::new("stats.json", SYNC_INTERVAL);
+        let absolute = StagedFile::new(
+            std::env::current_dir().unwrap().join("stats.json"),
+            SYNC_INTERVAL,
+        );
+        assert_eq!(relative.staging_path(), absolute.staging_path());
+    }
+
+    /// Stale staging copy does not override newer persistent file
+    #[test]
+    fn test_newer_copy() {
+        let file = create_file("newer");
+        file.write(b"staged", false).unwrap();
+        fs::write(file.path(), "persistent").unwrap();
+
+        let now = time::SystemTime::now();
+        let set_modified = |path: &Path, time: time::SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap()
+        };
+        set_modified(file.staging_path(), now - time::Duration::from_secs(60));
+        set_modified(file.path(), now);
+        assert_eq!(file.read_to_string().unwrap(), "persistent");
+
+        set_modified(file.staging_path(), now + time::Duration::from_secs(60));
+        assert_eq!(file.read_to_string().unwrap(), "staged");
+        file.remove();
+    }
+}
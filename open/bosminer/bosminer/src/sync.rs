@@ -21,10 +21,14 @@
 // contact us at opensource@braiins.com.
 
 pub mod event;
+pub mod heartbeat;
+
+use crate::bus;
+use crate::log_id;
 
 use std::fmt;
 use std::sync::atomic::Ordering;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use atomic_enum::atomic_enum;
 
@@ -57,9 +61,19 @@ impl fmt::Display for Status {
 pub struct StatusMonitor {
     status: AtomicStatus,
     event_sender: Mutex<Option<event::Sender>>,
+    /// Client whose finished transitions are published to the bus
+    client: Option<(log_id::ClientName, Arc<bus::Bus>)>,
 }
 
 impl StatusMonitor {
+    /// Monitor of a client which also publishes its finished transitions to the `bus`
+    pub fn for_client(client_name: log_id::ClientName, bus: Arc<bus::Bus>) -> Self {
+        Self {
+            client: Some((client_name, bus)),
+            ..Default::default()
+        }
+    }
+
     #[inline]
     pub fn status(&self) -> Status {
         self.status.load(Ordering::Relaxed)
@@ -331,6 +345,12 @@ impl StatusMonitor {
             .expect("BUG: cannot lock event sender for notification")
             .as_ref()
             .map(|v| v.notify());
+        if let Some((client_name, bus)) = &self.client {
+            bus.publish(bus::Event::StatusChanged {
+                client: *client_name,
+                status: self.status(),
+            });
+        }
     }
 }
 
@@ -339,6 +359,7 @@ impl Default for StatusMonitor {
         Self {
             status: AtomicStatus::new(Status::Created),
             event_sender: Mutex::new(None),
+            client: None,
         }
     }
 }
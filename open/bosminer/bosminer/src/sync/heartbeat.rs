@@ -0,0 +1,128 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Heartbeats of the main loops of the core. Every loop beats at least once per its period even
+//! when it has nothing to do so a supervisor can tell a wedged loop from an idle one.
+
+use crate::clock;
+
+use std::sync::{Arc, Mutex as StdMutex, Weak};
+use std::time;
+
+#[derive(Debug)]
+pub struct Heartbeat {
+    name: &'static str,
+    /// The loop is considered stalled when it has not beaten for this long
+    timeout: time::Duration,
+    last_beat: StdMutex<time::Instant>,
+}
+
+impl Heartbeat {
+    pub fn new(name: &'static str, timeout: time::Duration) -> Self {
+        Self {
+            name,
+            timeout,
+            last_beat: StdMutex::new(clock::now()),
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn beat(&self) {
+        *self.last_beat.lock().expect("BUG: cannot lock heartbeat") = clock::now();
+    }
+
+    pub fn is_stalled(&self, now: time::Instant) -> bool {
+        let last_beat = *self.last_beat.lock().expect("BUG: cannot lock heartbeat");
+        now.saturating_duration_since(last_beat) > self.timeout
+    }
+}
+
+/// Heartbeats of all running loops. The registry keeps only weak references so a loop which has
+/// ended (e.g. after the miner has been shut down) is not checked anymore.
+#[derive(Debug, Default)]
+pub struct Registry {
+    heartbeats: StdMutex<Vec<Weak<Heartbeat>>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&self, heartbeat: &Arc<Heartbeat>) {
+        self.heartbeats
+            .lock()
+            .expect("BUG: cannot lock heartbeat registry")
+            .push(Arc::downgrade(heartbeat));
+    }
+
+    /// Return names of all loops which have not beaten in time
+    pub fn find_stalled(&self, now: time::Instant) -> Vec<&'static str> {
+        let mut heartbeats = self
+            .heartbeats
+            .lock()
+            .expect("BUG: cannot lock heartbeat registry");
+        heartbeats.retain(|heartbeat| heartbeat.strong_count() > 0);
+        heartbeats
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|heartbeat| heartbeat.is_stalled(now))
+            .map(|heartbeat| heartbeat.name())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stalled_loop() {
+        let timeout = time::Duration::from_secs(10);
+        let registry = Registry::new();
+        // the heartbeats are created after this time so the scheduler is not stalled yet
+        let now = clock::now();
+        let router = Arc::new(Heartbeat::new("router", timeout));
+        let scheduler = Arc::new(Heartbeat::new("scheduler", 2 * timeout));
+        registry.add(&router);
+        registry.add(&scheduler);
+
+        assert!(registry.find_stalled(now).is_empty());
+        assert_eq!(registry.find_stalled(now + 2 * timeout), vec!["router"]);
+
+        // the reference time is taken before the beat so the router cannot be late yet
+        let now = clock::now();
+        router.beat();
+        assert!(registry.find_stalled(now + timeout).is_empty());
+
+        // the loop has ended and it is not expected to beat anymore
+        drop(router);
+        assert_eq!(
+            registry.find_stalled(clock::now() + 3 * timeout),
+            vec!["scheduler"]
+        );
+    }
+}
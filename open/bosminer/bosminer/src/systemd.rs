@@ -0,0 +1,315 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Integration with systemd service manager using the `sd_notify` protocol. The manager is told
+//! when the miner becomes healthy, its watchdog is kept alive only while the main loops of the
+//! core keep beating and it is notified when the miner is being shut down. Nothing is sent
+//! unless the miner has been started by systemd with `NOTIFY_SOCKET` set.
+
+use ii_logging::macros::*;
+
+use crate::clock;
+use crate::hub;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use std::env;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+use std::time;
+
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+const WATCHDOG_USEC_ENV: &str = "WATCHDOG_USEC";
+const WATCHDOG_PID_ENV: &str = "WATCHDOG_PID";
+
+const READY: &str = "READY=1";
+const WATCHDOG: &str = "WATCHDOG=1";
+const STOPPING: &str = "STOPPING=1";
+
+/// Health of the core is checked this often until the miner is reported as ready
+const READY_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+/// Client of the notification socket of the service manager
+#[derive(Debug)]
+pub struct Notifier {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl Notifier {
+    pub fn new<T: Into<PathBuf>>(path: T) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        // the manager is never waited for
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            path: path.into(),
+        })
+    }
+
+    /// Connect to the socket passed by the service manager. Returns `None` when the miner has
+    /// not been started by systemd or when the socket cannot be used.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var_os(NOTIFY_SOCKET_ENV)?;
+        if path.to_string_lossy().starts_with('@') {
+            warn!(
+                "Systemd: abstract notification socket {:?} is not supported",
+                path
+            );
+            return None;
+        }
+        Self::new(path)
+            .map_err(|e| warn!("Systemd: cannot create notification socket: {}", e))
+            .ok()
+    }
+
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        self.socket
+            .send_to(state.as_bytes(), &self.path)
+            .map(|_| ())
+    }
+}
+
+/// Return how often the watchdog has to be kept alive when it has been enabled for this process.
+/// The watchdog is pinged twice per its timeout as recommended by `sd_watchdog_enabled`.
+pub fn watchdog_interval() -> Option<time::Duration> {
+    if let Ok(pid) = env::var(WATCHDOG_PID_ENV) {
+        if pid.parse::<u32>().ok()? != process::id() {
+            return None;
+        }
+    }
+    let usec = env::var(WATCHDOG_USEC_ENV).ok()?.parse::<u64>().ok()?;
+    if usec == 0 {
+        return None;
+    }
+    Some(time::Duration::from_micros(usec) / 2)
+}
+
+/// Translates state of the core into notifications of the service manager
+#[derive(Debug)]
+pub struct Supervisor {
+    notifier: Notifier,
+    /// Readiness is reported only once
+    ready: bool,
+    /// Loops which have been found stalled in the last check
+    stalled: Vec<&'static str>,
+}
+
+impl Supervisor {
+    pub fn new(notifier: Notifier) -> Self {
+        Self {
+            notifier,
+            ready: false,
+            stalled: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Report readiness when the core has become `healthy` and keep the watchdog alive (when
+    /// `watchdog` is enabled) unless some of the main loops have `stalled`
+    pub fn check(
+        &mut self,
+        healthy: bool,
+        stalled: Vec<&'static str>,
+        watchdog: bool,
+    ) -> io::Result<()> {
+        if !self.ready && healthy {
+            self.notifier.notify(READY)?;
+            self.ready = true;
+            info!("Systemd: miner is ready");
+        }
+        if stalled.is_empty() {
+            if !self.stalled.is_empty() {
+                info!("Systemd: all loops are running again");
+            }
+            if watchdog {
+                self.notifier.notify(WATCHDOG)?;
+            }
+        } else if stalled != self.stalled {
+            warn!(
+                "Systemd: watchdog is not kept alive because of stalled loops: {}",
+                stalled.join(", ")
+            );
+        }
+        self.stalled = stalled;
+        Ok(())
+    }
+
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notifier.notify(STOPPING)
+    }
+}
+
+/// Tell the service manager that the miner is being shut down
+pub fn notify_stopping() {
+    if let Some(notifier) = Notifier::from_env() {
+        if let Err(e) = Supervisor::new(notifier).stopping() {
+            warn!("Systemd: cannot notify about shutdown: {}", e);
+        }
+    }
+}
+
+/// Supervise the `core` on behalf of the service manager. The task ends immediately when the
+/// miner has not been started by systemd and after reporting readiness when the watchdog is not
+/// enabled.
+pub async fn run(core: Arc<hub::Core>) {
+    let notifier = match Notifier::from_env() {
+        Some(notifier) => notifier,
+        None => return,
+    };
+    let watchdog_interval = watchdog_interval();
+    let mut supervisor = Supervisor::new(notifier);
+    loop {
+        let healthy = core.summary().await.is_healthy();
+        let stalled = core.get_heartbeats().find_stalled(clock::now());
+        if let Err(e) = supervisor.check(healthy, stalled, watchdog_interval.is_some()) {
+            warn!("Systemd: cannot notify service manager: {}", e);
+        }
+        let interval = match (watchdog_interval, supervisor.is_ready()) {
+            (Some(interval), true) => interval,
+            (Some(interval), false) => interval.min(READY_CHECK_INTERVAL),
+            (None, true) => return,
+            (None, false) => READY_CHECK_INTERVAL,
+        };
+        delay_for(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sync::heartbeat::{self, Heartbeat};
+
+    use std::fs;
+
+    /// Datagram socket standing in for the service manager
+    struct Manager {
+        socket: UnixDatagram,
+        path: PathBuf,
+    }
+
+    impl Manager {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("bosminer-notify-{}-{}", process::id(), name));
+            let _ = fs::remove_file(&path);
+            let socket = UnixDatagram::bind(&path).expect("BUG: cannot bind test socket");
+            socket
+                .set_nonblocking(true)
+                .expect("BUG: cannot set test socket non-blocking");
+            Self { socket, path }
+        }
+
+        fn supervisor(&self) -> Supervisor {
+            Supervisor::new(Notifier::new(&self.path).expect("BUG: cannot create notifier"))
+        }
+
+        /// Return all messages received since the last call
+        fn messages(&self) -> Vec<String> {
+            let mut messages = vec![];
+            let mut buffer = [0u8; 64];
+            loop {
+                match self.socket.recv(&mut buffer) {
+                    Ok(length) => {
+                        messages.push(String::from_utf8_lossy(&buffer[..length]).into_owned())
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(e) => panic!("BUG: cannot receive notification: {}", e),
+                }
+            }
+            messages
+        }
+    }
+
+    impl Drop for Manager {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_healthy() {
+        let manager = Manager::new("healthy");
+        let mut supervisor = manager.supervisor();
+
+        // the watchdog is kept alive while the miner is starting
+        supervisor.check(false, vec![], true).unwrap();
+        assert_eq!(manager.messages(), vec![WATCHDOG]);
+        supervisor.check(true, vec![], true).unwrap();
+        assert_eq!(manager.messages(), vec![READY, WATCHDOG]);
+        supervisor.check(true, vec![], true).unwrap();
+        assert_eq!(manager.messages(), vec![WATCHDOG]);
+        assert!(supervisor.is_ready());
+
+        // readiness is reported only once even when the miner has been unhealthy in between
+        supervisor.check(false, vec![], false).unwrap();
+        supervisor.check(true, vec![], false).unwrap();
+        assert!(manager.messages().is_empty());
+    }
+
+    #[test]
+    fn test_wedged() {
+        let manager = Manager::new("wedged");
+        let mut supervisor = manager.supervisor();
+        let timeout = time::Duration::from_secs(30);
+        let heartbeats = heartbeat::Registry::new();
+        let router = Arc::new(Heartbeat::new("router", timeout));
+        heartbeats.add(&router);
+
+        supervisor
+            .check(true, heartbeats.find_stalled(clock::now()), true)
+            .unwrap();
+        assert_eq!(manager.messages(), vec![READY, WATCHDOG]);
+
+        // no ping is sent while the router is wedged so the manager restarts the miner
+        let wedged = clock::now() + 2 * timeout;
+        for _ in 0..2 {
+            supervisor
+                .check(true, heartbeats.find_stalled(wedged), true)
+                .unwrap();
+            assert!(manager.messages().is_empty());
+        }
+
+        router.beat();
+        supervisor
+            .check(true, heartbeats.find_stalled(clock::now()), true)
+            .unwrap();
+        assert_eq!(manager.messages(), vec![WATCHDOG]);
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let manager = Manager::new("shutdown");
+        let mut supervisor = manager.supervisor();
+
+        supervisor.check(true, vec![], true).unwrap();
+        supervisor.stopping().unwrap();
+        assert_eq!(manager.messages(), vec![READY, WATCHDOG, STOPPING]);
+    }
+}
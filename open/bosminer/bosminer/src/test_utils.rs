@@ -21,7 +21,10 @@
 // contact us at opensource@braiins.com.
 
 pub mod block_mining;
+#[cfg(test)]
+pub mod simulation;
 
+use crate::error;
 use crate::hal;
 use crate::job::{self, Bitcoin as _};
 use crate::node;
@@ -37,7 +40,9 @@ use futures::lock::Mutex;
 use ii_async_compat::futures;
 
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
+use std::time::Duration;
 
 use async_trait::async_trait;
 
@@ -103,26 +108,86 @@ impl fmt::Display for TestClient {
 pub struct TestWorkSolver {
     #[member_work_solver_stats]
     work_solver_stats: stats::BasicWorkSolver,
+    id: Option<usize>,
+    status: StdMutex<node::Status>,
+    topology: Option<node::Topology>,
+    backend_stats: Option<serde_json::Value>,
+    power_saving: AtomicBool,
 }
 
 impl TestWorkSolver {
     pub fn new() -> Self {
         Self {
             work_solver_stats: Default::default(),
+            id: None,
+            status: StdMutex::new(node::Status::Ok),
+            topology: None,
+            backend_stats: None,
+            power_saving: AtomicBool::new(false),
+        }
+    }
+
+    /// Create work solver distinguishable by its `id` (it is also part of its name)
+    pub fn with_id(id: usize) -> Self {
+        Self {
+            id: Some(id),
+            ..Self::new()
         }
     }
+
+    pub fn set_status(&self, status: node::Status) {
+        *self.status.lock().expect("cannot lock status") = status;
+    }
+
+    pub fn with_topology(mut self, topology: node::Topology) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    pub fn with_backend_stats(mut self, backend_stats: serde_json::Value) -> Self {
+        self.backend_stats = Some(backend_stats);
+        self
+    }
+
+    pub fn is_power_saving(&self) -> bool {
+        self.power_saving.load(Ordering::Relaxed)
+    }
 }
 
 #[async_trait]
 impl node::WorkSolver for TestWorkSolver {
+    fn get_id(&self) -> Option<usize> {
+        self.id
+    }
+
     async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
         None
     }
+
+    async fn get_status(&self) -> node::Status {
+        *self.status.lock().expect("cannot lock status")
+    }
+
+    async fn topology(&self) -> Option<node::Topology> {
+        self.topology.clone()
+    }
+
+    async fn backend_stats(&self) -> Option<serde_json::Value> {
+        self.backend_stats.clone()
+    }
+
+    async fn set_power_saving(&self, enabled: bool) -> error::Result<()> {
+        self.power_saving.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 impl fmt::Display for TestWorkSolver {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Test work solver")
+        match self.id {
+            Some(id) => write!(f, "Test work solver {}", id),
+            None => write!(f, "Test work solver"),
+        }
     }
 }
 
@@ -136,7 +201,7 @@ impl job::Bitcoin for TestBlock {
     }
 
     fn version_mask(&self) -> u32 {
-        0
+        ii_bitcoin::BIP320_VERSION_MASK
     }
 
     fn previous_hash(&self) -> &ii_bitcoin::DHash {
@@ -164,6 +229,71 @@ impl job::Bitcoin for TestBlock {
     }
 }
 
+/// Test block with the version mask (and optionally ntime rolling) restricted by the pool
+#[derive(Debug, Clone, Copy)]
+pub struct MaskedTestBlock {
+    pub block: TestBlock,
+    pub version_mask: u32,
+    pub ntime_roll_limit: u32,
+}
+
+impl MaskedTestBlock {
+    pub fn new(block: TestBlock, version_mask: u32) -> Self {
+        Self {
+            block,
+            version_mask,
+            ntime_roll_limit: 0,
+        }
+    }
+
+    pub fn with_ntime_roll_limit(mut self, ntime_roll_limit: u32) -> Self {
+        self.ntime_roll_limit = ntime_roll_limit;
+        self
+    }
+}
+
+impl job::Bitcoin for MaskedTestBlock {
+    fn origin(&self) -> Weak<dyn node::Client> {
+        self.block.origin()
+    }
+
+    fn version(&self) -> u32 {
+        self.block.version
+    }
+
+    fn version_mask(&self) -> u32 {
+        self.version_mask
+    }
+
+    fn previous_hash(&self) -> &ii_bitcoin::DHash {
+        &self.block.previous_hash
+    }
+
+    fn merkle_root(&self) -> &ii_bitcoin::DHash {
+        &self.block.merkle_root
+    }
+
+    fn time(&self) -> u32 {
+        self.block.time
+    }
+
+    fn ntime_roll_limit(&self) -> u32 {
+        self.ntime_roll_limit
+    }
+
+    fn bits(&self) -> u32 {
+        self.block.bits
+    }
+
+    fn target(&self) -> ii_bitcoin::Target {
+        self.block.target
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
 /// Trait used for `TestBlock` customization
 pub trait TestBlockBuilder {
     /// Modify job target
@@ -179,9 +309,12 @@ impl TestBlockBuilder for TestBlock {
 }
 
 #[derive(Debug)]
-struct TestSolution {
+pub struct TestSolution {
     test_block: TestBlock,
     target: ii_bitcoin::Target,
+    midstate_idx: usize,
+    age_hint: Option<Duration>,
+    hw_timestamp: Option<Duration>,
 }
 
 impl TestSolution {
@@ -189,8 +322,35 @@ impl TestSolution {
         Self {
             test_block: *test_block,
             target: Default::default(),
+            midstate_idx: 0,
+            age_hint: None,
+            hw_timestamp: None,
         }
     }
+
+    /// Simulate backend which reports arbitrary midstate index
+    pub fn with_midstate_idx(mut self, midstate_idx: usize) -> Self {
+        self.midstate_idx = midstate_idx;
+        self
+    }
+
+    /// Simulate backend which reports corrupted nonce
+    pub fn with_nonce(mut self, nonce: u32) -> Self {
+        self.test_block.nonce = nonce;
+        self
+    }
+
+    /// Simulate backend which reports that the solution has been found some time ago
+    pub fn with_age_hint(mut self, age: Duration) -> Self {
+        self.age_hint = Some(age);
+        self
+    }
+
+    /// Simulate backend which measures latency of the solution in hardware
+    pub fn with_hw_timestamp(mut self, hw_timestamp: Duration) -> Self {
+        self.hw_timestamp = Some(hw_timestamp);
+        self
+    }
 }
 
 impl hal::BackendSolution for TestSolution {
@@ -201,7 +361,7 @@ impl hal::BackendSolution for TestSolution {
 
     #[inline]
     fn midstate_idx(&self) -> usize {
-        0
+        self.midstate_idx
     }
 
     #[inline]
@@ -212,6 +372,14 @@ impl hal::BackendSolution for TestSolution {
     fn target(&self) -> &ii_bitcoin::Target {
         &self.target
     }
+
+    fn age_hint(&self) -> Option<Duration> {
+        self.age_hint
+    }
+
+    fn hw_timestamp(&self) -> Option<Duration> {
+        self.hw_timestamp
+    }
 }
 
 impl From<&TestBlock> for work::Assignment {
@@ -230,7 +398,11 @@ impl From<&TestBlock> for work::Assignment {
 
 impl From<&TestBlock> for work::Solution {
     fn from(test_block: &TestBlock) -> Self {
-        Self::new(test_block.into(), TestSolution::new(test_block), None)
+        Self::new(
+            work::Assignment::from(test_block),
+            TestSolution::new(test_block),
+            None,
+        )
     }
 }
 
@@ -395,6 +567,45 @@ pub fn create_test_work_generator(work_solver: Arc<dyn node::WorkSolver>) -> wor
     )
 }
 
+/// Check that `work` (e.g. from `work::audit::Report`) is distributed in `expected` ratios which
+/// do not have to be normalized. Share of each item must not differ from its expected share by
+/// more than `tolerance` (e.g. 0.05 allows 20-30% share when 25% is expected).
+pub fn check_distribution(work: &[u64], expected: &[f64], tolerance: f64) -> Result<(), String> {
+    assert_eq!(
+        work.len(),
+        expected.len(),
+        "BUG: distribution with different number of items"
+    );
+    let expected_total: f64 = expected.iter().sum();
+    assert!(
+        expected_total > 0.0,
+        "BUG: expected distribution without any work"
+    );
+
+    let total: u64 = work.iter().sum();
+    if total == 0 {
+        return Err("no work has been distributed".to_string());
+    }
+    for (idx, (amount, ratio)) in work.iter().zip(expected).enumerate() {
+        let share = *amount as f64 / total as f64;
+        let expected_share = ratio / expected_total;
+        if (share - expected_share).abs() > tolerance {
+            return Err(format!(
+                "item {} has share {:.3} instead of {:.3} with tolerance {}",
+                idx, share, expected_share, tolerance
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Same as `check_distribution` but it panics when the distribution does not match
+pub fn assert_distribution(work: &[u64], expected: &[f64], tolerance: f64) {
+    if let Err(reason) = check_distribution(work, expected, tolerance) {
+        panic!("unexpected distribution {:?}: {}", work, reason);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -449,4 +660,36 @@ mod test {
             _ => panic!("test work generator continues after returning all work"),
         };
     }
+
+    #[test]
+    fn test_check_distribution() {
+        // exact match is accepted even without any tolerance
+        assert_eq!(check_distribution(&[25, 75], &[0.25, 0.75], 0.0), Ok(()));
+        // ratios are normalized
+        assert_eq!(check_distribution(&[25, 75], &[1.0, 3.0], 0.0), Ok(()));
+        assert_eq!(
+            check_distribution(&[0, 10, 30], &[0.0, 2.0, 6.0], 0.0),
+            Ok(())
+        );
+
+        // the share differs by 0.05 in both directions
+        assert_eq!(check_distribution(&[30, 70], &[1.0, 3.0], 0.06), Ok(()));
+        assert_eq!(check_distribution(&[20, 80], &[1.0, 3.0], 0.06), Ok(()));
+        assert!(check_distribution(&[30, 70], &[1.0, 3.0], 0.04).is_err());
+        assert!(check_distribution(&[20, 80], &[1.0, 3.0], 0.04).is_err());
+
+        // item which should not get any work
+        assert_eq!(check_distribution(&[1, 99], &[0.0, 1.0], 0.02), Ok(()));
+        let reason = check_distribution(&[5, 95], &[0.0, 1.0], 0.02).unwrap_err();
+        assert!(reason.starts_with("item 0 has share 0.050 instead of 0.000"));
+
+        // nothing can be verified without any work
+        assert!(check_distribution(&[0, 0], &[1.0, 1.0], 1.0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected distribution [10, 10]")]
+    fn test_assert_distribution() {
+        assert_distribution(&[10, 10], &[1.0, 3.0], 0.2);
+    }
 }
@@ -24,49 +24,41 @@
 //! This test is deterministic - we know hardware can mine all the test blocks in `test_utils`,
 //! and we want to verify that we receive correct solution for each block (which tests
 //! that all work has been correctly defined and sent to hardware).
+//!
+//! Each work solver (hash chain) has its own engine channel and it receives all test problems so
+//! that every solved or missing problem is attributed to the chain which has received it.
 
 use ii_logging::macros::*;
 
 use ii_bitcoin::HashTrait;
 
 use crate::backend;
+use crate::client;
 use crate::hal::{self, BackendConfig as _};
 use crate::job::Bitcoin;
 use crate::node;
 use crate::test_utils;
 use crate::work;
 
+use serde::Serialize;
+
 use std::time::{Duration, Instant};
 
 use ii_async_compat::tokio;
 use tokio::time::delay_for;
 
 use futures::channel::mpsc;
-use futures::lock::Mutex;
 use futures::stream::StreamExt;
 use ii_async_compat::futures;
 
 use std::collections::HashMap;
-use std::sync::Arc;
-
-#[derive(Debug)]
-struct ExhaustedWorkHandler {
-    reschedule_sender: mpsc::UnboundedSender<work::DynEngine>,
-}
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
 
-impl ExhaustedWorkHandler {
-    pub fn new(reschedule_sender: mpsc::UnboundedSender<work::DynEngine>) -> Self {
-        Self { reschedule_sender }
-    }
-}
+/// Interval of polling the last broadcast engine for being exhausted
+const EXHAUSTED_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
-impl work::ExhaustedHandler for ExhaustedWorkHandler {
-    fn handle_exhausted(&self, engine: work::DynEngine) {
-        self.reschedule_sender
-            .unbounded_send(engine)
-            .expect("reschedule notify send failed");
-    }
-}
+/// Interval of polling the registries for being solved
+const SOLVED_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Problem is a "work recipe" for mining hardware that is to have a particular
 /// solution in a particular midstate.
@@ -250,44 +242,266 @@ impl Registry {
         }
         everything_solved
     }
+
+    /// Number of all problems in registry
+    fn problem_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Number of problems which have been solved
+    fn solved_count(&self) -> usize {
+        self.map.values().filter(|state| state.solved).count()
+    }
+
+    /// Problems which have not been solved ordered by midstate index and block hash
+    fn missing_problems(&self) -> Vec<MissingProblem> {
+        let mut missing: Vec<_> = self
+            .map
+            .iter()
+            .filter(|(_, state)| !state.solved)
+            .map(|(key, _)| MissingProblem {
+                block: key.hash.to_string(),
+                midstate_idx: key.midstate_idx,
+            })
+            .collect();
+        missing.sort_by(|a, b| (a.midstate_idx, &a.block).cmp(&(b.midstate_idx, &b.block)));
+        missing
+    }
+}
+
+/// Problem which has not been solved by the chain it has been assigned to
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MissingProblem {
+    /// Hash of the test block
+    pub block: String,
+    /// Index of the midstate which should have been solved
+    pub midstate_idx: usize,
+}
+
+/// Result of the test for one work solver (hash chain)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChainResult {
+    /// Index of the chain in order of creation of work solvers
+    pub chain: usize,
+    /// Backend specific id of the work solver (e.g. hash chain connector)
+    pub id: Option<usize>,
+    pub name: String,
+    /// Number of problems sent to the chain
+    pub assigned: usize,
+    pub solved: usize,
+    pub missing: Vec<MissingProblem>,
+    /// Seconds from the first assigned problem to the last solution (or to the end of the test
+    /// when some problem has not been solved)
+    pub elapsed: f64,
+}
+
+impl ChainResult {
+    fn is_passed(&self, problem_count: usize) -> bool {
+        self.assigned == problem_count && self.solved == problem_count
+    }
+}
+
+/// Aggregated result of the test for all chains
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Report {
+    pub passed: bool,
+    /// Number of problems each chain has to solve
+    pub problem_count: usize,
+    /// Duration of the whole test in seconds
+    pub elapsed: f64,
+    pub chains: Vec<ChainResult>,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("BUG: cannot serialize block mining report")
+    }
+}
+
+/// Output of the test results
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    /// Results are only logged
+    Log,
+    /// Results are also printed to standard output as JSON (consumed by factory lines)
+    Json,
+}
+
+/// Work solver (hash chain) with its own engine channel and registry of problems sent to it
+struct Chain {
+    engine_sender: work::EngineSender,
+    work_solver: Option<Arc<dyn node::WorkSolver>>,
+    registry: Registry,
+    /// Time when the first problem has been sent to the chain
+    started: Option<Instant>,
+    /// Time when the last assigned problem has been solved
+    finished: Option<Instant>,
+}
+
+impl Chain {
+    fn is_work_solver(&self, unique_ptr: &Arc<dyn std::any::Any>) -> bool {
+        self.work_solver.as_ref().map_or(false, |work_solver| {
+            Arc::ptr_eq(&work_solver.clone().get_unique_ptr(), unique_ptr)
+        })
+    }
+
+    fn add_solution(&mut self, solution: Solution) {
+        self.registry.add_solution(solution);
+        if self.finished.is_none() && self.registry.check_everything_solved(false) {
+            self.finished = Some(Instant::now());
+        }
+    }
+
+    fn take_result(&self, chain: usize, now: Instant) -> ChainResult {
+        ChainResult {
+            chain,
+            id: self
+                .work_solver
+                .as_ref()
+                .and_then(|work_solver| work_solver.get_id()),
+            name: self
+                .work_solver
+                .as_ref()
+                .map_or_else(String::new, |work_solver| work_solver.to_string()),
+            assigned: self.registry.problem_count(),
+            solved: self.registry.solved_count(),
+            missing: self.registry.missing_problems(),
+            elapsed: self.started.map_or(0.0, |started| {
+                let finished = match self.finished {
+                    Some(finished) if self.registry.check_everything_solved(false) => finished,
+                    _ => now,
+                };
+                finished.duration_since(started).as_secs_f64()
+            }),
+        }
+    }
+}
+
+/// Engine router creating dedicated engine channel for each work solver so that problems are not
+/// broadcast to all of them
+#[derive(Default)]
+struct Router {
+    chains: StdMutex<Vec<Chain>>,
+}
+
+impl Router {
+    fn lock_chains(&self) -> StdMutexGuard<Vec<Chain>> {
+        self.chains.lock().expect("BUG: cannot lock chains")
+    }
+
+    fn chain_count(&self) -> usize {
+        self.lock_chains().len()
+    }
+
+    /// Register the problem to the chain and send its work. The engine with the work is returned
+    /// so that the caller can wait for the work to be taken.
+    fn send_problem(
+        &self,
+        chain: usize,
+        problem: Problem,
+        midstate_count: usize,
+    ) -> work::DynEngine {
+        let mut chains = self.lock_chains();
+        let chain = &mut chains[chain];
+        if !chain.registry.add_problem(problem.clone()) {
+            panic!("duplicate problem");
+        }
+        chain.started.get_or_insert_with(Instant::now);
+        let engine: work::DynEngine = Arc::new(test_utils::OneWorkEngine::new(
+            problem.into_work(midstate_count),
+        ));
+        chain.engine_sender.broadcast_engine(engine.clone());
+        engine
+    }
+
+    /// Pair the solution with the problem of the chain which has found it
+    fn add_solution(&self, solution: work::Solution) {
+        let unique_ptr = match solution.path().last() {
+            Some(node) => node.clone().get_unique_ptr(),
+            None => {
+                warn!("no work solver for {:?}", solution);
+                return;
+            }
+        };
+        match self
+            .lock_chains()
+            .iter_mut()
+            .find(|chain| chain.is_work_solver(&unique_ptr))
+        {
+            Some(chain) => chain.add_solution(solution.into()),
+            None => warn!("no chain for {:?}", solution),
+        }
+    }
+
+    fn check_everything_solved(&self) -> bool {
+        self.lock_chains()
+            .iter()
+            .all(|chain| chain.registry.check_everything_solved(false))
+    }
+
+    fn take_results(&self) -> Vec<ChainResult> {
+        let now = Instant::now();
+        self.lock_chains()
+            .iter()
+            .enumerate()
+            .map(|(idx, chain)| {
+                chain.registry.check_everything_solved(true);
+                chain.take_result(idx, now)
+            })
+            .collect()
+    }
+}
+
+impl work::EngineRouter for Router {
+    fn open_route(&self) -> (usize, work::EngineReceiver) {
+        let (engine_sender, engine_receiver) = work::engine_channel(work::IgnoreEvents);
+        let mut chains = self.lock_chains();
+        chains.push(Chain {
+            engine_sender,
+            work_solver: None,
+            registry: Registry::new(),
+            started: None,
+            finished: None,
+        });
+        (chains.len() - 1, engine_receiver)
+    }
+
+    fn attach(&self, route: usize, work_solver: Arc<dyn node::WorkSolver>) {
+        self.lock_chains()[route].work_solver = Some(work_solver);
+    }
 }
 
 /// This builds the solver chain:
-/// - build `engine_sender`/`engine_receiver` pair to send engines to `Solver`
-/// - add channel to `engine_sender` that will notify us of engine being exhausted
 /// - make a channel to get solutions back
-/// - build a solver and connect everything to it
-fn build_solvers() -> (
-    work::EngineSender,
+/// - build a solver which routes engines to each created work solver through its own channel
+///   provided by the `router`
+fn build_solvers(
+    router: Arc<Router>,
+) -> (
     mpsc::UnboundedReceiver<work::Solution>,
-    mpsc::UnboundedReceiver<work::DynEngine>,
     work::SolverBuilder<crate::Frontend>,
 ) {
-    let (reschedule_sender, reschedule_receiver) = mpsc::unbounded();
-    let (engine_sender, engine_receiver) =
-        work::engine_channel(ExhaustedWorkHandler::new(reschedule_sender));
+    // all work solvers get their engine receivers from the router
+    let (_, engine_receiver) = work::engine_channel(work::IgnoreEvents);
     let (solution_queue_tx, solution_queue_rx) = mpsc::unbounded();
+    let mut work_solver_builder = work::SolverBuilder::new(
+        Arc::new(crate::Frontend::new()),
+        Arc::new(backend::IgnoreHierarchy),
+        engine_receiver,
+        solution_queue_tx,
+    );
+    work_solver_builder.set_engine_router(router);
     (
-        // Send engines here (preferably OneWork engines)
-        engine_sender,
         // Receive solutions from this
         solution_queue_rx,
-        // Receive exhausted engines here (once OneWorkEngine has been turned into MiningWork,
-        // then you will be able to receive it here)
-        reschedule_receiver,
         // This is a solver that you hand off to backend
-        work::SolverBuilder::new(
-            Arc::new(crate::Frontend::new()),
-            Arc::new(backend::IgnoreHierarchy),
-            engine_receiver,
-            solution_queue_tx,
-        ),
+        work_solver_builder,
     )
 }
 
 async fn collect_solutions(
     mut solution_queue_rx: mpsc::UnboundedReceiver<work::Solution>,
-    registry: Arc<Mutex<Registry>>,
+    router: Arc<Router>,
 ) {
     while let Some(solution) = solution_queue_rx.next().await {
         let job: &test_utils::TestBlock = solution.job();
@@ -298,23 +512,54 @@ async fn collect_solutions(
             solution.midstate_idx(),
             solution.hash()
         );
-        registry.lock().await.add_solution(solution.into());
+        router.add_solution(solution);
+    }
+}
+
+/// Wait for the work (engine) to be taken by the chain
+async fn wait_for_exhausted(engine: &work::DynEngine, timeout: Duration) -> bool {
+    let started = Instant::now();
+    while !engine.is_exhausted() {
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        delay_for(EXHAUSTED_POLL_INTERVAL).await;
     }
+    true
 }
 
-pub async fn run<T: hal::Backend>(mut backend_config: T::Config) {
+/// Generate all blocks for all possible midstates and send each of them exactly once to the
+/// chain. Sending is stopped when the chain does not take the work in `timeout`.
+async fn feed_chain(router: Arc<Router>, chain: usize, midstate_count: usize, timeout: Duration) {
+    let mut engine: Option<work::DynEngine> = None;
+    for target_midstate in 0..midstate_count {
+        for test_block in test_utils::TEST_BLOCKS.iter() {
+            // wait for the previous work (engine) to be sent out (exhausted)
+            if let Some(engine) = engine.take() {
+                if !wait_for_exhausted(&engine, timeout).await {
+                    error!("chain {} does not take any work", chain);
+                    return;
+                }
+            }
+            let problem = Problem::new(test_block.into(), target_midstate);
+            engine = Some(router.send_problem(chain, problem, midstate_count));
+        }
+    }
+}
+
+/// Run the test on all work solvers created by the backend and return results for each of them
+pub async fn verify<T: hal::Backend>(mut backend_config: T::Config) -> Report {
     // this is a small miner core: we generate work, collect solutions, and we pair them together
-    // we expect all (generated) problems to be solved
-    // ii_async_compat::run_main_exits(async move {
-    // read config
+    // we expect all (generated) problems to be solved by all chains
+    let started = Instant::now();
     let midstate_count = backend_config.midstate_count();
+    let problem_count = midstate_count * test_utils::TEST_BLOCKS.len();
+    // backends register their clients during initialization even though no client is used here
+    backend_config.set_client_manager(client::Manager::new(midstate_count));
 
     // Create solver and channels to send/receive work
-    let (engine_sender, solution_queue_rx, mut reschedule_receiver, work_solver_builder) =
-        build_solvers();
-
-    // create problem registry
-    let registry = Arc::new(Mutex::new(Registry::new()));
+    let router = Arc::new(Router::default());
+    let (solution_queue_rx, work_solver_builder) = build_solvers(router.clone());
 
     // start HW backend for selected target
     match T::create(&mut backend_config) {
@@ -330,48 +575,52 @@ pub async fn run<T: hal::Backend>(mut backend_config: T::Config) {
         }
     }
 
-    // start task to collect solutions and put them to registry
-    tokio::spawn(collect_solutions(solution_queue_rx, registry.clone()));
-
-    // TODO: first work sent to miner is for some reason ignored
-    // workaround: send two works
-    engine_sender.broadcast_engine(Arc::new(test_utils::OneWorkEngine::new(
-        Problem::new((&test_utils::TEST_BLOCKS[0]).into(), 0).into_work(midstate_count),
-    )));
+    // start task to collect solutions and pair them with problems of the chains
+    tokio::spawn(collect_solutions(solution_queue_rx, router.clone()));
 
-    // generate all blocks for all possible midstates
-    for target_midstate in 0..midstate_count {
-        for test_block in test_utils::TEST_BLOCKS.iter() {
-            let problem = Problem {
-                model_solution: test_block.into(),
-                target_midstate,
-            };
-            let is_unique = registry.lock().await.add_problem(problem.clone());
-            if !is_unique {
-                panic!("duplicate problem");
-            }
-            // wait for the work (engine) to be sent out (exhausted)
-            reschedule_receiver.next().await;
-            engine_sender.broadcast_engine(Arc::new(test_utils::OneWorkEngine::new(
-                problem.clone().into_work(midstate_count),
-            )));
-        }
-    }
+    // feed all chains at once so that a broken chain does not block the others
+    futures::future::join_all(
+        (0..router.chain_count())
+            .map(|chain| feed_chain(router.clone(), chain, midstate_count, T::JOB_TIMEOUT)),
+    )
+    .await;
 
     // wait for hw to finish computation
     let timeout_started = Instant::now();
     while timeout_started.elapsed() < T::JOB_TIMEOUT {
-        delay_for(Duration::from_secs(1)).await;
-
-        if registry.lock().await.check_everything_solved(false) {
+        if router.check_everything_solved() {
             break;
         }
+        delay_for(SOLVED_POLL_INTERVAL).await;
     }
 
-    // go through registry and check if everything was solved
-    let registry = registry.lock().await;
-    assert!(registry.check_everything_solved(true));
-    // });
+    // go through registries and check if everything was solved
+    let chains = router.take_results();
+    Report {
+        passed: !chains.is_empty() && chains.iter().all(|chain| chain.is_passed(problem_count)),
+        problem_count,
+        elapsed: started.elapsed().as_secs_f64(),
+        chains,
+    }
+}
+
+/// Run the test and check that all chains have solved all problems
+pub async fn run<T: hal::Backend>(backend_config: T::Config) {
+    run_with_output::<T>(backend_config, Output::Log).await
+}
+
+pub async fn run_with_output<T: hal::Backend>(backend_config: T::Config, output: Output) {
+    let report = verify::<T>(backend_config).await;
+    for chain in report.chains.iter() {
+        info!(
+            "chain {} ({}): solved {}/{} problems in {:.1}s",
+            chain.chain, chain.name, chain.solved, chain.assigned, chain.elapsed
+        );
+    }
+    if output == Output::Json {
+        println!("{}", report.to_json());
+    }
+    assert!(report.passed, "block mining test has failed");
 }
 
 #[test]
@@ -403,3 +652,217 @@ fn test_registry() {
     registry.add_solution(Solution::new(block1.clone(), 1));
     assert!(registry.check_everything_solved(false));
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error;
+    use crate::stats;
+    use crate::test_utils::TestSolution;
+
+    use bosminer_macros::WorkSolverNode;
+
+    use async_trait::async_trait;
+
+    use std::fmt;
+
+    /// Chain solving test blocks without any hashing because the valid nonce is known
+    #[derive(Debug, WorkSolverNode)]
+    struct TestChain {
+        #[member_work_solver_stats]
+        work_solver_stats: stats::BasicWorkSolver,
+        id: usize,
+        /// Midstate which is never solved to simulate broken hardware
+        broken_midstate: Option<usize>,
+        work_generator: StdMutex<Option<work::Generator>>,
+        solution_sender: work::SolutionSender,
+    }
+
+    impl TestChain {
+        async fn run(self: Arc<Self>) {
+            let mut work_generator = self
+                .work_generator
+                .lock()
+                .expect("BUG: cannot lock work generator")
+                .take()
+                .expect("BUG: missing work generator");
+
+            while let Some(work) = work_generator.generate().await {
+                let block = *work.job::<test_utils::TestBlock>();
+                let work = Arc::new(work);
+                for (midstate_idx, midstate) in work.midstates.iter().enumerate() {
+                    if midstate.version == block.version()
+                        && self.broken_midstate != Some(midstate_idx)
+                    {
+                        self.solution_sender.send(work::Solution::new(
+                            work.clone(),
+                            TestSolution::new(&block).with_midstate_idx(midstate_idx),
+                            None,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    #[async_trait]
+    impl node::WorkSolver for TestChain {
+        fn get_id(&self) -> Option<usize> {
+            Some(self.id)
+        }
+
+        async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
+            None
+        }
+    }
+
+    impl fmt::Display for TestChain {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Test chain {}", self.id)
+        }
+    }
+
+    #[derive(Debug, WorkSolverNode)]
+    struct TestBackend {
+        #[member_work_solver_stats]
+        work_solver_stats: stats::BasicWorkSolver,
+    }
+
+    #[async_trait]
+    impl node::WorkSolver for TestBackend {
+        async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
+            None
+        }
+    }
+
+    impl fmt::Display for TestBackend {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Test backend")
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestConfig {
+        /// Broken midstate of each chain
+        chains: Vec<Option<usize>>,
+    }
+
+    impl hal::BackendConfig for TestConfig {
+        fn midstate_count(&self) -> usize {
+            2
+        }
+    }
+
+    #[async_trait]
+    impl hal::Backend for TestBackend {
+        type Type = Self;
+        type Config = TestConfig;
+
+        const DEFAULT_HASHRATE_INTERVAL: Duration = Duration::from_secs(60);
+        const JOB_TIMEOUT: Duration = Duration::from_secs(1);
+
+        fn create(_config: &mut TestConfig) -> hal::WorkNode<Self> {
+            node::WorkSolverType::WorkHub(Box::new(|| Self {
+                work_solver_stats: Default::default(),
+            }))
+        }
+
+        async fn init_work_hub(
+            config: TestConfig,
+            work_hub: work::SolverBuilder<Self>,
+        ) -> error::Result<hal::FrontendConfig> {
+            for (id, broken_midstate) in config.chains.into_iter().enumerate() {
+                let chain = work_hub
+                    .create_work_solver(|work_generator, solution_sender| TestChain {
+                        work_solver_stats: Default::default(),
+                        id,
+                        broken_midstate,
+                        work_generator: StdMutex::new(Some(work_generator)),
+                        solution_sender,
+                    })
+                    .await;
+                tokio::spawn(chain.run());
+            }
+            Ok(hal::FrontendConfig {
+                cgminer_custom_commands: None,
+                api: Default::default(),
+            })
+        }
+
+        async fn init_work_solver(
+            _config: TestConfig,
+            _work_solver: Arc<Self>,
+        ) -> error::Result<hal::FrontendConfig> {
+            panic!("BUG: called `init_work_solver`");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_attribution() {
+        let report = verify::<TestBackend>(TestConfig {
+            chains: vec![None, Some(1), None],
+        })
+        .await;
+        let block_count = test_utils::TEST_BLOCKS.len();
+        let problem_count = 2 * block_count;
+
+        assert!(!report.passed);
+        assert_eq!(report.problem_count, problem_count);
+        assert_eq!(report.chains.len(), 3);
+        for (idx, chain) in report.chains.iter().enumerate() {
+            assert_eq!(chain.chain, idx);
+            assert_eq!(chain.id, Some(idx));
+            assert_eq!(chain.name, format!("Test chain {}", idx));
+            // each chain receives all problems
+            assert_eq!(chain.assigned, problem_count);
+        }
+        // healthy chains have solved everything
+        for chain in &[&report.chains[0], &report.chains[2]] {
+            assert_eq!(chain.solved, problem_count);
+            assert_eq!(chain.missing, vec![]);
+        }
+        // only the problems of the broken midstate are missing in the broken chain
+        let broken_chain = &report.chains[1];
+        assert_eq!(broken_chain.solved, block_count);
+        assert_eq!(broken_chain.missing.len(), block_count);
+        assert!(broken_chain
+            .missing
+            .iter()
+            .all(|problem| problem.midstate_idx == 1));
+    }
+
+    #[tokio::test]
+    async fn test_report_json() {
+        let report = verify::<TestBackend>(TestConfig {
+            chains: vec![None, Some(0)],
+        })
+        .await;
+        let block_count = test_utils::TEST_BLOCKS.len();
+        let json: serde_json::Value =
+            serde_json::from_str(&report.to_json()).expect("BUG: invalid report");
+
+        assert_eq!(json["passed"], serde_json::json!(false));
+        assert_eq!(json["problem_count"], serde_json::json!(2 * block_count));
+        assert!(json["elapsed"].is_f64());
+
+        let chain = &json["chains"][0];
+        assert_eq!(chain["chain"], serde_json::json!(0));
+        assert_eq!(chain["id"], serde_json::json!(0));
+        assert_eq!(chain["name"], serde_json::json!("Test chain 0"));
+        assert_eq!(chain["solved"], serde_json::json!(2 * block_count));
+        assert_eq!(chain["missing"], serde_json::json!([]));
+
+        let chain = &json["chains"][1];
+        assert_eq!(chain["assigned"], serde_json::json!(2 * block_count));
+        assert_eq!(chain["solved"], serde_json::json!(block_count));
+        assert!(chain["elapsed"].is_f64());
+        let missing = chain["missing"]
+            .as_array()
+            .expect("BUG: missing problems are not listed");
+        assert_eq!(missing.len(), block_count);
+        for problem in missing {
+            assert_eq!(problem["midstate_idx"], serde_json::json!(0));
+            assert_eq!(problem["block"].as_str().map(str::len), Some(64));
+        }
+    }
+}
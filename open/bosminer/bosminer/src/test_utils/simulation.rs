@@ -0,0 +1,1138 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Deterministic simulation of the whole core running against virtual clock (see `clock`). The
+//! simulated backend and mock pools are driven by the same clock so a day of mining with
+//! scheduling, vardiff and daily rollups takes just a few seconds of wall time.
+
+use crate::backend;
+use crate::client;
+use crate::clock;
+use crate::error;
+use crate::hal::{self, BackendConfig as _};
+use crate::hub;
+use crate::job;
+use crate::node::{self, WorkSolverStats as _};
+use crate::stats::{self, Mining as _};
+use crate::sync;
+use crate::test_utils::{TestBlock, TestSolution, TEST_BLOCKS};
+use crate::work;
+
+use bosminer_config::{
+    ClientDescriptor, ClientSharePolicy, ClientUserInfo, DigestConfig, GroupDescriptor,
+};
+use bosminer_macros::{ClientNode, WorkSolverNode};
+
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::lock::Mutex;
+use ii_async_compat::prelude::*;
+use ii_async_compat::select;
+use ii_cgminer_api::{command, response};
+use tokio::time::delay_for;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, Weak};
+use std::time;
+
+/// Job of the mock pool built from a test block so simulated work solvers know the valid nonce
+/// without any hashing
+#[derive(Debug)]
+pub struct Job {
+    pool: Weak<Pool>,
+    block: TestBlock,
+    target: ii_bitcoin::Target,
+}
+
+impl job::Bitcoin for Job {
+    fn origin(&self) -> Weak<dyn node::Client> {
+        self.pool.clone()
+    }
+
+    fn version(&self) -> u32 {
+        self.block.version
+    }
+
+    fn version_mask(&self) -> u32 {
+        ii_bitcoin::BIP320_VERSION_MASK
+    }
+
+    fn previous_hash(&self) -> &ii_bitcoin::DHash {
+        &self.block.previous_hash
+    }
+
+    fn merkle_root(&self) -> &ii_bitcoin::DHash {
+        &self.block.merkle_root
+    }
+
+    fn time(&self) -> u32 {
+        self.block.time
+    }
+
+    fn bits(&self) -> u32 {
+        self.block.bits
+    }
+
+    fn target(&self) -> ii_bitcoin::Target {
+        self.target
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+/// Work engine repeating the only assignment of the test block until it is terminated
+#[derive(Debug)]
+struct Engine {
+    work: work::Assignment,
+    terminated: AtomicBool,
+}
+
+impl work::Engine for Engine {
+    fn terminate(&self) {
+        self.terminated.store(true, Ordering::Relaxed);
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.terminated.load(Ordering::Relaxed)
+    }
+
+    fn next_work(&self) -> work::LoopState<work::Assignment> {
+        if self.is_exhausted() {
+            work::LoopState::Exhausted
+        } else {
+            work::LoopState::Continue(self.work.clone())
+        }
+    }
+}
+
+fn engine_generator() -> work::EngineGenerator {
+    Box::new(|job| {
+        let block = job
+            .downcast_ref::<Job>()
+            .expect("BUG: mock pool received foreign job")
+            .block;
+        let midstate = work::Midstate {
+            version: block.version,
+            state: block.midstate,
+        };
+        Arc::new(Engine {
+            work: work::Assignment::new(job, vec![midstate], block.time),
+            terminated: AtomicBool::new(false),
+        })
+    })
+}
+
+/// Mock pool accepting all shares and regulating their difficulty with simple vardiff
+#[derive(Debug, ClientNode)]
+pub struct Pool {
+    url: String,
+    #[member_status]
+    status: sync::StatusMonitor,
+    #[member_client_stats]
+    stats: stats::BasicClient,
+    difficulty: AtomicUsize,
+    /// The difficulty is regulated by vardiff (otherwise it is kept at the initial value)
+    vardiff: bool,
+    /// Offline pool cannot be connected and the established connection is lost
+    online: AtomicBool,
+    stop_sender: mpsc::Sender<()>,
+    stop_receiver: Mutex<mpsc::Receiver<()>>,
+    last_job: Mutex<Option<Arc<Job>>>,
+    job_sender: Mutex<job::Sender>,
+    solution_receiver: Mutex<job::SolutionReceiver>,
+}
+
+impl Pool {
+    const INITIAL_DIFFICULTY: usize = 512;
+    const JOB_INTERVAL: time::Duration = time::Duration::from_secs(30);
+    const VARDIFF_INTERVAL: time::Duration = time::Duration::from_secs(60);
+    /// Vardiff keeps the rate of shares within the half and double of this value
+    const SHARES_PER_MINUTE: f64 = 20.0;
+    /// Time after which connection attempt to offline pool fails
+    const CONNECTION_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+    pub fn new(url: String, solver: job::Solver, vardiff: bool) -> Self {
+        let (stop_sender, stop_receiver) = mpsc::channel(1);
+        Self {
+            url,
+            status: Default::default(),
+            stats: Default::default(),
+            difficulty: AtomicUsize::new(Self::INITIAL_DIFFICULTY),
+            vardiff,
+            online: AtomicBool::new(true),
+            stop_sender,
+            stop_receiver: Mutex::new(stop_receiver),
+            last_job: Mutex::new(None),
+            job_sender: Mutex::new(solver.job_sender),
+            solution_receiver: Mutex::new(solver.solution_receiver),
+        }
+    }
+
+    pub fn set_online(&self, online: bool) {
+        self.online.store(online, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn is_online(&self) -> bool {
+        self.online.load(Ordering::Relaxed)
+    }
+
+    fn target(&self) -> ii_bitcoin::Target {
+        ii_bitcoin::Target::from_pool_difficulty(self.difficulty.load(Ordering::Relaxed))
+    }
+
+    async fn send_job(self: &Arc<Self>, index: usize) {
+        let job = Arc::new(Job {
+            pool: Arc::downgrade(self),
+            block: TEST_BLOCKS[index % TEST_BLOCKS.len()],
+            target: self.target(),
+        });
+        self.last_job.lock().await.replace(job.clone());
+        self.job_sender.lock().await.send(job);
+    }
+
+    /// Double or halve the difficulty when the rate of shares is out of the expected range and
+    /// return `true` when it has been changed
+    fn retarget(&self, shares_per_minute: f64, now: time::Instant) -> bool {
+        let difficulty = self.difficulty.load(Ordering::Relaxed);
+        let new_difficulty = if shares_per_minute > 2.0 * Self::SHARES_PER_MINUTE {
+            difficulty * 2
+        } else if shares_per_minute < Self::SHARES_PER_MINUTE / 2.0 && difficulty > 1 {
+            difficulty / 2
+        } else {
+            return false;
+        };
+        self.difficulty.store(new_difficulty, Ordering::Relaxed);
+        self.stats.difficulty_history.record(
+            &self.target(),
+            stats::DifficultyTrigger::Vardiff,
+            now,
+        );
+        true
+    }
+
+    async fn main_loop(self: Arc<Self>) -> error::Result<()> {
+        let mut solution_receiver = self.solution_receiver.lock().await;
+        let mut job_interval = tokio::time::interval(Self::JOB_INTERVAL);
+
+        self.stats.difficulty_history.record(
+            &self.target(),
+            stats::DifficultyTrigger::Initial,
+            clock::now(),
+        );
+        let mut index = 0;
+        let mut shares = 0;
+        let mut last_retarget = clock::now();
+
+        while !self.status.is_shutting_down() {
+            select! {
+                _ = job_interval.tick().fuse() => {
+                    self.send_job(index).await;
+                    index += 1;
+                }
+                solution = solution_receiver.receive().fuse() => {
+                    match solution {
+                        Some(solution) => {
+                            for activity in &[
+                                stats::JobActivity::Submitted,
+                                stats::JobActivity::Accepted,
+                            ] {
+                                self.stats.job_history.account(solution.dyn_job(), *activity);
+                            }
+                            self.stats
+                                .accepted
+                                .account_solution(&solution.job_target(), clock::now())
+                                .await;
+                            shares += 1;
+                        }
+                        None => {
+                            Err("Standard application shutdown")?;
+                        }
+                    }
+                }
+            }
+
+            if !self.is_online() {
+                Err("Connection to pool has been lost")?;
+            }
+
+            let now = clock::now();
+            let elapsed = now.duration_since(last_retarget);
+            if self.vardiff && elapsed >= Self::VARDIFF_INTERVAL {
+                // the new difficulty is applied immediately with a new job
+                if self.retarget(shares as f64 * 60.0 / elapsed.as_secs_f64(), now) {
+                    self.send_job(index).await;
+                    index += 1;
+                }
+                shares = 0;
+                last_retarget = now;
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(self: Arc<Self>) {
+        if !self.is_online() {
+            delay_for(Self::CONNECTION_TIMEOUT).await;
+            self.status.initiate_failing();
+            return;
+        }
+        if self.status.initiate_running() {
+            if let Err(_) = self.clone().main_loop().await {
+                self.status.initiate_failing();
+            }
+        }
+    }
+
+    async fn main_task(self: Arc<Self>) {
+        loop {
+            let mut stop_receiver = self.stop_receiver.lock().await;
+            select! {
+                _ = self.clone().run().fuse() => {}
+                _ = stop_receiver.next() => {}
+            }
+
+            // Invalidate current job to stop working on it
+            self.job_sender.lock().await.invalidate();
+
+            if self.status.can_stop() {
+                // NOTE: it is not safe to add here any code!
+                break;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl node::Client for Pool {
+    fn start(self: Arc<Self>) {
+        tokio::spawn(self.clone().main_task());
+    }
+
+    fn stop(&self) {
+        if let Err(e) = self.stop_sender.clone().try_send(()) {
+            assert!(
+                e.is_full(),
+                "BUG: Unexpected error in stop sender: {}",
+                e.to_string()
+            );
+        }
+    }
+
+    async fn get_last_job(&self) -> Option<Arc<dyn job::Bitcoin>> {
+        self.last_job
+            .lock()
+            .await
+            .as_ref()
+            .map(|job| job.clone() as Arc<dyn job::Bitcoin>)
+    }
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+/// Simulated work solver which finds a share of the job difficulty in the time the real hardware
+/// with the same hashrate needs on average
+#[derive(Debug, WorkSolverNode)]
+pub struct WorkSolver {
+    #[member_work_solver_stats]
+    work_solver_stats: stats::BasicWorkSolver,
+    id: usize,
+    /// Hashrate in hashes per second
+    hashrate: StdMutex<f64>,
+    work_generator: StdMutex<Option<work::Generator>>,
+    solution_sender: work::SolutionSender,
+}
+
+impl WorkSolver {
+    /// Difficulty of solutions reported by the simulated chips. It bounds the rate of solutions
+    /// when vardiff of the pool drops to the minimum (e.g. when all shares are withheld by
+    /// quarantine) which would otherwise wake the work solver every few milliseconds of virtual
+    /// time.
+    const CHIP_DIFFICULTY: usize = 256;
+
+    fn new(
+        id: usize,
+        hashrate: f64,
+        work_generator: work::Generator,
+        solution_sender: work::SolutionSender,
+    ) -> Self {
+        Self {
+            work_solver_stats: Default::default(),
+            id,
+            hashrate: StdMutex::new(hashrate),
+            work_generator: StdMutex::new(Some(work_generator)),
+            solution_sender,
+        }
+    }
+
+    fn hashrate(&self) -> f64 {
+        *self.hashrate.lock().expect("BUG: cannot lock hashrate")
+    }
+
+    pub fn set_hashrate(&self, hashrate: f64) {
+        assert!(hashrate > 0.0, "BUG: simulated hashrate must be positive");
+        *self.hashrate.lock().expect("BUG: cannot lock hashrate") = hashrate;
+    }
+
+    /// Time in which the work solver reports a solution of the job with `target`. Like real
+    /// chips it reports only solutions meeting `CHIP_DIFFICULTY` so each of them is a share when
+    /// the job difficulty is lower.
+    fn solution_time(&self, target: &ii_bitcoin::Target) -> time::Duration {
+        let kilo_hashes = |target: &ii_bitcoin::Target| {
+            ii_bitcoin::Shares::new(target)
+                .into_kilo_hashes()
+                .into_f64()
+        };
+        let chip_target = ii_bitcoin::Target::from_pool_difficulty(Self::CHIP_DIFFICULTY);
+        let hashes = kilo_hashes(target).max(kilo_hashes(&chip_target)) * 1000.0;
+        time::Duration::from_secs_f64(hashes / self.hashrate())
+    }
+
+    async fn run(self: Arc<Self>) {
+        let mut work_generator = self
+            .work_generator
+            .lock()
+            .expect("BUG: cannot lock work generator")
+            .take()
+            .expect("BUG: missing work generator");
+
+        while let Some(work) = work_generator.generate().await {
+            work_generator.account_dispatched(&work).await;
+            let block = work.job::<Job>().block;
+            delay_for(self.solution_time(&work.job::<Job>().target)).await;
+            self.solution_sender.send(work::Solution::new(
+                work,
+                TestSolution::new(&block),
+                Some(clock::now()),
+            ));
+        }
+    }
+}
+
+#[async_trait]
+impl node::WorkSolver for WorkSolver {
+    fn get_id(&self) -> Option<usize> {
+        Some(self.id)
+    }
+
+    async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
+        Some(ii_bitcoin::HashesUnit::Hashes(self.hashrate() as u128))
+    }
+}
+
+impl fmt::Display for WorkSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Simulated work solver {}", self.id)
+    }
+}
+
+/// Backend specific CGMiner command reporting the number of simulated work solvers. It fails once
+/// its backend has been torn down by restart.
+pub const WORK_SOLVERS: &str = "simworksolvers";
+
+fn create_custom_commands(work_solvers: Vec<Arc<WorkSolver>>) -> command::Map {
+    let handler: command::ParameterLessHandler = Box::new(move || {
+        let work_solvers = work_solvers.clone();
+        Box::pin(async move {
+            if work_solvers
+                .iter()
+                .any(|work_solver| work_solver.work_solver_stats().removal().is_removed())
+            {
+                Err(response::ErrorCode::InvalidCommand.into())
+            } else {
+                Ok(response::AscCount {
+                    count: work_solvers.len() as i32,
+                }
+                .into())
+            }
+        })
+    });
+    let mut commands = command::Map::new();
+    commands.insert(
+        WORK_SOLVERS,
+        command::Descriptor::new(
+            WORK_SOLVERS,
+            command::HandlerType::ParameterLess(handler),
+            None,
+        ),
+    );
+    commands
+}
+
+/// Call the backend specific command `WORK_SOLVERS` and return whether it has succeeded
+async fn call_work_solvers_command(frontend_config: &hal::FrontendConfig) -> bool {
+    frontend_config
+        .cgminer_custom_commands
+        .as_ref()
+        .and_then(|commands| commands.get(WORK_SOLVERS))
+        .expect("BUG: missing simulated backend command")
+        .call(WORK_SOLVERS, None)
+        .await
+        .is_ok()
+}
+
+/// Root work hub of the simulated backend
+#[derive(Debug, WorkSolverNode)]
+pub struct Backend {
+    #[member_work_solver_stats]
+    work_solver_stats: stats::BasicWorkSolver,
+}
+
+#[async_trait]
+impl node::WorkSolver for Backend {
+    async fn get_nominal_hashrate(&self) -> Option<ii_bitcoin::HashesUnit> {
+        None
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Simulated backend")
+    }
+}
+
+#[async_trait]
+impl hal::Backend for Backend {
+    type Type = Self;
+    type Config = Config;
+
+    const DEFAULT_HASHRATE_INTERVAL: time::Duration = time::Duration::from_secs(60);
+    const JOB_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+    fn create(_config: &mut Config) -> hal::WorkNode<Self> {
+        node::WorkSolverType::WorkHub(Box::new(|| Self {
+            work_solver_stats: Default::default(),
+        }))
+    }
+
+    async fn init_work_hub(
+        config: Config,
+        work_hub: work::SolverBuilder<Self>,
+    ) -> error::Result<hal::FrontendConfig> {
+        let mut work_solvers = Vec::with_capacity(config.work_solvers.len());
+        for (id, hashrate) in config.work_solvers.iter().enumerate() {
+            let work_solver = work_hub
+                .create_work_solver(|work_generator, solution_sender| {
+                    WorkSolver::new(id, *hashrate, work_generator, solution_sender)
+                })
+                .await;
+            work_solvers.push(work_solver.clone());
+            tokio::spawn(work_solver.run());
+        }
+        let cgminer_custom_commands = create_custom_commands(work_solvers.clone());
+        // Work solvers of the previous backend are replaced when the core is restarted
+        *config
+            .created_work_solvers
+            .lock()
+            .expect("BUG: cannot lock work solvers") = work_solvers;
+
+        let client_manager = config.client_manager.expect("BUG: missing client manager");
+        let mut labels = config.labels;
+        let vardiff = config.vardiff;
+        let created_pools = config.created_pools;
+        for (group_idx, (descriptor, pool_count)) in config.groups.into_iter().enumerate() {
+            let group = client_manager.create_group(descriptor).await?;
+            for pool_idx in 0..pool_count {
+                let descriptor = ClientDescriptor::create(
+                    &format!("drain://pool-{}-{}", group_idx, pool_idx),
+                    &ClientUserInfo::new("simulation", None),
+                    true,
+                )
+                .expect("BUG: cannot create client descriptor")
+                .with_label(labels.remove(&(group_idx, pool_idx)))
+                // all simulated solutions are the same test block and they would be dropped as
+                // duplicates
+                .with_share_policy(ClientSharePolicy {
+                    duplicate_window: Some(0),
+                    ..Default::default()
+                });
+                let client_handle = client::Handle::with_node(
+                    descriptor,
+                    client_manager.services(),
+                    |descriptor, solver| {
+                        let pool = Arc::new(Pool::new(descriptor.get_full_url(), solver, vardiff));
+                        created_pools
+                            .lock()
+                            .expect("BUG: cannot lock pools")
+                            .insert((group_idx, pool_idx), pool.clone());
+                        pool
+                    },
+                );
+                let client_handle = group.push_client(client_handle).await;
+                let _ = client_handle.replace_engine_generator(engine_generator());
+            }
+        }
+
+        Ok(hal::FrontendConfig {
+            cgminer_custom_commands: Some(cgminer_custom_commands),
+            api: Default::default(),
+        })
+    }
+
+    async fn init_work_solver(
+        _config: Config,
+        _work_solver: Arc<Self>,
+    ) -> error::Result<hal::FrontendConfig> {
+        panic!("BUG: called `init_work_solver`");
+    }
+}
+
+/// Configuration of the simulated core
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Wall clock time when the simulation starts (it has to lie in the past)
+    pub epoch: time::SystemTime,
+    /// Hashrate of each simulated work solver in hashes per second
+    pub work_solvers: Vec<f64>,
+    /// Groups with the number of mock pools in each of them
+    pub groups: Vec<(GroupDescriptor, usize)>,
+    /// Labels of mock pools addressed by group and pool index
+    pub labels: BTreeMap<(usize, usize), String>,
+    pub digest: Option<DigestConfig>,
+    /// Mock pools regulate difficulty with vardiff. Each simulated work is solved with one share
+    /// so the generated work is proportional to the mining time only with fixed difficulty.
+    pub vardiff: bool,
+    client_manager: Option<client::Manager>,
+    created_work_solvers: Arc<StdMutex<Vec<Arc<WorkSolver>>>>,
+    created_pools: Arc<StdMutex<BTreeMap<(usize, usize), Arc<Pool>>>>,
+}
+
+impl Config {
+    /// 2020-01-01 00:00:00 UTC
+    pub const DEFAULT_EPOCH: u64 = 1_577_836_800;
+
+    /// Create simulation of one work solver with `hashrate` mining on one mock pool
+    pub fn new(hashrate: f64) -> Self {
+        Self {
+            epoch: time::UNIX_EPOCH + time::Duration::from_secs(Self::DEFAULT_EPOCH),
+            work_solvers: vec![hashrate],
+            groups: vec![(Default::default(), 1)],
+            labels: Default::default(),
+            digest: None,
+            vardiff: true,
+            client_manager: None,
+            created_work_solvers: Default::default(),
+            created_pools: Default::default(),
+        }
+    }
+}
+
+impl hal::BackendConfig for Config {
+    fn midstate_count(&self) -> usize {
+        1
+    }
+
+    fn set_client_manager(&mut self, client_manager: client::Manager) {
+        self.client_manager.replace(client_manager);
+    }
+
+    fn digest(&self) -> Option<DigestConfig> {
+        self.digest.clone()
+    }
+}
+
+/// Event injected into the simulation by the script
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Change hashrate of the work solver (e.g. to simulate failing hash chain)
+    SetHashrate {
+        work_solver: usize,
+        hashrate: f64,
+    },
+    Quarantine {
+        work_solver: usize,
+        enabled: bool,
+    },
+    EnablePool {
+        group: usize,
+        pool: usize,
+    },
+    DisablePool {
+        group: usize,
+        pool: usize,
+    },
+    /// Make the pool unreachable (e.g. to simulate network outage) or bring it back online
+    SetPoolOnline {
+        group: usize,
+        pool: usize,
+        online: bool,
+    },
+    /// Rename label of the pool at runtime
+    SetLabel {
+        group: usize,
+        pool: usize,
+        label: Option<String>,
+    },
+    /// Change quota of the group at runtime
+    SetQuota {
+        group: usize,
+        quota: usize,
+        horizon: time::Duration,
+    },
+    /// Shut down the core gracefully as if the miner received a termination signal
+    Shutdown {
+        grace_period: time::Duration,
+    },
+    /// Restart the backend with its original configuration (work solvers and pools are created
+    /// again)
+    Restart {
+        grace_period: time::Duration,
+    },
+    /// Record distribution of work among pools and work solvers for `duration`
+    StartAudit {
+        duration: time::Duration,
+    },
+    /// Do nothing, the event just marks the time when generated work of pools is captured
+    Checkpoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolReport {
+    pub url: String,
+    pub label: Option<String>,
+    pub generated_work: u64,
+    pub accepted: stats::MeterSnapshot,
+    pub difficulty_changes: Vec<stats::DifficultyChange>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkSolverReport {
+    pub name: String,
+    pub generated_work: u64,
+    pub quarantine: stats::QuarantineSnapshot,
+}
+
+/// Final statistics and event logs of the simulation
+#[derive(Debug)]
+pub struct Report {
+    /// Virtual wall clock time when the simulation has finished
+    pub end: time::SystemTime,
+    /// Statistics of all valid shares with job difficulty
+    pub valid_job_diff: stats::MeterSnapshot,
+    pub pools: Vec<PoolReport>,
+    pub work_solvers: Vec<WorkSolverReport>,
+    /// Events executed by the script with the virtual time of their execution
+    pub events: Vec<(time::SystemTime, Event)>,
+    /// Generated work of all pools captured right after execution of each event
+    pub event_generated_work: Vec<Vec<u64>>,
+    /// Whether the command `WORK_SOLVERS` of the current backend succeeded right after execution
+    /// of each event
+    pub event_backend_command: Vec<bool>,
+    /// Whether the command `WORK_SOLVERS` of the replaced backend succeeded after each restart
+    pub replaced_backend_command: Vec<bool>,
+    pub work_history: Vec<work::history::Record>,
+    /// Work distribution recorded by the last audit started by the script
+    pub work_audit: Option<work::audit::Report>,
+    /// Records of the daily digest in chronological order when it is enabled
+    pub digest: Vec<client::digest::DayRecord>,
+}
+
+/// Execute the `event` and return the frontend configuration of the new backend when the core
+/// has been restarted
+async fn apply_event(
+    core: &hub::Core,
+    work_solvers: &[Arc<WorkSolver>],
+    pools: &BTreeMap<(usize, usize), Arc<Pool>>,
+    event: &Event,
+) -> Option<hal::FrontendConfig> {
+    let get_group = |group: usize| async move {
+        core.get_client_manager()
+            .get_groups()
+            .await
+            .get(group)
+            .cloned()
+            .expect("BUG: simulation script refers to missing group")
+    };
+    let get_pool = |group: usize, pool: usize| async move {
+        get_group(group)
+            .await
+            .get_clients()
+            .await
+            .get(pool)
+            .cloned()
+            .expect("BUG: simulation script refers to missing pool")
+    };
+
+    match *event {
+        Event::SetHashrate {
+            work_solver,
+            hashrate,
+        } => work_solvers[work_solver].set_hashrate(hashrate),
+        Event::Quarantine {
+            work_solver,
+            enabled,
+        } => {
+            work_solvers[work_solver]
+                .work_solver_stats()
+                .quarantine()
+                .set_enabled(enabled);
+        }
+        Event::EnablePool { group, pool } => {
+            let descriptor = get_pool(group, pool).await.descriptor().await;
+            let _ = core
+                .get_client_manager()
+                .set_client_enabled(&descriptor, true)
+                .await;
+        }
+        Event::DisablePool { group, pool } => {
+            let descriptor = get_pool(group, pool).await.descriptor().await;
+            let _ = core
+                .get_client_manager()
+                .set_client_enabled(&descriptor, false)
+                .await;
+        }
+        Event::SetPoolOnline {
+            group,
+            pool,
+            online,
+        } => pools
+            .get(&(group, pool))
+            .expect("BUG: simulation script refers to missing pool")
+            .set_online(online),
+        Event::SetLabel {
+            group,
+            pool,
+            ref label,
+        } => get_pool(group, pool).await.set_label(label.clone()).await,
+        Event::SetQuota {
+            group,
+            quota,
+            horizon,
+        } => core
+            .get_client_manager()
+            .set_quota(&get_group(group).await, quota, horizon)
+            .await
+            .expect("BUG: simulation script sets invalid quota"),
+        Event::Shutdown { grace_period } => core.shutdown(grace_period).await,
+        Event::Restart { grace_period } => {
+            return Some(
+                core.restart(grace_period)
+                    .await
+                    .expect("BUG: cannot restart simulated backend"),
+            );
+        }
+        Event::StartAudit { duration } => core.start_work_audit(duration).await,
+        Event::Checkpoint => {}
+    }
+    None
+}
+
+async fn get_generated_work(core: &hub::Core) -> Vec<u64> {
+    let mut generated_work = Vec::new();
+    for group in core.get_client_manager().get_groups().await {
+        for client in group.get_clients().await {
+            generated_work.push(*client.stats().generated_work().take_snapshot());
+        }
+    }
+    generated_work
+}
+
+async fn take_report(
+    core: &hub::Core,
+    work_solvers: &[Arc<WorkSolver>],
+    events: Vec<(time::SystemTime, Event)>,
+    event_generated_work: Vec<Vec<u64>>,
+    event_backend_command: Vec<bool>,
+    replaced_backend_command: Vec<bool>,
+) -> Report {
+    let mut pools = Vec::new();
+    for group in core.get_client_manager().get_groups().await {
+        for client in group.get_clients().await {
+            let stats = client.stats();
+            let descriptor = client.descriptor().await;
+            pools.push(PoolReport {
+                url: descriptor.get_full_url(),
+                label: descriptor.label,
+                generated_work: *stats.generated_work().take_snapshot(),
+                accepted: stats.accepted().take_snapshot().await.clone(),
+                difficulty_changes: stats.difficulty_history().take_snapshot().clone(),
+            });
+        }
+    }
+
+    Report {
+        end: clock::system_time(),
+        valid_job_diff: core
+            .get_frontend()
+            .work_solver_stats()
+            .valid_job_diff()
+            .take_snapshot()
+            .await
+            .clone(),
+        pools,
+        work_solvers: work_solvers
+            .iter()
+            .map(|work_solver| {
+                let stats = work_solver.work_solver_stats();
+                WorkSolverReport {
+                    name: work_solver.to_string(),
+                    generated_work: *stats.generated_work().take_snapshot(),
+                    quarantine: stats.quarantine().take_snapshot().clone(),
+                }
+            })
+            .collect(),
+        events,
+        event_generated_work,
+        event_backend_command,
+        replaced_backend_command,
+        work_history: core.get_work_history().records(),
+        work_audit: core.get_work_audit(),
+        digest: core
+            .get_digest_store()
+            .map(|store| {
+                let mut records = store.last_days(usize::max_value());
+                records.reverse();
+                records
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Run the core configured by `core_config` for the virtual `duration` and execute events from
+/// the `event_script` at given offsets from the start. The simulation runs on its own runtime
+/// and returns the final statistics and event logs.
+pub fn simulate(
+    core_config: Config,
+    duration: time::Duration,
+    mut event_script: Vec<(time::Duration, Event)>,
+) -> Report {
+    let mut runtime = tokio::runtime::Builder::new()
+        .basic_scheduler()
+        .enable_all()
+        .build()
+        .expect("BUG: cannot build simulation runtime");
+
+    runtime.block_on(async move {
+        let _simulation = clock::enter_simulation(core_config.epoch).await;
+        let start = clock::now();
+
+        let backend_registry = Arc::new(backend::Registry::new());
+        let core = Arc::new(hub::Core::new(
+            core_config.midstate_count(),
+            core_config.network(),
+            &backend_registry,
+            None,
+            core_config.digest(),
+            core_config.work_history(),
+            core_config.fan_out(),
+        ));
+        let created_work_solvers = core_config.created_work_solvers.clone();
+        let created_pools = core_config.created_pools.clone();
+        let get_work_solvers = || {
+            created_work_solvers
+                .lock()
+                .expect("BUG: cannot lock work solvers")
+                .clone()
+        };
+        let get_pools = || {
+            created_pools
+                .lock()
+                .expect("BUG: cannot lock pools")
+                .clone()
+        };
+        let mut frontend_config = core
+            .build_backend::<Backend>(core_config)
+            .await
+            .expect("BUG: cannot build simulated backend");
+        let mut work_solvers = get_work_solvers();
+        let mut pools = get_pools();
+        tokio::spawn(core.clone().run());
+
+        event_script.sort_by_key(|(offset, _)| *offset);
+        let mut events = Vec::with_capacity(event_script.len());
+        let mut event_generated_work = Vec::with_capacity(event_script.len());
+        let mut event_backend_command = Vec::with_capacity(event_script.len());
+        let mut replaced_backend_command = Vec::new();
+        for (offset, event) in event_script
+            .into_iter()
+            .take_while(|(offset, _)| *offset < duration)
+        {
+            let elapsed = clock::now().duration_since(start);
+            delay_for(offset.checked_sub(elapsed).unwrap_or_default()).await;
+            if let Some(new_frontend_config) =
+                apply_event(&core, &work_solvers, &pools, &event).await
+            {
+                work_solvers = get_work_solvers();
+                pools = get_pools();
+                replaced_backend_command.push(call_work_solvers_command(&frontend_config).await);
+                frontend_config = new_frontend_config;
+            }
+            events.push((clock::system_time(), event));
+            event_generated_work.push(get_generated_work(&core).await);
+            event_backend_command.push(call_work_solvers_command(&frontend_config).await);
+        }
+        let elapsed = clock::now().duration_since(start);
+        delay_for(duration.checked_sub(elapsed).unwrap_or_default()).await;
+
+        take_report(
+            &core,
+            &work_solvers,
+            events,
+            event_generated_work,
+            event_backend_command,
+            replaced_backend_command,
+        )
+        .await
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shutdown;
+
+    const HOUR: time::Duration = time::Duration::from_secs(3600);
+    /// Upper bound of wall time of a simulated day. It takes about 20 s in debug build, the margin
+    /// covers tests running in parallel.
+    const SOAK_WALL_TIME: time::Duration = time::Duration::from_secs(120);
+
+    /// Vardiff settles on the difficulty matching the hashrate and it drops to the minimum when
+    /// the only work solver is quarantined because the pool stops receiving shares
+    #[test]
+    fn test_soak_vardiff_quarantine() {
+        let config = Config::new(2e12);
+        let epoch = config.epoch;
+        let started = time::Instant::now();
+        let report = simulate(
+            config,
+            24 * HOUR,
+            vec![(
+                12 * HOUR,
+                Event::Quarantine {
+                    work_solver: 0,
+                    enabled: true,
+                },
+            )],
+        );
+        // the day is simulated in virtual time
+        let wall_time = started.elapsed();
+        assert!(
+            wall_time < SOAK_WALL_TIME,
+            "simulation took {:?} of wall time",
+            wall_time
+        );
+
+        assert_eq!(report.events.len(), 1);
+        assert!(report.events[0].0 >= epoch + 12 * HOUR);
+        assert!(report.end >= epoch + 24 * HOUR);
+
+        let pool = &report.pools[0];
+        let difficulties: Vec<_> = pool
+            .difficulty_changes
+            .iter()
+            .map(|change| change.new_difficulty)
+            .collect();
+        // the initial difficulty is doubled once and then halved down to the minimum
+        assert_eq!(
+            difficulties,
+            vec![512, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2, 1]
+        );
+        // ~27 shares per minute with difficulty 1024 until the quarantine
+        let accepted = pool.accepted.solutions as f64 / (12.0 * 60.0);
+        assert!(
+            accepted > 20.0 && accepted < 35.0,
+            "accepted {}/min",
+            accepted
+        );
+
+        let quarantine = &report.work_solvers[0].quarantine;
+        assert!(quarantine.enabled);
+        assert!(quarantine.valid > 0);
+        assert_eq!(quarantine.invalid, 0);
+        assert!(!report.work_history.is_empty());
+    }
+
+    /// No work is generated after the shutdown and it takes only a moment when there are no
+    /// solutions to wait for
+    #[test]
+    fn test_shutdown() {
+        let config = Config::new(2e12);
+        let epoch = config.epoch;
+        let report = simulate(
+            config,
+            2 * HOUR,
+            vec![
+                (
+                    HOUR,
+                    Event::Shutdown {
+                        grace_period: shutdown::DEFAULT_GRACE_PERIOD,
+                    },
+                ),
+                (HOUR + HOUR / 2, Event::Checkpoint),
+            ],
+        );
+
+        assert_eq!(report.events.len(), 2);
+        // the shutdown is bounded by the grace period
+        assert!(report.events[0].0 <= epoch + HOUR + shutdown::DEFAULT_GRACE_PERIOD);
+        assert!(report.event_generated_work[0][0] > 0);
+        assert_eq!(
+            report.event_generated_work[0],
+            report.event_generated_work[1]
+        );
+        assert!(report
+            .work_history
+            .iter()
+            .any(|record| record.event == work::history::Event::Invalidate));
+    }
+
+    /// Work flows again after each restart, the restarted backend replaces the previous work
+    /// solvers and pools instead of adding new ones and only its custom commands can be used
+    #[test]
+    fn test_restart() {
+        let config = Config::new(2e12);
+        let restart = || Event::Restart {
+            grace_period: shutdown::DEFAULT_GRACE_PERIOD,
+        };
+        let report = simulate(
+            config,
+            3 * HOUR,
+            vec![
+                (HOUR, restart()),
+                (HOUR + HOUR / 2, Event::Checkpoint),
+                (2 * HOUR, restart()),
+                (2 * HOUR + HOUR / 2, Event::Checkpoint),
+            ],
+        );
+
+        assert_eq!(report.events.len(), 4);
+        assert_eq!(report.pools.len(), 1);
+        assert_eq!(report.work_solvers.len(), 1);
+        for generated_work in report.event_generated_work.chunks(2) {
+            // the pool is registered again on restart and it gets work of the new backend
+            assert_eq!(generated_work[0].len(), 1);
+            assert!(generated_work[1][0] > generated_work[0][0]);
+        }
+        assert!(report.pools[0].generated_work > 0);
+        assert!(report.work_solvers[0].generated_work > 0);
+        assert_eq!(report.event_backend_command, vec![true; 4]);
+        assert_eq!(report.replaced_backend_command, vec![false; 2]);
+    }
+}
@@ -23,16 +23,25 @@
 //! Basic components for building WorkEngine broadcasting infrastructure and to send WorkEngines
 //! to the actual work solving (mining) backends
 
+pub mod audit;
 pub mod engine;
+// the ring buffer is mapped to memory shared with other processes with `mmap`
+#[cfg(unix)]
+pub mod export;
+mod filter;
+pub mod history;
 mod solver;
 
+use crate::clock;
 use crate::hal;
 use crate::job;
 use crate::node;
 
+use bosminer_config::FanOutConfig;
 use ii_bitcoin::HashTrait as _;
 
-pub use solver::{Generator, SolutionSender, SolverBuilder};
+pub use filter::SolutionFilter;
+pub use solver::{set_paused, set_removed, EngineRouter, Generator, SolutionSender, SolverBuilder};
 
 use ii_async_compat::prelude::*;
 use tokio::sync::watch;
@@ -42,6 +51,7 @@ use once_cell::sync::OnceCell;
 use std::fmt::{self, Debug};
 use std::iter;
 use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard, Weak};
 use std::time;
 
@@ -95,30 +105,154 @@ pub struct Assignment {
     pub midstates: Vec<Midstate>,
     /// nTime value for current work
     pub ntime: u32,
+    /// Bits of the version which may differ between the midstates and the job
+    version_mask: u32,
+    /// Job target captured when the work has been generated. All solutions of the work are
+    /// classified against it regardless of any later difficulty change.
+    job_target: ii_bitcoin::Target,
+    /// Sequence number of the job broadcast the work has been generated from
+    generation: Option<u64>,
+    /// Sequence number of the work among all assignments of its generator (starting with 1).
+    /// Work which has not been generated by any generator has zero sequence number.
+    sequence: u64,
+    /// Number of invalidations of the engine channel before the work has been generated
+    invalidation: u64,
+    /// First nonce scanned by hardware with programmable start of the nonce range. The whole
+    /// nonce space is still scanned by wrapping around from the last nonce to zero.
+    nonce_start: u32,
+    /// Time when the work has been generated
+    time: time::Instant,
+    /// Time when the work has been dispatched to the hardware (it is set only once by the work
+    /// generator when the backend accounts the work as dispatched)
+    dispatch_time: OnceCell<time::Instant>,
 }
 
 impl Assignment {
     pub fn new(job: Arc<dyn job::Bitcoin>, midstates: Vec<Midstate>, ntime: u32) -> Self {
         Self {
             path: vec![],
+            version_mask: job.version_mask(),
+            job_target: job.target(),
             job,
             midstates,
             ntime,
+            generation: None,
+            sequence: 0,
+            invalidation: 0,
+            nonce_start: 0,
+            time: clock::now(),
+            dispatch_time: OnceCell::new(),
         }
     }
 
+    /// Set the first nonce scanned by the hardware
+    pub fn with_nonce_start(mut self, nonce_start: u32) -> Self {
+        self.nonce_start = nonce_start;
+        self
+    }
+
+    /// Set the version bits actually rolled in the midstates which are narrower than the mask of
+    /// the job
+    pub fn with_version_mask(mut self, version_mask: u32) -> Self {
+        self.version_mask = version_mask & self.job.version_mask();
+        self
+    }
+
+    /// Version bits used for the work at the time it has been generated
+    #[inline]
+    pub fn version_mask(&self) -> u32 {
+        self.version_mask
+    }
+
+    /// Check that all midstates differ from the job version only in the bits of the version
+    /// mask
+    pub fn is_version_in_mask(&self) -> bool {
+        let job_version = self.job.version() & !self.version_mask;
+        self.midstates
+            .iter()
+            .all(|midstate| midstate.version & !self.version_mask == job_version)
+    }
+
     /// Return origin from which the work has been generated
     #[inline]
     pub fn origin(&self) -> Weak<dyn node::Client> {
         self.job.origin()
     }
 
+    pub fn job<T: job::Bitcoin>(&self) -> &T {
+        self.job
+            .downcast_ref::<T>()
+            .expect("cannot downcast to original job")
+    }
+
+    /// Return the original job without downcasting it to protocol specific type
+    #[inline]
+    pub fn dyn_job(&self) -> &Arc<dyn job::Bitcoin> {
+        &self.job
+    }
+
     /// Return merkle root tail
     #[inline]
     pub fn merkle_root_tail(&self) -> u32 {
         self.job.merkle_root_tail()
     }
 
+    /// Return sequence number of the job broadcast when it is known
+    #[inline]
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Return sequence number of the work among all assignments of its generator
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Return target of the job captured when the work has been generated
+    #[inline]
+    pub fn job_target(&self) -> &ii_bitcoin::Target {
+        &self.job_target
+    }
+
+    /// Return number of invalidations of the engine channel seen when the work has been
+    /// generated
+    #[inline]
+    pub fn invalidation(&self) -> u64 {
+        self.invalidation
+    }
+
+    /// Return the first nonce scanned by the hardware (it is zero unless the work solver offsets
+    /// its nonce scan)
+    #[inline]
+    pub fn nonce_start(&self) -> u32 {
+        self.nonce_start
+    }
+
+    /// Return time when the work has been generated
+    #[inline]
+    pub fn time(&self) -> time::Instant {
+        self.time
+    }
+
+    /// Return time when the work has been dispatched to the hardware if it has been already
+    #[inline]
+    pub fn dispatch_time(&self) -> Option<time::Instant> {
+        self.dispatch_time.get().cloned()
+    }
+
+    /// Record dispatch of the work to the hardware (only the first dispatch is kept)
+    pub(crate) fn set_dispatch_time(&self, time: time::Instant) {
+        let _ = self.dispatch_time.set(time);
+    }
+
+    /// Check if the work has been generated at most `max_age` ago and it is still worth
+    /// submitting it to the hardware
+    #[inline]
+    pub fn is_fresh(&self, max_age: time::Duration) -> bool {
+        clock::now().duration_since(self.time) <= max_age
+    }
+
     /// Return current target (nBits)
     #[inline]
     pub fn bits(&self) -> u32 {
@@ -134,49 +268,104 @@ impl Assignment {
 
 /// Container with mining work and a corresponding solution received at a particular time
 /// This data structure is used when posting work+solution pairs for further submission upstream.
+/// Cloning is cheap because the work and the backend solution are shared by all clones. Lazy
+/// evaluated values are copied only when they have been already evaluated at the time of cloning
+/// otherwise each clone evaluates them on its own (with the same result).
 #[derive(Clone)]
 pub struct Solution {
-    /// Time stamp when it has been fetched from the solution FIFO
+    /// Time stamp when the solution has been found (corrected by age hint of the backend)
     timestamp: time::Instant,
+    /// Time stamp when it has been fetched from the solution FIFO
+    raw_timestamp: time::Instant,
     /// Original mining work associated with this solution
-    work: Assignment,
+    work: Arc<Assignment>,
     /// Solution of the PoW puzzle
     solution: Arc<dyn hal::BackendSolution>,
     /// Lazy evaluated double hash of this solution
     hash: OnceCell<ii_bitcoin::DHash>,
-    /// Lazy evaluated job target to ensure that the value is stable for this solution
-    job_target: OnceCell<ii_bitcoin::Target>,
     /// Lazy evaluated backend target to ensure that the value is stable for this solution
     backend_target: OnceCell<ii_bitcoin::Target>,
+    /// The solution is delivered to another client than the one its job comes from
+    rerouted: bool,
 }
 
 impl Solution {
-    pub fn new(
-        work: Assignment,
+    /// The `work` can be passed already shared to avoid copying of the whole assignment when
+    /// there are more solutions for the same work
+    pub fn new<T: Into<Arc<Assignment>>>(
+        work: T,
         solution: impl hal::BackendSolution + 'static,
         timestamp: Option<time::Instant>,
     ) -> Self {
+        let raw_timestamp = timestamp.unwrap_or_else(|| clock::now());
         Self {
-            timestamp: timestamp.unwrap_or_else(|| time::Instant::now()),
-            work,
+            timestamp: solution
+                .age_hint()
+                .and_then(|age| raw_timestamp.checked_sub(age))
+                .unwrap_or(raw_timestamp),
+            raw_timestamp,
+            work: work.into(),
             solution: Arc::new(solution),
             hash: OnceCell::new(),
             backend_target: OnceCell::new(),
-            job_target: OnceCell::new(),
+            rerouted: false,
         }
     }
 
+    /// Mark the solution delivered to another client because its origin has been removed
+    pub fn reroute(mut self) -> Self {
+        self.rerouted = true;
+        self
+    }
+
+    /// Rejection of rerouted solution by the remote server says nothing about the client quality
+    #[inline]
+    pub fn is_rerouted(&self) -> bool {
+        self.rerouted
+    }
+
     /// Return origin from which the work has been generated
     #[inline]
     pub fn origin(&self) -> Weak<dyn node::Client> {
         self.work.job.origin()
     }
 
+    /// Time when the solution has been found which should be used by all statistics
     #[inline]
     pub fn timestamp(&self) -> time::Instant {
         self.timestamp
     }
 
+    /// Time when the solution has been fetched from the backend
+    #[inline]
+    pub fn raw_timestamp(&self) -> time::Instant {
+        self.raw_timestamp
+    }
+
+    /// Time the solution has spent in hardware buffers according to the backend
+    #[inline]
+    pub fn age(&self) -> time::Duration {
+        self.raw_timestamp - self.timestamp
+    }
+
+    /// Time since the work has been dispatched until the solution has been found as measured by
+    /// the backend itself
+    #[inline]
+    pub fn hw_timestamp(&self) -> Option<time::Duration> {
+        self.solution.hw_timestamp()
+    }
+
+    /// Time since the work has been dispatched to the hardware until the solution has been
+    /// found (corrected by age hint of the backend). It is unknown for work which has not been
+    /// accounted as dispatched.
+    pub fn latency(&self) -> Option<time::Duration> {
+        self.work.dispatch_time().map(|dispatch_time| {
+            self.timestamp
+                .checked_duration_since(dispatch_time)
+                .unwrap_or_default()
+        })
+    }
+
     pub fn job<T: job::Bitcoin>(&self) -> &T {
         self.work
             .job
@@ -184,6 +373,22 @@ impl Solution {
             .expect("cannot downcast to original job")
     }
 
+    #[inline]
+    pub fn dyn_job(&self) -> &Arc<dyn job::Bitcoin> {
+        self.work.dyn_job()
+    }
+
+    #[inline]
+    pub fn generation(&self) -> Option<u64> {
+        self.work.generation()
+    }
+
+    /// Sequence number of the assignment the solution has been found for
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.work.sequence()
+    }
+
     #[inline]
     pub fn nonce(&self) -> u32 {
         self.solution.nonce()
@@ -194,6 +399,8 @@ impl Solution {
         self.work.ntime
     }
 
+    /// Version of the midstate the solution has been found for. It is always taken from the
+    /// assignment so the version is submitted exactly as it has been mined.
     #[inline]
     pub fn version(&self) -> u32 {
         let i = self.midstate_idx();
@@ -201,15 +408,18 @@ impl Solution {
     }
 
     #[inline]
-    pub fn network_target(&self) -> ii_bitcoin::Target {
+    pub fn network_target(&self, network: ii_bitcoin::Network) -> ii_bitcoin::Target {
         // NOTE: it is expected that job has been checked in client and is correct
-        ii_bitcoin::Target::from_compact(self.work.job.bits())
+        network
+            .target_from_compact(self.work.job.bits())
             .expect("BUG: job has incorrect nbits")
     }
 
+    /// Target of the job snapshotted in the assignment so that the classification of the
+    /// solution does not depend on the time it is evaluated
     #[inline]
     pub fn job_target(&self) -> &ii_bitcoin::Target {
-        self.job_target.get_or_init(|| self.work.job.target())
+        self.work.job_target()
     }
 
     #[inline]
@@ -222,6 +432,22 @@ impl Solution {
         self.solution.midstate_idx()
     }
 
+    #[inline]
+    pub fn solution_idx(&self) -> usize {
+        self.solution.solution_idx()
+    }
+
+    /// Check whether indices reported by backend refer to something that does not exist in the
+    /// work assignment. Such solution cannot be processed because it would access out of range
+    /// midstate.
+    pub fn is_malformed(&self) -> bool {
+        self.midstate_idx() >= self.work.midstates.len()
+            || self
+                .solution
+                .solution_count()
+                .map_or(false, |count| self.solution_idx() >= count)
+    }
+
     /// Return double hash of this solution
     #[inline]
     pub fn hash(&self) -> &ii_bitcoin::DHash {
@@ -284,6 +510,19 @@ pub trait Engine: Debug + Send + Sync {
     fn is_exhausted(&self) -> bool;
 
     fn next_work(&self) -> LoopState<Assignment>;
+
+    /// Return the job the engine generates work from (if any)
+    fn job(&self) -> Option<Arc<dyn job::Bitcoin>> {
+        None
+    }
+
+    /// Divide the remaining space of the engine to `count` engines with disjoint sub-ranges
+    /// which can be drawn independently. The forked engine does not generate any more work but
+    /// it is exhausted only after all its forks are exhausted. An empty vector is returned when
+    /// the engine cannot be partitioned.
+    fn fork(&self, _count: usize) -> Vec<DynEngine> {
+        vec![]
+    }
 }
 
 /// Shared work engine type
@@ -291,8 +530,8 @@ pub type DynEngine = Arc<dyn Engine>;
 
 /// Interface required by `EngineReceiver` used for notification of exhausted work
 pub trait ExhaustedHandler: Debug + Send + Sync + 'static {
-    /// Called when all work is exhausted in given work engine
-    fn handle_exhausted(&self, _engine: DynEngine) {}
+    /// Called when all work is exhausted in given work engine generated from the `job`
+    fn handle_exhausted(&self, _engine: DynEngine, _job: Option<Arc<dyn job::Bitcoin>>) {}
 }
 
 /// Helper structure for ignoring all events provided by work module
@@ -301,20 +540,344 @@ pub struct IgnoreEvents;
 
 impl ExhaustedHandler for IgnoreEvents {}
 
+/// Sequence number of the last job broadcast shared by all engine senders
+static JOB_BROADCAST_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Marker of a job passed to `EngineSender::broadcast_job` which flows together with the work
+/// engine generated from the job to all work generators. It is used for measuring the time it
+/// takes to deliver the first assignment from the job to each work solver.
+#[derive(Debug)]
+pub struct JobBroadcast {
+    /// Unique sequence number of the broadcast
+    pub generation: u64,
+    /// The time the job has been passed to the engine sender
+    pub time: time::Instant,
+    /// Latency of the work solver which has delivered its first assignment as the first one
+    first_latency: OnceCell<time::Duration>,
+}
+
+impl JobBroadcast {
+    fn new(time: time::Instant) -> Self {
+        Self {
+            generation: JOB_BROADCAST_GENERATION.fetch_add(1, Ordering::Relaxed) + 1,
+            time,
+            first_latency: OnceCell::new(),
+        }
+    }
+
+    /// Account the first assignment delivered from the job by one work solver at `time`.
+    /// Returns the latency and the lag behind the fastest work solver.
+    pub fn account_delivery(&self, time: time::Instant) -> (time::Duration, time::Duration) {
+        let latency = time.checked_duration_since(self.time).unwrap_or_default();
+        let first_latency = *self.first_latency.get_or_init(|| latency);
+        (
+            latency,
+            latency.checked_sub(first_latency).unwrap_or_default(),
+        )
+    }
+}
+
+/// Value distributed by the engine broadcasting channel
+#[derive(Debug, Clone)]
+pub struct EngineBroadcast {
+    pub engine: DynEngine,
+    /// The job the engine has been generated from when it is suitable for measuring latency
+    pub job: Option<Arc<JobBroadcast>>,
+}
+
+impl EngineBroadcast {
+    fn new(engine: DynEngine, job: Option<Arc<JobBroadcast>>) -> Self {
+        Self { engine, job }
+    }
+}
+
+/// Staggered wake of work generators sharing one engine receiver when a new job is broadcast.
+/// Generators are ordered by their weight and split to batches of `batch_size`. Each batch starts
+/// drawing work from the new engine `spacing` later than the previous one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FanOut {
+    pub batch_size: usize,
+    pub spacing: time::Duration,
+}
+
+impl FanOut {
+    pub fn new(batch_size: usize, spacing: time::Duration) -> Self {
+        assert!(batch_size > 0, "BUG: fan-out batch size cannot be zero");
+        Self {
+            batch_size,
+            spacing,
+        }
+    }
+
+    /// Delay of the generator at position `rank` in the order of all generators
+    fn delay(&self, rank: usize) -> time::Duration {
+        self.spacing * (rank / self.batch_size) as u32
+    }
+}
+
+impl From<&FanOutConfig> for FanOut {
+    fn from(config: &FanOutConfig) -> Self {
+        Self::new(
+            config.batch_size(),
+            time::Duration::from_micros(config.spacing()),
+        )
+    }
+}
+
+/// Position of one work generator in the fan-out order
+#[derive(Debug, Default)]
+pub(crate) struct FanOutSlot {
+    /// Generators with higher weight are woken sooner
+    weight: AtomicU64,
+}
+
+impl FanOutSlot {
+    pub fn set_weight(&self, weight: u64) {
+        self.weight.store(weight, Ordering::Relaxed);
+    }
+
+    fn weight(&self) -> u64 {
+        self.weight.load(Ordering::Relaxed)
+    }
+}
+
+/// All work generators registered to one engine channel. Generators with the same weight are
+/// woken in order of their registration.
+#[derive(Debug, Default)]
+struct FanOutRegistry {
+    /// No stagger is used by default
+    fan_out: Option<FanOut>,
+    slots: Vec<Weak<FanOutSlot>>,
+}
+
+impl FanOutRegistry {
+    fn register(&mut self) -> Arc<FanOutSlot> {
+        let slot = Arc::new(FanOutSlot::default());
+        // drop slots of destroyed generators
+        self.slots.retain(|slot| slot.strong_count() > 0);
+        self.slots.push(Arc::downgrade(&slot));
+        slot
+    }
+
+    fn delay(&self, slot: &Arc<FanOutSlot>) -> Option<time::Duration> {
+        let fan_out = self.fan_out?;
+        let weight = slot.weight();
+        let mut rank = 0;
+        let mut ahead = true;
+        for other in self.slots.iter().filter_map(Weak::upgrade) {
+            if Arc::ptr_eq(&other, slot) {
+                // generators with the same weight registered later are behind us
+                ahead = false;
+            } else if other.weight() > weight || (ahead && other.weight() == weight) {
+                rank += 1;
+            }
+        }
+        Some(fan_out.delay(rank)).filter(|delay| *delay > time::Duration::from_secs(0))
+    }
+}
+
+/// Membership of one work generator in the group drawing work from partitioned engines
+#[derive(Debug, Default)]
+pub(crate) struct PartitionSlot;
+
+/// Remaining space of one engine divided among the generators which have been registered to
+/// the partition group when the engine has been forked
+#[derive(Debug)]
+struct Partition {
+    engine: Weak<dyn Engine>,
+    slots: Vec<Weak<PartitionSlot>>,
+    shards: Vec<DynEngine>,
+    /// Number of shards which have not returned their last work yet
+    remaining: AtomicUsize,
+}
+
+impl Partition {
+    fn new(
+        engine: Weak<dyn Engine>,
+        slots: Vec<Weak<PartitionSlot>>,
+        shards: Vec<DynEngine>,
+    ) -> Self {
+        let remaining = shards.iter().filter(|shard| !shard.is_exhausted()).count();
+        Self {
+            engine,
+            slots,
+            shards,
+            remaining: AtomicUsize::new(remaining),
+        }
+    }
+}
+
+/// View of a partitioned engine for one generator. The generator draws work from its own shard
+/// without any contention and it continues with the shards of other generators once its shard
+/// is exhausted.
+#[derive(Debug, Clone)]
+pub(crate) struct Shard {
+    partition: Arc<Partition>,
+    rank: usize,
+}
+
+impl Shard {
+    /// Same as `Engine::next_work` of the forked engine. Only the last work of all shards is
+    /// returned as `LoopState::Break`.
+    pub fn next_work(&self) -> LoopState<Assignment> {
+        let shards = &self.partition.shards;
+        for i in 0..shards.len() {
+            match shards[(self.rank + i) % shards.len()].next_work() {
+                LoopState::Exhausted => continue,
+                LoopState::Continue(work) => return LoopState::Continue(work),
+                LoopState::Break(work) => {
+                    return if self.partition.remaining.fetch_sub(1, Ordering::Relaxed) == 1 {
+                        LoopState::Break(work)
+                    } else {
+                        LoopState::Continue(work)
+                    };
+                }
+            }
+        }
+        LoopState::Exhausted
+    }
+}
+
+/// Partitioned engines of one engine channel
+#[derive(Debug, Default)]
+struct PartitionRegistry {
+    slots: Vec<Weak<PartitionSlot>>,
+    partitions: Vec<Arc<Partition>>,
+}
+
+impl PartitionRegistry {
+    fn register(&mut self) -> Arc<PartitionSlot> {
+        let slot = Arc::new(PartitionSlot);
+        // drop slots of destroyed generators
+        self.slots.retain(|slot| slot.strong_count() > 0);
+        self.slots.push(Arc::downgrade(&slot));
+        slot
+    }
+
+    /// Find the shard of the `engine` for the generator with the `slot`. The engine is forked
+    /// when it is not partitioned yet and there are at least two generators in the group.
+    /// Generators outside of the group only join an existing partition.
+    fn shard(&mut self, engine: &DynEngine, slot: Option<&Arc<PartitionSlot>>) -> Option<Shard> {
+        let weak_engine = Arc::downgrade(engine);
+        // forget partitions of destroyed engines
+        self.partitions
+            .retain(|partition| partition.engine.strong_count() > 0);
+        let partition = match self
+            .partitions
+            .iter()
+            .find(|partition| partition.engine.ptr_eq(&weak_engine))
+        {
+            Some(partition) => partition.clone(),
+            None => {
+                if slot.is_none() {
+                    return None;
+                }
+                self.slots.retain(|slot| slot.strong_count() > 0);
+                if self.slots.len() < 2 {
+                    return None;
+                }
+                let shards = engine.fork(self.slots.len());
+                if shards.is_empty() {
+                    return None;
+                }
+                let partition = Arc::new(Partition::new(weak_engine, self.slots.clone(), shards));
+                self.partitions.push(partition.clone());
+                partition
+            }
+        };
+        // generators registered after the fork start with the first shard
+        let rank = slot
+            .and_then(|slot| {
+                partition.slots.iter().position(|other| {
+                    other
+                        .upgrade()
+                        .map_or(false, |other| Arc::ptr_eq(&other, slot))
+                })
+            })
+            .unwrap_or(0);
+        Some(Shard { partition, rank })
+    }
+}
+
 /// Builds a WorkEngine broadcasting channel. The broadcast channel requires an initial value. We
 /// use the empty work engine that signals 'exhausted' state all the time.
 /// Only parameter is event handler implementing `ExhaustedHandler` trait that will be used to
 /// signal that all work in current engine has been exhausted. This way it is possible to track what
 /// engines are "done".
 pub fn engine_channel(event_handler: impl ExhaustedHandler) -> (EngineSender, EngineReceiver) {
+    engine_channel_with_history(event_handler, Default::default())
+}
+
+/// Same as `engine_channel` but lifecycle of the engines is recorded to the specified `history`
+pub fn engine_channel_with_history(
+    event_handler: impl ExhaustedHandler,
+    history: Arc<history::Recorder>,
+) -> (EngineSender, EngineReceiver) {
     let work_engine: DynEngine = Arc::new(engine::ExhaustedWork);
-    let (sender, receiver) = watch::channel(work_engine.clone());
+    let (sender, receiver) = watch::channel(EngineBroadcast::new(work_engine.clone(), None));
+    let (invalidation_sender, invalidation_receiver) = watch::channel(0);
     (
-        EngineSender::create(work_engine, sender),
-        EngineReceiver::new(receiver, event_handler),
+        EngineSender::create(
+            work_engine,
+            sender,
+            InvalidationSender::new(invalidation_sender),
+            history.clone(),
+        ),
+        EngineReceiver::new(
+            receiver,
+            InvalidationReceiver(invalidation_receiver),
+            event_handler,
+            history,
+        ),
     )
 }
 
+/// Sending half of the counter of invalidations of the engine channel
+struct InvalidationSender {
+    sender: watch::Sender<u64>,
+    count: u64,
+}
+
+impl InvalidationSender {
+    fn new(sender: watch::Sender<u64>) -> Self {
+        Self { sender, count: 0 }
+    }
+
+    fn invalidate(&mut self) {
+        self.count += 1;
+        self.sender
+            .broadcast(self.count)
+            .expect("cannot broadcast invalidation");
+    }
+}
+
+/// Receiving half of the counter of invalidations of the engine channel. Backends use it to find
+/// out that the work queued in the hardware has been invalidated without waiting for a new engine.
+#[derive(Debug, Clone)]
+pub struct InvalidationReceiver(watch::Receiver<u64>);
+
+impl InvalidationReceiver {
+    /// Number of invalidations of the engine channel so far
+    #[inline]
+    pub fn current(&self) -> u64 {
+        *self.0.borrow()
+    }
+
+    /// Wait until the number of invalidations differs from `invalidation` and return the new one.
+    /// The future never finishes when the engine channel is closed.
+    pub async fn wait_for_change(&mut self, invalidation: u64) -> u64 {
+        loop {
+            let current = self.current();
+            if current != invalidation {
+                return current;
+            }
+            if self.0.next().await.is_none() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
 /// The responsibility of Engine generator is to transform a `job::Bitcoin`
 /// into a `work::Engine`. The engine then becomes a source of work based on
 /// this Job.
@@ -323,35 +886,86 @@ pub type EngineGenerator = Box<dyn Fn(Arc<dyn job::Bitcoin>) -> DynEngine + Send
 struct EngineSenderInner {
     engine_generator: Option<EngineGenerator>,
     current_engine: DynEngine,
-    sender: Option<watch::Sender<DynEngine>>,
+    /// Marker of the job the current engine has been generated from
+    current_job: Option<Arc<JobBroadcast>>,
+    /// Version mask of the last broadcasted job
+    version_mask: Option<u32>,
+    sender: Option<watch::Sender<EngineBroadcast>>,
+    /// Counter of invalidations which is always bound to the `sender`
+    invalidation: Option<InvalidationSender>,
+    /// History of the channel the sender is connected to
+    history: Option<Arc<history::Recorder>>,
 }
 
 impl EngineSenderInner {
     fn re_broadcast(&mut self) {
         if let Some(sender) = &self.sender {
             sender
-                .broadcast(self.current_engine.clone())
+                .broadcast(EngineBroadcast::new(
+                    self.current_engine.clone(),
+                    self.current_job.clone(),
+                ))
                 .expect("cannot broadcast work engine");
         }
     }
 
     fn broadcast_engine(&mut self, engine: DynEngine) {
         self.current_engine = engine;
+        self.current_job = None;
         self.re_broadcast();
     }
 
-    /// Generates a new work engine for the specified `job` and broadcasts it to its subscribers
-    fn broadcast_job(&mut self, job: Arc<dyn job::Bitcoin>) {
+    /// Terminate the current engine when the version mask of the new `job` does not allow some
+    /// bits rolled by the engine. Work generated from the engine would be rejected by the pool.
+    fn check_version_mask(&mut self, job: &dyn job::Bitcoin) {
+        let mask = job.version_mask();
+        let previous_mask = match self.version_mask.replace(mask) {
+            Some(previous_mask) if previous_mask & !mask != 0 => previous_mask,
+            _ => return,
+        };
+        if let Some(history) = &self.history {
+            history.record(history::Event::VersionMaskNarrowed {
+                previous_mask,
+                mask,
+            });
+        }
+        self.current_engine.terminate();
+    }
+
+    /// Generates a new work engine for the specified `job` and broadcasts it to its subscribers.
+    /// Returns the engine together with the sequence number of the broadcast.
+    fn broadcast_job(&mut self, job: Arc<dyn job::Bitcoin>) -> (DynEngine, u64) {
+        self.check_version_mask(job.as_ref());
+        let job_broadcast = Arc::new(JobBroadcast::new(clock::now()));
+        let generation = job_broadcast.generation;
+        if let Some(history) = &self.history {
+            history.record(history::Event::Broadcast {
+                generation: job_broadcast.generation,
+                job_id: job.id(),
+            });
+        }
         let engine = self
             .engine_generator
             .as_ref()
             .expect("BUG: missing engine generator")(job);
-        self.broadcast_engine(engine);
+        self.current_engine = engine.clone();
+        self.current_job = Some(job_broadcast);
+        self.re_broadcast();
+        (engine, generation)
     }
 
     fn invalidate(&mut self) {
+        if let Some(history) = &self.history {
+            history.record(history::Event::Invalidate);
+        }
         self.current_engine = Arc::new(engine::ExhaustedWork);
+        self.current_job = None;
         self.re_broadcast();
+        // the counter is raised after the engine has been replaced so that the work generated
+        // with the new value of the counter never comes from the invalidated engine
+        if let Some(invalidation) = &mut self.invalidation {
+            invalidation.invalidate();
+        }
     }
 }
 
@@ -365,18 +979,24 @@ impl EngineSender {
         let engine = engine
             .into()
             .unwrap_or_else(|| Arc::new(engine::ExhaustedWork));
-        Self::create(engine, None)
+        Self::create(engine, None, None, None)
     }
 
-    fn create<T>(current_engine: DynEngine, sender: T) -> Self
+    fn create<T, U, V>(current_engine: DynEngine, sender: T, invalidation: U, history: V) -> Self
     where
-        T: Into<Option<watch::Sender<DynEngine>>>,
+        T: Into<Option<watch::Sender<EngineBroadcast>>>,
+        U: Into<Option<InvalidationSender>>,
+        V: Into<Option<Arc<history::Recorder>>>,
     {
         Self {
             inner: StdMutex::new(EngineSenderInner {
                 engine_generator: Some(Box::new(|_| Arc::new(engine::ExhaustedWork))),
                 current_engine,
+                current_job: None,
+                version_mask: None,
                 sender: sender.into(),
+                invalidation: invalidation.into(),
+                history: history.into(),
             }),
         }
     }
@@ -402,6 +1022,12 @@ impl EngineSender {
         let b = &mut *other.lock_inner();
 
         mem::swap(&mut a.sender, &mut b.sender);
+        mem::swap(&mut a.invalidation, &mut b.invalidation);
+        mem::swap(&mut a.history, &mut b.history);
+        // the jobs have been received long before the switch so the time of their delivery
+        // does not say anything about propagation latency
+        a.current_job = None;
+        b.current_job = None;
 
         a.re_broadcast();
         b.re_broadcast();
@@ -412,8 +1038,10 @@ impl EngineSender {
         self.lock_inner().broadcast_engine(engine)
     }
 
+    /// Returns the engine created for the job and the sequence number of the broadcast which is
+    /// shared by all assignments of the job
     #[inline]
-    pub fn broadcast_job(&self, job: Arc<dyn job::Bitcoin>) {
+    pub fn broadcast_job(&self, job: Arc<dyn job::Bitcoin>) -> (DynEngine, u64) {
         self.lock_inner().broadcast_job(job)
     }
 
@@ -421,6 +1049,12 @@ impl EngineSender {
     pub fn invalidate(&self) {
         self.lock_inner().invalidate();
     }
+
+    /// Returns true when current engine is not able to generate any more work
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        self.lock_inner().current_engine.is_exhausted()
+    }
 }
 
 impl Debug for EngineSender {
@@ -433,38 +1067,168 @@ impl Debug for EngineSender {
 #[derive(Debug, Clone)]
 pub struct EngineReceiver {
     /// Broadcast channel that is used to distribute current `WorkEngine`
-    watch_receiver: watch::Receiver<DynEngine>,
+    watch_receiver: watch::Receiver<EngineBroadcast>,
+    /// Counter of invalidations of the channel
+    invalidation_receiver: InvalidationReceiver,
     /// A channel that is (if present) used to send back exhausted engines
     /// to be "recycled" or just so that engine sender is notified that all work
     /// has been generated from them
     event_handler: Arc<dyn ExhaustedHandler>,
+    /// Lifecycle of the engines distributed by the channel
+    history: Arc<history::Recorder>,
+    /// Distribution of work among clients and work solvers when it is audited
+    audit: Arc<audit::Recorder>,
+    /// Order in which the generators start drawing work from a new job
+    fan_out: Arc<StdMutex<FanOutRegistry>>,
+    /// Engines divided among the generators of the partition group
+    partitions: Arc<StdMutex<PartitionRegistry>>,
+    /// Maximal age of assignments submitted to the hardware shared by all generators
+    assignment_max_age: Arc<StdMutex<Option<time::Duration>>>,
 }
 
 impl EngineReceiver {
     fn new(
-        watch_receiver: watch::Receiver<DynEngine>,
+        watch_receiver: watch::Receiver<EngineBroadcast>,
+        invalidation_receiver: InvalidationReceiver,
         event_handler: impl ExhaustedHandler,
+        history: Arc<history::Recorder>,
     ) -> Self {
         Self {
             watch_receiver,
+            invalidation_receiver,
             event_handler: Arc::new(event_handler),
+            history,
+            audit: Default::default(),
+            fan_out: Default::default(),
+            partitions: Default::default(),
+            assignment_max_age: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn history(&self) -> &Arc<history::Recorder> {
+        &self.history
+    }
+
+    #[inline]
+    pub fn invalidation_receiver(&self) -> &InvalidationReceiver {
+        &self.invalidation_receiver
+    }
+
+    #[inline]
+    pub fn audit(&self) -> &Arc<audit::Recorder> {
+        &self.audit
+    }
+
+    fn lock_fan_out(&self) -> StdMutexGuard<FanOutRegistry> {
+        self.fan_out
+            .lock()
+            .expect("BUG: cannot lock fan-out registry")
+    }
+
+    /// Stagger wake of all generators of this channel when a new job is broadcast. The `None`
+    /// wakes all generators at once.
+    pub fn set_fan_out(&self, fan_out: Option<FanOut>) {
+        self.lock_fan_out().fan_out = fan_out;
+    }
+
+    pub fn fan_out(&self) -> Option<FanOut> {
+        self.lock_fan_out().fan_out
+    }
+
+    pub(crate) fn register_fan_out(&self) -> Arc<FanOutSlot> {
+        self.lock_fan_out().register()
+    }
+
+    /// How long the generator with the `slot` should wait before drawing work from a new job
+    pub(crate) fn fan_out_delay(&self, slot: &Arc<FanOutSlot>) -> Option<time::Duration> {
+        self.lock_fan_out().delay(slot)
+    }
+
+    fn lock_partitions(&self) -> StdMutexGuard<PartitionRegistry> {
+        self.partitions
+            .lock()
+            .expect("BUG: cannot lock partition registry")
+    }
+
+    /// Add a generator to the group which divides the space of each engine among its members
+    pub(crate) fn register_partition(&self) -> Arc<PartitionSlot> {
+        self.lock_partitions().register()
+    }
+
+    /// Returns the view of the partitioned `engine` for the generator with the `slot` or `None`
+    /// when the generator should draw work from the engine directly
+    pub(crate) fn shard(
+        &self,
+        engine: &DynEngine,
+        slot: Option<&Arc<PartitionSlot>>,
+    ) -> Option<Shard> {
+        self.lock_partitions().shard(engine, slot)
+    }
+
+    /// Limit age of assignments submitted to the hardware by all generators of this channel. The
+    /// `None` disables the limit.
+    pub fn set_assignment_max_age(&self, max_age: Option<time::Duration>) {
+        *self
+            .assignment_max_age
+            .lock()
+            .expect("BUG: cannot lock assignment max age") = max_age;
+    }
+
+    pub fn assignment_max_age(&self) -> Option<time::Duration> {
+        *self
+            .assignment_max_age
+            .lock()
+            .expect("BUG: cannot lock assignment max age")
+    }
+
+    /// Returns the most recent broadcast without waiting when its engine can generate some work
+    fn current_broadcast(&self) -> Option<EngineBroadcast> {
+        let broadcast = self.watch_receiver.borrow().clone();
+        if broadcast.engine.is_exhausted() {
+            None
+        } else {
+            Some(broadcast)
         }
     }
 
     /// Provides the most recent WorkEngine as long as the engine is able to provide any work.
     /// Otherwise, it sleeps and waits for a new
+    ///
+    /// The future is cancellation safe. When it is dropped while waiting, no new engine is lost
+    /// and the next call returns the engine broadcasted in the meantime.
     pub async fn get_engine(&mut self) -> Option<DynEngine> {
-        let mut engine = self.watch_receiver.borrow().clone();
+        self.get_broadcast().await.map(|broadcast| broadcast.engine)
+    }
+
+    /// Returns the most recent WorkEngine immediately when it is able to provide any work. It
+    /// does not wait for a new engine so it can be used outside of async context.
+    pub fn try_get_engine(&self) -> Option<DynEngine> {
+        self.try_get_broadcast().map(|broadcast| broadcast.engine)
+    }
+
+    fn try_get_broadcast(&self) -> Option<EngineBroadcast> {
+        let broadcast = self.watch_receiver.borrow();
+        if broadcast.engine.is_exhausted() {
+            None
+        } else {
+            Some(broadcast.clone())
+        }
+    }
+
+    /// Same as `get_engine` but the engine is accompanied by the marker of its job
+    pub async fn get_broadcast(&mut self) -> Option<EngineBroadcast> {
         loop {
-            if !engine.is_exhausted() {
+            // The current engine is always taken from the channel after the receiver has been
+            // woken up. The value returned by the channel is not used at all because the
+            // notification may have been consumed by a future which has been dropped.
+            if let Some(broadcast) = self.try_get_broadcast() {
                 // return only work engine which can generate some work
-                return Some(engine);
+                return Some(broadcast);
             }
-            match self.watch_receiver.next().await {
+            if self.watch_receiver.next().await.is_none() {
                 // end of stream
-                None => return None,
-                // new work engine received
-                Some(value) => engine = value,
+                return None;
             }
         }
     }
@@ -472,13 +1236,17 @@ impl EngineReceiver {
     /// This function should be called just when last entry has been taken out of engine
     #[inline]
     pub fn handle_exhausted(&self, engine: DynEngine) {
-        self.event_handler.handle_exhausted(engine);
+        let job = engine.job();
+        self.event_handler.handle_exhausted(engine, job);
     }
 }
 
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use crate::job::Bitcoin as _;
+
+    use ii_async_compat::tokio;
 
     #[test]
     fn test_block_double_hash() {
@@ -494,4 +1262,188 @@ pub mod test {
             assert_eq!(&block.hash, hash);
         }
     }
+
+    #[test]
+    fn test_solution_clone() {
+        for block in crate::test_utils::TEST_BLOCKS.iter() {
+            let solution: Solution = block.into();
+
+            // hash is not evaluated yet so the clone has to evaluate it on its own
+            let clone = solution.clone();
+            assert!(Arc::ptr_eq(&solution.work, &clone.work));
+            assert!(Arc::ptr_eq(&solution.solution, &clone.solution));
+            assert!(clone.hash.get().is_none());
+            assert_eq!(&block.hash, clone.hash());
+            assert!(solution.hash.get().is_none());
+
+            // already evaluated hash is copied to the clone
+            assert_eq!(&block.hash, solution.hash());
+            let clone = solution.clone();
+            assert_eq!(Some(&block.hash), clone.hash.get());
+        }
+    }
+
+    #[test]
+    fn test_solution_age_hint() {
+        const AGE: time::Duration = time::Duration::from_millis(250);
+
+        let block = &crate::test_utils::TEST_BLOCKS[0];
+        let now = time::Instant::now();
+
+        let solution = Solution::new(
+            Assignment::from(block),
+            crate::test_utils::TestSolution::new(block),
+            Some(now),
+        );
+        assert_eq!(solution.timestamp(), now);
+        assert_eq!(solution.raw_timestamp(), now);
+        assert_eq!(solution.age(), time::Duration::from_secs(0));
+
+        // backend reports that the solution has been waiting in its buffers
+        let solution = Solution::new(
+            Assignment::from(block),
+            crate::test_utils::TestSolution::new(block).with_age_hint(AGE),
+            Some(now),
+        );
+        assert_eq!(solution.timestamp(), now - AGE);
+        assert_eq!(solution.raw_timestamp(), now);
+        assert_eq!(solution.age(), AGE);
+    }
+
+    /// Exhausted handler remembering the jobs of all exhausted engines
+    #[derive(Debug, Default)]
+    struct ExhaustedJobs(Arc<StdMutex<Vec<Option<Arc<dyn job::Bitcoin>>>>>);
+
+    impl ExhaustedHandler for ExhaustedJobs {
+        fn handle_exhausted(&self, _engine: DynEngine, job: Option<Arc<dyn job::Bitcoin>>) {
+            self.0.lock().expect("BUG: cannot lock jobs").push(job);
+        }
+    }
+
+    /// Waiting for a new engine is cancelled right after the engine has been broadcasted and the
+    /// engine must be still available to the receiver
+    #[tokio::test]
+    async fn test_get_engine_cancellation() {
+        let (engine_sender, mut engine_receiver) = engine_channel(IgnoreEvents);
+        assert!(engine_receiver.try_get_engine().is_none());
+
+        for _ in 0..8 {
+            let engine: DynEngine = Arc::new(crate::test_utils::TestWorkEngine::new());
+            {
+                let mut get_engine = engine_receiver.get_engine().boxed_local();
+                assert!((&mut get_engine).now_or_never().is_none());
+                engine_sender.broadcast_engine(engine.clone());
+                // the future is dropped without being polled again
+            }
+            let current = engine_receiver
+                .try_get_engine()
+                .expect("BUG: missed engine");
+            assert!(Arc::ptr_eq(&current, &engine));
+            let current = engine_receiver
+                .get_engine()
+                .await
+                .expect("BUG: missed engine");
+            assert!(Arc::ptr_eq(&current, &engine));
+
+            engine.terminate();
+            assert!(engine_receiver.try_get_engine().is_none());
+        }
+    }
+
+    /// Narrowing of the version mask between two jobs terminates the engine of the wider one
+    #[test]
+    fn test_version_mask_narrowed() {
+        const VERSION_MASK: u32 = 0x0001_e000;
+
+        let history = Arc::new(history::Recorder::new(history::Recorder::DEFAULT_LENGTH));
+        let (engine_sender, _engine_receiver) =
+            engine_channel_with_history(IgnoreEvents, history.clone());
+        engine_sender.replace_engine_generator(Box::new(|job| {
+            Arc::new(engine::VersionRolling::new(job, 4))
+        }));
+        let narrowed = || {
+            history
+                .records()
+                .into_iter()
+                .filter(|record| match record.event {
+                    history::Event::VersionMaskNarrowed { .. } => true,
+                    _ => false,
+                })
+                .map(|record| record.event)
+                .collect::<Vec<_>>()
+        };
+
+        let block = crate::test_utils::TEST_BLOCKS[0];
+        let (wide_engine, _) = engine_sender.broadcast_job(Arc::new(block));
+        let (narrow_engine, _) = engine_sender.broadcast_job(Arc::new(
+            crate::test_utils::MaskedTestBlock::new(block, VERSION_MASK),
+        ));
+        assert!(wide_engine.is_exhausted());
+        assert_eq!(
+            narrowed(),
+            vec![history::Event::VersionMaskNarrowed {
+                previous_mask: ii_bitcoin::BIP320_VERSION_MASK,
+                mask: VERSION_MASK,
+            }]
+        );
+        while let LoopState::Continue(work) = narrow_engine.next_work() {
+            assert_eq!(work.version_mask(), VERSION_MASK);
+            assert!(work.is_version_in_mask());
+        }
+
+        // the mask has been widened again
+        let (engine, _) = engine_sender.broadcast_job(Arc::new(block));
+        assert!(!engine.is_exhausted());
+        assert_eq!(narrowed().len(), 1);
+    }
+
+    #[test]
+    fn test_broadcast_job_engine() {
+        let exhausted_jobs = ExhaustedJobs::default();
+        let jobs = exhausted_jobs.0.clone();
+        let (engine_sender, engine_receiver) = engine_channel(exhausted_jobs);
+        engine_sender.replace_engine_generator(Box::new(|job| {
+            Arc::new(engine::VersionRolling::new(job, 1))
+        }));
+
+        let block = &crate::test_utils::TEST_BLOCKS[0];
+        let (engine, _) = engine_sender.broadcast_job(Arc::new(*block));
+        let engine_job = engine.job().expect("BUG: missing engine job");
+        assert_eq!(engine_job.previous_hash(), block.previous_hash());
+
+        // the job is passed to the handler together with the engine
+        engine_receiver.handle_exhausted(engine);
+        engine_receiver.handle_exhausted(Arc::new(engine::ExhaustedWork));
+        let jobs = jobs.lock().expect("BUG: cannot lock jobs");
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(
+            jobs[0].as_ref().map(|job| *job.previous_hash()),
+            Some(*block.previous_hash())
+        );
+        assert!(jobs[1].is_none());
+    }
+
+    /// Simple micro-benchmark measuring cost of cloning solutions which are distributed to more
+    /// consumers
+    #[test]
+    #[ignore]
+    fn bench_solution_clone() {
+        const CLONES: u32 = 1_000_000;
+
+        let solution: Solution = (&crate::test_utils::TEST_BLOCKS[0]).into();
+        solution.hash();
+
+        let start = time::Instant::now();
+        for _ in 0..CLONES {
+            let clone = solution.clone();
+            assert_eq!(clone.nonce(), solution.nonce());
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{} clones in {:?} ({:?} per clone)",
+            CLONES,
+            elapsed,
+            elapsed / CLONES
+        );
+    }
 }
@@ -0,0 +1,349 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Audit of work distribution among clients and work solvers which verifies that quotas, weights
+//! and other scheduling features deliver work in the intended ratios.
+//!
+//! The recording is bounded by its duration and the participating clients and work solvers are
+//! fixed at its start. The matrix of work amounts is preallocated and indexed by their positions
+//! so each delivered assignment costs just an atomic increment. Generators cache the current
+//! session and look their work solver up only once per session.
+
+use crate::backend;
+use crate::clock;
+use crate::node;
+
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+/// Upper limit of the recording duration
+pub const MAX_DURATION: time::Duration = time::Duration::from_secs(24 * 60 * 60);
+
+/// Client participating in the audit
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// Human readable identification of the client (typically pool URL)
+    pub label: String,
+    pub node: Arc<dyn node::Client>,
+}
+
+/// Work solver participating in the audit
+#[derive(Debug, Clone)]
+pub struct Solver {
+    pub uid: backend::Uid,
+    pub path: String,
+    pub node: Arc<dyn node::WorkSolver>,
+}
+
+/// Key of the client which does not depend on the type of its trait object
+fn client_key(node: &Arc<dyn node::Client>) -> usize {
+    let unique_ptr = node.clone().get_unique_ptr();
+    &*unique_ptr as *const dyn Any as *const () as usize
+}
+
+#[derive(Debug)]
+struct Session {
+    id: u64,
+    start: time::Instant,
+    duration: time::Duration,
+    /// Set by the first delivery after the end of the recording
+    finished: AtomicBool,
+    clients: Vec<Client>,
+    client_keys: Vec<usize>,
+    solvers: Vec<Solver>,
+    /// Row-major matrix of work amounts with clients in rows and work solvers in columns
+    work: Vec<AtomicU64>,
+    /// Work of clients or work solvers which do not participate in the audit
+    unmatched: AtomicU64,
+}
+
+impl Session {
+    fn solver_index(&self, work_solver: &Arc<dyn node::WorkSolver>) -> Option<usize> {
+        self.solvers
+            .iter()
+            .position(|solver| Arc::ptr_eq(&solver.node, work_solver))
+    }
+
+    fn client_index(&self, origin: &Arc<dyn node::Client>) -> Option<usize> {
+        let key = client_key(origin);
+        self.client_keys
+            .iter()
+            .position(|client_key| *client_key == key)
+    }
+
+    fn is_recording(&self, now: time::Instant) -> bool {
+        if self.finished.load(Ordering::Relaxed) {
+            return false;
+        }
+        if now.duration_since(self.start) >= self.duration {
+            self.finished.store(true, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    fn record(&self, solver: Option<usize>, origin: &Arc<dyn node::Client>, amount: u64) {
+        if !self.is_recording(clock::now()) {
+            return;
+        }
+        let cell = match (self.client_index(origin), solver) {
+            (Some(client), Some(solver)) => &self.work[client * self.solvers.len() + solver],
+            _ => &self.unmatched,
+        };
+        cell.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    fn report(&self) -> Report {
+        let now = clock::now();
+        let recording = self.is_recording(now);
+        let columns = self.solvers.len();
+        Report {
+            elapsed: now.duration_since(self.start).min(self.duration),
+            duration: self.duration,
+            recording,
+            clients: self
+                .clients
+                .iter()
+                .map(|client| client.label.clone())
+                .collect(),
+            solvers: self
+                .solvers
+                .iter()
+                .map(|solver| (solver.uid, solver.path.clone()))
+                .collect(),
+            work: (0..self.clients.len())
+                .map(|row| {
+                    self.work[row * columns..(row + 1) * columns]
+                        .iter()
+                        .map(|cell| cell.load(Ordering::Relaxed))
+                        .collect()
+                })
+                .collect(),
+            unmatched: self.unmatched.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Work distribution recorded by the audit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Time since the start of the recording limited by its duration
+    pub elapsed: time::Duration,
+    pub duration: time::Duration,
+    /// The recording is still in progress
+    pub recording: bool,
+    /// Labels of the participating clients
+    pub clients: Vec<String>,
+    /// Uids and paths of the participating work solvers
+    pub solvers: Vec<(backend::Uid, String)>,
+    /// Amount of work delivered from the client (row) to the work solver (column)
+    pub work: Vec<Vec<u64>>,
+    /// Work of clients or work solvers which have appeared after the start of the recording
+    pub unmatched: u64,
+}
+
+impl Report {
+    /// Amount of work delivered from each client to all work solvers
+    pub fn client_work(&self) -> Vec<u64> {
+        self.work.iter().map(|row| row.iter().sum()).collect()
+    }
+
+    /// Amount of work delivered to each work solver from all clients
+    pub fn solver_work(&self) -> Vec<u64> {
+        (0..self.solvers.len())
+            .map(|column| self.work.iter().map(|row| row[column]).sum())
+            .collect()
+    }
+}
+
+/// Audit shared by all generators of the engine receiver
+#[derive(Debug, Default)]
+pub struct Recorder {
+    /// Identifier of the current session (zero when there has been none)
+    session_id: AtomicU64,
+    session: StdMutex<Option<Arc<Session>>>,
+}
+
+impl Recorder {
+    fn lock_session(&self) -> StdMutexGuard<Option<Arc<Session>>> {
+        self.session
+            .lock()
+            .expect("BUG: cannot lock work audit session")
+    }
+
+    /// Start a new recording of work delivered from `clients` to `solvers` for `duration`. The
+    /// previous recording is discarded.
+    pub fn start(&self, clients: Vec<Client>, solvers: Vec<Solver>, duration: time::Duration) {
+        assert!(
+            duration > time::Duration::from_secs(0) && duration <= MAX_DURATION,
+            "BUG: invalid duration of work audit"
+        );
+        let mut session = self.lock_session();
+        let id = self.session_id.load(Ordering::Relaxed) + 1;
+        let cells = clients.len() * solvers.len();
+        session.replace(Arc::new(Session {
+            id,
+            start: clock::now(),
+            duration,
+            finished: AtomicBool::new(false),
+            client_keys: clients
+                .iter()
+                .map(|client| client_key(&client.node))
+                .collect(),
+            clients,
+            solvers,
+            work: (0..cells).map(|_| AtomicU64::new(0)).collect(),
+            unmatched: AtomicU64::new(0),
+        }));
+        self.session_id.store(id, Ordering::Release);
+    }
+
+    /// Returns work distribution of the last recording
+    pub fn report(&self) -> Option<Report> {
+        self.lock_session().as_ref().map(|session| session.report())
+    }
+}
+
+/// Cache of the current session in the generator
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Probe {
+    session: Option<Arc<Session>>,
+    /// Index of the generator's work solver in the cached session
+    solver: Option<usize>,
+}
+
+impl Probe {
+    /// Account `amount` of work delivered from `origin` client to `work_solver`
+    pub(crate) fn record(
+        &mut self,
+        recorder: &Recorder,
+        work_solver: &Arc<dyn node::WorkSolver>,
+        origin: &Arc<dyn node::Client>,
+        amount: u64,
+    ) {
+        let session_id = recorder.session_id.load(Ordering::Acquire);
+        if session_id == 0 {
+            // no audit has been started yet
+            return;
+        }
+        if self
+            .session
+            .as_ref()
+            .map_or(true, |session| session.id != session_id)
+        {
+            self.session = recorder.lock_session().clone();
+            self.solver = self
+                .session
+                .as_ref()
+                .and_then(|session| session.solver_index(work_solver));
+        }
+        if let Some(session) = &self.session {
+            session.record(self.solver, origin, amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use ii_async_compat::tokio;
+
+    fn create_clients(count: usize) -> Vec<Client> {
+        (0..count)
+            .map(|idx| Client {
+                label: format!("pool {}", idx),
+                node: Arc::new(test_utils::TestClient::new()),
+            })
+            .collect()
+    }
+
+    fn create_solvers(count: usize) -> Vec<Solver> {
+        (0..count)
+            .map(|idx| Solver {
+                uid: idx as backend::Uid + 1,
+                path: format!("solver {}", idx),
+                node: Arc::new(test_utils::TestWorkSolver::with_id(idx)),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_audit_matrix() {
+        tokio::time::pause();
+        let recorder = Recorder::default();
+        let clients = create_clients(2);
+        let solvers = create_solvers(3);
+        let foreign_client: Arc<dyn node::Client> = Arc::new(test_utils::TestClient::new());
+        let foreign_solver: Arc<dyn node::WorkSolver> = Arc::new(test_utils::TestWorkSolver::new());
+
+        let mut probes = vec![Probe::default(); solvers.len()];
+        // deliveries before the start of the audit are ignored
+        probes[0].record(&recorder, &solvers[0].node, &clients[0].node, 4);
+        assert_eq!(recorder.report(), None);
+
+        recorder.start(
+            clients.clone(),
+            solvers.clone(),
+            time::Duration::from_secs(60),
+        );
+        for (idx, probe) in probes.iter_mut().enumerate() {
+            probe.record(&recorder, &solvers[idx].node, &clients[0].node, 4);
+            probe.record(&recorder, &solvers[idx].node, &clients[1].node, idx as u64);
+        }
+        let mut probe = Probe::default();
+        probe.record(&recorder, &foreign_solver, &clients[0].node, 4);
+        probes[0].record(&recorder, &solvers[0].node, &foreign_client, 4);
+
+        tokio::time::advance(time::Duration::from_secs(30)).await;
+        let report = recorder.report().expect("BUG: missing audit report");
+        assert!(report.recording);
+        assert_eq!(report.elapsed, time::Duration::from_secs(30));
+        assert_eq!(report.clients, vec!["pool 0", "pool 1"]);
+        assert_eq!(report.solvers[2], (3, "solver 2".to_string()));
+        assert_eq!(report.work, vec![vec![4, 4, 4], vec![0, 1, 2]]);
+        assert_eq!(report.unmatched, 8);
+        assert_eq!(report.client_work(), vec![12, 3]);
+        assert_eq!(report.solver_work(), vec![4, 5, 6]);
+
+        // nothing is recorded after the end of the audit
+        tokio::time::advance(time::Duration::from_secs(30)).await;
+        probes[1].record(&recorder, &solvers[1].node, &clients[1].node, 4);
+        let report = recorder.report().expect("BUG: missing audit report");
+        assert!(!report.recording);
+        assert_eq!(report.elapsed, time::Duration::from_secs(60));
+        assert_eq!(report.work[1], vec![0, 1, 2]);
+
+        // new audit starts from scratch and probes switch to it
+        recorder.start(
+            clients.clone(),
+            solvers.clone(),
+            time::Duration::from_secs(60),
+        );
+        probes[1].record(&recorder, &solvers[1].node, &clients[1].node, 4);
+        let report = recorder.report().expect("BUG: missing audit report");
+        assert_eq!(report.work, vec![vec![0, 0, 0], vec![0, 4, 0]]);
+    }
+}
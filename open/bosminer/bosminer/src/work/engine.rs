@@ -22,9 +22,13 @@
 
 //! Provides work engines that are capable for converting Jobs to actual work suitable for mining
 //! backend processing
+use ii_logging::macros::*;
+
 use super::*;
 use crate::job;
 
+use once_cell::sync::OnceCell;
+
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
@@ -51,6 +55,21 @@ const BIP320_UPPER_BOUND_EXCLUSIVE_INDEX: u32 = ii_bitcoin::BIP320_VERSION_MAX +
 /// hash_space * roll_ntime_seconds / new_stratum_job_every_sec = 2**(32 + 16) * 256 / 30 = 2.4e15
 const ROLL_NTIME_SECONDS: u32 = 256;
 
+/// Deposit the least significant bits of `value` to the positions of set bits of `mask` (the
+/// lowest bit of `value` goes to the lowest set bit of `mask`)
+fn deposit_bits(mut value: u32, mut mask: u32) -> u32 {
+    let mut result = 0;
+    while mask != 0 && value != 0 {
+        let bit = mask & mask.wrapping_neg();
+        if value & 1 != 0 {
+            result |= bit;
+        }
+        value >>= 1;
+        mask &= !bit;
+    }
+    result
+}
+
 /// Primitive for atomic range counter
 /// This structure can be freely shared among parallel processes and each range is returned only to
 /// one competing process. The structure returns ranges until maximal allowed index is reached.
@@ -126,12 +145,47 @@ impl AtomicRange {
         let current = current.into().unwrap_or_else(|| self.get_current());
         self.checked_add(current, self.step_size).is_none()
     }
+
+    /// Atomically take all remaining ranges and divide them to `count` disjoint atomic ranges
+    /// with the same step size. The sizes of the new ranges differ at most by one step and this
+    /// range is exhausted afterwards.
+    pub fn split(&self, count: usize) -> Vec<AtomicRange> {
+        assert!(count > 0);
+        let current = self
+            .curr_index
+            .swap(self.max_index, Ordering::Relaxed)
+            .min(self.max_index);
+        let count = count as u32;
+        let steps = (self.max_index - current) / self.step_size;
+        let mut start = current;
+        (0..count)
+            .map(|i| {
+                let size = (steps / count + u32::from(i < steps % count)) * self.step_size;
+                let range = Self::new(start, start + size, self.step_size);
+                start += size;
+                range
+            })
+            .collect()
+    }
+}
+
+/// Number of distinct timestamps rolled for the `job`. The ntime roll limit advertised by the pool
+/// can only narrow the local range.
+fn get_ntime_count(job: &dyn job::Bitcoin) -> u32 {
+    match job.ntime_roll_limit() {
+        0 => ROLL_NTIME_SECONDS,
+        limit => limit.saturating_add(1).min(ROLL_NTIME_SECONDS),
+    }
 }
 
 /// Version rolling implements WorkEngine trait and represents a shared source of work for mining
 /// backends. Each instance takes care of atomically allocating version field ranges until the
 /// range is full exhausted. After version has been rolled over, ntime is incremented and version
-/// resetted to 0. The limit of `ntime` range is determined by `ROLL_NTIME_SECONDS`.
+/// resetted to 0. The limit of `ntime` range is determined by `ROLL_NTIME_SECONDS` and by the
+/// ntime roll limit of the job.
+///
+/// Only the BIP320 bits allowed by the version mask of the job are rolled. The mask may be
+/// different for each job because pools are able to change it during the session.
 ///
 /// TODO: Rolling ntime together with version IS A HACK. This needs to be fixed properly by raising
 /// `ntime` in sync with real-time clock.
@@ -140,48 +194,83 @@ pub struct VersionRolling {
     job: Arc<dyn job::Bitcoin>,
     /// Number of midstates that each generated work covers
     midstate_count: usize,
-    /// Current range of the rolled part of the version (before BIP320 shift)
-    /// We keep current version in lower 16 bits and `ntime_offset`
-    /// in upper 8 bits. When version overflows, the ntime_offset gets
-    /// automatically incremented.
+    /// Current range of the rolled part of the version (before it is deposited to the mask)
+    /// We keep current version in lower bits and `ntime_offset` in the upper ones. When version
+    /// overflows, the ntime_offset gets automatically incremented.
     curr_range: AtomicRange,
-    /// Base Bitcoin block header version with BIP320 bits cleared
+    /// Base Bitcoin block header version with the rolled bits cleared
     base_version: u32,
+    /// Bits of the version rolled by the engine
+    version_mask: u32,
+    /// Number of distinct versions which can be rolled in the mask
+    version_count: u32,
+    /// Number of distinct timestamps which can be rolled within the limit of the job
+    ntime_count: u32,
+    /// Disjoint ranges the remaining space has been forked into
+    forks: Arc<OnceCell<Vec<AtomicRange>>>,
 }
 
 impl VersionRolling {
     pub fn new(job: Arc<dyn job::Bitcoin>, midstate_count: usize) -> Self {
-        let base_version = job.version() & !ii_bitcoin::BIP320_VERSION_MASK;
+        Self::with_version_mask(job, midstate_count, ii_bitcoin::BIP320_VERSION_MASK)
+    }
+
+    /// Roll only the bits of `version_mask`. The bits outside of the version mask of the job
+    /// itself are never rolled regardless of the requested mask.
+    pub fn with_version_mask(
+        job: Arc<dyn job::Bitcoin>,
+        midstate_count: usize,
+        version_mask: u32,
+    ) -> Self {
         // we have to be sure we have no "leftover" midstates when we roll
         assert_eq!(
             BIP320_UPPER_BOUND_EXCLUSIVE_INDEX % (midstate_count as u32),
             0
         );
+        let version_mask = version_mask & job.version_mask() & ii_bitcoin::BIP320_VERSION_MASK;
+        let version_count = 1 << version_mask.count_ones();
+        let ntime_count = get_ntime_count(job.as_ref());
+        let max_index = if version_count % midstate_count as u32 == 0 {
+            version_count * ntime_count
+        } else {
+            warn!(
+                "Version mask {:#010x} of job {:?} is too narrow for {} midstates",
+                version_mask,
+                job.id(),
+                midstate_count
+            );
+            0
+        };
         Self {
+            base_version: job.version() & !version_mask,
             job,
             midstate_count,
-            curr_range: AtomicRange::new(
-                0,
-                BIP320_UPPER_BOUND_EXCLUSIVE_INDEX * ROLL_NTIME_SECONDS,
-                midstate_count as u32,
-            ),
-            base_version,
+            curr_range: AtomicRange::new(0, max_index, midstate_count as u32),
+            version_mask,
+            version_count,
+            ntime_count,
+            forks: Default::default(),
         }
     }
 
-    /// Convert the allocated index to a block version as per BIP320
+    /// Bits of the version rolled by the engine
+    #[inline]
+    pub fn version_mask(&self) -> u32 {
+        self.version_mask
+    }
+
+    /// Convert the allocated index to a block version with the index deposited to the rolled bits
     #[inline]
     fn get_block_version(&self, index: u32) -> u32 {
-        let version = index % BIP320_UPPER_BOUND_EXCLUSIVE_INDEX;
-        assert!(version <= ii_bitcoin::BIP320_VERSION_MAX);
-        self.base_version | (version << ii_bitcoin::BIP320_VERSION_SHIFT)
+        let version = index % self.version_count;
+        self.base_version | deposit_bits(version, self.version_mask)
     }
 
     /// Convert the allocated index to a ntime offset
     #[inline]
     fn get_ntime_offset(&self, index: u32) -> u32 {
-        let ntime_offset = index / BIP320_UPPER_BOUND_EXCLUSIVE_INDEX;
-        assert!(ntime_offset < ROLL_NTIME_SECONDS);
+        let ntime_offset = index / self.version_count;
+        assert!(ntime_offset < self.ntime_count);
         ntime_offset
     }
 }
@@ -189,10 +278,48 @@ impl VersionRolling {
 impl Engine for VersionRolling {
     fn terminate(&self) {
         self.curr_range.terminate();
+        if let Some(forks) = self.forks.get() {
+            forks.iter().for_each(AtomicRange::terminate);
+        }
     }
 
+    /// The forked engine is exhausted only when all its forks are exhausted
     fn is_exhausted(&self) -> bool {
         self.curr_range.is_exhausted(None)
+            && self.forks.get().map_or(true, |forks| {
+                forks.iter().all(|range| range.is_exhausted(None))
+            })
+    }
+
+    /// The engine can be forked only once. Further calls do not return any engine.
+    fn fork(&self, count: usize) -> Vec<DynEngine> {
+        let mut forked = false;
+        let forks = self.forks.get_or_init(|| {
+            forked = true;
+            self.curr_range.split(count)
+        });
+        if !forked {
+            return vec![];
+        }
+        forks
+            .iter()
+            .map(|range| {
+                Arc::new(Self {
+                    job: self.job.clone(),
+                    midstate_count: self.midstate_count,
+                    curr_range: range.clone(),
+                    base_version: self.base_version,
+                    version_mask: self.version_mask,
+                    version_count: self.version_count,
+                    ntime_count: self.ntime_count,
+                    forks: Default::default(),
+                }) as DynEngine
+            })
+            .collect()
+    }
+
+    fn job(&self) -> Option<Arc<dyn job::Bitcoin>> {
+        Some(self.job.clone())
     }
 
     fn next_work(&self) -> LoopState<Assignment> {
@@ -233,7 +360,8 @@ impl Engine for VersionRolling {
         let ntime_offset = self.get_ntime_offset(current);
         assert_eq!(ntime_offset, self.get_ntime_offset(next - 1));
 
-        let work = Assignment::new(self.job.clone(), midstates, self.job.time() + ntime_offset);
+        let work = Assignment::new(self.job.clone(), midstates, self.job.time() + ntime_offset)
+            .with_version_mask(self.version_mask);
         if self.curr_range.is_exhausted(next) {
             // when the whole version space has been exhausted then mark the generated work as
             // a last one (the next call of this method will return 'Exhausted')
@@ -295,6 +423,61 @@ pub mod test {
         compare_range(5, 9, 4);
     }
 
+    fn check_split(range: AtomicRange, count: usize) {
+        let expected: Vec<_> = {
+            let range = AtomicRange::new(range.get_current(), range.max_index, range.step_size);
+            std::iter::from_fn(|| range.next()).collect()
+        };
+        let ranges = range.split(count);
+        assert_eq!(ranges.len(), count);
+        assert!(range.is_exhausted(None));
+        assert_eq!(range.next(), None);
+
+        let mut generated = vec![];
+        let mut sizes = vec![];
+        for range in ranges.iter() {
+            let size = generated.len();
+            generated.extend(std::iter::from_fn(|| range.next()));
+            sizes.push(generated.len() - size);
+        }
+        // the sizes of sub-ranges are balanced
+        assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+        // the sub-ranges are disjoint and they cover the whole remaining space
+        generated.sort();
+        assert_eq!(generated, expected);
+    }
+
+    #[test]
+    fn test_atomic_range_split() {
+        check_split(AtomicRange::new(0, 1, 1), 1);
+        check_split(AtomicRange::new(0, 10, 1), 3);
+        check_split(AtomicRange::new(1, 9, 4), 2);
+        check_split(AtomicRange::new(0, 9, 4), 3);
+        check_split(AtomicRange::new(0, 3, 4), 3);
+        check_split(AtomicRange::new(0, 8, 2), 8);
+        check_split(
+            AtomicRange::new(
+                0,
+                BIP320_UPPER_BOUND_EXCLUSIVE_INDEX * ROLL_NTIME_SECONDS,
+                64,
+            ),
+            3,
+        );
+
+        // split of partially consumed range
+        let range = AtomicRange::new(0, 100, 4);
+        range.next();
+        range.next();
+        check_split(range, 5);
+
+        // terminated range cannot be split
+        let range = AtomicRange::new(0, 100, 4);
+        range.terminate();
+        for range in range.split(2) {
+            assert!(range.is_exhausted(None));
+        }
+    }
+
     #[test]
     fn test_block_midstate() {
         for block in test_utils::TEST_BLOCKS.iter() {
@@ -403,4 +586,265 @@ pub mod test {
         }
         assert!(engine.is_exhausted());
     }
+
+    /// Create engine which rolls only `count` midstates around the end of the first version range
+    fn get_small_engine(job: Arc<test_utils::TestBlock>, midstate_count: usize) -> VersionRolling {
+        let mut engine = VersionRolling::new(job, midstate_count);
+        let start = make_compound_index(0, BIP320_UPPER_BOUND_EXCLUSIVE_INDEX - 32);
+        engine.curr_range = AtomicRange::new(start, start + 64, midstate_count as u32);
+        engine
+    }
+
+    /// Collect block header version and ntime of all midstates in the work
+    fn collect_midstates(work: &Assignment, midstates: &mut Vec<(u32, u32)>) {
+        midstates.extend(
+            work.midstates
+                .iter()
+                .map(|midstate| (work.ntime, midstate.version)),
+        );
+    }
+
+    /// Drain the engine and return all generated midstates
+    fn drain_engine(engine: &dyn Engine) -> Vec<(u32, u32)> {
+        let mut midstates = vec![];
+        loop {
+            match engine.next_work() {
+                LoopState::Exhausted => panic!("expected 'LoopState::Break'"),
+                LoopState::Continue(work) => collect_midstates(&work, &mut midstates),
+                LoopState::Break(work) => {
+                    collect_midstates(&work, &mut midstates);
+                    break;
+                }
+            }
+        }
+        assert!(engine.is_exhausted());
+        midstates
+    }
+
+    #[test]
+    fn test_engine_fork() {
+        let job = Arc::new(test_utils::TEST_BLOCKS[0]);
+        let mut expected = drain_engine(&get_small_engine(job.clone(), 4));
+        expected.sort();
+        // the space crosses boundary of version range
+        assert_eq!(expected.len(), 64);
+        assert_ne!(expected.first().unwrap().0, expected.last().unwrap().0);
+
+        let engine = get_small_engine(job.clone(), 4);
+        // draw some work before the engine is forked
+        let mut midstates = vec![];
+        collect_midstates(&engine.next_work().unwrap(), &mut midstates);
+
+        let forks = engine.fork(3);
+        assert_eq!(forks.len(), 3);
+        // the engine can be forked only once
+        assert!(engine.fork(3).is_empty());
+        // forked engine does not generate any work but it is not exhausted until its forks are
+        match engine.next_work() {
+            LoopState::Exhausted => {}
+            _ => panic!("expected 'LoopState::Exhausted'"),
+        }
+        for fork in forks.iter() {
+            assert!(!engine.is_exhausted());
+            midstates.extend(drain_engine(fork.as_ref()));
+        }
+        assert!(engine.is_exhausted());
+
+        // the union of all assignments covers the whole space with no overlaps
+        midstates.sort();
+        assert_eq!(midstates, expected);
+    }
+
+    #[test]
+    fn test_terminate_forked_engine() {
+        let job = Arc::new(test_utils::TEST_BLOCKS[0]);
+        let engine = get_small_engine(job, 1);
+        let forks = engine.fork(2);
+        assert!(!engine.is_exhausted());
+
+        engine.terminate();
+        assert!(engine.is_exhausted());
+        for fork in forks.iter() {
+            assert!(fork.is_exhausted());
+        }
+    }
+
+    #[test]
+    fn test_partitioned_engine() {
+        let job = Arc::new(test_utils::TEST_BLOCKS[0]);
+        let mut expected = drain_engine(&get_small_engine(job.clone(), 2));
+        expected.sort();
+
+        let mut registry = PartitionRegistry::default();
+        let engine: DynEngine = Arc::new(get_small_engine(job, 2));
+        let slot = registry.register();
+        // single generator draws work from the engine directly
+        assert!(registry.shard(&engine, Some(&slot)).is_none());
+
+        let other_slot = registry.register();
+        // generator outside of the group does not fork the engine
+        assert!(registry.shard(&engine, None).is_none());
+        let shards = vec![
+            registry.shard(&engine, Some(&slot)).expect("missing shard"),
+            registry
+                .shard(&engine, Some(&other_slot))
+                .expect("missing shard"),
+            registry.shard(&engine, None).expect("missing shard"),
+        ];
+        assert_eq!(shards[0].rank, 0);
+        assert_eq!(shards[1].rank, 1);
+        assert_eq!(registry.partitions.len(), 1);
+
+        // the first generator is faster and it continues with the shard of the other one
+        let mut midstates = vec![];
+        let mut breaks = 0;
+        loop {
+            match shards[0].next_work() {
+                LoopState::Exhausted => break,
+                LoopState::Continue(work) => collect_midstates(&work, &mut midstates),
+                LoopState::Break(work) => {
+                    collect_midstates(&work, &mut midstates);
+                    breaks += 1;
+                }
+            }
+        }
+        for shard in shards.iter() {
+            match shard.next_work() {
+                LoopState::Exhausted => {}
+                _ => panic!("expected 'LoopState::Exhausted'"),
+            }
+        }
+        // the last work of the whole engine is reported only once
+        assert_eq!(breaks, 1);
+        assert!(engine.is_exhausted());
+
+        midstates.sort();
+        assert_eq!(midstates, expected);
+
+        // partition is forgotten together with its engine
+        drop(shards);
+        drop(engine);
+        let engine: DynEngine = Arc::new(ExhaustedWork);
+        assert!(registry.shard(&engine, Some(&slot)).is_none());
+        assert!(registry.partitions.is_empty());
+    }
+
+    #[test]
+    fn test_deposit_bits() {
+        assert_eq!(deposit_bits(0, ii_bitcoin::BIP320_VERSION_MASK), 0);
+        assert_eq!(
+            deposit_bits(
+                ii_bitcoin::BIP320_VERSION_MAX,
+                ii_bitcoin::BIP320_VERSION_MASK
+            ),
+            ii_bitcoin::BIP320_VERSION_MASK
+        );
+        assert_eq!(
+            deposit_bits(0x1234, ii_bitcoin::BIP320_VERSION_MASK),
+            0x1234 << ii_bitcoin::BIP320_VERSION_SHIFT
+        );
+        assert_eq!(deposit_bits(0b101, 0b1101_0000), 0b1001_0000);
+        // bits which do not fit into the mask are ignored
+        assert_eq!(deposit_bits(0b1111, 0b0110), 0b0110);
+    }
+
+    /// Narrower mask of the job takes precedence over the mask requested for the engine
+    #[test]
+    fn test_version_mask() {
+        const VERSION_MASK: u32 = 0x0001_6000;
+
+        for &midstate_count in &[1, 2, 4] {
+            let job = Arc::new(test_utils::MaskedTestBlock::new(
+                test_utils::TEST_BLOCKS[0],
+                VERSION_MASK,
+            ));
+            let engine = VersionRolling::with_version_mask(
+                job.clone(),
+                midstate_count,
+                ii_bitcoin::BIP320_VERSION_MASK,
+            );
+            assert_eq!(engine.version_mask(), VERSION_MASK);
+
+            let mut headers = Vec::new();
+            loop {
+                let work = match engine.next_work() {
+                    LoopState::Exhausted => break,
+                    LoopState::Continue(work) | LoopState::Break(work) => work,
+                };
+                assert_eq!(work.version_mask(), VERSION_MASK);
+                assert!(work.is_version_in_mask());
+                for midstate in work.midstates.iter() {
+                    assert_eq!(
+                        midstate.version & !VERSION_MASK,
+                        job.version() & !VERSION_MASK
+                    );
+                    headers.push((work.ntime, midstate.version));
+                }
+            }
+            // all combinations of the three rolled bits and ntime have been generated
+            let count = headers.len();
+            headers.sort();
+            headers.dedup();
+            assert_eq!(headers.len(), count);
+            assert_eq!(count, 8 * ROLL_NTIME_SECONDS as usize);
+        }
+
+        // the mask does not allow enough versions for all midstates of one work
+        let job = Arc::new(test_utils::MaskedTestBlock::new(
+            test_utils::TEST_BLOCKS[0],
+            0x0000_2000,
+        ));
+        let engine = VersionRolling::new(job, 4);
+        assert!(engine.is_exhausted());
+        assert!(match engine.next_work() {
+            LoopState::Exhausted => true,
+            _ => false,
+        });
+    }
+
+    /// Timestamp is never rolled beyond the limit advertised by the pool
+    #[test]
+    fn test_ntime_roll_limit() {
+        const VERSION_MASK: u32 = 0x0001_6000;
+
+        // ntime roll limit of the job and the expected number of distinct timestamps
+        let limits = [
+            (0, ROLL_NTIME_SECONDS),
+            (1, 2),
+            (7, 8),
+            (ROLL_NTIME_SECONDS - 1, ROLL_NTIME_SECONDS),
+            (ROLL_NTIME_SECONDS, ROLL_NTIME_SECONDS),
+            (std::u32::MAX, ROLL_NTIME_SECONDS),
+        ];
+        for &(limit, ntime_count) in limits.iter() {
+            let job = Arc::new(
+                test_utils::MaskedTestBlock::new(test_utils::TEST_BLOCKS[0], VERSION_MASK)
+                    .with_ntime_roll_limit(limit),
+            );
+            let engine = VersionRolling::new(job.clone(), 2);
+
+            let mut headers = Vec::new();
+            loop {
+                let work = match engine.next_work() {
+                    LoopState::Exhausted => break,
+                    LoopState::Continue(work) | LoopState::Break(work) => work,
+                };
+                assert!(work.ntime >= job.time(), "limit {}", limit);
+                if limit > 0 {
+                    assert!(work.ntime - job.time() <= limit, "limit {}", limit);
+                }
+                collect_midstates(&work, &mut headers);
+            }
+            // the whole allowed space has been generated
+            let count = headers.len();
+            headers.sort();
+            headers.dedup();
+            assert_eq!(headers.len(), count);
+            assert_eq!(count, 8 * ntime_count as usize, "limit {}", limit);
+            assert_eq!(
+                headers.iter().map(|(ntime, _)| *ntime).max(),
+                Some(job.time() + ntime_count - 1)
+            );
+        }
+    }
 }
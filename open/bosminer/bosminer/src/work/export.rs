@@ -0,0 +1,757 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Export of raw solutions to a co-located process through a ring buffer in shared memory. The
+//! buffer is a file (typically in `/dev/shm`) mapped by the miner and by the reader. Each
+//! solution is written as a fixed-size record protected by its own sequence lock so the miner
+//! never waits for the reader: the oldest record is simply overwritten when the buffer is full.
+//!
+//! The file is created again whenever the miner starts so the reader has to reopen it when the
+//! write index stops increasing (the old file is unlinked, not truncated, so a stale mapping of
+//! the reader stays valid). Only solutions which are not malformed are exported.
+//!
+//! # Layout
+//!
+//! All offsets are in bytes and all integers are stored in native byte order of the miner
+//! (little endian on all supported platforms). Counters are 64-bit words accessed atomically.
+//!
+//! Header (64 bytes):
+//!
+//! | offset | type       | field                                                          |
+//! |--------|------------|----------------------------------------------------------------|
+//! | 0      | `[u8; 8]`  | magic `BOSSOLRB` (written last when the buffer is created)     |
+//! | 8      | `u32`      | layout version (1)                                             |
+//! | 12     | `u32`      | record size (128)                                              |
+//! | 16     | `u64`      | capacity (number of record slots)                              |
+//! | 24     | `u64`      | write index (number of records written so far)                 |
+//! | 32     | `u64`      | read index (number of records consumed, updated by the reader) |
+//! | 40     | `u64`      | dropped (records overwritten before they have been consumed)   |
+//! | 48     |            | reserved                                                       |
+//!
+//! Record `n` (counting from zero) is stored in slot `n % capacity` at offset
+//! `64 + slot * 128`:
+//!
+//! | offset | type       | field                                                          |
+//! |--------|------------|----------------------------------------------------------------|
+//! | 0      | `u64`      | sequence lock                                                  |
+//! | 8      | `u64`      | assignment id (sequence number of the work in its generator)   |
+//! | 16     | `u32`      | nonce                                                          |
+//! | 20     | `u32`      | midstate index                                                 |
+//! | 24     | `u32`      | ntime                                                          |
+//! | 28     | `u32`      | version                                                        |
+//! | 32     | `[u8; 32]` | double SHA256 hash of the block header (internal byte order)   |
+//! | 64     | `u64`      | time when the solution has been found (ns since Unix epoch)    |
+//! | 72     | `u64`      | time when it has been fetched from the hardware (ns)           |
+//! | 80     | `u64`      | time when it has been exported (ns)                            |
+//! | 88     | `u64`      | generation of the job broadcast (zero when unknown)            |
+//! | 96     |            | reserved                                                       |
+//!
+//! # Sequence lock
+//!
+//! There is a single writer. Record `n` is written as follows:
+//!
+//! 1. store `2n + 1` (odd) to the sequence lock of its slot and issue a release fence
+//! 2. store the payload
+//! 3. store `2n + 2` (even) to the sequence lock with release ordering
+//! 4. increment `dropped` when the overwritten record `n - capacity` has not been consumed
+//!    (its index is not lower than the read index)
+//! 5. store `n + 1` to the write index with release ordering
+//!
+//! The reader keeps the index `r` of the next record and it reads it when `r` is lower than the
+//! write index (loaded with acquire ordering). When the writer is more than `capacity` records
+//! ahead, the reader skips to `write index - capacity`. The record is read this way:
+//!
+//! 1. load the sequence lock `s1` with acquire ordering: `s1 > 2r + 2` means that the record has
+//!    been overwritten already and it is skipped
+//! 2. copy the payload and issue an acquire fence
+//! 3. load the sequence lock `s2` again: the copy is consistent only when `s1 == s2`, otherwise
+//!    the record has been overwritten while being copied and it is skipped
+//! 4. store `r + 1` to the read index to let the writer account records dropped before the
+//!    reader has consumed them
+//!
+//! `Reader` implements this protocol and it can serve as a reference for external readers.
+
+use crate::clock;
+use crate::work;
+
+use ii_bitcoin::HashTrait as _;
+
+use std::convert::TryInto;
+use std::fs;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{self, AtomicU64, Ordering};
+use std::time;
+
+/// Magic number at the beginning of the file
+pub const MAGIC: [u8; 8] = *b"BOSSOLRB";
+/// Version of the layout of the header and of the records
+pub const LAYOUT_VERSION: u32 = 1;
+pub const HEADER_SIZE: usize = 64;
+pub const RECORD_SIZE: usize = 128;
+
+const MAGIC_OFFSET: usize = 0;
+const LAYOUT_OFFSET: usize = 8;
+const CAPACITY_OFFSET: usize = 16;
+const WRITE_INDEX_OFFSET: usize = 24;
+const READ_INDEX_OFFSET: usize = 32;
+const DROPPED_OFFSET: usize = 40;
+
+/// Size of the record without its sequence lock
+const PAYLOAD_SIZE: usize = RECORD_SIZE - 8;
+const PAYLOAD_WORDS: usize = PAYLOAD_SIZE / 8;
+
+/// Pair of 32-bit values stored in one 64-bit word
+fn pack_u32(first: u32, second: u32) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes[..4].copy_from_slice(&first.to_ne_bytes());
+    bytes[4..].copy_from_slice(&second.to_ne_bytes());
+    u64::from_ne_bytes(bytes)
+}
+
+fn unpack_u32(word: u64) -> (u32, u32) {
+    let bytes = word.to_ne_bytes();
+    (
+        u32::from_ne_bytes(bytes[..4].try_into().expect("BUG: invalid slice")),
+        u32::from_ne_bytes(bytes[4..].try_into().expect("BUG: invalid slice")),
+    )
+}
+
+/// Solution in the form it is exported (times are in nanoseconds since Unix epoch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Record {
+    pub assignment_id: u64,
+    pub nonce: u32,
+    pub midstate_idx: u32,
+    pub ntime: u32,
+    pub version: u32,
+    pub hash: [u8; 32],
+    pub found_time: u64,
+    pub fetch_time: u64,
+    pub export_time: u64,
+    pub generation: u64,
+}
+
+impl Record {
+    /// Capture the `solution` at the time of the export. Returns `None` for malformed solution
+    /// which cannot be hashed.
+    pub fn from_solution(solution: &work::Solution) -> Option<Self> {
+        if solution.is_malformed() {
+            return None;
+        }
+        let now = clock::now();
+        let export_time = clock::system_time();
+        let unix_nanos = |time: time::SystemTime| {
+            time.duration_since(time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos() as u64)
+        };
+        let instant_nanos = |instant: time::Instant| {
+            export_time
+                .checked_sub(now.saturating_duration_since(instant))
+                .map_or(0, unix_nanos)
+        };
+        Some(Self {
+            assignment_id: solution.sequence(),
+            nonce: solution.nonce(),
+            midstate_idx: solution.midstate_idx() as u32,
+            ntime: solution.time(),
+            version: solution.version(),
+            hash: solution.hash().into_inner(),
+            found_time: instant_nanos(solution.timestamp()),
+            fetch_time: instant_nanos(solution.raw_timestamp()),
+            export_time: unix_nanos(export_time),
+            generation: solution.generation().unwrap_or_default(),
+        })
+    }
+
+    fn encode(&self) -> [u64; PAYLOAD_WORDS] {
+        let mut words = [0u64; PAYLOAD_WORDS];
+        words[0] = self.assignment_id;
+        words[1] = pack_u32(self.nonce, self.midstate_idx);
+        words[2] = pack_u32(self.ntime, self.version);
+        for (word, chunk) in words[3..7].iter_mut().zip(self.hash.chunks(8)) {
+            *word = u64::from_ne_bytes(chunk.try_into().expect("BUG: invalid hash chunk"));
+        }
+        words[7] = self.found_time;
+        words[8] = self.fetch_time;
+        words[9] = self.export_time;
+        words[10] = self.generation;
+        words
+    }
+
+    fn decode(words: &[u64; PAYLOAD_WORDS]) -> Self {
+        let (nonce, midstate_idx) = unpack_u32(words[1]);
+        let (ntime, version) = unpack_u32(words[2]);
+        let mut hash = [0u8; 32];
+        for (chunk, word) in hash.chunks_mut(8).zip(&words[3..7]) {
+            chunk.copy_from_slice(&word.to_ne_bytes());
+        }
+        Self {
+            assignment_id: words[0],
+            nonce,
+            midstate_idx,
+            ntime,
+            version,
+            hash,
+            found_time: words[7],
+            fetch_time: words[8],
+            export_time: words[9],
+            generation: words[10],
+        }
+    }
+}
+
+/// Shared mapping of the whole ring buffer file
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// The mapping is only accessed through atomic words
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(file: &fs::File, len: usize) -> io::Result<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+
+    /// Atomic word at `offset` which has to be aligned to 8 bytes
+    fn word(&self, offset: usize) -> &AtomicU64 {
+        assert!(
+            offset % 8 == 0 && offset + 8 <= self.len,
+            "BUG: invalid offset {} of ring buffer word",
+            offset
+        );
+        // The mapping is aligned to the page size so all aligned offsets are aligned in memory
+        unsafe { &*(self.ptr.add(offset) as *const AtomicU64) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+/// Outcome of reading one slot of the ring buffer
+enum SlotRead {
+    Record(Record),
+    /// The record has not been written yet
+    Pending,
+    /// The record has been overwritten with a newer one before or while it has been read
+    Overwritten,
+}
+
+/// Common access to the header and to the slots used by both sides of the ring buffer
+struct Layout {
+    mapping: Mapping,
+    capacity: u64,
+}
+
+impl Layout {
+    fn record_offset(&self, index: u64) -> usize {
+        HEADER_SIZE + (index % self.capacity) as usize * RECORD_SIZE
+    }
+
+    #[inline]
+    fn write_index(&self) -> &AtomicU64 {
+        self.mapping.word(WRITE_INDEX_OFFSET)
+    }
+
+    #[inline]
+    fn read_index(&self) -> &AtomicU64 {
+        self.mapping.word(READ_INDEX_OFFSET)
+    }
+
+    #[inline]
+    fn dropped(&self) -> &AtomicU64 {
+        self.mapping.word(DROPPED_OFFSET)
+    }
+
+    fn write_slot(&self, index: u64, record: &Record) {
+        let offset = self.record_offset(index);
+        let lock = self.mapping.word(offset);
+        lock.store(2 * index + 1, Ordering::Relaxed);
+        atomic::fence(Ordering::Release);
+        for (i, word) in record.encode().iter().enumerate() {
+            self.mapping
+                .word(offset + 8 + i * 8)
+                .store(*word, Ordering::Relaxed);
+        }
+        lock.store(2 * index + 2, Ordering::Release);
+    }
+
+    fn read_slot(&self, index: u64) -> SlotRead {
+        let offset = self.record_offset(index);
+        let lock = self.mapping.word(offset);
+        let expected = 2 * index + 2;
+        let seq = lock.load(Ordering::Acquire);
+        if seq < expected {
+            return SlotRead::Pending;
+        } else if seq > expected {
+            return SlotRead::Overwritten;
+        }
+        let mut words = [0u64; PAYLOAD_WORDS];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = self
+                .mapping
+                .word(offset + 8 + i * 8)
+                .load(Ordering::Relaxed);
+        }
+        atomic::fence(Ordering::Acquire);
+        if lock.load(Ordering::Relaxed) != seq {
+            return SlotRead::Overwritten;
+        }
+        SlotRead::Record(Record::decode(&words))
+    }
+}
+
+/// Writing side of the ring buffer owned by the miner. There must be only one writer.
+pub struct RingBuffer {
+    layout: Layout,
+}
+
+impl RingBuffer {
+    /// Create the ring buffer file at `path` with the total `size` in bytes (it is rounded down
+    /// to whole records). The previous file is unlinked.
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> io::Result<Self> {
+        let capacity = size.saturating_sub(HEADER_SIZE) / RECORD_SIZE;
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("ring buffer size {} cannot hold any record", size),
+            ));
+        }
+        let len = HEADER_SIZE + capacity * RECORD_SIZE;
+
+        let path = path.as_ref();
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        // The file is zeroed so all slots are empty
+        file.set_len(len as u64)?;
+        let mapping = Mapping::new(&file, len)?;
+
+        mapping.word(LAYOUT_OFFSET).store(
+            pack_u32(LAYOUT_VERSION, RECORD_SIZE as u32),
+            Ordering::Relaxed,
+        );
+        mapping
+            .word(CAPACITY_OFFSET)
+            .store(capacity as u64, Ordering::Relaxed);
+        // The header is complete once the magic is visible
+        mapping
+            .word(MAGIC_OFFSET)
+            .store(u64::from_ne_bytes(MAGIC), Ordering::Release);
+
+        Ok(Self {
+            layout: Layout {
+                mapping,
+                capacity: capacity as u64,
+            },
+        })
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> u64 {
+        self.layout.capacity
+    }
+
+    /// Number of records written so far
+    #[inline]
+    pub fn written(&self) -> u64 {
+        self.layout.write_index().load(Ordering::Relaxed)
+    }
+
+    /// Number of records overwritten before the reader has consumed them
+    #[inline]
+    pub fn dropped(&self) -> u64 {
+        self.layout.dropped().load(Ordering::Relaxed)
+    }
+
+    /// Append the `record` overwriting the oldest one when the buffer is full. It never waits
+    /// for the reader.
+    pub fn write(&self, record: &Record) {
+        let index = self.layout.write_index().load(Ordering::Relaxed);
+        self.layout.write_slot(index, record);
+        if index >= self.layout.capacity {
+            let overwritten = index - self.layout.capacity;
+            if self.layout.read_index().load(Ordering::Acquire) <= overwritten {
+                self.layout.dropped().fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.layout
+            .write_index()
+            .store(index + 1, Ordering::Release);
+    }
+
+    /// Export the `solution` (malformed solutions are ignored)
+    #[inline]
+    pub fn export(&self, solution: &work::Solution) {
+        if let Some(record) = Record::from_solution(solution) {
+            self.write(&record);
+        }
+    }
+}
+
+/// Reading side of the ring buffer. It is a reference implementation of the protocol for
+/// external readers and there should be only one reader at a time.
+pub struct Reader {
+    layout: Layout,
+    /// Index of the next record
+    next: u64,
+    /// Number of records skipped by this reader because they have been overwritten
+    missed: u64,
+}
+
+impl Reader {
+    /// Map the ring buffer at `path` and continue with the first record which has not been
+    /// consumed yet
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len < HEADER_SIZE {
+            return Err(invalid("ring buffer is smaller than its header"));
+        }
+        let mapping = Mapping::new(&file, len)?;
+        if mapping.word(MAGIC_OFFSET).load(Ordering::Acquire) != u64::from_ne_bytes(MAGIC) {
+            return Err(invalid("invalid magic of ring buffer"));
+        }
+        let (version, record_size) =
+            unpack_u32(mapping.word(LAYOUT_OFFSET).load(Ordering::Relaxed));
+        if version != LAYOUT_VERSION || record_size as usize != RECORD_SIZE {
+            return Err(invalid("unsupported layout of ring buffer"));
+        }
+        let capacity = mapping.word(CAPACITY_OFFSET).load(Ordering::Relaxed);
+        if capacity == 0 || HEADER_SIZE as u64 + capacity * RECORD_SIZE as u64 > len as u64 {
+            return Err(invalid("invalid capacity of ring buffer"));
+        }
+
+        let layout = Layout { mapping, capacity };
+        let next = layout.read_index().load(Ordering::Relaxed);
+        Ok(Self {
+            layout,
+            next,
+            missed: 0,
+        })
+    }
+
+    /// Number of records skipped by this reader because they have been overwritten
+    #[inline]
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Number of records overwritten before they have been consumed by any reader
+    #[inline]
+    pub fn dropped(&self) -> u64 {
+        self.layout.dropped().load(Ordering::Relaxed)
+    }
+
+    /// Take the next record without waiting for it
+    pub fn try_read(&mut self) -> Option<Record> {
+        loop {
+            let written = self.layout.write_index().load(Ordering::Acquire);
+            if self.next >= written {
+                return None;
+            }
+            // The oldest records have been overwritten already
+            let oldest = written.saturating_sub(self.layout.capacity);
+            if self.next < oldest {
+                self.missed += oldest - self.next;
+                self.next = oldest;
+            }
+            match self.layout.read_slot(self.next) {
+                SlotRead::Record(record) => {
+                    self.next += 1;
+                    self.layout.read_index().store(self.next, Ordering::Release);
+                    return Some(record);
+                }
+                SlotRead::Overwritten => {
+                    self.missed += 1;
+                    self.next += 1;
+                }
+                SlotRead::Pending => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils;
+
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    struct TestFile(PathBuf);
+
+    impl TestFile {
+        fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!(
+                "bosminer-export-{}-{}",
+                name,
+                std::process::id()
+            )))
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Size of the ring buffer with `capacity` records
+    fn size(capacity: usize) -> usize {
+        HEADER_SIZE + capacity * RECORD_SIZE
+    }
+
+    /// Record whose all fields are derived from its `id` so that a torn read is detected
+    fn record(id: u64) -> Record {
+        Record {
+            assignment_id: id,
+            nonce: id as u32,
+            midstate_idx: (id % 4) as u32,
+            ntime: (id >> 32) as u32 ^ 0x5e00_0000,
+            version: !(id as u32),
+            hash: [id as u8; 32],
+            found_time: id.wrapping_mul(3),
+            fetch_time: id.wrapping_mul(5),
+            export_time: id.wrapping_mul(7),
+            generation: !id,
+        }
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_ne_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_layout() {
+        let file = TestFile::new("layout");
+        let ring_buffer = RingBuffer::create(&file.0, size(4) + RECORD_SIZE - 1)
+            .expect("BUG: cannot create ring buffer");
+        // the size is rounded down to whole records
+        assert_eq!(ring_buffer.capacity(), 4);
+        let written = record(0x1234_5678_9abc);
+        ring_buffer.write(&record(0));
+        ring_buffer.write(&written);
+
+        // the file is read independently of the mapping to check the documented layout
+        let bytes = fs::read(&file.0).expect("BUG: cannot read ring buffer");
+        assert_eq!(bytes.len(), size(4));
+        assert_eq!(&bytes[0..8], b"BOSSOLRB");
+        assert_eq!(read_u32(&bytes, 8), LAYOUT_VERSION);
+        assert_eq!(read_u32(&bytes, 12), RECORD_SIZE as u32);
+        assert_eq!(read_u64(&bytes, 16), 4);
+        assert_eq!(read_u64(&bytes, 24), 2);
+        assert_eq!(read_u64(&bytes, 32), 0);
+        assert_eq!(read_u64(&bytes, 40), 0);
+
+        let slot = &bytes[HEADER_SIZE + RECORD_SIZE..HEADER_SIZE + 2 * RECORD_SIZE];
+        // record 1 is complete
+        assert_eq!(read_u64(slot, 0), 4);
+        assert_eq!(read_u64(slot, 8), written.assignment_id);
+        assert_eq!(read_u32(slot, 16), written.nonce);
+        assert_eq!(read_u32(slot, 20), written.midstate_idx);
+        assert_eq!(read_u32(slot, 24), written.ntime);
+        assert_eq!(read_u32(slot, 28), written.version);
+        assert_eq!(&slot[32..64], &written.hash[..]);
+        assert_eq!(read_u64(slot, 64), written.found_time);
+        assert_eq!(read_u64(slot, 72), written.fetch_time);
+        assert_eq!(read_u64(slot, 80), written.export_time);
+        assert_eq!(read_u64(slot, 88), written.generation);
+        assert!(slot[96..].iter().all(|byte| *byte == 0));
+
+        assert!(RingBuffer::create(&file.0, size(1) - 1).is_err());
+    }
+
+    #[test]
+    fn test_read_write() {
+        let file = TestFile::new("read-write");
+        let ring_buffer =
+            RingBuffer::create(&file.0, size(8)).expect("BUG: cannot create ring buffer");
+        let mut reader = Reader::open(&file.0).expect("BUG: cannot open ring buffer");
+        assert_eq!(reader.try_read(), None);
+
+        for id in 0..3 {
+            ring_buffer.write(&record(id));
+        }
+        for id in 0..3 {
+            assert_eq!(reader.try_read(), Some(record(id)));
+        }
+        assert_eq!(reader.try_read(), None);
+
+        // another reader continues with records which have not been consumed yet
+        ring_buffer.write(&record(3));
+        let mut reader = Reader::open(&file.0).expect("BUG: cannot open ring buffer");
+        assert_eq!(reader.try_read(), Some(record(3)));
+        assert_eq!(reader.try_read(), None);
+        assert_eq!(reader.missed(), 0);
+        assert_eq!(ring_buffer.dropped(), 0);
+    }
+
+    #[test]
+    fn test_wraparound() {
+        let file = TestFile::new("wraparound");
+        let ring_buffer =
+            RingBuffer::create(&file.0, size(4)).expect("BUG: cannot create ring buffer");
+        let mut reader = Reader::open(&file.0).expect("BUG: cannot open ring buffer");
+
+        // the oldest records are overwritten without waiting for the reader
+        for id in 0..10 {
+            ring_buffer.write(&record(id));
+        }
+        assert_eq!(ring_buffer.written(), 10);
+        assert_eq!(ring_buffer.dropped(), 6);
+        for id in 6..10 {
+            assert_eq!(reader.try_read(), Some(record(id)));
+        }
+        assert_eq!(reader.try_read(), None);
+        assert_eq!(reader.missed(), 6);
+
+        // records which have been consumed are not dropped when they are overwritten
+        for id in 10..14 {
+            ring_buffer.write(&record(id));
+        }
+        assert_eq!(ring_buffer.dropped(), 6);
+        assert_eq!(reader.dropped(), 6);
+        for id in 10..14 {
+            assert_eq!(reader.try_read(), Some(record(id)));
+        }
+        assert_eq!(reader.missed(), 6);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let file = TestFile::new("invalid");
+        fs::write(&file.0, vec![0u8; size(4)]).expect("BUG: cannot write file");
+        assert_eq!(
+            Reader::open(&file.0).err().map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+        // the file is replaced
+        RingBuffer::create(&file.0, size(4)).expect("BUG: cannot create ring buffer");
+        assert!(Reader::open(&file.0).is_ok());
+    }
+
+    #[test]
+    fn test_concurrent_read_during_write() {
+        const COUNT: u64 = 200_000;
+
+        let file = TestFile::new("concurrent");
+        let ring_buffer =
+            RingBuffer::create(&file.0, size(8)).expect("BUG: cannot create ring buffer");
+        let mut reader = Reader::open(&file.0).expect("BUG: cannot open ring buffer");
+        let done = Arc::new(AtomicBool::new(false));
+
+        let reader_done = done.clone();
+        let reader_thread = thread::spawn(move || {
+            let mut received = 0;
+            let mut last_id = None;
+            loop {
+                // the flag has to be checked before the last attempt so that nothing is missed
+                let finished = reader_done.load(Ordering::Acquire);
+                while let Some(read) = reader.try_read() {
+                    // any torn record would mix fields of different records
+                    assert_eq!(read, record(read.assignment_id));
+                    assert!(last_id.map_or(true, |id| read.assignment_id > id));
+                    last_id = Some(read.assignment_id);
+                    received += 1;
+                }
+                if finished {
+                    break;
+                }
+            }
+            (received, reader.missed(), last_id)
+        });
+
+        for id in 0..COUNT {
+            ring_buffer.write(&record(id));
+        }
+        done.store(true, Ordering::Release);
+
+        let (received, missed, last_id) = reader_thread.join().expect("BUG: reader failed");
+        assert_eq!(received + missed, COUNT);
+        assert_eq!(last_id, Some(COUNT - 1));
+        // the writer can account only records which have not been consumed
+        assert!(ring_buffer.dropped() <= missed);
+    }
+
+    #[test]
+    fn test_export_solution() {
+        let file = TestFile::new("solution");
+        let ring_buffer =
+            RingBuffer::create(&file.0, size(4)).expect("BUG: cannot create ring buffer");
+        let mut reader = Reader::open(&file.0).expect("BUG: cannot open ring buffer");
+        let solution: work::Solution = (&test_utils::TEST_BLOCKS[0]).into();
+
+        ring_buffer.export(&solution);
+        let exported = reader.try_read().expect("BUG: missing record");
+        assert_eq!(exported.assignment_id, solution.sequence());
+        assert_eq!(exported.nonce, solution.nonce());
+        assert_eq!(exported.midstate_idx, solution.midstate_idx() as u32);
+        assert_eq!(exported.ntime, solution.time());
+        assert_eq!(exported.version, solution.version());
+        assert_eq!(exported.hash, solution.hash().into_inner());
+        assert!(exported.found_time <= exported.fetch_time);
+        assert!(exported.fetch_time <= exported.export_time);
+        assert!(exported.found_time > 0);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Filter of duplicate solutions. The backend may occasionally return the same solution twice
+//! (e.g. when the solution FIFO is replayed after an error) and the pool would reject the second
+//! submission as a duplicate share.
+
+use super::Solution;
+
+use ii_bitcoin::HashTrait as _;
+
+use std::collections::{HashSet, VecDeque};
+
+/// Identity of a share from the point of view of the remote server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SolutionKey {
+    job_id: Option<u32>,
+    /// Work generated from the same job with different extra nonce differs only in merkle root
+    merkle_root: [u8; 32],
+    nonce: u32,
+    ntime: u32,
+    version: u32,
+}
+
+impl SolutionKey {
+    fn new(solution: &Solution) -> Self {
+        let job = solution.dyn_job();
+        Self {
+            job_id: job.id(),
+            merkle_root: job.merkle_root().into_inner(),
+            nonce: solution.nonce(),
+            ntime: solution.time(),
+            version: solution.version(),
+        }
+    }
+}
+
+/// Remembers the most recently submitted solutions of one client. The oldest solution is
+/// forgotten when the number of solutions exceeds the window.
+#[derive(Debug)]
+pub struct SolutionFilter {
+    window: usize,
+    keys: HashSet<SolutionKey>,
+    order: VecDeque<SolutionKey>,
+}
+
+impl SolutionFilter {
+    /// Filter with zero `window` lets all solutions through
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            keys: HashSet::with_capacity(window),
+            order: VecDeque::with_capacity(window),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Record the `solution` and return `false` when the same solution is still remembered
+    pub fn insert(&mut self, solution: &Solution) -> bool {
+        if self.window == 0 {
+            return true;
+        }
+        let key = SolutionKey::new(solution);
+        if !self.keys.insert(key) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > self.window {
+            let oldest = self
+                .order
+                .pop_front()
+                .expect("BUG: missing oldest solution");
+            self.keys.remove(&oldest);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::TEST_BLOCKS;
+
+    #[test]
+    fn test_solution_filter() {
+        let mut filter = SolutionFilter::new(2);
+        let solution: Solution = (&TEST_BLOCKS[0]).into();
+        assert!(filter.insert(&solution));
+        assert!(!filter.insert(&solution.clone()));
+        // Replayed solution is a new instance of the same work and nonce
+        assert!(!filter.insert(&(&TEST_BLOCKS[0]).into()));
+
+        // The same nonce of a different job is another share
+        let mut block = TEST_BLOCKS[1];
+        block.nonce = TEST_BLOCKS[0].nonce;
+        assert!(filter.insert(&(&block).into()));
+        assert_eq!(filter.len(), 2);
+
+        // The oldest solution is forgotten when the window is full
+        assert!(filter.insert(&(&TEST_BLOCKS[2]).into()));
+        assert_eq!(filter.len(), 2);
+        assert!(filter.insert(&solution));
+        assert!(!filter.insert(&(&TEST_BLOCKS[2]).into()));
+    }
+
+    #[test]
+    fn test_disabled_solution_filter() {
+        let mut filter = SolutionFilter::new(0);
+        let solution: Solution = (&TEST_BLOCKS[0]).into();
+        assert!(filter.insert(&solution));
+        assert!(filter.insert(&solution));
+        assert!(filter.is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Bounded log of work engine lifecycle events intended for post-mortem analysis of periods when
+//! no work has been solved (whether the engines have been broadcast at all, invalidated,
+//! exhausted or the work solvers have been waiting for them).
+//!
+//! Events are recorded only when an engine changes so the overhead on work generation is
+//! negligible. The log can be mirrored to a JSONL file which survives the crash of the miner
+//! (the file of the previous run is kept with suffix `.old`).
+
+use crate::clock;
+
+use ii_logging::macros::*;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::time;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// New work engine has been generated from a job
+    Broadcast {
+        generation: u64,
+        job_id: Option<u32>,
+    },
+    /// Current work engine has been replaced with an exhausted one
+    Invalidate,
+    /// The last work has been generated from the engine by the work solver
+    Exhausted { solver: String },
+    /// The work solver is waiting for a new engine
+    StarvationStart { solver: String },
+    /// The work solver has received a new engine after `duration_ms`
+    StarvationEnd { solver: String, duration_ms: u64 },
+    /// New block has been seen on the network for the first time in a job of the client
+    BlockChange { block: String, client: String },
+    /// Version mask of a new job does not allow some bits of the previous one and the current
+    /// engine has been terminated
+    VersionMaskNarrowed { previous_mask: u32, mask: u32 },
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Broadcast { .. } => "broadcast",
+            Event::Invalidate => "invalidate",
+            Event::Exhausted { .. } => "exhausted",
+            Event::StarvationStart { .. } => "starvation_start",
+            Event::StarvationEnd { .. } => "starvation_end",
+            Event::BlockChange { .. } => "block_change",
+            Event::VersionMaskNarrowed { .. } => "version_mask_narrowed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Debug)]
+pub struct Recorder {
+    length: usize,
+    records: StdMutex<VecDeque<Record>>,
+    /// Optional file where all records are appended
+    mirror: StdMutex<Option<fs::File>>,
+}
+
+impl Recorder {
+    pub const DEFAULT_LENGTH: usize = 1024;
+
+    pub fn new(length: usize) -> Self {
+        assert!(length > 0, "BUG: work history must not be empty");
+        Self {
+            length,
+            records: StdMutex::new(VecDeque::with_capacity(length)),
+            mirror: StdMutex::new(None),
+        }
+    }
+
+    /// Mirror all records to the file at `path`. The file from the previous run is preserved
+    /// with suffix `.old`.
+    pub fn with_mirror<P: AsRef<Path>>(self, path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut old_path = path.as_os_str().to_owned();
+        old_path.push(".old");
+        if let Err(e) = fs::rename(path, &old_path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                return Err(e);
+            }
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        *self.lock_mirror() = Some(file);
+        Ok(self)
+    }
+
+    fn lock_records(&self) -> StdMutexGuard<VecDeque<Record>> {
+        self.records.lock().expect("BUG: cannot lock work history")
+    }
+
+    fn lock_mirror(&self) -> StdMutexGuard<Option<fs::File>> {
+        self.mirror
+            .lock()
+            .expect("BUG: cannot lock work history mirror")
+    }
+
+    pub fn record(&self, event: Event) {
+        let timestamp = clock::system_time()
+            .duration_since(time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+        let record = Record { timestamp, event };
+
+        let mut mirror = self.lock_mirror();
+        if let Some(file) = mirror.as_mut() {
+            let result = serde_json::to_string(&record)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+                .and_then(|line| writeln!(file, "{}", line));
+            if let Err(e) = result {
+                // stop mirroring instead of flooding the log with the same error
+                error!("Cannot mirror work history: {}", e);
+                mirror.take();
+            }
+        }
+        drop(mirror);
+
+        let mut records = self.lock_records();
+        if records.len() == self.length {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    pub fn records(&self) -> Vec<Record> {
+        self.lock_records().iter().cloned().collect()
+    }
+
+    /// Format the most recent `count` records for a crash report. It does not block so that it
+    /// can be safely called from panic hook.
+    pub fn dump(&self, count: usize) -> String {
+        let records = match self.records.try_lock() {
+            Ok(records) => records,
+            Err(_) => return "work history is not available".to_string(),
+        };
+        let mut output = String::new();
+        for record in records.iter().skip(records.len().saturating_sub(count)) {
+            let _ = writeln!(output, "{} {:?}", record.timestamp, record.event);
+        }
+        output
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LENGTH)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solver_event(index: usize) -> Event {
+        Event::Exhausted {
+            solver: format!("solver {}", index),
+        }
+    }
+
+    #[test]
+    fn test_history_bound() {
+        let recorder = Recorder::new(3);
+        for index in 0..5 {
+            recorder.record(solver_event(index));
+        }
+        let events: Vec<_> = recorder
+            .records()
+            .drain(..)
+            .map(|record| record.event)
+            .collect();
+        assert_eq!(
+            events,
+            vec![solver_event(2), solver_event(3), solver_event(4)]
+        );
+
+        let dump = recorder.dump(2);
+        assert_eq!(dump.lines().count(), 2);
+        assert!(dump.contains("solver 4"));
+        assert!(!dump.contains("solver 2"));
+    }
+
+    #[test]
+    fn test_mirror() {
+        let path = std::env::temp_dir().join(format!(
+            "bosminer-work-history-{}.jsonl",
+            std::process::id()
+        ));
+        let mut old_path = path.clone().into_os_string();
+        old_path.push(".old");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&old_path);
+
+        let recorder = Recorder::new(1)
+            .with_mirror(&path)
+            .expect("BUG: cannot mirror");
+        recorder.record(Event::Broadcast {
+            generation: 1,
+            job_id: Some(10),
+        });
+        recorder.record(Event::StarvationEnd {
+            solver: "solver".to_string(),
+            duration_ms: 20,
+        });
+
+        // the mirror is not bounded by the length of in-memory history
+        let content = fs::read_to_string(&path).expect("BUG: cannot read mirror");
+        let records: Vec<Record> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("BUG: malformed record"))
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1], recorder.records()[0]);
+        assert!(content.starts_with(r#"{"timestamp":"#));
+        assert!(content.contains(r#""event":"broadcast","generation":1,"job_id":10"#));
+
+        // the previous run is preserved
+        let _recorder = Recorder::new(1)
+            .with_mirror(&path)
+            .expect("BUG: cannot mirror");
+        assert_eq!(fs::read_to_string(&old_path).unwrap(), content);
+        assert!(fs::read_to_string(&path).unwrap().is_empty());
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&old_path);
+    }
+}
@@ -20,24 +20,69 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use ii_logging::macros::*;
+
 use super::*;
 use crate::backend;
+use crate::clock;
+use crate::error;
+use crate::log_id;
 use crate::node;
+use crate::stats;
 
 use futures::channel::mpsc;
+use futures::future::{self, Either};
 use futures::lock::Mutex;
 use ii_async_compat::futures;
+use ii_async_compat::tokio;
+
+use ii_bitcoin::MeetsTarget as _;
 
 use std::sync::{Arc, Weak};
 use std::time;
 
 type WorkSolverPath = Vec<Arc<dyn node::WorkSolver>>;
 
+/// Deterministic start of the nonce scan of the work solver at the end of the `path`. Each node
+/// in the path is identified by its id provided by the backend (e.g. hash chain index) or by its
+/// name when it has no id so the offset does not depend on the order in which the nodes have
+/// been created. The multiplication by odd constant (derived from the golden ratio) is a
+/// bijection which spreads siblings with consecutive ids evenly over the whole nonce space.
+fn nonce_offset<'a, I>(path: I) -> u32
+where
+    I: IntoIterator<Item = &'a Arc<dyn node::WorkSolver>>,
+{
+    path.into_iter().fold(0, |offset: u32, node| {
+        let key = match node.get_id() {
+            Some(id) => id as u32,
+            // FNV-1a hash of the name
+            None => node
+                .to_string()
+                .bytes()
+                .fold(0x811c_9dc5, |hash: u32, byte| {
+                    (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+                }),
+        };
+        offset.rotate_left(5) ^ key.wrapping_mul(0x9e37_79b9)
+    })
+}
+
 enum NodeType<T> {
     Base(T),
     WorkHub(T),
 }
 
+/// Source of dedicated engine receivers for work solvers which should not share the engine
+/// receiver of their builder (e.g. hardware test sending its own problems to each hash chain)
+pub trait EngineRouter: Send + Sync {
+    /// Create an engine receiver for a new work solver. The returned route identifies the
+    /// receiver when the created work solver is attached to it.
+    fn open_route(&self) -> (usize, EngineReceiver);
+
+    /// Associate the work solver with the engine receiver of the `route`
+    fn attach(&self, route: usize, work_solver: Arc<dyn node::WorkSolver>);
+}
+
 /// Compound object that is supposed to be sent down to the mining backend for building hierarchy
 /// of work solvers and work hubs (special case of solver which only routes work to its child nodes
 /// and is useful for statistics aggregation and group control). Work solvers can be in turn split
@@ -53,6 +98,10 @@ pub struct SolverBuilder<T> {
     solution_sender: SolutionSender,
     /// Custom hierarchy builder object driven by `SolverBuilder`
     hierarchy_builder: Arc<dyn backend::HierarchyBuilder>,
+    /// Generators of all work solvers created by this builder draw work from partitioned engines
+    partitioned: bool,
+    /// Optional router providing each work solver with its own engine receiver
+    engine_router: Option<Arc<dyn EngineRouter>>,
 }
 
 impl<T> SolverBuilder<T>
@@ -69,11 +118,31 @@ where
             node: NodeType::Base(base_work_solver),
             path: vec![],
             engine_receiver,
-            solution_sender: SolutionSender(solution_sender),
+            solution_sender: SolutionSender::new(solution_sender),
             hierarchy_builder,
+            partitioned: false,
+            engine_router: None,
         }
     }
 
+    /// Route work to each work solver created by this builder and by the builders of its work
+    /// hubs through the engine receiver provided by the `engine_router` instead of the shared one
+    pub fn set_engine_router(&mut self, engine_router: Arc<dyn EngineRouter>) {
+        self.engine_router = Some(engine_router);
+    }
+
+    /// Request partitioned view of work engines for all work solvers created by this builder and
+    /// by the builders of its work hubs. Each generator then iterates its own disjoint part of
+    /// the engine instead of competing with other generators for the shared one.
+    pub fn set_partitioned(&mut self, partitioned: bool) {
+        self.partitioned = partitioned;
+    }
+
+    #[inline]
+    pub fn is_partitioned(&self) -> bool {
+        self.partitioned
+    }
+
     #[inline]
     pub fn to_node(&self) -> &Arc<T> {
         match &self.node {
@@ -125,6 +194,8 @@ where
             engine_receiver: self.engine_receiver.clone(),
             solution_sender: self.solution_sender.clone(),
             hierarchy_builder: self.hierarchy_builder.clone(),
+            partitioned: self.partitioned,
+            engine_router: self.engine_router.clone(),
         }
     }
 
@@ -140,24 +211,42 @@ where
         let inner_work_solver = Arc::new(Mutex::new(None));
 
         let path = self.get_path();
-        let work_generator = Generator::new(
-            self.engine_receiver.clone(),
-            path,
-            inner_work_solver.clone(),
-        );
-        let solution_sender = self.solution_sender.clone();
+        let (route, engine_receiver) = match &self.engine_router {
+            Some(engine_router) => {
+                let (route, engine_receiver) = engine_router.open_route();
+                (Some(route), engine_receiver)
+            }
+            None => (None, self.engine_receiver.clone()),
+        };
+        let mut work_generator = Generator::new(engine_receiver, path, inner_work_solver.clone());
+        work_generator.set_partitioned(self.partitioned);
+        // each work solver has its own solution sender which knows its owner
+        let solution_sender = SolutionSender::new(self.solution_sender.sender.clone());
+        let solution_sender_owner = solution_sender.work_solver.clone();
 
         let work_solver = Arc::new(create(work_generator, solution_sender));
         self.call_hierarchy_builder(node::WorkSolverType::WorkSolver(work_solver.clone()))
             .await;
 
         // create weak reference to newly created work solver to prevent circular dependency
-        *inner_work_solver.lock().await = Some(Arc::downgrade(
-            &(work_solver.clone() as Arc<dyn node::WorkSolver>),
-        ));
+        let weak_work_solver = Arc::downgrade(&(work_solver.clone() as Arc<dyn node::WorkSolver>));
+        *inner_work_solver.lock().await = Some(weak_work_solver.clone());
+        solution_sender_owner
+            .set(weak_work_solver)
+            .expect("BUG: solution sender already has an owner");
+        if let (Some(engine_router), Some(route)) = (&self.engine_router, route) {
+            engine_router.attach(route, work_solver.clone());
+        }
 
         work_solver
     }
+
+    /// Remove the work solver created by any builder of the hierarchy (e.g. when its hash board
+    /// has been unplugged or disabled). Its generator stops providing work and the node is
+    /// reported as removed while its statistics are kept.
+    pub async fn remove_work_solver(&self, node: Arc<dyn node::WorkSolver>) {
+        self.hierarchy_builder.remove_work_solver(node).await;
+    }
 }
 
 /// Generator is responsible for accepting a `WorkEngine` and draining as much
@@ -172,6 +261,33 @@ pub struct Generator {
     work_solver: Arc<Mutex<Option<Weak<dyn node::WorkSolver>>>>,
     /// Source of trait objects that implement `WorkEngine` interface
     engine_receiver: EngineReceiver,
+    /// Number of distinct work engines this generator has drawn work from
+    engine_generation: u64,
+    /// The last work engine used for detection of a new one
+    last_engine: Option<Weak<dyn Engine>>,
+    /// Job of a new engine which has not delivered any assignment yet
+    first_assignment: Option<Arc<JobBroadcast>>,
+    /// Position of this generator in the order of staggered wake after a new job
+    fan_out: Arc<FanOutSlot>,
+    /// Membership in the group of generators drawing work from partitioned engines
+    partition: Option<Arc<PartitionSlot>>,
+    /// Part of the last work engine reserved for this generator when the engine is partitioned
+    shard: Option<Shard>,
+    /// Maximal age of assignments overriding the limit shared by the engine receiver
+    assignment_max_age: Option<time::Duration>,
+    /// Session of the work audit cached by this generator
+    audit: audit::Probe,
+    /// Assignment taken from the engine which has not been returned by `generate` yet
+    ready: Option<Assignment>,
+    /// Sequence number of the last assignment generated by this generator
+    sequence: u64,
+    /// Sequence number of the last assignment dispatched to the hardware or skipped by the
+    /// backend
+    dispatched_sequence: u64,
+    /// Whether the hardware supports programmable start of the nonce scan
+    nonce_offset_enabled: bool,
+    /// Start of the nonce scan of all assignments resolved from the path of the work solver
+    nonce_offset: Option<u32>,
 }
 
 impl Generator {
@@ -180,15 +296,242 @@ impl Generator {
         path: WorkSolverPath,
         work_solver: Arc<Mutex<Option<Weak<dyn node::WorkSolver>>>>,
     ) -> Self {
+        let fan_out = engine_receiver.register_fan_out();
         Self {
             path,
             work_solver,
             engine_receiver,
+            engine_generation: 0,
+            last_engine: None,
+            first_assignment: None,
+            fan_out,
+            partition: None,
+            shard: None,
+            assignment_max_age: None,
+            audit: Default::default(),
+            ready: None,
+            sequence: 0,
+            dispatched_sequence: 0,
+            nonce_offset_enabled: false,
+            nonce_offset: None,
         }
     }
 
+    /// Set weight of the work solver (typically its nominal hash rate) used for ordering of
+    /// staggered wake after a new job. Generators with higher weight are woken sooner.
+    pub fn set_fan_out_weight(&self, weight: u64) {
+        self.fan_out.set_weight(weight);
+    }
+
+    /// Draw work from a disjoint part of each engine shared with other partitioned generators
+    /// instead of competing for the whole engine. The engine is partitioned only when there are
+    /// at least two generators in the group.
+    pub fn set_partitioned(&mut self, partitioned: bool) {
+        self.partition = if partitioned {
+            Some(
+                self.partition
+                    .take()
+                    .unwrap_or_else(|| self.engine_receiver.register_partition()),
+            )
+        } else {
+            None
+        };
+    }
+
+    #[inline]
+    pub fn is_partitioned(&self) -> bool {
+        self.partition.is_some()
+    }
+
+    /// Start nonce scan of all assignments at an offset derived from the path of the work solver
+    /// so that sibling work solvers fed with the same work do not race over the same nonces. It
+    /// should be enabled only by backends whose hardware can program start of the nonce range.
+    /// The offset is resolved with the first generated work because the work solver is usually
+    /// created after its generator.
+    pub fn set_nonce_offset(&mut self, enabled: bool) {
+        self.nonce_offset_enabled = enabled;
+        self.nonce_offset = None;
+    }
+
+    /// Return start of the nonce scan or `None` when the nonce offset is not enabled or it has
+    /// not been resolved yet
+    #[inline]
+    pub fn nonce_offset(&self) -> Option<u32> {
+        self.nonce_offset
+    }
+
+    /// Override maximal age of assignments submitted to the hardware by the work solver. The
+    /// `None` falls back to the limit shared by all generators of the engine receiver.
+    pub fn set_assignment_max_age(&mut self, max_age: Option<time::Duration>) {
+        self.assignment_max_age = max_age;
+    }
+
+    /// Return effective maximal age of assignments or `None` when it is not limited
+    pub fn assignment_max_age(&self) -> Option<time::Duration> {
+        self.assignment_max_age
+            .or_else(|| self.engine_receiver.assignment_max_age())
+    }
+
+    /// Check if the work has been generated after the last invalidation of the engine channel.
+    /// Work queued in the backend which is not current anymore should not be sent to the
+    /// hardware.
+    #[inline]
+    pub fn is_current(&self, work: &Assignment) -> bool {
+        work.invalidation() == self.engine_receiver.invalidation_receiver().current()
+    }
+
+    /// Counter of invalidations of the engine channel which can be awaited independently of
+    /// the generator
+    pub fn invalidation_receiver(&self) -> InvalidationReceiver {
+        self.engine_receiver.invalidation_receiver().clone()
+    }
+
+    /// Check if the work is still fresh enough right before it is submitted to the hardware.
+    /// Expired work is accounted in all work solvers in the path and it should be skipped by the
+    /// backend.
+    pub async fn is_fresh(&self, work: &Assignment) -> bool {
+        let max_age = match self.assignment_max_age() {
+            Some(value) => value,
+            None => return true,
+        };
+        if work.is_fresh(max_age) {
+            return true;
+        }
+        let work_solver = self
+            .work_solver
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|work_solver| work_solver.upgrade());
+        for node in self.path.iter().chain(work_solver.iter()) {
+            node.work_solver_stats().expired_assignments().inc();
+        }
+        trace!(
+            "{}Skipping assignment older than {} ms",
+            log_id::Prefix::assignment(work),
+            max_age.as_millis()
+        );
+        false
+    }
+
+    /// Account the work which has been submitted to the hardware in all work solvers in the path.
+    /// The backend should call it for each assignment returned by `generate` unless it skips
+    /// the assignment.
+    pub async fn account_dispatched(&mut self, work: &Assignment) {
+        work.set_dispatch_time(clock::now());
+        let work_amount = work.generated_work_amount() as u64;
+        let work_solver = self
+            .work_solver
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|work_solver| work_solver.upgrade());
+        for node in self.path.iter().chain(work_solver.iter()) {
+            node.work_solver_stats().dispatched_work().add(work_amount);
+        }
+        self.audit_dispatch(work);
+    }
+
+    /// Mark the assignment which has been intentionally dropped by the backend (e.g. it is not
+    /// current or fresh anymore) so that it is not reported as lost
+    pub fn account_skipped(&mut self, work: &Assignment) {
+        self.audit_dispatch(work);
+    }
+
+    /// Report assignments which have been generated but neither dispatched nor skipped by the
+    /// backend
+    fn audit_dispatch(&mut self, work: &Assignment) {
+        let sequence = work.sequence();
+        #[cfg(feature = "dispatch-audit")]
+        {
+            let expected = self.dispatched_sequence + 1;
+            if sequence > expected {
+                warn!(
+                    "{}Assignments {}..={} have been lost before dispatch",
+                    log_id::Prefix::assignment(work),
+                    expected,
+                    sequence - 1
+                );
+            }
+        }
+        self.dispatched_sequence = self.dispatched_sequence.max(sequence);
+    }
+
+    /// Increment engine generation when the engine differs from the previous one.
+    /// Returns true when the new engine has been generated from a new job.
+    fn observe_engine(&mut self, broadcast: &EngineBroadcast) -> bool {
+        let engine = Arc::downgrade(&broadcast.engine);
+        if !self
+            .last_engine
+            .as_ref()
+            .map_or(false, |last_engine| last_engine.ptr_eq(&engine))
+        {
+            self.engine_generation += 1;
+            self.last_engine = Some(engine);
+            self.shard = self
+                .engine_receiver
+                .shard(&broadcast.engine, self.partition.as_ref());
+            self.first_assignment = broadcast.job.clone();
+            return self.first_assignment.is_some();
+        }
+        false
+    }
+
+    /// Account latency of the first assignment from a new job in all work solvers in the path
+    fn account_first_assignment(&mut self, work_solver: &Arc<dyn node::WorkSolver>) {
+        if let Some(job) = self.first_assignment.take() {
+            let (latency, lag) = job.account_delivery(clock::now());
+            for node in self.path.iter().chain(iter::once(work_solver)) {
+                node.work_solver_stats()
+                    .job_latency()
+                    .account(job.generation, latency, lag);
+            }
+        }
+    }
+
+    /// Wait for a new engine and record how long the work solver has been starving (excessive
+    /// starvation is reported once per interval)
+    async fn wait_for_broadcast(
+        &mut self,
+        work_solver: &Arc<dyn node::WorkSolver>,
+    ) -> Option<EngineBroadcast> {
+        let solver = work_solver.to_string();
+        let recorder = self.engine_receiver.history().clone();
+        recorder.record(history::Event::StarvationStart {
+            solver: solver.clone(),
+        });
+        let start = clock::now();
+        let broadcast = self.engine_receiver.get_broadcast().await;
+        let now = clock::now();
+        let duration = now.duration_since(start);
+        if let Some(starvation) = work_solver
+            .work_solver_stats()
+            .work_starvation()
+            .account(duration, now)
+        {
+            warn!(
+                "Work solver '{}' has been starving for work {} ms within {} s",
+                solver,
+                starvation.as_millis(),
+                stats::WorkStarvation::INTERVAL.as_secs()
+            );
+        }
+        recorder.record(history::Event::StarvationEnd {
+            solver,
+            duration_ms: duration.as_millis() as u64,
+        });
+        broadcast
+    }
+
     /// Loops until new work is available or no more `WorkEngines` are supplied (signals
     /// Generator shutdown)
+    ///
+    /// The method is cancellation safe. Once the assignment is taken from the engine, it is kept
+    /// in `ready` during the remaining await points and the next call returns it immediately
+    /// when the previous future has been dropped.
+    ///
+    /// The generator of removed work solver does not provide any work anymore and it leaves the
+    /// partitioned engines to the other generators.
     pub async fn generate(&mut self) -> Option<Assignment> {
         let work_solver = self
             .work_solver
@@ -198,25 +541,97 @@ impl Generator {
             .expect("BUG: calling work generator before full registration")
             .upgrade()
             .expect("BUG: calling work generator after node destruction");
+        let removal = work_solver.work_solver_stats().removal();
+        if removal.is_removed() {
+            self.release();
+            return None;
+        }
+        if let Some(work) = self.ready.take() {
+            return Some(work);
+        }
+
+        let work = {
+            let next_work = self.next_work(&work_solver);
+            let removed = removal.wait_for_removal();
+            futures::pin_mut!(next_work, removed);
+            match future::select(next_work, removed).await {
+                Either::Left((work, _)) => Some(work),
+                Either::Right(_) => None,
+            }
+        };
+        work.unwrap_or_else(|| {
+            self.release();
+            None
+        })
+    }
 
+    /// Drop all work and the membership in the partition group of the removed work solver
+    fn release(&mut self) {
+        self.ready = None;
+        self.partition = None;
+        self.shard = None;
+    }
+
+    async fn next_work(&mut self, work_solver: &Arc<dyn node::WorkSolver>) -> Option<Assignment> {
         loop {
-            let engine = match self.engine_receiver.get_engine().await {
-                // end of stream
-                None => return None,
+            // paused work solver does not get any work as if all engines were exhausted
+            work_solver
+                .work_solver_stats()
+                .pause()
+                .wait_for_resume()
+                .await;
+            // the counter must be read before the engine so that the work from an invalidated
+            // engine cannot be marked as current
+            let invalidation = self.engine_receiver.invalidation_receiver().current();
+            let broadcast = match self.engine_receiver.current_broadcast() {
                 Some(value) => value,
+                None => match self.wait_for_broadcast(&work_solver).await {
+                    // end of stream
+                    None => return None,
+                    Some(value) => value,
+                },
             };
+            if self.observe_engine(&broadcast) {
+                // give way to the generators with higher priority when the job is fanned out
+                if let Some(delay) = self.engine_receiver.fan_out_delay(&self.fan_out) {
+                    tokio::time::delay_for(delay).await;
+                    // the engine could have been replaced in the meantime
+                    continue;
+                }
+            }
+            let generation = broadcast.job.as_ref().map(|job| job.generation);
+            let engine = broadcast.engine;
             // try to generate new work from engine
-            let mut work = match engine.next_work() {
+            let next_work = match &self.shard {
+                Some(shard) => shard.next_work(),
+                None => engine.next_work(),
+            };
+            let mut work = match next_work {
                 // one or more competing work engines are exhausted
                 // try to gen new work engine
                 // NOTE: this can happen simultaneously for multiple parallel generators because
                 // only one can win the last work and so there should not be included any logging
-                LoopState::Exhausted => continue,
+                LoopState::Exhausted => {
+                    // the engine could have been partitioned by other generators in the meantime
+                    if self.shard.is_none() {
+                        self.shard = self.engine_receiver.shard(&engine, self.partition.as_ref());
+                    }
+                    continue;
+                }
                 // consecutive call of work engine may return new work
                 LoopState::Continue(value) => value,
                 // tha last work is returned from work engine (the work is exhausted)
                 LoopState::Break(value) => {
+                    work_solver
+                        .work_solver_stats()
+                        .work_starvation()
+                        .account_exhausted();
                     // inform about this event
+                    self.engine_receiver
+                        .history()
+                        .record(history::Event::Exhausted {
+                            solver: work_solver.to_string(),
+                        });
                     self.engine_receiver.handle_exhausted(engine.clone());
                     value
                 }
@@ -225,16 +640,37 @@ impl Generator {
             let work_amount = work.generated_work_amount() as u64;
             // account generated work on the client side
             if let Some(origin) = work.origin().upgrade() {
-                origin.client_stats().generated_work().add(work_amount);
+                let client_stats = origin.client_stats();
+                client_stats.generated_work().add(work_amount);
+                client_stats
+                    .job_history()
+                    .account(work.dyn_job(), stats::JobActivity::Assignment);
+                self.audit.record(
+                    self.engine_receiver.audit(),
+                    &work_solver,
+                    &origin,
+                    work_amount,
+                );
             } else {
                 // Origin has been removed and no one will receive any solution
                 engine.terminate();
                 continue;
             }
 
+            self.sequence += 1;
+            work.sequence = self.sequence;
+            work.generation = generation;
+            work.invalidation = invalidation;
+            work.job_target = work.job.target();
+            if self.nonce_offset_enabled && self.nonce_offset.is_none() {
+                self.nonce_offset = Some(nonce_offset(
+                    self.path.iter().chain(iter::once(work_solver)),
+                ));
+            }
+            work.nonce_start = self.nonce_offset.unwrap_or_default();
             // account generated work in all work solvers in the path
-            let now = time::SystemTime::now();
-            for node in self.path.iter().chain(iter::once(&work_solver)) {
+            let now = clock::system_time();
+            for node in self.path.iter().chain(iter::once(work_solver)) {
                 let work_solver_stats = node.work_solver_stats();
                 // Arc does not support dynamic casting to trait bounds so there must be used
                 // another Arc indirection with implemented `node::Info` trait.
@@ -242,22 +678,1285 @@ impl Generator {
                 // impl<T: ?Sized + Info> Info for Arc<T> {}
                 work.path.push(Arc::new(node.clone()));
                 work_solver_stats.generated_work().add(work_amount);
-                work_solver_stats.last_work_time().touch(now).await;
+                work_solver_stats
+                    .work_progress()
+                    .account_assignment(self.engine_generation, now);
             }
-            return Some(work);
+            self.account_first_assignment(&work_solver);
+            self.ready = Some(work);
+            for node in self.path.iter().chain(iter::once(work_solver)) {
+                node.work_solver_stats().last_work_time().touch(now).await;
+            }
+            return self.ready.take();
         }
     }
 }
 
+/// Mark the work solver as removed and return false when it has been already removed. Its
+/// generator ends as if there were no more work engines.
+pub fn set_removed(work_solver: &dyn node::WorkSolver) -> bool {
+    let removed = work_solver.work_solver_stats().removal().set_removed();
+    if removed {
+        info!("Work solver '{}' has been removed", work_solver);
+    }
+    removed
+}
+
+/// Pause or resume mining on the work solver and return true when its state has been changed.
+/// See `node::WorkSolver::pause` and `node::WorkSolver::resume` for details.
+pub async fn set_paused(work_solver: &dyn node::WorkSolver, paused: bool) -> error::Result<bool> {
+    if paused {
+        work_solver.pause().await
+    } else {
+        work_solver.resume().await
+    }
+}
+
 /// This struct is to be passed to the underlying mining backend. It allows submission of
 /// `work::Solution`
 #[derive(Debug, Clone)]
-pub struct SolutionSender(mpsc::UnboundedSender<Solution>);
+pub struct SolutionSender {
+    sender: mpsc::UnboundedSender<Solution>,
+    /// Work solver node which submits solutions through this sender (it is not known for
+    /// the sender owned by `SolverBuilder`)
+    work_solver: Arc<OnceCell<Weak<dyn node::WorkSolver>>>,
+    /// Time of the last malformed solution used for detecting bursts of them
+    last_malformed: Arc<StdMutex<Option<time::Instant>>>,
+}
 
 impl SolutionSender {
+    /// Malformed solutions closer to each other than this interval belong to the same burst
+    const MALFORMED_BURST_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
+    fn new(sender: mpsc::UnboundedSender<Solution>) -> Self {
+        Self {
+            sender,
+            work_solver: Arc::new(OnceCell::new()),
+            last_malformed: Arc::new(StdMutex::new(None)),
+        }
+    }
+
+    /// Wall clock time when the `solution` has been found by the backend. It is derived from the
+    /// time when the solution has been fetched from the backend which can be a while ago.
+    fn found_system_time(solution: &Solution) -> time::SystemTime {
+        let now = clock::system_time();
+        let elapsed = clock::now().saturating_duration_since(solution.raw_timestamp());
+        now.checked_sub(elapsed + solution.age()).unwrap_or(now)
+    }
+
+    /// Account malformed solution to its work solver and log it (only the first one from
+    /// a burst is logged to prevent flooding the log with broken backend)
+    fn drop_malformed(&self, solution: Solution) {
+        if let Some(work_solver) = self.work_solver.get().and_then(|node| node.upgrade()) {
+            work_solver.work_solver_stats().malformed_solutions().inc();
+        }
+
+        let now = clock::now();
+        let previous = self
+            .last_malformed
+            .lock()
+            .expect("BUG: cannot lock malformed solution timestamp")
+            .replace(now);
+        if previous.map_or(true, |time| {
+            now.duration_since(time) >= Self::MALFORMED_BURST_INTERVAL
+        }) {
+            warn!(
+                "{} Dropping malformed solution: nonce={:08x} midstate_idx={} (midstates={}) \
+                 solution_idx={} (solutions={:?})",
+                log_id::Prefix::solution(&solution),
+                solution.nonce(),
+                solution.midstate_idx(),
+                solution.work.midstates.len(),
+                solution.solution_idx(),
+                solution.solution.solution_count()
+            );
+        }
+    }
+
+    /// Verify solution of quarantined work solver and account it to the dedicated quarantine
+    /// stage instead of submitting it
+    fn withhold(work_solver: &Arc<dyn node::WorkSolver>, solution: Solution) {
+        let valid = solution.hash().meets(solution.backend_target());
+        work_solver
+            .work_solver_stats()
+            .quarantine()
+            .account_solution(valid);
+    }
+
+    /// Account solution which does not meet the backend target to the work solver which has
+    /// found it. Such solution is a result of hardware error and it is never submitted but it is
+    /// still passed to the router which accounts it in the error statistics of the whole path.
+    fn account_hardware_error(
+        work_solver: &Option<Arc<dyn node::WorkSolver>>,
+        solution: &Solution,
+    ) {
+        if let Some(work_solver) = work_solver {
+            work_solver.work_solver_stats().hardware_errors().inc();
+        }
+        trace!(
+            "{} Solution not meeting backend target: nonce={:08x}",
+            log_id::Prefix::solution(solution),
+            solution.nonce()
+        );
+    }
+
     pub fn send(&self, solution: Solution) {
-        self.0
+        let work_solver = self.work_solver.get().and_then(|node| node.upgrade());
+        if let Some(work_solver) = &work_solver {
+            let work_solver_stats = work_solver.work_solver_stats();
+            work_solver_stats
+                .work_progress()
+                .account_solution(solution.nonce(), Self::found_system_time(&solution));
+            if let Some(latency) = solution.latency() {
+                work_solver_stats
+                    .solution_latency()
+                    .account(latency, solution.hw_timestamp());
+            }
+        }
+        if solution.is_malformed() {
+            self.drop_malformed(solution);
+            return;
+        }
+        if let Some(work_solver) = &work_solver {
+            if work_solver.work_solver_stats().quarantine().is_enabled() {
+                Self::withhold(work_solver, solution);
+                return;
+            }
+        }
+        // the hash is evaluated only once and it is reused by all consumers of the solution
+        if !solution.hash().meets(solution.backend_target()) {
+            Self::account_hardware_error(&work_solver, &solution);
+        } else if let Some(work_solver) = &work_solver {
+            // valid solution is the best evidence that the device works well
+            work_solver
+                .work_solver_stats()
+                .device_health()
+                .account_well(Self::found_system_time(&solution));
+        }
+        self.sender
             .unbounded_send(solution)
             .expect("solution queue send failed");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::{Stats as _, WorkSolver as _, WorkSolverStats as _};
+    use crate::test_utils;
+
+    use futures::future::FutureExt;
+    use futures::stream::StreamExt;
+    use ii_async_compat::tokio;
+    use ii_bitcoin::MeetsTarget as _;
+    use tokio::time::delay_for;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn set_owner(solution_sender: &SolutionSender, work_solver: Arc<dyn node::WorkSolver>) {
+        solution_sender
+            .work_solver
+            .set(Arc::downgrade(&work_solver))
+            .expect("BUG: cannot set owner of solution sender");
+    }
+
+    #[test]
+    fn test_malformed_solution() {
+        let work_solver = test_utils::create_test_work_solver();
+        let (sender, mut receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        // test block work has only one midstate
+        for midstate_idx in &[5, 1] {
+            let solution = Solution::new(
+                Assignment::from(test_block),
+                test_utils::TestSolution::new(test_block).with_midstate_idx(*midstate_idx),
+                None,
+            );
+            assert!(solution.is_malformed());
+            solution_sender.send(solution);
+        }
+        solution_sender.send(test_block.into());
+
+        let malformed_solutions = work_solver
+            .work_solver_stats()
+            .malformed_solutions()
+            .take_snapshot();
+        assert_eq!(*malformed_solutions, 2);
+
+        // only the valid solution has been passed through
+        drop(solution_sender);
+        let solutions: Vec<_> = futures::executor::block_on(receiver.collect());
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].midstate_idx(), 0);
+    }
+
+    #[test]
+    fn test_hardware_error() {
+        let work_solver = test_utils::create_test_work_solver();
+        let (sender, receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        let corrupted_nonce = test_block.nonce ^ 0x0100_0000;
+        solution_sender.send(Solution::new(
+            Assignment::from(test_block),
+            test_utils::TestSolution::new(test_block).with_nonce(corrupted_nonce),
+            None,
+        ));
+        solution_sender.send(test_block.into());
+
+        let hardware_errors = work_solver
+            .work_solver_stats()
+            .hardware_errors()
+            .take_snapshot();
+        assert_eq!(*hardware_errors, 1);
+
+        // the corrupted solution is passed to the router as well to account it on the whole path
+        drop(solution_sender);
+        let nonces: Vec<_> = futures::executor::block_on(receiver.collect::<Vec<_>>())
+            .iter()
+            .map(|solution| solution.nonce())
+            .collect();
+        assert_eq!(nonces, vec![corrupted_nonce, test_block.nonce]);
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let (sender, receiver) = mpsc::unbounded();
+        let work_solvers: Vec<_> = (0..2)
+            .map(|id| Arc::new(test_utils::TestWorkSolver::with_id(id)))
+            .collect();
+        let solution_senders: Vec<_> = work_solvers
+            .iter()
+            .map(|work_solver| {
+                let solution_sender = SolutionSender::new(sender.clone());
+                set_owner(&solution_sender, work_solver.clone());
+                solution_sender
+            })
+            .collect();
+        drop(sender);
+
+        let quarantine = work_solvers[0].work_solver_stats().quarantine();
+        assert!(quarantine.set_enabled(true));
+        assert!(!quarantine.set_enabled(true));
+
+        // the second solution is corrupted by a nonce from another block
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        let corrupted_nonce = test_utils::TEST_BLOCKS[1].nonce;
+        for solution_sender in &solution_senders {
+            solution_sender.send(test_block.into());
+            solution_sender.send(Solution::new(
+                Assignment::from(test_block),
+                test_utils::TestSolution::new(&test_utils::TEST_BLOCKS[1]),
+                None,
+            ));
+        }
+
+        // solutions of the quarantined work solver are verified, counted and withheld
+        let snapshot = quarantine.take_snapshot();
+        assert!(snapshot.enabled);
+        assert_eq!(snapshot.valid, 1);
+        assert_eq!(snapshot.invalid, 1);
+        assert_eq!(snapshot.events.len(), 1);
+        assert!(snapshot.events[0].enabled);
+        // but the work progress is still observed
+        let progress = work_solvers[0]
+            .work_solver_stats()
+            .work_progress()
+            .take_snapshot();
+        assert_eq!(progress.last_nonce, Some(corrupted_nonce));
+
+        // the other work solver is not affected
+        let snapshot = work_solvers[1]
+            .work_solver_stats()
+            .quarantine()
+            .take_snapshot();
+        assert!(!snapshot.enabled);
+        assert_eq!(snapshot.valid, 0);
+        assert_eq!(snapshot.invalid, 0);
+        assert!(snapshot.events.is_empty());
+        // its corrupted solution is counted as hardware error and it is left to the router
+        let hardware_errors = work_solvers[1]
+            .work_solver_stats()
+            .hardware_errors()
+            .take_snapshot();
+        assert_eq!(*hardware_errors, 1);
+
+        drop(solution_senders);
+        let nonces: Vec<_> = futures::executor::block_on(receiver.collect::<Vec<_>>())
+            .iter()
+            .map(|solution| solution.nonce())
+            .collect();
+        assert_eq!(nonces, vec![test_block.nonce, corrupted_nonce]);
+
+        // leaving the quarantine is recorded as well
+        assert!(quarantine.set_enabled(false));
+        let snapshot = quarantine.take_snapshot();
+        assert!(!snapshot.enabled);
+        assert_eq!(snapshot.events.len(), 2);
+        assert!(!snapshot.events[1].enabled);
+    }
+
+    #[tokio::test]
+    async fn test_work_progress() {
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        let (sender, _receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+
+        let progress = || {
+            work_solver
+                .work_solver_stats()
+                .work_progress()
+                .take_snapshot()
+        };
+        let initial = progress();
+        assert_eq!(initial.last_assignment_id, None);
+        assert_eq!(initial.last_assignment_time, None);
+        assert_eq!(initial.last_nonce, None);
+        assert_eq!(initial.last_solution_time, None);
+        assert_eq!(initial.engine_generation, 0);
+
+        // each engine provides all test blocks so the second engine starts from the first block
+        let mut assignment_id = 0;
+        for engine_generation in 1..=2 {
+            engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+            for test_block in test_utils::TEST_BLOCKS.iter().take(2) {
+                let work = generator.generate().await.expect("BUG: missing test work");
+                assignment_id += 1;
+                let current = progress();
+                assert_eq!(current.last_assignment_id, Some(assignment_id));
+                assert!(current.last_assignment_time.is_some());
+                assert_eq!(current.engine_generation, engine_generation);
+
+                solution_sender.send(Solution::new(
+                    work,
+                    test_utils::TestSolution::new(test_block),
+                    None,
+                ));
+                let current = progress();
+                assert_eq!(current.last_nonce, Some(test_block.nonce));
+                assert!(current.last_solution_time.is_some());
+            }
+        }
+
+        // stop the solver by closing its work source
+        engine_sender.invalidate();
+        drop(engine_sender);
+        let last = progress();
+        assert!(generator.generate().await.is_none());
+        delay_for(time::Duration::from_millis(10)).await;
+
+        let frozen = progress();
+        assert_eq!(frozen.last_assignment_id, last.last_assignment_id);
+        assert_eq!(frozen.last_assignment_time, last.last_assignment_time);
+        assert_eq!(frozen.last_nonce, last.last_nonce);
+        assert_eq!(frozen.last_solution_time, last.last_solution_time);
+        assert_eq!(frozen.engine_generation, last.engine_generation);
+    }
+
+    #[tokio::test]
+    async fn test_work_progress_age_hint() {
+        const AGE: time::Duration = time::Duration::from_secs(3600);
+
+        let work_solver = test_utils::create_test_work_solver();
+        let (sender, _receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        let before = clock::system_time();
+        solution_sender.send(Solution::new(
+            Assignment::from(test_block),
+            test_utils::TestSolution::new(test_block).with_age_hint(AGE),
+            None,
+        ));
+        let after = clock::system_time();
+
+        // the solution is accounted at the time when the backend has found it
+        let last_solution_time = work_solver
+            .work_solver_stats()
+            .work_progress()
+            .take_snapshot()
+            .last_solution_time
+            .expect("BUG: missing solution time");
+        // the progress is stored with millisecond resolution
+        let resolution = time::Duration::from_millis(1);
+        assert!(last_solution_time + resolution >= before - AGE);
+        assert!(last_solution_time <= after - AGE);
+    }
+
+    /// Latency of solutions is measured from the dispatch of their work and only solutions of
+    /// dispatched work are accounted
+    #[tokio::test]
+    async fn test_solution_latency() {
+        const LATENCY: time::Duration = time::Duration::from_millis(250);
+        const HW_LATENCY: time::Duration = time::Duration::from_millis(200);
+
+        let work_solver = test_utils::create_test_work_solver();
+        let (sender, _receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+        let latency = || {
+            work_solver
+                .work_solver_stats()
+                .solution_latency()
+                .take_snapshot()
+        };
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        solution_sender.send(Solution::new(
+            Assignment::from(test_block),
+            test_utils::TestSolution::new(test_block),
+            None,
+        ));
+        assert_eq!(latency().solutions, 0);
+        assert_eq!(latency().latency_p50, None);
+
+        let work = Assignment::from(test_block);
+        let dispatch_time = clock::now();
+        work.set_dispatch_time(dispatch_time);
+        // only the first dispatch is kept
+        work.set_dispatch_time(dispatch_time + LATENCY);
+        let work = Arc::new(work);
+        solution_sender.send(Solution::new(
+            work.clone(),
+            test_utils::TestSolution::new(test_block),
+            Some(dispatch_time + LATENCY),
+        ));
+        solution_sender.send(Solution::new(
+            work,
+            test_utils::TestSolution::new(test_block).with_hw_timestamp(HW_LATENCY),
+            Some(dispatch_time + 2 * LATENCY),
+        ));
+
+        let latency = latency();
+        assert_eq!(latency.solutions, 2);
+        assert_eq!(latency.latency_p50, Some(LATENCY));
+        assert_eq!(latency.latency_p95, Some(2 * LATENCY));
+        // the backend measures only the second solution
+        assert_eq!(latency.hw_latency_p50, Some(HW_LATENCY));
+        assert_eq!(latency.hw_latency_p95, Some(HW_LATENCY));
+    }
+
+    /// Solutions are accounted at the time when they have been found by the backend and not
+    /// when they have been fetched or passed to the solution sender
+    #[tokio::test]
+    async fn test_solution_latency_age_hint() {
+        const LATENCY: time::Duration = time::Duration::from_millis(250);
+        const AGE: time::Duration = time::Duration::from_millis(100);
+        const DELAY: time::Duration = time::Duration::from_millis(50);
+
+        tokio::time::pause();
+        let work_solver = test_utils::create_test_work_solver();
+        let (sender, _receiver) = mpsc::unbounded();
+        let solution_sender = SolutionSender::new(sender);
+        set_owner(&solution_sender, work_solver.clone());
+
+        let test_block = &test_utils::TEST_BLOCKS[0];
+        let work = Assignment::from(test_block);
+        work.set_dispatch_time(clock::now());
+        // the solution is found after the latency and fetched after it has been waiting in the
+        // hardware buffers
+        tokio::time::advance(LATENCY + AGE).await;
+        let solution = Solution::new(
+            work,
+            test_utils::TestSolution::new(test_block).with_age_hint(AGE),
+            Some(clock::now()),
+        );
+        // processing of the fetched solution is delayed by the backend
+        tokio::time::advance(DELAY).await;
+        let before = clock::system_time();
+        solution_sender.send(solution);
+        let after = clock::system_time();
+
+        let work_solver_stats = work_solver.work_solver_stats();
+        let latency = work_solver_stats.solution_latency().take_snapshot();
+        assert_eq!(latency.solutions, 1);
+        assert_eq!(latency.latency_p50, Some(LATENCY));
+
+        let last_solution_time = work_solver_stats
+            .work_progress()
+            .take_snapshot()
+            .last_solution_time
+            .expect("BUG: missing solution time");
+        // the progress is stored with millisecond resolution
+        let resolution = time::Duration::from_millis(1);
+        assert!(last_solution_time + resolution >= before - DELAY - AGE);
+        assert!(last_solution_time <= after - DELAY - AGE);
+    }
+
+    #[tokio::test]
+    async fn test_assignment_expiry() {
+        const MAX_AGE: time::Duration = time::Duration::from_millis(100);
+
+        tokio::time::pause();
+        let hub = test_utils::create_test_work_solver();
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver.clone(),
+            vec![hub.clone() as Arc<dyn node::WorkSolver>],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let expired = |node: &Arc<test_utils::TestWorkSolver>| {
+            *node
+                .work_solver_stats()
+                .expired_assignments()
+                .take_snapshot()
+        };
+
+        // the age is not limited by default
+        let work = generator.generate().await.expect("BUG: missing test work");
+        tokio::time::advance(MAX_AGE * 10).await;
+        assert!(!work.is_fresh(MAX_AGE));
+        assert!(generator.is_fresh(&work).await);
+        assert_eq!(expired(&work_solver), 0);
+
+        // the limit shared by all generators of the engine receiver
+        engine_receiver.set_assignment_max_age(Some(MAX_AGE));
+        let work = generator.generate().await.expect("BUG: missing test work");
+        tokio::time::advance(MAX_AGE).await;
+        assert!(work.is_fresh(MAX_AGE));
+        assert!(generator.is_fresh(&work).await);
+        tokio::time::advance(time::Duration::from_millis(1)).await;
+        assert!(!generator.is_fresh(&work).await);
+        assert_eq!(expired(&work_solver), 1);
+        assert_eq!(expired(&hub), 1);
+
+        // the generator can override the shared limit
+        generator.set_assignment_max_age(Some(MAX_AGE * 2));
+        assert!(generator.is_fresh(&work).await);
+        tokio::time::advance(MAX_AGE).await;
+        assert!(!generator.is_fresh(&work).await);
+        assert_eq!(expired(&work_solver), 2);
+        assert_eq!(expired(&hub), 2);
+
+        // new work is fresh again
+        let work = generator.generate().await.expect("BUG: missing test work");
+        assert!(generator.is_fresh(&work).await);
+        assert_eq!(expired(&work_solver), 2);
+    }
+
+    /// Work generated before invalidation of the engine channel is not current anymore even
+    /// when it has not been sent to the hardware yet
+    #[tokio::test]
+    async fn test_invalidation() {
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        let mut invalidations = generator.invalidation_receiver();
+        assert_eq!(invalidations.current(), 0);
+
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let mut queued = Vec::new();
+        for _ in 0..2 {
+            queued.push(generator.generate().await.expect("BUG: missing test work"));
+        }
+        assert!(queued.iter().all(|work| generator.is_current(work)));
+        assert!(invalidations.wait_for_change(0).now_or_never().is_none());
+
+        // the backend is notified without waiting for a new engine
+        engine_sender.invalidate();
+        assert_eq!(invalidations.wait_for_change(0).await, 1);
+        assert!(queued.iter().all(|work| !generator.is_current(work)));
+
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let work = generator.generate().await.expect("BUG: missing test work");
+        assert_eq!(work.invalidation(), 1);
+        assert!(generator.is_current(&work));
+        assert!(queued.iter().all(|work| !generator.is_current(work)));
+    }
+
+    /// Each generated assignment gets a unique sequence number and only the work submitted to
+    /// the hardware is accounted as dispatched
+    #[tokio::test]
+    async fn test_dispatched_work() {
+        let hub = test_utils::create_test_work_solver();
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![hub.clone() as Arc<dyn node::WorkSolver>],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let counters = |node: &Arc<test_utils::TestWorkSolver>| {
+            let stats = node.work_solver_stats();
+            (
+                *stats.generated_work().take_snapshot(),
+                *stats.dispatched_work().take_snapshot(),
+            )
+        };
+
+        let mut work_amount = 0;
+        let mut dispatched_amount = 0;
+        for sequence in 1..=3 {
+            let work = generator.generate().await.expect("BUG: missing test work");
+            assert_eq!(work.sequence(), sequence);
+            work_amount += work.generated_work_amount() as u64;
+            if sequence == 2 {
+                // skipped work is generated but it never reaches the hardware
+                generator.account_skipped(&work);
+                assert!(work.dispatch_time().is_none());
+            } else {
+                generator.account_dispatched(&work).await;
+                dispatched_amount += work.generated_work_amount() as u64;
+                assert!(work.dispatch_time().is_some());
+            }
+        }
+        assert_eq!(counters(&work_solver), (work_amount, dispatched_amount));
+        assert_eq!(counters(&hub), (work_amount, dispatched_amount));
+    }
+
+    /// Sibling work solvers with nonce offset start their scans at distinct nonces given by their
+    /// ids, the recreated work solver starts at the same nonce and work of the solver without the
+    /// capability is not affected
+    #[tokio::test]
+    async fn test_nonce_offset() {
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let hub: Arc<dyn node::WorkSolver> = Arc::new(test_utils::TestWorkSolver::new());
+        let work_solvers: Vec<Arc<dyn node::WorkSolver>> = vec![
+            Arc::new(test_utils::TestWorkSolver::with_id(0)),
+            Arc::new(test_utils::TestWorkSolver::with_id(1)),
+            // the same hash chain created again (e.g. after restart of the backend)
+            Arc::new(test_utils::TestWorkSolver::with_id(0)),
+            Arc::new(test_utils::TestWorkSolver::with_id(2)),
+        ];
+        let mut generators: Vec<_> = work_solvers
+            .iter()
+            .map(|work_solver| {
+                Generator::new(
+                    engine_receiver.clone(),
+                    vec![hub.clone()],
+                    Arc::new(Mutex::new(Some(Arc::downgrade(work_solver)))),
+                )
+            })
+            .collect();
+
+        let mut nonce_starts = vec![];
+        for (generator, work_solver) in generators[..3].iter_mut().zip(work_solvers.iter()) {
+            generator.set_nonce_offset(true);
+            // the offset is resolved with the first work
+            assert_eq!(generator.nonce_offset(), None);
+            let work = generator.generate().await.expect("BUG: missing test work");
+            assert_eq!(Some(work.nonce_start()), generator.nonce_offset());
+            assert_eq!(
+                work.nonce_start(),
+                nonce_offset(vec![hub.clone(), work_solver.clone()].iter())
+            );
+            nonce_starts.push(work.nonce_start());
+        }
+        assert_ne!(nonce_starts[0], nonce_starts[1]);
+        assert_eq!(nonce_starts[0], nonce_starts[2]);
+
+        // the test engine has been exhausted by the previous work
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let generator = &mut generators[3];
+        let work = generator.generate().await.expect("BUG: missing test work");
+        assert_eq!(generator.nonce_offset(), None);
+        assert_eq!(work.nonce_start(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pause() {
+        const DELAY: time::Duration = time::Duration::from_millis(50);
+
+        let hub = test_utils::create_test_work_solver();
+        let work_solvers: Vec<_> = (0..2)
+            .map(|id| Arc::new(test_utils::TestWorkSolver::with_id(id)))
+            .collect();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generators: Vec<_> = work_solvers
+            .iter()
+            .map(|work_solver| {
+                Generator::new(
+                    engine_receiver.clone(),
+                    vec![hub.clone() as Arc<dyn node::WorkSolver>],
+                    Arc::new(Mutex::new(Some(Arc::downgrade(
+                        &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+                    )))),
+                )
+            })
+            .collect();
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let generated_work = |node: &Arc<test_utils::TestWorkSolver>| {
+            *node.work_solver_stats().generated_work().take_snapshot()
+        };
+
+        assert!(set_paused(work_solvers[0].as_ref(), true)
+            .await
+            .expect("BUG: cannot pause work solver"));
+        assert!(!set_paused(work_solvers[0].as_ref(), true)
+            .await
+            .expect("BUG: cannot pause work solver"));
+        assert!(work_solvers[0].is_power_saving());
+        assert_eq!(
+            work_solvers[0].get_mining_status().await,
+            node::Status::Paused
+        );
+        // the health reported by the backend is not affected
+        assert_eq!(work_solvers[0].get_status().await, node::Status::Ok);
+
+        // the paused work solver does not get any work
+        assert!(tokio::time::timeout(DELAY, generators[0].generate())
+            .await
+            .is_err());
+        assert_eq!(generated_work(&work_solvers[0]), 0);
+        // while the other one is mining as usual
+        for _ in 0..2 {
+            generators[1]
+                .generate()
+                .await
+                .expect("BUG: missing test work");
+        }
+        assert_eq!(generated_work(&work_solvers[1]), 2);
+        assert_eq!(generated_work(&hub), 2);
+        assert!(!work_solvers[1].is_power_saving());
+        assert_eq!(work_solvers[1].get_mining_status().await, node::Status::Ok);
+
+        // the ongoing pause is accounted
+        let snapshot = work_solvers[0].work_solver_stats().pause().take_snapshot();
+        assert!(snapshot.paused);
+        assert_eq!(snapshot.pauses, 1);
+        assert!(snapshot.duration >= DELAY);
+        assert!(snapshot.last_change.is_some());
+
+        // the resumed work solver continues where it has stopped
+        assert!(set_paused(work_solvers[0].as_ref(), false)
+            .await
+            .expect("BUG: cannot resume work solver"));
+        assert!(!work_solvers[0].is_power_saving());
+        assert_eq!(work_solvers[0].get_mining_status().await, node::Status::Ok);
+        generators[0]
+            .generate()
+            .await
+            .expect("BUG: missing test work");
+        assert_eq!(generated_work(&work_solvers[0]), 1);
+        assert_eq!(generated_work(&hub), 3);
+
+        // the finished pause is not growing anymore
+        let resumed = work_solvers[0].work_solver_stats().pause().take_snapshot();
+        assert!(!resumed.paused);
+        assert_eq!(resumed.pauses, 1);
+        assert!(resumed.duration >= snapshot.duration);
+        delay_for(DELAY).await;
+        let idle = work_solvers[0].work_solver_stats().pause().take_snapshot();
+        assert_eq!(idle.duration, resumed.duration);
+    }
+
+    #[tokio::test]
+    async fn test_pause_mid_stream() {
+        const DELAY: time::Duration = time::Duration::from_secs(600);
+
+        tokio::time::pause();
+        let hub = test_utils::create_test_work_solver();
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![hub.clone() as Arc<dyn node::WorkSolver>],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        let test_block = |work: &Assignment| work.job::<test_utils::TestBlock>().hash;
+
+        let work = generator.generate().await.expect("BUG: missing test work");
+        assert_eq!(test_block(&work), test_utils::TEST_BLOCKS[0].hash);
+        let generation = work.generation();
+        // account some hashrate before the pause
+        for _ in 0..60 {
+            tokio::time::advance(time::Duration::from_secs(1)).await;
+            work_solver
+                .mining_stats()
+                .valid_backend_diff()
+                .account_solution(&ii_bitcoin::Target::from_pool_difficulty(1), clock::now())
+                .await;
+        }
+        let hashrate = work_solver
+            .get_hashrate(*stats::TIME_MEAN_INTERVAL_5M)
+            .await;
+
+        assert!(work_solver.pause().await.expect("BUG: cannot pause"));
+        assert!(!work_solver.pause().await.expect("BUG: cannot pause"));
+        // no assignment is produced while the work solver is paused
+        assert!(generator.generate().now_or_never().is_none());
+        tokio::time::advance(DELAY).await;
+        assert!(generator.generate().now_or_never().is_none());
+        assert_eq!(
+            *work_solver
+                .work_solver_stats()
+                .generated_work()
+                .take_snapshot(),
+            1
+        );
+        // the hashrate does not decay during the pause
+        assert_eq!(
+            work_solver
+                .get_hashrate(*stats::TIME_MEAN_INTERVAL_5M)
+                .await,
+            hashrate
+        );
+
+        assert!(work_solver.resume().await.expect("BUG: cannot resume"));
+        assert!(!work_solver.resume().await.expect("BUG: cannot resume"));
+        assert_eq!(
+            work_solver
+                .get_hashrate(*stats::TIME_MEAN_INTERVAL_5M)
+                .await,
+            hashrate
+        );
+        // the generation continues with the same engine
+        let work = generator.generate().await.expect("BUG: missing test work");
+        assert_eq!(test_block(&work), test_utils::TEST_BLOCKS[1].hash);
+        assert_eq!(work.generation(), generation);
+        assert_eq!(
+            *work_solver
+                .work_solver_stats()
+                .generated_work()
+                .take_snapshot(),
+            2
+        );
+    }
+
+    /// Job violating the contract of `job::Bitcoin::target` by reading the current difficulty
+    /// of its client
+    #[derive(Debug)]
+    struct VardiffJob {
+        block: test_utils::TestBlock,
+        difficulty: AtomicUsize,
+    }
+
+    impl job::Bitcoin for VardiffJob {
+        fn origin(&self) -> Weak<dyn node::Client> {
+            self.block.origin()
+        }
+
+        fn version(&self) -> u32 {
+            self.block.version
+        }
+
+        fn version_mask(&self) -> u32 {
+            ii_bitcoin::BIP320_VERSION_MASK
+        }
+
+        fn previous_hash(&self) -> &ii_bitcoin::DHash {
+            &self.block.previous_hash
+        }
+
+        fn merkle_root(&self) -> &ii_bitcoin::DHash {
+            &self.block.merkle_root
+        }
+
+        fn time(&self) -> u32 {
+            self.block.time
+        }
+
+        fn bits(&self) -> u32 {
+            self.block.bits
+        }
+
+        fn target(&self) -> ii_bitcoin::Target {
+            ii_bitcoin::Target::from_pool_difficulty(self.difficulty.load(Ordering::Relaxed))
+        }
+
+        fn is_valid(&self) -> bool {
+            true
+        }
+    }
+
+    /// Solution is classified against the job target snapshotted when its work has been
+    /// generated even when the target of the job changes afterwards
+    #[tokio::test]
+    async fn test_job_target_snapshot() {
+        let work_solver = test_utils::create_test_work_solver();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+        let block = test_utils::TEST_BLOCKS[0];
+        let job = Arc::new(VardiffJob {
+            block,
+            difficulty: AtomicUsize::new(1),
+        });
+        engine_sender.broadcast_engine(Arc::new(engine::VersionRolling::new(job.clone(), 1)));
+        let work = generator.generate().await.expect("BUG: missing test work");
+
+        // the difficulty is raised before the solution of the work is evaluated
+        job.difficulty.store(usize::MAX, Ordering::Relaxed);
+        let solution = Solution::new(work, test_utils::TestSolution::new(&block), None);
+        assert_eq!(
+            *solution.job_target(),
+            ii_bitcoin::Target::from_pool_difficulty(1)
+        );
+        assert!(solution.hash().meets(solution.job_target()));
+        assert!(!solution.hash().meets(&job::Bitcoin::target(job.as_ref())));
+    }
+
+    /// Removed work solver does not get any work while the other one keeps mining
+    #[tokio::test]
+    async fn test_remove_work_solver() {
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let (solution_sender, _solution_receiver) = mpsc::unbounded();
+        let registry = Arc::new(backend::Registry::new());
+        let builder = SolverBuilder::new(
+            test_utils::create_test_work_solver(),
+            registry.clone(),
+            engine_receiver,
+            solution_sender,
+        );
+        // both work solvers are attached to one work hub under the root
+        let hub_builder = builder
+            .create_work_hub(|| test_utils::TestWorkSolver::with_id(100))
+            .await;
+        let mut generators = vec![];
+        let mut work_solvers = vec![];
+        for id in 0..2 {
+            let mut generator = None;
+            let work_solver = hub_builder
+                .create_work_solver(|work_generator, _| {
+                    generator = Some(work_generator);
+                    test_utils::TestWorkSolver::with_id(id)
+                })
+                .await;
+            generators.push(generator.expect("BUG: missing work generator"));
+            work_solvers.push(work_solver);
+        }
+        let generated_work = |node: &Arc<test_utils::TestWorkSolver>| {
+            *node.work_solver_stats().generated_work().take_snapshot()
+        };
+
+        // the generator waiting for the first engine is woken by the removal
+        let mut removed_generator = generators.remove(0);
+        let waiting = tokio::spawn(async move { removed_generator.generate().await.is_none() });
+        tokio::task::yield_now().await;
+        hub_builder
+            .remove_work_solver(work_solvers[0].clone())
+            .await;
+        assert!(waiting.await.expect("BUG: generator task has failed"));
+        assert!(!set_removed(work_solvers[0].as_ref()));
+        assert_eq!(
+            work_solvers[0].get_mining_status().await,
+            node::Status::Removed
+        );
+        assert!(work_solvers[0]
+            .work_solver_stats()
+            .removal()
+            .removed_at()
+            .is_some());
+
+        let mut generator = generators.pop().expect("BUG: missing work generator");
+        engine_sender.broadcast_engine(Arc::new(test_utils::TestWorkEngine::new()));
+        for _ in 0..2 {
+            generator.generate().await.expect("BUG: missing test work");
+        }
+        assert_eq!(generated_work(&work_solvers[0]), 0);
+        assert_eq!(generated_work(&work_solvers[1]), 2);
+        assert_eq!(work_solvers[1].get_mining_status().await, node::Status::Ok);
+        // the removed work solver is kept in the registry with its statistics
+        assert_eq!(registry.get_work_solvers().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_job_latency() {
+        const DELAY: time::Duration = time::Duration::from_millis(50);
+
+        let hub = test_utils::create_test_work_solver();
+        let solvers = vec![
+            test_utils::create_test_work_solver(),
+            test_utils::create_test_work_solver(),
+        ];
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let _ = engine_sender.replace_engine_generator(Box::new(move |job| {
+            Arc::new(engine::VersionRolling::new(job, 1))
+        }));
+        let mut generators: Vec<_> = solvers
+            .iter()
+            .map(|work_solver| {
+                Generator::new(
+                    engine_receiver.clone(),
+                    vec![hub.clone() as Arc<dyn node::WorkSolver>],
+                    Arc::new(Mutex::new(Some(Arc::downgrade(
+                        &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+                    )))),
+                )
+            })
+            .collect();
+
+        for test_block in test_utils::TEST_BLOCKS.iter().take(2) {
+            engine_sender.broadcast_job(Arc::new(*test_block));
+            generators[0]
+                .generate()
+                .await
+                .expect("BUG: missing test work");
+            // the second work solver is late
+            delay_for(DELAY).await;
+            generators[1]
+                .generate()
+                .await
+                .expect("BUG: missing test work");
+            // only the first assignment from each job is accounted
+            generators[0]
+                .generate()
+                .await
+                .expect("BUG: missing test work");
+        }
+
+        let job_latency = |work_solver: &Arc<test_utils::TestWorkSolver>| {
+            work_solver
+                .work_solver_stats()
+                .job_latency()
+                .take_snapshot()
+        };
+        let fast = job_latency(&solvers[0]);
+        assert_eq!(fast.deliveries, 2);
+        assert!(fast.latency_p95 < Some(DELAY));
+        assert_eq!(fast.spread_p95, Some(time::Duration::from_secs(0)));
+
+        let slow = job_latency(&solvers[1]);
+        assert_eq!(slow.deliveries, 2);
+        assert!(slow.latency_p50 >= Some(DELAY));
+        assert!(slow.spread_p50 >= Some(DELAY));
+
+        // the hub aggregates both work solvers
+        let global = job_latency(&hub);
+        assert_eq!(global.deliveries, 4);
+        assert!(global.latency_p50 < Some(DELAY));
+        assert!(global.latency_p95 >= Some(DELAY));
+        assert!(global.spread_p50 >= Some(DELAY));
+        assert!(global.spread_p50 <= global.latency_p95);
+    }
+
+    /// Broadcast a new job and draw `assignments` from all generators concurrently. Returns the
+    /// generators together with the delays of their first assignments after the broadcast.
+    async fn fan_out_job(
+        engine_sender: &EngineSender,
+        generators: Vec<Generator>,
+        assignments: usize,
+    ) -> (Vec<Generator>, Vec<time::Duration>) {
+        let start = clock::now();
+        engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[0]));
+        let tasks: Vec<_> = generators
+            .into_iter()
+            .map(|mut generator| {
+                tokio::spawn(async move {
+                    generator.generate().await.expect("BUG: missing test work");
+                    let delay = clock::now().duration_since(start);
+                    for _ in 1..assignments {
+                        generator.generate().await.expect("BUG: missing test work");
+                    }
+                    (generator, delay)
+                })
+            })
+            .collect();
+
+        let mut generators = Vec::new();
+        let mut delays = Vec::new();
+        for task in tasks {
+            let (generator, delay) = task.await.expect("BUG: fan-out task failed");
+            generators.push(generator);
+            delays.push(delay);
+        }
+        (generators, delays)
+    }
+
+    #[tokio::test]
+    async fn test_fan_out() {
+        const SOLVER_COUNT: usize = 8;
+        const BATCH_SIZE: usize = 2;
+        const ASSIGNMENTS: usize = 4;
+        const SPACING: time::Duration = time::Duration::from_millis(20);
+
+        let hub = test_utils::create_test_work_solver();
+        let solvers: Vec<_> = (0..SOLVER_COUNT)
+            .map(|_| test_utils::create_test_work_solver())
+            .collect();
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let _ = engine_sender.replace_engine_generator(Box::new(move |job| {
+            Arc::new(engine::VersionRolling::new(job, 1))
+        }));
+        let generators: Vec<_> = solvers
+            .iter()
+            .map(|work_solver| {
+                Generator::new(
+                    engine_receiver.clone(),
+                    vec![hub.clone() as Arc<dyn node::WorkSolver>],
+                    Arc::new(Mutex::new(Some(Arc::downgrade(
+                        &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+                    )))),
+                )
+            })
+            .collect();
+        // the last work solver has the highest priority
+        generators[SOLVER_COUNT - 1].set_fan_out_weight(1);
+        let generated_work = || *hub.work_solver_stats().generated_work().take_snapshot();
+
+        // all generators are woken at once by default
+        assert_eq!(engine_receiver.fan_out(), None);
+        let (generators, delays) = fan_out_job(&engine_sender, generators, ASSIGNMENTS).await;
+        assert!(delays.iter().all(|delay| *delay < SPACING));
+        let unstaggered_work = generated_work();
+        assert_eq!(unstaggered_work, (SOLVER_COUNT * ASSIGNMENTS) as u64);
+
+        engine_receiver.set_fan_out(Some(FanOut::new(BATCH_SIZE, SPACING)));
+        let (_generators, delays) = fan_out_job(&engine_sender, generators, ASSIGNMENTS).await;
+        for (i, delay) in delays.iter().enumerate() {
+            let rank = if i == SOLVER_COUNT - 1 { 0 } else { i + 1 };
+            let batch = (rank / BATCH_SIZE) as u32;
+            assert!(*delay >= SPACING * batch, "solver {} is early", i);
+            assert!(*delay < SPACING * (batch + 1), "solver {} is late", i);
+        }
+        // the stagger does not change the amount of work
+        assert_eq!(generated_work() - unstaggered_work, unstaggered_work);
+
+        let job_latency = hub.work_solver_stats().job_latency().take_snapshot();
+        let last_batch = ((SOLVER_COUNT - 1) / BATCH_SIZE) as u32;
+        assert!(job_latency.last_fan_out >= Some(SPACING * last_batch));
+        assert!(job_latency.fan_out_p50 < Some(SPACING));
+    }
+
+    #[tokio::test]
+    async fn test_work_history() {
+        const DELAY: time::Duration = time::Duration::from_millis(50);
+
+        let work_solver = Arc::new(test_utils::TestWorkSolver::with_id(1));
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let history = engine_receiver.history().clone();
+        // each job provides exactly one work
+        let _ = engine_sender.replace_engine_generator(Box::new(move |_| {
+            Arc::new(test_utils::OneWorkEngine::new(
+                (&test_utils::TEST_BLOCKS[0]).into(),
+            ))
+        }));
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+
+        // broadcast and exhaust the first job
+        engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[0]));
+        generator.generate().await.expect("BUG: missing test work");
+
+        // the generator is starving until the second job is broadcast
+        let broadcast = tokio::spawn(async move {
+            delay_for(DELAY).await;
+            engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[1]));
+            engine_sender
+        });
+        generator.generate().await.expect("BUG: missing test work");
+        let _engine_sender = broadcast.await.expect("BUG: broadcast task failed");
+
+        let events: Vec<_> = history
+            .records()
+            .into_iter()
+            .map(|record| record.event)
+            .collect();
+        let solver = work_solver.to_string();
+        assert_eq!(events.len(), 6);
+        // generation of job broadcasts is global so only its order can be checked
+        let generation = |event: &history::Event| match event {
+            history::Event::Broadcast {
+                generation,
+                job_id: None,
+            } => *generation,
+            event => panic!("unexpected event {:?}", event),
+        };
+        assert!(generation(&events[0]) < generation(&events[3]));
+        assert_eq!(
+            events[1],
+            history::Event::Exhausted {
+                solver: solver.clone()
+            }
+        );
+        assert_eq!(
+            events[2],
+            history::Event::StarvationStart {
+                solver: solver.clone()
+            }
+        );
+        match &events[4] {
+            history::Event::StarvationEnd {
+                solver: name,
+                duration_ms,
+            } => {
+                assert_eq!(*name, solver);
+                assert!(*duration_ms >= DELAY.as_millis() as u64);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(events[5], history::Event::Exhausted { solver });
+    }
+
+    #[tokio::test]
+    async fn test_work_starvation() {
+        const DELAY: time::Duration = time::Duration::from_millis(50);
+
+        let work_solver = Arc::new(test_utils::TestWorkSolver::with_id(1));
+        let (engine_sender, engine_receiver) = engine_channel(IgnoreEvents);
+        let _ = engine_sender.replace_engine_generator(Box::new(move |_| {
+            Arc::new(test_utils::OneWorkEngine::new(
+                (&test_utils::TEST_BLOCKS[0]).into(),
+            ))
+        }));
+        let mut generator = Generator::new(
+            engine_receiver,
+            vec![],
+            Arc::new(Mutex::new(Some(Arc::downgrade(
+                &(work_solver.clone() as Arc<dyn node::WorkSolver>),
+            )))),
+        );
+
+        engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[0]));
+        generator.generate().await.expect("BUG: missing test work");
+
+        let broadcast = tokio::spawn(async move {
+            delay_for(DELAY).await;
+            engine_sender.broadcast_job(Arc::new(test_utils::TEST_BLOCKS[1]));
+            engine_sender
+        });
+        generator.generate().await.expect("BUG: missing test work");
+        let _engine_sender = broadcast.await.expect("BUG: broadcast task failed");
+
+        let starvation = work_solver
+            .work_solver_stats()
+            .work_starvation()
+            .take_snapshot();
+        assert!(starvation.duration >= DELAY);
+        assert_eq!(starvation.waits, 1);
+        // both engines have been exhausted by generating their only work
+        assert_eq!(starvation.exhausted_engines, 2);
+    }
+}
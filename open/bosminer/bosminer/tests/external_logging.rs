@@ -0,0 +1,86 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Test of BOSminer embedded in an application which installs its own logger.
+//!
+//! **Warning**: The test needs to be in a separate binary due to global logger initialization
+
+use bosminer::{backend, hub, LoggingMode};
+use bosminer_config::WorkHistoryConfig;
+
+use ii_async_compat::tokio;
+use tokio::time::delay_for;
+
+use once_cell::sync::Lazy;
+
+use std::env;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// Messages of all records passed to the external logger
+static RECORDS: Lazy<Mutex<Vec<String>>> = Lazy::new(Default::default);
+
+#[tokio::test]
+async fn test_external_logging() {
+    env_logger::Builder::new()
+        .parse_filters("trace")
+        .format(|buf, record| {
+            RECORDS
+                .lock()
+                .expect("cannot lock captured records")
+                .push(record.args().to_string());
+            writeln!(buf, "{}: {}", record.level(), record.args())
+        })
+        .init();
+    // there is no guard because the records are not buffered by BOSminer
+    assert!(bosminer::entry::setup_logging(Some(LoggingMode::External)).is_none());
+
+    // mirror of work history in a missing directory is reported as warning
+    let work_history_config = WorkHistoryConfig {
+        length: None,
+        mirror: Some(
+            env::temp_dir()
+                .join(format!("bosminer-missing-{}", std::process::id()))
+                .join("work-history.jsonl")
+                .to_string_lossy()
+                .into_owned(),
+        ),
+    };
+    let backend_registry = Arc::new(backend::Registry::new());
+    let core = Arc::new(hub::Core::new(
+        1,
+        Default::default(),
+        &backend_registry,
+        None,
+        None,
+        work_history_config,
+        None,
+    ));
+    tokio::spawn(core.clone().run());
+    delay_for(time::Duration::from_millis(10)).await;
+
+    let records = RECORDS.lock().expect("cannot lock captured records");
+    assert!(records
+        .iter()
+        .any(|record| record.starts_with("Cannot mirror work history")));
+}
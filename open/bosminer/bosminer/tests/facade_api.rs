@@ -0,0 +1,109 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Public API snapshot of the stable facade. Any change of the items re-exported by
+//! `bosminer::facade` fails the test until the golden file `tests/facade_api.txt` is updated.
+//! Breaking change of the signatures used by embedding applications fails compilation of this
+//! test with the `lib-api` feature enabled.
+
+/// Golden file with one re-exported item per line in the form `path [as name]`
+const GOLDEN_FILE: &str = include_str!("facade_api.txt");
+
+/// Extract items of all `pub use` declarations from the facade source
+fn facade_items(source: &str) -> Vec<String> {
+    const PUB_USE: &str = "pub use ";
+
+    let mut items: Vec<_> = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with(PUB_USE))
+        .map(|line| line[PUB_USE.len()..].trim_end_matches(';').to_string())
+        .collect();
+    items.sort();
+    items
+}
+
+#[test]
+fn test_facade_snapshot() {
+    let mut golden: Vec<_> = GOLDEN_FILE
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    golden.sort();
+
+    let items = facade_items(include_str!("../src/facade.rs"));
+    assert_eq!(
+        items, golden,
+        "the stable facade has changed: update 'tests/facade_api.txt' when it is intended and \
+         bump the crate version if the change is breaking"
+    );
+}
+
+#[cfg(feature = "lib-api")]
+mod signatures {
+    use bosminer::facade::*;
+
+    use std::sync::Arc;
+
+    /// Typical use of the facade by an embedding application. It is never executed.
+    #[allow(dead_code)]
+    async fn embed<T: Backend>(backend_config: T::Config) -> Result<()> {
+        let miner: RunningMiner = Runner::<T>::new(backend_config).start().await?;
+
+        let summary: CoreSummary = miner.summary().await;
+        let _: Option<BackendInfo> = summary.backend_info;
+        let _: u64 = summary.generated_work;
+        let _: usize = summary.work_hubs + summary.work_solvers;
+        let _: usize = summary.clients + summary.running_clients;
+        let _: bool = summary.initialized;
+
+        let client_manager: &ClientManager = miner.client_manager();
+        for group in client_manager.get_groups().await {
+            let group: Arc<ClientGroup> = group;
+            let _: &GroupDescriptor = group.descriptor();
+            for client in group.get_clients().await {
+                let client: Arc<ClientHandle> = client;
+                let _: ClientDescriptor = client.descriptor().await;
+                let _: bool = client.is_enabled() && client.is_running();
+                let _: std::result::Result<(), ()> = client.try_enable();
+                let _: std::result::Result<(), ()> = client.try_disable();
+                let descriptor = client.descriptor().await;
+                let options: PoolTestOptions = Default::default();
+                if let Ok(report) = client_manager.test_pool(&descriptor, options).await {
+                    let _: PoolTestReport = report;
+                }
+            }
+        }
+
+        let mut client_events: EventReceiver = miner.client_events();
+        let _ = client_events.wait_for_event().await;
+        let progress: &Arc<Progress> = miner.progress();
+        let _: bool = progress.is_finished();
+        let work_history: &Arc<WorkHistory> = miner.work_history();
+        let _: Vec<WorkRecord> = work_history.records();
+
+        miner.shutdown().await;
+        Ok(())
+    }
+}
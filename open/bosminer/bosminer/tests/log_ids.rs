@@ -0,0 +1,125 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Test of identifiers prefixed to the log records of a job and its solution.
+//!
+//! **Warning**: The test needs to be in a separate binary due to global logger initialization
+
+use bosminer::test_utils;
+use bosminer::{backend, job, log_id, work, Frontend, LoggingMode};
+
+use ii_async_compat::{futures, tokio};
+
+use futures::channel::mpsc;
+use once_cell::sync::Lazy;
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Messages of all records passed to the external logger
+static RECORDS: Lazy<Mutex<Vec<String>>> = Lazy::new(Default::default);
+
+fn find_record(prefix: &str, message: &str) -> bool {
+    let expected = format!("{} {}", prefix, message);
+    RECORDS
+        .lock()
+        .expect("cannot lock captured records")
+        .iter()
+        .any(|record| record.starts_with(&expected))
+}
+
+#[tokio::test]
+async fn test_log_ids() {
+    env_logger::Builder::new()
+        .parse_filters("trace")
+        .format(|buf, record| {
+            RECORDS
+                .lock()
+                .expect("cannot lock captured records")
+                .push(record.args().to_string());
+            writeln!(buf, "{}: {}", record.level(), record.args())
+        })
+        .init();
+    assert!(bosminer::entry::setup_logging(Some(LoggingMode::External)).is_none());
+
+    let (engine_sender, engine_receiver) = work::engine_channel(work::IgnoreEvents);
+    let _ = engine_sender.replace_engine_generator(Box::new(move |job| {
+        Arc::new(work::engine::VersionRolling::new(job, 1))
+    }));
+    let (solution_sender, solution_receiver) = mpsc::unbounded();
+    let mut job_solver = job::Solver::new(Arc::new(engine_sender), solution_receiver);
+    let work_solver_builder = work::SolverBuilder::new(
+        Arc::new(Frontend::new()),
+        Arc::new(backend::Registry::new()),
+        engine_receiver,
+        solution_sender,
+    );
+
+    let mut work_generator = None;
+    let mut solution_sender = None;
+    work_solver_builder
+        .create_work_solver(|local_work_generator, local_solution_sender| {
+            work_generator = Some(local_work_generator);
+            solution_sender = Some(local_solution_sender);
+            Arc::new(test_utils::TestWorkSolver::new())
+        })
+        .await;
+    let mut work_generator = work_generator.unwrap();
+    let solution_sender = solution_sender.unwrap();
+
+    // scripted round trip of a real block so that all lifecycle records are emitted
+    let block = &test_utils::TEST_BLOCKS[0];
+    job_solver.job_sender.send(Arc::new(*block));
+    let work = work_generator.generate().await.unwrap();
+    let generation = work
+        .generation()
+        .expect("BUG: work generated from a job without generation");
+    solution_sender.send(work::Solution::new(
+        work,
+        test_utils::TestSolution::new(block),
+        None,
+    ));
+    job_solver.solution_receiver.receive().await.unwrap();
+
+    let prefix = log_id::Prefix::job(block)
+        .with_generation(generation)
+        .with_client(job_solver.client_name())
+        .to_string();
+    assert!(prefix.starts_with("[job="));
+    assert!(prefix.ends_with(&format!(
+        " gen={} client={}]",
+        generation,
+        job_solver.client_name()
+    )));
+    for message in &[
+        "--- broadcasting new job ---",
+        "===== BLOCK FOUND =====",
+        "----- Found share within current job's difficulty",
+    ] {
+        assert!(
+            find_record(&prefix, message),
+            "missing record '{} {}'",
+            prefix,
+            message
+        );
+    }
+}
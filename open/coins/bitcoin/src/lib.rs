@@ -36,6 +36,7 @@ use std::convert::TryInto;
 use std::fmt;
 use std::mem::size_of;
 use std::slice::Chunks;
+use std::str::FromStr;
 use std::time;
 
 /// SHA256 digest size used in Bitcoin protocol
@@ -243,6 +244,17 @@ impl Target {
         Self(Self::difficulty_1_target() / difficulty)
     }
 
+    /// Create target from pool difficulty which is not rounded to an integer. The difficulty is
+    /// converted to a fixed point number rounded up so solutions meeting the target always meet
+    /// the difficulty, too.
+    pub fn from_precise_difficulty(difficulty: f64) -> Self {
+        const FRACTION_BITS: i32 = 16;
+        // very small difficulties are clamped to avoid division by zero
+        let difficulty = (difficulty * 2f64.powi(FRACTION_BITS)).ceil().max(1.0) as u128;
+        let divisor = uint::U256::from_big_endian(&difficulty.to_be_bytes());
+        Self((Self::difficulty_1_target() << FRACTION_BITS as usize) / divisor)
+    }
+
     /// Create target from its compact representation used by Bitcoin protocol
     pub fn from_compact(bits: u32) -> Result<Self, &'static str> {
         // this code is inspired by `rust-bitcoin` crate implementation
@@ -274,6 +286,13 @@ impl Target {
         (Self::difficulty_1_target() / self.0).low_u64() as usize
     }
 
+    /// Convert target to difficulty which is not rounded to an integer. It is also suitable for
+    /// a hash converted to target because it does not fail for very small values (the zero hash
+    /// has infinite difficulty).
+    pub fn get_precise_difficulty(&self) -> f64 {
+        u256_to_f64(Self::difficulty_1_target()) / u256_to_f64(self.0)
+    }
+
     /// Convert target to its compact representation used by Bitcoin protocol
     pub fn into_compact(self) -> u32 {
         // this code is inspired by `rust-bitcoin` crate implementation
@@ -390,6 +409,94 @@ impl MeetsTarget for DHash {
     }
 }
 
+/// Bitcoin network determines consensus constants used for target and difficulty computations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Network {
+    pub const MAINNET: &'static str = "mainnet";
+    pub const TESTNET: &'static str = "testnet";
+    pub const REGTEST: &'static str = "regtest";
+
+    /// Compact representation of proof-of-work limit for mainnet and testnet
+    const POW_LIMIT_BITS: u32 = 0x1d00ffff;
+    /// Compact representation of proof-of-work limit for regtest
+    const REGTEST_POW_LIMIT_BITS: u32 = 0x207fffff;
+
+    /// Target with difficulty 1. All networks use the mainnet value as a reference so the
+    /// difficulty of regtest network is only a fraction of 1.
+    pub fn difficulty_1_target(&self) -> Target {
+        Target::default()
+    }
+
+    /// The highest (easiest) target which is allowed by the network
+    pub fn pow_limit(&self) -> Target {
+        let bits = match self {
+            Self::Mainnet | Self::Testnet => Self::POW_LIMIT_BITS,
+            Self::Regtest => Self::REGTEST_POW_LIMIT_BITS,
+        };
+        Target::from_compact(bits).expect("BUG: invalid proof-of-work limit")
+    }
+
+    /// Create network target from its compact representation in block header. The target is
+    /// clamped to the proof-of-work limit because no valid block can have an easier target.
+    pub fn target_from_compact(&self, bits: u32) -> Result<Target, &'static str> {
+        Ok(Target::from_compact(bits)?.min(self.pow_limit()))
+    }
+
+    /// Convert target to network difficulty. The result is not rounded to an integer (as in the
+    /// case of pool difficulty) so that fractional difficulty of test networks can be displayed.
+    pub fn difficulty(&self, target: &Target) -> f64 {
+        target.get_precise_difficulty()
+    }
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self::Mainnet
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::MAINNET => Ok(Self::Mainnet),
+            Self::TESTNET => Ok(Self::Testnet),
+            Self::REGTEST => Ok(Self::Regtest),
+            _ => Err(format!(
+                "unknown network '{}' (expected '{}', '{}' or '{}')",
+                s,
+                Self::MAINNET,
+                Self::TESTNET,
+                Self::REGTEST
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Mainnet => Self::MAINNET,
+            Self::Testnet => Self::TESTNET,
+            Self::Regtest => Self::REGTEST,
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Approximate 256bit integer with floating point number using its 64 most significant bits
+fn u256_to_f64(value: uint::U256) -> f64 {
+    let shift = value.bits().saturating_sub(64);
+    (value >> shift).low_u64() as f64 * 2f64.powi(shift as i32)
+}
+
 /// Structure used for storing all shares determined from solution target difficulty
 /// Share=1 represents a space of 2^32 calculated hashes for Bitcoin mainnet; exactly
 /// 2^256 / (0xffff << 208), where 0xffff << 208 is defined as target difficulty 1 for Bitcoin
@@ -782,6 +889,111 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_hash_difficulty() {
+        for block in TEST_BLOCKS.iter() {
+            let target: Target = block.hash.into();
+            let difficulty = target.get_precise_difficulty();
+
+            // the block hash is at least as difficult as the network target
+            let network_target = Target::from_compact(block.bits).unwrap();
+            assert!(difficulty >= Network::Mainnet.difficulty(&network_target));
+            // and it is truncated to the integer difficulty
+            assert_eq!(difficulty as usize, target.get_difficulty());
+        }
+
+        // difficulty of very small hashes is still finite
+        let target: Target = uint::U256::from(1).into();
+        let difficulty = target.get_precise_difficulty();
+        assert!(difficulty.is_finite());
+        assert!((difficulty / 2.695953529101131e67 - 1.0).abs() < 1e-9);
+        // the zero hash does not cause division by zero
+        let target: Target = uint::U256::zero().into();
+        assert_eq!(target.get_precise_difficulty(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_precise_difficulty() {
+        for difficulty in &[1, 4, 1024, 65536, 1 << 40] {
+            assert_eq!(
+                Target::from_precise_difficulty(*difficulty as f64),
+                Target::from_pool_difficulty(*difficulty)
+            );
+        }
+        // fractional part of the difficulty is not truncated
+        let target = Target::from_precise_difficulty(1.5);
+        assert!(target < Target::from_pool_difficulty(1));
+        assert!(target > Target::from_pool_difficulty(2));
+        assert_eq!(
+            target,
+            Target::from(Target::difficulty_1_target() * 2u32 / 3u32)
+        );
+        // difficulty between integers is rounded up
+        let target = Target::from_precise_difficulty(1024.1);
+        assert!(target < Target::from_pool_difficulty(1024));
+        assert!(target > Target::from_pool_difficulty(1025));
+    }
+
+    #[test]
+    fn test_network_difficulty() {
+        const EPSILON: f64 = 1e-9;
+
+        for network in &[Network::Mainnet, Network::Testnet, Network::Regtest] {
+            let difficulty_1_target = network.difficulty_1_target();
+            assert_eq!(network.difficulty(&difficulty_1_target), 1.0);
+            // mainnet block 100000 with known difficulty
+            let target = network.target_from_compact(0x1b04864c).unwrap();
+            assert!((network.difficulty(&target) - 14484.162361225399).abs() < EPSILON);
+        }
+
+        // easiest possible target of each network
+        assert_eq!(
+            Network::Mainnet.difficulty(&Network::Mainnet.pow_limit()),
+            1.0
+        );
+        assert_eq!(
+            Network::Testnet.difficulty(&Network::Testnet.pow_limit()),
+            1.0
+        );
+        let regtest_difficulty = Network::Regtest.difficulty(&Network::Regtest.pow_limit());
+        assert!((regtest_difficulty - 4.656542373906925e-10).abs() < EPSILON * 1e-9);
+    }
+
+    #[test]
+    fn test_network_target_clamping() {
+        const REGTEST_BITS: u32 = 0x207fffff;
+
+        // regtest target is clamped to proof-of-work limit on other networks
+        for network in &[Network::Mainnet, Network::Testnet] {
+            assert_eq!(
+                network.target_from_compact(REGTEST_BITS).unwrap(),
+                network.pow_limit()
+            );
+        }
+        assert_eq!(
+            Network::Regtest.target_from_compact(REGTEST_BITS).unwrap(),
+            Target::from_compact(REGTEST_BITS).unwrap()
+        );
+
+        // targets below the limit are not affected
+        for block in TEST_BLOCKS.iter() {
+            assert_eq!(
+                Network::Mainnet.target_from_compact(block.bits).unwrap(),
+                Target::from_compact(block.bits).unwrap()
+            );
+        }
+        assert!(Network::Mainnet.target_from_compact(0xfffffff).is_err());
+    }
+
+    #[test]
+    fn test_network_from_str() {
+        for network in &[Network::Mainnet, Network::Testnet, Network::Regtest] {
+            assert_eq!(*network, network.to_string().parse().unwrap());
+        }
+        assert_eq!(Network::Testnet, "testnet".parse().unwrap());
+        assert!("bitcoin".parse::<Network>().is_err());
+    }
+
     #[test]
     fn test_target_bytes() {
         for block in TEST_BLOCKS.iter() {
@@ -0,0 +1,235 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Access control of API clients compatible with the `--api-allow` option of CGMiner. Each rule
+//! grants a privilege to a network of source addresses, e.g. `W:10.0.0.0/24` grants write access
+//! to the whole subnet and `192.168.1.10` grants read-only access to a single host.
+
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+/// Prefix of the rule granting write privilege
+const WRITE_PREFIX: &str = "W:";
+
+/// Privilege of the client (it is also required by each command)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Privilege {
+    /// Commands which only read the state of the miner
+    Read,
+    /// Commands which change the configuration or the state of the miner
+    Write,
+}
+
+/// Return the address with IPv4 addresses mapped to IPv6 converted to IPv4 ones
+fn canonical(addr: IpAddr) -> IpAddr {
+    match addr {
+        IpAddr::V6(v6) if v6.segments()[..6] == [0, 0, 0, 0, 0, 0xffff] => {
+            IpAddr::V4(v6.to_ipv4().expect("BUG: IPv4-mapped address"))
+        }
+        _ => addr,
+    }
+}
+
+/// Privilege granted to the network of source addresses
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "String", try_from = "String")]
+pub struct Rule {
+    addr: IpAddr,
+    prefix_len: u8,
+    privilege: Privilege,
+}
+
+impl Rule {
+    /// Create the rule for all addresses with the same first `prefix_len` bits as `addr`
+    pub fn new(addr: IpAddr, prefix_len: u8, privilege: Privilege) -> Result<Self, String> {
+        let addr = canonical(addr);
+        let max_prefix_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(format!(
+                "prefix length {} of '{}' exceeds {} bits",
+                prefix_len, addr, max_prefix_len
+            ));
+        }
+        Ok(Self {
+            addr,
+            prefix_len,
+            privilege,
+        })
+    }
+
+    /// Create the rule for the single host
+    pub fn host(addr: IpAddr, privilege: Privilege) -> Self {
+        let prefix_len = match canonical(addr) {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        Self::new(addr, prefix_len, privilege).expect("BUG: invalid host prefix")
+    }
+
+    #[inline]
+    pub fn privilege(&self) -> Privilege {
+        self.privilege
+    }
+
+    /// Check if the source address belongs to the network of the rule
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let mask = |bits: u32| match self.prefix_len {
+            0 => 0,
+            prefix_len => u128::max_value() << (bits - prefix_len as u32),
+        };
+        match (self.addr, canonical(addr)) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                let mask = mask(32) as u32;
+                u32::from(network) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                let mask = mask(128);
+                u128::from(network) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (privilege, network) = if value.len() >= WRITE_PREFIX.len()
+            && value[..WRITE_PREFIX.len()].eq_ignore_ascii_case(WRITE_PREFIX)
+        {
+            (Privilege::Write, &value[WRITE_PREFIX.len()..])
+        } else {
+            (Privilege::Read, value)
+        };
+        let mut parts = network.splitn(2, '/');
+        let addr = parts.next().expect("BUG: missing address");
+        let prefix_len = parts
+            .next()
+            .map(|prefix_len| {
+                prefix_len
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid prefix length in '{}'", value))
+            })
+            .transpose()?;
+        let addr = match addr.parse::<IpAddr>() {
+            Ok(addr) => addr,
+            // CGMiner accepts shortened IPv4 network such as '0/0' or '10/8'
+            Err(_) if prefix_len.is_some() => {
+                let parts: Vec<_> = addr.split('.').collect();
+                if parts.len() > 4 {
+                    return Err(format!("invalid address in '{}'", value));
+                }
+                let mut octets = [0u8; 4];
+                for (octet, part) in octets.iter_mut().zip(parts) {
+                    *octet = part
+                        .parse()
+                        .map_err(|_| format!("invalid address in '{}'", value))?;
+                }
+                IpAddr::V4(Ipv4Addr::from(octets))
+            }
+            Err(_) => return Err(format!("invalid address in '{}'", value)),
+        };
+        match prefix_len {
+            Some(prefix_len) => Self::new(addr, prefix_len, privilege),
+            None => Ok(Self::host(addr, privilege)),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.privilege == Privilege::Write {
+            write!(f, "{}", WRITE_PREFIX)?;
+        }
+        let host_prefix_len = match self.addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if self.prefix_len == host_prefix_len {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.prefix_len)
+        }
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.to_string()
+    }
+}
+
+/// Rules granting privileges to the source addresses of API clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowList {
+    rules: Option<Vec<Rule>>,
+}
+
+impl AllowList {
+    /// Create the list from configured `rules`. Missing rules grant write access to loopback
+    /// addresses and read-only access to all other addresses.
+    pub fn new(rules: Option<Vec<Rule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Allow write access from loopback addresses and read-only access from anywhere else
+    pub fn local_write() -> Self {
+        Self::new(None)
+    }
+
+    /// The list has been created from explicitly configured rules
+    #[inline]
+    pub fn is_configured(&self) -> bool {
+        self.rules.is_some()
+    }
+
+    /// Return the highest privilege granted to the source address by any rule or `None` when the
+    /// address is not allowed to use the API at all
+    pub fn privilege(&self, addr: IpAddr) -> Option<Privilege> {
+        match &self.rules {
+            None if canonical(addr).is_loopback() => Some(Privilege::Write),
+            None => Some(Privilege::Read),
+            Some(rules) => rules
+                .iter()
+                .filter(|rule| rule.contains(addr))
+                .map(Rule::privilege)
+                .max(),
+        }
+    }
+}
@@ -22,13 +22,14 @@
 
 //! Defines the API command handler (`Handler`)
 
+use crate::access::Privilege;
 use crate::response;
 use crate::support::ValueExt as _;
 use crate::support::{MultiResponse, ResponseType, UnixTime, When};
 
 use serde_json as json;
 
-use ii_async_compat::futures::Future;
+use ii_async_compat::futures::{future, Future};
 
 use std::collections::HashMap;
 use std::marker;
@@ -57,11 +58,32 @@ const LCD: &str = "lcd";
 
 // List of all standard commands which can be optionally implemented.
 pub const DEVDETAILS: &str = "devdetails";
+pub const NOTIFY: &str = "notify";
 
 // List of all extended commands which have to be implemented externally.
 pub const TEMPCTRL: &str = "tempctrl";
 pub const TEMPS: &str = "temps";
 pub const FANS: &str = "fans";
+pub const DIFF_HISTORY: &str = "diffhistory";
+pub const SET_ASIC_BOOST: &str = "set_asic_boost";
+pub const DIAG: &str = "diag";
+pub const DIGEST: &str = "digest";
+pub const NODES: &str = "nodes";
+pub const WORK_HISTORY: &str = "workhistory";
+pub const QUARANTINE: &str = "quarantine";
+pub const SET_QUOTA: &str = "setquota";
+pub const PROGRESS: &str = "progress";
+pub const JOB_HISTORY: &str = "jobhistory";
+pub const TEST_POOL: &str = "testpool";
+pub const CHAIN_PAUSE: &str = "chainpause";
+pub const CHAIN_RESUME: &str = "chainresume";
+pub const WORK_AUDIT: &str = "workaudit";
+pub const SESSION_HISTORY: &str = "sessionhistory";
+pub const LATENCY: &str = "latency";
+pub const BLOCK_CANDIDATES: &str = "blockcandidates";
+pub const RESTART: &str = "restart";
+pub const SHUTDOWN: &str = "shutdown";
+pub const POOL_ADDRESSES: &str = "pooladdresses";
 
 pub type Result<T> = std::result::Result<T, response::Error>;
 /// Type describing command table
@@ -142,6 +164,8 @@ impl HandlerType {
 pub struct Descriptor {
     handler: HandlerType,
     parameter_check: Option<ParameterCheckHandler>,
+    /// Privilege required from the client (commands are read-only by default)
+    privilege: Privilege,
 }
 
 impl Descriptor {
@@ -152,13 +176,43 @@ impl Descriptor {
         Self {
             handler,
             parameter_check: parameter_check.into(),
+            privilege: Privilege::Read,
         }
     }
 
+    /// Classify the command as the one requiring `privilege` (e.g. `Write` for commands changing
+    /// the configuration or the state of the miner)
+    pub fn with_privilege(mut self, privilege: Privilege) -> Self {
+        self.privilege = privilege;
+        self
+    }
+
     #[inline]
     pub fn has_parameters(&self) -> bool {
         self.handler.has_parameters()
     }
+
+    #[inline]
+    pub fn privilege(&self) -> Privilege {
+        self.privilege
+    }
+
+    /// Check the `parameter` of the `command` and run its handler. It allows forwarding of
+    /// commands to another command table. Built-in commands are handled only by the receiver.
+    pub fn call(&self, command: &str, parameter: Option<&json::Value>) -> AsyncHandler {
+        if let Some(check) = self.parameter_check.as_ref() {
+            if let Err(error) = check(command, &parameter) {
+                return Box::pin(future::ready(Err(error)));
+            }
+        }
+        match &self.handler {
+            HandlerType::ParameterLess(handle) => handle(),
+            HandlerType::Parameter(handle) => handle(parameter),
+            HandlerType::Version | HandlerType::Check => Box::pin(future::ready(Err(
+                response::ErrorCode::InvalidCommand.into(),
+            ))),
+        }
+    }
 }
 
 /// Generates a descriptor for a specified command type (`ParameterLess` or `Parameter`) that also
@@ -194,17 +248,23 @@ macro_rules! command {
     };
 }
 
-/// Generates a map that associated a command name with its descriptor
+/// Generates a map that associated a command name with its descriptor. Commands are read-only
+/// unless the required privilege is specified after the handler (e.g. `, Write`).
 #[macro_export]
 macro_rules! commands {
     () => (
         $crate::command::Map::new()
     );
-    ($(($name:ident: $type:ident$(($parameter:ident))? $(-> $handler:ident . $method:ident)?)),+) => {
+    ($(($name:ident: $type:ident$(($parameter:ident))? $(-> $handler:ident . $method:ident)?
+        $(, $privilege:ident)?)),+) => {
         {
             let mut map = $crate::command::Map::new();
             $(
                 let descriptor = command!($name: $type $(($parameter))? $(-> $handler . $method)?);
+                $(
+                    let descriptor = descriptor
+                        .with_privilege($crate::access::Privilege::$privilege);
+                )?
                 map.insert($name, descriptor);
             )*
             map
@@ -260,12 +320,12 @@ where
             (DEVS: ParameterLess -> handler.handle_devs),
             (EDEVS: ParameterLess -> handler.handle_edevs),
             (SUMMARY: ParameterLess -> handler.handle_summary),
-            (SWITCH_POOL: Parameter(check_switch_pool) -> handler.handle_switch_pool),
+            (SWITCH_POOL: Parameter(check_switch_pool) -> handler.handle_switch_pool, Write),
             (CONFIG: ParameterLess -> handler.handle_config),
-            (ENABLE_POOL: Parameter(check_enable_pool) -> handler.handle_enable_pool),
-            (DISABLE_POOL: Parameter(check_disable_pool) -> handler.handle_disable_pool),
-            (ADD_POOL: Parameter(check_add_pool) -> handler.handle_add_pool),
-            (REMOVE_POOL: Parameter(check_remove_pool) -> handler.handle_remove_pool),
+            (ENABLE_POOL: Parameter(check_enable_pool) -> handler.handle_enable_pool, Write),
+            (DISABLE_POOL: Parameter(check_disable_pool) -> handler.handle_disable_pool, Write),
+            (ADD_POOL: Parameter(check_add_pool) -> handler.handle_add_pool, Write),
+            (REMOVE_POOL: Parameter(check_remove_pool) -> handler.handle_remove_pool, Write),
             (STATS: ParameterLess -> handler.handle_stats),
             (ESTATS: ParameterLess -> handler.handle_estats),
             (COIN: ParameterLess -> handler.handle_coin),
@@ -333,31 +393,41 @@ where
         })
     }
 
-    fn handle_check(&self, parameter: Option<&json::Value>) -> Result<response::Check> {
+    fn handle_check(
+        &self,
+        parameter: Option<&json::Value>,
+        privilege: Privilege,
+    ) -> Result<response::Check> {
         let command =
             parameter.ok_or_else(|| response::Error::from(response::ErrorCode::MissingCheckCmd))?;
-        let result = match command {
-            json::Value::String(command) => self.commands.get(command.as_str()).into(),
-            _ => response::Bool::N,
+        let descriptor = match command {
+            json::Value::String(command) => self.commands.get(command.as_str()),
+            _ => None,
         };
 
         Ok(response::Check {
-            exists: result,
-            access: result,
+            exists: descriptor.into(),
+            access: descriptor
+                .filter(|descriptor| descriptor.privilege() <= privilege)
+                .into(),
         })
     }
 
-    /// Handles a single `command` with optional `parameter`. `multi_command` flag ensures that no
-    /// command with parameters can be processed in batched mode.
+    /// Handles a single `command` with optional `parameter` requested by the client with
+    /// `privilege`. `multi_command` flag ensures that no command with parameters can be processed
+    /// in batched mode.
     async fn handle_single(
         &self,
         command: &str,
         parameter: Option<&json::Value>,
         multi_command: bool,
+        privilege: Privilege,
     ) -> response::Dispatch {
         let dispatch = match self.commands.get(command) {
             Some(descriptor) => {
-                if multi_command && descriptor.has_parameters() {
+                if (multi_command && descriptor.has_parameters())
+                    || descriptor.privilege() > privilege
+                {
                     Err(response::ErrorCode::AccessDeniedCmd(command.to_string()).into())
                 } else {
                     let check_result = descriptor
@@ -371,9 +441,9 @@ where
                             HandlerType::Version => {
                                 self.handle_version().map(|response| response.into())
                             }
-                            HandlerType::Check => {
-                                self.handle_check(parameter).map(|response| response.into())
-                            }
+                            HandlerType::Check => self
+                                .handle_check(parameter, privilege)
+                                .map(|response| response.into()),
                         },
                         Err(response) => Err(response),
                     }
@@ -398,8 +468,15 @@ where
         self.get_single_response(error_code.into())
     }
 
-    /// Handles a command request that can actually be a batched request of multiple commands
+    /// Handles a command request that can actually be a batched request of multiple commands.
+    /// The client is expected to have write privilege.
     pub async fn handle(&self, command_request: Request) -> ResponseType {
+        self.handle_as(command_request, Privilege::Write).await
+    }
+
+    /// Handles a command request of the client with `privilege`. Commands requiring higher
+    /// privilege are replied with "Access denied" status.
+    pub async fn handle_as(&self, command_request: Request, privilege: Privilege) -> ResponseType {
         let command = match command_request
             .value
             .get("command")
@@ -417,13 +494,17 @@ where
         if commands.len() == 0 {
             self.get_single_response(response::ErrorCode::InvalidCommand.into())
         } else if commands.len() == 1 {
-            self.get_single_response(self.handle_single(command, parameter, false).await)
+            self.get_single_response(
+                self.handle_single(command, parameter, false, privilege)
+                    .await,
+            )
         } else {
             let mut responses = MultiResponse::new();
             for command in commands {
-                if let ResponseType::Single(response) =
-                    self.get_single_response(self.handle_single(command, parameter, true).await)
-                {
+                if let ResponseType::Single(response) = self.get_single_response(
+                    self.handle_single(command, parameter, true, privilege)
+                        .await,
+                ) {
                     responses.add_response(command, response);
                 }
             }
@@ -22,6 +22,7 @@
 
 //! A generic CGMiner API server
 
+pub mod access;
 pub mod command;
 pub mod response;
 pub mod support;
@@ -123,30 +124,55 @@ impl ii_wire::Framing for Framing {
 /// wire-based connection type
 type Connection = ii_wire::Connection<Framing>;
 
-async fn handle_connection_task(mut conn: Connection, command_receiver: Arc<command::Receiver>) {
-    let response = match conn.next().await {
-        Some(Ok(command)) => command_receiver.handle(command).await,
+async fn handle_connection_task(
+    mut conn: Connection,
+    command_receiver: Arc<command::Receiver>,
+    privilege: Option<access::Privilege>,
+) {
+    let request = match conn.next().await {
+        Some(Ok(command)) => Ok(command),
         Some(Err(err)) if err.kind() == io::ErrorKind::InvalidData => {
-            command_receiver.error_response(response::ErrorCode::InvalidJSON)
+            Err(response::ErrorCode::InvalidJSON)
         }
         _ => return, // We pretty much ignore I/O errors here
     };
+    // The request is read even from denied addresses so that the client receives the reply
+    let response = match (request, privilege) {
+        (_, None) => command_receiver.error_response(response::ErrorCode::AccessDenied),
+        (Ok(command), Some(privilege)) => command_receiver.handle_as(command, privilege).await,
+        (Err(error_code), _) => command_receiver.error_response(error_code),
+    };
 
     conn.send(response)
         .await
         .unwrap_or_else(|e| warn!("CGMiner API: cannot send response ({})", e));
 }
 
-/// Start up an API server with a `command_receiver` object, listening on `listen_addr`
-pub async fn run(command_receiver: command::Receiver, listen_addr: SocketAddr) -> io::Result<()> {
+/// Start up an API server with a `command_receiver` object, listening on `listen_addr`.
+/// Requests from source addresses not allowed by `allow_list` are replied with "Access denied"
+/// status and write commands from read-only sources are rejected.
+pub async fn run(
+    command_receiver: command::Receiver,
+    listen_addr: SocketAddr,
+    allow_list: access::AllowList,
+) -> io::Result<()> {
     let mut server = ii_wire::Server::bind(&listen_addr)?;
     let command_receiver = Arc::new(command_receiver);
 
     while let Some(conn) = server.next().await {
         if let Ok(conn) = conn {
+            let peer_addr = match conn.peer_addr() {
+                Ok(peer_addr) => peer_addr,
+                Err(_) => continue,
+            };
+            let privilege = allow_list.privilege(peer_addr.ip());
+            if privilege.is_none() {
+                info!("CGMiner API: access denied to {}", peer_addr);
+            }
             tokio::spawn(handle_connection_task(
                 Connection::new(conn),
                 command_receiver.clone(),
+                privilege,
             ));
         }
     }
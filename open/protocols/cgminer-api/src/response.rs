@@ -116,6 +116,7 @@ pub enum StatusCode {
     EnablePool = 47,
     DisablePool = 48,
     AddPool = 55,
+    Notify = 60,
     RemovePool = 68,
     DevDetails = 69,
     Stats = 70,
@@ -129,6 +130,26 @@ pub enum StatusCode {
     TempCtrl = 200,
     Temps = 201,
     Fans = 202,
+    DiffHistory = 203,
+    AsicBoost = 204,
+    Diag = 205,
+    Digest = 206,
+    Nodes = 207,
+    WorkHistory = 208,
+    Quarantine = 209,
+    Quota = 210,
+    Progress = 211,
+    JobHistory = 212,
+    TestPool = 213,
+    ChainPause = 214,
+    ChainResume = 215,
+    WorkAudit = 216,
+    SessionHistory = 217,
+    Latency = 218,
+    BlockCandidates = 219,
+    Restart = 220,
+    Shutdown = 221,
+    PoolAddresses = 222,
 
     // info status codes
     PoolAlreadyEnabled = 49,
@@ -144,9 +165,20 @@ pub enum StatusCode {
     AccessDeniedCmd = 45,
     MissingAddPoolDetails = 52,
     InvalidAddPoolDetails = 53,
+    RemoveLastPool = 66,
     MissingCheckCmd = 71,
     InvalidAscId = 107,
 
+    // extended error status codes
+    InvalidDigestDays = 250,
+    InvalidNodesQuery = 251,
+    InvalidQuarantineParameter = 252,
+    InvalidDigestQuery = 253,
+    InvalidTestPoolParameter = 254,
+    TestPoolUnavailable = 255,
+    ChainPowerFailed = 256,
+    InvalidWorkAuditParameter = 257,
+
     // special value which is added to the custom status codes
     CustomBase = 300,
 }
@@ -197,10 +229,20 @@ pub enum ErrorCode {
     MissingPoolParameter,
     InvalidPoolId(i32, i32),
     AccessDeniedCmd(String),
+    AccessDenied,
     MissingAddPoolDetails,
     InvalidAddPoolDetails(String),
+    RemoveLastPool(i32, String),
     MissingCheckCmd,
     InvalidAscId(i32, i32),
+    InvalidDigestDays(String),
+    InvalidNodesQuery(String),
+    InvalidQuarantineParameter(String),
+    InvalidDigestQuery(String),
+    InvalidTestPoolParameter(String),
+    TestPoolUnavailable(i32, String),
+    ChainPowerFailed(i32, String),
+    InvalidWorkAuditParameter(String),
 }
 
 impl From<ErrorCode> for Dispatch {
@@ -209,6 +251,7 @@ impl From<ErrorCode> for Dispatch {
     }
 }
 
+#[derive(Debug)]
 pub struct Error {
     status: Status,
     code: StatusCodeType,
@@ -285,6 +328,7 @@ impl From<ErrorCode> for Error {
                 StatusCode::AccessDeniedCmd,
                 format!("Access denied to '{}' command", name),
             ),
+            ErrorCode::AccessDenied => (StatusCode::AccessDeniedCmd, "Access denied".to_string()),
             ErrorCode::MissingAddPoolDetails => (
                 StatusCode::MissingAddPoolDetails,
                 "Missing addpool details".to_string(),
@@ -293,6 +337,10 @@ impl From<ErrorCode> for Error {
                 StatusCode::InvalidAddPoolDetails,
                 format!("Invalid addpool details '{}'", parameter),
             ),
+            ErrorCode::RemoveLastPool(idx, url) => (
+                StatusCode::RemoveLastPool,
+                format!("Cannot remove last pool {}:'{}'", idx, url),
+            ),
             ErrorCode::MissingCheckCmd => {
                 (StatusCode::MissingCheckCmd, "Missing check cmd".to_string())
             }
@@ -303,6 +351,38 @@ impl From<ErrorCode> for Error {
                     idx_requested, idx_last
                 ),
             ),
+            ErrorCode::InvalidDigestDays(parameter) => (
+                StatusCode::InvalidDigestDays,
+                format!("Invalid number of digest days '{}'", parameter),
+            ),
+            ErrorCode::InvalidNodesQuery(reason) => (
+                StatusCode::InvalidNodesQuery,
+                format!("Invalid nodes query: {}", reason),
+            ),
+            ErrorCode::InvalidQuarantineParameter(parameter) => (
+                StatusCode::InvalidQuarantineParameter,
+                format!("Invalid quarantine parameter '{}'", parameter),
+            ),
+            ErrorCode::InvalidDigestQuery(reason) => (
+                StatusCode::InvalidDigestQuery,
+                format!("Invalid digest query: {}", reason),
+            ),
+            ErrorCode::InvalidTestPoolParameter(parameter) => (
+                StatusCode::InvalidTestPoolParameter,
+                format!("Invalid testpool parameter '{}'", parameter),
+            ),
+            ErrorCode::TestPoolUnavailable(idx, reason) => (
+                StatusCode::TestPoolUnavailable,
+                format!("Cannot test pool {}: {}", idx, reason),
+            ),
+            ErrorCode::ChainPowerFailed(idx, reason) => (
+                StatusCode::ChainPowerFailed,
+                format!("Cannot change power of chain {}: {}", idx, reason),
+            ),
+            ErrorCode::InvalidWorkAuditParameter(parameter) => (
+                StatusCode::InvalidWorkAuditParameter,
+                format!("Invalid work audit parameter '{}'", parameter),
+            ),
         };
 
         Self {
@@ -413,6 +493,29 @@ pub struct Pool {
     // Follows attribute extensions
     #[serde(rename = "AsicBoost")]
     pub asic_boost: bool,
+    /// Operator defined label of the pool
+    #[serde(rename = "Label")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Unique display name of the pool used in logs and other responses instead of the URL
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Composite health score driving the failover (0.0 to 1.0)
+    #[serde(rename = "Health Score")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_score: Option<f64>,
+    /// Seconds since the last job has been received from the pool
+    #[serde(rename = "Job Age")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_age: Option<u64>,
+    /// 95th percentile of share submission latency in milliseconds
+    #[serde(rename = "Submit Latency P95")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_latency_p95: Option<u64>,
+    /// Ratio of rejected shares in the recent window used by the health score
+    #[serde(rename = "Recent Rejected%")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_rejected_ratio: Option<Percent>,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
@@ -489,10 +592,16 @@ pub struct Asc {
     #[serde(rename = "Device Elapsed")]
     pub device_elapsed: Elapsed,
     // Follows attribute extensions
+    #[serde(rename = "MHS 24h")]
+    pub mhs_24h: MegaHashes,
     #[serde(rename = "Hardware Error MHS 15m")]
     pub hardware_error_mhs_15m: MegaHashes,
     #[serde(rename = "Nominal MHS")]
     pub nominal_mhs: MegaHashes,
+    #[serde(rename = "Work Starvation ms")]
+    pub work_starvation_ms: u64,
+    #[serde(rename = "Exhausted Engines")]
+    pub exhausted_engines: u64,
 }
 
 impl From<Asc> for Dispatch {
@@ -591,6 +700,49 @@ pub struct Summary {
     // Follows attribute extensions
     #[serde(rename = "MHS 24h")]
     pub mhs_24h: MegaHashes,
+    #[serde(rename = "Safe Mode")]
+    pub safe_mode: bool,
+    /// Number of work solvers whose solutions are withheld from pools
+    #[serde(rename = "Quarantined")]
+    pub quarantined: u32,
+    /// Verified solutions withheld from pools
+    #[serde(rename = "Quarantined Accepted")]
+    pub quarantined_accepted: u64,
+    /// Network difficulty derived from nBits of the current job
+    #[serde(rename = "Network Difficulty")]
+    pub network_difficulty: Difficulty,
+    /// The time the last new block has been seen in jobs of any pool
+    #[serde(rename = "Last Block Time")]
+    pub last_block_time: Time,
+    /// Seconds since the last new block has been seen
+    #[serde(rename = "Since Last Block")]
+    pub since_last_block: Elapsed,
+    /// Average interval between new blocks seen during the session in seconds
+    #[serde(rename = "Average Block Interval")]
+    pub average_block_interval: Interval,
+    /// Number of jobs which are still referenced by the miner
+    #[serde(rename = "Live Jobs")]
+    pub live_jobs: u32,
+    /// Age of the oldest job which is still referenced in seconds
+    #[serde(rename = "Oldest Job Age")]
+    pub oldest_job_age: Elapsed,
+    /// Superseded jobs which have not been released in time
+    #[serde(rename = "Leaked Jobs")]
+    pub leaked_jobs: u64,
+    /// Seconds of mining accumulated over all runs of the miner including this one (`Elapsed`
+    /// is measured since the start of this process)
+    #[serde(rename = "Lifetime Elapsed")]
+    pub lifetime_elapsed: Elapsed,
+    #[serde(rename = "Lifetime Accepted")]
+    pub lifetime_accepted: u64,
+    #[serde(rename = "Lifetime Rejected")]
+    pub lifetime_rejected: u64,
+    #[serde(rename = "Lifetime Difficulty Accepted")]
+    pub lifetime_difficulty_accepted: Difficulty,
+    #[serde(rename = "Lifetime Difficulty Rejected")]
+    pub lifetime_difficulty_rejected: Difficulty,
+    #[serde(rename = "Lifetime Best Share")]
+    pub lifetime_best_share: u64,
 }
 
 impl From<Summary> for Dispatch {
@@ -791,6 +943,86 @@ where
     }
 }
 
+/// The last reason why the device has not been well
+#[derive(Serialize, Eq, PartialEq, Clone, Debug)]
+pub enum NotWellReason {
+    None,
+    #[serde(rename = "Thread Fail Init")]
+    ThreadFailInit,
+    #[serde(rename = "Thread Zero Hash")]
+    ThreadZeroHash,
+    #[serde(rename = "Thread Fail Queue")]
+    ThreadFailQueue,
+    #[serde(rename = "Device Idle for 60s")]
+    SickIdle,
+    #[serde(rename = "Device reports Idle for 600s")]
+    DeadIdle,
+    #[serde(rename = "Device Nostart")]
+    NoStart,
+    #[serde(rename = "Device Over Heat")]
+    OverHeat,
+    #[serde(rename = "Device Thermal Cutoff")]
+    ThermalCutoff,
+    #[serde(rename = "Device Communications")]
+    CommsError,
+    #[serde(rename = "Device Throttle")]
+    Throttle,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct NotifyDevice {
+    #[serde(rename = "NOTIFY")]
+    pub idx: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Last Well")]
+    pub last_well: Time,
+    #[serde(rename = "Last Not Well")]
+    pub last_not_well: Time,
+    #[serde(rename = "Reason Not Well")]
+    pub reason_not_well: NotWellReason,
+    #[serde(rename = "*Thread Fail Init")]
+    pub thread_fail_init: u64,
+    #[serde(rename = "*Thread Zero Hash")]
+    pub thread_zero_hash: u64,
+    #[serde(rename = "*Thread Fail Queue")]
+    pub thread_fail_queue: u64,
+    #[serde(rename = "*Dev Sick Idle 60s")]
+    pub dev_sick_idle_60s: u64,
+    #[serde(rename = "*Dev Dead Idle 600s")]
+    pub dev_dead_idle_600s: u64,
+    #[serde(rename = "*Dev Nostart")]
+    pub dev_nostart: u64,
+    #[serde(rename = "*Dev Over Heat")]
+    pub dev_over_heat: u64,
+    #[serde(rename = "*Dev Thermal Cutoff")]
+    pub dev_thermal_cutoff: u64,
+    #[serde(rename = "*Dev Comms Error")]
+    pub dev_comms_error: u64,
+    #[serde(rename = "*Dev Throttle")]
+    pub dev_throttle: u64,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Notify {
+    pub list: Vec<NotifyDevice>,
+}
+
+impl From<Notify> for Dispatch {
+    fn from(notify: Notify) -> Self {
+        Dispatch::from_success(
+            StatusCode::Notify.into(),
+            "Notify".to_string(),
+            Some(Body {
+                name: "NOTIFY",
+                list: notify.list,
+            }),
+        )
+    }
+}
+
 #[derive(Serialize, PartialEq, Clone, Debug)]
 pub struct PoolStats {
     #[serde(flatten)]
@@ -843,15 +1075,46 @@ pub struct PoolStats {
 pub struct AscStats {
     #[serde(flatten)]
     pub header: StatsHeader,
+    /// Backend specific values (e.g. frequency, voltage or temperatures of hash chain)
+    #[serde(flatten)]
+    pub backend_stats: json::Map<String, json::Value>,
+}
+
+/// General statistics of the miner which precede statistics of devices and pools
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct MinerStats {
+    #[serde(flatten)]
+    pub header: StatsHeader,
+    #[serde(rename = "Uptime")]
+    pub uptime: Elapsed,
+    #[serde(rename = "Generated Work")]
+    pub generated_work: u64,
+    #[serde(rename = "Valid Jobs")]
+    pub valid_jobs: u64,
+    #[serde(rename = "Invalid Jobs")]
+    pub invalid_jobs: u64,
+    #[serde(rename = "Live Jobs")]
+    pub live_jobs: u32,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
 #[serde(untagged)]
 enum StatsType {
+    Miner(MinerStats),
     Pool(PoolStats),
     Asc(AscStats),
 }
 
+impl StatsType {
+    fn header_mut(&mut self) -> &mut StatsHeader {
+        match self {
+            StatsType::Miner(stats) => &mut stats.header,
+            StatsType::Pool(stats) => &mut stats.header,
+            StatsType::Asc(stats) => &mut stats.header,
+        }
+    }
+}
+
 #[derive(Serialize, PartialEq, Clone, Debug)]
 pub struct StatsHeader {
     #[serde(rename = "STATS")]
@@ -872,21 +1135,34 @@ pub struct StatsHeader {
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
 pub struct Stats {
+    pub miner_stats: Option<MinerStats>,
     pub asc_stats: Vec<AscStats>,
     pub pool_stats: Vec<PoolStats>,
 }
 
 impl Stats {
+    /// Sections are numbered the same way as in CGMiner (`STATS` 0, 1, ...) regardless of the
+    /// index set by the handler because parsers of the response depend on it
     fn into_list(self) -> Vec<StatsType> {
-        self.asc_stats
+        let mut list: Vec<_> = self
+            .miner_stats
             .into_iter()
-            .map(|stats| StatsType::Asc(stats))
+            .map(|stats| StatsType::Miner(stats))
+            .chain(
+                self.asc_stats
+                    .into_iter()
+                    .map(|stats| StatsType::Asc(stats)),
+            )
             .chain(
                 self.pool_stats
                     .into_iter()
                     .map(|stats| StatsType::Pool(stats)),
             )
-            .collect()
+            .collect();
+        for (idx, stats) in list.iter_mut().enumerate() {
+            stats.header_mut().idx = idx as i32;
+        }
+        list
     }
 }
 
@@ -125,3 +125,898 @@ impl From<Fans> for Dispatch {
         )
     }
 }
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DiffChange {
+    #[serde(rename = "DIFFCHANGE")]
+    pub idx: i32,
+    /// Index of the pool whose difficulty has been changed
+    #[serde(rename = "POOL")]
+    pub pool: i32,
+    #[serde(rename = "When")]
+    pub when: Time,
+    #[serde(rename = "Trigger")]
+    pub trigger: String,
+    #[serde(rename = "Old Difficulty")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_difficulty: Option<Difficulty>,
+    #[serde(rename = "New Difficulty")]
+    pub new_difficulty: Difficulty,
+    /// Accepted shares per minute before the change
+    #[serde(rename = "Accepted Rate Before")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_rate_before: Option<f64>,
+    /// Accepted shares per minute after the change
+    #[serde(rename = "Accepted Rate After")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_rate_after: Option<f64>,
+    #[serde(rename = "MHS Before")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mhs_before: Option<MegaHashes>,
+    #[serde(rename = "MHS After")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mhs_after: Option<MegaHashes>,
+    /// Accepted shares of the pool which have been left out of the rates because they were
+    /// accounted too late
+    #[serde(rename = "Dropped Samples")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_samples: Option<u64>,
+}
+
+pub struct DiffHistory {
+    pub list: Vec<DiffChange>,
+}
+
+impl From<DiffHistory> for Dispatch {
+    fn from(diff_history: DiffHistory) -> Self {
+        let change_count = diff_history.list.len();
+        Dispatch::from_success(
+            StatusCode::DiffHistory.into(),
+            format!("{} Difficulty change(s)", change_count),
+            Some(Body {
+                name: "DIFFHISTORY",
+                list: diff_history.list,
+            }),
+        )
+    }
+}
+
+/// Current AsicBoost setting
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct AsicBoost {
+    #[serde(rename = "AsicBoost")]
+    pub enabled: bool,
+    /// Number of midstates sent to the hardware in one work
+    #[serde(rename = "MidstateCount")]
+    pub midstate_count: u32,
+}
+
+impl From<AsicBoost> for Dispatch {
+    fn from(asic_boost: AsicBoost) -> Self {
+        Dispatch::from_success(
+            StatusCode::AsicBoost.into(),
+            format!(
+                "AsicBoost {}",
+                if asic_boost.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            ),
+            Some(Body {
+                name: "ASICBOOST",
+                list: vec![asic_boost],
+            }),
+        )
+    }
+}
+
+/// Work progress of a single work solver
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DiagSolver {
+    #[serde(rename = "DIAG")]
+    pub idx: i32,
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Sequence number of the last work delivered to the solver (zero when there is none)
+    #[serde(rename = "Last Assignment ID")]
+    pub last_assignment_id: u64,
+    #[serde(rename = "Last Assignment Time")]
+    pub last_assignment_time: Time,
+    #[serde(rename = "Last Nonce")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_nonce: Option<u32>,
+    #[serde(rename = "Last Solution Time")]
+    pub last_solution_time: Time,
+    /// Number of distinct work engines the solver has received work from
+    #[serde(rename = "Engine Generation")]
+    pub engine_generation: u64,
+    /// Last activity reported by the backend itself
+    #[serde(rename = "Last Activity")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<Time>,
+}
+
+pub struct Diag {
+    pub list: Vec<DiagSolver>,
+}
+
+impl From<Diag> for Dispatch {
+    fn from(diag: Diag) -> Self {
+        let solver_count = diag.list.len();
+        Dispatch::from_success(
+            StatusCode::Diag.into(),
+            format!("{} Work solver(s)", solver_count),
+            Some(Body {
+                name: "DIAG",
+                list: diag.list,
+            }),
+        )
+    }
+}
+
+/// Share counters accumulated during one day
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DigestCounters {
+    #[serde(rename = "Accepted")]
+    pub accepted: u64,
+    #[serde(rename = "Rejected")]
+    pub rejected: u64,
+    #[serde(rename = "Stale")]
+    pub stale: u64,
+    /// Sum of difficulties of accepted shares
+    #[serde(rename = "Difficulty Accepted")]
+    pub accepted_difficulty: u64,
+    #[serde(rename = "Difficulty Rejected")]
+    pub rejected_difficulty: u64,
+    /// Submitted shares without any response
+    #[serde(rename = "Unresolved")]
+    pub unresolved: u64,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DigestPool {
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "User")]
+    pub user: String,
+    #[serde(rename = "Label")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub counters: DigestCounters,
+}
+
+/// Daily record of shares submitted to all pools
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct DigestDay {
+    #[serde(rename = "DIGEST")]
+    pub idx: i32,
+    /// Local date in format YYYY-MM-DD
+    #[serde(rename = "Date")]
+    pub date: String,
+    /// The record does not cover the whole day
+    #[serde(rename = "Partial")]
+    pub partial: bool,
+    #[serde(flatten)]
+    pub total: DigestCounters,
+    #[serde(rename = "Pools")]
+    pub pools: Vec<DigestPool>,
+}
+
+pub struct Digest {
+    pub list: Vec<DigestDay>,
+}
+
+impl From<Digest> for Dispatch {
+    fn from(digest: Digest) -> Self {
+        let day_count = digest.list.len();
+        Dispatch::from_success(
+            StatusCode::Digest.into(),
+            format!("{} Day(s)", day_count),
+            Some(Body {
+                name: "DIGEST",
+                list: digest.list,
+            }),
+        )
+    }
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum NodeStatus {
+    Ok,
+    Degraded,
+    Failed,
+    Paused,
+    Removed,
+}
+
+/// Layout of chips behind a work solver
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct NodeTopology {
+    #[serde(rename = "Version")]
+    pub version: u32,
+    #[serde(rename = "Chips")]
+    pub chips: u32,
+    #[serde(rename = "Domains")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domains: Option<u32>,
+    /// Chip addressing scheme e.g. "linear/4" for chips with address step 4
+    #[serde(rename = "Addressing")]
+    pub addressing: String,
+}
+
+/// Work hub or work solver from the backend hierarchy
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Node {
+    #[serde(rename = "NODE")]
+    pub idx: i32,
+    /// Stable identifier which is used as a cursor for the next page
+    #[serde(rename = "UID")]
+    pub uid: u64,
+    /// Index of the work solver in ASC commands which does not change when other work solvers
+    /// are removed (work hubs do not have any)
+    #[serde(rename = "ASC")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asc: Option<i32>,
+    /// Names of all nodes from the root delimited with '/'
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Status")]
+    pub status: NodeStatus,
+    #[serde(rename = "MHS 1m")]
+    pub mhs_1m: MegaHashes,
+    #[serde(rename = "Topology")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<NodeTopology>,
+}
+
+pub struct Nodes {
+    pub list: Vec<Node>,
+    /// Cursor for the next page when there are more matching nodes
+    pub next_cursor: Option<u64>,
+}
+
+impl From<Nodes> for Dispatch {
+    fn from(nodes: Nodes) -> Self {
+        let node_count = nodes.list.len();
+        let msg = match nodes.next_cursor {
+            Some(cursor) => format!("{} Node(s), next cursor {}", node_count, cursor),
+            None => format!("{} Node(s)", node_count),
+        };
+        Dispatch::from_success(
+            StatusCode::Nodes.into(),
+            msg,
+            Some(Body {
+                name: "NODES",
+                list: nodes.list,
+            }),
+        )
+    }
+}
+
+/// Lifecycle event of work engines
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct WorkEvent {
+    #[serde(rename = "WORKEVENT")]
+    pub idx: i32,
+    /// Unix time in milliseconds
+    #[serde(rename = "Timestamp")]
+    pub timestamp: u64,
+    #[serde(rename = "Event")]
+    pub event: String,
+    #[serde(rename = "Generation")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<u64>,
+    #[serde(rename = "Job ID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<u32>,
+    #[serde(rename = "Solver")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solver: Option<String>,
+    /// Duration of starvation in milliseconds
+    #[serde(rename = "Duration")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    /// Hash of the new block
+    #[serde(rename = "Block")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<String>,
+    #[serde(rename = "Client")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// Version mask of the previous job when the mask has been narrowed
+    #[serde(rename = "Previous Version Mask")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_version_mask: Option<u32>,
+    #[serde(rename = "Version Mask")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_mask: Option<u32>,
+}
+
+pub struct WorkHistory {
+    pub list: Vec<WorkEvent>,
+}
+
+impl From<WorkHistory> for Dispatch {
+    fn from(work_history: WorkHistory) -> Self {
+        let event_count = work_history.list.len();
+        Dispatch::from_success(
+            StatusCode::WorkHistory.into(),
+            format!("{} Event(s)", event_count),
+            Some(Body {
+                name: "WORKHISTORY",
+                list: work_history.list,
+            }),
+        )
+    }
+}
+
+/// Quarantine of a single work solver
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct QuarantineSolver {
+    #[serde(rename = "QUARANTINE")]
+    pub idx: i32,
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Enabled")]
+    pub enabled: bool,
+    /// Withheld solutions which have passed the local verification
+    #[serde(rename = "Accepted")]
+    pub accepted: u64,
+    /// Withheld solutions which have failed the local verification
+    #[serde(rename = "Hardware Errors")]
+    pub hardware_errors: u64,
+    /// Time of the last change of the quarantine (zero when there is none)
+    #[serde(rename = "Last Change")]
+    pub last_change: Time,
+}
+
+pub struct Quarantine {
+    pub list: Vec<QuarantineSolver>,
+}
+
+impl From<Quarantine> for Dispatch {
+    fn from(quarantine: Quarantine) -> Self {
+        let solver_count = quarantine.list.len();
+        Dispatch::from_success(
+            StatusCode::Quarantine.into(),
+            format!("{} Work solver(s)", solver_count),
+            Some(Body {
+                name: "QUARANTINE",
+                list: quarantine.list,
+            }),
+        )
+    }
+}
+
+/// Pause of a single hash chain after its change
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ChainPauseState {
+    #[serde(rename = "CHAIN")]
+    pub idx: i32,
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Paused")]
+    pub paused: bool,
+    /// Number of pauses since the start of mining
+    #[serde(rename = "Pauses")]
+    pub pauses: u64,
+    /// Total time in seconds spent in pause including the ongoing one
+    #[serde(rename = "Paused Time")]
+    pub paused_time: f64,
+    /// Time of the last pause or resume (zero when there is none)
+    #[serde(rename = "Last Change")]
+    pub last_change: Time,
+}
+
+pub struct ChainPause {
+    pub state: ChainPauseState,
+}
+
+impl From<ChainPause> for Dispatch {
+    fn from(chain_pause: ChainPause) -> Self {
+        Dispatch::from_success(
+            StatusCode::ChainPause.into(),
+            format!("Chain {} paused", chain_pause.state.idx),
+            Some(Body {
+                name: "CHAINPAUSE",
+                list: vec![chain_pause.state],
+            }),
+        )
+    }
+}
+
+pub struct ChainResume {
+    pub state: ChainPauseState,
+}
+
+impl From<ChainResume> for Dispatch {
+    fn from(chain_resume: ChainResume) -> Self {
+        Dispatch::from_success(
+            StatusCode::ChainResume.into(),
+            format!("Chain {} resumed", chain_resume.state.idx),
+            Some(Body {
+                name: "CHAINRESUME",
+                list: vec![chain_resume.state],
+            }),
+        )
+    }
+}
+
+/// Amount of work delivered from a single pool to a single work solver during the audit
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct WorkAuditCell {
+    #[serde(rename = "WORKAUDIT")]
+    pub idx: i32,
+    /// Index of the pool at the start of the audit
+    #[serde(rename = "POOL")]
+    pub pool: i32,
+    #[serde(rename = "URL")]
+    pub url: String,
+    /// Unique identifier of the work solver (see NODES)
+    #[serde(rename = "UID")]
+    pub uid: u64,
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Work")]
+    pub work: u64,
+    /// Time in seconds since the start of the audit limited by its duration
+    #[serde(rename = "Elapsed")]
+    pub elapsed: f64,
+    #[serde(rename = "Duration")]
+    pub duration: f64,
+    #[serde(rename = "Recording")]
+    pub recording: bool,
+}
+
+pub struct WorkAudit {
+    pub list: Vec<WorkAuditCell>,
+}
+
+impl From<WorkAudit> for Dispatch {
+    fn from(work_audit: WorkAudit) -> Self {
+        let cell_count = work_audit.list.len();
+        Dispatch::from_success(
+            StatusCode::WorkAudit.into(),
+            format!("{} Cell(s)", cell_count),
+            Some(Body {
+                name: "WORKAUDIT",
+                list: work_audit.list,
+            }),
+        )
+    }
+}
+
+/// Quota of the group which the pool belongs to after its change
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Quota {
+    #[serde(rename = "POOL")]
+    pub idx: i32,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "Group")]
+    pub group: String,
+    #[serde(rename = "Quota")]
+    pub quota: u32,
+    /// Time in seconds over which the distribution of work converges to the new quota
+    #[serde(rename = "Horizon")]
+    pub horizon: u32,
+}
+
+impl From<Quota> for Dispatch {
+    fn from(quota: Quota) -> Self {
+        Dispatch::from_success(
+            StatusCode::Quota.into(),
+            format!("Set quota of group '{}' to {}", quota.group, quota.quota),
+            Some(Body {
+                name: "QUOTA",
+                list: vec![quota],
+            }),
+        )
+    }
+}
+
+/// Initialization progress of a single hash chain
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ChainProgress {
+    #[serde(rename = "PROGRESS")]
+    pub idx: i32,
+    #[serde(rename = "Chain")]
+    pub chain: i32,
+    #[serde(rename = "Phase")]
+    pub phase: String,
+    /// Current step of the phase (zero for phases without steps)
+    #[serde(rename = "Step")]
+    pub step: u32,
+    #[serde(rename = "Steps")]
+    pub steps: u32,
+    #[serde(rename = "Percent")]
+    pub percent: f64,
+    #[serde(rename = "Last Change")]
+    pub last_change: Time,
+}
+
+pub struct Progress {
+    /// All chains have finished their initialization (successfully or not)
+    pub finished: bool,
+    pub list: Vec<ChainProgress>,
+}
+
+impl From<Progress> for Dispatch {
+    fn from(progress: Progress) -> Self {
+        let chain_count = progress.list.len();
+        Dispatch::from_success(
+            StatusCode::Progress.into(),
+            format!(
+                "{} Chain(s) {}",
+                chain_count,
+                if progress.finished {
+                    "finished"
+                } else {
+                    "initializing"
+                }
+            ),
+            Some(Body {
+                name: "PROGRESS",
+                list: progress.list,
+            }),
+        )
+    }
+}
+
+/// Work and shares of a single job superseded by another one
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct JobRecord {
+    #[serde(rename = "JOBHISTORY")]
+    pub idx: i32,
+    #[serde(rename = "POOL")]
+    pub pool: i32,
+    #[serde(rename = "Job ID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<u32>,
+    #[serde(rename = "Prev Hash")]
+    pub previous_hash: String,
+    #[serde(rename = "nTime")]
+    pub ntime: u32,
+    /// Maximal roll of nTime advertised by the pool (zero when there is no limit)
+    #[serde(rename = "nTime Roll Limit")]
+    pub ntime_roll_limit: u32,
+    #[serde(rename = "When")]
+    pub when: Time,
+    /// How long the job has been mined in seconds
+    #[serde(rename = "Duration")]
+    pub duration: f64,
+    #[serde(rename = "Assignments")]
+    pub assignments: u64,
+    /// Solutions which meet the job target
+    #[serde(rename = "Solutions")]
+    pub solutions: u64,
+    #[serde(rename = "Submitted")]
+    pub submitted: u64,
+    #[serde(rename = "Accepted")]
+    pub accepted: u64,
+    #[serde(rename = "Rejected")]
+    pub rejected: u64,
+}
+
+pub struct JobHistory {
+    pub list: Vec<JobRecord>,
+}
+
+impl From<JobHistory> for Dispatch {
+    fn from(job_history: JobHistory) -> Self {
+        let job_count = job_history.list.len();
+        Dispatch::from_success(
+            StatusCode::JobHistory.into(),
+            format!("{} Job(s)", job_count),
+            Some(Body {
+                name: "JOBHISTORY",
+                list: job_history.list,
+            }),
+        )
+    }
+}
+
+/// Session established with the pool on connect or transition of the reject breaker of the pool.
+/// Security parameters are present only for secure connections and the reason only for breaker
+/// transitions.
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct SessionRecord {
+    #[serde(rename = "SESSIONHISTORY")]
+    pub idx: i32,
+    #[serde(rename = "POOL")]
+    pub pool: i32,
+    #[serde(rename = "When")]
+    pub when: Time,
+    /// Whether the previous session has been resumed or the breaker transition (e.g. 'breaker
+    /// tripped')
+    #[serde(rename = "Outcome")]
+    pub outcome: String,
+    #[serde(rename = "Protocol")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(rename = "Cipher")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cipher: Option<String>,
+    #[serde(rename = "Fingerprint")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(rename = "Valid From")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<Time>,
+    #[serde(rename = "Expires")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Time>,
+    /// The certificate has been verified against the authority pinned for the pool (missing when
+    /// there is no pinned authority)
+    #[serde(rename = "Pin Matched")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_matched: Option<bool>,
+    #[serde(rename = "Reason")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+pub struct SessionHistory {
+    pub list: Vec<SessionRecord>,
+}
+
+impl From<SessionHistory> for Dispatch {
+    fn from(session_history: SessionHistory) -> Self {
+        let session_count = session_history.list.len();
+        Dispatch::from_success(
+            StatusCode::SessionHistory.into(),
+            format!("{} Session(s)", session_count),
+            Some(Body {
+                name: "SESSIONHISTORY",
+                list: session_history.list,
+            }),
+        )
+    }
+}
+
+/// Results of connection attempts to a resolved address of the pool
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct AddressRecord {
+    #[serde(rename = "POOLADDRESSES")]
+    pub idx: i32,
+    #[serde(rename = "POOL")]
+    pub pool: i32,
+    #[serde(rename = "Address")]
+    pub address: String,
+    #[serde(rename = "Attempts")]
+    pub attempts: u64,
+    #[serde(rename = "Failures")]
+    pub failures: u64,
+    #[serde(rename = "Last Attempt")]
+    pub last_attempt: Time,
+    /// The last connection attempt has failed
+    #[serde(rename = "Dead")]
+    pub dead: bool,
+    #[serde(rename = "Last Error")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+pub struct PoolAddresses {
+    pub list: Vec<AddressRecord>,
+}
+
+impl From<PoolAddresses> for Dispatch {
+    fn from(pool_addresses: PoolAddresses) -> Self {
+        let address_count = pool_addresses.list.len();
+        Dispatch::from_success(
+            StatusCode::PoolAddresses.into(),
+            format!("{} Address(es)", address_count),
+            Some(Body {
+                name: "POOLADDRESSES",
+                list: pool_addresses.list,
+            }),
+        )
+    }
+}
+
+/// Result of the dry-run connectivity test of a pool
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct PoolTest {
+    #[serde(rename = "POOL")]
+    pub idx: i32,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "Success")]
+    pub success: bool,
+    /// Stage of the test which has failed
+    #[serde(rename = "Failed Stage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_stage: Option<String>,
+    #[serde(rename = "Error")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Duration of the stages in seconds (only the finished ones are present)
+    #[serde(rename = "Resolve Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_time: Option<f64>,
+    #[serde(rename = "Connect Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_time: Option<f64>,
+    #[serde(rename = "Handshake Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handshake_time: Option<f64>,
+    #[serde(rename = "Authorize Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorize_time: Option<f64>,
+    #[serde(rename = "Address")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(rename = "Extranonce1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_nonce1: Option<String>,
+    #[serde(rename = "Extranonce2 Size")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_nonce2_size: Option<u32>,
+    #[serde(rename = "Version Mask")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_mask: Option<String>,
+    #[serde(rename = "Difficulty")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<f64>,
+    /// Summary of the first job when waiting for it has been requested
+    #[serde(rename = "Job ID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(rename = "Prev Hash")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_hash: Option<String>,
+    #[serde(rename = "nTime")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ntime: Option<u32>,
+    #[serde(rename = "Clean Jobs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clean_jobs: Option<bool>,
+    /// Time in seconds from the start of the test to the first job
+    #[serde(rename = "Job Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_time: Option<f64>,
+}
+
+impl From<PoolTest> for Dispatch {
+    fn from(pool_test: PoolTest) -> Self {
+        Dispatch::from_success(
+            StatusCode::TestPool.into(),
+            format!(
+                "Test of pool {}:'{}' {}",
+                pool_test.idx,
+                pool_test.url,
+                if pool_test.success {
+                    "succeeded"
+                } else {
+                    "failed"
+                }
+            ),
+            Some(Body {
+                name: "TESTPOOL",
+                list: vec![pool_test],
+            }),
+        )
+    }
+}
+
+/// Latency of solutions returned by a single work solver (percentiles in milliseconds are
+/// missing until there is any solution)
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct LatencySolver {
+    #[serde(rename = "LATENCY")]
+    pub idx: i32,
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Solutions")]
+    pub solutions: u64,
+    /// Time from dispatch of the work to the hardware to receipt of its solution
+    #[serde(rename = "Latency P50 ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p50: Option<f64>,
+    #[serde(rename = "Latency P95 ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p95: Option<f64>,
+    /// The same latency measured by the hardware when it supports it
+    #[serde(rename = "HW Latency P50 ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hw_latency_p50: Option<f64>,
+    #[serde(rename = "HW Latency P95 ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hw_latency_p95: Option<f64>,
+}
+
+pub struct Latency {
+    pub list: Vec<LatencySolver>,
+}
+
+impl From<Latency> for Dispatch {
+    fn from(latency: Latency) -> Self {
+        let solver_count = latency.list.len();
+        Dispatch::from_success(
+            StatusCode::Latency.into(),
+            format!("{} Work solver(s)", solver_count),
+            Some(Body {
+                name: "LATENCY",
+                list: latency.list,
+            }),
+        )
+    }
+}
+
+/// Solution meeting the network target archived with the response of the pool
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct BlockCandidate {
+    #[serde(rename = "BLOCKCANDIDATES")]
+    pub idx: i32,
+    #[serde(rename = "Hash")]
+    pub hash: String,
+    /// Serialized block header in hex
+    #[serde(rename = "Header")]
+    pub header: String,
+    #[serde(rename = "Pool")]
+    pub pool: String,
+    #[serde(rename = "Archived")]
+    pub archived: Time,
+    /// One of "accepted", "rejected", "stale" or "unknown" when there has been no response
+    #[serde(rename = "Response")]
+    pub response: String,
+    #[serde(rename = "Response Time")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<Time>,
+}
+
+pub struct BlockCandidates {
+    pub list: Vec<BlockCandidate>,
+}
+
+impl From<BlockCandidates> for Dispatch {
+    fn from(block_candidates: BlockCandidates) -> Self {
+        let candidate_count = block_candidates.list.len();
+        Dispatch::from_success(
+            StatusCode::BlockCandidates.into(),
+            format!("{} Block candidate(s)", candidate_count),
+            Some(Body {
+                name: "BLOCKCANDIDATES",
+                list: block_candidates.list,
+            }),
+        )
+    }
+}
+
+/// The miner is going to tear down all clients and the backend and build them again
+pub struct Restart;
+
+impl From<Restart> for Dispatch {
+    fn from(_restart: Restart) -> Self {
+        Dispatch::from_success::<()>(
+            StatusCode::Restart.into(),
+            "Restarting miner".to_string(),
+            None,
+        )
+    }
+}
+
+/// The miner is going to stop mining and exit
+pub struct Shutdown;
+
+impl From<Shutdown> for Dispatch {
+    fn from(_shutdown: Shutdown) -> Self {
+        Dispatch::from_success::<()>(
+            StatusCode::Shutdown.into(),
+            "Shutting down miner".to_string(),
+            None,
+        )
+    }
+}
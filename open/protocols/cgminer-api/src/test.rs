@@ -25,11 +25,12 @@
 mod handler;
 mod utils;
 
+use crate::access;
 use crate::command;
 use crate::commands;
 use crate::response;
 
-use utils::{assert_json_eq, codec_roundtrip};
+use utils::{assert_json_eq, codec_roundtrip, codec_roundtrip_as};
 
 use ii_async_compat::tokio;
 
@@ -133,6 +134,46 @@ impl TestCustomHandler {
                 CustomCommandTwo { value }
             })
     }
+
+    async fn handle_notify(&self) -> command::Result<response::Notify> {
+        Ok(response::Notify {
+            list: vec![response::NotifyDevice {
+                idx: 0,
+                name: "BC5".to_string(),
+                id: 0,
+                last_well: 1589440405,
+                last_not_well: 1589440329,
+                reason_not_well: response::NotWellReason::CommsError,
+                thread_fail_init: 0,
+                thread_zero_hash: 0,
+                thread_fail_queue: 0,
+                dev_sick_idle_60s: 0,
+                dev_dead_idle_600s: 0,
+                dev_nostart: 0,
+                dev_over_heat: 0,
+                dev_thermal_cutoff: 0,
+                dev_comms_error: 3,
+                dev_throttle: 0,
+            }],
+        })
+    }
+
+    async fn handle_dev_details(&self) -> command::Result<response::DevDetails<CustomCommandOne>> {
+        Ok(response::DevDetails {
+            list: vec![response::DevDetail {
+                idx: 0,
+                name: "BC5".to_string(),
+                id: 0,
+                driver: "bitcoincore".to_string(),
+                kernel: "".to_string(),
+                model: "".to_string(),
+                device_path: "1-1".to_string(),
+                info: CustomCommandOne {
+                    attribute: "value".to_string(),
+                },
+            }],
+        })
+    }
 }
 
 #[tokio::test]
@@ -206,6 +247,104 @@ async fn test_multiple() {
     assert_json_eq(&response, &expected);
 }
 
+#[tokio::test]
+async fn test_stats_numbering() {
+    let command: json::Value = json::json!({
+        "command": "stats"
+    });
+    let response = codec_roundtrip(command, None).await;
+    let stats = response["STATS"]
+        .as_array()
+        .expect("BUG: missing STATS section");
+
+    // all sections are numbered in sequence regardless of the index set by the handler
+    let numbering: Vec<_> = stats
+        .iter()
+        .map(|section| (section["STATS"].clone(), section["ID"].clone()))
+        .collect();
+    assert_eq!(
+        numbering,
+        vec![
+            (json::json!(0), json::json!("MINER0")),
+            (json::json!(1), json::json!("ASC0")),
+            (json::json!(2), json::json!("POOL0")),
+        ]
+    );
+    // backend specific values are merged into the section of the device
+    assert_eq!(stats[1]["Frequency"], json::json!(650.0));
+    assert_eq!(stats[0]["Uptime"], json::json!(0));
+}
+
+/// Compare the shape of optional commands with the reference output of cgminer
+#[tokio::test]
+async fn test_notify_and_dev_details() {
+    use command::{DEVDETAILS, NOTIFY};
+
+    let handler = Arc::new(TestCustomHandler);
+    let custom_commands = commands![
+        (NOTIFY: ParameterLess -> handler.handle_notify)
+    ];
+    let command: json::Value = json::json!({ "command": NOTIFY });
+    let response = codec_roundtrip(command, custom_commands).await;
+    let expected = json::json!({
+        "STATUS": [{
+            "STATUS": "S",
+            "When": 0,
+            "Code": 60,
+            "Msg": "Notify",
+            "Description": "TestMiner v1.0",
+        }],
+        "NOTIFY": [{
+            "NOTIFY": 0,
+            "Name": "BC5",
+            "ID": 0,
+            "Last Well": 1589440405,
+            "Last Not Well": 1589440329,
+            "Reason Not Well": "Device Communications",
+            "*Thread Fail Init": 0,
+            "*Thread Zero Hash": 0,
+            "*Thread Fail Queue": 0,
+            "*Dev Sick Idle 60s": 0,
+            "*Dev Dead Idle 600s": 0,
+            "*Dev Nostart": 0,
+            "*Dev Over Heat": 0,
+            "*Dev Thermal Cutoff": 0,
+            "*Dev Comms Error": 3,
+            "*Dev Throttle": 0,
+        }],
+        "id": 1
+    });
+    assert_json_eq(&response, &expected);
+
+    let handler = Arc::new(TestCustomHandler);
+    let custom_commands = commands![
+        (DEVDETAILS: ParameterLess -> handler.handle_dev_details)
+    ];
+    let command: json::Value = json::json!({ "command": DEVDETAILS });
+    let response = codec_roundtrip(command, custom_commands).await;
+    let expected = json::json!({
+        "STATUS": [{
+            "STATUS": "S",
+            "When": 0,
+            "Code": 69,
+            "Msg": "Device Details",
+            "Description": "TestMiner v1.0",
+        }],
+        "DEVDETAILS": [{
+            "DEVDETAILS": 0,
+            "Name": "BC5",
+            "ID": 0,
+            "Driver": "bitcoincore",
+            "Kernel": "",
+            "Model": "",
+            "Device Path": "1-1",
+            "Attribute": "value",
+        }],
+        "id": 1
+    });
+    assert_json_eq(&response, &expected);
+}
+
 #[tokio::test]
 async fn test_single_custom_command() {
     let handler = Arc::new(TestCustomHandler);
@@ -338,3 +477,243 @@ async fn test_multiple_custom_commands() {
 
     assert_json_eq(&response, &expected);
 }
+
+/// Command forwarded to another command table is checked and handled by its target
+#[tokio::test]
+async fn test_forwarded_command() {
+    const CUSTOM_COMMAND: &str = "custom_command";
+    let custom_commands = || {
+        let handler = Arc::new(TestCustomHandler);
+        let check: command::ParameterCheckHandler =
+            Box::new(|_command, parameter| match parameter {
+                Some(_) => Ok(()),
+                None => Err(CustomErrorCode::MissingParameter("value".to_string()).into()),
+            });
+        let target = commands![
+            (CUSTOM_COMMAND: Parameter(check) -> handler.handle_command_two)
+        ];
+        let forward: command::ParameterHandler =
+            Box::new(move |parameter| target[CUSTOM_COMMAND].call(CUSTOM_COMMAND, parameter));
+        let mut custom_commands = command::Map::new();
+        custom_commands.insert(
+            CUSTOM_COMMAND,
+            command::Descriptor::new(
+                CUSTOM_COMMAND,
+                command::HandlerType::Parameter(forward),
+                None,
+            ),
+        );
+        custom_commands
+    };
+
+    let command = json::json!({
+        "command": CUSTOM_COMMAND,
+        "parameter": 42
+    });
+    let response = codec_roundtrip(command, custom_commands()).await;
+    assert_eq!(response["STATUS"][0]["Code"], 302);
+    assert_eq!(response["CUSTOM_COMMAND_TWO"][0]["Value"], 42);
+
+    // parameter check of the target is not bypassed by forwarding
+    let command = json::json!({ "command": CUSTOM_COMMAND });
+    let response = codec_roundtrip(command, custom_commands()).await;
+    assert_eq!(response["STATUS"][0]["STATUS"], "E");
+    assert_eq!(response["STATUS"][0]["Code"], 310);
+}
+
+#[test]
+fn test_allow_rule() {
+    let parse = |rule: &str| rule.parse::<access::Rule>();
+
+    let rule = parse("W:10.0.0.0/24").unwrap();
+    assert_eq!(rule.privilege(), access::Privilege::Write);
+    assert!(rule.contains("10.0.0.1".parse().unwrap()));
+    assert!(rule.contains("10.0.0.255".parse().unwrap()));
+    assert!(!rule.contains("10.0.1.1".parse().unwrap()));
+    // IPv4 client connected to IPv6 socket
+    assert!(rule.contains("::ffff:10.0.0.1".parse().unwrap()));
+    assert!(!rule.contains("fd00::1".parse().unwrap()));
+    assert_eq!(rule.to_string(), "W:10.0.0.0/24");
+
+    // rules without prefix are read-only and they match just the single host
+    let rule = parse("192.168.1.10").unwrap();
+    assert_eq!(rule.privilege(), access::Privilege::Read);
+    assert!(rule.contains("192.168.1.10".parse().unwrap()));
+    assert!(!rule.contains("192.168.1.11".parse().unwrap()));
+    assert_eq!(rule.to_string(), "192.168.1.10");
+
+    let rule = parse("w:fd00::/16").unwrap();
+    assert_eq!(rule.privilege(), access::Privilege::Write);
+    assert!(rule.contains("fd00:1::1".parse().unwrap()));
+    assert!(!rule.contains("fe80::1".parse().unwrap()));
+    assert!(!rule.contains("10.0.0.1".parse().unwrap()));
+    assert_eq!(rule.to_string(), "W:fd00::/16");
+
+    // shortened networks used by CGMiner
+    let rule = parse("W:0/0").unwrap();
+    assert!(rule.contains("1.2.3.4".parse().unwrap()));
+    assert_eq!(rule.to_string(), "W:0.0.0.0/0");
+    assert!(parse("10/8")
+        .unwrap()
+        .contains("10.20.30.40".parse().unwrap()));
+    assert!(parse("::/0").unwrap().contains("::1".parse().unwrap()));
+
+    for invalid in &[
+        "",
+        "W:",
+        "R:10.0.0.1",
+        "10.0.0.0/33",
+        "fd00::/129",
+        "10.0.0.0/x",
+        "1.2.3.4.5/8",
+        "localhost",
+    ] {
+        assert!(parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_allow_list() {
+    let allow_list = access::AllowList::new(Some(vec![
+        "W:127.0.0.1".parse().unwrap(),
+        "10.0.0.0/8".parse().unwrap(),
+        "W:10.0.0.1".parse().unwrap(),
+        "fd00::1".parse().unwrap(),
+    ]));
+    let privilege = |addr: &str| allow_list.privilege(addr.parse().unwrap());
+
+    // missing list allows writing only from loopback
+    let local_write = access::AllowList::local_write();
+    let default_privilege = |addr: &str| local_write.privilege(addr.parse().unwrap());
+    assert_eq!(
+        default_privilege("127.0.0.1"),
+        Some(access::Privilege::Write)
+    );
+    assert_eq!(default_privilege("::1"), Some(access::Privilege::Write));
+    assert_eq!(
+        default_privilege("::ffff:127.0.0.1"),
+        Some(access::Privilege::Write)
+    );
+    assert_eq!(default_privilege("10.0.0.1"), Some(access::Privilege::Read));
+    assert_eq!(default_privilege("fd00::1"), Some(access::Privilege::Read));
+    assert_eq!(privilege("127.0.0.1"), Some(access::Privilege::Write));
+    assert_eq!(
+        privilege("::ffff:127.0.0.1"),
+        Some(access::Privilege::Write)
+    );
+    assert_eq!(privilege("10.1.2.3"), Some(access::Privilege::Read));
+    // the highest privilege of all matching rules is granted
+    assert_eq!(privilege("10.0.0.1"), Some(access::Privilege::Write));
+    assert_eq!(privilege("fd00::1"), Some(access::Privilege::Read));
+    assert_eq!(privilege("192.168.1.1"), None);
+    assert_eq!(
+        access::AllowList::new(Some(vec![])).privilege("127.0.0.1".parse().unwrap()),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_write_privilege() {
+    let handler = Arc::new(TestCustomHandler);
+
+    const CUSTOM_COMMAND: &str = "custom_command";
+    const CUSTOM_WRITE_COMMAND: &str = "custom_write_command";
+    let custom_commands = || {
+        commands![
+            (CUSTOM_COMMAND: ParameterLess -> handler.handle_command_one),
+            (CUSTOM_WRITE_COMMAND: ParameterLess -> handler.handle_command_one, Write)
+        ]
+    };
+    let read = |command: json::Value, custom_commands: Option<command::Map>| {
+        codec_roundtrip_as(command, custom_commands, access::Privilege::Read)
+    };
+
+    // built-in and custom write commands are denied to read-only clients
+    for command in &["removepool", "addpool", "switchpool", CUSTOM_WRITE_COMMAND] {
+        let response = read(
+            json::json!({ "command": command, "parameter": "0" }),
+            Some(custom_commands()),
+        )
+        .await;
+        assert_eq!(response["STATUS"][0]["STATUS"], "E", "{}", command);
+        assert_eq!(response["STATUS"][0]["Code"], 45, "{}", command);
+        assert_eq!(
+            response["STATUS"][0]["Msg"],
+            format!("Access denied to '{}' command", command)
+        );
+    }
+    // read commands are still allowed
+    for command in &["pools", "version", CUSTOM_COMMAND] {
+        let response = read(json::json!({ "command": command }), Some(custom_commands())).await;
+        assert_eq!(response["STATUS"][0]["STATUS"], "S", "{}", command);
+    }
+    // batched commands are checked one by one
+    let response = read(json::json!({ "command": "pools+removepool" }), None).await;
+    assert_eq!(response["pools"][0]["STATUS"][0]["STATUS"], "S");
+    assert_eq!(response["removepool"][0]["STATUS"][0]["Code"], 45);
+
+    // check command reports the access of the client
+    let response = read(
+        json::json!({ "command": "check", "parameter": "removepool" }),
+        None,
+    )
+    .await;
+    assert_eq!(response["CHECK"][0]["Exists"], "Y");
+    assert_eq!(response["CHECK"][0]["Access"], "N");
+    let response = codec_roundtrip(
+        json::json!({ "command": "check", "parameter": "removepool" }),
+        None,
+    )
+    .await;
+    assert_eq!(response["CHECK"][0]["Access"], "Y");
+
+    // write commands are allowed to the client with write privilege
+    let response = codec_roundtrip(
+        json::json!({ "command": CUSTOM_WRITE_COMMAND }),
+        custom_commands(),
+    )
+    .await;
+    assert_eq!(response["STATUS"][0]["STATUS"], "S");
+}
+
+#[tokio::test]
+async fn test_access_denied() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // find unused port
+    let listen_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .unwrap();
+    let command_receiver = command::Receiver::new(
+        handler::BasicTest,
+        "TestMiner".to_string(),
+        "v1.0".to_string(),
+        None,
+    );
+    tokio::spawn(crate::run(
+        command_receiver,
+        listen_addr,
+        access::AllowList::new(Some(vec!["10.0.0.1".parse().unwrap()])),
+    ));
+
+    // wait for the server to bind the address
+    let mut stream = loop {
+        match tokio::net::TcpStream::connect(listen_addr).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::time::delay_for(std::time::Duration::from_millis(10)).await,
+        }
+    };
+    stream
+        .write_all(json::json!({ "command": "version" }).to_string().as_bytes())
+        .await
+        .unwrap();
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await.unwrap();
+
+    // response is null terminated
+    let response: json::Value = json::from_slice(&buf[..buf.len() - 1]).unwrap();
+    assert_eq!(response["STATUS"][0]["STATUS"], "E");
+    assert_eq!(response["STATUS"][0]["Code"], 45);
+    assert_eq!(response["STATUS"][0]["Msg"], "Access denied");
+    assert!(response.get("VERSION").is_none());
+}
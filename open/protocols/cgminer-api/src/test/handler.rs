@@ -69,6 +69,12 @@ impl command::Handler for BasicTest {
                 current_block_height: 0,
                 current_block_version: 0,
                 asic_boost: false,
+                label: None,
+                name: "".to_string(),
+                health_score: None,
+                job_age: None,
+                submit_latency_p95: None,
+                recent_rejected_ratio: None,
             }],
         })
     }
@@ -102,8 +108,11 @@ impl command::Handler for BasicTest {
                 device_hardware_ratio: 0.0,
                 device_rejected_ratio: 0.0,
                 device_elapsed: 0,
+                mhs_24h: 0.0,
                 hardware_error_mhs_15m: 0.0,
                 nominal_mhs: 0.0,
+                work_starvation_ms: 0,
+                exhausted_engines: 0,
             }],
         })
     }
@@ -144,6 +153,22 @@ impl command::Handler for BasicTest {
             pool_rejected_ratio: 0.0,
             pool_stale_ratio: 0.0,
             last_getwork: 0,
+            safe_mode: false,
+            quarantined: 0,
+            quarantined_accepted: 0,
+            network_difficulty: 0.0,
+            last_block_time: 0,
+            since_last_block: 0,
+            average_block_interval: 0.0,
+            live_jobs: 0,
+            oldest_job_age: 0,
+            leaked_jobs: 0,
+            lifetime_elapsed: 0,
+            lifetime_accepted: 0,
+            lifetime_rejected: 0,
+            lifetime_difficulty_accepted: 0.0,
+            lifetime_difficulty_rejected: 0.0,
+            lifetime_best_share: 0,
         })
     }
 
@@ -211,22 +236,41 @@ impl command::Handler for BasicTest {
     }
 
     async fn handle_stats(&self) -> command::Result<response::Stats> {
+        let mut backend_stats = json::Map::new();
+        backend_stats.insert("Frequency".to_string(), json::json!(650.0));
         Ok(response::Stats {
+            miner_stats: Some(response::MinerStats {
+                header: response::StatsHeader {
+                    idx: 0,
+                    id: "MINER0".to_string(),
+                    elapsed: 0,
+                    calls: 0,
+                    wait: 0.0,
+                    max: 0.0,
+                    min: 0.0,
+                },
+                uptime: 0,
+                generated_work: 0,
+                valid_jobs: 0,
+                invalid_jobs: 0,
+                live_jobs: 0,
+            }),
             asc_stats: vec![response::AscStats {
                 header: response::StatsHeader {
                     idx: 0,
-                    id: "".to_string(),
+                    id: "ASC0".to_string(),
                     elapsed: 0,
                     calls: 0,
                     wait: 0.0,
                     max: 0.0,
                     min: 0.0,
                 },
+                backend_stats,
             }],
             pool_stats: vec![response::PoolStats {
                 header: response::StatsHeader {
                     idx: 0,
-                    id: "".to_string(),
+                    id: "POOL0".to_string(),
                     elapsed: 0,
                     calls: 0,
                     wait: 0.0,
@@ -260,6 +304,7 @@ impl command::Handler for BasicTest {
 
     async fn handle_estats(&self) -> command::Result<response::Stats> {
         Ok(response::Stats {
+            miner_stats: None,
             asc_stats: vec![response::AscStats {
                 header: response::StatsHeader {
                     idx: 0,
@@ -270,6 +315,7 @@ impl command::Handler for BasicTest {
                     max: 0.0,
                     min: 0.0,
                 },
+                backend_stats: Default::default(),
             }],
             pool_stats: vec![],
         })
@@ -317,8 +363,11 @@ impl command::Handler for BasicTest {
             device_hardware_ratio: 0.0,
             device_rejected_ratio: 0.0,
             device_elapsed: 0,
+            mhs_24h: 0.0,
             hardware_error_mhs_15m: 0.0,
             nominal_mhs: 0.0,
+            work_starvation_ms: 0,
+            exhausted_engines: 0,
         })
     }
 
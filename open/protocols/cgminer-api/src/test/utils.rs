@@ -20,6 +20,7 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use crate::access;
 use crate::command;
 use crate::response;
 use crate::support;
@@ -42,6 +43,18 @@ impl support::When for ZeroTime {
 }
 
 pub async fn codec_roundtrip<T>(command: json::Value, custom_commands: T) -> Value
+where
+    T: Into<Option<command::Map>>,
+{
+    codec_roundtrip_as(command, custom_commands, access::Privilege::Write).await
+}
+
+/// Same as `codec_roundtrip` but the command is sent by the client with `privilege`
+pub async fn codec_roundtrip_as<T>(
+    command: json::Value,
+    custom_commands: T,
+    privilege: access::Privilege,
+) -> Value
 where
     T: Into<Option<command::Map>>,
 {
@@ -57,7 +70,7 @@ where
     command_buf.extend_from_slice(command.to_string().as_bytes());
 
     let command = codec.decode(&mut command_buf).unwrap().unwrap();
-    let response = command_receiver.handle(command).await;
+    let response = command_receiver.handle_as(command, privilege).await;
     json::to_value(&response).unwrap()
 }
 
@@ -136,7 +136,12 @@ fn build_ok_response_message(id: u32) -> Rpc {
 }
 
 pub fn build_subscribe_ok_response_message() -> Rpc {
-    build_result_response_message(1, build_subscribe_ok_result())
+    build_subscribe_ok_response_message_from(build_subscribe_ok_result())
+}
+
+/// Subscribe response with custom result (e.g. different extra nonce 1)
+pub fn build_subscribe_ok_response_message_from(result: SubscribeResult) -> Rpc {
+    build_result_response_message(1, result)
 }
 
 pub fn build_authorize_ok_response_message() -> Rpc {
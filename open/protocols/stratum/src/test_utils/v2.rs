@@ -166,7 +166,10 @@ pub fn build_open_channel_success() -> OpenStandardMiningChannelSuccess {
         channel_id: 0,
         // Represents difficulty 4
         target: Uint256Bytes(init_target_le),
-        extranonce_prefix: Bytes0_32::new(),
+        // The translation reports V1 extranonce 1 as the prefix
+        extranonce_prefix: Bytes0_32::from_slice(
+            v1::build_subscribe_ok_result().extra_nonce_1().0.as_ref(),
+        ),
         group_channel_id: 0,
     }
 }
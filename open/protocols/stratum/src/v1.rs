@@ -84,6 +84,13 @@ pub trait Handler: 'static + Send {
 
     async fn visit_set_difficulty(&mut self, _id: &MessageId, _payload: &messages::SetDifficulty) {}
 
+    async fn visit_suggest_difficulty(
+        &mut self,
+        _id: &MessageId,
+        _payload: &messages::SuggestDifficulty,
+    ) {
+    }
+
     async fn visit_notify(&mut self, _id: &MessageId, _payload: &messages::Notify) {}
 
     async fn visit_set_version_mask(
@@ -119,6 +126,10 @@ pub fn build_message_from_frame(frame: framing::Frame) -> Result<Message<Protoco
                     as Box<dyn AnyPayload<Protocol>>,
                 Method::SetDifficulty => Box::new(messages::SetDifficulty::try_from(request)?)
                     as Box<dyn AnyPayload<Protocol>>,
+                Method::SuggestDifficulty => {
+                    Box::new(messages::SuggestDifficulty::try_from(request)?)
+                        as Box<dyn AnyPayload<Protocol>>
+                }
                 Method::SetExtranonce => Box::new(messages::SetExtranonce::try_from(request)?)
                     as Box<dyn AnyPayload<Protocol>>,
                 Method::Notify => {
@@ -334,6 +334,27 @@ impl SetDifficulty {
 }
 
 impl_conversion_request!(SetDifficulty, Method::SetDifficulty, visit_set_difficulty);
+
+/// Difficulty preferred by the client. The server is free to ignore the suggestion or to reply
+/// with `SetDifficulty` of any value.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct SuggestDifficulty(pub [f32; 1]);
+
+impl SuggestDifficulty {
+    pub fn new(difficulty: f32) -> Self {
+        Self([difficulty])
+    }
+
+    pub fn value(&self) -> f32 {
+        self.0[0]
+    }
+}
+
+impl_conversion_request!(
+    SuggestDifficulty,
+    Method::SuggestDifficulty,
+    visit_suggest_difficulty
+);
 //#[derive(Deserialize)]
 //struct Helper(#[serde(with = "DurationDef")] Duration);
 //
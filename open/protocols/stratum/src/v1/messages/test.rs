@@ -85,3 +85,17 @@ fn test_subscribe_malformed_result_json() {
         Rpc::Request(_) => (),
     }
 }
+
+#[test]
+fn test_suggest_difficulty_json() {
+    let request =
+        match Rpc::from_str(r#"{"id":3,"method":"mining.suggest_difficulty","params":[1024.0]}"#)
+            .expect("Cannot prepare test request")
+        {
+            Rpc::Request(request) => request,
+            Rpc::Response(resp) => panic!("Received response ({:?}) instead of request", resp),
+        };
+    let suggest = SuggestDifficulty::try_from(request).expect("Conversion failed");
+    assert_eq!(suggest, SuggestDifficulty::new(1024.0));
+    assert_eq!(suggest.value(), 1024.0);
+}
@@ -49,6 +49,8 @@ pub enum Method {
     Authorize,
     #[serde(rename = "mining.set_difficulty")]
     SetDifficulty,
+    #[serde(rename = "mining.suggest_difficulty")]
+    SuggestDifficulty,
     #[serde(rename = "mining.set_extranonce")]
     SetExtranonce,
     #[serde(rename = "mining.configure")]
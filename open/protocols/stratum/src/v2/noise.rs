@@ -27,6 +27,7 @@
 use bytes::{Bytes, BytesMut};
 use snow::{params::NoiseParams, Builder, HandshakeState, TransportState};
 use std::convert::TryFrom;
+use std::time::SystemTime;
 use tokio::net::TcpStream;
 use tokio_util::codec::{Framed, FramedParts};
 
@@ -79,6 +80,34 @@ pub fn generate_keypair() -> Result<StaticKeypair> {
     builder.generate_keypair().map_err(Into::into)
 }
 
+/// Security parameters of the noise session established by the `Initiator`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    /// Name of the noise protocol (handshake pattern, DH function, cipher and hash function)
+    pub protocol: &'static str,
+    /// Cipher used for encryption of the transport messages
+    pub cipher: &'static str,
+    /// Hex encoded SHA-256 digest of the static public key of the responder
+    pub fingerprint: String,
+    pub valid_from: SystemTime,
+    pub not_valid_after: SystemTime,
+}
+
+impl SessionInfo {
+    pub fn new(certificate: &auth::Certificate) -> Self {
+        Self {
+            protocol: PARAMS,
+            cipher: PARAMS
+                .split('_')
+                .nth(3)
+                .expect("BUG: missing cipher in noise parameters"),
+            fingerprint: certificate.fingerprint(),
+            valid_from: certificate.valid_from(),
+            not_valid_after: certificate.not_valid_after(),
+        }
+    }
+}
+
 pub struct Initiator {
     stage: usize,
     handshake_state: HandshakeState,
@@ -86,6 +115,8 @@ pub struct Initiator {
     /// the SignatureNoiseMessage and of the static public key of the `Responder` and will verify
     /// the authenticity of the static public key of the Responder
     authority_public_key: ed25519_dalek::PublicKey,
+    /// Security parameters available after successful verification of the remote static key
+    session_info: Option<SessionInfo>,
 }
 
 impl Initiator {
@@ -102,16 +133,32 @@ impl Initiator {
             stage: 0,
             handshake_state,
             authority_public_key,
+            session_info: None,
         }
     }
 
     pub async fn connect(self, connection: TcpStream) -> Result<v2::Framed> {
+        self.connect_with_info(connection)
+            .await
+            .map(|(framed_stream, _)| framed_stream)
+    }
+
+    /// Same as `connect` but it also provides security parameters of the established session
+    pub async fn connect_with_info(
+        self,
+        connection: TcpStream,
+    ) -> Result<(v2::Framed, SessionInfo)> {
         let mut noise_framed_stream = ii_wire::Connection::<Framing>::new(connection).into_inner();
 
         let handshake = handshake::Handshake::new(self);
-        let transport_mode = handshake.run(&mut noise_framed_stream).await?;
-
-        Ok(transport_mode.into_stratum_framed_stream(noise_framed_stream))
+        let (transport_mode, session_info) = handshake.run(&mut noise_framed_stream).await?;
+        let session_info =
+            session_info.expect("BUG: noise handshake finished without verification");
+
+        Ok((
+            transport_mode.into_stratum_framed_stream(noise_framed_stream),
+            session_info,
+        ))
     }
 
     /// Verify the signature of the remote static key
@@ -138,14 +185,17 @@ impl Initiator {
         certificate
             .validate()
             .context("Validation of certificate")?;
+        self.session_info = Some(SessionInfo::new(&certificate));
 
         Ok(())
     }
 }
 
 impl handshake::Step for Initiator {
-    fn into_handshake_state(self) -> HandshakeState {
-        self.handshake_state
+    type Output = Option<SessionInfo>;
+
+    fn into_handshake_state(self) -> (HandshakeState, Self::Output) {
+        (self.handshake_state, self.session_info)
     }
 
     fn step(
@@ -210,15 +260,17 @@ impl Responder {
         let mut noise_framed_stream = ii_wire::Connection::<Framing>::new(connection).into_inner();
 
         let handshake = handshake::Handshake::new(self);
-        let transport_mode = handshake.run(&mut noise_framed_stream).await?;
+        let (transport_mode, _) = handshake.run(&mut noise_framed_stream).await?;
 
         Ok(transport_mode.into_stratum_framed_stream(noise_framed_stream))
     }
 }
 
 impl handshake::Step for Responder {
-    fn into_handshake_state(self) -> HandshakeState {
-        self.handshake_state
+    type Output = ();
+
+    fn into_handshake_state(self) -> (HandshakeState, Self::Output) {
+        (self.handshake_state, ())
     }
 
     fn step(
@@ -318,7 +370,39 @@ impl TransportMode {
 #[cfg(test)]
 pub(crate) mod test {
     use super::*;
+    use bitcoin_hashes::{sha256, Hash};
     use handshake::Step as _;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// Validity of the fixture certificate (2020-01-01 - 2100-01-01)
+    const FIXTURE_VALID_FROM: u64 = 1_577_836_800;
+    const FIXTURE_NOT_VALID_AFTER: u64 = 4_102_444_800;
+    /// SHA-256 of the fixture static public key
+    const FIXTURE_FINGERPRINT: &str =
+        "75877bb41d393b5fb8455ce60ecd8dda001d06316496b14dfa7f895656eeca4a";
+
+    fn build_authority_keypair(secret: u8) -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[secret; 32])
+            .expect("BUG: cannot build authority secret key");
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    /// Helper that builds a certificate of a fixed static public key signed by a fixed
+    /// authority so that all its metadata are known in advance
+    fn build_fixture_certificate() -> auth::Certificate {
+        let authority_keypair = build_authority_keypair(1);
+        let header = auth::SignedPartHeader::with_validity(
+            UNIX_EPOCH + Duration::from_secs(FIXTURE_VALID_FROM),
+            UNIX_EPOCH + Duration::from_secs(FIXTURE_NOT_VALID_AFTER),
+        )
+        .expect("BUG: cannot build certificate header");
+        let signed_part = auth::SignedPart::new(header, vec![2u8; 32], authority_keypair.public);
+        let signature = signed_part
+            .sign_with(&authority_keypair)
+            .expect("BUG: cannot sign");
+        auth::Certificate::new(signed_part, signature)
+    }
 
     /// Helper that builds:
     /// - serialized signature noise message
@@ -401,12 +485,14 @@ pub(crate) mod test {
         let initiator_transport_mode = TransportMode::new(
             initiator
                 .into_handshake_state()
+                .0
                 .into_transport_mode()
                 .expect("BUG: cannot convert initiator into transport mode"),
         );
         let responder_transport_mode = TransportMode::new(
             responder
                 .into_handshake_state()
+                .0
                 .into_transport_mode()
                 .expect("BUG: cannot convert responder into transport mode"),
         );
@@ -433,6 +519,26 @@ pub(crate) mod test {
         assert_eq!(&message[..], &decrypted_msg, "Messages don't match");
     }
 
+    #[test]
+    fn test_session_info() {
+        let certificate = build_fixture_certificate();
+        certificate
+            .validate()
+            .expect("BUG: fixture certificate not valid");
+
+        let session_info = SessionInfo::new(&certificate);
+        assert_eq!(
+            session_info,
+            SessionInfo {
+                protocol: "Noise_NX_25519_ChaChaPoly_BLAKE2s",
+                cipher: "ChaChaPoly",
+                fingerprint: FIXTURE_FINGERPRINT.to_string(),
+                valid_from: UNIX_EPOCH + Duration::from_secs(FIXTURE_VALID_FROM),
+                not_valid_after: UNIX_EPOCH + Duration::from_secs(FIXTURE_NOT_VALID_AFTER),
+            }
+        );
+    }
+
     fn bind_test_server() -> Option<(ii_wire::Server, ii_wire::Address)> {
         const ADDR: &'static str = "127.0.0.1";
         const MIN_PORT: u16 = 9999;
@@ -463,6 +569,7 @@ pub(crate) mod test {
         // Prepare test certificate and a serialized noise message that contains the signature
         let (signature_noise_message, authority_keypair, static_keypair) =
             build_serialized_signature_noise_message_and_keypairs();
+        let static_keypair_public = static_keypair.public.clone();
 
         // Spawn server task that reacts to any incoming message and responds
         // with SetupConnectionSuccess
@@ -492,12 +599,20 @@ pub(crate) mod test {
             .await
             .expect("BUG: Cannot connect to noise endpoint");
 
+        let expected_fingerprint =
+            hex::encode(sha256::Hash::hash(&static_keypair_public[..]).into_inner());
         let initiator = Initiator::new(authority_keypair.public);
-        let mut client_framed_stream = initiator
-            .connect(connection)
+        let (mut client_framed_stream, session_info) = initiator
+            .connect_with_info(connection)
             .await
             .expect("BUG: cannot connect to noise responder");
 
+        assert_eq!(session_info.protocol, PARAMS);
+        assert_eq!(session_info.cipher, "ChaChaPoly");
+        assert_eq!(session_info.fingerprint, expected_fingerprint);
+        assert!(session_info.valid_from <= SystemTime::now());
+        assert!(session_info.not_valid_after > SystemTime::now());
+
         let received_frame = client_framed_stream
             .next()
             .await
@@ -50,7 +50,10 @@ impl SignedPartHeader {
 
     pub fn with_duration(valid_for: Duration) -> Result<Self> {
         let valid_from = SystemTime::now();
-        let not_valid_after = valid_from + valid_for;
+        Self::with_validity(valid_from, valid_from + valid_for)
+    }
+
+    pub fn with_validity(valid_from: SystemTime, not_valid_after: SystemTime) -> Result<Self> {
         Ok(Self {
             version: Self::VERSION,
             valid_from: Self::system_time_to_unix_time_u32(&valid_from)?,
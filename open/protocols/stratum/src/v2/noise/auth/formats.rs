@@ -22,6 +22,7 @@
 
 //! All formats that need to be persisted as physical files, too
 
+use bitcoin_hashes::{sha256, Hash};
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fmt;
@@ -261,6 +262,20 @@ impl Certificate {
         signed_part.verify_expiration(SystemTime::now())
     }
 
+    /// Hex encoded SHA-256 digest of the certified static public key
+    pub fn fingerprint(&self) -> String {
+        let public_key = self.public_key.clone().into_inner();
+        hex::encode(sha256::Hash::hash(&public_key[..]).into_inner())
+    }
+
+    pub fn valid_from(&self) -> SystemTime {
+        self.signed_part_header.valid_from()
+    }
+
+    pub fn not_valid_after(&self) -> SystemTime {
+        self.signed_part_header.not_valid_after()
+    }
+
     pub fn from_noise_message(
         signature_noise_message: SignatureNoiseMessage,
         pubkey: StaticPublicKey,
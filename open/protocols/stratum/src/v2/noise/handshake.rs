@@ -61,6 +61,9 @@ pub(super) enum StepResult {
 
 /// Objects that can perform 1 handshake step implement this trait
 pub(super) trait Step {
+    /// Information gathered during the handshake which is provided along the transport mode
+    type Output;
+
     /// Proceeds with the handshake and processes an optional incoming message - `in_msg` and
     /// generates a new handshake message to be sent out
     ///
@@ -69,8 +72,8 @@ pub(super) trait Step {
     /// this buffer and returned as appropriate `StepResult`
     fn step(&mut self, in_msg: Option<Message>, noise_bytes: BytesMut) -> Result<StepResult>;
 
-    /// Transforms step into the handshake state
-    fn into_handshake_state(self) -> HandshakeState;
+    /// Transforms step into the handshake state and the gathered information
+    fn into_handshake_state(self) -> (HandshakeState, Self::Output);
 }
 
 /// The purpose of this object is to interpret the `StepResult` instructions while driving the
@@ -112,7 +115,7 @@ where
     pub(super) async fn run(
         mut self,
         handshake_stream: &mut super::NoiseFramedTcpStream,
-    ) -> Result<super::TransportMode> {
+    ) -> Result<(super::TransportMode, T::Output)> {
         let mut in_msg: Option<Message> = None;
 
         loop {
@@ -155,10 +158,10 @@ where
         // At this point the handshake has been successfully completed
         // Consume the handshake step object extracting the `HandshakeState` and attempt to
         // transition into transport mode
-        self.handshake_step
-            .into_handshake_state()
+        let (handshake_state, output) = self.handshake_step.into_handshake_state();
+        handshake_state
             .into_transport_mode()
             .map_err(Into::into)
-            .map(|t| super::TransportMode::new(t))
+            .map(|t| (super::TransportMode::new(t), output))
     }
 }
@@ -55,6 +55,10 @@ pub enum ErrorKind {
     /// CLI usage / configuration error
     #[fail(display = "Could not parse `{}` as IP address", _0)]
     BadIp(String),
+
+    /// Job has been rejected because its coinbase transaction did not pass verification
+    #[fail(display = "Job rejected: {}", _0)]
+    CoinbaseRejected(crate::translation::CoinbaseRejection),
 }
 
 /// Implement Fail trait instead of use Derive to get more control over custom type.
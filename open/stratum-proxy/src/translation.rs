@@ -26,6 +26,7 @@ use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fmt;
 use std::mem::size_of;
+use std::sync::Arc;
 
 use ii_async_compat::{bytes, futures};
 
@@ -44,12 +45,15 @@ use ii_stratum::v2::{
 
 use ii_logging::macros::*;
 
-use crate::error::{Error, Result, ResultExt};
+use crate::error::{Error, ErrorKind, Result, ResultExt};
 use crate::util;
 
+mod coinbase;
 #[cfg(test)]
 mod test;
 
+pub use coinbase::{CoinbaseCheck, CoinbaseRejection, CoinbaseVerifier};
+
 /// Sequential ID to pair up messages, requests etc.
 #[derive(Default, Debug)]
 pub struct SeqId(u32);
@@ -70,16 +74,40 @@ impl SeqId {
 pub struct V2ToV1TranslationOptions {
     /// Try to send `extranonce.subscribe` during handshake
     pub try_enable_xnsub: bool,
+    /// Session of a previous translation which should be resumed by requesting its extranonce 1
+    /// in `mining.subscribe`
+    pub resume_session: Option<V1Session>,
+    /// Verifier of coinbase transactions of jobs received from upstream
+    pub coinbase_verifier: Option<Arc<CoinbaseVerifier>>,
 }
 
 impl Default for V2ToV1TranslationOptions {
     fn default() -> Self {
         Self {
             try_enable_xnsub: false,
+            resume_session: None,
+            coinbase_verifier: None,
         }
     }
 }
 
+/// Upstream V1 session which can be handed over to a translation of a new connection. The job
+/// mapping is preserved so that solutions of jobs from the previous connection can still be
+/// submitted when the upstream server resumes the session.
+#[derive(Clone, Debug)]
+pub struct V1Session {
+    extra_nonce1: v1::ExtraNonce1,
+    /// Next V2 job ID (IDs of the previous jobs must not be reused)
+    next_job_id: u32,
+    job_map: JobMap,
+}
+
+impl V1Session {
+    pub fn extra_nonce1(&self) -> &v1::ExtraNonce1 {
+        &self.extra_nonce1
+    }
+}
+
 /// States of the Translation setup
 #[derive(PartialEq, Debug)]
 enum V2ToV1TranslationState {
@@ -194,6 +222,10 @@ impl V2ToV1Translation {
         v2_tx: mpsc::Sender<v2::Frame>,
         options: V2ToV1TranslationOptions,
     ) -> Self {
+        let (v2_job_id, v2_to_v1_job_map) = match options.resume_session.as_ref() {
+            Some(session) => (SeqId(session.next_job_id), session.job_map.clone()),
+            None => (SeqId::new(), JobMap::default()),
+        };
         Self {
             v2_conn_details: None,
             v2_channel_details: None,
@@ -210,12 +242,21 @@ impl V2ToV1Translation {
             v1_deferred_notify: None,
             v2_tx,
             v2_req_id: SeqId::new(),
-            v2_job_id: SeqId::new(),
-            v2_to_v1_job_map: JobMap::default(),
+            v2_job_id,
+            v2_to_v1_job_map,
             options,
         }
     }
 
+    /// Upstream session that can be resumed by translation of the next connection
+    pub fn session(&self) -> Option<V1Session> {
+        self.v1_extra_nonce1.as_ref().map(|extra_nonce1| V1Session {
+            extra_nonce1: extra_nonce1.clone(),
+            next_job_id: self.v2_job_id.0,
+            job_map: self.v2_to_v1_job_map.clone(),
+        })
+    }
+
     /// Builds a V1 request from V1 method and assigns a unique identifier to it
     fn v1_method_into_message<M, E>(
         &mut self,
@@ -266,7 +307,15 @@ impl V2ToV1Translation {
                 req_id: v2_channel_details.req_id,
                 channel_id: Self::CHANNEL_ID,
                 target: init_target.clone(),
-                extranonce_prefix: Bytes0_32::new(),
+                // Extranonce 1 identifies the upstream session so the client can detect whether
+                // it has been resumed
+                extranonce_prefix: self
+                    .v1_extra_nonce1
+                    .as_ref()
+                    .and_then(|extra_nonce1| {
+                        Bytes0_32::try_from(extra_nonce1.0.as_ref().as_slice()).ok()
+                    })
+                    .unwrap_or_default(),
                 group_channel_id: Self::DEFAULT_GROUP_CHANNEL_ID,
             };
             util::submit_message(&mut self.v2_tx, msg)?;
@@ -462,6 +511,16 @@ impl V2ToV1Translation {
         self.v1_extra_nonce1 = Some(subscribe_result.extra_nonce_1().clone());
         self.v1_extra_nonce2_size = subscribe_result.extra_nonce_2_size().clone();
 
+        if let Some(session) = self.options.resume_session.take() {
+            if Some(session.extra_nonce1()) == self.v1_extra_nonce1.as_ref() {
+                info!("V1 session has been resumed");
+            } else {
+                // Jobs of the previous session cannot be submitted in the new one
+                info!("V1 session resumption refused, starting a new session");
+                self.v2_to_v1_job_map.clear();
+            }
+        }
+
         // In order to finalize the opening procedure we need 3 items: authorization,
         // subscription and difficulty
         if self.v1_authorized && self.v2_target.is_some() {
@@ -627,16 +686,12 @@ impl V2ToV1Translation {
         util::submit_message(&mut self.v2_tx, err_msg)
     }
 
-    /// Iterates the merkle branches and calculates block merkle root using the extra nonce 1.
+    /// Builds coinbase transaction of the job using the extra nonce 1.
     /// Extra nonce 2 encodes the channel ID.
     /// TODO review, whether a Result has to be returned as missing enonce1 would be considered a bug
-    fn calculate_merkle_root(
-        &mut self,
-        payload: &v1::messages::Notify,
-    ) -> crate::error::Result<sha256d::Hash> {
+    fn build_coin_base(&self, payload: &v1::messages::Notify) -> crate::error::Result<BytesMut> {
         // TODO get rid of extra nonce 1 cloning
         if let Some(v1_extra_nonce1) = self.v1_extra_nonce1.clone() {
-            // Build coin base transaction,
             let mut coin_base: BytesMut = BytesMut::with_capacity(
                 payload.coin_base_1().len()
                     + (v1_extra_nonce1.0).len()
@@ -650,25 +705,7 @@ impl V2ToV1Translation {
                     .as_ref(),
             );
             coin_base.extend_from_slice(payload.coin_base_2());
-
-            let mut engine = sha256d::Hash::engine();
-            engine.input(&coin_base);
-
-            let cb_tx_hash = sha256d::Hash::from_engine(engine);
-            trace!("Coinbase TX hash: {:x?} {:x?}", cb_tx_hash, coin_base);
-
-            let merkle_root =
-                payload
-                    .merkle_branch()
-                    .iter()
-                    .fold(cb_tx_hash, |curr_merkle_root, tx_hash| {
-                        let mut engine = sha256d::Hash::engine();
-                        engine.input(&curr_merkle_root.into_inner());
-                        engine.input(tx_hash.as_ref().as_slice());
-                        sha256d::Hash::from_engine(engine)
-                    });
-            trace!("Merkle root calculated: {:x?}", merkle_root);
-            Ok(merkle_root)
+            Ok(coin_base)
         } else {
             Err(super::error::ErrorKind::General(
                 "Extra nonce 1 missing, cannot calculate merkle root".into(),
@@ -677,6 +714,28 @@ impl V2ToV1Translation {
         }
     }
 
+    /// Iterates the merkle branches and calculates block merkle root of the `coin_base`
+    fn calculate_merkle_root(coin_base: &[u8], payload: &v1::messages::Notify) -> sha256d::Hash {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(coin_base);
+
+        let cb_tx_hash = sha256d::Hash::from_engine(engine);
+        trace!("Coinbase TX hash: {:x?} {:x?}", cb_tx_hash, coin_base);
+
+        let merkle_root =
+            payload
+                .merkle_branch()
+                .iter()
+                .fold(cb_tx_hash, |curr_merkle_root, tx_hash| {
+                    let mut engine = sha256d::Hash::engine();
+                    engine.input(&curr_merkle_root.into_inner());
+                    engine.input(tx_hash.as_ref().as_slice());
+                    sha256d::Hash::from_engine(engine)
+                });
+        trace!("Merkle root calculated: {:x?}", merkle_root);
+        merkle_root
+    }
+
     /// Builds SetNewPrevHash for the specified v1 Notify `payload`
     ///
     /// The SetNewPrevHash has to reference the future job that the V2 downstream has
@@ -755,7 +814,13 @@ impl V2ToV1Translation {
     }
 
     fn perform_notify(&mut self, payload: &v1::messages::Notify) -> Result<()> {
-        let merkle_root = self.calculate_merkle_root(payload)?;
+        let coin_base = self.build_coin_base(payload)?;
+        if let Some(coinbase_verifier) = self.options.coinbase_verifier.as_ref() {
+            coinbase_verifier
+                .verify(&coin_base)
+                .map_err(ErrorKind::CoinbaseRejected)?;
+        }
+        let merkle_root = Self::calculate_merkle_root(&coin_base, payload);
 
         let v2_job = v2::messages::NewMiningJob {
             channel_id: Self::CHANNEL_ID,
@@ -1092,7 +1157,10 @@ impl v2::Handler for V2ToV1Translation {
             let hostname_port = format!("{}:{}", hostname, conn_details.endpoint_port);
             let subscribe = v1::messages::Subscribe(
                 Some(conn_details.device.fw_ver.to_string()),
-                None,
+                self.options
+                    .resume_session
+                    .as_ref()
+                    .map(|session| session.extra_nonce1().clone()),
                 Some(hostname_port),
                 None,
             );
@@ -0,0 +1,142 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Verification of coinbase transactions of jobs from pools which commit to include a marker in
+//! them. A job with tampered coinbase (e.g. by a man-in-the-middle) is not passed downstream.
+
+use ii_logging::macros::*;
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Requirements on the coinbase transaction of each job
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoinbaseCheck {
+    /// Bytes which have to be present anywhere in the coinbase transaction
+    pub marker: Vec<u8>,
+    /// Output script which has to be present in the coinbase transaction
+    pub payout_script: Option<Vec<u8>>,
+    /// Number of consecutive rejected jobs which raises an alert
+    pub alert_threshold: usize,
+}
+
+/// Reason for rejecting a job by `CoinbaseVerifier`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoinbaseRejection {
+    MissingMarker,
+    PayoutScriptMismatch,
+}
+
+impl fmt::Display for CoinbaseRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingMarker => write!(f, "coinbase marker is missing"),
+            Self::PayoutScriptMismatch => write!(f, "coinbase payout script does not match"),
+        }
+    }
+}
+
+/// Returns true when `needle` is present in `haystack`
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+/// Verifies coinbase transactions of jobs and counts the rejected ones. The verifier is shared
+/// by all translations of one client so consecutive rejections are counted across reconnects.
+#[derive(Debug)]
+pub struct CoinbaseVerifier {
+    check: CoinbaseCheck,
+    /// Total number of rejected jobs
+    rejected: AtomicU64,
+    /// Number of jobs rejected since the last accepted one
+    consecutive_rejected: AtomicUsize,
+}
+
+impl CoinbaseVerifier {
+    pub fn new(check: CoinbaseCheck) -> Self {
+        Self {
+            check,
+            rejected: AtomicU64::new(0),
+            consecutive_rejected: AtomicUsize::new(0),
+        }
+    }
+
+    fn check(&self, coin_base: &[u8]) -> Result<(), CoinbaseRejection> {
+        if !contains(coin_base, &self.check.marker) {
+            Err(CoinbaseRejection::MissingMarker)?;
+        }
+        if let Some(payout_script) = &self.check.payout_script {
+            if !contains(coin_base, payout_script) {
+                Err(CoinbaseRejection::PayoutScriptMismatch)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify `coin_base` of a new job and account the result. An alert is raised when the
+    /// number of consecutive rejected jobs reaches the threshold.
+    pub fn verify(&self, coin_base: &[u8]) -> Result<(), CoinbaseRejection> {
+        match self.check(coin_base) {
+            Ok(()) => {
+                let consecutive_rejected = self.consecutive_rejected.swap(0, Ordering::Relaxed);
+                if consecutive_rejected >= self.check.alert_threshold {
+                    info!(
+                        "Coinbase verification recovered after {} rejected jobs",
+                        consecutive_rejected
+                    );
+                }
+                Ok(())
+            }
+            Err(rejection) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                let consecutive_rejected =
+                    self.consecutive_rejected.fetch_add(1, Ordering::Relaxed) + 1;
+                if consecutive_rejected == self.check.alert_threshold {
+                    error!(
+                        "ALERT: {} consecutive jobs rejected ({}), the jobs may have been \
+                         tampered with",
+                        consecutive_rejected, rejection
+                    );
+                }
+                Err(rejection)
+            }
+        }
+    }
+
+    /// Total number of rejected jobs
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Number of jobs rejected since the last accepted one
+    pub fn consecutive_rejected(&self) -> usize {
+        self.consecutive_rejected.load(Ordering::Relaxed)
+    }
+
+    /// Returns true when the number of consecutive rejected jobs has reached the alert threshold
+    pub fn is_alerting(&self) -> bool {
+        self.consecutive_rejected() >= self.check.alert_threshold
+    }
+}
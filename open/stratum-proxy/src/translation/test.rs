@@ -165,6 +165,302 @@ async fn test_setup_connection_translate() {
     // });
 }
 
+/// Captures messages generated by the translation during opening of the channel
+#[derive(Default)]
+struct OpenChannelCapture {
+    subscribe: Option<v1::messages::Subscribe>,
+    success: Option<v2::messages::OpenStandardMiningChannelSuccess>,
+}
+
+#[async_trait]
+impl v1::Handler for OpenChannelCapture {
+    async fn visit_subscribe(&mut self, _id: &v1::MessageId, payload: &v1::messages::Subscribe) {
+        self.subscribe = Some(payload.clone());
+    }
+}
+
+#[async_trait]
+impl v2::Handler for OpenChannelCapture {
+    async fn visit_open_standard_mining_channel_success(
+        &mut self,
+        _header: &v2::framing::Header,
+        payload: &v2::messages::OpenStandardMiningChannelSuccess,
+    ) {
+        self.success = Some(payload.clone());
+    }
+}
+
+/// Drives the translation through channel opening with upstream (mock) server responding with
+/// `subscribe_result`
+async fn open_channel(
+    translation: &mut V2ToV1Translation,
+    v1_rx: &mut mpsc::Receiver<v1::Frame>,
+    v2_rx: &mut mpsc::Receiver<v2::Frame>,
+    subscribe_result: v1::messages::SubscribeResult,
+) -> OpenChannelCapture {
+    let mut capture = OpenChannelCapture::default();
+
+    v2_simulate_incoming_message(translation, test_utils::v2::build_setup_connection()).await;
+    v1_verify_generated_response_message(v1_rx).await;
+    v1_simulate_incoming_message(
+        translation,
+        test_utils::v1::build_configure_ok_response_message(),
+    )
+    .await;
+    v2_verify_generated_response_message(v2_rx).await;
+
+    v2_simulate_incoming_message(translation, test_utils::v2::build_open_channel()).await;
+    let frame = v1_rx.next().await.expect("Subscribe was expected");
+    v1::build_message_from_frame(frame)
+        .expect("Deserialization failed")
+        .accept(&mut capture)
+        .await;
+    v1_verify_generated_response_message(v1_rx).await;
+
+    v1_simulate_incoming_message(
+        translation,
+        test_utils::v1::build_subscribe_ok_response_message_from(subscribe_result),
+    )
+    .await;
+    v1_simulate_incoming_message(
+        translation,
+        test_utils::v1::build_authorize_ok_response_message(),
+    )
+    .await;
+    v1_simulate_incoming_message(
+        translation,
+        test_utils::v1::build_set_difficulty_request_message(),
+    )
+    .await;
+    let frame = v2_rx
+        .next()
+        .await
+        .expect("Open channel success was expected");
+    v2::build_message_from_frame(frame)
+        .expect("Deserialization failed")
+        .accept(&mut capture)
+        .await;
+
+    capture
+}
+
+/// Builds a session of a translation which has already received the first job
+async fn build_previous_session() -> V1Session {
+    let (v1_tx, mut v1_rx) = mpsc::channel(1);
+    let (v2_tx, mut v2_rx) = mpsc::channel(1);
+    let mut translation = V2ToV1Translation::new(v1_tx, v2_tx, Default::default());
+
+    let capture = open_channel(
+        &mut translation,
+        &mut v1_rx,
+        &mut v2_rx,
+        test_utils::v1::build_subscribe_ok_result(),
+    )
+    .await;
+    // No session is requested for a new connection
+    assert_eq!(capture.subscribe.expect("No subscribe").1, None);
+
+    v1_simulate_incoming_message(
+        &mut translation,
+        test_utils::v1::build_mining_notify_request_message(),
+    )
+    .await;
+    // NewMiningJob and SetNewPrevHash
+    v2_verify_generated_response_message(&mut v2_rx).await;
+    v2_verify_generated_response_message(&mut v2_rx).await;
+
+    translation.session().expect("No V1 session")
+}
+
+#[tokio::test]
+async fn test_session_resumed() {
+    let session = build_previous_session().await;
+
+    let (v1_tx, mut v1_rx) = mpsc::channel(1);
+    let (v2_tx, mut v2_rx) = mpsc::channel(1);
+    let options = V2ToV1TranslationOptions {
+        resume_session: Some(session.clone()),
+        ..Default::default()
+    };
+    let mut translation = V2ToV1Translation::new(v1_tx, v2_tx, options);
+
+    // Upstream server assigns the same extra nonce 1
+    let capture = open_channel(
+        &mut translation,
+        &mut v1_rx,
+        &mut v2_rx,
+        test_utils::v1::build_subscribe_ok_result(),
+    )
+    .await;
+
+    assert_eq!(
+        capture.subscribe.expect("No subscribe").1.as_ref(),
+        Some(session.extra_nonce1())
+    );
+    assert_eq!(
+        capture
+            .success
+            .expect("No open channel success")
+            .extranonce_prefix,
+        test_utils::v2::build_open_channel_success().extranonce_prefix
+    );
+    // Job from the previous connection can still be submitted and its ID is not reused
+    assert!(translation.v2_to_v1_job_map.get(&0).is_some());
+    assert_eq!(translation.v2_job_id.next(), 1);
+}
+
+#[tokio::test]
+async fn test_session_resumption_refused() {
+    let session = build_previous_session().await;
+
+    let (v1_tx, mut v1_rx) = mpsc::channel(1);
+    let (v2_tx, mut v2_rx) = mpsc::channel(1);
+    let options = V2ToV1TranslationOptions {
+        resume_session: Some(session.clone()),
+        ..Default::default()
+    };
+    let mut translation = V2ToV1Translation::new(v1_tx, v2_tx, options);
+
+    // Upstream server ignores the requested session and assigns a new extra nonce 1
+    let mut subscribe_result = test_utils::v1::build_subscribe_ok_result();
+    subscribe_result.1 =
+        v1::ExtraNonce1(v1::HexBytes::try_from("deadbeef").expect("Cannot parse extra nonce 1"));
+    let capture = open_channel(&mut translation, &mut v1_rx, &mut v2_rx, subscribe_result).await;
+
+    assert_eq!(
+        capture.subscribe.expect("No subscribe").1.as_ref(),
+        Some(session.extra_nonce1())
+    );
+    assert_eq!(
+        capture
+            .success
+            .expect("No open channel success")
+            .extranonce_prefix
+            .as_ref(),
+        &[0xde, 0xad, 0xbe, 0xef]
+    );
+    // Jobs of the previous session are dropped
+    assert!(translation.v2_to_v1_job_map.is_empty());
+    assert_eq!(
+        translation.session().expect("No V1 session").extra_nonce1(),
+        &v1::ExtraNonce1(v1::HexBytes::try_from("deadbeef").unwrap())
+    );
+}
+
+/// Marker which is present in the coinbase of the test job
+const COINBASE_MARKER: &[u8] = b"/slush/";
+
+fn build_coinbase_verifier(marker: &[u8], payout_script: Option<&str>) -> CoinbaseVerifier {
+    CoinbaseVerifier::new(CoinbaseCheck {
+        marker: marker.to_vec(),
+        payout_script: payout_script.map(|script| {
+            v1::HexBytes::try_from(script)
+                .expect("Cannot parse payout script")
+                .as_ref()
+                .clone()
+        }),
+        alert_threshold: 3,
+    })
+}
+
+#[test]
+fn test_coinbase_verifier() {
+    let coin_base = [b"header".as_ref(), COINBASE_MARKER, b"outputs"].concat();
+
+    let verifier = build_coinbase_verifier(COINBASE_MARKER, Some("6f757470"));
+    assert_eq!(verifier.verify(&coin_base), Ok(()));
+    assert_eq!(
+        verifier.verify(b"header/slash/outputs"),
+        Err(CoinbaseRejection::MissingMarker)
+    );
+    assert_eq!(verifier.rejected(), 1);
+    assert_eq!(verifier.consecutive_rejected(), 1);
+
+    let verifier = build_coinbase_verifier(COINBASE_MARKER, Some("deadbeef"));
+    for i in 1..=4 {
+        assert_eq!(
+            verifier.verify(&coin_base),
+            Err(CoinbaseRejection::PayoutScriptMismatch)
+        );
+        assert_eq!(verifier.consecutive_rejected(), i);
+        // The alert is raised after 3 consecutive rejected jobs
+        assert_eq!(verifier.is_alerting(), i >= 3);
+    }
+
+    // Accepted job resets only the consecutive rejections
+    let verifier = build_coinbase_verifier(b"header", None);
+    assert_eq!(
+        verifier.verify(b"no tag"),
+        Err(CoinbaseRejection::MissingMarker)
+    );
+    assert_eq!(verifier.verify(&coin_base), Ok(()));
+    assert_eq!(verifier.consecutive_rejected(), 0);
+    assert_eq!(verifier.rejected(), 1);
+    assert!(!verifier.is_alerting());
+}
+
+/// Opens channel of translation with `coinbase_verifier` and feeds it with the test job. Returns
+/// whether the job has been passed downstream.
+async fn notify_with_coinbase_verifier(coinbase_verifier: Arc<CoinbaseVerifier>) -> bool {
+    let (v1_tx, mut v1_rx) = mpsc::channel(1);
+    let (v2_tx, mut v2_rx) = mpsc::channel(1);
+    let options = V2ToV1TranslationOptions {
+        coinbase_verifier: Some(coinbase_verifier),
+        ..Default::default()
+    };
+    let mut translation = V2ToV1Translation::new(v1_tx, v2_tx, options);
+    open_channel(
+        &mut translation,
+        &mut v1_rx,
+        &mut v2_rx,
+        test_utils::v1::build_subscribe_ok_result(),
+    )
+    .await;
+
+    v1_simulate_incoming_message(
+        &mut translation,
+        test_utils::v1::build_mining_notify_request_message(),
+    )
+    .await;
+    match v2_rx.try_next() {
+        Ok(Some(_)) => {
+            assert!(translation.v2_to_v1_job_map.get(&0).is_some());
+            true
+        }
+        _ => {
+            assert!(translation.v2_to_v1_job_map.is_empty());
+            false
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_notify_coinbase_verification() {
+    // Payout script of the first output of the test job
+    let payout_script = "76a914505b9f58045298b98a7af6333445098ac700ac3088ac";
+
+    let coinbase_verifier = Arc::new(build_coinbase_verifier(
+        COINBASE_MARKER,
+        Some(payout_script),
+    ));
+    assert!(notify_with_coinbase_verifier(coinbase_verifier.clone()).await);
+    assert_eq!(coinbase_verifier.rejected(), 0);
+
+    // Rejections are counted across translations sharing the verifier
+    let coinbase_verifier = Arc::new(build_coinbase_verifier(b"/pool/", Some(payout_script)));
+    for i in 1..=3 {
+        assert!(!notify_with_coinbase_verifier(coinbase_verifier.clone()).await);
+        assert_eq!(coinbase_verifier.consecutive_rejected(), i);
+    }
+    assert!(coinbase_verifier.is_alerting());
+
+    let coinbase_verifier = Arc::new(build_coinbase_verifier(COINBASE_MARKER, Some("76a914")));
+    assert!(notify_with_coinbase_verifier(coinbase_verifier.clone()).await);
+    let coinbase_verifier = Arc::new(build_coinbase_verifier(COINBASE_MARKER, Some("76a915")));
+    assert!(!notify_with_coinbase_verifier(coinbase_verifier.clone()).await);
+    assert_eq!(coinbase_verifier.rejected(), 1);
+}
+
 #[test]
 fn test_diff_1_bitcoin_target() {
     // Difficulty 1 target in big-endian format
@@ -49,6 +49,10 @@
 //! ie. configuration suitable for testing. This is because as of now
 //! there's no way to have common setup/teardown for tests, and so
 //! it's best that the default is test-friendly.
+//!
+//! Applications which install their own logger through the `log` facade
+//! (e.g. `env_logger`) can use `LoggingConfig::external()` to forward
+//! all records to it instead of creating a separate drain.
 
 use std::env;
 use std::fmt;
@@ -59,7 +63,7 @@ use std::path::{Path, PathBuf};
 use std::sync::{Mutex, MutexGuard};
 
 use lazy_static::lazy_static;
-use slog::{o, Discard, Drain, FilterLevel, Logger};
+use slog::{o, Discard, Drain, FilterLevel, Logger, Never, OwnedKVList, Record};
 use slog_async::{Async, AsyncGuard};
 use slog_envlogger::EnvLogger;
 use slog_term;
@@ -80,6 +84,9 @@ pub enum LoggingTarget {
     File(PathBuf),
     /// Don't log anything anywhere
     None,
+    /// Forward records to the logger installed by the application with the `log` facade.
+    /// Filtering is left to the external logger.
+    External,
 }
 
 /// Describes logger configuration which can be set in runtime
@@ -131,6 +138,15 @@ impl LoggingConfig {
             drain_channel_size: Self::ASYNC_LOGGER_DRAIN_CHANNEL_SIZE,
         }
     }
+
+    /// Configuration for embedding in an application which owns the logger
+    pub fn external() -> Self {
+        Self {
+            target: LoggingTarget::External,
+            level: Level::Trace,
+            drain_channel_size: Self::ASYNC_LOGGER_DRAIN_CHANNEL_SIZE,
+        }
+    }
 }
 
 /// Default configuration for logger used for unit tests and integration tests
@@ -159,9 +175,18 @@ fn lock_logger_config() -> MutexGuard<'static, Option<LoggingConfig>> {
 /// Panics if `LOGGER` is already instantiated, ie. its configuration
 /// can no longer be changed.
 pub fn set_logger_config(config: LoggingConfig) -> LoggingConfig {
-    lock_logger_config()
-        .replace(config)
-        .expect("Could not set logger config, LOGGER already instantiated")
+    try_set_logger_config(config).expect("Could not set logger config, LOGGER already instantiated")
+}
+
+/// Same as `set_logger_config` but `None` is returned instead of panic
+/// when `LOGGER` is already instantiated.
+pub fn try_set_logger_config(config: LoggingConfig) -> Option<LoggingConfig> {
+    let mut config_lock = lock_logger_config();
+    if config_lock.is_some() {
+        config_lock.replace(config)
+    } else {
+        None
+    }
 }
 
 /// Setup logger with configuration passed in `config`
@@ -243,6 +268,42 @@ fn get_file_drain(path: &Path) -> impl Drain<Ok = (), Err = impl fmt::Debug> {
     file_drain
 }
 
+/// Drain forwarding records to the logger installed with the `log` facade
+struct LogFacadeDrain;
+
+impl Drain for LogFacadeDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, _values: &OwnedKVList) -> Result<(), Never> {
+        let level = match record.level() {
+            Level::Critical | Level::Error => log::Level::Error,
+            Level::Warning => log::Level::Warn,
+            Level::Info => log::Level::Info,
+            Level::Debug => log::Level::Debug,
+            Level::Trace => log::Level::Trace,
+        };
+        if level <= log::max_level() {
+            let target = if record.tag().is_empty() {
+                record.module()
+            } else {
+                record.tag()
+            };
+            log::logger().log(
+                &log::Record::builder()
+                    .args(*record.msg())
+                    .level(level)
+                    .target(target)
+                    .module_path(Some(record.module()))
+                    .file(Some(record.file()))
+                    .line(Some(record.line()))
+                    .build(),
+            );
+        }
+        Ok(())
+    }
+}
+
 /// Logger flush RAII guard.
 ///
 /// The guard ensures logs are flushed when it goes out of scope.
@@ -270,6 +331,7 @@ impl GuardedLogger {
             Stderr => Self::with_drain(config, get_terminal_drain(true)),
             Stdout => Self::with_drain(config, get_terminal_drain(false)),
             File(path) => Self::with_drain(config, get_file_drain(path)),
+            External => Self::with_external(),
         }
     }
 
@@ -288,6 +350,14 @@ impl GuardedLogger {
         }
     }
 
+    /// Records are passed synchronously because the external logger does its own buffering
+    fn with_external() -> Self {
+        Self {
+            logger: Logger::root(LogFacadeDrain, o!()),
+            guard: Mutex::new(FlushGuard(None)),
+        }
+    }
+
     fn with_discard() -> Self {
         Self {
             logger: Logger::root(Discard, o!()),
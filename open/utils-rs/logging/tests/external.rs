@@ -0,0 +1,81 @@
+// Copyright (C) 2026  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Test of forwarding records to the logger installed with the `log` facade.
+//!
+//! **Warning**: Each logging test needs to be in a separate files
+//! due to global LOGGER initialization
+
+use ii_logging::macros::*;
+use ii_logging::{self, LoggingConfig};
+
+use lazy_static::lazy_static;
+
+use std::sync::Mutex;
+
+/// External logger which keeps all records in memory
+struct CaptureLogger(Mutex<Vec<(log::Level, String)>>);
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0
+            .lock()
+            .expect("cannot lock captured records")
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+lazy_static! {
+    static ref CAPTURE: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+}
+
+#[test]
+fn test_logging_external() {
+    log::set_logger(&*CAPTURE).expect("Could not set external logger");
+    log::set_max_level(log::LevelFilter::Info);
+
+    ii_logging::set_logger_config(LoggingConfig::external());
+    info!("Hello, {}!", "World");
+    warn!("Warning");
+    // filtered out by the external logger
+    debug!("Debug");
+    // there is no guard of external logger
+    ii_logging::LOGGER.flush();
+
+    // configuration cannot be changed after the logger has been instantiated
+    assert!(ii_logging::try_set_logger_config(LoggingConfig::default()).is_none());
+
+    let records = CAPTURE.0.lock().expect("cannot lock captured records");
+    assert_eq!(
+        *records,
+        vec![
+            (log::Level::Info, "Hello, World!".to_string()),
+            (log::Level::Warn, "Warning".to_string()),
+        ]
+    );
+}
@@ -22,6 +22,7 @@
 
 //! This crate is intended for various statistical algorithms used mainly for mining.
 
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
@@ -54,9 +55,10 @@ impl WindowedTimeMeanState {
                 self.prev_window = 0.0;
             }
             Some(start_time) => {
+                // Late sample (e.g. solution with older timestamp) belongs to the current window
                 let elapsed = now
                     .checked_duration_since(start_time)
-                    .expect("BUG: non-monotonic clock")
+                    .unwrap_or_default()
                     .as_secs_f64();
                 // Check if current window is full
                 if elapsed >= self.interval {
@@ -79,7 +81,7 @@ impl WindowedTimeMeanState {
             Some(start_time) => {
                 let elapsed = now
                     .checked_duration_since(start_time)
-                    .expect("BUG: non-monotonic clock")
+                    .unwrap_or_default()
                     .as_secs_f64();
 
                 let a = elapsed / self.interval;
@@ -114,15 +116,255 @@ impl WindowedTimeMean {
     }
 
     /// Measure arithmetic mean at specific time from inserted samples within given time interval.
-    /// TODO: do not ignore time
-    pub fn measure(&self, _now: Instant) -> f64 {
-        self.state.measure(Instant::now())
+    pub fn measure(&self, now: Instant) -> f64 {
+        self.state.measure(now)
     }
 
     /// Insert another sample for arithmetic mean measurement at specific time.
-    /// TODO: do not ignore time
-    pub fn insert(&mut self, sample: f64, _now: Instant) {
-        self.state.insert(sample, Instant::now());
+    pub fn insert(&mut self, sample: f64, now: Instant) {
+        self.state.insert(sample, now);
+    }
+}
+
+/// Calculation of exponentially decaying mean of samples per second. The `interval` is the time
+/// constant of the decay so the samples older than the interval contribute less and less to the
+/// mean. The mean is decayed lazily when it is measured hence it decreases correctly even when
+/// no samples are inserted.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialTimeMean {
+    /// Time constant of the decay in seconds
+    interval: f64,
+    /// Beginning of the measurement used for correction of the initial underestimation
+    started: Instant,
+    /// Time of the last inserted sample
+    updated: Instant,
+    /// Mean value (not corrected) at the time of the last inserted sample
+    value: f64,
+}
+
+impl ExponentialTimeMean {
+    pub fn new(interval: Duration, started: Instant) -> Self {
+        assert!(interval.as_secs() > 0);
+        Self {
+            interval: interval.as_secs_f64(),
+            started,
+            updated: started,
+            value: 0.0,
+        }
+    }
+
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(self.interval)
+    }
+
+    fn decay(&self, since: Instant, now: Instant) -> f64 {
+        let elapsed = now
+            .checked_duration_since(since)
+            .unwrap_or_default()
+            .as_secs_f64();
+        (-elapsed / self.interval).exp()
+    }
+
+    /// Measure mean at specific time. The value is divided by the weight of the whole period
+    /// since the beginning so the mean is not underestimated until the interval elapses.
+    pub fn measure(&self, now: Instant) -> f64 {
+        let weight = 1.0 - self.decay(self.started, now);
+        if weight > 0.0 {
+            self.value * self.decay(self.updated, now) / weight
+        } else {
+            0.0
+        }
+    }
+
+    /// Insert another sample for mean measurement at specific time. Late sample (e.g. solution
+    /// with older timestamp) is accounted as if it were inserted with the previous one.
+    pub fn insert(&mut self, sample: f64, now: Instant) {
+        self.value = self.value * self.decay(self.updated, now) + sample / self.interval;
+        self.updated = self.updated.max(now);
+    }
+
+    /// Exclude the period `<since, now)` from the measurement (e.g. when the measured source
+    /// has been stopped on purpose). The mean is shifted forward in time so it does not decay
+    /// during the period. Samples inserted within the period are kept as if they were inserted
+    /// at its end.
+    pub fn exclude(&mut self, since: Instant, now: Instant) {
+        let elapsed = now.checked_duration_since(since).unwrap_or_default();
+        self.started = (self.started + elapsed).min(now);
+        self.updated = (self.updated + elapsed).min(now);
+    }
+}
+
+/// Samples aggregated into a single time bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// Index of the bucket counted from the origin of `TimeBuckets`
+    idx: u64,
+    count: u64,
+    sum: f64,
+}
+
+/// Ring buffer of samples aggregated into fixed-width time buckets. Unlike `WindowedTimeMean`
+/// it keeps enough history to answer queries about arbitrary time range (limited by the number
+/// of buckets) e.g. "how many samples were inserted between t1 and t2".
+#[derive(Debug, Clone)]
+pub struct TimeBuckets {
+    bucket_width: Duration,
+    capacity: usize,
+    /// Time of the first inserted sample, all buckets are aligned to it
+    origin: Option<Instant>,
+    buckets: VecDeque<Bucket>,
+    /// Number of samples which have not been accounted because they are older than the history
+    dropped: u64,
+}
+
+impl TimeBuckets {
+    pub fn new(bucket_width: Duration, capacity: usize) -> Self {
+        assert!(bucket_width.as_nanos() > 0);
+        assert!(capacity > 0);
+        Self {
+            bucket_width,
+            capacity,
+            origin: None,
+            buckets: VecDeque::with_capacity(capacity),
+            dropped: 0,
+        }
+    }
+
+    /// Total time span covered by the history
+    #[inline]
+    pub fn span(&self) -> Duration {
+        self.bucket_width * self.capacity as u32
+    }
+
+    /// Number of samples which have been dropped because they were inserted too late (before
+    /// the first sample or after their bucket has fallen out of the history)
+    #[inline]
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    fn bucket_idx(&self, origin: Instant, time: Instant) -> Option<u64> {
+        time.checked_duration_since(origin)
+            .map(|elapsed| (elapsed.as_nanos() / self.bucket_width.as_nanos()) as u64)
+    }
+
+    /// Insert sample at specific time. Samples older than the whole history or than the first
+    /// sample are dropped and accounted in `dropped`.
+    pub fn insert(&mut self, sample: f64, now: Instant) {
+        let origin = *self.origin.get_or_insert(now);
+        let idx = match self.bucket_idx(origin, now) {
+            Some(idx) => idx,
+            None => {
+                self.dropped += 1;
+                return;
+            }
+        };
+
+        match self.buckets.back_mut() {
+            Some(bucket) if bucket.idx == idx => {
+                bucket.count += 1;
+                bucket.sum += sample;
+                return;
+            }
+            Some(bucket) if bucket.idx > idx => {
+                // Late sample, find its bucket if it is still in the history
+                if bucket.idx - idx >= self.capacity as u64 {
+                    self.dropped += 1;
+                    return;
+                }
+                let pos = self
+                    .buckets
+                    .iter()
+                    .position(|bucket| bucket.idx >= idx)
+                    .expect("BUG: missing newer bucket");
+                match self.buckets.get_mut(pos) {
+                    Some(bucket) if bucket.idx == idx => {
+                        bucket.count += 1;
+                        bucket.sum += sample;
+                    }
+                    // There has been no sample in this time slot yet
+                    _ => self.buckets.insert(
+                        pos,
+                        Bucket {
+                            idx,
+                            count: 1,
+                            sum: sample,
+                        },
+                    ),
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        self.buckets.push_back(Bucket {
+            idx,
+            count: 1,
+            sum: sample,
+        });
+        // Drop all buckets which are out of the history span
+        while let Some(bucket) = self.buckets.front() {
+            if idx - bucket.idx < self.capacity as u64 {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Return `true` when the history still covers given time
+    pub fn covers(&self, time: Instant, now: Instant) -> bool {
+        match self.origin {
+            None => false,
+            Some(origin) => {
+                time >= origin
+                    && now
+                        .checked_duration_since(time)
+                        .map_or(true, |age| age <= self.span())
+            }
+        }
+    }
+
+    /// Return number of samples and their sum in the interval `<from, to)`. Buckets which
+    /// overlap the interval only partially are accounted proportionally.
+    pub fn between(&self, from: Instant, to: Instant) -> (f64, f64) {
+        let origin = match self.origin {
+            None => return (0.0, 0.0),
+            Some(origin) => origin,
+        };
+        if to <= from {
+            return (0.0, 0.0);
+        }
+        let width = self.bucket_width.as_secs_f64();
+        let offset = |time: Instant| match time.checked_duration_since(origin) {
+            Some(elapsed) => elapsed.as_secs_f64(),
+            None => -origin.duration_since(time).as_secs_f64(),
+        };
+        let (from, to) = (offset(from), offset(to));
+
+        let mut count = 0.0;
+        let mut sum = 0.0;
+        for bucket in &self.buckets {
+            let start = bucket.idx as f64 * width;
+            let end = start + width;
+            let overlap = end.min(to) - start.max(from);
+            if overlap > 0.0 {
+                let ratio = overlap / width;
+                count += bucket.count as f64 * ratio;
+                sum += bucket.sum * ratio;
+            }
+        }
+        (count, sum)
+    }
+
+    /// Return arithmetic mean of samples per second in the interval `<from, to)`
+    pub fn rate_between(&self, from: Instant, to: Instant) -> f64 {
+        match to.checked_duration_since(from) {
+            Some(interval) if interval.as_nanos() > 0 => {
+                self.between(from, to).1 / interval.as_secs_f64()
+            }
+            _ => 0.0,
+        }
     }
 }
 
@@ -191,4 +433,207 @@ pub mod test {
         assert_eq!(mean.measure(start + Duration::from_secs(17)), 2.0);
         assert_eq!(mean.measure(start + Duration::from_secs(18)), 1.5);
     }
+
+    fn assert_mean(mean: &ExponentialTimeMean, now: Instant, expected: f64) {
+        let value = mean.measure(now);
+        assert!(
+            (value - expected).abs() < 1e-9,
+            "mean {} differs from {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_exponential_time_mean() {
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let mut mean = ExponentialTimeMean::new(minute, start);
+
+        assert_eq!(mean.interval(), minute);
+        // check if mean is equal to 0 when any sample was not inserted yet
+        assert_eq!(mean.measure(start), 0.0);
+        assert_eq!(mean.measure(start + minute), 0.0);
+
+        // constant rate of 2 per second is measured precisely from the beginning
+        for i in 1..=600 {
+            mean.insert(1.0, start + Duration::from_millis(i * 500));
+        }
+        let now = start + Duration::from_secs(300);
+        let value = mean.measure(now);
+        assert!((value - 2.0).abs() < 0.02, "unexpected mean {}", value);
+
+        // the mean decays lazily without any samples
+        let raw = value * (1.0 - (-5.0f64).exp());
+        assert_mean(
+            &mean,
+            now + minute,
+            raw * (-1.0f64).exp() / (1.0 - (-6.0f64).exp()),
+        );
+        assert_mean(
+            &mean,
+            now + minute * 5,
+            raw * (-5.0f64).exp() / (1.0 - (-10.0f64).exp()),
+        );
+        // measurement does not change the state
+        assert_mean(&mean, now, value);
+
+        // late sample is accounted without decay
+        mean.insert(60.0, now - minute);
+        assert_mean(&mean, now, value + 1.0 / (1.0 - (-5.0f64).exp()));
+    }
+
+    #[test]
+    fn test_exponential_time_mean_idle_start() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+        let mut mean = ExponentialTimeMean::new(interval, start);
+
+        // single sample right after the start is measured against the time since the start
+        mean.insert(10.0, start + Duration::from_secs(5));
+        let weight = 1.0 - (-0.5f64).exp();
+        assert_mean(&mean, start + Duration::from_secs(5), 1.0 / weight);
+        assert_mean(
+            &mean,
+            start + Duration::from_secs(15),
+            (-1.0f64).exp() / (1.0 - (-1.5f64).exp()),
+        );
+    }
+
+    #[test]
+    fn test_exponential_time_mean_exclude() {
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let mut mean = ExponentialTimeMean::new(minute, start);
+
+        for i in 1..=600 {
+            mean.insert(1.0, start + Duration::from_millis(i * 500));
+        }
+        let paused = start + Duration::from_secs(300);
+        let running = mean;
+
+        // the excluded period does not decay the mean
+        let resumed = paused + minute * 10;
+        mean.exclude(paused, resumed);
+        assert_mean(&mean, resumed, running.measure(paused));
+        assert_mean(&mean, resumed + minute, running.measure(paused + minute));
+
+        // sample inserted during the period is kept as if it were inserted at its end
+        let mut mean = ExponentialTimeMean::new(minute, start);
+        mean.insert(60.0, start + minute);
+        mean.exclude(start + minute / 2, start + minute * 2);
+        assert_eq!(mean.started, start + minute * 3 / 2);
+        assert_eq!(mean.updated, start + minute * 2);
+    }
+
+    #[test]
+    fn test_time_buckets_between() {
+        let start = Instant::now();
+        let mut buckets = TimeBuckets::new(Duration::from_secs(10), 6);
+
+        assert_eq!(
+            buckets.between(start, start + Duration::from_secs(60)),
+            (0.0, 0.0)
+        );
+
+        // one sample per second with value 2 for the first 30 seconds
+        for i in 0..30 {
+            buckets.insert(2.0, start + Duration::from_secs(i));
+        }
+        assert_eq!(
+            buckets.between(start, start + Duration::from_secs(30)),
+            (30.0, 60.0)
+        );
+        assert_eq!(
+            buckets.between(
+                start + Duration::from_secs(10),
+                start + Duration::from_secs(20)
+            ),
+            (10.0, 20.0)
+        );
+        // partial overlap is accounted proportionally
+        assert_eq!(
+            buckets.between(
+                start + Duration::from_secs(25),
+                start + Duration::from_secs(40)
+            ),
+            (5.0, 10.0)
+        );
+        assert_eq!(
+            buckets.rate_between(start, start + Duration::from_secs(30)),
+            2.0
+        );
+        // empty or inverted interval
+        assert_eq!(buckets.rate_between(start, start), 0.0);
+        assert_eq!(
+            buckets.between(start + Duration::from_secs(20), start),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_time_buckets_expiration() {
+        let start = Instant::now();
+        let mut buckets = TimeBuckets::new(Duration::from_secs(10), 3);
+
+        buckets.insert(1.0, start);
+        buckets.insert(1.0, start + Duration::from_secs(15));
+        assert!(buckets.covers(start, start + Duration::from_secs(15)));
+
+        // the first bucket falls out of the history
+        buckets.insert(1.0, start + Duration::from_secs(35));
+        assert!(!buckets.covers(start, start + Duration::from_secs(35)));
+        assert_eq!(
+            buckets.between(start, start + Duration::from_secs(40)),
+            (2.0, 2.0)
+        );
+
+        // late sample which still fits into the history
+        buckets.insert(5.0, start + Duration::from_secs(16));
+        assert_eq!(
+            buckets.between(
+                start + Duration::from_secs(10),
+                start + Duration::from_secs(20)
+            ),
+            (2.0, 6.0)
+        );
+        assert_eq!(buckets.dropped(), 0);
+
+        // late sample which falls into an empty slot of the history
+        buckets.insert(3.0, start + Duration::from_secs(25));
+        assert_eq!(
+            buckets.between(
+                start + Duration::from_secs(20),
+                start + Duration::from_secs(30)
+            ),
+            (1.0, 3.0)
+        );
+        assert_eq!(buckets.dropped(), 0);
+
+        // late sample which is older than the history is dropped
+        buckets.insert(7.0, start + Duration::from_secs(5));
+        assert_eq!(buckets.dropped(), 1);
+        assert_eq!(
+            buckets.between(start, start + Duration::from_secs(40)),
+            (4.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn test_time_buckets_before_origin() {
+        let start = Instant::now() + Duration::from_secs(60);
+        let mut buckets = TimeBuckets::new(Duration::from_secs(10), 6);
+
+        buckets.insert(1.0, start);
+        // sample older than the first one is not accounted to the first bucket
+        buckets.insert(2.0, start - Duration::from_secs(1));
+        assert_eq!(buckets.dropped(), 1);
+        assert_eq!(
+            buckets.between(
+                start - Duration::from_secs(10),
+                start + Duration::from_secs(10)
+            ),
+            (1.0, 1.0)
+        );
+    }
 }